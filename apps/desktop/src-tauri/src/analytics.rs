@@ -0,0 +1,204 @@
+//! Summaries of planned and finished work over a range of days, built from
+//! the server's tasks and the local time entries.
+//!
+//! Channels aren't a server concept yet: a task's channel is the `#channel`
+//! line at the top of its notes, as written by the Sunsama importer. Time
+//! imported from other trackers counts under its project's name.
+
+use chrono::{DateTime, Days, NaiveDate};
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use opensunsama_core::task::channel;
+use tauri::{AppHandle, Manager};
+
+use crate::api::Api;
+use crate::clock;
+use crate::db::{external_projects, tasks, time_entries};
+use crate::db::Database;
+use crate::{sync, timezone};
+
+/// Channel name for tracked time on tasks without one
+pub const NO_CHANNEL: &str = "No channel";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletedTask {
+    pub title: String,
+    pub channel: Option<String>,
+    /// `YYYY-MM-DD`, in the local timezone
+    pub completed_on: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodSummary {
+    /// First day, `YYYY-MM-DD`
+    pub start: String,
+    /// Last day, inclusive
+    pub end: String,
+    pub planned_tasks: usize,
+    pub completed: Vec<CompletedTask>,
+    /// Sum of the estimates of the tasks planned in the period
+    pub planned_minutes: i64,
+    /// Time tracked in the period, on any task
+    pub tracked_minutes: i64,
+    /// Tracked minutes by channel
+    pub tracked_by_channel: BTreeMap<String, i64>,
+}
+
+/// Summarize the `days` days starting at `start`. Needs to be signed in;
+/// time tracked while offline is included once it's in the local database.
+pub async fn summarize(app: &AppHandle, start: NaiveDate, days: u64) -> Result<PeriodSummary, String> {
+    let tz = timezone::local();
+    let end = start
+        .checked_add_days(Days::new(days.max(1) - 1))
+        .ok_or_else(|| format!("Invalid date: {}", start))?;
+
+    let api = Api::new(app)?;
+    let mut tasks: Vec<Value> = Vec::new();
+    for day in start.iter_days().take_while(|day| *day <= end) {
+        tasks.extend(
+            api.get_all::<Value>(&format!("/tasks?scheduledDate={}", day))
+                .await?,
+        );
+    }
+
+    let (range_start, _) =
+        timezone::day_bounds(start, tz).ok_or_else(|| format!("Invalid date: {}", start))?;
+    let (_, range_end) =
+        timezone::day_bounds(end, tz).ok_or_else(|| format!("Invalid date: {}", end))?;
+    let (entries, projects) = app.state::<Database>().with_conn(|conn| {
+        Ok((
+            time_entries::list_between(conn, range_start, range_end)?,
+            external_projects::names(conn)?,
+        ))
+    })?;
+
+    let channels: HashMap<&str, Option<String>> = tasks
+        .iter()
+        .filter_map(|task| {
+            let id = task.get("id").and_then(Value::as_str)?;
+            Some((id, channel(task.get("notes").and_then(Value::as_str))))
+        })
+        .collect();
+
+    // Running entries count up to now; everything is clipped to the range
+    let now = clock::now_millis();
+    let mut tracked_by_channel = BTreeMap::new();
+    let mut tracked_minutes = 0;
+    for entry in &entries {
+        let started = entry.started_at.max(range_start);
+        let ended = entry.ended_at.unwrap_or(now).min(range_end);
+        let minutes = (ended - started).max(0) / 60_000;
+        // Time imported from other trackers is filed under its project
+        let channel = channels
+            .get(entry.task_id.as_str())
+            .cloned()
+            .flatten()
+            .or_else(|| projects.get(&entry.task_id).cloned())
+            .unwrap_or_else(|| NO_CHANNEL.to_string());
+        *tracked_by_channel.entry(channel).or_insert(0) += minutes;
+        tracked_minutes += minutes;
+    }
+
+    let completed = tasks
+        .iter()
+        .filter_map(|task| {
+            let completed_at = task.get("completedAt").and_then(Value::as_str)?;
+            Some(CompletedTask {
+                title: task.get("title").and_then(Value::as_str)?.to_string(),
+                channel: channel(task.get("notes").and_then(Value::as_str)),
+                completed_on: local_date(completed_at, tz).unwrap_or_default(),
+            })
+        })
+        .collect();
+    let planned_minutes = tasks
+        .iter()
+        .filter_map(|task| task.get("estimatedMins").and_then(Value::as_i64))
+        .sum();
+
+    Ok(PeriodSummary {
+        start: start.to_string(),
+        end: end.to_string(),
+        planned_tasks: tasks.len(),
+        completed,
+        planned_minutes,
+        tracked_minutes,
+        tracked_by_channel,
+    })
+}
+
+/// Minutes tracked in the `days` days starting at `start`, by channel.
+/// Channels come from the tasks on the server, or the project for imported
+/// time; tasks that can't be found count under `NO_CHANNEL`.
+pub async fn tracked_by_channel(
+    app: &AppHandle,
+    start: NaiveDate,
+    days: u64,
+) -> Result<BTreeMap<String, i64>, String> {
+    let tz = timezone::local();
+    let end = start
+        .checked_add_days(Days::new(days.max(1) - 1))
+        .ok_or_else(|| format!("Invalid date: {}", start))?;
+    let (range_start, _) =
+        timezone::day_bounds(start, tz).ok_or_else(|| format!("Invalid date: {}", start))?;
+    let (_, range_end) =
+        timezone::day_bounds(end, tz).ok_or_else(|| format!("Invalid date: {}", end))?;
+    let (entries, projects) = app.state::<Database>().with_conn(|conn| {
+        Ok((
+            time_entries::list_between(conn, range_start, range_end)?,
+            external_projects::names(conn)?,
+        ))
+    })?;
+
+    let task_ids: Vec<String> = entries
+        .iter()
+        .filter(|entry| !entry.task_id.starts_with(time_entries::IMPORTED_PREFIX))
+        .map(|entry| entry.task_id.clone())
+        .collect();
+    let channels = task_channels(app, task_ids).await?;
+
+    let now = clock::now_millis();
+    let mut tracked = BTreeMap::new();
+    for entry in &entries {
+        let started = entry.started_at.max(range_start);
+        let ended = entry.ended_at.unwrap_or(now).min(range_end);
+        let channel = match projects.get(&entry.task_id) {
+            Some(project) => Some(project.clone()),
+            None => channels.get(&entry.task_id).cloned().flatten(),
+        };
+        *tracked
+            .entry(channel.unwrap_or_else(|| NO_CHANNEL.to_string()))
+            .or_insert(0) += (ended - started).max(0) / 60_000;
+    }
+    Ok(tracked)
+}
+
+/// The channels of the tasks `ids`, by id. The local copy of the server's
+/// tasks is brought up to date first, with one request for whatever changed
+/// since the last pass, and then read in one query, rather than fetching
+/// each task. Tasks it doesn't have are left out.
+pub async fn task_channels(
+    app: &AppHandle,
+    mut ids: Vec<String>,
+) -> Result<HashMap<String, Option<String>>, String> {
+    ids.sort();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    // A copy a pass behind still has most tasks
+    if let Err(e) = sync::task_cache::sync(app).await {
+        tracing::warn!("Failed to update the local task copy: {}", e);
+    }
+    app.state::<Database>()
+        .with_conn(|conn| tasks::channels(conn, &ids))
+}
+
+fn local_date(rfc3339: &str, tz: Tz) -> Option<String> {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .ok()
+        .map(|at| at.with_timezone(&tz).date_naive().to_string())
+}
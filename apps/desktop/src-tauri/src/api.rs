@@ -0,0 +1,271 @@
+//! Typed access to the Open Sunsama REST API. Responses come wrapped in
+//! `{ success, data, meta }`; errors in `{ success: false, error }`. Task
+//! fields sealed by end-to-end encryption are sealed and opened here, so no
+//! caller sends them in plaintext by mistake.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::{Method, RequestBuilder, Response, StatusCode};
+
+use crate::auth::AuthState;
+use crate::error::{AppError, ErrorCode};
+use crate::http::{self, Integration};
+use crate::{crypto, server};
+
+const PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    data: Option<T>,
+    meta: Option<PageMeta>,
+    error: Option<ApiError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageMeta {
+    total: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    code: Option<String>,
+    message: String,
+}
+
+/// Signed-in API client for the configured server
+pub struct Api {
+    app: AppHandle,
+    base_url: String,
+    token: String,
+}
+
+impl Api {
+    /// Fails when nobody is signed in
+    pub fn new(app: &AppHandle) -> Result<Self, AppError> {
+        let token = app
+            .state::<AuthState>()
+            .token()
+            .ok_or_else(|| AppError::new(ErrorCode::NotSignedIn, "Not signed in"))?;
+
+        Ok(Self {
+            app: app.clone(),
+            base_url: server::current_url(app).trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, AppError> {
+        let response = self.send(self.request(Method::GET, path)).await?;
+        self.open(path, read_envelope(response).await?.0)
+    }
+
+    /// Fetch every page of a paginated collection
+    pub async fn get_all<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, AppError> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+
+        for page in 1.. {
+            let url = format!("{}{}page={}&limit={}", path, separator, page, PAGE_SIZE);
+            let response = self.send(self.request(Method::GET, &url)).await?;
+            let (batch, total) = read_envelope::<Vec<Value>>(response).await?;
+
+            let fetched = batch.len();
+            for item in batch {
+                items.push(self.open(path, item)?);
+            }
+            let done = match total {
+                Some(total) => items.len() >= total,
+                None => fetched < PAGE_SIZE,
+            };
+            if done || fetched == 0 {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    pub async fn post<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, AppError> {
+        let body = self.seal(path, body)?;
+        let request = self.request(Method::POST, path).json(&body);
+        self.open(path, read_envelope(self.send(request).await?).await?.0)
+    }
+
+    pub async fn patch<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, AppError> {
+        let body = self.seal(path, body)?;
+        let request = self.request(Method::PATCH, path).json(&body);
+        self.open(path, read_envelope(self.send(request).await?).await?.0)
+    }
+
+    pub async fn put<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, AppError> {
+        let body = self.seal(path, body)?;
+        let request = self.request(Method::PUT, path).json(&body);
+        self.open(path, read_envelope(self.send(request).await?).await?.0)
+    }
+
+    pub async fn delete(&self, path: &str) -> Result<(), AppError> {
+        let response = self.send(self.request(Method::DELETE, path)).await?;
+        if response.status().is_success() || response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        read_envelope::<serde_json::Value>(response).await.map(|_| ())
+    }
+
+    /// Send a raw request body, for uploads
+    pub async fn post_bytes<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<T, AppError> {
+        let request = self
+            .request(Method::POST, path)
+            .header("Content-Type", content_type)
+            .body(body);
+        Ok(read_envelope::<T>(self.send(request).await?).await?.0)
+    }
+
+    /// Send one chunk of a resumable upload
+    pub async fn put_chunk<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        offset: u64,
+        total: u64,
+        body: Vec<u8>,
+    ) -> Result<T, AppError> {
+        let end = offset + body.len() as u64;
+        let request = self
+            .request(Method::PUT, path)
+            .header("Content-Type", "application/octet-stream")
+            .header("Content-Range", format!("bytes {}-{}/{}", offset, end.saturating_sub(1), total))
+            .body(body);
+        Ok(read_envelope::<T>(self.send(request).await?).await?.0)
+    }
+
+    /// Start downloading an API resource from byte `offset`. The caller
+    /// reads the body in chunks; a 200 instead of a 206 means the server
+    /// ignored the range and is sending everything.
+    pub async fn download_from(&self, path: &str, offset: u64) -> Result<Response, AppError> {
+        let mut request = self.request(Method::GET, path);
+        if offset > 0 {
+            request = request.header("Range", format!("bytes={}-", offset));
+        }
+        self.send(request).await?.error_for_status().map_err(download_error)
+    }
+
+    /// Download a file. Only requests to the API server carry the token;
+    /// attachment URLs may point at object storage.
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>, AppError> {
+        let request = if url.starts_with(&self.base_url) {
+            http::client(&self.app).get(url).bearer_auth(&self.token)
+        } else if url.starts_with('/') {
+            self.request(Method::GET, url)
+        } else {
+            http::client(&self.app).get(url)
+        };
+
+        let response = self.send(request).await?.error_for_status().map_err(download_error)?;
+        response
+            .bytes()
+            .await
+            .map(|bytes| bytes.to_vec())
+            .map_err(download_error)
+    }
+
+    /// Encode a request body, sealing task fields once encryption is set up.
+    /// Fails while encryption is locked rather than send them in plaintext.
+    fn seal<B: Serialize + ?Sized>(&self, path: &str, body: &B) -> Result<Value, AppError> {
+        let mut body =
+            serde_json::to_value(body).map_err(|e| format!("Failed to encode request: {}", e))?;
+        if is_task_path(path) {
+            crypto::seal_fields(&self.app, &mut body, crypto::TASK_FIELDS)?;
+        }
+        Ok(body)
+    }
+
+    /// Decode response data, opening sealed task fields in a task or a list
+    /// of them
+    fn open<T: DeserializeOwned>(&self, path: &str, mut data: Value) -> Result<T, AppError> {
+        if is_task_path(path) {
+            match &mut data {
+                Value::Array(tasks) => tasks
+                    .iter_mut()
+                    .for_each(|task| crypto::open_fields(&self.app, task, crypto::TASK_FIELDS)),
+                task => crypto::open_fields(&self.app, task, crypto::TASK_FIELDS),
+            }
+        }
+        serde_json::from_value(data).map_err(|e| {
+            AppError::new(ErrorCode::Server, format!("Unexpected response from server: {}", e))
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        http::client(&self.app)
+            .request(method, format!("{}{}", self.base_url, path))
+            .bearer_auth(&self.token)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, AppError> {
+        http::send(&self.app, Integration::Backend, request).await
+    }
+}
+
+fn is_task_path(path: &str) -> bool {
+    path == "/tasks" || path.starts_with("/tasks/") || path.starts_with("/tasks?")
+}
+
+fn download_error(error: tauri_plugin_http::reqwest::Error) -> AppError {
+    let message = format!("Download failed: {}", error);
+    match error.status() {
+        Some(status) => AppError::from_response(status, None, message),
+        None => AppError::new(ErrorCode::Offline, message),
+    }
+}
+
+async fn read_envelope<T: DeserializeOwned>(
+    response: Response,
+) -> Result<(T, Option<usize>), AppError> {
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Offline, format!("Failed to read response: {}", e)))?;
+    let envelope: Envelope<T> = serde_json::from_str(&body).map_err(|e| {
+        if status.is_success() {
+            AppError::new(ErrorCode::Server, format!("Unexpected response from server: {}", e))
+        } else {
+            AppError::from_response(status, None, format!("Server returned {}", status))
+        }
+    })?;
+
+    if let Some(error) = envelope.error {
+        let message = match &error.code {
+            Some(code) => format!("{}: {}", code, error.message),
+            None => format!("{}: {}", status.as_u16(), error.message),
+        };
+        return Err(AppError::from_response(status, error.code, message));
+    }
+    let total = envelope.meta.and_then(|meta| meta.total);
+    envelope
+        .data
+        .map(|data| (data, total))
+        .ok_or_else(|| AppError::new(ErrorCode::Server, format!("Server returned {} without data", status)))
+}
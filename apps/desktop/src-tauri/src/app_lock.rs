@@ -0,0 +1,285 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Emitter, Manager, WindowEvent};
+
+use crate::error::{AppError, ErrorCode};
+use crate::os_auth::{self, AuthOutcome};
+use crate::{keychain, settings};
+
+const PASSWORD_ACCOUNT: &str = "app-lock-password";
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const UNLOCK_REASON: &str = "unlock Open Sunsama";
+const VERIFY_REASON: &str = "change the Open Sunsama app password";
+/// Wrong passwords allowed before each further attempt has to wait
+const FREE_ATTEMPTS: u32 = 3;
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// Commands the locked webview may still call: the lock screen's own.
+/// Everything else is refused until the user unlocks.
+const LOCK_SCREEN_COMMANDS: &[&str] = &[
+    "lock_app",
+    "unlock_app",
+    "is_app_locked",
+    "report_activity",
+];
+
+/// Lock state, the last time the user interacted with the app, and wrong
+/// app passwords since the last right one
+pub struct AppLockState {
+    locked: AtomicBool,
+    last_activity: Mutex<Instant>,
+    failures: Mutex<Failures>,
+}
+
+#[derive(Default)]
+struct Failures {
+    count: u32,
+    last: Option<Instant>,
+}
+
+impl AppLockState {
+    /// `locked` is whether app lock is enabled, so a launch starts behind
+    /// the lock until the user authenticates
+    pub fn new(locked: bool) -> Self {
+        Self {
+            locked: AtomicBool::new(locked),
+            last_activity: Mutex::new(Instant::now()),
+            failures: Mutex::new(Failures::default()),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    pub fn touch(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_activity
+            .lock()
+            .map(|last| last.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// How long until another password may be tried. Free for the first
+    /// few, then doubling from a second up to `MAX_BACKOFF`.
+    fn wait_before_attempt(&self, now: Instant) -> Duration {
+        let Ok(failures) = self.failures.lock() else {
+            return Duration::ZERO;
+        };
+        let Some(last) = failures.last.filter(|_| failures.count >= FREE_ATTEMPTS) else {
+            return Duration::ZERO;
+        };
+        let shift = (failures.count - FREE_ATTEMPTS).min(16);
+        let backoff = Duration::from_secs(1 << shift).min(MAX_BACKOFF);
+        backoff.saturating_sub(now.saturating_duration_since(last))
+    }
+
+    fn record_attempt(&self, verified: bool, now: Instant) {
+        if let Ok(mut failures) = self.failures.lock() {
+            *failures = if verified {
+                Failures::default()
+            } else {
+                Failures {
+                    count: failures.count + 1,
+                    last: Some(now),
+                }
+            };
+        }
+    }
+}
+
+/// Run a command unless the app is locked and it isn't one the lock screen
+/// needs, so whatever still runs in a locked webview can't read or change
+/// data (or turn the lock off through the settings)
+pub fn gate(invoke: Invoke, handler: impl Fn(Invoke) -> bool) -> bool {
+    let locked = invoke.message.webview().state::<AppLockState>().is_locked();
+    if locked && !LOCK_SCREEN_COMMANDS.contains(&invoke.message.command()) {
+        invoke
+            .resolver
+            .reject(AppError::new(ErrorCode::Locked, "Open Sunsama is locked"));
+        return true;
+    }
+    handler(invoke)
+}
+
+/// Lock the app. The webview covers its content on `app-locked`.
+pub fn lock(app: &AppHandle) {
+    let state = app.state::<AppLockState>();
+    if !state.locked.swap(true, Ordering::SeqCst) {
+        let _ = app.emit("app-locked", ());
+    }
+}
+
+/// Unlock with the app password if one is given, otherwise with the OS
+/// prompt (Touch ID, Windows Hello). Falls back to requiring the app
+/// password where no OS authentication is available.
+pub async fn unlock(app: &AppHandle, password: Option<String>) -> Result<(), String> {
+    authenticate(app, password, UNLOCK_REASON).await?;
+
+    let state = app.state::<AppLockState>();
+    state.touch();
+    if state.locked.swap(false, Ordering::SeqCst) {
+        let _ = app.emit("app-unlocked", ());
+    }
+    Ok(())
+}
+
+/// Set (or with `None`, remove) the fallback app password. Takes the
+/// current password, or the OS prompt without one, so whoever finds the app
+/// open can't change it; setting the first one where there's no OS prompt
+/// needs neither.
+pub async fn set_password(
+    app: &AppHandle,
+    current: Option<String>,
+    password: Option<&str>,
+) -> Result<(), String> {
+    if app.state::<AppLockState>().is_locked() {
+        return Err("Open Sunsama is locked".to_string());
+    }
+    match current {
+        Some(current) => authenticate(app, Some(current), VERIFY_REASON).await?,
+        None => match os_prompt(VERIFY_REASON).await? {
+            AuthOutcome::Verified => {}
+            AuthOutcome::Unavailable if keychain::get(PASSWORD_ACCOUNT)?.is_none() => {}
+            AuthOutcome::Unavailable => {
+                return Err("System authentication is unavailable; enter your app password".to_string())
+            }
+            AuthOutcome::Failed => return Err("Authentication failed".to_string()),
+        },
+    }
+
+    let Some(password) = password else {
+        return keychain::delete(PASSWORD_ACCOUNT);
+    };
+    if password.chars().count() < 4 {
+        return Err("App password must be at least 4 characters".to_string());
+    }
+
+    let mut salt = [0u8; 16];
+    getrandom::getrandom(&mut salt).map_err(|e| format!("No secure randomness: {}", e))?;
+    let salt = SaltString::encode_b64(&salt).map_err(|e| format!("Failed to hash password: {}", e))?;
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| format!("Failed to hash password: {}", e))?;
+
+    keychain::set(PASSWORD_ACCOUNT, &hash.to_string())
+}
+
+/// Check the app password if one is given, otherwise the OS prompt. Wrong
+/// passwords back off, so the app password can't be guessed at speed.
+async fn authenticate(
+    app: &AppHandle,
+    password: Option<String>,
+    reason: &'static str,
+) -> Result<(), String> {
+    let verified = match password {
+        Some(password) => {
+            let state = app.state::<AppLockState>();
+            let wait = state.wait_before_attempt(Instant::now());
+            if !wait.is_zero() {
+                return Err(format!("Too many attempts; try again in {} seconds", wait.as_secs().max(1)));
+            }
+            let verified = verify_password(&password)?;
+            state.record_attempt(verified, Instant::now());
+            verified
+        }
+        None => match os_prompt(reason).await? {
+            AuthOutcome::Verified => true,
+            AuthOutcome::Failed => false,
+            AuthOutcome::Unavailable => {
+                return Err("System authentication is unavailable; enter your app password".to_string())
+            }
+        },
+    };
+
+    if !verified {
+        return Err("Authentication failed".to_string());
+    }
+    Ok(())
+}
+
+/// The OS prompt blocks until the user answers, so it runs off the async
+/// runtime
+async fn os_prompt(reason: &'static str) -> Result<AuthOutcome, String> {
+    tauri::async_runtime::spawn_blocking(move || os_auth::authenticate(reason))
+        .await
+        .map_err(|e| format!("Authentication failed: {}", e))
+}
+
+fn verify_password(password: &str) -> Result<bool, String> {
+    let stored = keychain::get(PASSWORD_ACCOUNT)?
+        .ok_or_else(|| "No app password is set".to_string())?;
+    let hash = PasswordHash::new(&stored).map_err(|e| format!("Corrupt password hash: {}", e))?;
+
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok())
+}
+
+/// Count window focus as activity and lock after the configured idle time
+pub fn start_idle_monitor(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let handle = app.clone();
+        window.on_window_event(move |event| {
+            if let WindowEvent::Focused(true) = event {
+                handle.state::<AppLockState>().touch();
+            }
+        });
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+
+            let Ok(current) = settings::load(&app) else {
+                continue;
+            };
+            let security = current.security;
+            if !security.app_lock_enabled || security.auto_lock_minutes == 0 {
+                continue;
+            }
+
+            let idle_limit = Duration::from_secs(u64::from(security.auto_lock_minutes) * 60);
+            if app.state::<AppLockState>().idle_for() >= idle_limit {
+                lock(&app);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrong_passwords_back_off_until_the_right_one() {
+        let state = AppLockState::new(true);
+        let start = Instant::now();
+        for _ in 0..FREE_ATTEMPTS {
+            assert_eq!(state.wait_before_attempt(start), Duration::ZERO);
+            state.record_attempt(false, start);
+        }
+        assert_eq!(state.wait_before_attempt(start), Duration::from_secs(1));
+        assert_eq!(state.wait_before_attempt(start + Duration::from_secs(1)), Duration::ZERO);
+
+        state.record_attempt(false, start);
+        assert_eq!(state.wait_before_attempt(start), Duration::from_secs(2));
+        for _ in 0..20 {
+            state.record_attempt(false, start);
+        }
+        assert_eq!(state.wait_before_attempt(start), MAX_BACKOFF);
+
+        state.record_attempt(true, start);
+        assert_eq!(state.wait_before_attempt(start), Duration::ZERO);
+    }
+}
@@ -0,0 +1,146 @@
+//! Command-line flags, from the first launch or forwarded by the
+//! single-instance plugin when the app is launched again:
+//!
+//! - `--minimized`: start hidden in the tray (autostart passes this)
+//! - `--quick-add`: open the quick-add dialog
+//! - `--today`, `--view <id>`: go to a view from `views::VIEWS`
+//! - `--data-dir <path>`: keep data in `path` (read by `data_dir`, first
+//!   launch only)
+//! - `--profile <id>`: use that profile for this run (first launch only)
+//! - any other argument: a file to open; .ics files are imported into the
+//!   calendar, everything else is handed to the webview
+//!
+//! Unknown flags are ignored, since OSes add their own (e.g. `-psn_` on
+//! macOS). On the first launch the webview isn't listening yet, so actions
+//! wait until it calls `take_startup_actions`.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::automation;
+use crate::file_drop::{self, DroppedFile};
+use crate::views::{self, NavView};
+
+#[derive(Debug, Default)]
+pub struct StartupArgs {
+    pub minimized: bool,
+    pub quick_add: bool,
+    pub view: Option<&'static NavView>,
+    pub data_dir: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub files: Vec<PathBuf>,
+}
+
+/// What the webview should do once it's up
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum StartupAction {
+    QuickAdd,
+    Navigate { route: &'static str },
+    OpenFiles { files: Vec<DroppedFile> },
+}
+
+#[derive(Default)]
+pub struct StartupState {
+    pending: Mutex<Vec<StartupAction>>,
+}
+
+/// Parse arguments, without the program name. Relative paths are resolved
+/// against `cwd`, which for a forwarded launch is the new instance's.
+pub fn parse(args: impl IntoIterator<Item = String>, cwd: &Path) -> StartupArgs {
+    let mut parsed = StartupArgs::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--minimized" | "--hidden" => parsed.minimized = true,
+            "--quick-add" => parsed.quick_add = true,
+            "--today" => parsed.view = find_view("today"),
+            "--view" => parsed.view = args.next().as_deref().and_then(find_view),
+            "--data-dir" => parsed.data_dir = args.next().map(|path| cwd.join(path)),
+            "--profile" => parsed.profile = args.next(),
+            flag if flag.starts_with('-') => {
+                if let Some(id) = flag.strip_prefix("--view=") {
+                    parsed.view = find_view(id);
+                } else if let Some(path) = flag.strip_prefix("--data-dir=") {
+                    parsed.data_dir = Some(cwd.join(path));
+                } else if let Some(id) = flag.strip_prefix("--profile=") {
+                    parsed.profile = Some(id.to_string());
+                }
+            }
+            // Handled by the deep-link plugin
+            url if url.strip_prefix(automation::SCHEME).is_some_and(|rest| rest.starts_with(':')) => {}
+            path => parsed.files.push(cwd.join(path)),
+        }
+    }
+
+    parsed
+}
+
+/// Apply the arguments this process was started with
+pub fn apply_startup(app: &AppHandle) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let args = parse(std::env::args().skip(1), &cwd);
+
+    if !args.minimized {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+    let actions = actions(app, &args);
+    if let Ok(mut pending) = app.state::<StartupState>().pending.lock() {
+        pending.extend(actions);
+    }
+}
+
+/// Apply the arguments of a second launch, which the running app takes over
+pub fn apply_forwarded(app: &AppHandle, argv: Vec<String>, cwd: &str) {
+    let args = parse(argv.into_iter().skip(1), Path::new(cwd));
+
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if !args.minimized {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    for action in actions(app, &args) {
+        let _ = match action {
+            StartupAction::QuickAdd => window.emit("quick-add-task", ()),
+            StartupAction::Navigate { route } => window.emit("navigate", route),
+            StartupAction::OpenFiles { files } => window.emit("files-opened", files),
+        };
+    }
+}
+
+/// Hand over actions from the first launch's arguments; empty afterwards
+pub fn take_pending(app: &AppHandle) -> Vec<StartupAction> {
+    app.state::<StartupState>()
+        .pending
+        .lock()
+        .map(|mut pending| std::mem::take(&mut *pending))
+        .unwrap_or_default()
+}
+
+fn actions(app: &AppHandle, args: &StartupArgs) -> Vec<StartupAction> {
+    let mut actions = Vec::new();
+    if let Some(view) = args.view {
+        actions.push(StartupAction::Navigate { route: view.route });
+    }
+    if args.quick_add {
+        actions.push(StartupAction::QuickAdd);
+    }
+    if !args.files.is_empty() {
+        actions.push(StartupAction::OpenFiles {
+            files: file_drop::open_files(app, &args.files),
+        });
+    }
+    actions
+}
+
+fn find_view(id: &str) -> Option<&'static NavView> {
+    views::VIEWS.iter().find(|view| view.id == id)
+}
@@ -0,0 +1,348 @@
+//! Content-addressed attachment store. Files are copied into
+//! `attachments/objects/<first two hash chars>/<sha256>` under the app data
+//! directory, so attaching the same file twice stores it once. Metadata
+//! lives in the `attachments` table; blobs no row refers to are removed by
+//! `collect_garbage`. Downloads in progress wait in `attachments/partial`.
+//! Placing a blob and recording its row happen under the same lock as
+//! garbage collection, so a blob is never collected before its row exists.
+
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::{clock, data_dir};
+use crate::db::attachments::{self, Attachment};
+use crate::db::Database;
+
+const STORE_DIR: &str = "attachments";
+const OBJECTS_DIR: &str = "objects";
+/// Named copies handed to other apps, since blobs have no file extension
+const OPENED_DIR: &str = "opened";
+/// Downloads in progress, kept across restarts so they can resume
+const PARTIAL_DIR: &str = "partial";
+const STALE_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Held while a blob is placed and recorded, and while garbage is collected
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Copy `source` into the store and record it, optionally against a task
+pub fn attach(app: &AppHandle, source: &Path, task_id: Option<&str>) -> Result<Attachment, String> {
+    let file_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| "Attachment path has no file name".to_string())?;
+    let file = File::open(source).map_err(|e| format!("Failed to open {}: {}", file_name, e))?;
+
+    store_reader(app, file, &file_name, task_id)
+}
+
+/// Store in-memory contents (screenshots, clipboard images) as an attachment
+pub fn attach_bytes(
+    app: &AppHandle,
+    bytes: &[u8],
+    file_name: &str,
+    task_id: Option<&str>,
+) -> Result<Attachment, String> {
+    store_reader(app, bytes, file_name, task_id)
+}
+
+fn store_reader(
+    app: &AppHandle,
+    mut reader: impl Read,
+    file_name: &str,
+    task_id: Option<&str>,
+) -> Result<Attachment, String> {
+    let objects = objects_dir(app)?;
+
+    // Hash while copying to a temporary file, then move it to its
+    // content address unless an identical blob is already there
+    let staging = objects.join(format!("{}.tmp", uuid::Uuid::new_v4()));
+    let copied = File::create(&staging).and_then(|file| {
+        let mut writer = HashingWriter {
+            inner: file,
+            hasher: Sha256::new(),
+        };
+        let size = io::copy(&mut reader, &mut writer)?;
+        writer.inner.sync_all()?;
+        Ok((size, writer.hasher.finalize()))
+    });
+    let (size, digest) = copied.map_err(|e| {
+        let _ = std::fs::remove_file(&staging);
+        format!("Failed to store {}: {}", file_name, e)
+    })?;
+    let hash = hex(&digest);
+
+    let _store = lock_store();
+    place_blob(&objects, &staging, &hash).map_err(|e| format!("Failed to store {}: {}", file_name, e))?;
+    record(app, file_name, task_id, size, hash, None)
+}
+
+/// Move a finished download into the store and record it. The file must
+/// hash to `expected_hash`; a corrupt download is deleted.
+pub fn adopt(
+    app: &AppHandle,
+    staged: &Path,
+    file_name: &str,
+    task_id: Option<&str>,
+    expected_hash: &str,
+    remote_id: &str,
+) -> Result<Attachment, String> {
+    let hashed = File::open(staged).and_then(|mut file| {
+        let mut hasher = Sha256::new();
+        let size = io::copy(&mut file, &mut hasher)?;
+        Ok((size, hasher.finalize()))
+    });
+    let (size, digest) = hashed.map_err(|e| format!("Failed to read {}: {}", file_name, e))?;
+    let hash = hex(&digest);
+    if !hash.eq_ignore_ascii_case(expected_hash) {
+        let _ = std::fs::remove_file(staged);
+        return Err(format!("Downloaded {} is corrupt", file_name));
+    }
+
+    let _store = lock_store();
+    place_blob(&objects_dir(app)?, staged, &hash).map_err(|e| format!("Failed to store {}: {}", file_name, e))?;
+    record(app, file_name, task_id, size, hash, Some(remote_id))
+}
+
+/// Directory for downloads in progress
+pub fn partial_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = store_dir(app)?.join(PARTIAL_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create attachment store: {}", e))?;
+    Ok(dir)
+}
+
+/// Move `staging` to its content address unless an identical blob is
+/// already there
+fn place_blob(objects: &Path, staging: &Path, hash: &str) -> io::Result<()> {
+    let blob = blob_path(objects, hash);
+    if blob.exists() {
+        let _ = std::fs::remove_file(staging);
+        return Ok(());
+    }
+    if let Some(parent) = blob.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(staging, &blob)
+}
+
+fn record(
+    app: &AppHandle,
+    file_name: &str,
+    task_id: Option<&str>,
+    size: u64,
+    hash: String,
+    remote_id: Option<&str>,
+) -> Result<Attachment, String> {
+    let attachment = Attachment {
+        id: uuid::Uuid::new_v4().to_string(),
+        task_id: task_id.map(str::to_string),
+        file_name: file_name.to_string(),
+        mime_type: mime_type(Path::new(file_name)).to_string(),
+        size_bytes: size as i64,
+        hash,
+        created_at: clock::now_millis(),
+        remote_id: remote_id.map(str::to_string),
+    };
+    app.state::<Database>()
+        .with_conn(|conn| attachments::insert(conn, &attachment))?;
+
+    Ok(attachment)
+}
+
+/// Open an attachment in its default app
+pub fn open(app: &AppHandle, id: &str) -> Result<(), String> {
+    let path = named_copy(app, id)?;
+    open::that_detached(&path).map_err(|e| format!("Failed to open attachment: {}", e))
+}
+
+/// Show an attachment in Finder / Explorer / the file manager
+pub fn reveal(app: &AppHandle, id: &str) -> Result<(), String> {
+    let path = named_copy(app, id)?;
+    reveal_path(&path)
+}
+
+pub fn reveal_path(path: &Path) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg("-R").arg(path).spawn().map(|_| ());
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("explorer")
+        .arg(format!("/select,{}", path.display()))
+        .spawn()
+        .map(|_| ());
+    // No portable "select this file" on Linux; open the containing folder
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let result = open::that_detached(path.parent().unwrap_or(path));
+
+    result.map_err(|e| format!("Failed to reveal attachment: {}", e))
+}
+
+/// Delete blobs no attachment refers to and leftover temporary files, and
+/// clear the named copies. Returns the number of store files removed.
+pub fn collect_garbage(app: &AppHandle) -> Result<usize, String> {
+    let store = store_dir(app)?;
+    let _store = lock_store();
+    let referenced = app
+        .state::<Database>()
+        .with_conn(|conn| attachments::referenced_hashes(conn))?;
+
+    let mut removed = 0;
+    let objects = store.join(OBJECTS_DIR);
+    for shard in read_dir(&objects) {
+        if !shard.is_dir() {
+            // Interrupted copies are left in the objects directory itself;
+            // recent ones may still be in progress
+            if is_stale(&shard) && std::fs::remove_file(&shard).is_ok() {
+                removed += 1;
+            }
+            continue;
+        }
+        for blob in read_dir(&shard) {
+            let hash = blob.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            if !referenced.contains(&hash) && std::fs::remove_file(&blob).is_ok() {
+                removed += 1;
+            }
+        }
+        // Fails harmlessly while the shard still holds blobs
+        let _ = std::fs::remove_dir(&shard);
+    }
+
+    let _ = std::fs::remove_dir_all(store.join(OPENED_DIR));
+
+    Ok(removed)
+}
+
+/// Path of the blob holding an attachment's contents
+pub fn blob(app: &AppHandle, attachment: &Attachment) -> Result<PathBuf, String> {
+    Ok(blob_path(&store_dir(app)?.join(OBJECTS_DIR), &attachment.hash))
+}
+
+pub fn get(app: &AppHandle, id: &str) -> Result<Attachment, String> {
+    app.state::<Database>()
+        .with_conn(|conn| attachments::get(conn, id))?
+        .ok_or_else(|| format!("Attachment not found: {}", id))
+}
+
+/// Best-effort MIME type from the file extension
+pub fn mime_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "eml" => "message/rfc822",
+        "ics" => "text/calendar",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A copy of the blob under the attachment's original name, for apps that
+/// go by file extension
+fn named_copy(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    let attachment = get(app, id)?;
+    let blob = blob(app, &attachment)?;
+    if !blob.exists() {
+        return Err(format!("Attachment file is missing: {}", attachment.file_name));
+    }
+
+    let dir = store_dir(app)?.join(OPENED_DIR).join(&attachment.id);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to prepare attachment: {}", e))?;
+    let path = dir.join(safe_file_name(&attachment.file_name));
+    if !path.exists() {
+        std::fs::copy(&blob, &path).map_err(|e| format!("Failed to prepare attachment: {}", e))?;
+    }
+    Ok(path)
+}
+
+fn lock_store() -> MutexGuard<'static, ()> {
+    STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn store_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(data_dir::get(app)?.join(STORE_DIR))
+}
+
+fn objects_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let objects = store_dir(app)?.join(OBJECTS_DIR);
+    std::fs::create_dir_all(&objects)
+        .map_err(|e| format!("Failed to create attachment store: {}", e))?;
+    Ok(objects)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn blob_path(objects: &Path, hash: &str) -> PathBuf {
+    objects.join(&hash[..2]).join(hash)
+}
+
+fn safe_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '\0') { '_' } else { c })
+        .collect();
+    if name.trim_matches('.').is_empty() {
+        "attachment".to_string()
+    } else {
+        name
+    }
+}
+
+fn is_stale(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > STALE_TEMP_AGE)
+}
+
+fn read_dir(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.filter_map(Result::ok).map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+struct HashingWriter {
+    inner: File,
+    hasher: Sha256,
+}
+
+impl Write for HashingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
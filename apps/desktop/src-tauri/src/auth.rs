@@ -0,0 +1,29 @@
+use tokio::sync::watch;
+
+/// Session token handed over by the webview after login, used for native
+/// requests to the backend (sync, uploads). Long-lived connections subscribe
+/// to hear about sign-in, sign-out and refreshed tokens.
+pub struct AuthState {
+    token: watch::Sender<Option<String>>,
+}
+
+impl Default for AuthState {
+    fn default() -> Self {
+        let (token, _) = watch::channel(None);
+        Self { token }
+    }
+}
+
+impl AuthState {
+    pub fn token(&self) -> Option<String> {
+        self.token.borrow().clone()
+    }
+
+    pub fn set_token(&self, token: Option<String>) {
+        self.token.send_replace(token);
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Option<String>> {
+        self.token.subscribe()
+    }
+}
@@ -0,0 +1,119 @@
+//! Auto-scheduler: moves time blocks to the next free slot in the local
+//! schedule, or re-flows the rest of a day after a meeting ran over, as
+//! placed by `opensunsama-core`, and moves their tasks to the new day on
+//! the server through the outbox, queued with the move itself.
+
+use chrono_tz::Tz;
+use opensunsama_core::schedule::auto_schedule::{self, blockers_end, next_free_slot, SLOT_MS};
+use opensunsama_core::timezone::local_date;
+use rusqlite::Connection;
+use serde_json::json;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::outbox;
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::Database;
+use crate::{clock, settings, sync, timezone};
+
+/// Move the block `block_id` to the next free slot from now, keeping its
+/// length. A task moved to another day is rescheduled on the server too.
+/// Emits `time-blocks-changed`.
+pub fn reschedule(app: &AppHandle, block_id: &str) -> Result<TimeBlock, String> {
+    let planning = settings::load(app)?.planning.work_week();
+    let tz = timezone::local();
+    let db = app.state::<Database>();
+
+    let mut block = db
+        .with_conn(|conn| time_blocks::get(conn, block_id))?
+        .ok_or_else(|| "Time block not found".to_string())?;
+    let duration = (block.end_at - block.start_at).max(SLOT_MS);
+    let (start_at, end_at) = db
+        .with_conn(|conn| {
+            let after = clock::now_millis().max(blockers_end(conn, &block)?);
+            next_free_slot(conn, Some(block_id), after, duration, &planning, tz)
+        })?
+        .ok_or_else(|| "No free slot in the next two weeks".to_string())?;
+
+    let previous_start = block.start_at;
+    block.start_at = start_at;
+    block.end_at = end_at;
+    block.timezone = tz.name().to_string();
+    let queued = db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        time_blocks::reschedule(&tx, block_id, start_at, end_at, tz.name())?;
+        let queued = queue_task_date(app, &tx, &block, previous_start, tz)?;
+        tx.commit()?;
+        Ok(queued)
+    })?;
+    let _ = app.emit("time-blocks-changed", ());
+
+    if queued {
+        sync::outbox::flush_after(app, Duration::ZERO);
+    }
+    Ok(block)
+}
+
+/// Push the task blocks of the day that haven't ended by `from` to the
+/// first free time after it, in their order and keeping their length, e.g.
+/// after a meeting ran over. Blocks that no longer fit that day go to the
+/// next free slot on a later one. Returns the blocks that moved and emits
+/// `time-blocks-changed`.
+pub fn reflow_day(app: &AppHandle, from: i64) -> Result<Vec<TimeBlock>, String> {
+    let planning = settings::load(app)?.planning.work_week();
+    let tz = timezone::local();
+    let date = local_date(from, tz).ok_or_else(|| "Invalid reflow time".to_string())?;
+
+    let (blocks, queued) = app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        let moved = auto_schedule::reflow(&tx, date, from, &planning, tz)?;
+        let mut queued = false;
+        let mut blocks = Vec::with_capacity(moved.len());
+        for (block, previous_start) in moved {
+            queued |= queue_task_date(app, &tx, &block, previous_start, tz)?;
+            blocks.push(block);
+        }
+        tx.commit()?;
+        Ok((blocks, queued))
+    })?;
+    if blocks.is_empty() {
+        return Ok(blocks);
+    }
+    let _ = app.emit("time-blocks-changed", ());
+
+    if queued {
+        sync::outbox::flush_after(app, Duration::ZERO);
+    }
+    Ok(blocks)
+}
+
+/// When a task's block moved to another day, queue the task's new date for
+/// the server. Returns whether anything was queued.
+fn queue_task_date(
+    app: &AppHandle,
+    conn: &Connection,
+    block: &TimeBlock,
+    previous_start: i64,
+    tz: Tz,
+) -> rusqlite::Result<bool> {
+    let Some(task_id) = &block.task_id else {
+        return Ok(false);
+    };
+    let Some(date) = local_date(block.start_at, tz) else {
+        return Ok(false);
+    };
+    if local_date(previous_start, tz) == Some(date) {
+        return Ok(false);
+    }
+    outbox::insert(
+        conn,
+        &sync::outbox::entry(
+            app,
+            "PATCH",
+            &format!("/tasks/{}", task_id),
+            Some(&json!({ "scheduledDate": date.to_string() })),
+            Duration::ZERO,
+        ),
+    )?;
+    Ok(true)
+}
@@ -0,0 +1,242 @@
+//! `opensunsama://` automation links. The Shortcuts app ("Open URLs"),
+//! AppleScript (`open location`), Raycast/Alfred and shell scripts can run
+//! core actions without the app in front:
+//!
+//! - `opensunsama://add-task?title=…&notes=…&date=YYYY-MM-DD`
+//! - `opensunsama://new-task` (opens quick add)
+//! - `opensunsama://start-timer?taskId=…`
+//! - `opensunsama://start-focus`
+//! - `opensunsama://today`
+//! - `opensunsama://today-plan`
+//!
+//! Links follow the x-callback-url convention: on success `x-success` is
+//! opened with a `result` parameter, on failure `x-error` gets
+//! `errorMessage`. `today-plan` copies the plan to the clipboard when no
+//! callback is given, so a Shortcut can read it with "Get Clipboard".
+//!
+//! Any web page or app can open these links, so adding a task and handing
+//! out the plan ask the user first, and callbacks only go to app schemes,
+//! never to web pages.
+
+use chrono::{Local, NaiveDate};
+use serde_json::json;
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+use tokio::sync::oneshot;
+
+use crate::api::Api;
+use crate::app_lock::AppLockState;
+use crate::timer::{self, TaskRef, TimerState};
+use crate::{daily_plan, views};
+
+pub const SCHEME: &str = "opensunsama";
+
+/// Callback schemes that would hand results to a web page or script
+const BLOCKED_CALLBACK_SCHEMES: &[&str] = &[
+    "http", "https", "file", "data", "javascript", "ftp", "about", "blob",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    AddTask {
+        title: String,
+        notes: Option<String>,
+        scheduled_date: Option<NaiveDate>,
+    },
+    NewTask,
+    StartTimer {
+        task_id: Option<String>,
+    },
+    StartFocus,
+    ShowToday,
+    TodayPlan,
+}
+
+impl Action {
+    pub fn parse(url: &Url) -> Result<Self, String> {
+        if url.scheme() != SCHEME {
+            return Err(format!("Not an {} link", SCHEME));
+        }
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let param = |key: &str| params.get(key).filter(|value| !value.trim().is_empty()).cloned();
+
+        match url.host_str().unwrap_or_default() {
+            "add-task" => {
+                let title = param("title").ok_or_else(|| "add-task needs a title".to_string())?;
+                let scheduled_date = param("date")
+                    .map(|date| {
+                        NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                            .map_err(|e| format!("Invalid date '{}': {}", date, e))
+                    })
+                    .transpose()?;
+                Ok(Action::AddTask {
+                    title: title.trim().to_string(),
+                    notes: param("notes"),
+                    scheduled_date,
+                })
+            }
+            "new-task" => Ok(Action::NewTask),
+            "start-timer" => Ok(Action::StartTimer {
+                task_id: param("taskId"),
+            }),
+            "start-focus" => Ok(Action::StartFocus),
+            "today" => Ok(Action::ShowToday),
+            "today-plan" => Ok(Action::TodayPlan),
+            other => Err(format!("Unknown action '{}'", other)),
+        }
+    }
+}
+
+/// Run every automation link in `urls`, reporting each result through its
+/// callbacks. Links for other schemes are ignored.
+pub fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    for url in urls.into_iter().filter(|url| url.scheme() == SCHEME) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+            match run(&app, &url).await {
+                Ok(result) => callback(params.get("x-success"), "result", result.as_deref()),
+                Err(e) => {
+                    let _ = app.emit("automation-error", &e);
+                    callback(params.get("x-error"), "errorMessage", Some(&e));
+                }
+            }
+        });
+    }
+}
+
+async fn run(app: &AppHandle, url: &Url) -> Result<Option<String>, String> {
+    let action = Action::parse(url)?;
+    let copy_plan = action == Action::TodayPlan && !url.query_pairs().any(|(key, _)| key == "x-success");
+
+    if let Some((message, ok_label)) = confirmation(&action) {
+        if !confirm(app, message, ok_label).await {
+            return Err("Cancelled by the user".to_string());
+        }
+    }
+
+    let result = perform(app, action).await?;
+    if let (true, Some(plan)) = (copy_plan, &result) {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(plan.clone()))
+            .map_err(|e| format!("Failed to copy plan: {}", e))?;
+    }
+    Ok(result)
+}
+
+/// Run one action. Returns the new task's id for `AddTask` and the plan
+/// text for `TodayPlan`. Refused while the app is locked.
+pub async fn perform(app: &AppHandle, action: Action) -> Result<Option<String>, String> {
+    if app.state::<AppLockState>().is_locked() {
+        return Err("Open Sunsama is locked".to_string());
+    }
+
+    match action {
+        Action::AddTask {
+            title,
+            notes,
+            scheduled_date,
+        } => {
+            let task: serde_json::Value = Api::new(app)?
+                .post(
+                    "/tasks",
+                    &json!({
+                        "title": title,
+                        "notes": notes,
+                        "scheduledDate": scheduled_date.map(|date| date.format("%Y-%m-%d").to_string()),
+                    }),
+                )
+                .await?;
+            let _ = app.emit("task-created", &task);
+            Ok(task.get("id").and_then(|id| id.as_str()).map(str::to_string))
+        }
+        Action::NewTask => {
+            show_and_emit(app, "quick-add-task", json!(null));
+            Ok(None)
+        }
+        Action::StartFocus => {
+            show_and_emit(app, "start-focus-mode", json!(null));
+            Ok(None)
+        }
+        Action::StartTimer { task_id } => {
+            let task = match task_id {
+                Some(id) => Some(task_ref(app, id).await?),
+                None => None,
+            };
+            timer::start(app, task)?;
+            Ok(None)
+        }
+        Action::ShowToday => {
+            if let Some(today) = views::VIEWS.iter().find(|view| view.id == "today") {
+                views::navigate(app, today);
+            }
+            Ok(None)
+        }
+        Action::TodayPlan => daily_plan::describe(app, Local::now().date_naive())
+            .await
+            .map(Some),
+    }
+}
+
+/// A task to time by id, titled from the focused task or the backend
+async fn task_ref(app: &AppHandle, id: String) -> Result<TaskRef, String> {
+    if let Some(current) = app.state::<TimerState>().current_task().filter(|task| task.id == id) {
+        return Ok(current);
+    }
+    let task: serde_json::Value = Api::new(app)?.get(&format!("/tasks/{}", id)).await?;
+    let title = task
+        .get("title")
+        .and_then(|title| title.as_str())
+        .unwrap_or_default()
+        .to_string();
+    Ok(TaskRef { id, title })
+}
+
+fn show_and_emit(app: &AppHandle, event: &str, payload: serde_json::Value) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.emit(event, payload);
+    }
+}
+
+/// What to ask before running a link's action, and the confirming button.
+/// Views and the timer only change what's on screen; these create data or
+/// hand it to whoever opened the link.
+fn confirmation(action: &Action) -> Option<(String, &'static str)> {
+    match action {
+        Action::AddTask { title, .. } => Some((format!("Add the task \"{}\"?", title), "Add Task")),
+        Action::TodayPlan => Some(("Share today's plan with the app that asked for it?".to_string(), "Share")),
+        _ => None,
+    }
+}
+
+/// Ask in a native dialog; dismissing it counts as no
+async fn confirm(app: &AppHandle, message: String, ok_label: &str) -> bool {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .message(message)
+        .title("Open Sunsama")
+        .buttons(MessageDialogButtons::OkCancelCustom(ok_label.to_string(), "Cancel".to_string()))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+    rx.await.unwrap_or(false)
+}
+
+/// Open an x-callback URL with `key=value` appended. Web callbacks are
+/// dropped, so a page can't read results back through them.
+fn callback(target: Option<&String>, key: &str, value: Option<&str>) {
+    let Some(mut url) = target.and_then(|target| Url::parse(target).ok()) else {
+        return;
+    };
+    if BLOCKED_CALLBACK_SCHEMES.contains(&url.scheme()) {
+        tracing::warn!(scheme = url.scheme(), "ignoring automation callback to a web URL");
+        return;
+    }
+    if let Some(value) = value {
+        url.query_pairs_mut().append_pair(key, value);
+    }
+    let _ = open::that_detached(url.as_str());
+}
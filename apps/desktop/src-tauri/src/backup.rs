@@ -0,0 +1,265 @@
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use zip::ZipArchive;
+
+use crate::{clock, data_dir};
+use crate::db::{self, Database};
+use crate::settings::{self, migrations, AppSettings};
+use crate::zipfile::{self, sha256_hex};
+
+const BACKUP_DIR: &str = "backups";
+const BACKUP_FORMAT: &str = "open-sunsama-backup";
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "database.db";
+const SETTINGS_ENTRY: &str = "settings.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupKind {
+    Manual,
+    Scheduled,
+    /// Taken automatically before a restore overwrites the current data
+    PreRestore,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    format: String,
+    kind: BackupKind,
+    created_at: i64,
+    app_version: String,
+    settings_version: u64,
+    /// Whether the database copy is SQLCipher-encrypted with the local key
+    encrypted: bool,
+    /// SHA-256 (hex) of every other entry in the archive
+    checksums: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub id: String,
+    pub kind: BackupKind,
+    pub created_at: i64,
+    pub size_bytes: u64,
+    pub encrypted: bool,
+}
+
+/// Snapshot the database and settings into a new archive in the backups
+/// directory. The archive is written under a temporary name and renamed
+/// when complete, so a crash never leaves a half-written backup behind.
+pub fn create(app: &AppHandle, kind: BackupKind) -> Result<BackupInfo, String> {
+    let dir = backup_dir(app)?;
+    let created_at = clock::now_millis();
+    let id = backup_id(created_at);
+    let key = db::current_key(app)?;
+
+    let snapshot = dir.join(format!("{}.db.tmp", id));
+    app.state::<Database>().snapshot(&snapshot, key.as_deref())?;
+    let database = std::fs::read(&snapshot);
+    let _ = std::fs::remove_file(&snapshot);
+    let database = database.map_err(|e| format!("Failed to read database snapshot: {}", e))?;
+
+    let settings = serde_json::to_vec_pretty(&settings::load(app)?)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    let manifest = Manifest {
+        format: BACKUP_FORMAT.to_string(),
+        kind,
+        created_at,
+        app_version: app.package_info().version.to_string(),
+        settings_version: migrations::CURRENT_VERSION,
+        encrypted: key.is_some(),
+        checksums: BTreeMap::from([
+            (DATABASE_ENTRY.to_string(), sha256_hex(&database)),
+            (SETTINGS_ENTRY.to_string(), sha256_hex(&settings)),
+        ]),
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+
+    let path = archive_path(&dir, &id);
+    let partial = path.with_extension("zip.partial");
+    zipfile::write(
+        &partial,
+        &[
+            (MANIFEST_ENTRY, &manifest_json),
+            (DATABASE_ENTRY, &database),
+            (SETTINGS_ENTRY, &settings),
+        ],
+    )
+    .inspect_err(|_| {
+        let _ = std::fs::remove_file(&partial);
+    })?;
+    std::fs::rename(&partial, &path).map_err(|e| format!("Failed to save backup: {}", e))?;
+
+    info(&path, id, &manifest)
+}
+
+/// All backups, newest first. Archives that can't be read are skipped.
+pub fn list(app: &AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let dir = backup_dir(app)?;
+    let entries =
+        std::fs::read_dir(&dir).map_err(|e| format!("Failed to read backups directory: {}", e))?;
+
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_string_lossy().into_owned();
+            let manifest = read_manifest(&mut zipfile::open(&path).ok()?).ok()?;
+            info(&path, id, &manifest).ok()
+        })
+        .collect();
+
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(backups)
+}
+
+/// Verify a backup and replace the current database and settings with it.
+/// A pre-restore backup of the current state is taken first.
+pub fn restore(app: &AppHandle, id: &str) -> Result<(), String> {
+    let dir = backup_dir(app)?;
+    let path = archive_path(&dir, id);
+    if !is_valid_id(id) || !path.is_file() {
+        return Err(format!("Backup not found: {}", id));
+    }
+
+    let mut archive = zipfile::open(&path)?;
+    let manifest = read_manifest(&mut archive)?;
+    let database = zipfile::read_verified(&mut archive, &manifest.checksums, DATABASE_ENTRY)?;
+    let settings_json = zipfile::read_verified(&mut archive, &manifest.checksums, SETTINGS_ENTRY)?;
+
+    let key = db::current_key(app)?;
+    if manifest.encrypted && key.is_none() {
+        return Err(
+            "This backup is encrypted; turn on local encryption before restoring it".to_string(),
+        );
+    }
+    // Unencrypted backups are opened without a key and re-keyed below
+    let backup_key = if manifest.encrypted { key.as_deref() } else { None };
+
+    let stored: Value = serde_json::from_slice(&settings_json)
+        .map_err(|e| format!("Backup settings are not valid JSON: {}", e))?;
+    let stored = migrations::migrate_value(stored, manifest.settings_version)?;
+
+    create(app, BackupKind::PreRestore)?;
+
+    let staged = dir.join(format!("{}.db.restore", id));
+    std::fs::write(&staged, &database).map_err(|e| format!("Failed to stage backup: {}", e))?;
+    let db = app.state::<Database>();
+    db.restore_from(&staged, backup_key).inspect_err(|_| {
+        let _ = std::fs::remove_file(&staged);
+    })?;
+    if backup_key != key.as_deref() {
+        db.change_key(key.as_deref())?;
+    }
+
+    let current = settings::load(app)?;
+    let mut restored = AppSettings::from_value(stored);
+    restored.preserve_managed(&current);
+    settings::save(app, &restored)?;
+    settings::apply(app, &restored)?;
+
+    let _ = app.emit("settings-changed", &restored);
+    let _ = app.emit("backup-restored", id);
+    Ok(())
+}
+
+/// Delete the oldest backups beyond the configured retention count
+pub fn prune(app: &AppHandle, keep: usize) -> Result<(), String> {
+    let dir = backup_dir(app)?;
+    for backup in list(app)?.into_iter().skip(keep) {
+        std::fs::remove_file(archive_path(&dir, &backup.id))
+            .map_err(|e| format!("Failed to delete backup {}: {}", backup.id, e))?;
+    }
+    Ok(())
+}
+
+/// Take a scheduled backup whenever the newest one is older than the
+/// configured interval, then apply retention
+pub fn start_scheduler(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            if let Err(e) = run_scheduled(&app) {
+                tracing::warn!("Scheduled backup failed: {}", e);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+fn run_scheduled(app: &AppHandle) -> Result<(), String> {
+    let config = settings::load(app)?.backup;
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let interval_ms = i64::from(config.interval_hours.max(1)) * 60 * 60 * 1000;
+    let newest = list(app)?.first().map(|backup| backup.created_at);
+    if newest.is_some_and(|at| clock::now_millis() - at < interval_ms) {
+        return Ok(());
+    }
+
+    create(app, BackupKind::Scheduled)?;
+    prune(app, config.keep.max(1) as usize)
+}
+
+fn backup_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = data_dir::get(app)?.join(BACKUP_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create backups directory: {}", e))?;
+    Ok(dir)
+}
+
+fn backup_id(created_at: i64) -> String {
+    let time = Utc
+        .timestamp_millis_opt(created_at)
+        .single()
+        .unwrap_or_else(Utc::now);
+    format!("backup-{}", time.format("%Y%m%d-%H%M%S-%3f"))
+}
+
+/// Ids come from the webview, so only accept ones this module could have made
+fn is_valid_id(id: &str) -> bool {
+    id.starts_with("backup-") && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn archive_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.zip", id))
+}
+
+fn info(path: &Path, id: String, manifest: &Manifest) -> Result<BackupInfo, String> {
+    let size_bytes = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read backup: {}", e))?
+        .len();
+
+    Ok(BackupInfo {
+        id,
+        kind: manifest.kind,
+        created_at: manifest.created_at,
+        size_bytes,
+        encrypted: manifest.encrypted,
+    })
+}
+
+fn read_manifest(archive: &mut ZipArchive<File>) -> Result<Manifest, String> {
+    let manifest: Manifest = serde_json::from_slice(&zipfile::read_entry(archive, MANIFEST_ENTRY)?)
+        .map_err(|e| format!("Invalid backup manifest: {}", e))?;
+    if manifest.format != BACKUP_FORMAT {
+        return Err("Not an Open Sunsama backup".to_string());
+    }
+    Ok(manifest)
+}
@@ -0,0 +1,145 @@
+//! Bulk task changes in one call, e.g. dragging many tasks to tomorrow.
+//! Every change is checked first; the valid ones are queued to the outbox,
+//! along with any local cleanup, in a single transaction and sent in order
+//! on one flush. Each op gets its own result, so one bad op doesn't sink
+//! the rest.
+
+use chrono::{DateTime, NaiveDate, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clock::ClockState;
+use crate::db::outbox::{self, OutboxEntry};
+use crate::db::{dependencies, subtasks, tasks, Database};
+use crate::sync::{self, SERVER_FIELDS};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Op {
+    /// Move to a day (`YYYY-MM-DD`), or to the backlog with none
+    #[serde(rename_all = "camelCase")]
+    Schedule {
+        task_id: String,
+        date: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Complete { task_id: String, completed: bool },
+    /// Set fields of the task as the server names them, other than the ones
+    /// the server assigns
+    #[serde(rename_all = "camelCase")]
+    Update { task_id: String, changes: Value },
+    #[serde(rename_all = "camelCase")]
+    Delete { task_id: String },
+}
+
+impl Op {
+    fn task_id(&self) -> &str {
+        match self {
+            Op::Schedule { task_id, .. }
+            | Op::Complete { task_id, .. }
+            | Op::Update { task_id, .. }
+            | Op::Delete { task_id } => task_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpResult {
+    pub task_id: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// Queue `ops` in one transaction. Returns a result per op, in order, and
+/// emits `tasks-batch-applied` with them.
+pub fn apply(app: &AppHandle, ops: Vec<Op>) -> Result<Vec<OpResult>, String> {
+    let checked: Vec<(Op, Result<OutboxEntry, String>)> = ops
+        .into_iter()
+        .map(|op| {
+            let entry = request(app, &op);
+            (op, entry)
+        })
+        .collect();
+
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        for (op, entry) in &checked {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            outbox::insert(&tx, entry)?;
+            if let Op::Delete { task_id } = op {
+                subtasks::delete_task(&tx, task_id)?;
+                tasks::delete(&tx, task_id)?;
+                dependencies::delete_task(&tx, task_id)?;
+            }
+        }
+        tx.commit()
+    })?;
+    if checked.iter().any(|(_, entry)| entry.is_ok()) {
+        sync::outbox::flush_after(app, Duration::ZERO);
+    }
+
+    let results: Vec<OpResult> = checked
+        .into_iter()
+        .map(|(op, entry)| OpResult {
+            task_id: op.task_id().to_string(),
+            ok: entry.is_ok(),
+            error: entry.err(),
+        })
+        .collect();
+    let _ = app.emit("tasks-batch-applied", &results);
+    Ok(results)
+}
+
+/// The server request carrying `op`
+fn request(app: &AppHandle, op: &Op) -> Result<OutboxEntry, String> {
+    let task_id = op.task_id();
+    if task_id.is_empty() {
+        return Err("Missing task id".to_string());
+    }
+    let path = format!("/tasks/{}", task_id);
+    let patch = |body: Value| {
+        Ok(sync::outbox::entry(
+            app,
+            "PATCH",
+            &path,
+            Some(&body),
+            Duration::ZERO,
+        ))
+    };
+
+    match op {
+        Op::Schedule { date, .. } => {
+            if let Some(date) = date {
+                date.parse::<NaiveDate>()
+                    .map_err(|e| format!("Invalid date '{}': {}", date, e))?;
+            }
+            patch(json!({ "scheduledDate": date }))
+        }
+        Op::Complete { completed, .. } => {
+            let now = app.state::<ClockState>().now_millis();
+            let completed_at = completed
+                .then_some(now)
+                .and_then(DateTime::from_timestamp_millis)
+                .map(|at| at.to_rfc3339_opts(SecondsFormat::Millis, true));
+            patch(json!({ "completedAt": completed_at }))
+        }
+        Op::Update { changes, .. } => match changes {
+            Value::Object(fields) if !fields.is_empty() => {
+                let server_field = fields
+                    .keys()
+                    .find(|key| SERVER_FIELDS.contains(&key.as_str()));
+                if let Some(field) = server_field {
+                    return Err(format!("'{}' is set by the server", field));
+                }
+                patch(changes.clone())
+            }
+            _ => Err("Changes must be a non-empty object".to_string()),
+        },
+        Op::Delete { .. } => Ok(sync::outbox::entry(app, "DELETE", &path, None, Duration::ZERO)),
+    }
+}
@@ -0,0 +1,164 @@
+//! Weekly time budgets per channel. Budgets live in the local database;
+//! usage is the time tracked on the channel's tasks since Monday. The
+//! notification scheduler checks usage every few minutes and alerts once a
+//! week per channel at each of 80% and 100%.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::analytics;
+use crate::connectivity::ConnectivityState;
+use crate::db::budgets::{self, Budget};
+use crate::db::Database;
+use crate::notifications::{self, Category, Notice};
+use crate::{clock, settings, weekly_review};
+
+/// Usage levels alerted on, in percent of the budget
+const THRESHOLDS: [u32; 2] = [80, 100];
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// When usage was last checked for alerts
+#[derive(Default)]
+pub struct BudgetState {
+    checked_at: Mutex<Option<Instant>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub channel: String,
+    pub weekly_minutes: u32,
+    /// Tracked this week
+    pub used_minutes: i64,
+    pub percent: u32,
+    /// Highest threshold alerted this week, 0 for none
+    pub alerted: u32,
+}
+
+/// Set the weekly budget of `channel`, or remove it with `None`
+pub fn set(app: &AppHandle, channel: &str, weekly_minutes: Option<u32>) -> Result<(), String> {
+    let channel = channel.trim().trim_start_matches('#').trim();
+    if channel.is_empty() {
+        return Err("Budgets need a channel".to_string());
+    }
+    app.state::<Database>()
+        .with_conn(|conn| match weekly_minutes {
+            Some(weekly_minutes) if weekly_minutes > 0 => budgets::set(
+                conn,
+                &Budget {
+                    channel: channel.to_string(),
+                    weekly_minutes,
+                },
+            ),
+            _ => budgets::delete(conn, channel),
+        })
+}
+
+pub fn list(app: &AppHandle) -> Result<Vec<Budget>, String> {
+    app.state::<Database>()
+        .with_conn(|conn| budgets::list(conn))
+}
+
+/// Usage of every budget in the week starting `monday`
+pub async fn status(app: &AppHandle, monday: NaiveDate) -> Result<Vec<BudgetStatus>, String> {
+    let week_start = monday.to_string();
+    let db = app.state::<Database>();
+    let budgets = db.with_conn(|conn| budgets::list(conn))?;
+    if budgets.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tracked = analytics::tracked_by_channel(app, monday, 7).await?;
+
+    budgets
+        .into_iter()
+        .map(|budget| {
+            let used_minutes = tracked
+                .iter()
+                .filter(|(channel, _)| channel.eq_ignore_ascii_case(&budget.channel))
+                .map(|(_, minutes)| minutes)
+                .sum::<i64>();
+            let percent = used_minutes * 100 / i64::from(budget.weekly_minutes.max(1));
+            let alerted =
+                db.with_conn(|conn| budgets::alerted(conn, &budget.channel, &week_start))?;
+            Ok(BudgetStatus {
+                percent: u32::try_from(percent).unwrap_or(u32::MAX),
+                used_minutes,
+                alerted,
+                channel: budget.channel,
+                weekly_minutes: budget.weekly_minutes,
+            })
+        })
+        .collect()
+}
+
+/// Alert on budgets that crossed a threshold since the last check. Called
+/// by the scheduler on every tick; does the work every few minutes while
+/// online. Emits `budget-alert` with the channel's status.
+pub async fn check_due(app: &AppHandle) -> Result<(), String> {
+    {
+        let mut checked_at = app
+            .state::<BudgetState>()
+            .checked_at
+            .lock()
+            .map_err(|_| "Budget state poisoned".to_string())?;
+        if checked_at.is_some_and(|at| at.elapsed() < CHECK_INTERVAL) {
+            return Ok(());
+        }
+        *checked_at = Some(Instant::now());
+    }
+    if !settings::load(app)?.notifications.budget_alerts
+        || !app.state::<ConnectivityState>().is_online()
+    {
+        return Ok(());
+    }
+
+    let monday = weekly_review::week_start(weekly_review::today());
+    let week_start = monday.to_string();
+    for status in status(app, monday).await? {
+        let Some(threshold) = THRESHOLDS
+            .into_iter()
+            .filter(|threshold| status.percent >= *threshold && *threshold > status.alerted)
+            .max()
+        else {
+            continue;
+        };
+
+        let now = clock::now_millis();
+        app.state::<Database>().with_conn(|conn| {
+            for reached in THRESHOLDS
+                .into_iter()
+                .filter(|reached| *reached <= threshold)
+            {
+                budgets::record_alert(conn, &status.channel, &week_start, reached, now)?;
+            }
+            Ok(())
+        })?;
+
+        let title = if threshold >= 100 {
+            format!("#{} is over its weekly budget", status.channel)
+        } else {
+            format!(
+                "#{} has used {}% of its weekly budget",
+                status.channel, threshold
+            )
+        };
+        let _ = notifications::notify(
+            app,
+            Notice::new(Category::Budget, title).body(format!(
+                "{} of {} min tracked this week.",
+                status.used_minutes, status.weekly_minutes
+            )),
+        );
+        let _ = app.emit(
+            "budget-alert",
+            BudgetStatus {
+                alerted: threshold,
+                ..status
+            },
+        );
+    }
+    Ok(())
+}
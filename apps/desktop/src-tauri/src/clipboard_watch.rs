@@ -0,0 +1,155 @@
+//! Opt-in clipboard watcher for quick capture. Copied text that matches one
+//! of the configured patterns is offered as a task.
+//!
+//! Privacy: nothing is read unless the watcher is enabled, clipboard text is
+//! never logged, stored or sent anywhere, only a hash of the last value is
+//! kept to detect changes, and a match is held in memory only until it is
+//! accepted, dismissed or expires. Nothing is read while the app is locked.
+//! Whatever is on the clipboard when the watcher starts is never offered.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::app_lock::AppLockState;
+use crate::clock;
+use crate::notifications::{self, Category, Notice};
+use crate::settings::{self, ClipboardPattern};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const DISABLED_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const SUGGESTION_TTL_MS: i64 = 5 * 60 * 1000;
+const MAX_TITLE_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureSuggestion {
+    pub id: String,
+    /// Name of the pattern that matched
+    pub source: String,
+    pub title: String,
+    pub text: String,
+    pub created_at: i64,
+}
+
+#[derive(Default)]
+pub struct ClipboardWatchState {
+    pending: Mutex<HashMap<String, CaptureSuggestion>>,
+}
+
+impl ClipboardWatchState {
+    /// Remove and return a pending suggestion
+    pub fn take(&self, id: &str) -> Option<CaptureSuggestion> {
+        let mut pending = self.pending.lock().ok()?;
+        pending.remove(id).filter(|s| clock::now_millis() - s.created_at < SUGGESTION_TTL_MS)
+    }
+
+    fn insert(&self, suggestion: CaptureSuggestion) {
+        if let Ok(mut pending) = self.pending.lock() {
+            let now = clock::now_millis();
+            pending.retain(|_, s| now - s.created_at < SUGGESTION_TTL_MS);
+            pending.insert(suggestion.id.clone(), suggestion);
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.clear();
+        }
+    }
+}
+
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_hash: Option<u64> = None;
+        let mut compiled: Option<(Vec<ClipboardPattern>, Vec<(String, Regex)>)> = None;
+
+        loop {
+            let config = settings::load(&app).map(|s| s.clipboard).unwrap_or_default();
+            if !config.enabled || app.state::<AppLockState>().is_locked() {
+                // Forget everything so re-enabling starts fresh
+                last_hash = None;
+                app.state::<ClipboardWatchState>().clear();
+                tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+                continue;
+            }
+
+            let text = tauri::async_runtime::spawn_blocking(read_text).await.ok().flatten();
+            let hash = text.as_deref().map(hash_text);
+            // The first read only records what was already there
+            let changed = last_hash.is_some() && hash.is_some() && hash != last_hash;
+            if hash.is_some() {
+                last_hash = hash;
+            }
+
+            if let Some(text) = text.filter(|text| changed && text.len() <= config.max_length) {
+                if compiled.as_ref().is_none_or(|(patterns, _)| *patterns != config.patterns) {
+                    compiled = Some((config.patterns.clone(), compile(&config.patterns)));
+                }
+                if let Some(suggestion) = compiled.as_ref().and_then(|(_, regexes)| match_text(regexes, &text)) {
+                    offer(&app, suggestion);
+                }
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+fn read_text() -> Option<String> {
+    arboard::Clipboard::new().ok()?.get_text().ok()
+}
+
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Invalid patterns are skipped rather than disabling the watcher
+fn compile(patterns: &[ClipboardPattern]) -> Vec<(String, Regex)> {
+    patterns
+        .iter()
+        .filter_map(|p| Regex::new(&p.regex).ok().map(|regex| (p.name.clone(), regex)))
+        .collect()
+}
+
+fn match_text(regexes: &[(String, Regex)], text: &str) -> Option<CaptureSuggestion> {
+    regexes.iter().find_map(|(name, regex)| {
+        let captures = regex.captures(text)?;
+        let matched = captures.get(0)?.as_str();
+        let title: String = captures
+            .name("title")
+            .map_or(matched, |m| m.as_str())
+            .chars()
+            .take(MAX_TITLE_CHARS)
+            .collect();
+
+        Some(CaptureSuggestion {
+            id: uuid::Uuid::new_v4().to_string(),
+            source: name.clone(),
+            title,
+            text: matched.to_string(),
+            created_at: clock::now_millis(),
+        })
+    })
+}
+
+/// Announce `suggestion` and keep it for `clipboard-capture` listeners,
+/// which offer to add it as a task
+fn offer(app: &AppHandle, suggestion: CaptureSuggestion) {
+    let _ = notifications::notify(
+        app,
+        Notice::new(Category::Capture, format!("Add {} as a task?", suggestion.source))
+            .body(&suggestion.title),
+    );
+
+    let _ = app.emit("clipboard-capture", &suggestion);
+    app.state::<ClipboardWatchState>().insert(suggestion);
+}
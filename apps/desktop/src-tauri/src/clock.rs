@@ -0,0 +1,73 @@
+//! Time as the app sees it. Most code reads the system clock through
+//! `now_millis`. The timer, the daily rollover, the reminder scheduler, the
+//! outbox and settings change times read the `Clock` managed as
+//! `ClockState` instead, so their time can be driven from outside, as
+//! `testing::ManualClock` does.
+
+use chrono::{DateTime, NaiveDate};
+use std::ops::Deref;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::timezone;
+
+/// A source of wall-clock and monotonic time
+pub trait Clock: Send + Sync {
+    /// Wall-clock time as Unix milliseconds
+    fn now_millis(&self) -> i64;
+
+    /// Monotonic time, for telling sleep and clock changes apart
+    fn instant(&self) -> Instant;
+
+    /// The local date
+    fn today(&self) -> NaiveDate {
+        DateTime::from_timestamp_millis(self.now_millis())
+            .unwrap_or_default()
+            .with_timezone(&timezone::local())
+            .date_naive()
+    }
+}
+
+/// The operating system's clocks
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        now_millis()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The clock the time-dependent jobs run on, managed before any of them
+/// start
+pub struct ClockState(Box<dyn Clock>);
+
+impl ClockState {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Box::new(clock))
+    }
+}
+
+impl Default for ClockState {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl Deref for ClockState {
+    type Target = dyn Clock;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// Current wall-clock time as Unix milliseconds
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
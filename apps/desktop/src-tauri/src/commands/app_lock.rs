@@ -0,0 +1,44 @@
+use tauri::State;
+
+use crate::app_lock::{self, AppLockState};
+use crate::error::AppError;
+
+/// Lock the app immediately
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn lock_app(app: tauri::AppHandle) {
+    app_lock::lock(&app);
+}
+
+/// Unlock with the OS prompt, or with the app password when given
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn unlock_app(app: tauri::AppHandle, password: Option<String>) -> Result<(), AppError> {
+    Ok(app_lock::unlock(&app, password).await?)
+}
+
+/// Whether the app is currently locked
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn is_app_locked(state: State<'_, AppLockState>) -> bool {
+    state.is_locked()
+}
+
+/// Record user activity to postpone auto-lock (throttled by the webview)
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn report_activity(state: State<'_, AppLockState>) {
+    state.touch();
+}
+
+/// Set or clear the fallback app password used where OS authentication is
+/// unavailable. Takes the current password, or shows the OS prompt without one.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn set_app_lock_password(
+    app: tauri::AppHandle,
+    current_password: Option<String>,
+    password: Option<String>,
+) -> Result<(), AppError> {
+    Ok(app_lock::set_password(&app, current_password, password.as_deref()).await?)
+}
@@ -0,0 +1,64 @@
+use std::path::Path;
+use tauri::State;
+
+use crate::attachments;
+use crate::db::attachments::{self as attachment_rows, Attachment};
+use crate::db::Database;
+use crate::error::AppError;
+
+/// Copy a file into the attachment store, optionally linking it to a task
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn attach_file(
+    app: tauri::AppHandle,
+    path: String,
+    task_id: Option<String>,
+) -> Result<Attachment, AppError> {
+    Ok(attachments::attach(&app, Path::new(&path), task_id.as_deref())?)
+}
+
+/// List a task's attachments
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_attachments(db: State<'_, Database>, task_id: String) -> Result<Vec<Attachment>, AppError> {
+    Ok(db.with_conn(|conn| attachment_rows::list_for_task(conn, &task_id))?)
+}
+
+/// Link an attachment to a task, or unlink it with `None`
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_attachment_task(
+    db: State<'_, Database>,
+    id: String,
+    task_id: Option<String>,
+) -> Result<(), AppError> {
+    Ok(db.with_conn(|conn| attachment_rows::set_task(conn, &id, task_id.as_deref()))?)
+}
+
+/// Remove an attachment. Its file is freed by the next garbage collection.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn delete_attachment(db: State<'_, Database>, id: String) -> Result<(), AppError> {
+    Ok(db.with_conn(|conn| attachment_rows::delete(conn, &id))?)
+}
+
+/// Open an attachment in its default app
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn open_attachment(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(attachments::open(&app, &id)?)
+}
+
+/// Show an attachment in the system file manager
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn reveal_attachment(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(attachments::reveal(&app, &id)?)
+}
+
+/// Delete stored files no attachment refers to; returns how many were removed
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn gc_attachments(app: tauri::AppHandle) -> Result<usize, AppError> {
+    Ok(attachments::collect_garbage(&app)?)
+}
@@ -0,0 +1,17 @@
+use tauri::State;
+
+use crate::auth::AuthState;
+
+/// Hand the session token to the native layer after login
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn set_auth_token(state: State<'_, AuthState>, token: String) {
+    state.set_token(Some(token));
+}
+
+/// Forget the session token on logout
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn clear_auth_token(state: State<'_, AuthState>) {
+    state.set_token(None);
+}
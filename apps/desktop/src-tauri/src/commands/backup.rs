@@ -0,0 +1,26 @@
+use crate::backup::{self, BackupInfo, BackupKind};
+use crate::error::AppError;
+use crate::settings;
+
+/// Back up the local database and settings now
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn create_backup_now(app: tauri::AppHandle) -> Result<BackupInfo, AppError> {
+    let backup = backup::create(&app, BackupKind::Manual)?;
+    backup::prune(&app, settings::load(&app)?.backup.keep.max(1) as usize)?;
+    Ok(backup)
+}
+
+/// List backups, newest first
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_backups(app: tauri::AppHandle) -> Result<Vec<BackupInfo>, AppError> {
+    Ok(backup::list(&app)?)
+}
+
+/// Verify a backup and restore the database and settings from it
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn restore_backup(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(backup::restore(&app, &id)?)
+}
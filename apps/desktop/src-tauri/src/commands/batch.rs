@@ -0,0 +1,10 @@
+use crate::batch::{self, Op, OpResult};
+use crate::error::AppError;
+
+/// Apply many task changes in one call and one local transaction; returns
+/// a result per op, in order
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn batch(app: tauri::AppHandle, ops: Vec<Op>) -> Result<Vec<OpResult>, AppError> {
+    Ok(batch::apply(&app, ops)?)
+}
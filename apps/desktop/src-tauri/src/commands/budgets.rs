@@ -0,0 +1,39 @@
+use chrono::NaiveDate;
+
+use crate::budgets::{self, BudgetStatus};
+use crate::db::budgets::Budget;
+use crate::error::AppError;
+use crate::weekly_review;
+
+/// Set a channel's weekly time budget in minutes; `None` or 0 removes it
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_channel_budget(
+    app: tauri::AppHandle,
+    channel: String,
+    weekly_minutes: Option<u32>,
+) -> Result<(), AppError> {
+    Ok(budgets::set(&app, &channel, weekly_minutes)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_channel_budgets(app: tauri::AppHandle) -> Result<Vec<Budget>, AppError> {
+    Ok(budgets::list(&app)?)
+}
+
+/// Budget usage in the week containing `date` (`YYYY-MM-DD`, default today)
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_budget_status(
+    app: tauri::AppHandle,
+    date: Option<String>,
+) -> Result<Vec<BudgetStatus>, AppError> {
+    let date = match date {
+        Some(date) => date
+            .parse::<NaiveDate>()
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        None => weekly_review::today(),
+    };
+    Ok(budgets::status(&app, weekly_review::week_start(date)).await?)
+}
@@ -0,0 +1,61 @@
+use crate::db::calendar_subscriptions::CalendarSubscription;
+use crate::error::AppError;
+use crate::integrations::calendar_feeds;
+
+/// Subscribe to an ICS calendar by URL and fetch it right away. The
+/// all-day events of a holiday calendar are days off.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn subscribe_calendar(
+    app: tauri::AppHandle,
+    name: String,
+    url: String,
+    refresh_minutes: Option<u32>,
+    holidays: Option<bool>,
+) -> Result<CalendarSubscription, AppError> {
+    Ok(calendar_feeds::subscribe(
+        &app,
+        &name,
+        &url,
+        refresh_minutes,
+        holidays.unwrap_or(false),
+    )
+    .await?)
+}
+
+/// Rename a calendar subscription or change how often it's refreshed
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn update_calendar_subscription(
+    app: tauri::AppHandle,
+    id: String,
+    name: String,
+    refresh_minutes: u32,
+) -> Result<CalendarSubscription, AppError> {
+    Ok(calendar_feeds::update(&app, &id, &name, refresh_minutes)?)
+}
+
+/// Unsubscribe from a calendar and remove its events
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn unsubscribe_calendar(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(calendar_feeds::unsubscribe(&app, &id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_calendar_subscriptions(
+    app: tauri::AppHandle,
+) -> Result<Vec<CalendarSubscription>, AppError> {
+    Ok(calendar_feeds::list(&app)?)
+}
+
+/// Fetch a subscribed calendar now instead of waiting for its next refresh
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn refresh_calendar_subscription(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<CalendarSubscription, AppError> {
+    Ok(calendar_feeds::refresh(&app, &id).await?)
+}
@@ -0,0 +1,30 @@
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::api::Api;
+use crate::clipboard_watch::ClipboardWatchState;
+use crate::error::{AppError, ErrorCode};
+
+/// Create a task from a clipboard capture suggestion
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn accept_clipboard_capture(
+    app: tauri::AppHandle,
+    state: State<'_, ClipboardWatchState>,
+    id: String,
+) -> Result<Value, AppError> {
+    let suggestion = state
+        .take(&id)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "This suggestion has expired"))?;
+
+    Ok(Api::new(&app)?
+        .post("/tasks", &json!({ "title": suggestion.title, "notes": suggestion.text }))
+        .await?)
+}
+
+/// Drop a clipboard capture suggestion
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn dismiss_clipboard_capture(state: State<'_, ClipboardWatchState>, id: String) {
+    state.take(&id);
+}
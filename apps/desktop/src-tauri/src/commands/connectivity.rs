@@ -0,0 +1,18 @@
+use tauri::State;
+
+use crate::connectivity::{self, ConnectivityState, ConnectivityStatus};
+use crate::error::AppError;
+
+/// Get the last known connectivity status
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_connectivity_status(state: State<'_, ConnectivityState>) -> ConnectivityStatus {
+    state.status()
+}
+
+/// Re-check backend reachability immediately
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn check_connectivity(app: tauri::AppHandle) -> Result<ConnectivityStatus, AppError> {
+    Ok(connectivity::check_now(&app).await)
+}
@@ -0,0 +1,15 @@
+use crate::context_menu::{self, ContextMenuItem, MenuPosition};
+use crate::error::AppError;
+
+/// Open a native context menu over the calling window. Resolves to the id of
+/// the chosen item, or `null` when the menu is dismissed.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn show_context_menu(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    items: Vec<ContextMenuItem>,
+    position: Option<MenuPosition>,
+) -> Result<Option<String>, AppError> {
+    Ok(context_menu::show(&app, &window, &items, position).await?)
+}
@@ -0,0 +1,29 @@
+use crate::crash::{self, CrashReport};
+use crate::error::AppError;
+
+/// Crash reports saved by earlier runs, newest first
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_crash_reports(app: tauri::AppHandle) -> Result<Vec<CrashReport>, AppError> {
+    Ok(crash::list(&app)?)
+}
+
+/// Send a crash report to the server and delete it
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn send_crash_report(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(crash::send(&app, &id).await?)
+}
+
+/// Open a GitHub issue pre-filled from a crash report
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn open_crash_issue(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(crash::open_issue(&app, &id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn dismiss_crash_report(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(crash::dismiss(&app, &id)?)
+}
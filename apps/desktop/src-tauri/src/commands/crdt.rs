@@ -0,0 +1,57 @@
+use crate::error::AppError;
+use crate::sync::crdt::{self, TextField};
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_task_text(app: tauri::AppHandle, task_id: String, field: TextField) -> Result<String, AppError> {
+    Ok(crdt::text(&app, &task_id, field)?)
+}
+
+/// Record the field's new text; returns the text after the edit. `base` is
+/// the server's plain value, used when the field is edited here first.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn edit_task_text(
+    app: tauri::AppHandle,
+    task_id: String,
+    field: TextField,
+    text: String,
+    base: Option<String>,
+) -> Result<String, AppError> {
+    Ok(crdt::edit(&app, &task_id, field, &text, base.as_deref())?)
+}
+
+/// Base64 state vector of the field's document
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_task_text_state_vector(
+    app: tauri::AppHandle,
+    task_id: String,
+    field: TextField,
+) -> Result<String, AppError> {
+    Ok(crdt::state_vector(&app, &task_id, field)?)
+}
+
+/// Base64 update with what a replica at `state_vector` is missing
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_task_text_update(
+    app: tauri::AppHandle,
+    task_id: String,
+    field: TextField,
+    state_vector: Option<String>,
+) -> Result<String, AppError> {
+    Ok(crdt::update_since(&app, &task_id, field, state_vector.as_deref())?)
+}
+
+/// Merge a base64 update from another replica; returns the merged text
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn apply_task_text_update(
+    app: tauri::AppHandle,
+    task_id: String,
+    field: TextField,
+    update: String,
+) -> Result<String, AppError> {
+    Ok(crdt::apply_update(&app, &task_id, field, &update)?)
+}
@@ -0,0 +1,18 @@
+use std::path::Path;
+
+use crate::data_dir::{self, DataDirInfo};
+use crate::error::AppError;
+
+/// Where data is kept and why
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_data_dir(app: tauri::AppHandle) -> DataDirInfo {
+    data_dir::info(&app)
+}
+
+/// Copy all data to `new_path` and restart using it
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn migrate_data_dir(app: tauri::AppHandle, new_path: String) -> Result<(), AppError> {
+    Ok(data_dir::migrate(&app, Path::new(&new_path))?)
+}
@@ -0,0 +1,29 @@
+use tauri::State;
+
+use crate::db::{self, Database};
+use crate::error::AppError;
+use crate::settings;
+
+/// Turn SQLCipher encryption of the local database on or off. The existing
+/// database is converted in place.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_local_encryption(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let mut current = settings::load(&app)?;
+    if current.security.encrypt_local_data == enabled {
+        return Ok(());
+    }
+
+    if enabled {
+        db::enable_encryption(&db)?;
+    } else {
+        db::disable_encryption(&db)?;
+    }
+
+    current.security.encrypt_local_data = enabled;
+    Ok(settings::save(&app, &current)?)
+}
@@ -0,0 +1,20 @@
+use crate::demo::{self, DemoSummary};
+use crate::error::AppError;
+
+/// Fill a profile (default: the active one) with demo tasks, tracked time
+/// and calendar events, replacing earlier demo data
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn seed_demo_data(
+    app: tauri::AppHandle,
+    profile: Option<String>,
+) -> Result<DemoSummary, AppError> {
+    Ok(demo::seed(&app, profile.as_deref())?)
+}
+
+/// Remove the demo data from a profile (default: the active one)
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn clear_demo_data(app: tauri::AppHandle, profile: Option<String>) -> Result<(), AppError> {
+    Ok(demo::clear(&app, profile.as_deref())?)
+}
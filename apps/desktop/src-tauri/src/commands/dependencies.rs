@@ -0,0 +1,53 @@
+use chrono::NaiveDate;
+
+use crate::dependencies::{self, BlockedTask, TaskDependencies};
+use crate::error::AppError;
+use crate::weekly_review;
+
+/// What a task waits on and what waits on it
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_task_dependencies(
+    app: tauri::AppHandle,
+    task_id: String,
+) -> Result<TaskDependencies, AppError> {
+    Ok(dependencies::get(&app, &task_id)?)
+}
+
+/// Make `blocker_id` block `blocked_id`; refused if it would make a cycle
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn add_task_dependency(
+    app: tauri::AppHandle,
+    blocker_id: String,
+    blocked_id: String,
+) -> Result<(), AppError> {
+    Ok(dependencies::add(&app, &blocker_id, &blocked_id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn remove_task_dependency(
+    app: tauri::AppHandle,
+    blocker_id: String,
+    blocked_id: String,
+) -> Result<(), AppError> {
+    Ok(dependencies::remove(&app, &blocker_id, &blocked_id)?)
+}
+
+/// Unfinished tasks on `date` (`YYYY-MM-DD`, default today) still waiting
+/// on unfinished ones
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_blocked_tasks(
+    app: tauri::AppHandle,
+    date: Option<String>,
+) -> Result<Vec<BlockedTask>, AppError> {
+    let date = match date {
+        Some(date) => date
+            .parse::<NaiveDate>()
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        None => weekly_review::today(),
+    };
+    Ok(dependencies::blocked_tasks(&app, date).await?)
+}
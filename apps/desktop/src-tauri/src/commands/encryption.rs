@@ -0,0 +1,65 @@
+use crate::crypto::{self, EncryptionStatus};
+use crate::error::AppError;
+
+/// Whether end-to-end encryption is set up and unlocked on this device
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_encryption_status(app: tauri::AppHandle) -> Result<EncryptionStatus, AppError> {
+    Ok(crypto::status(&app)?)
+}
+
+/// Enable end-to-end encryption. Returns the recovery code to show the user once.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn setup_encryption(app: tauri::AppHandle, passphrase: String) -> Result<String, AppError> {
+    Ok(crypto::setup(&app, &passphrase).await?)
+}
+
+/// Unlock encryption on this device with the passphrase
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn unlock_encryption(app: tauri::AppHandle, passphrase: String) -> Result<(), AppError> {
+    Ok(crypto::unlock(&app, &passphrase)?)
+}
+
+/// Rotate to a new data key. Returns the new key id.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn rotate_encryption_key(app: tauri::AppHandle, passphrase: String) -> Result<String, AppError> {
+    Ok(crypto::rotate(&app, &passphrase)?)
+}
+
+/// Regain access with the recovery code and set a new passphrase. Returns a
+/// new recovery code.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn recover_encryption(
+    app: tauri::AppHandle,
+    recovery_code: String,
+    new_passphrase: String,
+) -> Result<String, AppError> {
+    Ok(crypto::recover(&app, &recovery_code, &new_passphrase)?)
+}
+
+/// Issue a new recovery code, invalidating the old one
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn regenerate_recovery_code(app: tauri::AppHandle, passphrase: String) -> Result<String, AppError> {
+    Ok(crypto::regenerate_recovery_code(&app, &passphrase)?)
+}
+
+/// Seal a task's notes or description before the webview sends it to the
+/// server; requests made natively are sealed for it
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn encrypt_payload(app: tauri::AppHandle, plaintext: String) -> Result<String, AppError> {
+    Ok(crypto::encrypt(&app, plaintext.as_bytes())?)
+}
+
+/// Decrypt a payload received from the server
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn decrypt_payload(app: tauri::AppHandle, payload: String) -> Result<String, AppError> {
+    let plaintext = crypto::decrypt(&app, &payload)?;
+    String::from_utf8(plaintext).map_err(|_| "Decrypted payload is not text".into())
+}
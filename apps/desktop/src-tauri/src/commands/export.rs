@@ -0,0 +1,23 @@
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::export::{self, ConflictStrategy, ExportSummary, ImportSummary};
+
+/// Export tasks, notes, subtasks, time blocks, attachments and settings to a zip
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn export_all_data(app: tauri::AppHandle, path: String) -> Result<ExportSummary, AppError> {
+    Ok(export::export_all(&app, Path::new(&path)).await?)
+}
+
+/// Import an export archive into the signed-in account
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn import_archive(
+    app: tauri::AppHandle,
+    path: String,
+    conflict: ConflictStrategy,
+    include_settings: Option<bool>,
+) -> Result<ImportSummary, AppError> {
+    Ok(export::import(&app, Path::new(&path), conflict, include_settings.unwrap_or(true)).await?)
+}
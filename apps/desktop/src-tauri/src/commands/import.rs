@@ -0,0 +1,54 @@
+use chrono::NaiveDate;
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::importers::sunsama::{self, SunsamaImportReport};
+use crate::importers::toggl::{self, TogglImportReport};
+
+/// Parse a Sunsama export and report what importing it would create
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn preview_sunsama_import(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<SunsamaImportReport, AppError> {
+    Ok(sunsama::import(&app, Path::new(&path), true).await?)
+}
+
+/// Import a Sunsama export into the signed-in account
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn import_sunsama(app: tauri::AppHandle, path: String) -> Result<SunsamaImportReport, AppError> {
+    Ok(sunsama::import(&app, Path::new(&path), false).await?)
+}
+
+/// Fetch Toggl Track history for a date range (`YYYY-MM-DD`, inclusive) and
+/// report what importing it would store
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn preview_toggl_import(
+    app: tauri::AppHandle,
+    start: String,
+    end: String,
+    workspace_id: Option<u64>,
+) -> Result<TogglImportReport, AppError> {
+    Ok(toggl::import(&app, parse_day(&start)?, parse_day(&end)?, workspace_id, true).await?)
+}
+
+/// Import Toggl Track projects and time entries for a date range into the
+/// local database
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn import_toggl(
+    app: tauri::AppHandle,
+    start: String,
+    end: String,
+    workspace_id: Option<u64>,
+) -> Result<TogglImportReport, AppError> {
+    Ok(toggl::import(&app, parse_day(&start)?, parse_day(&end)?, workspace_id, false).await?)
+}
+
+fn parse_day(date: &str) -> Result<NaiveDate, String> {
+    date.parse()
+        .map_err(|e| format!("Invalid date '{}': {}", date, e))
+}
@@ -0,0 +1,40 @@
+use crate::db::time_exports::TimeExport;
+use crate::error::AppError;
+use crate::integrations;
+use crate::integrations::time_export::{self, ExportSummary};
+
+/// Save (or with `None`, forget) the API token of an integration
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_integration_token(service: String, token: Option<String>) -> Result<(), AppError> {
+    Ok(integrations::set_token(&service, token.as_deref())?)
+}
+
+/// Whether an API token is saved for an integration; the token itself never
+/// leaves the keychain
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn has_integration_token(service: String) -> Result<bool, AppError> {
+    Ok(integrations::token(&service)?.is_some())
+}
+
+/// Push finished time entries to the configured time tracker now
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn export_time_entries(app: tauri::AppHandle) -> Result<ExportSummary, AppError> {
+    Ok(time_export::run(&app).await?)
+}
+
+/// Time entries the exporter gave up on
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_failed_time_exports(app: tauri::AppHandle) -> Result<Vec<TimeExport>, AppError> {
+    Ok(time_export::list_failed(&app)?)
+}
+
+/// Try failed time entry exports again on the next run
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn retry_failed_time_exports(app: tauri::AppHandle) -> Result<usize, AppError> {
+    Ok(time_export::retry_failed(&app)?)
+}
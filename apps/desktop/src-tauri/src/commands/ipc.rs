@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+use std::path::Path;
+use tauri::ipc::Response;
+
+use crate::analytics;
+use crate::db::tasks::{TaskFilter, TaskSort};
+use crate::error::AppError;
+use crate::importers::sunsama;
+use crate::ipc::{self, Encoding, IpcFeatures};
+use crate::sync::task_cache;
+
+/// Encodings and binary commands this build supports
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_ipc_features() -> IpcFeatures {
+    ipc::features()
+}
+
+/// `query_tasks`, answered as raw bytes
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn query_tasks_packed(
+    app: tauri::AppHandle,
+    filter: Option<TaskFilter>,
+    sort: Option<TaskSort>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+    encoding: Option<Encoding>,
+) -> Result<Response, AppError> {
+    let page = task_cache::query(
+        &app,
+        &filter.unwrap_or_default(),
+        sort.unwrap_or_default(),
+        cursor.as_deref(),
+        limit,
+    )?;
+    Ok(ipc::encode(&page, encoding)?)
+}
+
+/// Planned, finished and tracked work over `days` days from `start`
+/// (`YYYY-MM-DD`), answered as raw bytes
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_period_summary_packed(
+    app: tauri::AppHandle,
+    start: String,
+    days: u64,
+    encoding: Option<Encoding>,
+) -> Result<Response, AppError> {
+    let start = start
+        .parse::<NaiveDate>()
+        .map_err(|e| format!("Invalid date '{}': {}", start, e))?;
+    let summary = analytics::summarize(&app, start, days).await?;
+    Ok(ipc::encode(&summary, encoding)?)
+}
+
+/// `preview_sunsama_import`, answered as raw bytes
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn preview_sunsama_import_packed(
+    app: tauri::AppHandle,
+    path: String,
+    encoding: Option<Encoding>,
+) -> Result<Response, AppError> {
+    let report = sunsama::import(&app, Path::new(&path), true).await?;
+    Ok(ipc::encode(&report, encoding)?)
+}
@@ -0,0 +1,50 @@
+use crate::error::AppError;
+use crate::lan_sync::{self, LanDevice, LanSyncStatus, MergeSummary, PairedDevice, PairingInfo};
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_lan_sync_status(app: tauri::AppHandle) -> Result<LanSyncStatus, AppError> {
+    Ok(lan_sync::status(&app)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_lan_devices(app: tauri::AppHandle) -> Result<Vec<LanDevice>, AppError> {
+    Ok(lan_sync::devices(&app)?)
+}
+
+/// Show a pairing code (and QR code) for another device to enter
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn start_lan_pairing(app: tauri::AppHandle) -> Result<PairingInfo, AppError> {
+    Ok(lan_sync::start_pairing(&app)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn cancel_lan_pairing(app: tauri::AppHandle) {
+    lan_sync::cancel_pairing(&app);
+}
+
+/// Pair with a device on the network using the code it shows
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn pair_lan_device(
+    app: tauri::AppHandle,
+    device_id: String,
+    code: String,
+) -> Result<PairedDevice, AppError> {
+    Ok(lan_sync::pair(&app, &device_id, &code).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn unpair_lan_device(app: tauri::AppHandle, device_id: String) -> Result<(), AppError> {
+    Ok(lan_sync::unpair(&app, &device_id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn sync_lan_device(app: tauri::AppHandle, device_id: String) -> Result<MergeSummary, AppError> {
+    Ok(lan_sync::sync_with(&app, &device_id).await?)
+}
@@ -0,0 +1,17 @@
+use crate::error::AppError;
+use crate::meetings;
+use crate::timer::TimerStatus;
+
+/// Open the video call link of a meeting in the local schedule
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn join_meeting(app: tauri::AppHandle, block_id: String) -> Result<(), AppError> {
+    Ok(meetings::join(&app, &block_id)?)
+}
+
+/// Start timing the task linked to a meeting
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn track_meeting(app: tauri::AppHandle, block_id: String) -> Result<TimerStatus, AppError> {
+    Ok(meetings::track(&app, &block_id)?)
+}
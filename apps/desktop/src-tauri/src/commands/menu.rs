@@ -0,0 +1,9 @@
+use crate::error::AppError;
+use crate::menu::{self, MenuItemState};
+
+/// Enable, disable or relabel menu and tray items by id
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_menu_state(app: tauri::AppHandle, items: Vec<MenuItemState>) -> Result<(), AppError> {
+    Ok(menu::set_state(&app, &items)?)
+}
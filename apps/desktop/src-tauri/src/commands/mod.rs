@@ -1,11 +1,112 @@
+mod app_lock;
+mod attachments;
+mod auth;
+mod backup;
+mod batch;
+mod budgets;
+mod calendars;
+mod clipboard;
+mod connectivity;
+mod context_menu;
+mod crash;
+mod crdt;
+mod data_dir;
+mod database;
+mod demo;
+mod dependencies;
+mod encryption;
+mod export;
+mod import;
+mod integrations;
+mod ipc;
+mod lan_sync;
+mod meetings;
+mod menu;
 mod notifications;
+mod objectives;
+mod perf;
+mod power;
+mod profiles;
+mod quick_complete;
+mod recent;
+mod review;
+mod schedule;
+mod screenshot;
+mod server;
 mod settings;
+mod share;
+mod shortcuts;
+mod speech;
+mod startup;
+mod subtasks;
+mod sync;
+mod tasks;
+mod templates;
+mod theme;
+mod timer;
+mod timezone;
+mod transfers;
+mod updates;
+mod views;
+mod window;
+mod window_effects;
 
+pub use app_lock::*;
+pub use attachments::*;
+pub use auth::*;
+pub use backup::*;
+pub use batch::*;
+pub use budgets::*;
+pub use calendars::*;
+pub use clipboard::*;
+pub use connectivity::*;
+pub use context_menu::*;
+pub use crash::*;
+pub use crdt::*;
+pub use data_dir::*;
+pub use database::*;
+pub use demo::*;
+pub use dependencies::*;
+pub use encryption::*;
+pub use export::*;
+pub use import::*;
+pub use integrations::*;
+pub use ipc::*;
+pub use lan_sync::*;
+pub use meetings::*;
+pub use menu::*;
 pub use notifications::*;
+pub use objectives::*;
+pub use perf::*;
+pub use power::*;
+pub use profiles::*;
+pub use quick_complete::*;
+pub use recent::*;
+pub use review::*;
+pub use schedule::*;
+pub use screenshot::*;
+pub use server::*;
 pub use settings::*;
+pub use share::*;
+pub use shortcuts::*;
+pub use speech::*;
+pub use startup::*;
+pub use subtasks::*;
+pub use sync::*;
+pub use tasks::*;
+pub use templates::*;
+pub use theme::*;
+pub use timer::*;
+pub use timezone::*;
+pub use transfers::*;
+pub use updates::*;
+pub use views::*;
+pub use window::*;
+pub use window_effects::*;
 
 /// Check if running in desktop environment
 #[tauri::command]
+#[tracing::instrument(skip_all)]
 pub fn is_desktop() -> bool {
     true
 }
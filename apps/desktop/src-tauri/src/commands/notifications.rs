@@ -1,5 +1,14 @@
 use serde::{Deserialize, Serialize};
-use tauri_plugin_notification::NotificationExt;
+use std::path::Path;
+use tauri::Manager;
+
+use crate::db::snoozed::Snoozed;
+use crate::dnd::{DndState, DndStatus};
+use crate::error::AppError;
+use crate::notifications::{self, Category, Notice};
+use crate::settings;
+use crate::snooze::{self, SnoozeRequest};
+use crate::sounds::{self, SoundOption};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NotificationOptions {
@@ -7,23 +16,92 @@ pub struct NotificationOptions {
     pub body: Option<String>,
     #[serde(rename = "actionTypeId")]
     pub action_type_id: Option<String>,
+    /// Decides whether quiet hours apply; defaults to a reminder
+    #[serde(default)]
+    pub category: Category,
+    /// Overrides the category's sound: "default", "none" or a sound name
+    pub sound: Option<String>,
+    #[serde(rename = "taskId")]
+    pub task_id: Option<String>,
+    /// Handed back when the notification is clicked, even after being held
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationStatus {
+    pub system_dnd: DndStatus,
+    /// Non-urgent notifications are being held for the system DND mode
+    pub muted_by_system: bool,
+    pub quiet_hours: bool,
+    /// Notifications waiting for quiet hours or DND to end
+    pub held: usize,
 }
 
-/// Show a native notification
+/// Show a native notification, subject to the notification settings
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub fn show_notification(
     app: tauri::AppHandle,
     options: NotificationOptions,
-) -> Result<(), String> {
-    let mut notification = app.notification().builder();
-    
-    notification = notification.title(&options.title);
-    
-    if let Some(body) = &options.body {
-        notification = notification.body(body);
-    }
-
-    notification
-        .show()
-        .map_err(|e| format!("Failed to show notification: {}", e))
+) -> Result<(), AppError> {
+    let mut notice = Notice::new(options.category, options.title);
+    notice.body = options.body;
+    notice.sound = options.sound;
+    notice.task_id = options.task_id;
+    notice.data = options.data;
+
+    Ok(notifications::notify(&app, notice)?)
+}
+
+/// Sounds that can be chosen for notifications on this platform
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_notification_sounds(app: tauri::AppHandle) -> Result<Vec<SoundOption>, AppError> {
+    Ok(sounds::list(&app)?)
+}
+
+/// Import a custom notification sound. Returns its name for the settings.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn import_notification_sound(app: tauri::AppHandle, path: String) -> Result<String, AppError> {
+    Ok(sounds::import(&app, Path::new(&path))?)
+}
+
+/// Whether notifications are being held, and why
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_notification_status(app: tauri::AppHandle) -> Result<NotificationStatus, AppError> {
+    let config = settings::load(&app)?.notifications;
+    Ok(NotificationStatus {
+        system_dnd: app.state::<DndState>().status(),
+        muted_by_system: notifications::system_dnd(&app, &config),
+        quiet_hours: notifications::is_quiet(&config),
+        held: notifications::held_count(&app)?,
+    })
+}
+
+/// Put a notification off; it's delivered again at the new time, even after
+/// a restart
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn snooze_notification(
+    app: tauri::AppHandle,
+    request: SnoozeRequest,
+) -> Result<Snoozed, AppError> {
+    Ok(snooze::snooze(&app, request)?)
+}
+
+/// Snoozed notifications waiting to be delivered, soonest first
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_snoozed(app: tauri::AppHandle) -> Result<Vec<Snoozed>, AppError> {
+    Ok(snooze::list(&app)?)
+}
+
+/// Drop a snoozed notification
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn cancel_snoozed(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(snooze::cancel(&app, &id)?)
 }
@@ -0,0 +1,78 @@
+use chrono::NaiveDate;
+
+use crate::db::objectives::Objective;
+use crate::error::AppError;
+use crate::objectives::{self, ObjectiveProgress};
+use crate::weekly_review;
+
+/// Objectives of the week containing `date` (`YYYY-MM-DD`, default today)
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_objectives(
+    app: tauri::AppHandle,
+    date: Option<String>,
+) -> Result<Vec<Objective>, AppError> {
+    Ok(objectives::list(&app, parse_date(date)?)?)
+}
+
+/// Create (with an empty id) or update an objective and its linked tasks
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn save_objective(app: tauri::AppHandle, objective: Objective) -> Result<Objective, AppError> {
+    Ok(objectives::save(&app, objective)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn delete_objective(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(objectives::delete(&app, &id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn link_task_to_objective(
+    app: tauri::AppHandle,
+    objective_id: String,
+    task_id: String,
+) -> Result<(), AppError> {
+    Ok(objectives::set_task_link(
+        &app,
+        &objective_id,
+        &task_id,
+        true,
+    )?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn unlink_task_from_objective(
+    app: tauri::AppHandle,
+    objective_id: String,
+    task_id: String,
+) -> Result<(), AppError> {
+    Ok(objectives::set_task_link(
+        &app,
+        &objective_id,
+        &task_id,
+        false,
+    )?)
+}
+
+/// Time tracked and tasks done per objective in the week containing `date`
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_objective_progress(
+    app: tauri::AppHandle,
+    date: Option<String>,
+) -> Result<Vec<ObjectiveProgress>, AppError> {
+    Ok(objectives::progress(&app, weekly_review::week_start(parse_date(date)?)).await?)
+}
+
+fn parse_date(date: Option<String>) -> Result<NaiveDate, String> {
+    match date {
+        Some(date) => date
+            .parse()
+            .map_err(|e| format!("Invalid date '{}': {}", date, e)),
+        None => Ok(weekly_review::today()),
+    }
+}
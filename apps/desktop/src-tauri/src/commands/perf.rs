@@ -0,0 +1,14 @@
+use crate::perf::{self, PerfMetrics};
+
+/// Call counts, errors and latencies of every command run so far
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_perf_metrics() -> PerfMetrics {
+    perf::metrics()
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn reset_perf_metrics() {
+    perf::reset();
+}
@@ -0,0 +1,21 @@
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::power::{self, Budget, PowerState, PowerStatus};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerInfo {
+    pub status: PowerStatus,
+    pub budget: Budget,
+}
+
+/// Battery and connection cost, and how background sync is adapting
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_power_status(app: tauri::AppHandle) -> PowerInfo {
+    PowerInfo {
+        status: app.state::<PowerState>().status(),
+        budget: power::budget(&app),
+    }
+}
@@ -0,0 +1,22 @@
+use crate::error::AppError;
+use crate::profiles::{self, Profile, ProfileList};
+
+/// All profiles, with the one this run uses as `active`
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_profiles(app: tauri::AppHandle) -> Result<ProfileList, AppError> {
+    Ok(profiles::list(&app)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn create_profile(app: tauri::AppHandle, name: String) -> Result<Profile, AppError> {
+    Ok(profiles::create(&app, &name)?)
+}
+
+/// Restart into another profile
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn switch_profile(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(profiles::switch(&app, &id)?)
+}
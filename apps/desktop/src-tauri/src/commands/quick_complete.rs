@@ -0,0 +1,16 @@
+use crate::error::AppError;
+use crate::quick_complete::{self, PendingCompletion};
+
+/// Complete the timed or focused task; undoable for a few seconds
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn complete_current_task(app: tauri::AppHandle) -> Result<PendingCompletion, AppError> {
+    Ok(quick_complete::complete_current(&app)?)
+}
+
+/// Undo the last completion. Returns false if it had already been sent.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn undo_complete_task(app: tauri::AppHandle) -> Result<bool, AppError> {
+    Ok(quick_complete::undo(&app)?)
+}
@@ -0,0 +1,23 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::recent_tasks::{self, RecentTask, RecentTasksState};
+
+/// Record that a task was opened or edited, for the Open Recent menus
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn touch_recent_task(app: tauri::AppHandle, id: String, title: String) -> Result<(), AppError> {
+    Ok(recent_tasks::touch(&app, &id, &title)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn remove_recent_task(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(recent_tasks::remove(&app, &id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn list_recent_tasks(state: State<'_, RecentTasksState>) -> Vec<RecentTask> {
+    state.list()
+}
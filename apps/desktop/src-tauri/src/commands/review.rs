@@ -0,0 +1,21 @@
+use chrono::NaiveDate;
+
+use crate::error::AppError;
+use crate::weekly_review::{self, WeeklyReview};
+
+/// Generate the weekly review for the week containing `date` (`YYYY-MM-DD`,
+/// default today) and write its Markdown and PDF reports
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn generate_weekly_review(
+    app: tauri::AppHandle,
+    date: Option<String>,
+) -> Result<WeeklyReview, AppError> {
+    let date = match date {
+        Some(date) => date
+            .parse::<NaiveDate>()
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        None => weekly_review::today(),
+    };
+    Ok(weekly_review::generate(&app, weekly_review::week_start(date)).await?)
+}
@@ -0,0 +1,84 @@
+use tauri::State;
+
+use crate::auto_schedule;
+use crate::clock;
+use crate::db::reminders::{self, Reminder};
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::free_busy::{self, Constraints, FreeBusy};
+
+/// Save a time block to the local schedule
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn upsert_time_block(db: State<'_, Database>, block: TimeBlock) -> Result<(), AppError> {
+    Ok(db.with_conn(|conn| time_blocks::upsert(conn, &block))?)
+}
+
+/// Remove a time block from the local schedule
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn delete_time_block(db: State<'_, Database>, id: String) -> Result<(), AppError> {
+    Ok(db.with_conn(|conn| time_blocks::delete(conn, &id))?)
+}
+
+/// List time blocks overlapping a range of Unix milliseconds
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_time_blocks(
+    db: State<'_, Database>,
+    start: i64,
+    end: i64,
+) -> Result<Vec<TimeBlock>, AppError> {
+    Ok(db.with_conn(|conn| time_blocks::list_between(conn, start, end))?)
+}
+
+/// Move a time block to the next free slot in working hours
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn reschedule_to_free_slot(app: tauri::AppHandle, id: String) -> Result<TimeBlock, AppError> {
+    Ok(auto_schedule::reschedule(&app, &id)?)
+}
+
+/// Push the rest of today's task blocks past `from_time` (Unix ms, default
+/// now), e.g. after a meeting ran over. Returns the blocks that moved.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn reflow_day(
+    app: tauri::AppHandle,
+    from_time: Option<i64>,
+) -> Result<Vec<TimeBlock>, AppError> {
+    Ok(auto_schedule::reflow_day(&app, from_time.unwrap_or_else(clock::now_millis))?)
+}
+
+/// Busy time and free slots in working hours on a day (`YYYY-MM-DD`)
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_free_busy(
+    app: tauri::AppHandle,
+    date: String,
+    constraints: Option<Constraints>,
+) -> Result<FreeBusy, AppError> {
+    Ok(free_busy::get(&app, &date, &constraints.unwrap_or_default())?)
+}
+
+/// Schedule (or reschedule) a native reminder notification
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn schedule_reminder(db: State<'_, Database>, reminder: Reminder) -> Result<(), AppError> {
+    Ok(db.with_conn(|conn| reminders::upsert(conn, &reminder))?)
+}
+
+/// Cancel a scheduled reminder
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn cancel_reminder(db: State<'_, Database>, id: String) -> Result<(), AppError> {
+    Ok(db.with_conn(|conn| reminders::delete(conn, &id))?)
+}
+
+/// List reminders that have not fired yet
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_reminders(db: State<'_, Database>) -> Result<Vec<Reminder>, AppError> {
+    Ok(db.with_conn(|conn| reminders::list_pending(conn))?)
+}
@@ -0,0 +1,14 @@
+use crate::error::AppError;
+use crate::screenshot::{self, CaptureMode};
+
+/// Capture the screen, a window or a region into the attachment store and
+/// return the new attachment's id
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn capture_screenshot(
+    app: tauri::AppHandle,
+    mode: CaptureMode,
+    task_id: Option<String>,
+) -> Result<String, AppError> {
+    Ok(screenshot::capture(&app, mode, task_id).await?.id)
+}
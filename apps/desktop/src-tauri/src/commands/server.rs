@@ -0,0 +1,40 @@
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::connectivity;
+use crate::error::AppError;
+use crate::server::{self, ServerInfo, ServerState, DEFAULT_SERVER_URL};
+use crate::settings;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerChanged {
+    url: String,
+}
+
+/// Get the backend URL in use
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_server_url(app: tauri::AppHandle) -> String {
+    server::current_url(&app)
+}
+
+/// Point the app at a different (self-hosted) backend. The server must pass
+/// a health and version check before it is saved.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn set_server_url(app: tauri::AppHandle, url: String) -> Result<ServerInfo, AppError> {
+    let url = server::normalize_url(&url)?;
+    let info = server::check_health(&app, &url).await?;
+
+    let mut current = settings::load(&app)?;
+    current.sync.server_url = (url != DEFAULT_SERVER_URL).then(|| url.clone());
+    settings::save(&app, &current)?;
+
+    app.state::<ServerState>().set_url(url.clone());
+    let _ = app.emit("server-changed", ServerChanged { url });
+
+    connectivity::check_now(&app).await;
+
+    Ok(info)
+}
@@ -1,71 +1,123 @@
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::Path;
 use tauri_plugin_autostart::ManagerExt;
-use tauri_plugin_store::StoreExt;
 
-#[derive(Debug, Serialize, Deserialize, Default)]
-pub struct AppSettings {
-    #[serde(default)]
-    pub theme: String,
-    #[serde(default)]
-    pub auto_launch: bool,
-    #[serde(default)]
-    pub minimize_to_tray: bool,
-    #[serde(default)]
-    pub global_shortcuts_enabled: bool,
-}
+use crate::error::AppError;
+use crate::http::{self, PluginProxyConfig};
+use crate::settings::transfer;
+use crate::settings::{self, AppSettings, SettingChange, SettingsSection};
 
 /// Get auto-launch status
 #[tauri::command]
-pub fn get_auto_launch(app: tauri::AppHandle) -> Result<bool, String> {
+#[tracing::instrument(skip_all, err)]
+pub fn get_auto_launch(app: tauri::AppHandle) -> Result<bool, AppError> {
     let autostart = app.autolaunch();
     autostart
         .is_enabled()
-        .map_err(|e| format!("Failed to get auto-launch status: {}", e))
+        .map_err(|e| format!("Failed to get auto-launch status: {}", e).into())
 }
 
 /// Set auto-launch status
 #[tauri::command]
-pub fn set_auto_launch(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+#[tracing::instrument(skip_all, err)]
+pub fn set_auto_launch(app: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
     let autostart = app.autolaunch();
     if enabled {
         autostart
             .enable()
-            .map_err(|e| format!("Failed to enable auto-launch: {}", e))
+            .map_err(|e| format!("Failed to enable auto-launch: {}", e).into())
     } else {
         autostart
             .disable()
-            .map_err(|e| format!("Failed to disable auto-launch: {}", e))
+            .map_err(|e| format!("Failed to disable auto-launch: {}", e).into())
     }
 }
 
 /// Get app settings from store
 #[tauri::command]
-pub fn get_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
-    let store = app
-        .store("settings.json")
-        .map_err(|e| format!("Failed to open store: {}", e))?;
+#[tracing::instrument(skip_all, err)]
+pub fn get_settings(app: tauri::AppHandle) -> Result<AppSettings, AppError> {
+    Ok(settings::load(&app)?)
+}
 
-    let settings: AppSettings = match store.get("settings") {
-        Some(value) => serde_json::from_value(value.clone()).unwrap_or_default(),
-        None => AppSettings::default(),
-    };
+/// Save app settings to store
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_settings(app: tauri::AppHandle, mut settings: AppSettings) -> Result<(), AppError> {
+    settings.preserve_managed(&settings::load(&app)?);
+    settings::save(&app, &settings)?;
+    Ok(settings::apply(&app, &settings)?)
+}
 
-    Ok(settings)
+/// Get a single settings section
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_settings_section(
+    app: tauri::AppHandle,
+    section: SettingsSection,
+) -> Result<Value, AppError> {
+    Ok(settings::load(&app)?.section_value(section)?)
 }
 
-/// Save app settings to store
+/// Update some fields of a settings section, leaving the rest untouched.
+/// Returns the section as saved.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn update_settings_section(
+    app: tauri::AppHandle,
+    section: SettingsSection,
+    patch: Value,
+) -> Result<Value, AppError> {
+    let mut current = settings::load(&app)?;
+    let before = current.clone();
+    current.apply_patch(section, patch)?;
+    current.preserve_managed(&before);
+    settings::save(&app, &current)?;
+    settings::apply(&app, &current)?;
+
+    Ok(current.section_value(section)?)
+}
+
+/// Export settings (without secrets) to a JSON file
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn export_settings(app: tauri::AppHandle, path: String) -> Result<(), AppError> {
+    let current = settings::load(&app)?;
+    Ok(transfer::export_to_file(&current, Path::new(&path))?)
+}
+
+/// Validate a settings file and list what importing it would change
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn preview_settings_import(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Vec<SettingChange>, AppError> {
+    let current = settings::load(&app)?;
+    let imported = transfer::read_import(&current, Path::new(&path))?;
+    Ok(current.diff(&imported)?)
+}
+
+/// Import settings from a JSON file. Returns the fields that changed.
 #[tauri::command]
-pub fn set_settings(app: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
-    let store = app
-        .store("settings.json")
-        .map_err(|e| format!("Failed to open store: {}", e))?;
+#[tracing::instrument(skip_all, err)]
+pub fn import_settings(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Vec<SettingChange>, AppError> {
+    let current = settings::load(&app)?;
+    let imported = transfer::read_import(&current, Path::new(&path))?;
+    let changes = current.diff(&imported)?;
 
-    let value: Value =
-        serde_json::to_value(&settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    settings::save(&app, &imported)?;
+    settings::apply(&app, &imported)?;
 
-    store.set("settings", value);
-    store
-        .save()
-        .map_err(|e| format!("Failed to save settings: {}", e))
+    Ok(changes)
+}
+
+/// Proxy configuration to pass to the HTTP plugin's `fetch`, if any
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_proxy_config(app: tauri::AppHandle) -> Result<Option<PluginProxyConfig>, AppError> {
+    Ok(http::plugin_proxy_config(&settings::load(&app)?.network))
 }
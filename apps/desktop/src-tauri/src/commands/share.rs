@@ -0,0 +1,16 @@
+use crate::error::AppError;
+use crate::share::{self, ShareContent};
+
+/// Share text or a file through the OS share sheet
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn share_content(
+    window: tauri::WebviewWindow,
+    content: ShareContent,
+    title: Option<String>,
+) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || share::share(&window, content, title))
+        .await
+        .map_err(|e| format!("Failed to share: {}", e))??;
+    Ok(())
+}
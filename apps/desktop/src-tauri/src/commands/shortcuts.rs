@@ -0,0 +1,40 @@
+use tauri::State;
+
+use crate::error::AppError;
+use crate::shortcuts::capture::{self, ShortcutCheck};
+use crate::shortcuts::{ShortcutState, ShortcutSupportStatus};
+
+/// Which global shortcut backend is active and which shortcuts registered
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_shortcut_support_status(state: State<'_, ShortcutState>) -> Result<ShortcutSupportStatus, AppError> {
+    state
+        .status()
+        .ok_or_else(|| "Failed to read shortcut status".into())
+}
+
+/// Check an accelerator for conflicts before binding it. `id` is the
+/// shortcut being edited, whose current binding doesn't count as a conflict.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn try_register_shortcut(
+    app: tauri::AppHandle,
+    accelerator: String,
+    id: Option<String>,
+) -> Result<ShortcutCheck, AppError> {
+    Ok(capture::check(&app, &accelerator, id.as_deref())?)
+}
+
+/// Record the next key combination pressed. Resolves to the accelerator, or
+/// `null` if the user pressed Escape or nothing was pressed in time.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn begin_shortcut_capture(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    Ok(capture::capture(&app).await?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn cancel_shortcut_capture(app: tauri::AppHandle) {
+    capture::cancel(&app);
+}
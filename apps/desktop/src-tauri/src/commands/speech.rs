@@ -0,0 +1,33 @@
+use chrono::{Local, NaiveDate};
+use tauri::State;
+
+use crate::daily_plan;
+use crate::error::AppError;
+use crate::speech::SpeechState;
+
+/// Read the plan for `date` (YYYY-MM-DD, default today) aloud and return
+/// the text being spoken
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn speak_daily_plan(
+    app: tauri::AppHandle,
+    speech: State<'_, SpeechState>,
+    date: Option<String>,
+) -> Result<String, AppError> {
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        None => Local::now().date_naive(),
+    };
+
+    let text = daily_plan::describe(&app, date).await?;
+    speech.speak(text.clone())?;
+    Ok(text)
+}
+
+/// Stop any read-out in progress
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn stop_speaking(speech: State<'_, SpeechState>) -> Result<(), AppError> {
+    Ok(speech.stop()?)
+}
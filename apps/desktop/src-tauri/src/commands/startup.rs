@@ -0,0 +1,9 @@
+use crate::args::{self, StartupAction};
+
+/// Actions requested by the launch arguments, for the webview to run once
+/// it's ready. Returns them only once.
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn take_startup_actions(app: tauri::AppHandle) -> Vec<StartupAction> {
+    args::take_pending(&app)
+}
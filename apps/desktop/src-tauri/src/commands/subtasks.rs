@@ -0,0 +1,58 @@
+use crate::error::AppError;
+use crate::subtasks::{self, SubtaskChanges, SubtaskList};
+
+/// A task's subtasks in order, with their estimate total. Takes the
+/// server's list first, falling back to the local one while offline.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn list_subtasks(
+    app: tauri::AppHandle,
+    task_id: String,
+) -> Result<SubtaskList, AppError> {
+    match subtasks::refresh(&app, &task_id).await {
+        Ok(list) => Ok(list),
+        Err(e) => {
+            tracing::warn!("Failed to fetch subtasks: {}", e);
+            Ok(subtasks::list(&app, &task_id)?)
+        }
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn add_subtask(
+    app: tauri::AppHandle,
+    task_id: String,
+    title: String,
+    estimated_mins: Option<i64>,
+) -> Result<SubtaskList, AppError> {
+    Ok(subtasks::add(&app, &task_id, &title, estimated_mins)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn update_subtask(
+    app: tauri::AppHandle,
+    id: String,
+    changes: SubtaskChanges,
+) -> Result<SubtaskList, AppError> {
+    Ok(subtasks::update(&app, &id, changes)?)
+}
+
+/// Returns the task's remaining subtasks, or `None` if it was already gone
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn delete_subtask(app: tauri::AppHandle, id: String) -> Result<Option<SubtaskList>, AppError> {
+    Ok(subtasks::delete(&app, &id)?)
+}
+
+/// Reorder a task's subtasks in one go: `ids` first, the rest after
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn reorder_subtasks(
+    app: tauri::AppHandle,
+    task_id: String,
+    ids: Vec<String>,
+) -> Result<SubtaskList, AppError> {
+    Ok(subtasks::reorder(&app, &task_id, &ids)?)
+}
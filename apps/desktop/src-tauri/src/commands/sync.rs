@@ -0,0 +1,9 @@
+use crate::error::AppError;
+use crate::sync;
+
+/// Run a sync pass immediately
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn sync_now(app: tauri::AppHandle) -> Result<(), AppError> {
+    Ok(sync::run_once(&app).await?)
+}
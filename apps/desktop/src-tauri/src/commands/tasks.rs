@@ -0,0 +1,23 @@
+use crate::db::tasks::{TaskFilter, TaskPage, TaskSort};
+use crate::error::AppError;
+use crate::sync::task_cache;
+
+/// A page of the locally stored tasks matching `filter`. Pass the returned
+/// `nextCursor` back as `cursor` for the following page.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn query_tasks(
+    app: tauri::AppHandle,
+    filter: Option<TaskFilter>,
+    sort: Option<TaskSort>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<TaskPage, AppError> {
+    Ok(task_cache::query(
+        &app,
+        &filter.unwrap_or_default(),
+        sort.unwrap_or_default(),
+        cursor.as_deref(),
+        limit,
+    )?)
+}
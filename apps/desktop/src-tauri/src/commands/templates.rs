@@ -0,0 +1,43 @@
+use chrono::NaiveDate;
+use serde_json::Value;
+
+use crate::db::templates::Template;
+use crate::error::AppError;
+use crate::templates;
+use crate::weekly_review;
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_templates(app: tauri::AppHandle) -> Result<Vec<Template>, AppError> {
+    Ok(templates::list(&app)?)
+}
+
+/// Create (with an empty id) or update a task template or ritual checklist
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn save_template(app: tauri::AppHandle, template: Template) -> Result<Template, AppError> {
+    Ok(templates::save(&app, template)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn delete_template(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(templates::delete(&app, &id)?)
+}
+
+/// Create a template's task for `date` (`YYYY-MM-DD`, default today)
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn instantiate_template(
+    app: tauri::AppHandle,
+    id: String,
+    date: Option<String>,
+) -> Result<Value, AppError> {
+    let date = match date {
+        Some(date) => date
+            .parse::<NaiveDate>()
+            .map_err(|e| format!("Invalid date '{}': {}", date, e))?,
+        None => weekly_review::today(),
+    };
+    Ok(templates::instantiate(&app, &id, date).await?)
+}
@@ -0,0 +1,18 @@
+use tauri::Manager;
+
+use crate::error::AppError;
+use crate::theme;
+
+/// Get the current OS theme ("light" or "dark")
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_system_theme(app: tauri::AppHandle) -> Result<String, AppError> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+
+    window
+        .theme()
+        .map(|t| theme::theme_name(t).to_string())
+        .map_err(|e| format!("Failed to get system theme: {}", e).into())
+}
@@ -0,0 +1,45 @@
+use tauri::State;
+
+use crate::db::time_entries::TimeEntry;
+use crate::error::AppError;
+use crate::timer::{self, TaskRef, TimerState, TimerStatus};
+
+/// Tell the timer which task has focus in the webview
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn set_current_task(state: State<'_, TimerState>, task: Option<TaskRef>) {
+    state.set_current_task(task);
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_timer_status(app: tauri::AppHandle) -> Result<TimerStatus, AppError> {
+    Ok(timer::status(&app)?)
+}
+
+/// Timer status with elapsed time reconciled against sleep and clock
+/// changes; views should show this rather than counting ticks
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn get_timer_state(app: tauri::AppHandle) -> Result<TimerStatus, AppError> {
+    Ok(timer::state(&app)?)
+}
+
+/// Start timing `task`, or the focused task when omitted
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn start_timer(app: tauri::AppHandle, task: Option<TaskRef>) -> Result<TimerStatus, AppError> {
+    Ok(timer::start(&app, task)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn stop_timer(app: tauri::AppHandle) -> Result<Option<TimeEntry>, AppError> {
+    Ok(timer::stop(&app)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn toggle_timer(app: tauri::AppHandle) -> Result<TimerStatus, AppError> {
+    Ok(timer::toggle(&app)?)
+}
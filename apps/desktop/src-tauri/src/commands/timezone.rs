@@ -0,0 +1,26 @@
+use tauri::{Emitter, State};
+
+use crate::clock;
+use crate::db::Database;
+use crate::error::AppError;
+use crate::timezone::{self, RebaseSummary, TimezoneState};
+
+/// Get the current OS timezone (IANA name)
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_timezone(state: State<'_, TimezoneState>) -> String {
+    state.current()
+}
+
+/// Re-anchor today's time blocks and reminders to the given timezone
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn rebase_day_to_timezone(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+    tz: String,
+) -> Result<RebaseSummary, AppError> {
+    let summary = timezone::rebase_day(&db, &tz, clock::now_millis())?;
+    let _ = app.emit("schedule-rebased", &summary);
+    Ok(summary)
+}
@@ -0,0 +1,45 @@
+use crate::attachments;
+use crate::db::transfers::Transfer;
+use crate::error::AppError;
+use crate::sync::transfers::{self, RemoteFile};
+
+/// Attachment uploads and downloads, unfinished first
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn list_transfers(app: tauri::AppHandle) -> Result<Vec<Transfer>, AppError> {
+    Ok(transfers::list(&app)?)
+}
+
+/// Queue an attachment for upload now rather than at the next sync
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn upload_attachment(app: tauri::AppHandle, id: String) -> Result<Option<Transfer>, AppError> {
+    let attachment = attachments::get(&app, &id)?;
+    Ok(transfers::queue_upload(&app, &attachment, true)?)
+}
+
+/// Queue a server attachment for download; `None` if it's already here
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn download_attachment(app: tauri::AppHandle, file: RemoteFile) -> Result<Option<Transfer>, AppError> {
+    Ok(transfers::queue_download(&app, file)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn pause_transfer(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(transfers::pause(&app, &id)?)
+}
+
+/// Resume a paused transfer or retry a failed one
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn resume_transfer(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(transfers::resume(&app, &id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn cancel_transfer(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(transfers::cancel(&app, &id)?)
+}
@@ -0,0 +1,16 @@
+use crate::error::AppError;
+use crate::updates::{self, UpdateInfo};
+
+/// Check for an update and download it if available; `None` when up to date
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn check_for_updates(app: tauri::AppHandle) -> Result<Option<UpdateInfo>, AppError> {
+    Ok(updates::check(&app).await?)
+}
+
+/// Install a downloaded update and restart the app
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn install_update_and_restart(app: tauri::AppHandle) -> Result<(), AppError> {
+    Ok(updates::install_and_restart(&app)?)
+}
@@ -0,0 +1,8 @@
+use crate::views::{NavView, VIEWS};
+
+/// Views reachable from the View and tray menus
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_nav_views() -> Vec<NavView> {
+    VIEWS.to_vec()
+}
@@ -0,0 +1,61 @@
+use crate::error::AppError;
+use crate::mini_mode;
+use crate::windows::{self, TaskWindow};
+
+/// Keep the main window above other windows
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_always_on_top(app: tauri::AppHandle, on: bool) -> Result<(), AppError> {
+    Ok(mini_mode::set_always_on_top(&app, on)?)
+}
+
+/// Shrink the main window to the pinned timer strip
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn enter_mini_mode(app: tauri::AppHandle) -> Result<(), AppError> {
+    Ok(mini_mode::enter(&app)?)
+}
+
+/// Restore the main window from mini mode
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn exit_mini_mode(app: tauri::AppHandle) -> Result<(), AppError> {
+    Ok(mini_mode::exit(&app)?)
+}
+
+/// Show the floating timer widget
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn open_timer_widget(app: tauri::AppHandle) -> Result<(), AppError> {
+    Ok(windows::open_timer_widget(&app)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn close_timer_widget(app: tauri::AppHandle) -> Result<(), AppError> {
+    Ok(windows::close_timer_widget(&app)?)
+}
+
+/// Tell the native side a focus session started or ended
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn set_focus_session(app: tauri::AppHandle, active: bool) -> Result<(), AppError> {
+    Ok(windows::focus_session_changed(&app, active)?)
+}
+
+/// Pop a task out into its own window; returns the window label
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub fn open_task_window(
+    app: tauri::AppHandle,
+    task_id: String,
+    title: Option<String>,
+) -> Result<String, AppError> {
+    Ok(windows::open_task_window(&app, &task_id, title.as_deref())?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn list_task_windows(app: tauri::AppHandle) -> Vec<TaskWindow> {
+    windows::list_task_windows(&app)
+}
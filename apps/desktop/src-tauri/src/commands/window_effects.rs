@@ -0,0 +1,8 @@
+use crate::window_effects::{self, WindowEffectSupport};
+
+/// The window effects and titlebar styles this platform supports
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+pub fn get_window_effect_support() -> WindowEffectSupport {
+    window_effects::support()
+}
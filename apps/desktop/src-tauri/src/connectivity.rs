@@ -0,0 +1,107 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_http::reqwest;
+use tokio::sync::watch;
+
+use crate::{clock, http, power, server};
+
+const ONLINE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const OFFLINE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityStatus {
+    pub online: bool,
+    pub latency_ms: Option<u64>,
+    pub last_checked_at: Option<i64>,
+}
+
+impl Default for ConnectivityStatus {
+    fn default() -> Self {
+        // Assume online until the first probe says otherwise so the UI
+        // doesn't flash an offline banner on every launch.
+        Self {
+            online: true,
+            latency_ms: None,
+            last_checked_at: None,
+        }
+    }
+}
+
+/// Shared connectivity state. The sync engine subscribes to the watch
+/// channel to pause and resume; the webview listens for `connectivity-changed`.
+pub struct ConnectivityState {
+    sender: watch::Sender<ConnectivityStatus>,
+}
+
+impl ConnectivityState {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(ConnectivityStatus::default());
+
+        Self { sender }
+    }
+
+    pub fn status(&self) -> ConnectivityStatus {
+        self.sender.borrow().clone()
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.sender.borrow().online
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<ConnectivityStatus> {
+        self.sender.subscribe()
+    }
+}
+
+/// Start the background reachability monitor
+pub fn start_monitor(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let status = check_now(&app).await;
+            let interval = if status.online {
+                power::interval(&app, ONLINE_CHECK_INTERVAL)
+            } else {
+                OFFLINE_CHECK_INTERVAL
+            };
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Probe the backend once, update state and emit `connectivity-changed`
+/// if the online/offline state flipped.
+pub async fn check_now(app: &AppHandle) -> ConnectivityStatus {
+    let state = app.state::<ConnectivityState>();
+    let latency = probe(&http::client(app), &server::current_url(app)).await;
+
+    let status = ConnectivityStatus {
+        online: latency.is_some(),
+        latency_ms: latency.map(|d| d.as_millis() as u64),
+        last_checked_at: Some(clock::now_millis()),
+    };
+
+    let changed = state.sender.borrow().online != status.online;
+    state.sender.send_replace(status.clone());
+
+    if changed {
+        let _ = app.emit("connectivity-changed", &status);
+    }
+
+    status
+}
+
+/// Hit the backend health endpoint. Any response from the server counts as
+/// reachable; only transport failures (DNS, TLS, timeout) count as offline.
+async fn probe(client: &reqwest::Client, server_url: &str) -> Option<Duration> {
+    let url = format!("{}/health", server_url.trim_end_matches('/'));
+    let started = Instant::now();
+
+    match client.get(&url).timeout(PROBE_TIMEOUT).send().await {
+        Ok(response) if !response.status().is_server_error() => Some(started.elapsed()),
+        _ => None,
+    }
+}
@@ -0,0 +1,171 @@
+//! Native context menus built from an item list sent by the webview. The
+//! selection arrives through the app-wide menu event handler, which hands it
+//! back here to resolve the pending `show` call.
+
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{
+    menu::{
+        CheckMenuItemBuilder, IsMenuItem, Menu, MenuItemBuilder, MenuItemKind, PredefinedMenuItem,
+        Submenu,
+    },
+    AppHandle, LogicalPosition, Manager, WebviewWindow, Wry,
+};
+use tokio::sync::oneshot;
+
+/// Prefix for ids of context menu items, so they can't collide with the
+/// app and tray menus
+pub const MENU_ID_PREFIX: &str = "context:";
+
+/// Showing a popup blocks until it closes, but the chosen item's event is
+/// delivered through the event loop just after, so wait this long for it
+/// before treating the menu as dismissed
+const SELECTION_GRACE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ContextMenuItem {
+    #[serde(rename_all = "camelCase")]
+    Item {
+        id: String,
+        label: String,
+        #[serde(default = "enabled_default")]
+        enabled: bool,
+        accelerator: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Check {
+        id: String,
+        label: String,
+        checked: bool,
+        #[serde(default = "enabled_default")]
+        enabled: bool,
+    },
+    Separator,
+    #[serde(rename_all = "camelCase")]
+    Submenu {
+        label: String,
+        items: Vec<ContextMenuItem>,
+        #[serde(default = "enabled_default")]
+        enabled: bool,
+    },
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// Where to open the menu, in logical pixels relative to the window
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct MenuPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// The selection channel of the menu currently open, if any
+#[derive(Default)]
+pub struct ContextMenuState {
+    pending: Mutex<Option<oneshot::Sender<String>>>,
+}
+
+/// Show a context menu over `window` and wait for the chosen item id.
+/// Returns `None` when the menu is dismissed or replaced by another one.
+pub async fn show(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    items: &[ContextMenuItem],
+    position: Option<MenuPosition>,
+) -> Result<Option<String>, String> {
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    let map_err = |e: tauri::Error| format!("Failed to build context menu: {}", e);
+    let built = build_items(app, items).map_err(map_err)?;
+    let refs: Vec<&dyn IsMenuItem<Wry>> = built.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+    let menu = Menu::with_items(app, &refs).map_err(map_err)?;
+
+    // Replacing the sender drops the previous one, resolving its menu to `None`
+    let (sender, receiver) = oneshot::channel();
+    if let Ok(mut pending) = app.state::<ContextMenuState>().pending.lock() {
+        *pending = Some(sender);
+    }
+
+    let shown = match position {
+        Some(position) => window.popup_menu_at(&menu, LogicalPosition::new(position.x, position.y)),
+        None => window.popup_menu(&menu),
+    };
+    shown.map_err(|e| format!("Failed to show context menu: {}", e))?;
+
+    // The menu has closed by now; a choice is already on its way or there
+    // was none
+    match tokio::time::timeout(SELECTION_GRACE, receiver).await {
+        Ok(Ok(id)) => Ok(Some(id)),
+        _ => Ok(None),
+    }
+}
+
+/// Route a menu event for a context menu item to the waiting `show` call
+pub fn select(app: &AppHandle, menu_id: &str) {
+    let Some(id) = menu_id.strip_prefix(MENU_ID_PREFIX) else {
+        return;
+    };
+    let sender = app
+        .state::<ContextMenuState>()
+        .pending
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take());
+    if let Some(sender) = sender {
+        let _ = sender.send(id.to_string());
+    }
+}
+
+fn build_items(app: &AppHandle, items: &[ContextMenuItem]) -> tauri::Result<Vec<MenuItemKind<Wry>>> {
+    items.iter().map(|item| build_item(app, item)).collect()
+}
+
+fn build_item(app: &AppHandle, item: &ContextMenuItem) -> tauri::Result<MenuItemKind<Wry>> {
+    let built = match item {
+        ContextMenuItem::Item {
+            id,
+            label,
+            enabled,
+            accelerator,
+        } => {
+            let mut builder = MenuItemBuilder::with_id(menu_id(id), label).enabled(*enabled);
+            if let Some(accelerator) = accelerator {
+                builder = builder.accelerator(accelerator);
+            }
+            MenuItemKind::MenuItem(builder.build(app)?)
+        }
+        ContextMenuItem::Check {
+            id,
+            label,
+            checked,
+            enabled,
+        } => MenuItemKind::Check(
+            CheckMenuItemBuilder::with_id(menu_id(id), label)
+                .checked(*checked)
+                .enabled(*enabled)
+                .build(app)?,
+        ),
+        ContextMenuItem::Separator => MenuItemKind::Predefined(PredefinedMenuItem::separator(app)?),
+        ContextMenuItem::Submenu {
+            label,
+            items,
+            enabled,
+        } => {
+            let children = build_items(app, items)?;
+            let refs: Vec<&dyn IsMenuItem<Wry>> =
+                children.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+            MenuItemKind::Submenu(Submenu::with_items(app, label, *enabled, &refs)?)
+        }
+    };
+    Ok(built)
+}
+
+fn menu_id(id: &str) -> String {
+    format!("{}{}", MENU_ID_PREFIX, id)
+}
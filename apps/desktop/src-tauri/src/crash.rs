@@ -0,0 +1,355 @@
+//! Crash reports. A panic hook writes the panic message and location, a
+//! backtrace, the app version and the last tracing events to
+//! `crashes/<id>.json` in the data directory before the process goes down.
+//! Release builds abort on panic, so this covers panics on any thread,
+//! Tokio tasks included; in debug builds a panicking task just ends and
+//! the report is written all the same.
+//!
+//! On the next launch a notification says the app crashed and
+//! `crash-report-found` carries the newest report, so the webview can offer
+//! to send it to the server or open a pre-filled GitHub issue.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Url};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::api::Api;
+use crate::error::{AppError, ErrorCode};
+use crate::notifications::{self, Category, Notice};
+use crate::{clock, data_dir};
+
+const CRASH_DIR: &str = "crashes";
+const ISSUES_URL: &str = "https://github.com/ShadowWalker2014/open-sunsama/issues/new";
+/// Events kept for the next report
+const MAX_BREADCRUMBS: usize = 50;
+/// Before encoding; GitHub turns away much longer URLs
+const MAX_ISSUE_BODY: usize = 4_000;
+
+static BREADCRUMBS: Mutex<VecDeque<Breadcrumb>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breadcrumb {
+    /// Unix milliseconds
+    pub at: i64,
+    /// "command", "warn" or "error"
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    /// Unix milliseconds
+    pub crashed_at: i64,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: Option<String>,
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    pub backtrace: String,
+    /// Oldest first
+    pub breadcrumbs: Vec<Breadcrumb>,
+    /// Whether the user was told about it on a later launch
+    #[serde(default)]
+    pub notified: bool,
+}
+
+/// Records commands run and warnings and errors logged, for crash reports
+pub struct BreadcrumbLayer;
+
+impl<S> Layer<S> for BreadcrumbLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        if attrs.metadata().target().contains("::commands") {
+            record("command", attrs.metadata().name().to_string());
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let kind = match *event.metadata().level() {
+            Level::ERROR => "error",
+            Level::WARN => "warn",
+            _ => return,
+        };
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+        record(kind, format!("{}:{}", event.metadata().target(), fields.0));
+    }
+}
+
+/// Event fields as ` name=value` pairs
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let _ = write!(self.0, " {}={:?}", field.name(), value);
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        let _ = write!(self.0, " {}={}", field.name(), value);
+    }
+}
+
+fn record(kind: &str, text: String) {
+    let Ok(mut breadcrumbs) = BREADCRUMBS.lock() else {
+        return;
+    };
+    if breadcrumbs.len() == MAX_BREADCRUMBS {
+        breadcrumbs.pop_front();
+    }
+    breadcrumbs.push_back(Breadcrumb {
+        at: clock::now_millis(),
+        kind: kind.to_string(),
+        text,
+    });
+}
+
+/// Write a report for every panic from here on, then run the default hook
+pub fn install(app: &AppHandle) -> Result<(), String> {
+    let dir = data_dir::get(app)?.join(CRASH_DIR);
+    let app_version = app.package_info().version.to_string();
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = capture(info, &app_version);
+        if let Err(e) = write(&dir, &report) {
+            // Not tracing: the breadcrumb layer takes a lock the panic may hold
+            eprintln!("Failed to write crash report: {}", e);
+        }
+        previous(info);
+    }));
+    Ok(())
+}
+
+fn capture(info: &PanicHookInfo<'_>, app_version: &str) -> CrashReport {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string());
+    let crashed_at = clock::now_millis();
+    // The panicking thread may hold the lock; a report without them beats none
+    let breadcrumbs = BREADCRUMBS
+        .try_lock()
+        .map(|breadcrumbs| breadcrumbs.iter().cloned().collect())
+        .unwrap_or_default();
+
+    CrashReport {
+        id: format!("crash-{}", crashed_at),
+        crashed_at,
+        app_version: app_version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: std::thread::current().name().map(str::to_string),
+        message,
+        location: info
+            .location()
+            .map(|at| format!("{}:{}:{}", at.file(), at.line(), at.column())),
+        backtrace: Backtrace::force_capture().to_string(),
+        breadcrumbs,
+        notified: false,
+    }
+}
+
+/// Reports left by earlier runs, newest first
+pub fn list(app: &AppHandle) -> Result<Vec<CrashReport>, String> {
+    let dir = data_dir::get(app)?.join(CRASH_DIR);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| std::fs::read(entry.path()).ok())
+        .filter_map(|bytes| serde_json::from_slice(&bytes).ok())
+        .collect();
+    reports.sort_by_key(|report| std::cmp::Reverse(report.crashed_at));
+    Ok(reports)
+}
+
+/// Tell the user about crashes since the last launch. Emits
+/// `crash-report-found` with the newest report.
+pub fn check_previous(app: &AppHandle) -> Result<(), String> {
+    let dir = data_dir::get(app)?.join(CRASH_DIR);
+    let mut unseen: Vec<CrashReport> = list(app)?
+        .into_iter()
+        .filter(|report| !report.notified)
+        .collect();
+    let Some(newest) = unseen.first().cloned() else {
+        return Ok(());
+    };
+
+    for report in &mut unseen {
+        report.notified = true;
+        write(&dir, report)?;
+    }
+    let _ = notifications::notify(
+        app,
+        Notice::new(Category::System, "Open Sunsama quit unexpectedly")
+            .body("A crash report was saved. Open the app to send it or report the issue."),
+    );
+    let _ = app.emit("crash-report-found", &newest);
+    Ok(())
+}
+
+/// Send a report to the server, then delete it
+pub async fn send(app: &AppHandle, id: &str) -> Result<(), AppError> {
+    let path = report_path(app, id)?;
+    let report = read(&path)?;
+    let api = Api::new(app)?;
+    match api.post::<Value, _>("/crash-reports", &report).await {
+        Ok(_) => Ok(dismiss(app, id)?),
+        Err(e) if e.is_not_found() => Err(AppError::new(
+            ErrorCode::NotFound,
+            "This server doesn't accept crash reports; open an issue instead",
+        )),
+        Err(e) => Err(e.context("Failed to send crash report")),
+    }
+}
+
+/// Open a new GitHub issue filled in from a report
+pub fn open_issue(app: &AppHandle, id: &str) -> Result<(), String> {
+    let report = read(&report_path(app, id)?)?;
+    let mut url = Url::parse(ISSUES_URL).map_err(|e| format!("Invalid issue URL: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("title", &format!("Crash: {}", first_line(&report.message)))
+        .append_pair("labels", "crash")
+        .append_pair("body", &issue_body(&report));
+    open::that_detached(url.as_str()).map_err(|e| format!("Failed to open browser: {}", e))
+}
+
+/// Delete a report without sending it
+pub fn dismiss(app: &AppHandle, id: &str) -> Result<(), String> {
+    let path = report_path(app, id)?;
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to delete crash report: {}", e)),
+    }
+}
+
+fn issue_body(report: &CrashReport) -> String {
+    let mut body = format!(
+        "**Version:** {}\n**OS:** {} {}\n**Thread:** {}\n**Location:** {}\n\n```\n{}\n```\n\n<details><summary>Backtrace</summary>\n\n```\n",
+        report.app_version,
+        report.os,
+        report.arch,
+        report.thread.as_deref().unwrap_or("unnamed"),
+        report.location.as_deref().unwrap_or("unknown"),
+        report.message,
+    );
+    let closing = "\n```\n</details>\n";
+    let room = MAX_ISSUE_BODY.saturating_sub(body.len() + closing.len());
+    let mut backtrace = report.backtrace.as_str();
+    if backtrace.len() > room {
+        let mut end = room;
+        while !backtrace.is_char_boundary(end) {
+            end -= 1;
+        }
+        backtrace = &backtrace[..end];
+    }
+    body.push_str(backtrace);
+    body.push_str(closing);
+    body
+}
+
+fn first_line(text: &str) -> &str {
+    let line = text.lines().next().unwrap_or_default();
+    match line.char_indices().nth(80) {
+        Some((end, _)) => &line[..end],
+        None => line,
+    }
+}
+
+/// Path of the report `id`; ids come from the webview, so nothing but the
+/// ones this module makes is accepted
+fn report_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    let valid = id
+        .strip_prefix("crash-")
+        .is_some_and(|millis| !millis.is_empty() && millis.bytes().all(|b| b.is_ascii_digit()));
+    if !valid {
+        return Err(format!("Unknown crash report '{}'", id));
+    }
+    Ok(data_dir::get(app)?
+        .join(CRASH_DIR)
+        .join(format!("{}.json", id)))
+}
+
+fn read(path: &Path) -> Result<CrashReport, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read crash report: {}", e))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Invalid crash report: {}", e))
+}
+
+fn write(dir: &Path, report: &CrashReport) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create crash report directory: {}", e))?;
+    let json = serde_json::to_vec_pretty(report)
+        .map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+    std::fs::write(dir.join(format!("{}.json", report.id)), json)
+        .map_err(|e| format!("Failed to write crash report: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(message: &str, backtrace: &str) -> CrashReport {
+        CrashReport {
+            id: "crash-1".to_string(),
+            crashed_at: 1,
+            app_version: "1.2.3".to_string(),
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            thread: Some("main".to_string()),
+            message: message.to_string(),
+            location: Some("src/timer.rs:10:5".to_string()),
+            backtrace: backtrace.to_string(),
+            breadcrumbs: Vec::new(),
+            notified: false,
+        }
+    }
+
+    #[test]
+    fn issue_bodies_stay_short_enough_for_a_url() {
+        let body = issue_body(&report("boom", &"é".repeat(MAX_ISSUE_BODY)));
+        assert!(body.len() <= MAX_ISSUE_BODY);
+        assert!(body.contains("**Version:** 1.2.3"));
+        assert!(body.ends_with("</details>\n"));
+    }
+
+    #[test]
+    fn issue_titles_take_the_start_of_the_message() {
+        assert_eq!(first_line("called `unwrap()`\nmore"), "called `unwrap()`");
+        assert_eq!(first_line(&"x".repeat(200)).len(), 80);
+    }
+
+    #[test]
+    fn breadcrumbs_keep_the_latest_events() {
+        for n in 0..MAX_BREADCRUMBS + 5 {
+            record("warn", format!("event {}", n));
+        }
+        let breadcrumbs = BREADCRUMBS.lock().unwrap();
+        assert_eq!(breadcrumbs.len(), MAX_BREADCRUMBS);
+        assert_eq!(
+            breadcrumbs.back().unwrap().text,
+            format!("event {}", MAX_BREADCRUMBS + 4)
+        );
+    }
+}
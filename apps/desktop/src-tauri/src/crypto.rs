@@ -0,0 +1,560 @@
+//! End-to-end encryption for synced payloads.
+//!
+//! Payloads are sealed with XChaCha20-Poly1305 under a random data key. All
+//! data keys ever used form a key set, sealed under a random master key in
+//! `encryption.json`. The master key lives in the OS keychain and is also
+//! sealed twice next to the key set — under an Argon2id key derived from the
+//! user's passphrase and under one derived from the recovery code — so it can
+//! be restored on a new device or after the keychain entry is lost. The
+//! sealed envelopes sync through the server (which can open none of them),
+//! so a new device only needs the passphrase.
+//!
+//! Once set up, task notes and descriptions and synced settings leave the
+//! device sealed and are opened on receipt. That covers what the native side
+//! sends through [`Api`]; requests the webview makes itself carry what it
+//! passes them, so it seals those fields with `encrypt_payload`. Titles and
+//! the rest of a task stay readable by the server, which schedules, searches
+//! and notifies by them.
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::api::Api;
+use crate::error::{AppError, ErrorCode};
+use crate::{clock, data_dir, keychain};
+
+const KEYCHAIN_ACCOUNT: &str = "e2e-master-key";
+const ENVELOPE_STORE: &str = "encryption.json";
+const ENVELOPE_KEY: &str = "envelopes";
+const PAYLOAD_VERSION: u8 = 1;
+const ENVELOPES_PATH: &str = "/encryption/envelopes";
+
+/// Task fields sealed before they reach the server: the free text, not the
+/// fields the server works with
+pub const TASK_FIELDS: &[&str] = &["notes", "description"];
+
+pub const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const RECOVERY_CODE_LEN: usize = 20;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+pub type SecretKey = [u8; KEY_LEN];
+
+/// All data keys ever used, so payloads sealed before a rotation still open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeySet {
+    active: String,
+    keys: BTreeMap<String, String>,
+}
+
+/// Something sealed under a key. `salt` is set when that key is derived
+/// from a secret rather than being the master key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Envelope {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelopes {
+    /// Master key under the passphrase
+    passphrase: Envelope,
+    /// Master key under the recovery code
+    recovery: Envelope,
+    /// Key set under the master key
+    keyset: Envelope,
+    /// When any of them last changed, in Unix milliseconds; the newer copy
+    /// wins when syncing
+    #[serde(default)]
+    updated_at: i64,
+}
+
+/// Wire format of an encrypted payload
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedPayload {
+    v: u8,
+    kid: String,
+    nonce: String,
+    ct: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    pub unlocked: bool,
+    pub active_key_id: Option<String>,
+}
+
+/// Unlocked key set, cached after the first keychain read
+#[derive(Default)]
+pub struct CryptoState {
+    keyset: RwLock<Option<KeySet>>,
+}
+
+/// Whether encryption is set up, so payloads must be sealed
+pub fn is_enabled(app: &AppHandle) -> Result<bool, String> {
+    Ok(load_envelopes(app)?.is_some())
+}
+
+pub fn status(app: &AppHandle) -> Result<EncryptionStatus, String> {
+    let enabled = load_envelopes(app)?.is_some();
+    let keyset = if enabled { unlocked_keyset(app).ok() } else { None };
+
+    Ok(EncryptionStatus {
+        enabled,
+        unlocked: keyset.is_some(),
+        active_key_id: keyset.map(|keyset| keyset.active),
+    })
+}
+
+/// Turn on encryption with a new master and data key. Returns the recovery
+/// code, which is shown once and never stored. Refuses when another device
+/// already set it up, since new keys would replace the ones its data is
+/// sealed under; that device's passphrase unlocks this one instead.
+pub async fn setup(app: &AppHandle, passphrase: &str) -> Result<String, String> {
+    if load_envelopes(app)?.is_some() {
+        return Err("Encryption is already set up".to_string());
+    }
+    validate_passphrase(passphrase)?;
+    let api = Api::new(app)?;
+    match api.get::<Envelopes>(ENVELOPES_PATH).await {
+        Ok(remote) => {
+            write_envelopes(app, &remote)?;
+            return Err(
+                "Encryption is already set up on another device; unlock with its passphrase"
+                    .to_string(),
+            );
+        }
+        Err(e) if e.is_not_found() => {}
+        Err(e) => return Err(format!("Failed to fetch encryption keys: {}", e)),
+    }
+
+    let master = random_bytes::<KEY_LEN>()?;
+    let kid = new_key_id()?;
+    let mut keys = BTreeMap::new();
+    keys.insert(kid.clone(), BASE64.encode(random_bytes::<KEY_LEN>()?));
+    let keyset = KeySet { active: kid, keys };
+
+    let recovery_code = new_recovery_code()?;
+    let envelopes = Envelopes {
+        passphrase: seal_with_secret(passphrase, &master)?,
+        recovery: seal_with_secret(&recovery_code, &master)?,
+        keyset: seal_keyset(&master, &keyset)?,
+        updated_at: clock::now_millis(),
+    };
+    store_master(&master)?;
+    // Uploaded before use, so a second device can't set up over them
+    api.put::<Value, _>(ENVELOPES_PATH, &envelopes)
+        .await
+        .map_err(|e| format!("Failed to upload encryption keys: {}", e))?;
+    write_envelopes(app, &envelopes)?;
+    cache_keyset(app, keyset);
+
+    Ok(recovery_code)
+}
+
+/// Unlock on a device whose keychain lacks the master key (new device,
+/// reinstall)
+pub fn unlock(app: &AppHandle, passphrase: &str) -> Result<(), String> {
+    let envelopes = require_envelopes(app)?;
+    let master = open_with_secret(&envelopes.passphrase, passphrase)
+        .map_err(|_| "Incorrect passphrase".to_string())?;
+    let keyset = open_keyset(&master, &envelopes.keyset)?;
+
+    store_master(&master)?;
+    cache_keyset(app, keyset);
+    Ok(())
+}
+
+/// Start sealing new payloads with a fresh data key. Older keys are kept for
+/// decryption. Returns the new key id.
+pub fn rotate(app: &AppHandle, passphrase: &str) -> Result<String, String> {
+    let mut envelopes = require_envelopes(app)?;
+    let master = open_with_secret(&envelopes.passphrase, passphrase)
+        .map_err(|_| "Incorrect passphrase".to_string())?;
+    let mut keyset = open_keyset(&master, &envelopes.keyset)?;
+
+    let kid = new_key_id()?;
+    keyset
+        .keys
+        .insert(kid.clone(), BASE64.encode(random_bytes::<KEY_LEN>()?));
+    keyset.active = kid.clone();
+
+    envelopes.keyset = seal_keyset(&master, &keyset)?;
+    save_envelopes(app, &envelopes)?;
+    cache_keyset(app, keyset);
+
+    Ok(kid)
+}
+
+/// Restore access with the recovery code and set a new passphrase. Returns a
+/// new recovery code; the old one stops working.
+pub fn recover(app: &AppHandle, recovery_code: &str, new_passphrase: &str) -> Result<String, String> {
+    validate_passphrase(new_passphrase)?;
+    let mut envelopes = require_envelopes(app)?;
+    let master = open_with_secret(&envelopes.recovery, &normalize_recovery_code(recovery_code))
+        .map_err(|_| "Invalid recovery code".to_string())?;
+    let keyset = open_keyset(&master, &envelopes.keyset)?;
+
+    let recovery_code = new_recovery_code()?;
+    envelopes.passphrase = seal_with_secret(new_passphrase, &master)?;
+    envelopes.recovery = seal_with_secret(&recovery_code, &master)?;
+    save_envelopes(app, &envelopes)?;
+    store_master(&master)?;
+    cache_keyset(app, keyset);
+
+    Ok(recovery_code)
+}
+
+/// Replace the recovery code, e.g. after it may have been exposed
+pub fn regenerate_recovery_code(app: &AppHandle, passphrase: &str) -> Result<String, String> {
+    let mut envelopes = require_envelopes(app)?;
+    let master = open_with_secret(&envelopes.passphrase, passphrase)
+        .map_err(|_| "Incorrect passphrase".to_string())?;
+
+    let recovery_code = new_recovery_code()?;
+    envelopes.recovery = seal_with_secret(&recovery_code, &master)?;
+    save_envelopes(app, &envelopes)?;
+
+    Ok(recovery_code)
+}
+
+/// Seal a payload with the active key. The result is a JSON string safe to
+/// send to the server in place of the plaintext.
+pub fn encrypt(app: &AppHandle, plaintext: &[u8]) -> Result<String, AppError> {
+    let keyset = unlocked_keyset(app)?;
+    let key = decode_key(&keyset, &keyset.active)?;
+    let (nonce, ct) = seal(&key, plaintext)?;
+
+    serde_json::to_string(&EncryptedPayload {
+        v: PAYLOAD_VERSION,
+        kid: keyset.active,
+        nonce,
+        ct,
+    })
+    .map_err(|e| format!("Failed to encode payload: {}", e).into())
+}
+
+/// Open a payload produced by `encrypt` on any device sharing the key set
+pub fn decrypt(app: &AppHandle, payload: &str) -> Result<Vec<u8>, AppError> {
+    let payload: EncryptedPayload =
+        serde_json::from_str(payload).map_err(|_| "Not an encrypted payload".to_string())?;
+    if payload.v != PAYLOAD_VERSION {
+        return Err(format!("Unsupported payload version {}", payload.v).into());
+    }
+
+    let keyset = unlocked_keyset(app)?;
+    let key = decode_key(&keyset, &payload.kid)?;
+    Ok(open(&key, &payload.nonce, &payload.ct)?)
+}
+
+/// Seal the string `fields` of `value` in place. A no-op until encryption
+/// is set up; fails while it's locked rather than send plaintext.
+pub fn seal_fields(app: &AppHandle, value: &mut Value, fields: &[&str]) -> Result<(), String> {
+    if !is_enabled(app)? {
+        return Ok(());
+    }
+    let Some(object) = value.as_object_mut() else {
+        return Ok(());
+    };
+    for field in fields {
+        if let Some(Value::String(plaintext)) = object.get(*field) {
+            let sealed = encrypt(app, plaintext.as_bytes())?;
+            object.insert(field.to_string(), Value::String(sealed));
+        }
+    }
+    Ok(())
+}
+
+/// Open the `fields` of `value` sealed by `seal_fields`, in place. Fields
+/// that aren't sealed are left alone, and so are sealed ones while
+/// encryption is locked.
+pub fn open_fields(app: &AppHandle, value: &mut Value, fields: &[&str]) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    for field in fields {
+        let Some(Value::String(payload)) = object.get(*field) else {
+            continue;
+        };
+        if serde_json::from_str::<EncryptedPayload>(payload).is_err() {
+            continue;
+        }
+        match decrypt(app, payload).map(String::from_utf8) {
+            Ok(Ok(plaintext)) => {
+                object.insert(field.to_string(), Value::String(plaintext));
+            }
+            Ok(Err(_)) => tracing::warn!(field, "sealed field isn't text"),
+            Err(e) => tracing::warn!(field, "can't open sealed field: {}", e),
+        }
+    }
+}
+
+/// Exchange the sealed envelopes with the server: a device without them
+/// (new, reinstalled) takes the server's so the passphrase unlocks it, and
+/// whichever side changed them last (setup, rotation, recovery) wins.
+#[tracing::instrument(skip_all, err)]
+pub async fn sync_envelopes(app: &AppHandle, api: &Api) -> Result<(), String> {
+    let remote = match api.get::<Envelopes>(ENVELOPES_PATH).await {
+        Ok(remote) => Some(remote),
+        Err(e) if e.is_not_found() => None,
+        Err(e) => return Err(format!("Failed to fetch encryption keys: {}", e)),
+    };
+    let local = load_envelopes(app)?;
+
+    match (local, remote) {
+        (Some(local), remote) if remote.as_ref().is_none_or(|r| local.updated_at > r.updated_at) => {
+            api.put::<Value, _>(ENVELOPES_PATH, &local)
+                .await
+                .map_err(|e| format!("Failed to upload encryption keys: {}", e))?;
+        }
+        (local, Some(remote)) if local.as_ref().is_none_or(|l| remote.updated_at > l.updated_at) => {
+            write_envelopes(app, &remote)?;
+            // A rotation elsewhere added a key; reopen the set on next use
+            if let Ok(mut cached) = app.state::<CryptoState>().keyset.write() {
+                *cached = None;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn unlocked_keyset(app: &AppHandle) -> Result<KeySet, AppError> {
+    let state = app.state::<CryptoState>();
+    if let Some(keyset) = state.keyset.read().ok().and_then(|k| k.clone()) {
+        return Ok(keyset);
+    }
+
+    let envelopes = require_envelopes(app)?;
+    let encoded = keychain::get(KEYCHAIN_ACCOUNT)?
+        .ok_or_else(|| AppError::new(ErrorCode::Locked, "Encryption is locked; enter your passphrase"))?;
+    let master = BASE64
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| SecretKey::try_from(bytes).ok())
+        .ok_or_else(|| "Corrupt master key in keychain".to_string())?;
+    // Envelopes from a device that set up its own keys don't open with
+    // this keychain's master key; the passphrase fetches the right one
+    let keyset = open_keyset(&master, &envelopes.keyset).map_err(|_| {
+        AppError::new(ErrorCode::Locked, "Encryption keys changed; enter your passphrase")
+    })?;
+
+    cache_keyset(app, keyset.clone());
+    Ok(keyset)
+}
+
+fn cache_keyset(app: &AppHandle, keyset: KeySet) {
+    if let Ok(mut cached) = app.state::<CryptoState>().keyset.write() {
+        *cached = Some(keyset);
+    }
+}
+
+fn store_master(master: &SecretKey) -> Result<(), String> {
+    keychain::set(KEYCHAIN_ACCOUNT, &BASE64.encode(master))
+}
+
+fn load_envelopes(app: &AppHandle) -> Result<Option<Envelopes>, String> {
+    let store = app
+        .store(data_dir::store_path(app, ENVELOPE_STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+
+    match store.get(ENVELOPE_KEY) {
+        Some(value) => serde_json::from_value(value)
+            .map(Some)
+            .map_err(|e| format!("Corrupt encryption envelopes: {}", e)),
+        None => Ok(None),
+    }
+}
+
+fn require_envelopes(app: &AppHandle) -> Result<Envelopes, String> {
+    load_envelopes(app)?.ok_or_else(|| "Encryption is not set up".to_string())
+}
+
+/// Save changed envelopes, stamped so the next sync uploads them
+fn save_envelopes(app: &AppHandle, envelopes: &Envelopes) -> Result<(), String> {
+    let mut envelopes = envelopes.clone();
+    envelopes.updated_at = clock::now_millis();
+    write_envelopes(app, &envelopes)
+}
+
+fn write_envelopes(app: &AppHandle, envelopes: &Envelopes) -> Result<(), String> {
+    let store = app
+        .store(data_dir::store_path(app, ENVELOPE_STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    let value = serde_json::to_value(envelopes)
+        .map_err(|e| format!("Failed to encode envelopes: {}", e))?;
+
+    store.set(ENVELOPE_KEY, value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save envelopes: {}", e))
+}
+
+fn seal_with_secret(secret: &str, master: &SecretKey) -> Result<Envelope, String> {
+    let salt = random_bytes::<SALT_LEN>()?;
+    let key = derive_key(secret, &salt)?;
+    let (nonce, ciphertext) = seal(&key, master)?;
+
+    Ok(Envelope {
+        salt: Some(BASE64.encode(salt)),
+        nonce,
+        ciphertext,
+    })
+}
+
+fn open_with_secret(envelope: &Envelope, secret: &str) -> Result<SecretKey, String> {
+    let salt = envelope
+        .salt
+        .as_deref()
+        .and_then(|salt| BASE64.decode(salt).ok())
+        .ok_or_else(|| "Corrupt envelope".to_string())?;
+    let key = derive_key(secret, &salt)?;
+
+    open(&key, &envelope.nonce, &envelope.ciphertext)
+        .and_then(|bytes| SecretKey::try_from(bytes).map_err(|_| "Corrupt master key".to_string()))
+}
+
+fn seal_keyset(master: &SecretKey, keyset: &KeySet) -> Result<Envelope, String> {
+    let json =
+        serde_json::to_vec(keyset).map_err(|e| format!("Failed to encode key set: {}", e))?;
+    let (nonce, ciphertext) = seal(master, &json)?;
+
+    Ok(Envelope {
+        salt: None,
+        nonce,
+        ciphertext,
+    })
+}
+
+fn open_keyset(master: &SecretKey, envelope: &Envelope) -> Result<KeySet, String> {
+    let json = open(master, &envelope.nonce, &envelope.ciphertext)?;
+    serde_json::from_slice(&json).map_err(|e| format!("Corrupt key set: {}", e))
+}
+
+/// Argon2id key from a passphrase or recovery code
+fn derive_key(secret: &str, salt: &[u8]) -> Result<SecretKey, String> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(secret.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Seal `plaintext` under a raw key as nonce followed by ciphertext, for
+/// channels that carry bytes rather than JSON payloads
+pub fn seal_bytes(key: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = random_bytes::<NONCE_LEN>()?;
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+/// Open bytes sealed with [`seal_bytes`]
+pub fn open_bytes(key: &SecretKey, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+        return Err("Corrupt nonce".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Decryption failed".to_string())
+}
+
+fn seal(key: &SecretKey, plaintext: &[u8]) -> Result<(String, String), String> {
+    let nonce = random_bytes::<NONCE_LEN>()?;
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    Ok((BASE64.encode(nonce), BASE64.encode(ciphertext)))
+}
+
+fn open(key: &SecretKey, nonce: &str, ciphertext: &str) -> Result<Vec<u8>, String> {
+    let nonce = BASE64
+        .decode(nonce)
+        .ok()
+        .filter(|nonce| nonce.len() == NONCE_LEN)
+        .ok_or_else(|| "Corrupt nonce".to_string())?;
+    let ciphertext = BASE64
+        .decode(ciphertext)
+        .map_err(|e| format!("Corrupt ciphertext: {}", e))?;
+
+    XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| "Decryption failed".to_string())
+}
+
+fn decode_key(keyset: &KeySet, kid: &str) -> Result<SecretKey, String> {
+    let encoded = keyset
+        .keys
+        .get(kid)
+        .ok_or_else(|| format!("Unknown encryption key '{}'", kid))?;
+    BASE64
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| SecretKey::try_from(bytes).ok())
+        .ok_or_else(|| format!("Corrupt encryption key '{}'", kid))
+}
+
+pub fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("No secure randomness: {}", e))?;
+    Ok(bytes)
+}
+
+fn new_key_id() -> Result<String, String> {
+    Ok(hex(&random_bytes::<8>()?))
+}
+
+/// 20 random bytes as ten dash-separated groups of four hex digits
+fn new_recovery_code() -> Result<String, String> {
+    let digits = hex(&random_bytes::<RECOVERY_CODE_LEN>()?).to_uppercase();
+    let groups: Vec<&str> = (0..digits.len())
+        .step_by(4)
+        .map(|i| &digits[i..i + 4])
+        .collect();
+    Ok(groups.join("-"))
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    let digits: String = code
+        .chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let groups: Vec<&str> = (0..digits.len())
+        .step_by(4)
+        .map(|i| &digits[i..(i + 4).min(digits.len())])
+        .collect();
+    groups.join("-")
+}
+
+fn validate_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        ));
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
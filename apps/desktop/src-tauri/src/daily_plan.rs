@@ -0,0 +1,95 @@
+//! A plain-language rundown of one day's plan, for read-outs and
+//! notifications.
+
+use chrono::{NaiveDate, TimeZone};
+use chrono_tz::Tz;
+use opensunsama_core::task::is_completed;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::api::Api;
+use crate::connectivity::ConnectivityState;
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::Database;
+use crate::timezone;
+
+/// Describe `date` from the local schedule plus, when signed in and online,
+/// the tasks planned for it on the server
+pub async fn describe(app: &AppHandle, date: NaiveDate) -> Result<String, String> {
+    let tz = timezone::local();
+    let (start, end) =
+        timezone::day_bounds(date, tz).ok_or_else(|| format!("Invalid date: {}", date))?;
+    let blocks = app
+        .state::<Database>()
+        .with_conn(|conn| time_blocks::list_between(conn, start, end))?;
+
+    // The read-out still works offline; it just leaves the task list out
+    let tasks = if app.state::<ConnectivityState>().is_online() {
+        match Api::new(app) {
+            Ok(api) => api
+                .get_all::<Value>(&format!("/tasks?scheduledDate={}", date))
+                .await
+                .ok(),
+            Err(_) => None,
+        }
+    } else {
+        None
+    };
+
+    Ok(compose(date, tz, &blocks, tasks.as_deref()))
+}
+
+fn compose(date: NaiveDate, tz: Tz, blocks: &[TimeBlock], tasks: Option<&[Value]>) -> String {
+    let mut parts = vec![format!("Here's your plan for {}.", date.format("%A, %B %-d"))];
+
+    match blocks.len() {
+        0 => parts.push("Your calendar is clear.".to_string()),
+        1 => parts.push("You have one time block.".to_string()),
+        n => parts.push(format!("You have {} time blocks.", n)),
+    }
+    for block in blocks {
+        let starts = tz
+            .timestamp_millis_opt(block.start_at)
+            .single()
+            .map(|t| t.format("%-I:%M %p").to_string())
+            .unwrap_or_default();
+        let minutes = (block.end_at - block.start_at) / 60_000;
+        parts.push(format!("At {}, {}, for {}.", starts, block.title, spoken_duration(minutes)));
+    }
+
+    if let Some(tasks) = tasks {
+        let open: Vec<&Value> = tasks
+            .iter()
+            .filter(|task| !is_completed(task))
+            .collect();
+        let titles: Vec<&str> = open
+            .iter()
+            .filter_map(|task| task.get("title").and_then(Value::as_str))
+            .collect();
+        let estimate: i64 = open
+            .iter()
+            .filter_map(|task| task.get("estimatedMins").and_then(Value::as_i64))
+            .sum();
+
+        match titles.len() {
+            0 => parts.push("No tasks are planned.".to_string()),
+            1 => parts.push(format!("One task is planned: {}.", titles[0])),
+            n => parts.push(format!("{} tasks are planned: {}.", n, titles.join(", "))),
+        }
+        if estimate > 0 {
+            parts.push(format!("That's about {} of work.", spoken_duration(estimate)));
+        }
+    }
+
+    parts.join(" ")
+}
+
+fn spoken_duration(minutes: i64) -> String {
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    let plural = |n: i64, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+    match (hours, minutes) {
+        (0, m) => plural(m, "minute"),
+        (h, 0) => plural(h, "hour"),
+        (h, m) => format!("{} {}", plural(h, "hour"), plural(m, "minute")),
+    }
+}
@@ -0,0 +1,287 @@
+//! Where the app keeps its data: stores, the database, backups and
+//! attachments. Normally the OS app data directory, but it can be moved:
+//!
+//! - `--data-dir <path>` for this run
+//! - a `portable` file next to the executable keeps data in `data/` beside
+//!   it, e.g. on a USB stick
+//! - `migrate` moves the data somewhere else for good, recording the new
+//!   location in `data-dir.json` in the default directory
+//!
+//! Each profile keeps its data in its own directory under that location
+//! (see `profiles`); `get` returns the active profile's. The webview's own
+//! storage stays in the OS location for the default profile.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use crate::db::{self, Database};
+use crate::settings::SETTINGS_STORE;
+use crate::{args, profiles};
+
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DIR: &str = "data";
+const REDIRECT_FILE: &str = "data-dir.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DataDirSource {
+    Default,
+    Flag,
+    Portable,
+    Moved,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDirInfo {
+    pub path: String,
+    pub source: DataDirSource,
+}
+
+/// The resolved data directory, managed before anything touches data
+pub struct DataDir {
+    /// Where all profiles live
+    root: PathBuf,
+    /// The active profile's directory
+    path: PathBuf,
+    source: DataDirSource,
+    profile: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Redirect {
+    path: PathBuf,
+    /// The previous directory, emptied once the new one is in use
+    pending_cleanup: Option<PathBuf>,
+}
+
+impl DataDir {
+    /// Find the data location and the profile to use, `--profile` winning
+    /// over the one last switched to
+    pub fn resolve(app: &AppHandle) -> Result<Self, String> {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let args = args::parse(std::env::args().skip(1), &cwd);
+        let (root, source) = locate(app, args.data_dir)?;
+
+        let profile = match args.profile {
+            Some(id) if profiles::exists(&root, &id) => id,
+            _ => profiles::active_id(&root),
+        };
+        Ok(Self {
+            path: profiles::dir(&root, &profile),
+            root,
+            source,
+            profile,
+        })
+    }
+
+    /// The default profile under `root`, for tests
+    #[cfg(test)]
+    pub fn at(root: &Path) -> Self {
+        Self {
+            path: profiles::dir(root, profiles::DEFAULT_PROFILE),
+            root: root.to_path_buf(),
+            source: DataDirSource::Flag,
+            profile: profiles::DEFAULT_PROFILE.to_string(),
+        }
+    }
+}
+
+fn locate(app: &AppHandle, flag: Option<PathBuf>) -> Result<(PathBuf, DataDirSource), String> {
+    if let Some(path) = flag {
+        return Ok((path, DataDirSource::Flag));
+    }
+
+    if let Some(dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        if dir.join(PORTABLE_MARKER).exists() {
+            return Ok((dir.join(PORTABLE_DIR), DataDirSource::Portable));
+        }
+    }
+
+    let default = default_dir(app)?;
+    if let Some(mut redirect) = read_redirect(&default) {
+        // Only let go of the old data once the copy is there
+        if !is_empty_except(&redirect.path, REDIRECT_FILE)? {
+            if let Some(previous) = redirect.pending_cleanup.take() {
+                clean_up(&previous);
+                write_redirect(&default, &redirect)?;
+            }
+        }
+        if redirect.path == default {
+            let _ = std::fs::remove_file(default.join(REDIRECT_FILE));
+        } else {
+            return Ok((redirect.path, DataDirSource::Moved));
+        }
+    }
+
+    Ok((default, DataDirSource::Default))
+}
+
+/// The data directory, created if needed
+pub fn get<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app.state::<DataDir>().path.clone();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Where all profiles live, created if needed
+pub fn root(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.state::<DataDir>().root.clone();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    Ok(dir)
+}
+
+/// The id of the profile this run uses
+pub fn profile(app: &AppHandle) -> String {
+    app.state::<DataDir>().profile.clone()
+}
+
+/// Path for a store file; the store plugin takes absolute paths as they are
+pub fn store_path<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    Ok(get(app)?.join(name))
+}
+
+pub fn info(app: &AppHandle) -> DataDirInfo {
+    let dir = app.state::<DataDir>();
+    DataDirInfo {
+        path: dir.root.to_string_lossy().into_owned(),
+        source: dir.source,
+    }
+}
+
+/// Copy all data to `target` and restart using it. The old directory is
+/// emptied on the next launch, once the copy is known to open. Not
+/// possible while the location comes from `--data-dir` or portable mode.
+pub fn migrate(app: &AppHandle, target: &Path) -> Result<(), String> {
+    let current = app.state::<DataDir>();
+    if matches!(current.source, DataDirSource::Flag | DataDirSource::Portable) {
+        return Err("The data directory is set by --data-dir or portable mode".to_string());
+    }
+    if !target.is_absolute() {
+        return Err("Choose an absolute path for the data directory".to_string());
+    }
+    if target.starts_with(&current.root) || current.root.starts_with(target) {
+        return Err("The new data directory can't contain or be inside the current one".to_string());
+    }
+    let default = default_dir(app)?;
+    if !is_empty_except(target, REDIRECT_FILE)? {
+        return Err(format!("{} isn't empty", target.display()));
+    }
+
+    if let Ok(store) = app.store(store_path(app, SETTINGS_STORE)?) {
+        store
+            .save()
+            .map_err(|e| format!("Failed to flush settings: {}", e))?;
+    }
+    std::fs::create_dir_all(target)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    let copied = copy_data(app, &current, target);
+    if let Err(e) = copied {
+        clean_up(target);
+        return Err(e);
+    }
+
+    write_redirect(
+        &default,
+        &Redirect {
+            path: target.to_path_buf(),
+            pending_cleanup: Some(current.root.clone()),
+        },
+    )?;
+
+    app.restart()
+}
+
+fn copy_data(app: &AppHandle, current: &DataDir, target: &Path) -> Result<(), String> {
+    // The live database is copied consistently rather than file by file
+    let relative = current.path.strip_prefix(&current.root).unwrap_or(Path::new(""));
+    let database = current.path.join(db::DATABASE_FILE);
+    let target_profile = target.join(relative);
+    std::fs::create_dir_all(&target_profile)
+        .map_err(|e| format!("Failed to create {}: {}", target_profile.display(), e))?;
+    app.state::<Database>()
+        .snapshot(&target_profile.join(db::DATABASE_FILE), db::current_key(app)?.as_deref())?;
+
+    let redirect = current.root.join(REDIRECT_FILE);
+    let skip = |path: &Path| {
+        path == redirect
+            || (path.parent() == database.parent()
+                && path.to_string_lossy().starts_with(&*database.to_string_lossy()))
+    };
+    copy_dir(&current.root, target, &skip)
+}
+
+fn copy_dir(source: &Path, target: &Path, skip: &dyn Fn(&Path) -> bool) -> Result<(), String> {
+    let entries = std::fs::read_dir(source)
+        .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        let from = entry.path();
+        if skip(&from) {
+            continue;
+        }
+        let to = target.join(entry.file_name());
+        if from.is_dir() {
+            std::fs::create_dir_all(&to)
+                .map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+            copy_dir(&from, &to, skip)?;
+        } else {
+            std::fs::copy(&from, &to)
+                .map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove everything in `dir` except the redirect file, which lives in the
+/// default directory
+fn clean_up(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name() == REDIRECT_FILE {
+            continue;
+        }
+        let path = entry.path();
+        let _ = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+    }
+}
+
+fn is_empty_except(dir: &Path, allowed: &str) -> Result<bool, String> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => Ok(entries.flatten().all(|entry| entry.file_name() == allowed)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+        Err(e) => Err(format!("Failed to read {}: {}", dir.display(), e)),
+    }
+}
+
+fn default_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))
+}
+
+fn read_redirect(default: &Path) -> Option<Redirect> {
+    std::fs::read_to_string(default.join(REDIRECT_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn write_redirect(default: &Path, redirect: &Redirect) -> Result<(), String> {
+    std::fs::create_dir_all(default)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    let json = serde_json::to_string_pretty(redirect)
+        .map_err(|e| format!("Failed to serialize data directory: {}", e))?;
+    std::fs::write(default.join(REDIRECT_FILE), json)
+        .map_err(|e| format!("Failed to record data directory: {}", e))
+}
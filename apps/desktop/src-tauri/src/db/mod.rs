@@ -0,0 +1,64 @@
+//! The local database lives in `opensunsama-core`, shared with the mobile
+//! app. This adds what's desktop-only: where the file is, the
+//! keychain-held encryption key, and the template queries.
+
+use tauri::AppHandle;
+
+use crate::{data_dir, keychain, settings};
+
+pub use opensunsama_core::db::{
+    attachments, budgets, calendar_subscriptions, dependencies, external_projects, held_notices, holidays,
+    objectives, outbox, reminders, snoozed, subtasks, sync_cursors, tasks, text_docs, time_blocks,
+    time_entries, time_exports, tombstones, transfers, Database, DATABASE_FILE,
+};
+
+pub mod templates;
+
+/// Keychain account holding the SQLCipher key when local encryption is on
+const DATABASE_KEY_ACCOUNT: &str = "local-db-key";
+
+/// Open the database in the app data directory, unlocking it with the
+/// keychain-held key when local encryption is enabled
+pub fn init(app: &AppHandle) -> Result<Database, String> {
+    let dir = data_dir::get(app)?;
+    let key = current_key(app)?;
+    let db = Database::open(&dir.join(DATABASE_FILE), key.as_deref())?;
+    // Nothing is sending yet; a claim left by a run that quit mid-flush
+    // would otherwise hold the outbox until it lapses
+    db.with_conn(|conn| outbox::release(conn))?;
+    Ok(db)
+}
+
+/// The SQLCipher key the database is encrypted with, if local encryption is on
+pub fn current_key(app: &AppHandle) -> Result<Option<String>, String> {
+    if !settings::load(app)?.security.encrypt_local_data {
+        return Ok(None);
+    }
+
+    keychain::get(DATABASE_KEY_ACCOUNT)?
+        .map(Some)
+        .ok_or_else(|| "Local database is encrypted but its key is missing from the keychain".to_string())
+}
+
+/// Encrypt the existing database in place with a new keychain-held key
+pub fn enable_encryption(db: &Database) -> Result<(), String> {
+    let key = encode_hex(&random_key()?);
+    keychain::set(DATABASE_KEY_ACCOUNT, &key)?;
+    db.change_key(Some(&key))
+}
+
+/// Decrypt the database in place and drop its key from the keychain
+pub fn disable_encryption(db: &Database) -> Result<(), String> {
+    db.change_key(None)?;
+    keychain::delete(DATABASE_KEY_ACCOUNT)
+}
+
+fn random_key() -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key).map_err(|e| format!("No secure randomness: {}", e))?;
+    Ok(key)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,188 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::rituals::Ritual;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TemplateKind {
+    /// A reusable task with its subtasks
+    Task,
+    /// The steps of a ritual, created as one task whose subtasks are the
+    /// checklist
+    Checklist,
+}
+
+impl TemplateKind {
+    fn key(self) -> &'static str {
+        match self {
+            TemplateKind::Task => "task",
+            TemplateKind::Checklist => "checklist",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        match key {
+            "checklist" => TemplateKind::Checklist,
+            _ => TemplateKind::Task,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSubtask {
+    pub title: String,
+    /// Due this many days after the day the template is used for
+    #[serde(default)]
+    pub offset_days: Option<i64>,
+}
+
+/// A task, or a ritual's checklist, to be created again on any day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Template {
+    /// Empty for a new template
+    #[serde(default)]
+    pub id: String,
+    pub kind: TemplateKind,
+    /// Shown in the template picker
+    pub name: String,
+    /// Title of the created task
+    pub title: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub estimated_mins: Option<i64>,
+    /// The created task is due this many days after the day it's for
+    #[serde(default)]
+    pub due_offset_days: Option<i64>,
+    #[serde(default)]
+    pub subtasks: Vec<TemplateSubtask>,
+    /// Checklists only: created by the daily rollover on every working day
+    #[serde(default)]
+    pub ritual: Option<Ritual>,
+    /// Unix milliseconds
+    #[serde(default)]
+    pub created_at: i64,
+    /// Unix milliseconds
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+const COLUMNS: &str = "id, kind, name, title, notes, estimated_mins, due_offset_days, subtasks, \
+                       ritual, created_at, updated_at";
+
+pub fn upsert(conn: &Connection, template: &Template) -> rusqlite::Result<()> {
+    let subtasks = serde_json::to_string(&template.subtasks)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        &format!(
+            "INSERT INTO templates ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT(id) DO UPDATE SET
+                kind = excluded.kind,
+                name = excluded.name,
+                title = excluded.title,
+                notes = excluded.notes,
+                estimated_mins = excluded.estimated_mins,
+                due_offset_days = excluded.due_offset_days,
+                subtasks = excluded.subtasks,
+                ritual = excluded.ritual,
+                updated_at = excluded.updated_at",
+            COLUMNS
+        ),
+        params![
+            template.id,
+            template.kind.key(),
+            template.name,
+            template.title,
+            template.notes,
+            template.estimated_mins,
+            template.due_offset_days,
+            subtasks,
+            template.ritual.map(Ritual::key),
+            template.created_at,
+            template.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM template_runs WHERE template_id = ?1",
+        params![id],
+    )?;
+    conn.execute("DELETE FROM templates WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<Template>> {
+    conn.query_row(
+        &format!("SELECT {} FROM templates WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+/// All templates, by name
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<Template>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM templates ORDER BY name COLLATE NOCASE, created_at",
+        COLUMNS
+    ))?;
+    let templates = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(templates)
+}
+
+/// The task created from a template for `date`, if any
+pub fn run_task(
+    conn: &Connection,
+    template_id: &str,
+    date: &str,
+) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT task_id FROM template_runs WHERE template_id = ?1 AND date = ?2",
+        params![template_id, date],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Record the task created from a template for `date`; the latest one wins
+pub fn record_run(
+    conn: &Connection,
+    template_id: &str,
+    date: &str,
+    task_id: &str,
+    created_at: i64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO template_runs (template_id, date, task_id, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![template_id, date, task_id, created_at],
+    )?;
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Template> {
+    let kind: String = row.get(1)?;
+    let subtasks: String = row.get(7)?;
+    let ritual: Option<String> = row.get(8)?;
+    Ok(Template {
+        id: row.get(0)?,
+        kind: TemplateKind::from_key(&kind),
+        name: row.get(2)?,
+        title: row.get(3)?,
+        notes: row.get(4)?,
+        estimated_mins: row.get(5)?,
+        due_offset_days: row.get(6)?,
+        subtasks: serde_json::from_str(&subtasks).unwrap_or_default(),
+        ritual: ritual.as_deref().and_then(Ritual::from_key),
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
@@ -0,0 +1,105 @@
+//! Session bus service for Linux: `org.opensunsama.App` at
+//! `/org/opensunsama/App`, so GNOME extensions, i3/sway bindings and scripts
+//! can drive the app, e.g.
+//!
+//! ```sh
+//! gdbus call --session --dest org.opensunsama.App \
+//!     --object-path /org/opensunsama/App \
+//!     --method org.opensunsama.App.AddTask "Review PR"
+//! ```
+//!
+//! Methods go through the same actions as `opensunsama://` links. The same
+//! connection also serves the GNOME Shell search provider.
+
+use tauri::AppHandle;
+
+#[cfg(target_os = "linux")]
+mod search_provider;
+
+/// Claim the bus name and serve until the app exits. Does nothing off Linux.
+pub fn start(app: &AppHandle) {
+    platform::start(app)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use tauri::{AppHandle, Manager};
+    use zbus::{fdo, interface, Connection};
+
+    use super::search_provider::{self, SearchProvider};
+    use crate::automation::{self, Action};
+
+    const BUS_NAME: &str = "org.opensunsama.App";
+    const OBJECT_PATH: &str = "/org/opensunsama/App";
+
+    pub fn start(app: &AppHandle) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            match serve(app).await {
+                // The service lives as long as the connection
+                Ok(_connection) => std::future::pending::<()>().await,
+                Err(e) => tracing::warn!("Failed to start D-Bus service: {}", e),
+            }
+        });
+    }
+
+    struct AppService {
+        app: AppHandle,
+    }
+
+    #[interface(name = "org.opensunsama.App")]
+    impl AppService {
+        /// Create a task; returns its id
+        async fn add_task(&self, title: String) -> fdo::Result<String> {
+            if title.trim().is_empty() {
+                return Err(fdo::Error::InvalidArgs("Title is empty".to_string()));
+            }
+            let action = Action::AddTask {
+                title: title.trim().to_string(),
+                notes: None,
+                scheduled_date: None,
+            };
+            automation::perform(&self.app, action)
+                .await
+                .map(Option::unwrap_or_default)
+                .map_err(fdo::Error::Failed)
+        }
+
+        /// Start the timer, on `task_id` or the current task when empty
+        async fn start_timer(&self, task_id: String) -> fdo::Result<()> {
+            let task_id = Some(task_id).filter(|id| !id.is_empty());
+            automation::perform(&self.app, Action::StartTimer { task_id })
+                .await
+                .map(|_| ())
+                .map_err(fdo::Error::Failed)
+        }
+
+        /// Show or hide the main window
+        fn toggle(&self) {
+            if let Some(window) = self.app.get_webview_window("main") {
+                if window.is_visible().unwrap_or(false) {
+                    let _ = window.hide();
+                } else {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+        }
+    }
+
+    async fn serve(app: AppHandle) -> zbus::Result<Connection> {
+        zbus::connection::Builder::session()?
+            .name(BUS_NAME)?
+            .serve_at(OBJECT_PATH, AppService { app: app.clone() })?
+            .serve_at(search_provider::OBJECT_PATH, SearchProvider::new(app))?
+            .build()
+            .await
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use tauri::AppHandle;
+
+    pub fn start(_app: &AppHandle) {}
+}
@@ -0,0 +1,141 @@
+//! GNOME Shell search provider (`org.gnome.Shell.SearchProvider2`). Typing
+//! in the Activities overview lists matching open tasks; picking one brings
+//! the app forward on that task. Shell finds the provider through
+//! `linux/org.opensunsama.App.search-provider.ini`, installed by the package.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+use zbus::interface;
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::api::Api;
+use crate::app_lock::AppLockState;
+
+pub const OBJECT_PATH: &str = "/org/opensunsama/SearchProvider";
+
+/// Shell queries on every keystroke, so tasks are fetched at most this often
+const CACHE_TTL: Duration = Duration::from_secs(60);
+const MAX_RESULTS: usize = 8;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskSummary {
+    id: String,
+    title: String,
+    notes: Option<String>,
+    scheduled_date: Option<String>,
+    completed_at: Option<String>,
+}
+
+pub struct SearchProvider {
+    app: AppHandle,
+    cache: Mutex<Option<(Instant, Vec<TaskSummary>)>>,
+}
+
+impl SearchProvider {
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            app,
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Open tasks, refreshed when the cache is stale. Empty when signed out,
+    /// offline or locked, since Shell shows whatever is returned.
+    async fn tasks(&self) -> Vec<TaskSummary> {
+        if self.app.state::<AppLockState>().is_locked() {
+            return Vec::new();
+        }
+
+        let mut cache = self.cache.lock().await;
+        if let Some((fetched_at, tasks)) = cache.as_ref() {
+            if fetched_at.elapsed() < CACHE_TTL {
+                return tasks.clone();
+            }
+        }
+
+        let Ok(api) = Api::new(&self.app) else {
+            return Vec::new();
+        };
+        match api.get_all::<TaskSummary>("/tasks").await {
+            Ok(tasks) => {
+                let open: Vec<TaskSummary> = tasks.into_iter().filter(|task| task.completed_at.is_none()).collect();
+                *cache = Some((Instant::now(), open.clone()));
+                open
+            }
+            Err(_) => cache.as_ref().map(|(_, tasks)| tasks.clone()).unwrap_or_default(),
+        }
+    }
+
+    async fn search(&self, terms: &[String], within: Option<&[String]>) -> Vec<String> {
+        let terms: Vec<String> = terms.iter().map(|term| term.to_lowercase()).collect();
+        self.tasks()
+            .await
+            .into_iter()
+            .filter(|task| within.map_or(true, |ids| ids.contains(&task.id)))
+            .filter(|task| {
+                let title = task.title.to_lowercase();
+                terms.iter().all(|term| title.contains(term.as_str()))
+            })
+            .take(MAX_RESULTS)
+            .map(|task| task.id)
+            .collect()
+    }
+
+    fn show_and_emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Some(window) = self.app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+            let _ = window.emit(event, payload);
+        }
+    }
+}
+
+#[interface(name = "org.gnome.Shell.SearchProvider2")]
+impl SearchProvider {
+    async fn get_initial_result_set(&self, terms: Vec<String>) -> Vec<String> {
+        self.search(&terms, None).await
+    }
+
+    async fn get_subsearch_result_set(&self, previous_results: Vec<String>, terms: Vec<String>) -> Vec<String> {
+        self.search(&terms, Some(&previous_results)).await
+    }
+
+    async fn get_result_metas(&self, identifiers: Vec<String>) -> Vec<HashMap<String, OwnedValue>> {
+        let tasks = self.tasks().await;
+        identifiers
+            .iter()
+            .filter_map(|id| tasks.iter().find(|task| &task.id == id))
+            .map(|task| {
+                let description = match (&task.scheduled_date, &task.notes) {
+                    (Some(date), _) => format!("Scheduled {}", date),
+                    (None, Some(notes)) => notes.lines().next().unwrap_or_default().to_string(),
+                    (None, None) => "Backlog".to_string(),
+                };
+
+                let mut meta = HashMap::new();
+                for (key, value) in [
+                    ("id", task.id.clone()),
+                    ("name", task.title.clone()),
+                    ("description", description),
+                ] {
+                    if let Ok(value) = OwnedValue::try_from(Value::from(value)) {
+                        meta.insert(key.to_string(), value);
+                    }
+                }
+                meta
+            })
+            .collect()
+    }
+
+    fn activate_result(&self, identifier: String, _terms: Vec<String>, _timestamp: u32) {
+        self.show_and_emit("open-task", identifier);
+    }
+
+    fn launch_search(&self, terms: Vec<String>, _timestamp: u32) {
+        self.show_and_emit("search-tasks", terms.join(" "));
+    }
+}
@@ -0,0 +1,318 @@
+//! Demo data for screenshots, demos and frontend work without a backend:
+//! two weeks of tasks across a few channels around today, time tracked on
+//! the finished ones, planned task blocks and a few recurring meetings, all
+//! written to a profile's local database. Every row's id starts with
+//! `demo-`, so seeding again replaces the set and clearing removes only it.
+//! Signing in replaces the local task copy on the next full download.
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clock::ClockState;
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::time_entries::{self, TimeEntry};
+use crate::db::{self, Database, DATABASE_FILE};
+use crate::{data_dir, profiles, timezone, weekly_review};
+
+const PREFIX: &str = "demo-";
+
+/// Whether `id` belongs to demo data, which stays on this device
+pub fn is_demo(id: &str) -> bool {
+    id.starts_with(PREFIX)
+}
+
+/// Title, channel and estimate in minutes
+const TASKS: [(&str, &str, u32); 16] = [
+    ("Review pull requests", "engineering", 45),
+    ("Write release notes", "engineering", 30),
+    ("Fix flaky sync test", "engineering", 60),
+    ("Draft Q3 roadmap", "planning", 90),
+    ("Reply to customer emails", "support", 30),
+    ("Prepare design review deck", "design", 60),
+    ("Update onboarding docs", "docs", 45),
+    ("Expense report", "admin", 15),
+    ("Interview debrief", "hiring", 30),
+    ("Triage bug reports", "support", 45),
+    ("Sketch settings redesign", "design", 75),
+    ("Read chapter 4 of DDIA", "learning", 40),
+    ("Plan team offsite", "planning", 30),
+    ("Profile slow calendar view", "engineering", 90),
+    ("Renew domain and certificates", "admin", 15),
+    ("Write blog post outline", "docs", 45),
+];
+
+/// Day, title, start and length in minutes; all days get a standup
+const MEETINGS: [(Weekday, &str, (u32, u32), u32); 3] = [
+    (Weekday::Mon, "Weekly planning", (16, 0), 45),
+    (Weekday::Wed, "Design review", (16, 0), 60),
+    (Weekday::Thu, "1:1 with Alex", (16, 0), 30),
+];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemoSummary {
+    pub profile: String,
+    pub tasks: usize,
+    pub time_entries: usize,
+    pub events: usize,
+    pub task_blocks: usize,
+}
+
+/// Replace the demo data of `profile` (default: the active one). Emits
+/// `demo-data-changed` when that's the active profile.
+pub fn seed(app: &AppHandle, profile: Option<&str>) -> Result<DemoSummary, String> {
+    let clock = app.state::<ClockState>();
+    let (today, now) = (clock.today(), clock.now_millis());
+    let (profile, mut summary) = with_profile_db(app, profile, |conn| {
+        let tx = conn.transaction()?;
+        clear_rows(&tx)?;
+        let summary = seed_rows(&tx, today, now, timezone::local())?;
+        tx.commit()?;
+        Ok(summary)
+    })?;
+    summary.profile = profile;
+    changed(app, &summary);
+    Ok(summary)
+}
+
+/// Remove the demo data of `profile`, leaving everything else
+pub fn clear(app: &AppHandle, profile: Option<&str>) -> Result<(), String> {
+    let (profile, ()) = with_profile_db(app, profile, |conn| {
+        let tx = conn.transaction()?;
+        clear_rows(&tx)?;
+        tx.commit()
+    })?;
+    changed(
+        app,
+        &DemoSummary {
+            profile,
+            ..DemoSummary::default()
+        },
+    );
+    Ok(())
+}
+
+fn changed(app: &AppHandle, summary: &DemoSummary) {
+    if summary.profile == data_dir::profile(app) {
+        let _ = app.emit("demo-data-changed", summary);
+    }
+}
+
+/// Run `f` on the database of `profile`: the open one for the active
+/// profile, otherwise the profile's file, which must not be encrypted
+fn with_profile_db<T>(
+    app: &AppHandle,
+    profile: Option<&str>,
+    f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+) -> Result<(String, T), String> {
+    let active = data_dir::profile(app);
+    let id = profile
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .unwrap_or(&active)
+        .to_string();
+    if id == active {
+        return app
+            .state::<Database>()
+            .with_conn(f)
+            .map(|value| (id, value));
+    }
+
+    let root = data_dir::root(app)?;
+    if !profiles::exists(&root, &id) {
+        return Err(format!("Unknown profile {}", id));
+    }
+    let db = Database::open(&profiles::dir(&root, &id).join(DATABASE_FILE), None).map_err(|e| {
+        format!(
+            "Couldn't open the database of profile {} ({}); encrypted profiles can only be seeded while active",
+            id, e
+        )
+    })?;
+    db.with_conn(f).map(|value| (id, value))
+}
+
+fn clear_rows(conn: &Connection) -> rusqlite::Result<()> {
+    db::tasks::delete_prefixed(conn, PREFIX)?;
+    time_entries::delete_prefixed(conn, PREFIX)?;
+    time_blocks::delete_prefixed(conn, PREFIX)?;
+    Ok(())
+}
+
+/// Write the weekdays of last week and this week relative to `today`.
+/// Tasks that ended before `now` are done with their time tracked; the
+/// rest are open with time planned for them.
+fn seed_rows(
+    conn: &Connection,
+    today: NaiveDate,
+    now: i64,
+    tz: Tz,
+) -> rusqlite::Result<DemoSummary> {
+    let mut summary = DemoSummary::default();
+    let first = weekly_review::week_start(today) - Days::new(7);
+    let days = (0..14)
+        .filter_map(|offset| first.checked_add_days(Days::new(offset)))
+        .filter(|date| date.weekday().num_days_from_monday() < 5);
+
+    for (day, date) in days.enumerate() {
+        let at = |hour, minute| {
+            tz.from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(hour, minute, 0)?))
+                .earliest()
+        };
+        let Some(created) = at(8, 0).and_then(|at| at.checked_sub_days(Days::new(1))) else {
+            continue;
+        };
+
+        let mut meetings = vec![("Standup", (9, 30), 15)];
+        meetings.extend(
+            MEETINGS
+                .iter()
+                .filter(|(weekday, ..)| *weekday == date.weekday())
+                .map(|(_, title, start, minutes)| (*title, *start, *minutes)),
+        );
+        for (index, (title, (hour, minute), minutes)) in meetings.into_iter().enumerate() {
+            let Some(start) = at(hour, minute) else {
+                continue;
+            };
+            time_blocks::upsert(
+                conn,
+                &TimeBlock {
+                    id: format!("{}event-{}-{}", PREFIX, date, index),
+                    task_id: None,
+                    title: title.to_string(),
+                    start_at: start.timestamp_millis(),
+                    end_at: start.timestamp_millis() + i64::from(minutes) * 60_000,
+                    timezone: tz.name().to_string(),
+                    is_event: true,
+                    location: Some("Video call".to_string()),
+                    description: None,
+                    updated_at: 0,
+                },
+            )?;
+            summary.events += 1;
+        }
+
+        // Tasks run back to back from 10:00 with a short break between,
+        // skipping lunch
+        let Some(mut cursor) = at(10, 0) else {
+            continue;
+        };
+        let lunch = at(12, 0).zip(at(13, 0));
+        for slot in 0..3 + day % 2 {
+            let (title, channel, minutes) = TASKS[(day * 3 + slot) % TASKS.len()];
+            if let Some((_, afternoon)) =
+                lunch.filter(|(noon, one)| cursor >= *noon && cursor < *one)
+            {
+                cursor = afternoon;
+            }
+            let start = cursor.timestamp_millis();
+            let end = start + i64::from(minutes) * 60_000;
+            cursor = cursor + chrono::Duration::minutes(i64::from(minutes) + 15);
+
+            let id = format!("{}task-{}-{}", PREFIX, date, slot);
+            let done = end <= now;
+            let completed_at = done
+                .then(|| DateTime::from_timestamp_millis(end))
+                .flatten()
+                .map(|at| at.with_timezone(&tz).to_rfc3339());
+            let updated_at = completed_at.clone().unwrap_or_else(|| created.to_rfc3339());
+            db::tasks::upsert(
+                conn,
+                &json!({
+                    "id": id,
+                    "title": title,
+                    "notes": format!("#{}", channel),
+                    "scheduledDate": date.to_string(),
+                    "estimatedMins": minutes,
+                    "completedAt": completed_at,
+                    "createdAt": created.to_rfc3339(),
+                    "updatedAt": updated_at,
+                }),
+            )?;
+            summary.tasks += 1;
+
+            time_blocks::upsert(
+                conn,
+                &TimeBlock {
+                    id: format!("{}block-{}-{}", PREFIX, date, slot),
+                    task_id: Some(id.clone()),
+                    title: title.to_string(),
+                    start_at: start,
+                    end_at: end,
+                    timezone: tz.name().to_string(),
+                    is_event: false,
+                    location: None,
+                    description: None,
+                    updated_at: 0,
+                },
+            )?;
+            summary.task_blocks += 1;
+
+            if done {
+                // Somewhere between 80% and 120% of the estimate
+                let tracked = i64::from(minutes) * (8 + (day + slot) as i64 % 5) / 10;
+                time_entries::insert(
+                    conn,
+                    &TimeEntry {
+                        id: format!("{}entry-{}-{}", PREFIX, date, slot),
+                        task_id: id,
+                        task_title: title.to_string(),
+                        started_at: start,
+                        ended_at: Some(start + tracked * 60_000),
+                        updated_at: 0,
+                    },
+                )?;
+                summary.time_entries += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn count(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn seeding_again_replaces_the_demo_rows_and_clear_leaves_the_rest() {
+        let (_dir, db) = testing::database();
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let now = Tz::UTC
+            .from_local_datetime(&today.and_hms_opt(11, 0, 0).unwrap())
+            .unwrap()
+            .timestamp_millis();
+
+        db.with_conn(|conn| {
+            db::tasks::upsert(conn, &json!({ "id": "real", "title": "Keep me" }))?;
+            let first = seed_rows(conn, today, now, Tz::UTC)?;
+            assert_eq!(first.tasks, 35);
+            assert_eq!(first.events, 16);
+            assert!(first.time_entries > 0 && first.time_entries < first.tasks);
+            assert_eq!(count(conn, "tasks"), 36);
+
+            clear_rows(conn)?;
+            let second = seed_rows(conn, today, now, Tz::UTC)?;
+            assert_eq!(second.tasks, first.tasks);
+            assert_eq!(count(conn, "tasks"), 36);
+            assert_eq!(count(conn, "time_entries") as usize, second.time_entries);
+
+            clear_rows(conn)?;
+            assert_eq!(count(conn, "tasks"), 1);
+            assert_eq!(count(conn, "time_blocks"), 0);
+            assert_eq!(count(conn, "time_entries"), 0);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! Task dependencies: task A blocks task B until A is done. Dependencies
+//! live in the local database and can't form a cycle. A task is blocked
+//! while any of its blockers is unfinished on the server, and the
+//! auto-scheduler never places a task's block before its blockers' blocks
+//! end.
+
+use chrono::NaiveDate;
+use opensunsama_core::task::is_completed;
+use rusqlite::{Connection, TransactionBehavior};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::Api;
+use crate::clock;
+use crate::db::dependencies;
+use crate::db::Database;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDependencies {
+    pub task_id: String,
+    /// Tasks that have to be done first
+    pub blocked_by: Vec<String>,
+    /// Tasks waiting on this one
+    pub blocks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedTask {
+    pub task_id: String,
+    pub title: String,
+    /// Unfinished blockers
+    pub blocked_by: Vec<String>,
+}
+
+pub fn get(app: &AppHandle, task_id: &str) -> Result<TaskDependencies, String> {
+    app.state::<Database>().with_conn(|conn| {
+        Ok(TaskDependencies {
+            task_id: task_id.to_string(),
+            blocked_by: dependencies::blockers(conn, task_id)?,
+            blocks: dependencies::blocking(conn, task_id)?,
+        })
+    })
+}
+
+/// Make `blocker_id` block `blocked_id`. Refused when `blocked_id` already
+/// blocks `blocker_id`, directly or through other tasks. Emits
+/// `dependencies-changed`.
+pub fn add(app: &AppHandle, blocker_id: &str, blocked_id: &str) -> Result<(), String> {
+    if blocker_id == blocked_id {
+        return Err("A task can't block itself".to_string());
+    }
+    // The cycle check and the insert share a write transaction, so two adds
+    // can't each pass the check and close a cycle together
+    let added = app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        if reaches(&tx, blocked_id, blocker_id)? {
+            return Ok(false);
+        }
+        dependencies::add(&tx, blocker_id, blocked_id, clock::now_millis())?;
+        tx.commit()?;
+        Ok(true)
+    })?;
+    if !added {
+        return Err("That dependency would make a cycle".to_string());
+    }
+    let _ = app.emit("dependencies-changed", ());
+    Ok(())
+}
+
+pub fn remove(app: &AppHandle, blocker_id: &str, blocked_id: &str) -> Result<(), String> {
+    app.state::<Database>()
+        .with_conn(|conn| dependencies::remove(conn, blocker_id, blocked_id))?;
+    let _ = app.emit("dependencies-changed", ());
+    Ok(())
+}
+
+/// The unfinished tasks scheduled for `date` that wait on unfinished
+/// tasks. Blockers deleted on the server count as done.
+pub async fn blocked_tasks(app: &AppHandle, date: NaiveDate) -> Result<Vec<BlockedTask>, String> {
+    let api = Api::new(app)?;
+    let tasks: Vec<Value> = api
+        .get_all(&format!("/tasks?scheduledDate={}", date))
+        .await?;
+    let mut done: HashMap<String, bool> = tasks
+        .iter()
+        .filter_map(|task| Some((task.get("id")?.as_str()?.to_string(), is_completed(task))))
+        .collect();
+
+    let db = app.state::<Database>();
+    let mut blocked = Vec::new();
+    for task in tasks.iter().filter(|task| !is_completed(task)) {
+        let Some(task_id) = task.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+        let mut blocked_by = Vec::new();
+        for blocker_id in db.with_conn(|conn| dependencies::blockers(conn, task_id))? {
+            let finished = match done.get(&blocker_id) {
+                Some(finished) => *finished,
+                None => {
+                    let finished = match api.get::<Value>(&format!("/tasks/{}", blocker_id)).await {
+                        Ok(blocker) => is_completed(&blocker),
+                        Err(e) if e.is_not_found() => true,
+                        Err(e) => return Err(format!("Failed to load blocking task: {}", e)),
+                    };
+                    done.insert(blocker_id.clone(), finished);
+                    finished
+                }
+            };
+            if !finished {
+                blocked_by.push(blocker_id);
+            }
+        }
+
+        if !blocked_by.is_empty() {
+            blocked.push(BlockedTask {
+                task_id: task_id.to_string(),
+                title: task
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                blocked_by,
+            });
+        }
+    }
+    Ok(blocked)
+}
+
+/// Whether `to` waits on `from`, directly or through other tasks
+fn reaches(conn: &Connection, from: &str, to: &str) -> rusqlite::Result<bool> {
+    let mut seen = HashSet::from([from.to_string()]);
+    let mut queue = VecDeque::from([from.to_string()]);
+    while let Some(task_id) = queue.pop_front() {
+        for next in dependencies::blocking(conn, &task_id)? {
+            if next == to {
+                return Ok(true);
+            }
+            if seen.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(false)
+}
@@ -0,0 +1,146 @@
+//! The OS Do Not Disturb / Focus state, so non-urgent notifications can be
+//! held until it ends instead of being swallowed by the system.
+//!
+//! Windows reports it through `SHQueryUserNotificationState` (Focus Assist,
+//! presentations and full-screen apps). On Linux, KDE exposes an `Inhibited`
+//! property on the notification server and GNOME a `show-banners` setting.
+//! macOS has no public API; the Focus assertions file is read when the app
+//! is allowed to, and the state is reported as unknown otherwise.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DndStatus {
+    /// Whether this platform's DND state could be read
+    pub supported: bool,
+    pub active: bool,
+}
+
+#[derive(Default)]
+pub struct DndState {
+    status: Mutex<DndStatus>,
+}
+
+impl DndState {
+    pub fn status(&self) -> DndStatus {
+        self.status.lock().map(|status| *status).unwrap_or_default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status().active
+    }
+}
+
+/// Poll the OS state, emitting `dnd-changed` when it flips
+pub fn start_monitor(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let status = match platform::is_active().await {
+                Some(active) => DndStatus {
+                    supported: true,
+                    active,
+                },
+                None => DndStatus::default(),
+            };
+            let changed = match app.state::<DndState>().status.lock() {
+                Ok(mut current) if *current != status => {
+                    *current = status;
+                    true
+                }
+                _ => false,
+            };
+            if changed {
+                let _ = app.emit("dnd-changed", status);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS, QUNS_APP};
+
+    pub async fn is_active() -> Option<bool> {
+        let state = unsafe { SHQueryUserNotificationState() }.ok()?;
+        Some(state != QUNS_ACCEPTS_NOTIFICATIONS && state != QUNS_APP)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use zbus::Connection;
+
+    pub async fn is_active() -> Option<bool> {
+        if let Some(inhibited) = kde_inhibited().await {
+            return Some(inhibited);
+        }
+        gnome_banners_hidden()
+    }
+
+    async fn kde_inhibited() -> Option<bool> {
+        let connection = Connection::session().await.ok()?;
+        let proxy = zbus::Proxy::new(
+            &connection,
+            "org.freedesktop.Notifications",
+            "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications",
+        )
+        .await
+        .ok()?;
+        proxy.get_property::<bool>("Inhibited").await.ok()
+    }
+
+    fn gnome_banners_hidden() -> Option<bool> {
+        let output = std::process::Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        match String::from_utf8_lossy(&output.stdout).trim() {
+            "true" => Some(false),
+            "false" => Some(true),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use serde_json::Value;
+    use std::path::PathBuf;
+
+    /// Active Focus modes are recorded as assertions here. Reading it needs
+    /// Full Disk Access on recent macOS versions; without it the state is
+    /// unknown. Scheduled Focus modes without an assertion aren't seen.
+    const ASSERTIONS_FILE: &str = "Library/DoNotDisturb/DB/Assertions.json";
+
+    pub async fn is_active() -> Option<bool> {
+        let path = PathBuf::from(std::env::var_os("HOME")?).join(ASSERTIONS_FILE);
+        let json: Value = serde_json::from_slice(&std::fs::read(path).ok()?).ok()?;
+        let records = json
+            .get("data")?
+            .as_array()?
+            .iter()
+            .filter_map(|entry| entry.get("storeAssertionRecords").and_then(Value::as_array))
+            .map(Vec::len)
+            .sum::<usize>();
+        Some(records > 0)
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+mod platform {
+    pub async fn is_active() -> Option<bool> {
+        None
+    }
+}
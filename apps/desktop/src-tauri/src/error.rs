@@ -0,0 +1,125 @@
+//! The error every command returns, so the frontend can branch on `code`
+//! instead of matching message text. Errors from the server and the network
+//! are built typed where they happen (`api`, `http`); everything else native
+//! modules report as a string is `Internal`.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri_plugin_http::reqwest::StatusCode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCode {
+    /// Nobody is signed in
+    NotSignedIn,
+    /// The server no longer accepts the session
+    AuthExpired,
+    /// The server couldn't be reached
+    Offline,
+    /// Changed elsewhere since it was read
+    Conflict,
+    NotFound,
+    RateLimited,
+    /// The server failed to handle the request
+    Server,
+    /// The request or one of its values was rejected
+    InvalidInput,
+    /// The app or its encryption is locked
+    Locked,
+    Internal,
+}
+
+impl ErrorCode {
+    /// Whether the same request can succeed later
+    fn retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::Offline | ErrorCode::RateLimited | ErrorCode::Server
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("{message}")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub retryable: bool,
+    /// Extra context, e.g. the server's own error code
+    pub details: Option<Value>,
+    /// The request may have reached the server before it failed, so
+    /// sending it again could repeat a write
+    #[serde(skip)]
+    pub maybe_delivered: bool,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: code.retryable(),
+            details: None,
+            maybe_delivered: false,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// An error response from the server, classified by its status and the
+    /// server's own error code when the body carried one
+    pub fn from_response(status: StatusCode, server_code: Option<String>, message: impl Into<String>) -> Self {
+        let code = match (status.as_u16(), server_code.as_deref()) {
+            (401, _) | (_, Some("UNAUTHORIZED" | "TOKEN_EXPIRED")) => ErrorCode::AuthExpired,
+            (404, _) | (_, Some("NOT_FOUND")) => ErrorCode::NotFound,
+            (409, _) | (_, Some("CONFLICT")) => ErrorCode::Conflict,
+            (429, _) | (_, Some("RATE_LIMITED" | "TOO_MANY_REQUESTS")) => ErrorCode::RateLimited,
+            (500..=599, _) | (_, Some("INTERNAL_ERROR")) => ErrorCode::Server,
+            _ => ErrorCode::InvalidInput,
+        };
+
+        let error = AppError::new(code, message);
+        match server_code {
+            Some(server_code) => error.with_details(json!({ "serverCode": server_code })),
+            None => error,
+        }
+    }
+
+    pub fn maybe_delivered(mut self) -> Self {
+        self.maybe_delivered = true;
+        self
+    }
+
+    /// Prefix the message with what was being done, keeping the code
+    pub fn context(mut self, doing: &str) -> Self {
+        self.message = format!("{}: {}", doing, self.message);
+        self
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.code == ErrorCode::NotFound
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+/// For modules that still report errors as strings
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.message
+    }
+}
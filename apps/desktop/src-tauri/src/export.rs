@@ -0,0 +1,427 @@
+//! Full data export and import, so data can move between self-hosted
+//! instances or leave Open Sunsama entirely. The archive is a zip of plain
+//! JSON plus attachment files, described by the README written into it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+use zip::ZipArchive;
+
+use crate::api::Api;
+use crate::clock;
+use crate::error::{AppError, ErrorCode};
+use crate::settings::{self, transfer};
+use crate::sync::SERVER_FIELDS;
+use crate::zipfile::{self, sha256_hex};
+
+const EXPORT_FORMAT: &str = "open-sunsama-export";
+const EXPORT_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const README_ENTRY: &str = "README.md";
+const TASKS_ENTRY: &str = "tasks.json";
+const TIME_BLOCKS_ENTRY: &str = "time-blocks.json";
+const ATTACHMENTS_ENTRY: &str = "attachments.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+
+const README: &str = r#"# Open Sunsama data export
+
+Everything is plain JSON (UTF-8) so it can be read without Open Sunsama.
+
+| File                          | Contents                                                       |
+| ----------------------------- | -------------------------------------------------------------- |
+| `manifest.json`               | Format name and version, export time, source server, counts,  |
+|                               | and a SHA-256 checksum of every other file                     |
+| `tasks.json`                  | Every task exactly as the API returns it, including notes.     |
+|                               | Each task has a `subtasks` array                               |
+| `time-blocks.json`            | Every time block; `taskId` refers to `tasks.json`              |
+| `attachments.json`            | Attachment metadata; `archivePath` points at the file          |
+| `attachments/<task>/<file>`   | Attachment contents                                            |
+| `settings.json`               | Desktop app settings (secrets such as proxy passwords omitted) |
+
+Ids are the ids on the source server. Importing into another server
+creates new records and rewrites `taskId` references to match.
+"#;
+
+/// What to do when an imported record's id already exists on the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictStrategy {
+    /// Keep the server's version
+    Skip,
+    /// Replace the server's version with the archive's
+    Overwrite,
+    /// Import as a new record alongside the existing one
+    Duplicate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    format: String,
+    version: u32,
+    exported_at: i64,
+    app_version: String,
+    source_server: String,
+    counts: BTreeMap<String, usize>,
+    checksums: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub tasks: usize,
+    pub time_blocks: usize,
+    pub attachments: usize,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub tasks_created: usize,
+    pub tasks_updated: usize,
+    pub tasks_skipped: usize,
+    pub subtasks_created: usize,
+    pub time_blocks_created: usize,
+    pub time_blocks_updated: usize,
+    pub time_blocks_skipped: usize,
+    pub attachments_uploaded: usize,
+    pub settings_imported: bool,
+    /// Records that failed to import; the rest of the import carries on
+    pub failures: Vec<String>,
+}
+
+/// Download everything from the server and write it, with settings, to a
+/// zip at `path`
+pub async fn export_all(app: &AppHandle, path: &Path) -> Result<ExportSummary, String> {
+    let api = Api::new(app)?;
+
+    let mut tasks: Vec<Value> = api.get_all("/tasks").await?;
+    let time_blocks: Vec<Value> = api.get_all("/time-blocks").await?;
+
+    let mut attachments = Vec::new();
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    for task in &mut tasks {
+        let Some(task_id) = record_id(task) else {
+            continue;
+        };
+
+        let subtasks: Vec<Value> = api.get_all(&format!("/tasks/{}/subtasks", task_id)).await?;
+        if let Some(task) = task.as_object_mut() {
+            task.insert("subtasks".to_string(), Value::Array(subtasks));
+        }
+
+        let task_attachments: Vec<Value> =
+            api.get_all(&format!("/uploads?taskId={}", task_id)).await?;
+        for mut attachment in task_attachments {
+            let (Some(id), Some(url)) = (record_id(&attachment), string_field(&attachment, "url"))
+            else {
+                continue;
+            };
+            let name = attachment_name(&attachment);
+            let archive_path = format!("attachments/{}/{}-{}", task_id, id, name);
+
+            files.push((archive_path.clone(), api.download(&url).await?));
+            if let Some(attachment) = attachment.as_object_mut() {
+                attachment.insert("taskId".to_string(), Value::String(task_id.clone()));
+                attachment.insert("archivePath".to_string(), Value::String(archive_path));
+            }
+            attachments.push(attachment);
+        }
+    }
+
+    let current = settings::load(app)?;
+    let mut entries: Vec<(String, Vec<u8>)> = vec![
+        (TASKS_ENTRY.to_string(), to_json(&tasks)?),
+        (TIME_BLOCKS_ENTRY.to_string(), to_json(&time_blocks)?),
+        (ATTACHMENTS_ENTRY.to_string(), to_json(&attachments)?),
+        (SETTINGS_ENTRY.to_string(), transfer::export_json(&current)?.into_bytes()),
+        (README_ENTRY.to_string(), README.as_bytes().to_vec()),
+    ];
+    entries.extend(files);
+
+    let manifest = Manifest {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: clock::now_millis(),
+        app_version: app.package_info().version.to_string(),
+        source_server: api.base_url().to_string(),
+        counts: BTreeMap::from([
+            ("tasks".to_string(), tasks.len()),
+            ("timeBlocks".to_string(), time_blocks.len()),
+            ("attachments".to_string(), attachments.len()),
+        ]),
+        checksums: entries
+            .iter()
+            .map(|(name, bytes)| (name.clone(), sha256_hex(bytes)))
+            .collect(),
+    };
+    entries.insert(0, (MANIFEST_ENTRY.to_string(), to_json(&manifest)?));
+
+    let partial = path.with_extension("partial");
+    zipfile::write(&partial, &entries).inspect_err(|_| {
+        let _ = std::fs::remove_file(&partial);
+    })?;
+    std::fs::rename(&partial, path).map_err(|e| format!("Failed to save export: {}", e))?;
+
+    let size_bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    Ok(ExportSummary {
+        tasks: tasks.len(),
+        time_blocks: time_blocks.len(),
+        attachments: attachments.len(),
+        size_bytes,
+    })
+}
+
+/// Import an export archive into the signed-in account. Every entry is
+/// checked against the manifest before anything is written. Subtasks and
+/// attachments are only imported with tasks that are newly created, so
+/// re-importing the same archive never duplicates them.
+pub async fn import(
+    app: &AppHandle,
+    path: &Path,
+    conflict: ConflictStrategy,
+    include_settings: bool,
+) -> Result<ImportSummary, String> {
+    let mut archive = zipfile::open(path)?;
+    let manifest: Manifest = serde_json::from_slice(&zipfile::read_entry(&mut archive, MANIFEST_ENTRY)?)
+        .map_err(|e| format!("Invalid export manifest: {}", e))?;
+    if manifest.format != EXPORT_FORMAT {
+        return Err("Not an Open Sunsama export".to_string());
+    }
+    if manifest.version > EXPORT_VERSION {
+        return Err("This export was made by a newer version of Open Sunsama".to_string());
+    }
+
+    let tasks = read_records(&mut archive, &manifest, TASKS_ENTRY)?;
+    let time_blocks = read_records(&mut archive, &manifest, TIME_BLOCKS_ENTRY)?;
+    let attachments = read_records(&mut archive, &manifest, ATTACHMENTS_ENTRY)?;
+    let settings_json = zipfile::read_verified(&mut archive, &manifest.checksums, SETTINGS_ENTRY)?;
+
+    let api = Api::new(app)?;
+    let mut summary = ImportSummary::default();
+
+    let existing_tasks = existing_ids(&api, "/tasks").await?;
+    // Source task id -> id on this server, for tasks that exist after import
+    let mut task_ids: HashMap<String, String> = HashMap::new();
+    let mut created_tasks: HashSet<String> = HashSet::new();
+
+    for task in &tasks {
+        let Some(source_id) = record_id(task) else {
+            continue;
+        };
+        let exists = existing_tasks.contains(&source_id);
+        let body = strip_server_fields(task, &["subtasks", "attachments"]);
+
+        let result = match (exists, conflict) {
+            (true, ConflictStrategy::Skip) => {
+                summary.tasks_skipped += 1;
+                task_ids.insert(source_id.clone(), source_id);
+                continue;
+            }
+            (true, ConflictStrategy::Overwrite) => api
+                .patch::<Value, _>(&format!("/tasks/{}", source_id), &body)
+                .await
+                .map(|_| (source_id.clone(), false)),
+            _ => api.post::<Value, _>("/tasks", &body).await.and_then(|created| {
+                record_id(&created)
+                    .map(|id| (id, true))
+                    .ok_or_else(|| AppError::new(ErrorCode::Server, "Server returned a task without an id"))
+            }),
+        };
+
+        match result {
+            Ok((new_id, created)) => {
+                if created {
+                    summary.tasks_created += 1;
+                    created_tasks.insert(source_id.clone());
+                } else {
+                    summary.tasks_updated += 1;
+                }
+                task_ids.insert(source_id, new_id);
+            }
+            Err(e) => summary.failures.push(format!("Task {}: {}", source_id, e)),
+        }
+    }
+
+    for task in &tasks {
+        let Some(source_id) = record_id(task) else {
+            continue;
+        };
+        if !created_tasks.contains(&source_id) {
+            continue;
+        }
+        let new_id = &task_ids[&source_id];
+        let subtasks = task.get("subtasks").and_then(Value::as_array).cloned().unwrap_or_default();
+        for subtask in subtasks {
+            let body = strip_server_fields(&subtask, &["taskId"]);
+            match api
+                .post::<Value, _>(&format!("/tasks/{}/subtasks", new_id), &body)
+                .await
+            {
+                Ok(_) => summary.subtasks_created += 1,
+                Err(e) => summary.failures.push(format!("Subtask of task {}: {}", source_id, e)),
+            }
+        }
+    }
+
+    let existing_blocks = existing_ids(&api, "/time-blocks").await?;
+    for block in &time_blocks {
+        let Some(source_id) = record_id(block) else {
+            continue;
+        };
+        let exists = existing_blocks.contains(&source_id);
+        let mut body = strip_server_fields(block, &[]);
+        if let Some(fields) = body.as_object_mut() {
+            let task_id = string_field(block, "taskId").and_then(|id| task_ids.get(&id).cloned());
+            fields.insert("taskId".to_string(), task_id.map(Value::String).unwrap_or(Value::Null));
+        }
+
+        let result = match (exists, conflict) {
+            (true, ConflictStrategy::Skip) => {
+                summary.time_blocks_skipped += 1;
+                continue;
+            }
+            (true, ConflictStrategy::Overwrite) => api
+                .patch::<Value, _>(&format!("/time-blocks/{}", source_id), &body)
+                .await
+                .map(|_| summary.time_blocks_updated += 1),
+            _ => api
+                .post::<Value, _>("/time-blocks", &body)
+                .await
+                .map(|_| summary.time_blocks_created += 1),
+        };
+        if let Err(e) = result {
+            summary.failures.push(format!("Time block {}: {}", source_id, e));
+        }
+    }
+
+    for attachment in &attachments {
+        let (Some(source_task), Some(archive_path)) = (
+            string_field(attachment, "taskId"),
+            string_field(attachment, "archivePath"),
+        ) else {
+            continue;
+        };
+        if !created_tasks.contains(&source_task) {
+            continue;
+        }
+
+        let result = match zipfile::read_verified(&mut archive, &manifest.checksums, &archive_path) {
+            Ok(bytes) => {
+                let (content_type, body) = multipart_body(
+                    &task_ids[&source_task],
+                    &attachment_name(attachment),
+                    string_field(attachment, "mimeType").as_deref(),
+                    &bytes,
+                );
+                api.post_bytes::<Value>("/uploads", &content_type, body).await
+            }
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(_) => summary.attachments_uploaded += 1,
+            Err(e) => summary.failures.push(format!("Attachment {}: {}", archive_path, e)),
+        }
+    }
+
+    if include_settings {
+        let json = String::from_utf8(settings_json)
+            .map_err(|_| "Exported settings are not valid UTF-8".to_string())?;
+        let current = settings::load(app)?;
+        let imported = transfer::parse_import(&current, &json)?;
+        settings::save(app, &imported)?;
+        settings::apply(app, &imported)?;
+        let _ = app.emit("settings-changed", &imported);
+        summary.settings_imported = true;
+    }
+
+    let _ = app.emit("data-imported", &summary);
+    Ok(summary)
+}
+
+fn read_records(
+    archive: &mut ZipArchive<File>,
+    manifest: &Manifest,
+    name: &str,
+) -> Result<Vec<Value>, String> {
+    let bytes = zipfile::read_verified(archive, &manifest.checksums, name)?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Invalid {}: {}", name, e))
+}
+
+async fn existing_ids(api: &Api, path: &str) -> Result<HashSet<String>, String> {
+    let records: Vec<Value> = match api.get_all(path).await {
+        Ok(records) => records,
+        Err(e) if e.is_not_found() => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(records.iter().filter_map(record_id).collect())
+}
+
+fn record_id(record: &Value) -> Option<String> {
+    match record.get("id")? {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn string_field(record: &Value, field: &str) -> Option<String> {
+    record.get(field).and_then(Value::as_str).map(str::to_string)
+}
+
+/// A copy of `record` without server-assigned fields and `extra`
+fn strip_server_fields(record: &Value, extra: &[&str]) -> Value {
+    let fields: Map<String, Value> = record
+        .as_object()
+        .map(|object| {
+            object
+                .iter()
+                .filter(|(key, _)| !SERVER_FIELDS.contains(&key.as_str()) && !extra.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    Value::Object(fields)
+}
+
+/// A file name safe to use inside the archive
+fn attachment_name(attachment: &Value) -> String {
+    let name = string_field(attachment, "filename")
+        .or_else(|| string_field(attachment, "fileName"))
+        .or_else(|| string_field(attachment, "name"))
+        .unwrap_or_else(|| "file".to_string());
+
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_' | ' ') { c } else { '_' })
+        .collect()
+}
+
+/// Build a `multipart/form-data` body by hand so it stays cloneable for
+/// retries
+fn multipart_body(task_id: &str, file_name: &str, mime_type: Option<&str>, bytes: &[u8]) -> (String, Vec<u8>) {
+    let boundary = format!("open-sunsama-{:x}", clock::now_millis());
+    let mut body = Vec::with_capacity(bytes.len() + 512);
+
+    body.extend_from_slice(
+        format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"taskId\"\r\n\r\n{task_id}\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\n\
+             Content-Type: {mime}\r\n\r\n",
+            b = boundary,
+            mime = mime_type.unwrap_or("application/octet-stream"),
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| format!("Failed to serialize export: {}", e))
+}
@@ -0,0 +1,93 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, PhysicalPosition};
+
+use crate::attachments;
+use crate::importers::ics;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DroppedKind {
+    Email,
+    Calendar,
+    Image,
+    Document,
+    Directory,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedFile {
+    pub path: String,
+    pub name: String,
+    pub kind: DroppedKind,
+    pub mime_type: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilesDropped {
+    pub files: Vec<DroppedFile>,
+    /// Drop point in physical pixels relative to the window
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Handle files dropped on a window: .ics files go straight into the
+/// calendar importer, and everything is announced as `files-dropped`
+pub fn handle_drop(app: &AppHandle, paths: &[PathBuf], position: PhysicalPosition<f64>) {
+    let files = open_files(app, paths);
+    let _ = app.emit(
+        "files-dropped",
+        FilesDropped {
+            files,
+            x: position.x,
+            y: position.y,
+        },
+    );
+}
+
+/// Describe files handed to the app (dropped or passed on the command line),
+/// importing any .ics files into the calendar on the way
+pub fn open_files(app: &AppHandle, paths: &[PathBuf]) -> Vec<DroppedFile> {
+    let files: Vec<DroppedFile> = paths.iter().map(|path| describe(path)).collect();
+
+    for file in files.iter().filter(|file| file.kind == DroppedKind::Calendar) {
+        match ics::import_file(app, Path::new(&file.path)) {
+            Ok(summary) => {
+                let _ = app.emit("calendar-imported", &summary);
+            }
+            Err(e) => {
+                let _ = app.emit("calendar-import-error", e);
+            }
+        }
+    }
+
+    files
+}
+
+fn describe(path: &Path) -> DroppedFile {
+    let mime_type = attachments::mime_type(path);
+    let kind = if path.is_dir() {
+        DroppedKind::Directory
+    } else {
+        match mime_type {
+            "message/rfc822" => DroppedKind::Email,
+            "text/calendar" => DroppedKind::Calendar,
+            m if m.starts_with("image/") => DroppedKind::Image,
+            "application/octet-stream" => DroppedKind::Other,
+            _ => DroppedKind::Document,
+        }
+    };
+
+    DroppedFile {
+        path: path.to_string_lossy().into_owned(),
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        kind,
+        mime_type: mime_type.to_string(),
+    }
+}
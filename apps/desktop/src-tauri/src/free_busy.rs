@@ -0,0 +1,32 @@
+//! Free/busy for one day in the local timezone, worked out by
+//! `opensunsama-core` from the local schedule and the planning settings
+
+use chrono::NaiveDate;
+use tauri::{AppHandle, Manager};
+
+use crate::db::Database;
+use crate::settings;
+use crate::timezone;
+use crate::work_calendar::WorkCalendar;
+
+pub use opensunsama_core::schedule::free_busy::{for_day, Constraints, FreeBusy};
+
+/// Free/busy for `date` (`YYYY-MM-DD`) in the local timezone, with the
+/// planning settings for anything `constraints` leaves unset
+pub fn get(app: &AppHandle, date: &str, constraints: &Constraints) -> Result<FreeBusy, String> {
+    let date = date
+        .parse::<NaiveDate>()
+        .map_err(|e| format!("Invalid date '{}': {}", date, e))?;
+    let planning = settings::load(app)?.planning.work_week();
+    app.state::<Database>().with_conn(|conn| {
+        let calendar = WorkCalendar::read(conn, &planning)?;
+        for_day(
+            conn,
+            date,
+            timezone::local(),
+            &planning,
+            &calendar,
+            constraints,
+        )
+    })
+}
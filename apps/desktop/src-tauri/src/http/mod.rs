@@ -0,0 +1,311 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::{self, NoProxy, Proxy, RequestBuilder, Response};
+use tokio::net::TcpStream;
+
+use crate::error::{AppError, ErrorCode};
+use crate::settings::{self, NetworkSettings, ProxyMode};
+
+pub mod rate_limit;
+pub mod retry;
+pub mod tunnel;
+
+use rate_limit::{RateLimit, TokenBucket};
+use retry::RetryPolicy;
+
+/// Which remote service a request belongs to. Each gets its own rate limit
+/// and retry policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Integration {
+    Backend,
+    Geolocation,
+    Toggl,
+    Harvest,
+    Jira,
+    /// Subscribed ICS calendars, whatever host they're on
+    CalendarFeed,
+}
+
+impl Integration {
+    fn rate_limit(self) -> RateLimit {
+        match self {
+            Integration::Backend => RateLimit {
+                per_second: 10.0,
+                burst: 20,
+            },
+            Integration::Geolocation => RateLimit {
+                per_second: 0.5,
+                burst: 1,
+            },
+            // Toggl asks for no more than one request per second
+            Integration::Toggl => RateLimit {
+                per_second: 1.0,
+                burst: 1,
+            },
+            // Harvest allows 100 requests per 15 seconds
+            Integration::Harvest => RateLimit {
+                per_second: 6.0,
+                burst: 10,
+            },
+            Integration::Jira => RateLimit {
+                per_second: 5.0,
+                burst: 10,
+            },
+            Integration::CalendarFeed => RateLimit {
+                per_second: 2.0,
+                burst: 4,
+            },
+        }
+    }
+
+    fn retry_policy(self) -> RetryPolicy {
+        RetryPolicy::default()
+    }
+}
+
+/// Shared HTTP client for every native request. Rebuilt when the proxy
+/// settings change; `reqwest::Client` is reference counted, so callers clone it.
+pub struct HttpState {
+    client: RwLock<reqwest::Client>,
+    /// What the client was built from, for connections made without it
+    network: RwLock<NetworkSettings>,
+    limiters: Mutex<HashMap<Integration, TokenBucket>>,
+}
+
+impl HttpState {
+    pub fn new(app: &AppHandle) -> Self {
+        let network = settings::load(app).map(|s| s.network).unwrap_or_default();
+        let client = build_client(&network).unwrap_or_default();
+
+        Self {
+            client: RwLock::new(client),
+            network: RwLock::new(network),
+            limiters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How long to wait before `integration` may send, or `None` to go now
+    fn acquire(&self, integration: Integration) -> Option<Duration> {
+        let mut limiters = self.limiters.lock().ok()?;
+        limiters
+            .entry(integration)
+            .or_insert_with(|| TokenBucket::new(integration.rate_limit()))
+            .try_acquire()
+            .err()
+    }
+}
+
+/// Send a request through the integration's rate limiter, retrying with
+/// jittered backoff. Connection failures and 429s are always retried, since
+/// the server never acted on the request; timeouts and 5xx responses only
+/// for idempotent requests, where a second copy can't duplicate a write.
+/// After the last retry the final response is returned as-is for the caller
+/// to inspect.
+#[tracing::instrument(
+    skip_all,
+    err,
+    fields(
+        integration = ?integration,
+        http.request.method = tracing::field::Empty,
+        server.address = tracing::field::Empty,
+        url.path = tracing::field::Empty,
+        http.response.status_code = tracing::field::Empty,
+        retries = tracing::field::Empty,
+    )
+)]
+pub async fn send(
+    app: &AppHandle,
+    integration: Integration,
+    request: RequestBuilder,
+) -> Result<Response, AppError> {
+    let policy = integration.retry_policy();
+    let mut attempt = 0;
+    // Only the host and path; queries can carry secrets
+    let span = tracing::Span::current();
+    let mut idempotent = false;
+    if let Some(built) = request.try_clone().and_then(|r| r.build().ok()) {
+        span.record("http.request.method", built.method().as_str());
+        span.record("server.address", built.url().host_str().unwrap_or_default());
+        span.record("url.path", built.url().path());
+        idempotent = retry::is_idempotent(&built);
+    }
+
+    loop {
+        while let Some(wait) = app.state::<HttpState>().acquire(integration) {
+            tokio::time::sleep(wait).await;
+        }
+
+        let attempt_request = request
+            .try_clone()
+            .ok_or_else(|| AppError::new(ErrorCode::Internal, "Request body cannot be retried"))?;
+        let retries_left = attempt < policy.max_retries;
+
+        let delay = match attempt_request.send().await {
+            Ok(response) if retries_left && retry::is_retryable_status(response.status(), idempotent) => {
+                retry::retry_after(&response).unwrap_or_else(|| retry::backoff(attempt))
+            }
+            Ok(response) => {
+                span.record("http.response.status_code", response.status().as_u16());
+                span.record("retries", attempt);
+                return Ok(response);
+            }
+            Err(e) if retries_left && (e.is_connect() || (idempotent && e.is_timeout())) => {
+                retry::backoff(attempt)
+            }
+            Err(e) => {
+                let error = AppError::new(ErrorCode::Offline, format!("Request failed: {}", e));
+                // Past connecting, the server may have received it
+                return Err(if e.is_connect() || e.is_builder() { error } else { error.maybe_delivered() });
+            }
+        };
+
+        attempt += 1;
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// The current shared client
+pub fn client(app: &AppHandle) -> reqwest::Client {
+    app.state::<HttpState>()
+        .client
+        .read()
+        .map(|client| client.clone())
+        .unwrap_or_default()
+}
+
+/// Rebuild the shared client after the network settings changed
+pub fn reconfigure(app: &AppHandle, network: &NetworkSettings) -> Result<(), String> {
+    let client = build_client(network)?;
+    let state = app.state::<HttpState>();
+    if let Ok(mut current) = state.client.write() {
+        *current = client;
+    }
+    if let Ok(mut current) = state.network.write() {
+        *current = network.clone();
+    }
+    Ok(())
+}
+
+/// A TCP connection to `host:port` through the configured proxy, for
+/// protocols the shared client doesn't speak (the realtime WebSocket). Use
+/// `tls_config` on top of it.
+pub async fn connect_tcp(app: &AppHandle, host: &str, port: u16) -> Result<TcpStream, String> {
+    let network = app
+        .state::<HttpState>()
+        .network
+        .read()
+        .map(|network| network.clone())
+        .unwrap_or_default();
+    tunnel::connect(&network, host, port).await
+}
+
+/// TLS settings for every native connection: the OS trust store, so
+/// servers behind a company CA are trusted as they are in the browser. No
+/// ALPN; HTTP clients add their own.
+pub fn tls_config() -> Arc<rustls::ClientConfig> {
+    static CONFIG: OnceLock<Arc<rustls::ClientConfig>> = OnceLock::new();
+    CONFIG
+        .get_or_init(|| {
+            let mut roots = rustls::RootCertStore::empty();
+            let native = rustls_native_certs::load_native_certs();
+            for e in &native.errors {
+                tracing::warn!("Failed to load system certificates: {}", e);
+            }
+            roots.add_parsable_certificates(native.certs);
+            let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+                rustls::crypto::ring::default_provider(),
+            ))
+            .with_safe_default_protocol_versions()
+            .expect("the ring provider supports the default TLS versions")
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+            Arc::new(config)
+        })
+        .clone()
+}
+
+pub fn build_client(network: &NetworkSettings) -> Result<reqwest::Client, String> {
+    let mut tls = (*tls_config()).clone();
+    tls.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    let builder = reqwest::Client::builder()
+        .use_preconfigured_tls(tls)
+        .timeout(Duration::from_secs(network.request_timeout_secs.max(1)));
+
+    let builder = match network.proxy_mode {
+        // reqwest picks up HTTP(S)_PROXY and the OS proxy configuration by default
+        ProxyMode::System => builder,
+        ProxyMode::None => builder.no_proxy(),
+        ProxyMode::Manual => builder.proxy(manual_proxy(network)?),
+    };
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+fn manual_proxy(network: &NetworkSettings) -> Result<Proxy, String> {
+    let url = network
+        .proxy_url
+        .as_deref()
+        .filter(|url| !url.trim().is_empty())
+        .ok_or_else(|| "Manual proxy mode requires a proxy URL".to_string())?;
+
+    let mut proxy = Proxy::all(url.trim()).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+    if let Some(username) = network.proxy_username.as_deref().filter(|u| !u.is_empty()) {
+        proxy = proxy.basic_auth(username, network.proxy_password.as_deref().unwrap_or(""));
+    }
+    if let Some(no_proxy) = network.no_proxy.as_deref() {
+        proxy = proxy.no_proxy(NoProxy::from_string(no_proxy));
+    }
+
+    Ok(proxy)
+}
+
+/// Proxy configuration in the shape the HTTP plugin's `fetch` accepts, so
+/// webview requests go through the same proxy as native ones
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginProxyConfig {
+    pub all: PluginProxy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginProxy {
+    pub url: String,
+    pub basic_auth: Option<PluginBasicAuth>,
+    pub no_proxy: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginBasicAuth {
+    pub username: String,
+    pub password: String,
+}
+
+pub fn plugin_proxy_config(network: &NetworkSettings) -> Option<PluginProxyConfig> {
+    if network.proxy_mode != ProxyMode::Manual {
+        return None;
+    }
+
+    let url = network.proxy_url.clone().filter(|url| !url.trim().is_empty())?;
+    let basic_auth = network
+        .proxy_username
+        .clone()
+        .filter(|username| !username.is_empty())
+        .map(|username| PluginBasicAuth {
+            username,
+            password: network.proxy_password.clone().unwrap_or_default(),
+        });
+
+    Some(PluginProxyConfig {
+        all: PluginProxy {
+            url,
+            basic_auth,
+            no_proxy: network.no_proxy.clone(),
+        },
+    })
+}
@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+/// Requests per second and burst size for one integration
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+/// Token bucket. Callers ask how long to wait before a request may go out.
+#[derive(Debug)]
+pub struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.burst),
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Take a token if one is available, otherwise return how long until
+    /// the next one is
+    pub fn try_acquire(&mut self) -> Result<(), Duration> {
+        self.try_acquire_many(1)
+    }
+
+    /// Take `count` tokens at once (e.g. bytes for bandwidth limiting).
+    /// More than the burst size is capped to it, so it can always succeed.
+    pub fn try_acquire_many(&mut self, count: u32) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(f64::from(self.limit.burst));
+        self.refilled_at = now;
+
+        let wanted = f64::from(count.min(self.limit.burst));
+        if self.tokens >= wanted {
+            self.tokens -= wanted;
+            Ok(())
+        } else {
+            let missing = wanted - self.tokens;
+            Err(Duration::from_secs_f64(missing / self.limit.per_second))
+        }
+    }
+}
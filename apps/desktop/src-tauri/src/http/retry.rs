@@ -0,0 +1,65 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tauri_plugin_http::reqwest::{header, Method, Request, Response, StatusCode};
+
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+/// Retry behaviour for one integration
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3 }
+    }
+}
+
+/// Whether a response is worth retrying. A 5xx may come after the server
+/// already acted, so only idempotent requests retry it.
+pub fn is_retryable_status(status: StatusCode, idempotent: bool) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || (idempotent && status.is_server_error())
+}
+
+/// Whether sending `request` twice has the same effect as sending it once:
+/// an idempotent method, or a write carrying an `Idempotency-Key`
+pub fn is_idempotent(request: &Request) -> bool {
+    matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    ) || request.headers().contains_key(IDEMPOTENCY_KEY)
+}
+
+/// Delay requested by the server via `Retry-After` (seconds form only)
+pub fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|secs| Duration::from_secs(secs).min(MAX_DELAY))
+}
+
+/// Exponential backoff with full jitter: a random delay in
+/// `[0, min(MAX_DELAY, BASE_DELAY * 2^attempt))`
+pub fn backoff(attempt: u32) -> Duration {
+    let ceiling = BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_DELAY);
+    ceiling.mul_f64(random_fraction())
+}
+
+/// Uniform value in `[0, 1)`, seeded from the std hasher's per-process keys.
+/// Good enough for jitter without pulling in a RNG crate.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(crate::clock::now_millis() as u64);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
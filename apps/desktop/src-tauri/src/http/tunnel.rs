@@ -0,0 +1,115 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use tauri_plugin_http::reqwest::Url;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::settings::{NetworkSettings, ProxyMode};
+
+/// Longest CONNECT response read before giving up on the proxy
+const MAX_RESPONSE_BYTES: usize = 8 * 1024;
+
+/// A TCP connection to `host:port`, through an HTTP CONNECT tunnel when the
+/// network settings put a proxy in front of that host. For connections
+/// reqwest doesn't make itself, like the realtime WebSocket.
+pub async fn connect(network: &NetworkSettings, host: &str, port: u16) -> Result<TcpStream, String> {
+    let Some(proxy) = proxy_for(network, host)? else {
+        return TcpStream::connect((host, port))
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", host, e));
+    };
+    if proxy.scheme() != "http" {
+        return Err(format!("{} proxies can't tunnel this connection", proxy.scheme()));
+    }
+    let proxy_host = proxy.host_str().ok_or_else(|| "Invalid proxy URL".to_string())?;
+    let proxy_port = proxy.port_or_known_default().unwrap_or(80);
+    let mut stream = TcpStream::connect((proxy_host, proxy_port))
+        .await
+        .map_err(|e| format!("Failed to connect to the proxy: {}", e))?;
+
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if let Some((username, password)) = credentials(network, &proxy) {
+        let token = BASE64.encode(format!("{}:{}", username, password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to talk to the proxy: {}", e))?;
+
+    // Read byte by byte so nothing past the headers is taken from the tunnel
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_RESPONSE_BYTES {
+            return Err("The proxy sent an oversized response".to_string());
+        }
+        let byte = stream
+            .read_u8()
+            .await
+            .map_err(|e| format!("Failed to talk to the proxy: {}", e))?;
+        response.push(byte);
+    }
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(stream),
+        _ => Err(format!("The proxy refused the connection: {}", status_line)),
+    }
+}
+
+/// The proxy for `host`: the manual one, or with the system setting the
+/// same environment variables reqwest reads
+fn proxy_for(network: &NetworkSettings, host: &str) -> Result<Option<Url>, String> {
+    let (url, no_proxy) = match network.proxy_mode {
+        ProxyMode::None => return Ok(None),
+        ProxyMode::Manual => (
+            network.proxy_url.clone().filter(|url| !url.trim().is_empty()),
+            network.no_proxy.clone(),
+        ),
+        ProxyMode::System => (
+            env(&["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]),
+            env(&["NO_PROXY", "no_proxy"]),
+        ),
+    };
+    let Some(url) = url else {
+        return Ok(None);
+    };
+    if no_proxy.is_some_and(|no_proxy| bypasses(&no_proxy, host)) {
+        return Ok(None);
+    }
+    Url::parse(url.trim())
+        .map(Some)
+        .map_err(|e| format!("Invalid proxy URL: {}", e))
+}
+
+fn credentials(network: &NetworkSettings, proxy: &Url) -> Option<(String, String)> {
+    if network.proxy_mode == ProxyMode::Manual {
+        if let Some(username) = network.proxy_username.clone().filter(|u| !u.is_empty()) {
+            return Some((username, network.proxy_password.clone().unwrap_or_default()));
+        }
+    }
+    (!proxy.username().is_empty()).then(|| {
+        (
+            proxy.username().to_string(),
+            proxy.password().unwrap_or_default().to_string(),
+        )
+    })
+}
+
+fn env(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.trim().is_empty()))
+}
+
+/// Whether a comma-separated no-proxy list covers `host`
+fn bypasses(no_proxy: &str, host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    no_proxy.split(',').map(str::trim).any(|entry| {
+        let entry = entry
+            .trim_start_matches("*.")
+            .trim_start_matches('.')
+            .to_ascii_lowercase();
+        !entry.is_empty() && (entry == "*" || host == entry || host.ends_with(&format!(".{}", entry)))
+    })
+}
@@ -0,0 +1,734 @@
+//! Minimal iCalendar (RFC 5545) reader for importing events as local time
+//! blocks. Handles line folding, text escaping, UTC / TZID / floating
+//! times and DURATION. Recurring events import their first occurrence only;
+//! all-day events are skipped because they don't block time. Feeds expand
+//! them instead: [`expand`] repeats RRULE (daily through yearly, with BYDAY,
+//! BYMONTHDAY, BYMONTH and BYSETPOS), RDATE and EXDATE within a window.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::Database;
+use crate::timezone;
+
+const DEFAULT_DURATION_MS: i64 = 60 * 60 * 1000;
+/// Periods a rule is followed for before giving up, about 50 years daily
+const MAX_PERIODS: i64 = 20_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub summary: String,
+    /// Unix milliseconds
+    pub start_at: i64,
+    pub end_at: i64,
+    /// IANA zone the event was written in; the local zone for floating times
+    pub timezone: String,
+    pub all_day: bool,
+    pub recurring: bool,
+    /// How the event repeats; None for one-off events
+    pub recurrence: Option<Recurrence>,
+    /// RECURRENCE-ID: the occurrence of a recurring event this one replaces
+    pub recurrence_id: Option<i64>,
+    pub cancelled: bool,
+    pub location: Option<String>,
+    /// DESCRIPTION, followed by the event's URL if it has one
+    pub description: Option<String>,
+}
+
+impl IcsEvent {
+    /// The event as a local calendar block with id `{prefix}{uid}`
+    pub fn into_block(self, prefix: &str) -> TimeBlock {
+        TimeBlock {
+            id: format!("{}{}", prefix, self.uid),
+            task_id: None,
+            title: self.summary,
+            start_at: self.start_at,
+            end_at: self.end_at,
+            timezone: self.timezone,
+            is_event: true,
+            location: self.location,
+            description: self.description,
+            updated_at: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recurrence {
+    rule: Option<String>,
+    /// Zone the rule repeats wall-clock times in; UTC for UTC start times
+    zone: Tz,
+    rdates: Vec<i64>,
+    exdates: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsImportSummary {
+    pub file: String,
+    pub imported: usize,
+    pub skipped_all_day: usize,
+    pub skipped_cancelled: usize,
+    /// Recurring events, imported as their first occurrence only
+    pub recurring: usize,
+}
+
+/// Import the events of an .ics file into the local schedule. Re-importing
+/// the same file updates the blocks instead of duplicating them.
+pub fn import_file(app: &AppHandle, path: &Path) -> Result<IcsImportSummary, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read calendar file: {}", e))?;
+    let local = timezone::parse(&timezone::detect()).unwrap_or(Tz::UTC);
+    let events = parse(&text, local);
+
+    let mut summary = IcsImportSummary {
+        file: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let mut blocks = Vec::new();
+    for event in events {
+        if event.cancelled {
+            summary.skipped_cancelled += 1;
+            continue;
+        }
+        if event.all_day {
+            summary.skipped_all_day += 1;
+            continue;
+        }
+        summary.recurring += usize::from(event.recurring);
+        blocks.push(event.into_block("ics:"));
+    }
+
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        for block in &blocks {
+            time_blocks::upsert(&tx, block)?;
+        }
+        tx.commit()
+    })?;
+
+    summary.imported = blocks.len();
+    Ok(summary)
+}
+
+/// Replace recurring events with their occurrences that overlap `from..to`
+/// (Unix ms). Each occurrence gets the uid `{uid}/{start}`, and an instance
+/// moved with a RECURRENCE-ID takes the place of the occurrence it stands
+/// for. One-off events pass through; rules this reader can't follow keep
+/// the first occurrence only.
+pub fn expand(events: Vec<IcsEvent>, from: i64, to: i64) -> Vec<IcsEvent> {
+    let overrides: HashSet<(String, i64)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.clone(), event.recurrence_id?)))
+        .collect();
+
+    let mut expanded = Vec::new();
+    for event in events {
+        if let Some(id) = event.recurrence_id {
+            expanded.push(IcsEvent {
+                uid: format!("{}/{}", event.uid, id),
+                ..event
+            });
+            continue;
+        }
+        let Some(recurrence) = &event.recurrence else {
+            expanded.push(event);
+            continue;
+        };
+
+        let duration = event.end_at - event.start_at;
+        for start in recurrence.starts(event.start_at, from - duration, to) {
+            if overrides.contains(&(event.uid.clone(), start)) {
+                continue;
+            }
+            expanded.push(IcsEvent {
+                uid: format!("{}/{}", event.uid, start),
+                start_at: start,
+                end_at: start + duration,
+                recurrence: None,
+                ..event.clone()
+            });
+        }
+    }
+    expanded
+}
+
+/// Parse every VEVENT in `text`. Floating times are read in `local`.
+pub fn parse(text: &str, local: Tz) -> Vec<IcsEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+
+    for line in unfold(text) {
+        let Some(property) = Property::parse(&line) else {
+            continue;
+        };
+        match (property.name.as_str(), property.value.as_str()) {
+            ("BEGIN", "VEVENT") => current = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(event) = current.take().and_then(|props| build_event(&props, local)) {
+                    events.push(event);
+                }
+            }
+            _ => {
+                if let Some(props) = current.as_mut() {
+                    props.push(property);
+                }
+            }
+        }
+    }
+
+    events
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // The value starts at the first colon outside a quoted parameter
+        let mut quoted = false;
+        let split = line.char_indices().find(|&(_, c)| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+            c == ':' && !quoted
+        })?;
+        let (head, value) = (&line[..split.0], &line[split.0 + 1..]);
+
+        let mut parts = head.split(';');
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                Some((key.to_ascii_uppercase(), value.trim_matches('"').to_string()))
+            })
+            .collect();
+
+        Some(Self {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn build_event(props: &[Property], local: Tz) -> Option<IcsEvent> {
+    let get = |name: &str| props.iter().find(|p| p.name == name);
+
+    let start_prop = get("DTSTART")?;
+    let all_day = start_prop.param("VALUE") == Some("DATE") || start_prop.value.len() == 8;
+    let (start_at, tz) = parse_time(start_prop, local)?;
+
+    let end_at = match (get("DTEND"), get("DURATION")) {
+        (Some(end), _) => parse_time(end, local).map(|(end, _)| end),
+        (None, Some(duration)) => parse_duration_ms(&duration.value).map(|d| start_at + d),
+        _ => None,
+    }
+    .filter(|&end| end > start_at)
+    .unwrap_or(start_at + DEFAULT_DURATION_MS);
+
+    let summary = get("SUMMARY")
+        .map(|p| unescape(&p.value))
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "Untitled event".to_string());
+    // Events without a UID still need a stable id so re-imports update them
+    let uid = get("UID")
+        .map(|p| p.value.clone())
+        .unwrap_or_else(|| format!("{}-{}", start_at, summary));
+
+    let times = |name: &str| -> Vec<i64> {
+        props
+            .iter()
+            .filter(|p| p.name == name)
+            .flat_map(|p| parse_times(p, local))
+            .collect()
+    };
+    let rule = get("RRULE").map(|p| p.value.clone());
+    let rdates = times("RDATE");
+    let recurrence = (rule.is_some() || !rdates.is_empty()).then(|| Recurrence {
+        rule,
+        zone: if start_prop.value.trim().ends_with('Z') {
+            Tz::UTC
+        } else {
+            tz
+        },
+        rdates,
+        exdates: times("EXDATE"),
+    });
+
+    let text = |name: &str| {
+        get(name)
+            .map(|p| unescape(&p.value))
+            .filter(|s| !s.trim().is_empty())
+    };
+    let description = [text("DESCRIPTION"), text("URL")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(IcsEvent {
+        uid,
+        summary,
+        start_at,
+        end_at,
+        timezone: tz.name().to_string(),
+        all_day,
+        recurring: recurrence.is_some(),
+        recurrence,
+        recurrence_id: get("RECURRENCE-ID")
+            .and_then(|p| parse_time(p, local))
+            .map(|(at, _)| at),
+        cancelled: get("STATUS").is_some_and(|p| p.value.eq_ignore_ascii_case("CANCELLED")),
+        location: text("LOCATION"),
+        description: (!description.is_empty()).then_some(description),
+    })
+}
+
+/// A DATE or DATE-TIME value as Unix ms, with the zone it was written in
+fn parse_time(prop: &Property, local: Tz) -> Option<(i64, Tz)> {
+    let value = prop.value.trim();
+    let tz = prop
+        .param("TZID")
+        .and_then(|id| timezone::parse(id).ok())
+        .unwrap_or(local);
+
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        let midnight = tz.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?;
+        return Some((midnight.timestamp_millis(), tz));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive).timestamp_millis(), tz));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let time = tz.from_local_datetime(&naive).earliest()?;
+    Some((time.timestamp_millis(), tz))
+}
+
+/// Every time in a comma-separated RDATE or EXDATE. Periods are skipped.
+fn parse_times(prop: &Property, local: Tz) -> Vec<i64> {
+    prop.value
+        .split(',')
+        .filter_map(|value| {
+            let single = Property {
+                name: prop.name.clone(),
+                params: prop.params.clone(),
+                value: value.to_string(),
+            };
+            parse_time(&single, local).map(|(at, _)| at)
+        })
+        .collect()
+}
+
+impl Recurrence {
+    /// Occurrence starts strictly between `after` and `before`
+    fn starts(&self, dtstart: i64, after: i64, before: i64) -> Vec<i64> {
+        let mut starts = match &self.rule {
+            Some(rule) => Rule::parse(rule, self.zone)
+                .map(|rule| rule.starts(dtstart, self.zone, before))
+                .unwrap_or_else(|| vec![dtstart]),
+            None => vec![dtstart],
+        };
+        starts.extend(&self.rdates);
+        starts.retain(|start| *start > after && *start < before && !self.exdates.contains(start));
+        starts.sort_unstable();
+        starts.dedup();
+        starts
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The parts of an RRULE this reader follows. Weeks start on Monday.
+#[derive(Debug)]
+struct Rule {
+    frequency: Frequency,
+    interval: i64,
+    count: Option<usize>,
+    /// Last allowed start, inclusive
+    until: Option<i64>,
+    /// Weekdays, with an optional "nth in the month" (negative from the end)
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    by_set_pos: Vec<i32>,
+}
+
+impl Rule {
+    fn parse(value: &str, zone: Tz) -> Option<Self> {
+        let mut frequency = None;
+        let mut rule = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+        };
+        let numbers = |value: &str| -> Vec<i32> {
+            value
+                .split(',')
+                .filter_map(|n| n.trim().parse().ok())
+                .collect()
+        };
+
+        for part in value.split(';') {
+            let Some((key, value)) = part.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().to_ascii_uppercase();
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = match value.as_str() {
+                        "DAILY" => Some(Frequency::Daily),
+                        "WEEKLY" => Some(Frequency::Weekly),
+                        "MONTHLY" => Some(Frequency::Monthly),
+                        "YEARLY" => Some(Frequency::Yearly),
+                        // Sub-daily rules aren't followed
+                        _ => None,
+                    }
+                }
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|&n| n > 0)?,
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => {
+                    let until = Property {
+                        name: "UNTIL".to_string(),
+                        params: Vec::new(),
+                        value: value.clone(),
+                    };
+                    let (at, _) = parse_time(&until, zone)?;
+                    // A date-only UNTIL includes that whole day
+                    let day = if value.len() == 8 {
+                        24 * 3600 * 1000 - 1
+                    } else {
+                        0
+                    };
+                    rule.until = Some(at + day);
+                }
+                "BYDAY" => rule.by_day = value.split(',').filter_map(parse_by_day).collect(),
+                "BYMONTHDAY" => rule.by_month_day = numbers(&value),
+                "BYMONTH" => {
+                    rule.by_month = numbers(&value)
+                        .into_iter()
+                        .filter_map(|m| u32::try_from(m).ok())
+                        .collect()
+                }
+                "BYSETPOS" => rule.by_set_pos = numbers(&value),
+                _ => {}
+            }
+        }
+
+        rule.frequency = frequency?;
+        Some(rule)
+    }
+
+    /// Starts from `dtstart` on, up to `before`, UNTIL or COUNT
+    fn starts(&self, dtstart: i64, zone: Tz, before: i64) -> Vec<i64> {
+        let Some(first) = Utc
+            .timestamp_millis_opt(dtstart)
+            .single()
+            .map(|utc| utc.with_timezone(&zone).naive_local())
+        else {
+            return vec![dtstart];
+        };
+        let last = self.until.map_or(before, |until| until.min(before));
+
+        let mut starts = Vec::new();
+        for period in 0..MAX_PERIODS {
+            for date in self.dates(first.date(), period * self.interval) {
+                let Some(at) = local_millis(zone, date.and_time(first.time())) else {
+                    continue;
+                };
+                if at < dtstart {
+                    continue;
+                }
+                if at > last || self.count.is_some_and(|count| starts.len() >= count) {
+                    return starts;
+                }
+                starts.push(at);
+            }
+        }
+        starts
+    }
+
+    /// The dates of the period `offset` periods after the one `first` is in,
+    /// in order
+    fn dates(&self, first: NaiveDate, offset: i64) -> Vec<NaiveDate> {
+        let mut dates = match self.frequency {
+            Frequency::Daily => first
+                .checked_add_signed(Duration::days(offset))
+                .into_iter()
+                .filter(|date| {
+                    self.by_day.is_empty()
+                        || self.by_day.iter().any(|(_, day)| *day == date.weekday())
+                })
+                .filter(|date| self.by_month.is_empty() || self.by_month.contains(&date.month()))
+                .collect(),
+            Frequency::Weekly => {
+                let monday =
+                    first - Duration::days(i64::from(first.weekday().num_days_from_monday()));
+                let Some(monday) = monday.checked_add_signed(Duration::weeks(offset)) else {
+                    return Vec::new();
+                };
+                let mut days: Vec<u32> = if self.by_day.is_empty() {
+                    vec![first.weekday().num_days_from_monday()]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|(_, day)| day.num_days_from_monday())
+                        .collect()
+                };
+                days.sort_unstable();
+                days.dedup();
+                days.into_iter()
+                    .map(|day| monday + Duration::days(i64::from(day)))
+                    .filter(|date| {
+                        self.by_month.is_empty() || self.by_month.contains(&date.month())
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = i64::from(first.year()) * 12 + i64::from(first.month0()) + offset;
+                let (Ok(year), Ok(month)) = (
+                    i32::try_from(months.div_euclid(12)),
+                    u32::try_from(months.rem_euclid(12) + 1),
+                ) else {
+                    return Vec::new();
+                };
+                if !self.by_month.is_empty() && !self.by_month.contains(&month) {
+                    return Vec::new();
+                }
+                self.month_dates(year, month, first.day())
+            }
+            Frequency::Yearly => {
+                let Some(year) = i32::try_from(offset)
+                    .ok()
+                    .and_then(|offset| first.year().checked_add(offset))
+                else {
+                    return Vec::new();
+                };
+                let months = if self.by_month.is_empty() {
+                    vec![first.month()]
+                } else {
+                    self.by_month.clone()
+                };
+                months
+                    .into_iter()
+                    .flat_map(|month| self.month_dates(year, month, first.day()))
+                    .collect()
+            }
+        };
+        dates.sort_unstable();
+        dates.dedup();
+
+        if self.by_set_pos.is_empty() {
+            return dates;
+        }
+        let len = dates.len() as i32;
+        let mut picked: Vec<NaiveDate> = self
+            .by_set_pos
+            .iter()
+            .filter_map(|&pos| {
+                let index = if pos > 0 { pos - 1 } else { len + pos };
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|i| dates.get(i).copied())
+            })
+            .collect();
+        picked.sort_unstable();
+        picked.dedup();
+        picked
+    }
+
+    /// The dates BYDAY or BYMONTHDAY pick in a month, else `default_day`
+    fn month_dates(&self, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+        let Some(start) = NaiveDate::from_ymd_opt(year, month, 1) else {
+            return Vec::new();
+        };
+        let all: Vec<NaiveDate> = start
+            .iter_days()
+            .take_while(|date| date.month() == month)
+            .collect();
+
+        if !self.by_day.is_empty() {
+            return self
+                .by_day
+                .iter()
+                .flat_map(|&(nth, weekday)| {
+                    let matching: Vec<NaiveDate> = all
+                        .iter()
+                        .copied()
+                        .filter(|date| date.weekday() == weekday)
+                        .collect();
+                    match nth {
+                        None => matching,
+                        Some(nth) => {
+                            let index = if nth > 0 {
+                                nth - 1
+                            } else {
+                                matching.len() as i32 + nth
+                            };
+                            usize::try_from(index)
+                                .ok()
+                                .and_then(|i| matching.get(i).copied())
+                                .into_iter()
+                                .collect()
+                        }
+                    }
+                })
+                .filter(|date| {
+                    self.by_month_day.is_empty() || self.month_day_matches(*date, all.len())
+                })
+                .collect();
+        }
+
+        let days = if self.by_month_day.is_empty() {
+            vec![default_day as i32]
+        } else {
+            self.by_month_day.clone()
+        };
+        days.into_iter()
+            .filter_map(|day| {
+                let index = if day > 0 {
+                    day - 1
+                } else {
+                    all.len() as i32 + day
+                };
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|i| all.get(i).copied())
+            })
+            .collect()
+    }
+
+    fn month_day_matches(&self, date: NaiveDate, days_in_month: usize) -> bool {
+        let day = date.day() as i32;
+        self.by_month_day
+            .iter()
+            .any(|&d| d == day || d == day - days_in_month as i32 - 1)
+    }
+}
+
+/// A BYDAY entry such as "MO", "2TU" or "-1FR"
+fn parse_by_day(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let split = value.len().checked_sub(2)?;
+    let weekday = match value.get(split..)? {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let nth = match value[..split].trim_start_matches('+') {
+        "" => None,
+        nth => Some(nth.parse().ok().filter(|&n: &i32| n != 0)?),
+    };
+    Some((nth, weekday))
+}
+
+/// A wall-clock time in `zone` as Unix ms; times skipped by a DST change
+/// move an hour later
+fn local_millis(zone: Tz, time: NaiveDateTime) -> Option<i64> {
+    zone.from_local_datetime(&time)
+        .earliest()
+        .or_else(|| {
+            zone.from_local_datetime(&(time + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|time| time.timestamp_millis())
+}
+
+/// RFC 5545 duration ("PT1H30M", "P1D", "P1W") in milliseconds
+fn parse_duration_ms(value: &str) -> Option<i64> {
+    let value = value.trim().trim_start_matches('+');
+    let value = value.strip_prefix('P')?;
+    let mut total_secs = 0i64;
+    let mut number = String::new();
+
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                total_secs += amount
+                    * match unit {
+                        'W' => 7 * 24 * 3600,
+                        'D' => 24 * 3600,
+                        'H' => 3600,
+                        'M' => 60,
+                        'S' => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+
+    Some(total_secs * 1000)
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
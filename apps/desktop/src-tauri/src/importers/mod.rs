@@ -0,0 +1,117 @@
+//! Importers for data from other tools. Each reads its source into plain
+//! records, reports what it found, and only then writes to the server or
+//! the local database, so every importer supports a dry run.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::io::Read;
+use std::path::Path;
+
+use crate::zipfile;
+
+pub mod ics;
+pub mod sunsama;
+pub mod toggl;
+
+/// The files to parse at `path`: the file itself, or every entry of a zip
+pub fn read_source_files(path: &Path) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let is_zip = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read import file: {}", e))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        return Ok(vec![(name, bytes)]);
+    }
+
+    let mut archive = zipfile::open(path)?;
+    let mut files = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Failed to read import archive: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read {} from import archive: {}", entry.name(), e))?;
+        files.push((entry.name().to_string(), bytes));
+    }
+    Ok(files)
+}
+
+/// Parse a human duration into minutes: "90", "45m", "1h 30m", "1.5h", "1:30"
+pub fn parse_duration_minutes(input: &str) -> Option<u32> {
+    let input = input.trim().to_ascii_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+    if let Ok(minutes) = input.parse::<f64>() {
+        return Some(minutes.max(0.0).round() as u32);
+    }
+    if let Some((hours, minutes)) = input.split_once(':') {
+        let hours: u32 = hours.trim().parse().ok()?;
+        let minutes: u32 = minutes.trim().parse().ok()?;
+        return Some(hours * 60 + minutes);
+    }
+
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut matched = false;
+    for c in input.chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        // Only the first letter of a unit counts: "h", "hr", "hours", "min"
+        let factor = match c {
+            'h' => 60.0,
+            'm' => 1.0,
+            's' => 1.0 / 60.0,
+            _ => continue,
+        };
+        if number.is_empty() {
+            continue;
+        }
+        total += number.parse::<f64>().ok()? * factor;
+        number.clear();
+        matched = true;
+    }
+
+    matched.then(|| total.round() as u32)
+}
+
+/// Parse a calendar date: ISO ("2024-03-01", optionally with a time) or US
+/// style ("03/01/2024")
+pub fn parse_date(input: &str) -> Option<NaiveDate> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    NaiveDate::parse_from_str(input.get(..10).unwrap_or(input), "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%m/%d/%Y"))
+        .ok()
+}
+
+/// Parse a timestamp. RFC 3339 values carry their own offset; naive ones
+/// are read in `tz`.
+pub fn parse_datetime(input: &str, tz: Tz) -> Option<DateTime<Utc>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Some(time.with_timezone(&Utc));
+    }
+
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%m/%d/%Y %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+        .and_then(|naive| tz.from_local_datetime(&naive).earliest())
+        .map(|time| time.with_timezone(&Utc))
+}
@@ -0,0 +1,352 @@
+//! Importer for Sunsama's data export. Accepts the CSV task export, the
+//! JSON export, or a zip of either. Column and field names are matched
+//! loosely because they have changed between Sunsama versions.
+//!
+//! Open Sunsama has no channels, so a task's channel is kept as a
+//! `#channel` line at the top of its notes.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::path::Path;
+use tauri::AppHandle;
+
+use super::{parse_date, parse_datetime, parse_duration_minutes, read_source_files};
+use crate::api::Api;
+use crate::timezone;
+
+const TITLE_KEYS: &[&str] = &["title", "task", "text", "name", "task title"];
+const NOTES_KEYS: &[&str] = &["notes", "notesmarkdown", "description"];
+const CHANNEL_KEYS: &[&str] = &["channel", "channels", "stream", "streams", "context"];
+const DATE_KEYS: &[&str] = &["planned date", "planneddate", "day", "date", "scheduled date", "scheduleddate"];
+const DUE_KEYS: &[&str] = &["due date", "duedate", "due"];
+const COMPLETED_KEYS: &[&str] = &["completed at", "completedat", "completedate", "completed date", "completed", "status", "done"];
+const ESTIMATE_KEYS: &[&str] = &["time estimate", "timeestimate", "planned time", "plannedtime", "estimate"];
+const START_KEYS: &[&str] = &["start", "started at", "startdate", "start time"];
+const END_KEYS: &[&str] = &["end", "ended at", "enddate", "end time"];
+
+/// A task as found in the export, before it is sent anywhere
+#[derive(Debug, Clone, Default)]
+struct SunsamaTask {
+    title: String,
+    notes: Option<String>,
+    channel: Option<String>,
+    scheduled_date: Option<NaiveDate>,
+    due_date: Option<NaiveDate>,
+    completed_at: Option<DateTime<Utc>>,
+    completed: bool,
+    estimated_mins: Option<u32>,
+    subtasks: Vec<(String, bool)>,
+    time_entries: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SunsamaImportReport {
+    pub dry_run: bool,
+    pub tasks: usize,
+    pub completed_tasks: usize,
+    pub tasks_with_notes: usize,
+    pub subtasks: usize,
+    pub time_entries: usize,
+    pub channels: Vec<String>,
+    /// Rows or records that couldn't be mapped, with the reason
+    pub skipped: Vec<String>,
+    pub tasks_created: usize,
+    pub failures: Vec<String>,
+}
+
+/// Parse the export at `path` and, unless `dry_run`, create the tasks,
+/// subtasks and time blocks in the signed-in account
+pub async fn import(app: &AppHandle, path: &Path, dry_run: bool) -> Result<SunsamaImportReport, String> {
+    let tz = timezone::parse(&timezone::detect()).unwrap_or(Tz::UTC);
+    let mut report = SunsamaImportReport {
+        dry_run,
+        ..Default::default()
+    };
+
+    let mut tasks = Vec::new();
+    for (name, bytes) in read_source_files(path)? {
+        let lower = name.to_ascii_lowercase();
+        if lower.ends_with(".csv") {
+            tasks.extend(parse_csv(&name, &bytes, tz, &mut report.skipped)?);
+        } else if lower.ends_with(".json") {
+            tasks.extend(parse_json(&name, &bytes, tz, &mut report.skipped)?);
+        }
+    }
+    if tasks.is_empty() {
+        return Err("No Sunsama tasks found; expected the CSV or JSON export".to_string());
+    }
+
+    let mut channels = BTreeSet::new();
+    for task in &tasks {
+        report.tasks += 1;
+        report.completed_tasks += usize::from(task.completed);
+        report.tasks_with_notes += usize::from(task.notes.is_some());
+        report.subtasks += task.subtasks.len();
+        report.time_entries += task.time_entries.len();
+        channels.extend(task.channel.clone());
+    }
+    report.channels = channels.into_iter().collect();
+
+    if dry_run {
+        return Ok(report);
+    }
+
+    let api = Api::new(app)?;
+    for task in &tasks {
+        let created: Value = match api.post("/tasks", &task_body(task)).await {
+            Ok(created) => created,
+            Err(e) => {
+                report.failures.push(format!("Task '{}': {}", task.title, e));
+                continue;
+            }
+        };
+        report.tasks_created += 1;
+        let Some(task_id) = created.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+
+        for (title, completed) in &task.subtasks {
+            let body = json!({ "title": title, "completed": completed });
+            if let Err(e) = api
+                .post::<Value, _>(&format!("/tasks/{}/subtasks", task_id), &body)
+                .await
+            {
+                report.failures.push(format!("Subtask '{}': {}", title, e));
+            }
+        }
+
+        for (start, end) in &task.time_entries {
+            let body = json!({
+                "taskId": task_id,
+                "title": task.title,
+                "startTime": start.to_rfc3339(),
+                "endTime": end.to_rfc3339(),
+            });
+            if let Err(e) = api.post::<Value, _>("/time-blocks", &body).await {
+                report.failures.push(format!("Time entry for '{}': {}", task.title, e));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn task_body(task: &SunsamaTask) -> Value {
+    let notes = match (&task.channel, &task.notes) {
+        (Some(channel), Some(notes)) => Some(format!("#{}\n\n{}", channel, notes)),
+        (Some(channel), None) => Some(format!("#{}", channel)),
+        (None, notes) => notes.clone(),
+    };
+    let completed_at = task
+        .completed_at
+        .or_else(|| task.completed.then(Utc::now))
+        .map(|at| at.to_rfc3339());
+
+    json!({
+        "title": task.title,
+        "notes": notes,
+        "scheduledDate": task.scheduled_date.map(|d| d.to_string()),
+        "dueDate": task.due_date.map(|d| d.to_string()),
+        "estimatedMins": task.estimated_mins,
+        "completedAt": completed_at,
+    })
+}
+
+fn parse_csv(name: &str, bytes: &[u8], tz: Tz, skipped: &mut Vec<String>) -> Result<Vec<SunsamaTask>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(bytes);
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?
+        .iter()
+        .map(|header| header.trim_start_matches('\u{feff}').to_ascii_lowercase())
+        .collect();
+    let column = |keys: &[&str]| keys.iter().find_map(|key| headers.iter().position(|h| h == key));
+
+    let Some(title_col) = column(TITLE_KEYS) else {
+        return Err(format!("{} has no task title column", name));
+    };
+    let notes_col = column(NOTES_KEYS);
+    let channel_col = column(CHANNEL_KEYS);
+    let date_col = column(DATE_KEYS);
+    let due_col = column(DUE_KEYS);
+    let completed_col = column(COMPLETED_KEYS);
+    let estimate_col = column(ESTIMATE_KEYS);
+    let start_col = column(START_KEYS);
+    let end_col = column(END_KEYS);
+
+    let mut tasks = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        // Header is line 1
+        let line = index + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                skipped.push(format!("{} line {}: {}", name, line, e));
+                continue;
+            }
+        };
+        let cell = |col: Option<usize>| col.and_then(|col| record.get(col)).filter(|v| !v.is_empty());
+
+        let Some(title) = cell(Some(title_col)) else {
+            skipped.push(format!("{} line {}: no title", name, line));
+            continue;
+        };
+        let (completed, completed_at) = parse_completion(cell(completed_col), tz);
+        let time_entries = match (cell(start_col), cell(end_col)) {
+            (Some(start), Some(end)) => time_entry(start, end, tz).into_iter().collect(),
+            _ => Vec::new(),
+        };
+
+        tasks.push(SunsamaTask {
+            title: title.to_string(),
+            notes: cell(notes_col).map(str::to_string),
+            channel: cell(channel_col).map(|c| c.trim_start_matches('#').to_string()),
+            scheduled_date: cell(date_col).and_then(parse_date),
+            due_date: cell(due_col).and_then(parse_date),
+            completed_at,
+            completed,
+            estimated_mins: cell(estimate_col).and_then(parse_duration_minutes),
+            subtasks: Vec::new(),
+            time_entries,
+        });
+    }
+
+    Ok(tasks)
+}
+
+fn parse_json(name: &str, bytes: &[u8], tz: Tz, skipped: &mut Vec<String>) -> Result<Vec<SunsamaTask>, String> {
+    let value: Value =
+        serde_json::from_slice(bytes).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    let records = match value {
+        Value::Array(records) => records,
+        Value::Object(mut object) => match object.remove("tasks") {
+            Some(Value::Array(records)) => records,
+            _ => return Ok(Vec::new()),
+        },
+        _ => return Ok(Vec::new()),
+    };
+
+    let mut tasks = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let Some(title) = field(record, TITLE_KEYS).and_then(as_text) else {
+            skipped.push(format!("{} task {}: no title", name, index + 1));
+            continue;
+        };
+
+        let completion = field(record, COMPLETED_KEYS).map(|v| match v {
+            Value::Bool(done) => (*done, None),
+            other => parse_completion(as_text(other).as_deref(), tz),
+        });
+        let (completed, completed_at) = completion.unwrap_or((false, None));
+
+        // Sunsama's JSON stores durations in seconds
+        let estimated_mins = field(record, ESTIMATE_KEYS).and_then(|v| match v {
+            Value::Number(seconds) => seconds.as_f64().map(|s| (s / 60.0).round() as u32),
+            other => as_text(other).as_deref().and_then(parse_duration_minutes),
+        });
+
+        let channel = field(record, CHANNEL_KEYS).and_then(|v| match v {
+            Value::Array(values) => values.first().and_then(as_text),
+            other => as_text(other),
+        });
+
+        let subtasks = record
+            .get("subtasks")
+            .and_then(Value::as_array)
+            .map(|subtasks| {
+                subtasks
+                    .iter()
+                    .filter_map(|s| {
+                        let title = field(s, TITLE_KEYS).and_then(as_text)?;
+                        let done = field(s, COMPLETED_KEYS).is_some_and(|v| v.as_bool().unwrap_or(!v.is_null()));
+                        Some((title, done))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let time_entries = ["actualTime", "timeEntries"]
+            .iter()
+            .find_map(|key| record.get(*key).and_then(Value::as_array))
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| {
+                        let start = field(entry, START_KEYS).and_then(as_text)?;
+                        let end = field(entry, END_KEYS).and_then(as_text)?;
+                        time_entry(&start, &end, tz)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        tasks.push(SunsamaTask {
+            title,
+            notes: field(record, NOTES_KEYS).and_then(as_text),
+            channel: channel.map(|c| c.trim_start_matches('#').to_string()),
+            scheduled_date: field(record, DATE_KEYS).and_then(as_text).as_deref().and_then(parse_date),
+            due_date: field(record, DUE_KEYS).and_then(as_text).as_deref().and_then(parse_date),
+            completed_at,
+            completed,
+            estimated_mins,
+            subtasks,
+            time_entries,
+        });
+    }
+
+    Ok(tasks)
+}
+
+/// Look up the first matching key, ignoring case
+fn field<'a>(record: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    let object = record.as_object()?;
+    keys.iter().find_map(|key| {
+        object
+            .iter()
+            .find(|(name, value)| name.eq_ignore_ascii_case(key) && !value.is_null())
+            .map(|(_, value)| value)
+    })
+}
+
+fn as_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// A completion cell is a timestamp, a date, or a yes/no flag
+fn parse_completion(value: Option<&str>, tz: Tz) -> (bool, Option<DateTime<Utc>>) {
+    let Some(value) = value else {
+        return (false, None);
+    };
+    if let Some(at) = parse_datetime(value, tz) {
+        return (true, Some(at));
+    }
+    if let Some(date) = parse_date(value) {
+        let at = date
+            .and_hms_opt(12, 0, 0)
+            .and_then(|noon| noon.and_local_timezone(tz).earliest())
+            .map(|at| at.with_timezone(&Utc));
+        return (true, at);
+    }
+    let done = matches!(
+        value.to_ascii_lowercase().as_str(),
+        "true" | "yes" | "y" | "1" | "done" | "complete" | "completed"
+    );
+    (done, None)
+}
+
+fn time_entry(start: &str, end: &str, tz: Tz) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = parse_datetime(start, tz)?;
+    let end = parse_datetime(end, tz)?;
+    (end > start).then_some((start, end))
+}
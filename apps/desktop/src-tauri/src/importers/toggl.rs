@@ -0,0 +1,273 @@
+//! Importer for Toggl Track history. Pulls the workspace's projects and the
+//! signed-in Toggl user's time entries for a date range through the Toggl
+//! API into the local database, so analytics cover the time before the
+//! switch. Entries land in `time_entries` with ids under
+//! `time_entries::IMPORTED_PREFIX`, which makes importing the same range
+//! again harmless, and are filed under their Toggl project.
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::db::external_projects::{self, ExternalProject};
+use crate::db::time_entries::{self, TimeEntry, IMPORTED_PREFIX};
+use crate::db::Database;
+use crate::http::{self, Integration};
+use crate::{integrations, settings};
+
+const API_URL: &str = "https://api.track.toggl.com/api/v9";
+const REPORTS_URL: &str = "https://api.track.toggl.com/reports/api/v3";
+const SOURCE: &str = "toggl";
+/// The reports API answers for at most a year at a time
+const MAX_RANGE_DAYS: u64 = 365;
+const REPORT_PAGE_SIZE: u32 = 50;
+const PROJECT_PAGE_SIZE: usize = 200;
+
+#[derive(Debug, Deserialize)]
+struct Me {
+    id: u64,
+    default_workspace_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    active: bool,
+}
+
+/// Entries of one description and project, as the reports API groups them
+#[derive(Debug, Deserialize)]
+struct ReportRow {
+    description: Option<String>,
+    project_id: Option<u64>,
+    time_entries: Vec<ReportEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportEntry {
+    id: u64,
+    start: String,
+    stop: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TogglImportReport {
+    pub dry_run: bool,
+    pub workspace_id: u64,
+    pub projects: usize,
+    pub time_entries: usize,
+    pub tracked_minutes: i64,
+    /// Entries that couldn't be imported, with the reason
+    pub skipped: Vec<String>,
+    pub imported: usize,
+}
+
+/// Fetch Toggl projects and time entries from `start` to `end` (inclusive)
+/// and, unless `dry_run`, store them locally. Uses the workspace from the
+/// time export settings, or the Toggl account's default one.
+pub async fn import(
+    app: &AppHandle,
+    start: NaiveDate,
+    end: NaiveDate,
+    workspace_id: Option<u64>,
+    dry_run: bool,
+) -> Result<TogglImportReport, String> {
+    if end < start {
+        return Err("The import range ends before it starts".to_string());
+    }
+    let token = integrations::token(SOURCE)?
+        .ok_or_else(|| "Add your Toggl Track API token first".to_string())?;
+
+    let me: Me = fetch(app, get(app, &token, &format!("{}/me", API_URL))).await?;
+    let workspace_id = workspace_id
+        .or(settings::load(app)?.integrations.time_export.toggl_workspace_id)
+        .or(me.default_workspace_id)
+        .ok_or_else(|| "Choose the Toggl workspace to import from".to_string())?;
+    let mut report = TogglImportReport {
+        dry_run,
+        workspace_id,
+        ..Default::default()
+    };
+
+    let projects = fetch_projects(app, &token, workspace_id).await?;
+    report.projects = projects.len();
+
+    let mut entries = Vec::new();
+    let mut chunk_start = start;
+    while chunk_start <= end {
+        let chunk_end = chunk_start
+            .checked_add_days(Days::new(MAX_RANGE_DAYS - 1))
+            .map_or(end, |chunk_end| chunk_end.min(end));
+        for row in fetch_report(app, &token, workspace_id, me.id, chunk_start, chunk_end).await? {
+            entries.extend(to_entries(&row, &mut report.skipped));
+        }
+        match chunk_end.succ_opt() {
+            Some(next) => chunk_start = next,
+            None => break,
+        }
+    }
+    report.time_entries = entries.len();
+    report.tracked_minutes = entries
+        .iter()
+        .map(|entry| (entry.ended_at.unwrap_or(entry.started_at) - entry.started_at) / 60_000)
+        .sum();
+
+    if dry_run {
+        return Ok(report);
+    }
+
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        for project in &projects {
+            external_projects::upsert(
+                &tx,
+                &ExternalProject {
+                    id: project_task_id(Some(project.id)),
+                    source: SOURCE.to_string(),
+                    name: project.name.clone(),
+                    archived: !project.active,
+                },
+            )?;
+        }
+        for entry in &entries {
+            time_entries::merge(&tx, entry)?;
+        }
+        tx.commit()
+    })?;
+    report.imported = entries.len();
+
+    Ok(report)
+}
+
+async fn fetch_projects(
+    app: &AppHandle,
+    token: &str,
+    workspace_id: u64,
+) -> Result<Vec<Project>, String> {
+    let mut projects = Vec::new();
+    for page in 1.. {
+        let url = format!(
+            "{}/workspaces/{}/projects?active=both&per_page={}&page={}",
+            API_URL, workspace_id, PROJECT_PAGE_SIZE, page
+        );
+        let batch: Vec<Project> = fetch(app, get(app, token, &url)).await?;
+        let done = batch.len() < PROJECT_PAGE_SIZE;
+        projects.extend(batch);
+        if done {
+            break;
+        }
+    }
+    Ok(projects)
+}
+
+/// Every report row for `user_id` between two dates, following the
+/// reports API's row-number paging
+async fn fetch_report(
+    app: &AppHandle,
+    token: &str,
+    workspace_id: u64,
+    user_id: u64,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<ReportRow>, String> {
+    let url = format!("{}/workspace/{}/search/time_entries", REPORTS_URL, workspace_id);
+    let mut rows = Vec::new();
+    let mut first_row = 1;
+    loop {
+        let request = http::client(app)
+            .post(&url)
+            .basic_auth(token, Some("api_token"))
+            .json(&json!({
+                "start_date": start.to_string(),
+                "end_date": end.to_string(),
+                "user_ids": [user_id],
+                "page_size": REPORT_PAGE_SIZE,
+                "first_row_number": first_row,
+                "order_by": "date",
+            }));
+        let response = http::send(app, Integration::Toggl, request).await?;
+        let next_row = response
+            .headers()
+            .get("X-Next-Row-Number")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        rows.extend(read::<Vec<ReportRow>>(response).await?);
+
+        match next_row {
+            Some(next) if next > first_row => first_row = next,
+            _ => return Ok(rows),
+        }
+    }
+}
+
+fn to_entries(row: &ReportRow, skipped: &mut Vec<String>) -> Vec<TimeEntry> {
+    let title = row
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty())
+        .unwrap_or("(no description)");
+
+    row.time_entries
+        .iter()
+        .filter_map(|entry| {
+            let Some(stop) = &entry.stop else {
+                skipped.push(format!("'{}' is still running in Toggl", title));
+                return None;
+            };
+            let (Some(started_at), Some(ended_at)) = (millis(&entry.start), millis(stop)) else {
+                skipped.push(format!("'{}' has an unreadable start or stop time", title));
+                return None;
+            };
+            Some(TimeEntry {
+                id: format!("{}{}:{}", IMPORTED_PREFIX, SOURCE, entry.id),
+                task_id: project_task_id(row.project_id),
+                task_title: title.to_string(),
+                started_at,
+                ended_at: Some(ended_at),
+                updated_at: 0,
+            })
+        })
+        .collect()
+}
+
+/// Imported entries are timed against their project
+fn project_task_id(project_id: Option<u64>) -> String {
+    match project_id {
+        Some(id) => format!("{}{}:project:{}", IMPORTED_PREFIX, SOURCE, id),
+        None => format!("{}{}:no-project", IMPORTED_PREFIX, SOURCE),
+    }
+}
+
+fn millis(rfc3339: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .ok()
+        .map(|at| at.with_timezone(&Utc).timestamp_millis())
+}
+
+fn get(app: &AppHandle, token: &str, url: &str) -> RequestBuilder {
+    http::client(app).get(url).basic_auth(token, Some("api_token"))
+}
+
+async fn fetch<T: DeserializeOwned>(app: &AppHandle, request: RequestBuilder) -> Result<T, String> {
+    read(http::send(app, Integration::Toggl, request).await?).await
+}
+
+async fn read<T: DeserializeOwned>(response: Response) -> Result<T, String> {
+    if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        return Err("Toggl rejected the API token".to_string());
+    }
+    let body = response
+        .error_for_status()
+        .map_err(|e| format!("Toggl request failed: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Toggl response: {}", e))?;
+    serde_json::from_str(&body).map_err(|e| format!("Unexpected response from Toggl: {}", e))
+}
@@ -0,0 +1,366 @@
+//! Read-only calendar subscriptions: ICS feeds (team calendars, shared
+//! rooms) fetched from a URL and refreshed on a schedule. Each download
+//! replaces the feed's events in the local schedule, so they show up next to
+//! tasks and count as busy in free/busy. As with .ics imports, all-day
+//! events are left out, except that the all-day events of a holiday
+//! calendar become days off. Recurring events are expanded into their
+//! occurrences from a month back to a year ahead. Refreshes send the feed's
+//! ETag and Last-Modified back and keep the stored events on a 304.
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_http::reqwest::{header, StatusCode};
+
+use crate::clock;
+use crate::connectivity::ConnectivityState;
+use crate::db::calendar_subscriptions::{self, CalendarSubscription};
+use crate::db::holidays;
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::Database;
+use crate::http::{self, Integration};
+use crate::importers::ics::{self, IcsEvent};
+use crate::{rituals, settings, timezone};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_REFRESH_MINUTES: u32 = 60;
+/// Feeds aren't polled more often than this
+const MIN_REFRESH_MINUTES: u32 = 15;
+/// Longest all-day event taken as a run of holidays
+const MAX_HOLIDAY_DAYS: usize = 31;
+/// Window recurring events are expanded in, around the time of the fetch
+const RECURRENCE_DAYS_BACK: i64 = 31;
+const RECURRENCE_DAYS_AHEAD: i64 = 366;
+
+/// A downloaded feed, ready to replace the subscription's events
+struct Feed {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    blocks: Vec<TimeBlock>,
+    days_off: Vec<(String, String)>,
+}
+
+/// Refresh subscriptions as they come due while online
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            if !app.state::<ConnectivityState>().is_online() {
+                continue;
+            }
+            let Ok(subscriptions) = app
+                .state::<Database>()
+                .with_conn(|conn| calendar_subscriptions::list(conn))
+            else {
+                continue;
+            };
+            let now = clock::now_millis();
+            for subscription in subscriptions.into_iter().filter(|s| is_due(s, now)) {
+                // Failures are stored on the subscription for the UI to show
+                let _ = refresh(&app, &subscription.id).await;
+            }
+        }
+    });
+}
+
+/// Subscribe to the ICS feed at `url`. The feed is fetched first and the
+/// subscription only saved once it has returned a calendar. `webcal://`
+/// links are fetched over HTTPS.
+pub async fn subscribe(
+    app: &AppHandle,
+    name: &str,
+    url: &str,
+    refresh_minutes: Option<u32>,
+    holidays: bool,
+) -> Result<CalendarSubscription, String> {
+    let url = normalize_url(url)?;
+    let subscription = CalendarSubscription {
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        name: display_name(name, &url),
+        url,
+        refresh_minutes: refresh_minutes
+            .unwrap_or(DEFAULT_REFRESH_MINUTES)
+            .max(MIN_REFRESH_MINUTES),
+        etag: None,
+        last_modified: None,
+        checked_at: None,
+        fetched_at: None,
+        events: 0,
+        last_error: None,
+        created_at: clock::now_millis(),
+        holidays,
+    };
+    let Some(feed) = fetch(app, &subscription).await? else {
+        return Err("The URL didn't return an iCalendar feed".to_string());
+    };
+
+    let db = app.state::<Database>();
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        calendar_subscriptions::insert(&tx, &subscription)?;
+        save(&tx, &subscription, &feed, clock::now_millis())?;
+        tx.commit()
+    })?;
+    let _ = app.emit("calendar-subscriptions-changed", ());
+    stored(app, &subscription);
+
+    db.with_conn(|conn| calendar_subscriptions::get(conn, &subscription.id))?
+        .ok_or_else(|| "Calendar subscription not found".to_string())
+}
+
+/// Rename a subscription or change how often it's refreshed
+pub fn update(
+    app: &AppHandle,
+    id: &str,
+    name: &str,
+    refresh_minutes: u32,
+) -> Result<CalendarSubscription, String> {
+    let db = app.state::<Database>();
+    let subscription = db
+        .with_conn(|conn| calendar_subscriptions::get(conn, id))?
+        .ok_or_else(|| "Calendar subscription not found".to_string())?;
+    let name = display_name(name, &subscription.url);
+    let refresh_minutes = refresh_minutes.max(MIN_REFRESH_MINUTES);
+    db.with_conn(|conn| calendar_subscriptions::update(conn, id, &name, refresh_minutes))?;
+    let _ = app.emit("calendar-subscriptions-changed", ());
+
+    Ok(CalendarSubscription {
+        name,
+        refresh_minutes,
+        ..subscription
+    })
+}
+
+/// Unsubscribe and remove the feed's events from the local schedule
+pub fn unsubscribe(app: &AppHandle, id: &str) -> Result<(), String> {
+    let db = app.state::<Database>();
+    let Some(subscription) = db.with_conn(|conn| calendar_subscriptions::get(conn, id))? else {
+        return Ok(());
+    };
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        time_blocks::delete_prefixed(&tx, &subscription.block_prefix())?;
+        holidays::delete_source(&tx, id)?;
+        calendar_subscriptions::delete(&tx, id)?;
+        tx.commit()
+    })?;
+    let _ = app.emit("calendar-subscriptions-changed", ());
+    let _ = app.emit("time-blocks-changed", ());
+    if subscription.holidays {
+        holidays_changed(app);
+    }
+    Ok(())
+}
+
+pub fn list(app: &AppHandle) -> Result<Vec<CalendarSubscription>, String> {
+    app.state::<Database>()
+        .with_conn(|conn| calendar_subscriptions::list(conn))
+}
+
+/// Fetch a subscription now. Its events are replaced when the feed changed;
+/// a failure is recorded on the subscription and the old events stay.
+pub async fn refresh(app: &AppHandle, id: &str) -> Result<CalendarSubscription, String> {
+    let db = app.state::<Database>();
+    let subscription = db
+        .with_conn(|conn| calendar_subscriptions::get(conn, id))?
+        .ok_or_else(|| "Calendar subscription not found".to_string())?;
+
+    if let Err(e) = download(app, &subscription).await {
+        let now = clock::now_millis();
+        db.with_conn(|conn| calendar_subscriptions::set_error(conn, id, now, &e))?;
+        let _ = app.emit("calendar-subscriptions-changed", ());
+        return Err(e);
+    }
+    let _ = app.emit("calendar-subscriptions-changed", ());
+
+    db.with_conn(|conn| calendar_subscriptions::get(conn, id))?
+        .ok_or_else(|| "Calendar subscription not found".to_string())
+}
+
+async fn download(app: &AppHandle, subscription: &CalendarSubscription) -> Result<(), String> {
+    let feed = fetch(app, subscription).await?;
+    let now = clock::now_millis();
+    let db = app.state::<Database>();
+    let Some(feed) = feed else {
+        return db.with_conn(|conn| {
+            calendar_subscriptions::set_fetched(conn, &subscription.id, now, None)
+        });
+    };
+
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        save(&tx, subscription, &feed, now)?;
+        tx.commit()
+    })?;
+    stored(app, subscription);
+    Ok(())
+}
+
+/// Download and parse the feed; None when it hasn't changed since the last
+/// fetch
+async fn fetch(
+    app: &AppHandle,
+    subscription: &CalendarSubscription,
+) -> Result<Option<Feed>, String> {
+    let mut request = http::client(app)
+        .get(&subscription.url)
+        .header(header::ACCEPT, "text/calendar, */*;q=0.5");
+    if let Some(etag) = &subscription.etag {
+        request = request.header(header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &subscription.last_modified {
+        request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+    }
+
+    let response = http::send(app, Integration::CalendarFeed, request).await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+
+    let response = response
+        .error_for_status()
+        .map_err(|e| format!("Failed to fetch calendar: {}", e))?;
+    let validator = |name: header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let etag = validator(header::ETAG);
+    let last_modified = validator(header::LAST_MODIFIED);
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read calendar: {}", e))?;
+    if !text.trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Err("The URL didn't return an iCalendar feed".to_string());
+    }
+
+    let prefix = subscription.block_prefix();
+    let now = clock::now_millis();
+    let events = ics::expand(
+        ics::parse(&text, timezone::local()),
+        now - RECURRENCE_DAYS_BACK * 24 * 3600 * 1000,
+        now + RECURRENCE_DAYS_AHEAD * 24 * 3600 * 1000,
+    );
+    let (all_day, timed): (Vec<_>, Vec<_>) = events
+        .into_iter()
+        .filter(|event| !event.cancelled)
+        .partition(|event| event.all_day);
+    let days_off: Vec<(String, String)> = if subscription.holidays {
+        all_day
+            .iter()
+            .flat_map(|event| {
+                dates(event)
+                    .into_iter()
+                    .map(|date| (date.to_string(), event.summary.clone()))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let blocks = timed
+        .into_iter()
+        .map(|event| event.into_block(&prefix))
+        .collect();
+
+    Ok(Some(Feed {
+        etag,
+        last_modified,
+        blocks,
+        days_off,
+    }))
+}
+
+/// Replace the subscription's events with the feed's
+fn save(
+    conn: &Connection,
+    subscription: &CalendarSubscription,
+    feed: &Feed,
+    now: i64,
+) -> rusqlite::Result<()> {
+    time_blocks::delete_prefixed(conn, &subscription.block_prefix())?;
+    for block in &feed.blocks {
+        time_blocks::upsert(conn, block)?;
+    }
+    holidays::replace(conn, &subscription.id, &feed.days_off)?;
+    calendar_subscriptions::set_fetched(
+        conn,
+        &subscription.id,
+        now,
+        Some((
+            feed.etag.as_deref(),
+            feed.last_modified.as_deref(),
+            feed.blocks.len() + feed.days_off.len(),
+        )),
+    )
+}
+
+fn stored(app: &AppHandle, subscription: &CalendarSubscription) {
+    let _ = app.emit("time-blocks-changed", ());
+    if subscription.holidays {
+        holidays_changed(app);
+    }
+}
+
+/// The days an all-day event covers; its end date is exclusive
+fn dates(event: &IcsEvent) -> Vec<NaiveDate> {
+    let tz = timezone::parse(&event.timezone).unwrap_or(Tz::UTC);
+    let date = |millis: i64| {
+        Utc.timestamp_millis_opt(millis)
+            .single()
+            .map(|utc| utc.with_timezone(&tz).date_naive())
+    };
+    let (Some(first), Some(last)) = (date(event.start_at), date(event.end_at - 1)) else {
+        return Vec::new();
+    };
+    first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .take(MAX_HOLIDAY_DAYS)
+        .collect()
+}
+
+/// Reminders already scheduled for a new day off are dropped
+fn holidays_changed(app: &AppHandle) {
+    if let Ok(settings) = settings::load(app) {
+        let _ = rituals::schedule(app, &settings.planning);
+    }
+}
+
+/// Due once its refresh interval has passed since the last attempt. Failed
+/// feeds wait a full interval too rather than being retried every minute.
+fn is_due(subscription: &CalendarSubscription, now: i64) -> bool {
+    let interval = i64::from(subscription.refresh_minutes) * 60_000;
+    subscription
+        .checked_at
+        .is_none_or(|checked_at| now - checked_at >= interval)
+}
+
+fn normalize_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let url = match url.get(..9) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("webcal://") => {
+            format!("https://{}", &url[9..])
+        }
+        _ => url.to_string(),
+    };
+    let lower = url.to_ascii_lowercase();
+    if !lower.starts_with("https://") && !lower.starts_with("http://") {
+        return Err("Calendar URLs must start with https://, http:// or webcal://".to_string());
+    }
+    Ok(url)
+}
+
+fn display_name(name: &str, url: &str) -> String {
+    let name = name.trim();
+    if name.is_empty() {
+        url.to_string()
+    } else {
+        name.to_string()
+    }
+}
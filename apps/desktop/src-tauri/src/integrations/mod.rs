@@ -0,0 +1,35 @@
+//! Direct connections to third-party services, outside the Open Sunsama
+//! backend. Each service's API token lives in the keychain; settings only
+//! hold what isn't secret (workspace ids, sites, mapping rules). Calendar
+//! feeds need no token and keep their state in the database.
+
+use crate::keychain;
+
+pub mod calendar_feeds;
+pub mod time_export;
+
+/// Services that take an API token
+pub const SERVICES: &[&str] = &["toggl", "harvest", "jira"];
+
+const TOKEN_ACCOUNT_PREFIX: &str = "integration-token:";
+
+/// The API token saved for `service`
+pub fn token(service: &str) -> Result<Option<String>, String> {
+    keychain::get(&token_account(service)?)
+}
+
+/// Save the API token for `service`, or forget it with `None`
+pub fn set_token(service: &str, token: Option<&str>) -> Result<(), String> {
+    let account = token_account(service)?;
+    match token.map(str::trim).filter(|token| !token.is_empty()) {
+        Some(token) => keychain::set(&account, token),
+        None => keychain::delete(&account),
+    }
+}
+
+fn token_account(service: &str) -> Result<String, String> {
+    if !SERVICES.contains(&service) {
+        return Err(format!("Unknown integration: {}", service));
+    }
+    Ok(format!("{}{}", TOKEN_ACCOUNT_PREFIX, service))
+}
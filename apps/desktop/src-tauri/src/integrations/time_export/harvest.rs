@@ -0,0 +1,51 @@
+//! Harvest, through the v2 API. The target is `project_id/task_id`; entries
+//! are logged as a duration on the day they started.
+
+use serde_json::json;
+use tauri::AppHandle;
+
+use super::{create, local_date};
+use crate::db::time_entries::TimeEntry;
+use crate::error::AppError;
+use crate::http::{self, Integration};
+use crate::settings::TimeExportSettings;
+
+const API_URL: &str = "https://api.harvestapp.com/v2";
+
+pub async fn push(
+    app: &AppHandle,
+    config: &TimeExportSettings,
+    token: &str,
+    entry: &TimeEntry,
+    target: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let account_id = config
+        .harvest_account_id
+        .as_deref()
+        .ok_or_else(|| "Set the Harvest account to export to".to_string())?;
+    let target = target.ok_or_else(|| "No Harvest project for this entry".to_string())?;
+    let (project_id, task_id) = target
+        .split_once('/')
+        .and_then(|(project, task)| {
+            Some((project.trim().parse::<u64>().ok()?, task.trim().parse::<u64>().ok()?))
+        })
+        .ok_or_else(|| format!("Harvest targets are project_id/task_id, not {}", target))?;
+    let spent_date = local_date(entry.started_at).ok_or_else(|| "Invalid entry date".to_string())?;
+    let minutes = (entry.ended_at.unwrap_or(entry.started_at) - entry.started_at) / 60_000;
+
+    let body = json!({
+        "project_id": project_id,
+        "task_id": task_id,
+        "spent_date": spent_date.to_string(),
+        "hours": (minutes as f64 / 60.0 * 100.0).round() / 100.0,
+        "notes": entry.task_title,
+    });
+    let request = http::client(app)
+        .post(format!("{}/time_entries", API_URL))
+        .bearer_auth(token)
+        .header("Harvest-Account-Id", account_id)
+        .header("User-Agent", "Open Sunsama")
+        .json(&body);
+
+    create(app, Integration::Harvest, "Harvest", request).await
+}
@@ -0,0 +1,55 @@
+//! Jira Cloud worklogs, through the v3 REST API. The target is an issue
+//! key; Jira needs worklogs of at least a minute.
+
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+use tauri::AppHandle;
+
+use super::create;
+use crate::db::time_entries::TimeEntry;
+use crate::error::AppError;
+use crate::http::{self, Integration};
+use crate::settings::TimeExportSettings;
+
+pub async fn push(
+    app: &AppHandle,
+    config: &TimeExportSettings,
+    token: &str,
+    entry: &TimeEntry,
+    target: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let site = config
+        .jira_site
+        .as_deref()
+        .map(|site| site.trim_end_matches('/'))
+        .ok_or_else(|| "Set the Jira site to export to".to_string())?;
+    let email = config
+        .jira_email
+        .as_deref()
+        .ok_or_else(|| "Set the Jira account email".to_string())?;
+    let issue = target.ok_or_else(|| "No Jira issue for this entry".to_string())?;
+    let started = Utc
+        .timestamp_millis_opt(entry.started_at)
+        .single()
+        .ok_or_else(|| "Invalid entry start".to_string())?;
+    let seconds = (entry.ended_at.unwrap_or(entry.started_at) - entry.started_at) / 1000;
+
+    let body = json!({
+        "started": started.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string(),
+        "timeSpentSeconds": seconds.max(60),
+        "comment": {
+            "type": "doc",
+            "version": 1,
+            "content": [{
+                "type": "paragraph",
+                "content": [{ "type": "text", "text": entry.task_title }],
+            }],
+        },
+    });
+    let request = http::client(app)
+        .post(format!("{}/rest/api/3/issue/{}/worklog", site, issue.trim()))
+        .basic_auth(email, Some(token))
+        .json(&body);
+
+    create(app, Integration::Jira, "Jira", request).await
+}
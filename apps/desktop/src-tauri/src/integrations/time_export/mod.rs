@@ -0,0 +1,336 @@
+//! Time entry export: finished timer entries are pushed to Toggl Track,
+//! Harvest or Jira worklogs. Each provider is a submodule with the same
+//! `push` function, picked by `TimeExportProvider`.
+//!
+//! The mapping rules in the settings decide where each entry goes, and the
+//! outcome is kept per entry in `time_exports` so nothing is sent twice.
+//! Rate limits and transient failures are handled by `http::send`; entries
+//! that still fail are tried again on later runs with growing delays, then
+//! given up on and reported with `time-export-failed`. A failure after the
+//! request may have reached the provider is given up on at once, since
+//! none of them take an idempotency key and a retry could log it twice.
+//! Entries a rule skipped are looked at again once the rules change.
+
+use chrono::{Days, NaiveDate, TimeZone, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_http::reqwest::{RequestBuilder, StatusCode};
+
+use crate::analytics;
+use crate::connectivity::ConnectivityState;
+use crate::db::time_entries::TimeEntry;
+use crate::db::time_exports::{self, ExportStatus, TimeExport};
+use crate::db::Database;
+use crate::error::{AppError, ErrorCode};
+use crate::http::{self, Integration};
+use crate::integrations;
+use crate::settings::{self, SettingsSection, TimeExportRule, TimeExportSettings};
+use crate::{clock, timezone};
+
+mod harvest;
+mod jira;
+mod toggl;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Without a `since` date, entries this recent are exported
+const DEFAULT_LOOKBACK_DAYS: u64 = 7;
+/// Entries that keep failing are given up on after this many attempts
+const MAX_ATTEMPTS: i64 = 8;
+const RETRY_BASE_MS: i64 = 5 * 60 * 1000;
+const RETRY_MAX_MS: i64 = 6 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeExportProvider {
+    Toggl,
+    Harvest,
+    Jira,
+}
+
+impl TimeExportProvider {
+    /// Key in `time_exports` and the integration token store
+    pub fn key(self) -> &'static str {
+        match self {
+            TimeExportProvider::Toggl => "toggl",
+            TimeExportProvider::Harvest => "harvest",
+            TimeExportProvider::Jira => "jira",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TimeExportProvider::Toggl => "Toggl Track",
+            TimeExportProvider::Harvest => "Harvest",
+            TimeExportProvider::Jira => "Jira",
+        }
+    }
+
+    /// Toggl takes entries without a project; the others need a target
+    fn needs_target(self) -> bool {
+        self != TimeExportProvider::Toggl
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub exported: usize,
+    pub skipped: usize,
+    /// Failed this run; retried later unless given up on
+    pub failed: usize,
+}
+
+/// Export new entries every few minutes while enabled, emitting
+/// `time-entries-exported` when something went out
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let enabled = settings::load(&app)
+                .map(|settings| settings.integrations.time_export.enabled)
+                .unwrap_or(false);
+            if !enabled || !app.state::<ConnectivityState>().is_online() {
+                continue;
+            }
+            match run(&app).await {
+                Ok(summary) if summary.exported > 0 => {
+                    let _ = app.emit("time-entries-exported", summary);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("time export failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Push every finished entry that's due to the configured provider
+pub async fn run(app: &AppHandle) -> Result<ExportSummary, String> {
+    let config = settings::load(app)?.integrations.time_export;
+    let provider = config
+        .provider
+        .ok_or_else(|| "Choose where to export time entries first".to_string())?;
+    let token = integrations::token(provider.key())?
+        .ok_or_else(|| format!("Add your {} API token first", provider.label()))?;
+
+    let now = clock::now_millis();
+    let since = since(&config, now)?;
+    let rules_changed_at = settings::modified_times(app)?
+        .get(&settings::field_key(SettingsSection::Integrations, "time_export"))
+        .and_then(Value::as_i64)
+        .unwrap_or(0);
+    let db = app.state::<Database>();
+    let pending = db.with_conn(|conn| {
+        time_exports::pending(conn, provider.key(), since, now, rules_changed_at)
+    })?;
+
+    let needs_channels = config.rules.iter().any(|rule| rule.channel.is_some());
+    let channels = if needs_channels {
+        let task_ids = pending.iter().map(|(entry, _)| entry.task_id.clone()).collect();
+        analytics::task_channels(app, task_ids).await?
+    } else {
+        HashMap::new()
+    };
+    let mut summary = ExportSummary::default();
+    for (entry, attempts) in pending {
+        let ended_at = entry.ended_at.unwrap_or(entry.started_at);
+        let channel = channels.get(&entry.task_id).cloned().flatten();
+        let rule = config
+            .rules
+            .iter()
+            .find(|rule| matches(rule, &entry.task_title, channel.as_deref()));
+
+        let mut target = rule.and_then(|rule| rule.target.clone());
+        if target.is_none() && provider == TimeExportProvider::Jira {
+            target = issue_key(&entry.task_title);
+        }
+        let skip_reason = if ended_at - entry.started_at < i64::from(config.min_minutes) * 60_000 {
+            Some("Shorter than the minimum duration")
+        } else if rule.is_some_and(|rule| rule.skip) {
+            Some("Kept local by a mapping rule")
+        } else if provider.needs_target() && target.is_none() {
+            Some("No mapping rule says where it goes")
+        } else {
+            None
+        };
+
+        let mut export = TimeExport {
+            entry_id: entry.id.clone(),
+            provider: provider.key().to_string(),
+            status: ExportStatus::Skipped,
+            remote_id: None,
+            attempts,
+            last_error: skip_reason.map(str::to_string),
+            next_attempt_at: 0,
+            updated_at: now,
+        };
+        if skip_reason.is_none() {
+            let pushed = match provider {
+                TimeExportProvider::Toggl => {
+                    toggl::push(app, &config, &token, &entry, target.as_deref()).await
+                }
+                TimeExportProvider::Harvest => {
+                    harvest::push(app, &config, &token, &entry, target.as_deref()).await
+                }
+                TimeExportProvider::Jira => {
+                    jira::push(app, &config, &token, &entry, target.as_deref()).await
+                }
+            };
+            match pushed {
+                Ok(remote_id) => {
+                    export.status = ExportStatus::Exported;
+                    export.remote_id = remote_id;
+                }
+                // A bad token fails every entry the same way; stop here
+                // without counting it against them
+                Err(e) if e.code == ErrorCode::AuthExpired => {
+                    return Err(format!("{} rejected the API token", provider.label()))
+                }
+                Err(e) if e.maybe_delivered => {
+                    export.attempts += 1;
+                    export.last_error = Some(format!(
+                        "{}; it may have reached {}, so check there before retrying",
+                        e,
+                        provider.label()
+                    ));
+                    export.status = ExportStatus::Failed;
+                }
+                Err(e) => {
+                    export.attempts += 1;
+                    export.last_error = Some(e.message);
+                    export.status = if export.attempts >= MAX_ATTEMPTS {
+                        ExportStatus::Failed
+                    } else {
+                        ExportStatus::Retrying
+                    };
+                    export.next_attempt_at = now + retry_delay(export.attempts);
+                }
+            }
+        }
+
+        db.with_conn(|conn| time_exports::upsert(conn, &export))?;
+        match export.status {
+            ExportStatus::Exported => summary.exported += 1,
+            ExportStatus::Skipped => summary.skipped += 1,
+            ExportStatus::Retrying => summary.failed += 1,
+            ExportStatus::Failed => {
+                summary.failed += 1;
+                let _ = app.emit("time-export-failed", &export);
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Entries given up on for the configured provider
+pub fn list_failed(app: &AppHandle) -> Result<Vec<TimeExport>, String> {
+    let Some(provider) = settings::load(app)?.integrations.time_export.provider else {
+        return Ok(Vec::new());
+    };
+    app.state::<Database>()
+        .with_conn(|conn| time_exports::list_failed(conn, provider.key()))
+}
+
+/// Try entries that were given up on again. Returns how many.
+pub fn retry_failed(app: &AppHandle) -> Result<usize, String> {
+    let Some(provider) = settings::load(app)?.integrations.time_export.provider else {
+        return Ok(0);
+    };
+    app.state::<Database>()
+        .with_conn(|conn| time_exports::retry_failed(conn, provider.key()))
+}
+
+fn matches(rule: &TimeExportRule, title: &str, channel: Option<&str>) -> bool {
+    let channel_matches = rule.channel.as_deref().is_none_or(|wanted| {
+        channel.is_some_and(|channel| channel.eq_ignore_ascii_case(wanted.trim_start_matches('#')))
+    });
+    let title_matches = rule
+        .title_contains
+        .as_deref()
+        .is_none_or(|text| title.to_lowercase().contains(&text.to_lowercase()));
+    channel_matches && title_matches
+}
+
+/// Send a provider request and read the id of what it created. Once the
+/// provider accepted it, an unreadable reply still counts as created.
+async fn create(
+    app: &AppHandle,
+    integration: Integration,
+    label: &str,
+    request: RequestBuilder,
+) -> Result<Option<String>, AppError> {
+    let response = http::send(app, integration, request).await?;
+    let status = response.status();
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        return Err(AppError::new(ErrorCode::AuthExpired, format!("{} returned {}", label, status)));
+    }
+    let body = response.text().await;
+    if !status.is_success() {
+        let body = body.unwrap_or_default();
+        return Err(AppError::from_response(
+            status,
+            None,
+            format!("{} returned {}: {}", label, status, body.trim()),
+        ));
+    }
+
+    let created = body.ok().and_then(|body| serde_json::from_str::<Value>(&body).ok());
+    match created.as_ref().and_then(|created| created.get("id")) {
+        Some(Value::String(id)) => Ok(Some(id.clone())),
+        Some(Value::Number(id)) => Ok(Some(id.to_string())),
+        _ => {
+            tracing::warn!("{} didn't return an id for an exported entry", label);
+            Ok(None)
+        }
+    }
+}
+
+/// Unix milliseconds as RFC 3339
+fn rfc3339(millis: i64) -> String {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(|at| at.to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// The local date an entry started on
+fn local_date(millis: i64) -> Option<NaiveDate> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(|at| at.with_timezone(&timezone::local()).date_naive())
+}
+
+fn since(config: &TimeExportSettings, now: i64) -> Result<i64, String> {
+    let date = match &config.since {
+        Some(date) => date
+            .parse::<NaiveDate>()
+            .map_err(|e| format!("Invalid export start date: {}", e))?,
+        None => local_date(now)
+            .and_then(|today| today.checked_sub_days(Days::new(DEFAULT_LOOKBACK_DAYS)))
+            .ok_or_else(|| "Invalid local date".to_string())?,
+    };
+    timezone::day_bounds(date, timezone::local())
+        .map(|(start, _)| start)
+        .ok_or_else(|| format!("Invalid date: {}", date))
+}
+
+fn retry_delay(attempts: i64) -> i64 {
+    let exponent = u32::try_from(attempts.saturating_sub(1)).unwrap_or(u32::MAX).min(16);
+    RETRY_BASE_MS.saturating_mul(1 << exponent).min(RETRY_MAX_MS)
+}
+
+/// The first Jira issue key (`ABC-123`) in `text`
+fn issue_key(text: &str) -> Option<String> {
+    static PATTERN: OnceLock<Option<Regex>> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| Regex::new(r"\b[A-Z][A-Z0-9]+-\d+\b").ok())
+        .as_ref()?
+        .find(text)
+        .map(|found| found.as_str().to_string())
+}
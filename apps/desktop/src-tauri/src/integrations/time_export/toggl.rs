@@ -0,0 +1,50 @@
+//! Toggl Track, through the v9 API. The target is an optional project id.
+
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use super::{create, rfc3339};
+use crate::db::time_entries::TimeEntry;
+use crate::error::AppError;
+use crate::http::{self, Integration};
+use crate::settings::TimeExportSettings;
+
+const API_URL: &str = "https://api.track.toggl.com/api/v9";
+
+pub async fn push(
+    app: &AppHandle,
+    config: &TimeExportSettings,
+    token: &str,
+    entry: &TimeEntry,
+    target: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let workspace_id = config
+        .toggl_workspace_id
+        .ok_or_else(|| "Set the Toggl workspace to export to".to_string())?;
+    let project_id = match target {
+        Some(target) => Some(
+            target
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid Toggl project id: {}", target))?,
+        ),
+        None => None,
+    };
+    let ended_at = entry.ended_at.unwrap_or(entry.started_at);
+
+    let body = json!({
+        "created_with": "Open Sunsama",
+        "workspace_id": workspace_id,
+        "project_id": project_id.map_or(Value::Null, Value::from),
+        "description": entry.task_title,
+        "start": rfc3339(entry.started_at),
+        "stop": rfc3339(ended_at),
+        "duration": (ended_at - entry.started_at) / 1000,
+    });
+    let request = http::client(app)
+        .post(format!("{}/workspaces/{}/time_entries", API_URL, workspace_id))
+        .basic_auth(token, Some("api_token"))
+        .json(&body);
+
+    create(app, Integration::Toggl, "Toggl", request).await
+}
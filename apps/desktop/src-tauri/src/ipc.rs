@@ -0,0 +1,56 @@
+//! Binary responses for large IPC payloads: archive pages, analytics and
+//! import previews. They skip the JSON string the webview would otherwise
+//! parse and come back as raw bytes (an `ArrayBuffer`) in the encoding the
+//! caller asked for. `get_ipc_features` tells the frontend what this build
+//! supports so it can fall back to the plain JSON commands.
+
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Response;
+
+/// Commands that answer with raw bytes
+pub const PACKED_COMMANDS: &[&str] = &[
+    "query_tasks_packed",
+    "get_period_summary_packed",
+    "preview_sunsama_import_packed",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encoding {
+    /// UTF-8 JSON bytes, for callers without a MessagePack decoder
+    Json,
+    /// MessagePack with field names, decoding to the same shape as JSON
+    #[default]
+    Msgpack,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 2] = [Encoding::Json, Encoding::Msgpack];
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpcFeatures {
+    pub encodings: Vec<Encoding>,
+    /// Used when a packed command is called without one
+    pub default_encoding: Encoding,
+    pub packed_commands: Vec<&'static str>,
+}
+
+pub fn features() -> IpcFeatures {
+    IpcFeatures {
+        encodings: Encoding::ALL.to_vec(),
+        default_encoding: Encoding::default(),
+        packed_commands: PACKED_COMMANDS.to_vec(),
+    }
+}
+
+/// `value` as a raw response in `encoding`
+pub fn encode<T: Serialize>(value: &T, encoding: Option<Encoding>) -> Result<Response, String> {
+    let bytes = match encoding.unwrap_or_default() {
+        Encoding::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        Encoding::Msgpack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("Failed to encode response: {}", e))?;
+    Ok(Response::new(bytes))
+}
@@ -0,0 +1,79 @@
+//! Windows taskbar jump list. Each task relaunches the app with an
+//! automation link as its argument; the single-instance plugin forwards it
+//! to the running app, which handles it like any other `opensunsama://`
+//! link. Other platforms have no jump list, so this is a no-op there.
+
+/// Replace the jump list's tasks. Runs on its own thread since it needs a
+/// COM apartment.
+pub fn install() {
+    #[cfg(target_os = "windows")]
+    let _ = std::thread::Builder::new()
+        .name("jump-list".to_string())
+        .spawn(|| {
+            if let Err(e) = platform::install() {
+                tracing::warn!("Failed to set up jump list: {}", e);
+            }
+        });
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::core::{Interface, HSTRING, PROPVARIANT};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED,
+    };
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{
+        DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink,
+    };
+
+    /// Jump list tasks: label and the automation link they open
+    const TASKS: &[(&str, &str)] = &[
+        ("New Task", "opensunsama://new-task"),
+        ("Start Focus", "opensunsama://start-focus"),
+        ("Today View", "opensunsama://today"),
+    ];
+
+    pub fn install() -> Result<(), String> {
+        let exe = std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))?;
+        let exe = HSTRING::from(exe.as_os_str());
+
+        unsafe {
+            CoInitializeEx(None, COINIT_APARTMENTTHREADED)
+                .ok()
+                .map_err(|e| e.to_string())?;
+            let result = build(&exe, TASKS).map_err(|e| e.to_string());
+            CoUninitialize();
+            result
+        }
+    }
+
+    unsafe fn build(exe: &HSTRING, tasks: &[(&str, &str)]) -> windows::core::Result<()> {
+        let list: ICustomDestinationList = CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        let mut min_slots = 0u32;
+        let _removed: IObjectArray = list.BeginList(&mut min_slots)?;
+
+        let collection: IObjectCollection =
+            CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+        for (title, link) in tasks {
+            let shell_link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            shell_link.SetPath(exe)?;
+            shell_link.SetArguments(&HSTRING::from(*link))?;
+            shell_link.SetIconLocation(exe, 0)?;
+
+            // Jump list tasks show the title property, not the description
+            let properties: IPropertyStore = shell_link.cast()?;
+            properties.SetValue(&PKEY_Title, &PROPVARIANT::from(*title))?;
+            properties.Commit()?;
+
+            collection.AddObject(&shell_link)?;
+        }
+
+        let array: IObjectArray = collection.cast()?;
+        list.AddUserTasks(&array)?;
+        list.CommitList()
+    }
+}
@@ -0,0 +1,47 @@
+//! Thin wrapper over the OS keychain (Keychain, Credential Manager,
+//! Secret Service). All entries share one service name; accounts of
+//! profiles other than the default are prefixed with the profile id.
+
+use std::sync::OnceLock;
+
+use crate::profiles::DEFAULT_PROFILE;
+
+const SERVICE: &str = "app.opensunsama.desktop";
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Scope entries to the profile this run uses. Set once at startup.
+pub fn set_profile(profile: &str) {
+    if profile != DEFAULT_PROFILE {
+        let _ = PROFILE.set(profile.to_string());
+    }
+}
+
+fn entry(account: &str) -> Result<keyring::Entry, String> {
+    let account = match PROFILE.get() {
+        Some(profile) => format!("{}:{}", profile, account),
+        None => account.to_string(),
+    };
+    keyring::Entry::new(SERVICE, &account).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+pub fn get(account: &str) -> Result<Option<String>, String> {
+    match entry(account)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read from keychain: {}", e)),
+    }
+}
+
+pub fn set(account: &str, secret: &str) -> Result<(), String> {
+    entry(account)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to save to keychain: {}", e))
+}
+
+pub fn delete(account: &str) -> Result<(), String> {
+    match entry(account)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to remove from keychain: {}", e)),
+    }
+}
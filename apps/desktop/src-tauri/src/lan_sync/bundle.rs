@@ -0,0 +1,112 @@
+//! What two devices exchange when syncing over the LAN: every CRDT text
+//! document plus the time entries and time blocks in the local database,
+//! and the entries and blocks deleted. Text merges through the CRDT;
+//! entries and blocks keep whichever copy changed last, and a deletion wins
+//! over any copy older than it. Demo data stays behind.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::time_entries::{self, TimeEntry};
+use crate::db::tombstones::{self, Tombstone};
+use crate::db::{text_docs, Database};
+use crate::demo;
+use crate::sync::crdt::{self, TextField};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    pub text_docs: Vec<TextDocState>,
+    pub time_entries: Vec<TimeEntry>,
+    pub time_blocks: Vec<TimeBlock>,
+    #[serde(default)]
+    pub tombstones: Vec<Tombstone>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocState {
+    pub task_id: String,
+    pub field: TextField,
+    /// Base64 Yjs update holding the whole document
+    pub update: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeSummary {
+    pub text_docs: usize,
+    pub time_entries: usize,
+    pub time_blocks: usize,
+    /// Entries and blocks deleted here because the other side had
+    pub deleted: usize,
+}
+
+pub fn collect(app: &AppHandle) -> Result<Bundle, String> {
+    app.state::<Database>().with_conn(|conn| {
+        let text_docs = text_docs::all(conn)?
+            .into_iter()
+            .filter(|doc| !demo::is_demo(&doc.task_id))
+            .filter_map(|doc| {
+                Some(TextDocState {
+                    field: TextField::parse(&doc.field)?,
+                    update: BASE64.encode(&doc.state),
+                    task_id: doc.task_id,
+                })
+            })
+            .collect();
+
+        let mut time_entries = time_entries::list_between(conn, i64::MIN, i64::MAX)?;
+        time_entries.retain(|entry| !demo::is_demo(&entry.id));
+        let mut time_blocks = time_blocks::list_between(conn, i64::MIN, i64::MAX)?;
+        time_blocks.retain(|block| !demo::is_demo(&block.id));
+        let mut tombstones = tombstones::all(conn)?;
+        tombstones.retain(|tombstone| !demo::is_demo(&tombstone.id));
+
+        Ok(Bundle {
+            text_docs,
+            time_entries,
+            time_blocks,
+            tombstones,
+        })
+    })
+}
+
+pub fn merge(app: &AppHandle, bundle: Bundle) -> Result<MergeSummary, String> {
+    let mut summary = MergeSummary {
+        text_docs: bundle.text_docs.len(),
+        time_entries: bundle.time_entries.len(),
+        time_blocks: bundle.time_blocks.len(),
+        deleted: 0,
+    };
+
+    summary.deleted = app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        for entry in &bundle.time_entries {
+            time_entries::merge(&tx, entry)?;
+        }
+        for block in &bundle.time_blocks {
+            time_blocks::merge(&tx, block)?;
+        }
+        let mut deleted = 0;
+        for tombstone in &bundle.tombstones {
+            if tombstones::apply(&tx, tombstone)? {
+                deleted += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(deleted)
+    })?;
+
+    // Merged text is also sent on to the server when cloud sync is on
+    for doc in &bundle.text_docs {
+        crdt::apply_update(app, &doc.task_id, doc.field, &doc.update)?;
+    }
+
+    if summary.time_blocks > 0 || summary.deleted > 0 {
+        let _ = app.emit("time-blocks-changed", ());
+    }
+    Ok(summary)
+}
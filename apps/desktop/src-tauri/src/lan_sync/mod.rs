@@ -0,0 +1,720 @@
+//! Opt-in sync with paired devices on the same network, for people who
+//! don't want a cloud backend. Devices advertise themselves over mDNS as
+//! `_opensunsama._tcp` and sync over TCP. Every exchange after the greeting
+//! is encrypted with a key agreed during pairing.
+//!
+//! Pairing is started on one device, which shows a short code and a QR code
+//! with the code and its address. The other device connects with the code,
+//! scanned or typed. A pairing window lasts five minutes and closes after
+//! too many wrong codes.
+//!
+//! Paired devices sync whenever they see each other and then every few
+//! minutes while both are online. The device name, the id and the list of
+//! paired devices live in `lan-sync.json`; pair keys live in the keychain.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+use crate::crypto::{self, SecretKey, KEY_LEN};
+use crate::power::{self, Budget};
+use crate::settings::SyncSettings;
+use crate::{clock, data_dir, keychain, settings};
+
+mod bundle;
+mod protocol;
+
+pub use bundle::MergeSummary;
+use protocol::{Challenge, Hello, PairAck, PairOffer, PairStart, Spake, MAX_FRAME_LEN, MAX_HANDSHAKE_LEN};
+
+const SERVICE_TYPE: &str = "_opensunsama._tcp.local.";
+const STORE: &str = "lan-sync.json";
+const DEVICE_KEY: &str = "device";
+const PEERS_KEY: &str = "peers";
+/// Keychain account prefix for pair keys
+const KEY_ACCOUNT_PREFIX: &str = "lan-peer:";
+
+const PAIRING_WINDOW: Duration = Duration::from_secs(5 * 60);
+const MAX_PAIRING_FAILURES: u32 = 5;
+/// Pairing code length; 8 characters of a 32-letter alphabet (40 bits).
+/// SPAKE2 leaves only online guesses, a handful per pairing window.
+const CODE_LEN: usize = 8;
+/// No 0/O or 1/I, which get misread
+const CODE_ALPHABET: &[u8; 32] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+const NONCE_LEN: usize = 16;
+
+const AUTO_SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceIdentity {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairedDevice {
+    pub id: String,
+    pub name: String,
+    pub paired_at: i64,
+    pub last_synced_at: Option<i64>,
+}
+
+/// A device seen on the network
+#[derive(Debug, Clone)]
+struct DiscoveredDevice {
+    name: String,
+    addresses: Vec<IpAddr>,
+    port: u16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanDevice {
+    pub id: String,
+    pub name: String,
+    pub paired: bool,
+    /// Currently seen on the network
+    pub online: bool,
+    pub last_synced_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingInfo {
+    /// Shown as `ABCD-EFGH` for typing on the other device
+    pub code: String,
+    /// `opensunsama-pair:` link with the code and this device's address
+    pub uri: String,
+    /// The link as a QR code
+    pub qr_svg: String,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanSyncStatus {
+    pub enabled: bool,
+    pub device_id: String,
+    pub device_name: String,
+    pub port: Option<u16>,
+    pub pairing: Option<PairingInfo>,
+    pub devices: Vec<LanDevice>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanSyncCompleted {
+    pub device_id: String,
+    pub received: MergeSummary,
+}
+
+struct Pairing {
+    info: PairingInfo,
+    failures: u32,
+}
+
+struct Running {
+    daemon: ServiceDaemon,
+    port: u16,
+    stop: watch::Sender<bool>,
+}
+
+#[derive(Default)]
+pub struct LanSyncState {
+    running: Mutex<Option<Running>>,
+    discovered: Mutex<HashMap<String, DiscoveredDevice>>,
+    pairing: Mutex<Option<Pairing>>,
+}
+
+/// Start advertising and listening if the setting is on
+pub fn start(app: &AppHandle) {
+    if let Ok(settings) = settings::load(app) {
+        reconfigure(app, &settings.sync);
+    }
+}
+
+/// Follow the LAN sync setting after a change
+pub fn reconfigure(app: &AppHandle, sync: &SyncSettings) {
+    // Settings can be applied before setup gets here; `start` catches up
+    let Some(state) = app.try_state::<LanSyncState>() else {
+        return;
+    };
+    let running = state.running.lock().is_ok_and(|running| running.is_some());
+
+    if sync.lan_sync && !running {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = enable(&app).await {
+                let _ = app.emit("lan-sync-error", e);
+            }
+        });
+    } else if !sync.lan_sync && running {
+        disable(app);
+    }
+}
+
+pub fn status(app: &AppHandle) -> Result<LanSyncStatus, String> {
+    let state = app.state::<LanSyncState>();
+    let identity = identity(app)?;
+    let port = state
+        .running
+        .lock()
+        .ok()
+        .and_then(|running| running.as_ref().map(|running| running.port));
+    let pairing = state
+        .pairing
+        .lock()
+        .ok()
+        .and_then(|pairing| pairing.as_ref().map(|pairing| pairing.info.clone()))
+        .filter(|info| info.expires_at > clock::now_millis());
+
+    Ok(LanSyncStatus {
+        enabled: port.is_some(),
+        device_id: identity.id,
+        device_name: identity.name,
+        port,
+        pairing,
+        devices: devices(app)?,
+    })
+}
+
+/// Paired devices, then unpaired ones currently on the network
+pub fn devices(app: &AppHandle) -> Result<Vec<LanDevice>, String> {
+    let discovered = app
+        .state::<LanSyncState>()
+        .discovered
+        .lock()
+        .map(|discovered| discovered.clone())
+        .unwrap_or_default();
+    let paired = paired_devices(app)?;
+
+    let mut devices: Vec<LanDevice> = paired
+        .iter()
+        .map(|device| LanDevice {
+            id: device.id.clone(),
+            name: device.name.clone(),
+            paired: true,
+            online: discovered.contains_key(&device.id),
+            last_synced_at: device.last_synced_at,
+        })
+        .collect();
+    devices.extend(
+        discovered
+            .into_iter()
+            .filter(|(id, _)| !paired.iter().any(|device| &device.id == id))
+            .map(|(id, device)| LanDevice {
+                id,
+                name: device.name,
+                paired: false,
+                online: true,
+                last_synced_at: None,
+            }),
+    );
+    Ok(devices)
+}
+
+/// Open a pairing window on this device and return the code to enter on
+/// the other one
+pub fn start_pairing(app: &AppHandle) -> Result<PairingInfo, String> {
+    let state = app.state::<LanSyncState>();
+    let port = state
+        .running
+        .lock()
+        .ok()
+        .and_then(|running| running.as_ref().map(|running| running.port))
+        .ok_or_else(|| "Turn on LAN sync to pair devices".to_string())?;
+
+    let code = new_code()?;
+    let identity = identity(app)?;
+    let host = local_ip().map(|ip| ip.to_string()).unwrap_or_default();
+    let uri = format!(
+        "opensunsama-pair:?id={}&host={}&port={}&code={}",
+        identity.id, host, port, code
+    );
+    let qr_svg = QrCode::new(uri.as_bytes())
+        .map_err(|e| format!("Failed to create QR code: {}", e))?
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .build();
+
+    let info = PairingInfo {
+        code: format!("{}-{}", &code[..CODE_LEN / 2], &code[CODE_LEN / 2..]),
+        uri,
+        qr_svg,
+        expires_at: clock::now_millis() + PAIRING_WINDOW.as_millis() as i64,
+    };
+    *state
+        .pairing
+        .lock()
+        .map_err(|e| format!("Failed to lock pairing: {}", e))? = Some(Pairing {
+        info: info.clone(),
+        failures: 0,
+    });
+    Ok(info)
+}
+
+pub fn cancel_pairing(app: &AppHandle) {
+    if let Ok(mut pairing) = app.state::<LanSyncState>().pairing.lock() {
+        *pairing = None;
+    }
+}
+
+/// Pair with a device on the network showing `code`, then sync with it
+pub async fn pair(app: &AppHandle, device_id: &str, code: &str) -> Result<PairedDevice, String> {
+    let mut stream = connect(app, device_id).await?;
+    let identity = identity(app)?;
+    let (spake, message) = Spake::start(code);
+
+    protocol::write_plain(
+        &mut stream,
+        &Hello::Pair {
+            device_id: identity.id.clone(),
+            name: identity.name,
+            spake: message,
+        },
+    )
+    .await?;
+    let start: PairStart = protocol::read_plain(&mut stream).await?;
+    let code_key = spake.finish(&start.spake)?;
+    let offer: PairOffer = protocol::read_sealed(&mut stream, &code_key, MAX_HANDSHAKE_LEN)
+        .await
+        .map_err(|_| "Wrong pairing code".to_string())?;
+    if offer.device_id != device_id {
+        return Err("A different device answered".to_string());
+    }
+    let key = decode_key(&offer.key)?;
+    protocol::write_sealed(&mut stream, &key, &PairAck { device_id: identity.id }).await?;
+
+    let device = remember(app, &offer.device_id, &offer.name, &key)?;
+    sync_with(app, device_id).await?;
+    Ok(device)
+}
+
+pub fn unpair(app: &AppHandle, device_id: &str) -> Result<(), String> {
+    keychain::delete(&key_account(device_id))?;
+    let mut devices = paired_devices(app)?;
+    devices.retain(|device| device.id != device_id);
+    save_paired(app, &devices)?;
+    emit_devices(app);
+    Ok(())
+}
+
+/// Exchange data with a paired device on the network
+pub async fn sync_with(app: &AppHandle, device_id: &str) -> Result<MergeSummary, String> {
+    let key = pair_key(device_id)?.ok_or_else(|| "Device is not paired".to_string())?;
+    let mut stream = connect(app, device_id).await?;
+    let identity = identity(app)?;
+
+    protocol::write_plain(
+        &mut stream,
+        &Hello::Sync {
+            device_id: identity.id,
+            name: identity.name,
+        },
+    )
+    .await?;
+    let challenge: Challenge = protocol::read_plain(&mut stream).await?;
+    protocol::write_sealed(&mut stream, &key, &challenge).await?;
+    protocol::write_sealed(&mut stream, &key, &bundle::collect(app)?).await?;
+    let theirs: bundle::Bundle = protocol::read_sealed(&mut stream, &key, MAX_FRAME_LEN).await?;
+
+    let received = bundle::merge(app, theirs)?;
+    synced(app, device_id, &received)?;
+    Ok(received)
+}
+
+async fn enable(app: &AppHandle) -> Result<(), String> {
+    let listener = TcpListener::bind(("0.0.0.0", 0))
+        .await
+        .map_err(|e| format!("Failed to listen for devices: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to listen for devices: {}", e))?
+        .port();
+
+    let identity = identity(app)?;
+    let daemon = ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS: {}", e))?;
+    let host_name = format!("{}.local.", identity.id);
+    let properties = [("id", identity.id.as_str()), ("name", identity.name.as_str())];
+    let service = ServiceInfo::new(SERVICE_TYPE, &identity.id, &host_name, "", port, &properties[..])
+        .map_err(|e| format!("Failed to advertise device: {}", e))?
+        .enable_addr_auto();
+    daemon
+        .register(service)
+        .map_err(|e| format!("Failed to advertise device: {}", e))?;
+
+    let (stop, stopped) = watch::channel(false);
+    spawn_browser(app, daemon.clone(), identity.id, stopped.clone());
+    *app.state::<LanSyncState>()
+        .running
+        .lock()
+        .map_err(|e| format!("Failed to lock LAN sync: {}", e))? = Some(Running { daemon, port, stop });
+
+    spawn_listener(app, listener, stopped.clone());
+    spawn_auto_sync(app, stopped);
+    let _ = app.emit("lan-sync-status-changed", true);
+    Ok(())
+}
+
+fn disable(app: &AppHandle) {
+    let state = app.state::<LanSyncState>();
+    let running = state.running.lock().ok().and_then(|mut running| running.take());
+    if let Some(running) = running {
+        let _ = running.stop.send(true);
+        let _ = running.daemon.shutdown();
+    }
+    if let Ok(mut discovered) = state.discovered.lock() {
+        discovered.clear();
+    }
+    cancel_pairing(app);
+    let _ = app.emit("lan-sync-status-changed", false);
+}
+
+fn spawn_listener(app: &AppHandle, listener: TcpListener, mut stopped: watch::Receiver<bool>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(_) => continue,
+                },
+                _ = stopped.changed() => return,
+            };
+
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let handled = tokio::time::timeout(CONNECTION_TIMEOUT, handle_incoming(&app, stream)).await;
+                if let Ok(Err(e)) = handled {
+                    let _ = app.emit("lan-sync-error", e);
+                }
+            });
+        }
+    });
+}
+
+/// Track devices coming and going. mdns-sd hands out a blocking channel,
+/// so this runs on its own thread.
+fn spawn_browser(app: &AppHandle, daemon: ServiceDaemon, own_id: String, stopped: watch::Receiver<bool>) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let Ok(browser) = daemon.browse(SERVICE_TYPE) else {
+            let _ = app.emit("lan-sync-error", "Failed to browse for devices");
+            return;
+        };
+
+        while !*stopped.borrow() {
+            let Ok(event) = browser.recv_timeout(Duration::from_secs(1)) else {
+                continue;
+            };
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    let Some(id) = info.get_property_val_str("id").map(str::to_string) else {
+                        continue;
+                    };
+                    if id == own_id {
+                        continue;
+                    }
+                    let device = DiscoveredDevice {
+                        name: info.get_property_val_str("name").unwrap_or("Unknown device").to_string(),
+                        addresses: info.get_addresses().iter().copied().collect(),
+                        port: info.get_port(),
+                    };
+                    let is_new = app
+                        .state::<LanSyncState>()
+                        .discovered
+                        .lock()
+                        .map(|mut discovered| discovered.insert(id.clone(), device).is_none())
+                        .unwrap_or(false);
+                    emit_devices(&app);
+
+                    // A paired device just showed up; catch up with it
+                    if is_new && matches!(pair_key(&id), Ok(Some(_))) {
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = sync_with(&app, &id).await {
+                                let _ = app.emit("lan-sync-error", e);
+                            }
+                        });
+                    }
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    if let Ok(mut discovered) = app.state::<LanSyncState>().discovered.lock() {
+                        // Instances are named after the device id
+                        discovered.retain(|id, _| !fullname.starts_with(&format!("{}.", id)));
+                    }
+                    emit_devices(&app);
+                }
+                _ => {}
+            }
+        }
+        let _ = daemon.stop_browse(SERVICE_TYPE);
+    });
+}
+
+fn spawn_auto_sync(app: &AppHandle, mut stopped: watch::Receiver<bool>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(power::interval(&app, AUTO_SYNC_INTERVAL)) => {}
+                _ = stopped.changed() => return,
+            }
+            if power::budget(&app) == Budget::Paused {
+                continue;
+            }
+
+            let online: Vec<String> = devices(&app)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|device| device.paired && device.online)
+                .map(|device| device.id)
+                .collect();
+            for id in online {
+                if let Err(e) = sync_with(&app, &id).await {
+                    let _ = app.emit("lan-sync-error", e);
+                }
+            }
+        }
+    });
+}
+
+async fn handle_incoming(app: &AppHandle, mut stream: TcpStream) -> Result<(), String> {
+    match protocol::read_plain::<Hello>(&mut stream).await? {
+        Hello::Pair { device_id, name, spake } => accept_pairing(app, stream, &device_id, &name, &spake).await,
+        Hello::Sync { device_id, .. } => {
+            // Unknown devices get nothing
+            let Some(key) = pair_key(&device_id)? else {
+                return Ok(());
+            };
+            // Nothing large is read until the peer shows it holds the key
+            let nonce = BASE64.encode(crypto::random_bytes::<NONCE_LEN>()?);
+            protocol::write_plain(&mut stream, &Challenge { nonce: nonce.clone() }).await?;
+            let answer: Challenge = protocol::read_sealed(&mut stream, &key, MAX_HANDSHAKE_LEN).await?;
+            if answer.nonce != nonce {
+                return Err("Device failed the sync challenge".to_string());
+            }
+            let theirs: bundle::Bundle = protocol::read_sealed(&mut stream, &key, MAX_FRAME_LEN).await?;
+            let received = bundle::merge(app, theirs)?;
+            protocol::write_sealed(&mut stream, &key, &bundle::collect(app)?).await?;
+            synced(app, &device_id, &received)
+        }
+    }
+}
+
+async fn accept_pairing(
+    app: &AppHandle,
+    mut stream: TcpStream,
+    device_id: &str,
+    name: &str,
+    spake: &str,
+) -> Result<(), String> {
+    let state = app.state::<LanSyncState>();
+    let code = state
+        .pairing
+        .lock()
+        .ok()
+        .and_then(|pairing| pairing.as_ref().map(|pairing| pairing.info.clone()))
+        .filter(|info| info.expires_at > clock::now_millis())
+        .map(|info| info.code);
+    let Some(code) = code else {
+        return Ok(());
+    };
+
+    let (ours, message) = Spake::start(&code);
+    protocol::write_plain(&mut stream, &PairStart { spake: message }).await?;
+    let code_key = ours.finish(spake)?;
+    let key = crypto::random_bytes::<KEY_LEN>()?;
+    let identity = identity(app)?;
+    protocol::write_sealed(
+        &mut stream,
+        &code_key,
+        &PairOffer {
+            device_id: identity.id,
+            name: identity.name,
+            key: BASE64.encode(key),
+        },
+    )
+    .await?;
+
+    // Only a device that knew the code could open the offer and answer
+    let acked = protocol::read_sealed::<PairAck>(&mut stream, &key, MAX_HANDSHAKE_LEN)
+        .await
+        .is_ok_and(|ack| ack.device_id == device_id);
+    if !acked {
+        if let Ok(mut pairing) = state.pairing.lock() {
+            let failures = pairing.as_mut().map(|pairing| {
+                pairing.failures += 1;
+                pairing.failures
+            });
+            if failures.is_some_and(|failures| failures >= MAX_PAIRING_FAILURES) {
+                *pairing = None;
+                let _ = app.emit("lan-pairing-ended", ());
+            }
+        }
+        return Err("Pairing failed: wrong code".to_string());
+    }
+
+    cancel_pairing(app);
+    let device = remember(app, device_id, name, &key)?;
+    let _ = app.emit("lan-device-paired", &device);
+    Ok(())
+}
+
+/// Open a connection to a device seen on the network
+async fn connect(app: &AppHandle, device_id: &str) -> Result<TcpStream, String> {
+    let device = app
+        .state::<LanSyncState>()
+        .discovered
+        .lock()
+        .ok()
+        .and_then(|discovered| discovered.get(device_id).cloned())
+        .ok_or_else(|| "Device is not on this network".to_string())?;
+
+    let mut last_error = "Device has no address".to_string();
+    for address in &device.addresses {
+        let connecting = TcpStream::connect(SocketAddr::new(*address, device.port));
+        match tokio::time::timeout(CONNECTION_TIMEOUT, connecting).await {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(e)) => last_error = e.to_string(),
+            Err(_) => last_error = "Timed out".to_string(),
+        }
+    }
+    Err(format!("Failed to reach {}: {}", device.name, last_error))
+}
+
+fn synced(app: &AppHandle, device_id: &str, received: &MergeSummary) -> Result<(), String> {
+    let mut devices = paired_devices(app)?;
+    if let Some(device) = devices.iter_mut().find(|device| device.id == device_id) {
+        device.last_synced_at = Some(clock::now_millis());
+    }
+    save_paired(app, &devices)?;
+
+    let _ = app.emit(
+        "lan-sync-completed",
+        LanSyncCompleted {
+            device_id: device_id.to_string(),
+            received: received.clone(),
+        },
+    );
+    emit_devices(app);
+    Ok(())
+}
+
+fn remember(app: &AppHandle, device_id: &str, name: &str, key: &SecretKey) -> Result<PairedDevice, String> {
+    keychain::set(&key_account(device_id), &BASE64.encode(key))?;
+
+    let device = PairedDevice {
+        id: device_id.to_string(),
+        name: name.to_string(),
+        paired_at: clock::now_millis(),
+        last_synced_at: None,
+    };
+    let mut devices = paired_devices(app)?;
+    devices.retain(|existing| existing.id != device_id);
+    devices.push(device.clone());
+    save_paired(app, &devices)?;
+    emit_devices(app);
+    Ok(device)
+}
+
+fn emit_devices(app: &AppHandle) {
+    if let Ok(devices) = devices(app) {
+        let _ = app.emit("lan-devices-changed", devices);
+    }
+}
+
+/// This device's id and name, created on first use
+fn identity(app: &AppHandle) -> Result<DeviceIdentity, String> {
+    let store = app
+        .store(data_dir::store_path(app, STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    if let Some(identity) = store
+        .get(DEVICE_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+    {
+        return Ok(identity);
+    }
+
+    let identity = DeviceIdentity {
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        name: gethostname::gethostname().to_string_lossy().into_owned(),
+    };
+    store.set(
+        DEVICE_KEY,
+        serde_json::to_value(&identity).map_err(|e| format!("Failed to encode device: {}", e))?,
+    );
+    store
+        .save()
+        .map_err(|e| format!("Failed to save device: {}", e))?;
+    Ok(identity)
+}
+
+fn paired_devices(app: &AppHandle) -> Result<Vec<PairedDevice>, String> {
+    let store = app
+        .store(data_dir::store_path(app, STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    Ok(store
+        .get(PEERS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
+fn save_paired(app: &AppHandle, devices: &[PairedDevice]) -> Result<(), String> {
+    let store = app
+        .store(data_dir::store_path(app, STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store.set(
+        PEERS_KEY,
+        serde_json::to_value(devices).map_err(|e| format!("Failed to encode devices: {}", e))?,
+    );
+    store
+        .save()
+        .map_err(|e| format!("Failed to save devices: {}", e))
+}
+
+fn key_account(device_id: &str) -> String {
+    format!("{}{}", KEY_ACCOUNT_PREFIX, device_id)
+}
+
+fn pair_key(device_id: &str) -> Result<Option<SecretKey>, String> {
+    keychain::get(&key_account(device_id))?
+        .map(|encoded| decode_key(&encoded))
+        .transpose()
+}
+
+fn decode_key(encoded: &str) -> Result<SecretKey, String> {
+    BASE64
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| SecretKey::try_from(bytes).ok())
+        .ok_or_else(|| "Corrupt pair key".to_string())
+}
+
+fn new_code() -> Result<String, String> {
+    Ok(crypto::random_bytes::<CODE_LEN>()?
+        .iter()
+        .map(|byte| CODE_ALPHABET[(*byte as usize) % CODE_ALPHABET.len()] as char)
+        .collect())
+}
+
+/// The address other devices reach this one on. Connecting a UDP socket
+/// picks the outgoing interface without sending anything.
+fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("192.0.2.1", 9)).ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
@@ -0,0 +1,171 @@
+//! Wire protocol between two devices. Every message is a frame: a
+//! big-endian u32 length and that many bytes. Greetings are plain JSON;
+//! everything after them is sealed with XChaCha20-Poly1305. Until the peer
+//! has proven it holds a key, frames are capped at a few KiB.
+//!
+//! Pairing: the connecting device sends `Hello::Pair` with its SPAKE2
+//! message for the pairing code, and the host answers with its own. Both
+//! derive the same key only if they used the same code, and an eavesdropper
+//! learns nothing to guess the code against offline; each wrong guess costs
+//! a connection. The host sends its identity and a new random pair key
+//! sealed under the SPAKE2 key, the connecting device acknowledges under the
+//! pair key, and both keep the key in the keychain.
+//!
+//! Sync: the connecting device sends `Hello::Sync`; the host challenges it
+//! with a random nonce, which it returns sealed under the pair key. Then it
+//! sends its bundle, sealed; the host merges it and replies with its own.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spake2::{Ed25519Group, Identity, Password, Spake2};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::crypto::{self, SecretKey};
+
+/// Largest frame accepted from a peer that proved it holds the pair key
+pub const MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
+/// Largest frame accepted before that: greetings, pairing, the challenge
+pub const MAX_HANDSHAKE_LEN: usize = 4 * 1024;
+/// Both sides of a pairing use this SPAKE2 identity
+const SPAKE_IDENTITY: &[u8] = b"opensunsama-lan-pair";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", rename_all_fields = "camelCase")]
+pub enum Hello {
+    Pair {
+        device_id: String,
+        name: String,
+        /// Base64 SPAKE2 message for the pairing code
+        spake: String,
+    },
+    Sync {
+        device_id: String,
+        name: String,
+    },
+}
+
+/// The host's reply to `Hello::Pair`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairStart {
+    /// Base64 SPAKE2 message for the pairing code
+    pub spake: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairOffer {
+    pub device_id: String,
+    pub name: String,
+    /// Base64 pair key for all later syncs
+    pub key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairAck {
+    pub device_id: String,
+}
+
+/// The host's reply to `Hello::Sync`, and the answer sealed under the pair
+/// key
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Challenge {
+    /// Base64 random nonce
+    pub nonce: String,
+}
+
+/// One side of a SPAKE2 exchange for a pairing code
+pub struct Spake(Spake2<Ed25519Group>);
+
+impl Spake {
+    /// Start the exchange. Returns the message to send, in base64.
+    pub fn start(code: &str) -> (Self, String) {
+        let (spake, message) = Spake2::<Ed25519Group>::start_symmetric(
+            &Password::new(normalize_code(code).as_bytes()),
+            &Identity::new(SPAKE_IDENTITY),
+        );
+        (Spake(spake), BASE64.encode(message))
+    }
+
+    /// The key agreed with the peer's `message`. A peer that used another
+    /// code ends up with another key, so nothing it sealed opens.
+    pub fn finish(self, message: &str) -> Result<SecretKey, String> {
+        let message = BASE64
+            .decode(message)
+            .map_err(|e| format!("Invalid pairing message: {}", e))?;
+        let key = self
+            .0
+            .finish(&message)
+            .map_err(|e| format!("Invalid pairing message: {:?}", e))?;
+        SecretKey::try_from(key).map_err(|_| "Unexpected pairing key length".to_string())
+    }
+}
+
+pub async fn write_frame(stream: &mut TcpStream, bytes: &[u8]) -> Result<(), String> {
+    let len = u32::try_from(bytes.len()).map_err(|_| "Message too large".to_string())?;
+    let map_err = |e: std::io::Error| format!("Failed to send to device: {}", e);
+    stream.write_all(&len.to_be_bytes()).await.map_err(map_err)?;
+    stream.write_all(bytes).await.map_err(map_err)
+}
+
+/// Read a frame of at most `max_len` bytes
+pub async fn read_frame(stream: &mut TcpStream, max_len: usize) -> Result<Vec<u8>, String> {
+    let mut len = [0u8; 4];
+    stream
+        .read_exact(&mut len)
+        .await
+        .map_err(|e| format!("Failed to read from device: {}", e))?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > max_len {
+        return Err("Device sent an oversized message".to_string());
+    }
+
+    let mut bytes = vec![0u8; len];
+    stream
+        .read_exact(&mut bytes)
+        .await
+        .map_err(|e| format!("Failed to read from device: {}", e))?;
+    Ok(bytes)
+}
+
+pub async fn write_plain<T: Serialize>(stream: &mut TcpStream, message: &T) -> Result<(), String> {
+    let json = serde_json::to_vec(message).map_err(|e| format!("Failed to encode message: {}", e))?;
+    write_frame(stream, &json).await
+}
+
+pub async fn read_plain<T: DeserializeOwned>(stream: &mut TcpStream) -> Result<T, String> {
+    let bytes = read_frame(stream, MAX_HANDSHAKE_LEN).await?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Invalid message from device: {}", e))
+}
+
+pub async fn write_sealed<T: Serialize>(
+    stream: &mut TcpStream,
+    key: &SecretKey,
+    message: &T,
+) -> Result<(), String> {
+    let json = serde_json::to_vec(message).map_err(|e| format!("Failed to encode message: {}", e))?;
+    write_frame(stream, &crypto::seal_bytes(key, &json)?).await
+}
+
+pub async fn read_sealed<T: DeserializeOwned>(
+    stream: &mut TcpStream,
+    key: &SecretKey,
+    max_len: usize,
+) -> Result<T, String> {
+    let bytes = read_frame(stream, max_len).await?;
+    let json = crypto::open_bytes(key, &bytes)?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid message from device: {}", e))
+}
+
+/// Pairing codes are read aloud and typed on phones: ignore case, spaces
+/// and dashes
+pub fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
@@ -1,16 +1,104 @@
+mod analytics;
+mod api;
+mod app_lock;
+mod args;
+mod attachments;
+mod auth;
+mod auto_schedule;
+mod automation;
+mod backup;
+mod batch;
+mod budgets;
+mod clipboard_watch;
+mod clock;
 mod commands;
+mod connectivity;
+mod context_menu;
+mod crash;
+mod crypto;
+mod daily_plan;
+mod data_dir;
+mod db;
+mod dbus;
+mod demo;
+mod dependencies;
+mod dnd;
+mod error;
+mod export;
+mod file_drop;
+mod free_busy;
+mod http;
+mod importers;
+mod integrations;
+mod ipc;
+mod jump_list;
+mod keychain;
+mod lan_sync;
+mod meetings;
 mod menu;
+mod mini_mode;
+mod notifications;
+mod objectives;
+mod os_auth;
+mod overdue;
+mod pdf;
+mod perf;
+mod power;
+mod profiles;
+mod quick_complete;
+mod recent_tasks;
+mod rituals;
+mod rollover;
+mod scheduler;
+mod screenshot;
+mod server;
+mod settings;
+mod share;
+mod shortcuts;
+mod shutdown;
+mod snooze;
+mod sounds;
+mod speech;
+mod subtasks;
+mod sun;
+mod sync;
+mod telemetry;
+mod templates;
+#[cfg(test)]
+mod testing;
+mod theme;
+mod timer;
+mod timezone;
 mod tray;
+mod updates;
+mod views;
+mod weekly_review;
+mod window_effects;
+mod windows;
+mod work_calendar;
+mod zipfile;
+mod zoom;
 
-use tauri::{Emitter, Manager};
+use tauri::{DragDropEvent, Manager, RunEvent, WindowEvent};
 use tauri_plugin_autostart::MacosLauncher;
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_global_shortcut::ShortcutState;
 
 pub fn run() {
+    if let Err(e) = perf::init() {
+        tracing::warn!("{}", e);
+    }
+
     let mut builder = tauri::Builder::default();
 
-    // Register plugins
+    // Register plugins. Single-instance goes first so a second launch (or an
+    // automation link on Windows/Linux) is handed to the running app.
     builder = builder
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            args::apply_forwarded(app, args, &cwd);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_autostart::init(
@@ -25,7 +113,7 @@ pub fn run() {
             tauri_plugin_global_shortcut::Builder::new()
                 .with_handler(|app, shortcut, event| {
                     if event.state() == ShortcutState::Pressed {
-                        handle_global_shortcut(app, shortcut);
+                        shortcuts::handle(app, shortcut);
                     }
                 })
                 .build(),
@@ -33,67 +121,372 @@ pub fn run() {
 
     builder
         .setup(|app| {
+            // Time for the timer, rollover, reminders, outbox and settings; tests
+            // swap it
+            app.manage(clock::ClockState::default());
+
+            // Find the data directory (--data-dir, portable or moved) and
+            // profile, then upgrade stored settings before anything reads them
+            app.manage(data_dir::DataDir::resolve(app.handle())?);
+            crash::install(app.handle())?;
+            keychain::set_profile(&data_dir::profile(app.handle()));
+            settings::migrations::run(app.handle())?;
+
+            // The main window uses the profile's webview storage
+            windows::create_main_window(app.handle())?;
+
+            // Quit through the shutdown pipeline, including on logout
+            app.manage(shutdown::ShutdownState::default());
+            shutdown::watch_signals(app.handle());
+
             // Set up system tray
+            app.manage(menu::MenuRegistry::default());
+            app.manage(context_menu::ContextMenuState::default());
+            app.manage(recent_tasks::RecentTasksState::load(app.handle()));
+            app.manage(profiles::ProfilesState::default());
             tray::create_tray(app)?;
 
             // Set up menu
+            app.manage(windows::TaskWindowsState::default());
             menu::create_menu(app)?;
+            app.manage(mini_mode::MiniModeState::default());
 
-            // Register global shortcuts
-            register_global_shortcuts(app)?;
+            // Register global shortcuts (through the desktop portal on Wayland)
+            app.manage(shortcuts::ShortcutState::default());
+            app.manage(shortcuts::capture::CaptureState::default());
+            shortcuts::register_all(app.handle());
 
-            Ok(())
-        })
-        .invoke_handler(tauri::generate_handler![
-            commands::show_notification,
-            commands::get_auto_launch,
-            commands::set_auto_launch,
-            commands::get_settings,
-            commands::set_settings,
-            commands::is_desktop,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
+            // Back off background work on battery and metered connections
+            app.manage(power::PowerState::new());
+            power::start_monitor(app.handle());
 
-fn register_global_shortcuts(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
-    let shortcut_toggle = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyO);
-    let shortcut_new_task = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyT);
-    let shortcut_focus = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyF);
+            // Monitor backend reachability for offline mode
+            app.manage(http::HttpState::new(app.handle()));
+            // Traces and metrics for self-hosters, when switched on
+            telemetry::reconfigure(&settings::load(app.handle())?.network);
+            telemetry::start(app.handle());
+            app.manage(server::ServerState::new(app.handle()));
+            app.manage(connectivity::ConnectivityState::new());
+            connectivity::start_monitor(app.handle());
 
-    app.global_shortcut().register(shortcut_toggle)?;
-    app.global_shortcut().register(shortcut_new_task)?;
-    app.global_shortcut().register(shortcut_focus)?;
+            // Manual update checks from the Help and tray menus
+            app.manage(updates::UpdateState::default());
 
-    Ok(())
-}
+            // Native notifications, held during quiet hours and OS Do Not Disturb
+            app.manage(dnd::DndState::default());
+            dnd::start_monitor(app.handle());
+            notifications::start(app.handle());
+            sounds::register_all(app.handle());
+            let _ = crash::check_previous(app.handle());
+
+            // Open the local database and start delivering reminders
+            app.manage(db::init(app.handle())?);
+            app.manage(budgets::BudgetState::default());
+            scheduler::start(app.handle());
+            app.manage(meetings::MeetingState::default());
+            meetings::start(app.handle());
+            app.manage(overdue::OverdueState::default());
+            overdue::start(app.handle());
+            integrations::time_export::start(app.handle());
+            integrations::calendar_feeds::start(app.handle());
+            rituals::schedule(app.handle(), &settings::load(app.handle())?.planning)?;
+            app.manage(timer::TimerState::default());
+            timer::start_clock_monitor(app.handle());
+            app.manage(quick_complete::QuickCompleteState::default());
+
+            // Watch for OS timezone changes (travel)
+            app.manage(timezone::TimezoneState::new());
+            timezone::start_watcher(app.handle());
+
+            // Roll unfinished tasks over and announce the new day at midnight
+            app.manage(rollover::RolloverState::new(&**app.state::<clock::ClockState>()));
+            rollover::start(app.handle());
+            weekly_review::start(app.handle());
 
-fn handle_global_shortcut(app: &tauri::AppHandle, shortcut: &Shortcut) {
-    let toggle_shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyO);
-    let new_task_shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyT);
-    let focus_shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyF);
-
-    if shortcut == &toggle_shortcut {
-        // Toggle window visibility
-        if let Some(window) = app.get_webview_window("main") {
-            if window.is_visible().unwrap_or(false) {
-                let _ = window.hide();
-            } else {
-                let _ = window.show();
-                let _ = window.set_focus();
+            // Honor the theme setting natively and forward OS theme switches
+            app.manage(theme::AutoThemeState::default());
+            let settings = settings::load(app.handle())?;
+            theme::apply(app.handle(), &settings.appearance.theme);
+            zoom::apply(app.handle(), settings.appearance.zoom);
+            if let Err(e) = window_effects::apply(app.handle(), &settings.appearance) {
+                tracing::warn!("Window effect not applied: {}", e);
             }
-        }
-    } else if shortcut == &new_task_shortcut {
-        // Show window and emit event to create new task
-        if let Some(window) = app.get_webview_window("main") {
-            let _ = window.show();
-            let _ = window.set_focus();
-            let _ = window.emit("quick-add-task", ());
-        }
-    } else if shortcut == &focus_shortcut {
-        // Emit focus mode event
-        if let Some(window) = app.get_webview_window("main") {
-            let _ = window.emit("start-focus-mode", ());
-        }
-    }
+            theme::watch_system_theme(app.handle());
+            theme::start_auto_scheduler(app.handle());
+
+            // Background sync with the backend
+            app.manage(auth::AuthState::default());
+            app.manage(crypto::CryptoState::default());
+            sync::start(app.handle());
+            sync::realtime::start(app.handle());
+            app.manage(sync::transfers::TransferState::default());
+            sync::transfers::start(app.handle());
+
+            // Opt-in sync with paired devices on the local network
+            app.manage(lan_sync::LanSyncState::default());
+            lan_sync::start(app.handle());
+
+            // Start locked and lock after inactivity when app lock is enabled
+            app.manage(app_lock::AppLockState::new(settings.security.app_lock_enabled));
+            app_lock::start_idle_monitor(app.handle());
+
+            // Periodic snapshots of the database and settings
+            backup::start_scheduler(app.handle());
+
+            // Opt-in quick capture from the clipboard
+            app.manage(clipboard_watch::ClipboardWatchState::default());
+            clipboard_watch::start(app.handle());
+
+            // Text-to-speech engine, started on first use
+            app.manage(speech::SpeechState::default());
+
+            // opensunsama:// automation links (Shortcuts, AppleScript, launchers)
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            app.deep_link().register_all()?;
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                automation::handle_urls(&handle, event.urls());
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                automation::handle_urls(app.handle(), urls);
+            }
+            jump_list::install();
+            dbus::start(app.handle());
+
+            // Bring back task windows left open at the last quit
+            windows::restore_task_windows(app.handle());
+
+            // Show the window unless started with --minimized, and queue the
+            // other startup flags for the webview
+            app.manage(args::StartupState::default());
+            args::apply_startup(app.handle());
+
+            Ok(())
+        })
+        .on_window_event(|window, event| match event {
+            WindowEvent::DragDrop(DragDropEvent::Drop { paths, position }) => {
+                file_drop::handle_drop(window.app_handle(), paths, *position);
+            }
+            // Hide to the tray instead of closing when the setting is on
+            WindowEvent::CloseRequested { api, .. }
+                if window.label() == "main" && tray::hides_to_tray(window.app_handle()) =>
+            {
+                api.prevent_close();
+                tray::hide_to_tray(window);
+            }
+            // There's no minimize event; a resize that leaves it minimized is one
+            WindowEvent::Resized(_)
+                if window.label() == "main"
+                    && window.is_minimized().unwrap_or(false)
+                    && tray::hides_to_tray(window.app_handle()) =>
+            {
+                let _ = window.unminimize();
+                tray::hide_to_tray(window);
+            }
+            // Closing the main window quits, even with the timer widget open
+            WindowEvent::Destroyed if window.label() == "main" => window.app_handle().exit(0),
+            WindowEvent::Destroyed => windows::task_window_destroyed(window.app_handle(), window.label()),
+            _ => {}
+        })
+        .invoke_handler({
+            let handler = tauri::generate_handler![
+                commands::show_notification,
+                commands::list_notification_sounds,
+                commands::import_notification_sound,
+                commands::get_notification_status,
+                commands::snooze_notification,
+                commands::list_snoozed,
+                commands::cancel_snoozed,
+                commands::get_auto_launch,
+                commands::set_auto_launch,
+                commands::get_settings,
+                commands::set_settings,
+                commands::get_settings_section,
+                commands::update_settings_section,
+                commands::export_settings,
+                commands::preview_settings_import,
+                commands::import_settings,
+                commands::is_desktop,
+                commands::get_connectivity_status,
+                commands::check_connectivity,
+                commands::upsert_time_block,
+                commands::delete_time_block,
+                commands::list_time_blocks,
+                commands::reschedule_to_free_slot,
+                commands::reflow_day,
+                commands::get_free_busy,
+                commands::subscribe_calendar,
+                commands::update_calendar_subscription,
+                commands::unsubscribe_calendar,
+                commands::list_calendar_subscriptions,
+                commands::refresh_calendar_subscription,
+                commands::schedule_reminder,
+                commands::cancel_reminder,
+                commands::list_reminders,
+                commands::join_meeting,
+                commands::track_meeting,
+                commands::get_timezone,
+                commands::rebase_day_to_timezone,
+                commands::get_system_theme,
+                commands::set_auth_token,
+                commands::clear_auth_token,
+                commands::sync_now,
+                commands::get_server_url,
+                commands::set_server_url,
+                commands::get_proxy_config,
+                commands::get_encryption_status,
+                commands::setup_encryption,
+                commands::unlock_encryption,
+                commands::rotate_encryption_key,
+                commands::recover_encryption,
+                commands::regenerate_recovery_code,
+                commands::encrypt_payload,
+                commands::decrypt_payload,
+                commands::set_local_encryption,
+                commands::lock_app,
+                commands::unlock_app,
+                commands::is_app_locked,
+                commands::report_activity,
+                commands::set_app_lock_password,
+                commands::create_backup_now,
+                commands::list_backups,
+                commands::restore_backup,
+                commands::export_all_data,
+                commands::import_archive,
+                commands::preview_sunsama_import,
+                commands::import_sunsama,
+                commands::preview_toggl_import,
+                commands::import_toggl,
+                commands::set_integration_token,
+                commands::has_integration_token,
+                commands::export_time_entries,
+                commands::list_failed_time_exports,
+                commands::retry_failed_time_exports,
+                commands::attach_file,
+                commands::list_attachments,
+                commands::set_attachment_task,
+                commands::delete_attachment,
+                commands::open_attachment,
+                commands::reveal_attachment,
+                commands::gc_attachments,
+                commands::capture_screenshot,
+                commands::accept_clipboard_capture,
+                commands::dismiss_clipboard_capture,
+                commands::speak_daily_plan,
+                commands::stop_speaking,
+                commands::get_nav_views,
+                commands::check_for_updates,
+                commands::install_update_and_restart,
+                commands::set_menu_state,
+                commands::touch_recent_task,
+                commands::remove_recent_task,
+                commands::list_recent_tasks,
+                commands::show_context_menu,
+                commands::share_content,
+                commands::get_shortcut_support_status,
+                commands::try_register_shortcut,
+                commands::begin_shortcut_capture,
+                commands::cancel_shortcut_capture,
+                commands::set_current_task,
+                commands::get_timer_status,
+                commands::get_timer_state,
+                commands::start_timer,
+                commands::stop_timer,
+                commands::toggle_timer,
+                commands::complete_current_task,
+                commands::undo_complete_task,
+                commands::set_always_on_top,
+                commands::enter_mini_mode,
+                commands::exit_mini_mode,
+                commands::open_timer_widget,
+                commands::close_timer_widget,
+                commands::set_focus_session,
+                commands::get_window_effect_support,
+                commands::open_task_window,
+                commands::list_task_windows,
+                commands::take_startup_actions,
+                commands::get_data_dir,
+                commands::migrate_data_dir,
+                commands::list_profiles,
+                commands::create_profile,
+                commands::switch_profile,
+                commands::get_task_text,
+                commands::edit_task_text,
+                commands::get_task_text_state_vector,
+                commands::get_task_text_update,
+                commands::apply_task_text_update,
+                commands::get_lan_sync_status,
+                commands::list_lan_devices,
+                commands::start_lan_pairing,
+                commands::cancel_lan_pairing,
+                commands::pair_lan_device,
+                commands::unpair_lan_device,
+                commands::sync_lan_device,
+                commands::list_transfers,
+                commands::upload_attachment,
+                commands::download_attachment,
+                commands::pause_transfer,
+                commands::resume_transfer,
+                commands::cancel_transfer,
+                commands::get_power_status,
+                commands::generate_weekly_review,
+                commands::list_objectives,
+                commands::save_objective,
+                commands::delete_objective,
+                commands::link_task_to_objective,
+                commands::unlink_task_from_objective,
+                commands::get_objective_progress,
+                commands::set_channel_budget,
+                commands::list_channel_budgets,
+                commands::get_budget_status,
+                commands::list_templates,
+                commands::save_template,
+                commands::delete_template,
+                commands::instantiate_template,
+                commands::list_subtasks,
+                commands::add_subtask,
+                commands::update_subtask,
+                commands::delete_subtask,
+                commands::reorder_subtasks,
+                commands::get_task_dependencies,
+                commands::add_task_dependency,
+                commands::remove_task_dependency,
+                commands::get_blocked_tasks,
+                commands::batch,
+                commands::query_tasks,
+                commands::get_ipc_features,
+                commands::query_tasks_packed,
+                commands::get_period_summary_packed,
+                commands::preview_sunsama_import_packed,
+                commands::get_perf_metrics,
+                commands::reset_perf_metrics,
+                commands::list_crash_reports,
+                commands::send_crash_report,
+                commands::open_crash_issue,
+                commands::dismiss_crash_report,
+                commands::seed_demo_data,
+                commands::clear_demo_data,
+            ];
+            // Nothing but the lock screen answers while the app is locked
+            move |invoke| app_lock::gate(invoke, &handler)
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // Finish pending work before any exit goes through
+            RunEvent::ExitRequested { api, code, .. } => {
+                if shutdown::on_exit_requested(app, code) {
+                    api.prevent_exit();
+                }
+            }
+            // Clicking the dock icon brings back a window hidden to the tray
+            #[cfg(target_os = "macos")]
+            RunEvent::Reopen { .. } => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            _ => {}
+        });
 }
@@ -0,0 +1,167 @@
+//! Meeting-start notifications. Calendar events in the local schedule are
+//! announced `meeting_lead_minutes` before they start, checked often enough
+//! to stay on time and to catch up right after the machine wakes, and sent
+//! as `meeting-starting`. `join_meeting` opens the video call link found in
+//! the event and `track_meeting` times the task linked to it.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clock;
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::Database;
+use crate::notifications::{self, Category, Notice};
+use crate::settings;
+use crate::timer::{self, TaskRef, TimerStatus};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(20);
+/// A meeting that started this long ago is still worth a late notice
+const LATE_GRACE_MS: i64 = 2 * 60 * 1000;
+
+/// Video call links, most specific first
+const MEETING_URL_PATTERNS: &[&str] = &[
+    r"https://[\w.-]*zoom\.us/(?:j|my|w)/[^\s<>\x22]+",
+    r"https://meet\.google\.com/[a-z]{3}-[a-z]{4}-[a-z]{3}[^\s<>\x22]*",
+    r"https://teams\.(?:microsoft|live)\.com/(?:l/meetup-join|meet)/[^\s<>\x22]+",
+    r"https://[\w.-]+\.webex\.com/[^\s<>\x22]+",
+    r"https://(?:meet\.jit\.si|whereby\.com|around\.co|app\.gather\.town)/[^\s<>\x22]+",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingStarting {
+    pub block_id: String,
+    pub title: String,
+    pub start_at: i64,
+    pub join_url: Option<String>,
+    /// Task that "Track time" would time
+    pub task_id: Option<String>,
+}
+
+/// Meetings already announced, by block id and start time so a moved
+/// meeting is announced again
+#[derive(Default)]
+pub struct MeetingState {
+    notified: Mutex<HashSet<(String, i64)>>,
+}
+
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let _ = check(&app);
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+fn check(app: &AppHandle) -> Result<(), String> {
+    let config = settings::load(app)?.notifications;
+    if !config.meeting_alerts {
+        return Ok(());
+    }
+
+    let now = clock::now_millis();
+    let lead = i64::from(config.meeting_lead_minutes) * 60 * 1000;
+    let upcoming = app
+        .state::<Database>()
+        .with_conn(|conn| time_blocks::list_between(conn, now - LATE_GRACE_MS, now + lead + 1))?;
+
+    let state = app.state::<MeetingState>();
+    for block in upcoming {
+        if !block.is_event || block.start_at > now + lead || block.start_at < now - LATE_GRACE_MS {
+            continue;
+        }
+        let first_time = state
+            .notified
+            .lock()
+            .map(|mut notified| notified.insert((block.id.clone(), block.start_at)))
+            .unwrap_or(false);
+        if first_time {
+            announce(app, &block, now);
+        }
+    }
+
+    // Forget meetings that have started, once they can't come round again
+    if let Ok(mut notified) = state.notified.lock() {
+        notified.retain(|(_, start_at)| *start_at >= now - LATE_GRACE_MS);
+    }
+    Ok(())
+}
+
+fn announce(app: &AppHandle, block: &TimeBlock, now: i64) {
+    let minutes = (block.start_at - now + 59_999) / 60_000;
+    let when = match minutes {
+        i64::MIN..=0 => "Starting now".to_string(),
+        1 => "Starts in 1 minute".to_string(),
+        n => format!("Starts in {} minutes", n),
+    };
+    let body = match &block.location {
+        Some(location) => format!("{} · {}", when, location),
+        None => when,
+    };
+
+    let _ = notifications::notify(app, Notice::new(Category::Meeting, &block.title).body(body));
+    let _ = app.emit(
+        "meeting-starting",
+        MeetingStarting {
+            block_id: block.id.clone(),
+            title: block.title.clone(),
+            start_at: block.start_at,
+            join_url: join_url(block),
+            task_id: block.task_id.clone(),
+        },
+    );
+}
+
+/// Open the video call link of the meeting `block_id`
+pub fn join(app: &AppHandle, block_id: &str) -> Result<(), String> {
+    let block = find(app, block_id)?;
+    let url = join_url(&block).ok_or_else(|| "This meeting has no video call link".to_string())?;
+    open::that_detached(&url).map_err(|e| format!("Failed to open meeting link: {}", e))
+}
+
+/// Start the timer on the task linked to the meeting `block_id`
+pub fn track(app: &AppHandle, block_id: &str) -> Result<TimerStatus, String> {
+    let block = find(app, block_id)?;
+    let task_id = block
+        .task_id
+        .ok_or_else(|| "No task is linked to this meeting".to_string())?;
+    timer::start(
+        app,
+        Some(TaskRef {
+            id: task_id,
+            title: block.title,
+        }),
+    )
+}
+
+/// The video call link in a meeting's location, description or title
+pub fn join_url(block: &TimeBlock) -> Option<String> {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        MEETING_URL_PATTERNS
+            .iter()
+            .filter_map(|pattern| Regex::new(pattern).ok())
+            .collect()
+    });
+
+    let fields = [block.location.as_deref(), block.description.as_deref(), Some(block.title.as_str())];
+    patterns.iter().find_map(|pattern| {
+        fields
+            .iter()
+            .flatten()
+            .find_map(|text| pattern.find(text))
+            .map(|found| found.as_str().trim_end_matches(['.', ',', ')', '>']).to_string())
+    })
+}
+
+fn find(app: &AppHandle, block_id: &str) -> Result<TimeBlock, String> {
+    app.state::<Database>()
+        .with_conn(|conn| time_blocks::get(conn, block_id))?
+        .ok_or_else(|| "Meeting not found".to_string())
+}
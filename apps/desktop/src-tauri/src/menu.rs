@@ -1,8 +1,110 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
 use tauri::{
-    menu::{Menu, MenuItem, MenuItemBuilder, PredefinedMenuItem, Submenu},
-    Emitter, Manager,
+    menu::{IsMenuItem, Menu, MenuItem, MenuItemBuilder, PredefinedMenuItem, Submenu},
+    AppHandle, Emitter, Manager, Wry,
 };
 
+use crate::zoom::{self, ZoomChange};
+use crate::{context_menu, mini_mode, recent_tasks, updates, views, windows};
+
+/// Menu and tray items that can change at runtime, by id. The same id can
+/// appear in both menus; updates apply to every copy.
+#[derive(Default)]
+pub struct MenuRegistry {
+    items: Mutex<HashMap<String, Vec<MenuItem<Wry>>>>,
+}
+
+impl MenuRegistry {
+    pub fn register(&self, items: &[&MenuItem<Wry>]) {
+        if let Ok(mut registry) = self.items.lock() {
+            for item in items {
+                registry
+                    .entry(item.id().as_ref().to_string())
+                    .or_default()
+                    .push((*item).clone());
+            }
+        }
+    }
+
+    fn get(&self, id: &str) -> Vec<MenuItem<Wry>> {
+        self.items
+            .lock()
+            .ok()
+            .and_then(|registry| registry.get(id).cloned())
+            .unwrap_or_default()
+    }
+}
+
+/// A change to one menu item; omitted fields stay as they are
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MenuItemState {
+    pub id: String,
+    pub enabled: Option<bool>,
+    pub label: Option<String>,
+}
+
+/// Apply runtime state to menu and tray items. Unknown ids are an error so
+/// typos surface, but the known ones are still updated.
+pub fn set_state(app: &AppHandle, changes: &[MenuItemState]) -> Result<(), String> {
+    let registry = app.state::<MenuRegistry>();
+    let mut unknown = Vec::new();
+
+    for change in changes {
+        let items = registry.get(&change.id);
+        if items.is_empty() {
+            unknown.push(change.id.as_str());
+            continue;
+        }
+        for item in items {
+            if let Some(enabled) = change.enabled {
+                item.set_enabled(enabled)
+                    .map_err(|e| format!("Failed to update menu item {}: {}", change.id, e))?;
+            }
+            if let Some(label) = &change.label {
+                item.set_text(label)
+                    .map_err(|e| format!("Failed to update menu item {}: {}", change.id, e))?;
+            }
+        }
+    }
+
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Unknown menu items: {}", unknown.join(", ")))
+    }
+}
+
+/// Accelerators of the fixed app menu items, including the predefined ones,
+/// so shortcut settings can warn about clashes
+const MENU_ACCELERATORS: &[(&str, &str)] = &[
+    ("Settings", "CmdOrCtrl+,"),
+    ("New Task", "CmdOrCtrl+N"),
+    ("Reload", "CmdOrCtrl+R"),
+    ("Zoom In", "CmdOrCtrl+="),
+    ("Zoom Out", "CmdOrCtrl+-"),
+    ("Actual Size", "CmdOrCtrl+0"),
+    ("Mini Mode", "CmdOrCtrl+Shift+M"),
+    ("Quit", "CmdOrCtrl+Q"),
+    ("Close Window", "CmdOrCtrl+W"),
+    ("Undo", "CmdOrCtrl+Z"),
+    ("Redo", "CmdOrCtrl+Shift+Z"),
+    ("Cut", "CmdOrCtrl+X"),
+    ("Copy", "CmdOrCtrl+C"),
+    ("Paste", "CmdOrCtrl+V"),
+    ("Select All", "CmdOrCtrl+A"),
+];
+
+/// Every accelerator the app menu uses, with the item's label
+pub fn accelerators() -> Vec<(&'static str, &'static str)> {
+    let views = views::VIEWS
+        .iter()
+        .filter_map(|view| view.accelerator.map(|accelerator| (view.label, accelerator)));
+    MENU_ACCELERATORS.iter().copied().chain(views).collect()
+}
+
 pub fn create_menu(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     // App menu (macOS only)
     let about = PredefinedMenuItem::about(app, Some("About Open Sunsama"), None)?;
@@ -38,10 +140,17 @@ pub fn create_menu(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     let new_task = MenuItemBuilder::with_id("new_task", "New Task")
         .accelerator("CmdOrCtrl+N")
         .build(app)?;
+    let focus_session = MenuItemBuilder::with_id("focus_session", "Start Focus Session").build(app)?;
+    let open_recent = Submenu::with_id(app, "open_recent", "Open Recent", true)?;
     let file_sep = PredefinedMenuItem::separator(app)?;
     let close_window = PredefinedMenuItem::close_window(app, Some("Close Window"))?;
 
-    let file_menu = Submenu::with_items(app, "File", true, &[&new_task, &file_sep, &close_window])?;
+    let file_menu = Submenu::with_items(
+        app,
+        "File",
+        true,
+        &[&new_task, &focus_session, &open_recent, &file_sep, &close_window],
+    )?;
 
     // Edit menu
     let undo = PredefinedMenuItem::undo(app, Some("Undo"))?;
@@ -62,32 +171,51 @@ pub fn create_menu(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     )?;
 
     // View menu
-    let today_view = MenuItemBuilder::with_id("today_view", "Today")
-        .accelerator("CmdOrCtrl+1")
-        .build(app)?;
-    let calendar_view = MenuItemBuilder::with_id("calendar_view", "Calendar")
-        .accelerator("CmdOrCtrl+2")
-        .build(app)?;
+    let view_items = views::VIEWS
+        .iter()
+        .map(|view| {
+            let mut item = MenuItemBuilder::with_id(views::menu_id(view), view.label);
+            if let Some(accelerator) = view.accelerator {
+                item = item.accelerator(accelerator);
+            }
+            item.build(app)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     let view_sep = PredefinedMenuItem::separator(app)?;
     let reload = MenuItemBuilder::with_id("reload", "Reload")
         .accelerator("CmdOrCtrl+R")
         .build(app)?;
+    let zoom_sep = PredefinedMenuItem::separator(app)?;
+    let zoom_in = MenuItemBuilder::with_id("zoom_in", "Zoom In")
+        .accelerator("CmdOrCtrl+=")
+        .build(app)?;
+    let zoom_out = MenuItemBuilder::with_id("zoom_out", "Zoom Out")
+        .accelerator("CmdOrCtrl+-")
+        .build(app)?;
+    let zoom_reset = MenuItemBuilder::with_id("zoom_reset", "Actual Size")
+        .accelerator("CmdOrCtrl+0")
+        .build(app)?;
     let view_sep2 = PredefinedMenuItem::separator(app)?;
+    let mini_mode = MenuItemBuilder::with_id("mini_mode", "Mini Mode")
+        .accelerator("CmdOrCtrl+Shift+M")
+        .build(app)?;
     let fullscreen = PredefinedMenuItem::fullscreen(app, Some("Enter Full Screen"))?;
 
-    let view_menu = Submenu::with_items(
-        app,
-        "View",
-        true,
-        &[
-            &today_view,
-            &calendar_view,
-            &view_sep,
-            &reload,
-            &view_sep2,
-            &fullscreen,
-        ],
-    )?;
+    let mut view_menu_items: Vec<&dyn IsMenuItem<_>> =
+        view_items.iter().map(|item| item as &dyn IsMenuItem<_>).collect();
+    view_menu_items.extend([
+        &view_sep as &dyn IsMenuItem<_>,
+        &reload,
+        &zoom_sep,
+        &zoom_in,
+        &zoom_out,
+        &zoom_reset,
+        &view_sep2,
+        &mini_mode,
+        &fullscreen,
+    ]);
+
+    let view_menu = Submenu::with_items(app, "View", true, &view_menu_items)?;
 
     // Window menu
     let minimize = PredefinedMenuItem::minimize(app, Some("Minimize"))?;
@@ -103,12 +231,24 @@ pub fn create_menu(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     )?;
 
     // Help menu
+    let check_updates = MenuItemBuilder::with_id("check_updates", "Check for Updates...").build(app)?;
+    let help_sep_updates = PredefinedMenuItem::separator(app)?;
     let documentation = MenuItemBuilder::with_id("documentation", "Documentation").build(app)?;
     let help_sep = PredefinedMenuItem::separator(app)?;
     let report_issue = MenuItemBuilder::with_id("report_issue", "Report Issue").build(app)?;
 
-    let help_menu =
-        Submenu::with_items(app, "Help", true, &[&documentation, &help_sep, &report_issue])?;
+    let help_menu = Submenu::with_items(
+        app,
+        "Help",
+        true,
+        &[
+            &check_updates,
+            &help_sep_updates,
+            &documentation,
+            &help_sep,
+            &report_issue,
+        ],
+    )?;
 
     // Build the menu
     let menu = Menu::with_items(
@@ -125,6 +265,24 @@ pub fn create_menu(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
 
     app.set_menu(menu)?;
 
+    let registry = app.state::<MenuRegistry>();
+    registry.register(&[
+        &settings,
+        &new_task,
+        &focus_session,
+        &reload,
+        &zoom_in,
+        &zoom_out,
+        &zoom_reset,
+        &mini_mode,
+        &check_updates,
+        &documentation,
+        &report_issue,
+    ]);
+    registry.register(&view_items.iter().collect::<Vec<_>>());
+    recent_tasks::register_submenu(app.handle(), open_recent)?;
+    windows::register_window_menu(app.handle(), window_menu)?;
+
     // Handle menu events
     app.on_menu_event(|app, event| {
         let id = event.id.as_ref();
@@ -139,21 +297,45 @@ pub fn create_menu(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                     let _ = window.emit("quick-add-task", ());
                 }
             }
-            "today_view" => {
+            "focus_session" => {
+                // The webview knows whether a session is running and
+                // relabels the item through `set_menu_state`. Also the
+                // tray's item, so the window comes forward.
                 if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.emit("navigate", "/app");
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                    let _ = window.emit("toggle-focus-mode", ());
                 }
             }
-            "calendar_view" => {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.emit("navigate", "/app/calendar");
+            id if id.starts_with(views::MENU_ID_PREFIX) => {
+                if let Some(view) = views::from_menu_id(id) {
+                    views::navigate(app, view);
                 }
             }
+            recent_tasks::CLEAR_MENU_ID => {
+                let _ = recent_tasks::clear(app);
+            }
+            id if id.starts_with(recent_tasks::MENU_ID_PREFIX) => recent_tasks::open(app, id),
+            id if id.starts_with(context_menu::MENU_ID_PREFIX) => context_menu::select(app, id),
+            id if id.starts_with(windows::TASK_MENU_ID_PREFIX) => windows::focus_from_menu(app, id),
             "reload" => {
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.eval("window.location.reload()");
                 }
             }
+            "zoom_in" => {
+                let _ = zoom::change(app, ZoomChange::In);
+            }
+            "zoom_out" => {
+                let _ = zoom::change(app, ZoomChange::Out);
+            }
+            "zoom_reset" => {
+                let _ = zoom::change(app, ZoomChange::Reset);
+            }
+            "mini_mode" => {
+                let _ = mini_mode::toggle(app);
+            }
+            "check_updates" => updates::check_from_menu(app),
             "documentation" => {
                 let _ = tauri::async_runtime::spawn(async {
                     let _ = open::that("https://github.com/your-org/open-sunsama");
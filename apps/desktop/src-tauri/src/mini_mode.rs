@@ -0,0 +1,146 @@
+//! Mini mode: the main window shrunk to a strip with the timer and current
+//! task, pinned above other windows in the top-right corner of its screen.
+//! The normal geometry is kept so leaving mini mode puts the window back.
+//! Emits `mini-mode-changed` with whether mini mode is on, so the webview
+//! can switch layouts.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{
+    AppHandle, Emitter, LogicalPosition, LogicalSize, Manager, PhysicalPosition, PhysicalSize,
+    WebviewWindow,
+};
+
+const MINI_WIDTH: f64 = 360.0;
+const MINI_HEIGHT: f64 = 72.0;
+/// Distance from the screen edges
+const MINI_MARGIN: f64 = 16.0;
+/// Matches `minWidth`/`minHeight` in tauri.conf.json
+const NORMAL_MIN_SIZE: LogicalSize<f64> = LogicalSize {
+    width: 800.0,
+    height: 600.0,
+};
+
+struct Geometry {
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    maximized: bool,
+}
+
+#[derive(Default)]
+pub struct MiniModeState {
+    /// Normal geometry while in mini mode
+    saved: Mutex<Option<Geometry>>,
+    /// The user's always-on-top choice for the normal window
+    always_on_top: AtomicBool,
+}
+
+impl MiniModeState {
+    pub fn is_active(&self) -> bool {
+        self.saved.lock().is_ok_and(|saved| saved.is_some())
+    }
+}
+
+/// Pin the window above others. In mini mode it's always pinned, so the
+/// choice takes effect on leaving.
+pub fn set_always_on_top(app: &AppHandle, on: bool) -> Result<(), String> {
+    let state = app.state::<MiniModeState>();
+    state.always_on_top.store(on, Ordering::SeqCst);
+    if state.is_active() {
+        return Ok(());
+    }
+    main_window(app)?
+        .set_always_on_top(on)
+        .map_err(|e| format!("Failed to pin window: {}", e))
+}
+
+pub fn enter(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<MiniModeState>();
+    let window = main_window(app)?;
+    let mut saved = state
+        .saved
+        .lock()
+        .map_err(|_| "Mini mode state poisoned".to_string())?;
+    if saved.is_some() {
+        return Ok(());
+    }
+
+    let geometry = Geometry {
+        position: window.outer_position().map_err(window_error)?,
+        size: window.inner_size().map_err(window_error)?,
+        maximized: window.is_maximized().unwrap_or(false),
+    };
+    let monitor = window
+        .current_monitor()
+        .map_err(window_error)?
+        .ok_or_else(|| "The window isn't on a screen".to_string())?;
+    let scale = monitor.scale_factor();
+    let work_area = monitor.work_area();
+    let work_position = work_area.position.to_logical::<f64>(scale);
+    let work_size = work_area.size.to_logical::<f64>(scale);
+
+    let _ = window.show();
+    if geometry.maximized {
+        window.unmaximize().map_err(window_error)?;
+    }
+    window.set_decorations(false).map_err(window_error)?;
+    window.set_min_size(None::<LogicalSize<f64>>).map_err(window_error)?;
+    window.set_resizable(false).map_err(window_error)?;
+    window
+        .set_size(LogicalSize::new(MINI_WIDTH, MINI_HEIGHT))
+        .map_err(window_error)?;
+    window
+        .set_position(LogicalPosition::new(
+            work_position.x + work_size.width - MINI_WIDTH - MINI_MARGIN,
+            work_position.y + MINI_MARGIN,
+        ))
+        .map_err(window_error)?;
+    window.set_always_on_top(true).map_err(window_error)?;
+
+    *saved = Some(geometry);
+    let _ = app.emit("mini-mode-changed", true);
+    Ok(())
+}
+
+pub fn exit(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<MiniModeState>();
+    let window = main_window(app)?;
+    let Some(geometry) = state.saved.lock().ok().and_then(|mut saved| saved.take()) else {
+        return Ok(());
+    };
+
+    window
+        .set_always_on_top(state.always_on_top.load(Ordering::SeqCst))
+        .map_err(window_error)?;
+    window.set_decorations(true).map_err(window_error)?;
+    window.set_resizable(true).map_err(window_error)?;
+    window.set_min_size(Some(NORMAL_MIN_SIZE)).map_err(window_error)?;
+    window.set_size(geometry.size).map_err(window_error)?;
+    window.set_position(geometry.position).map_err(window_error)?;
+    if geometry.maximized {
+        window.maximize().map_err(window_error)?;
+    }
+    let _ = window.set_focus();
+
+    let _ = app.emit("mini-mode-changed", false);
+    Ok(())
+}
+
+pub fn toggle(app: &AppHandle) -> Result<bool, String> {
+    if app.state::<MiniModeState>().is_active() {
+        exit(app)?;
+        Ok(false)
+    } else {
+        enter(app)?;
+        Ok(true)
+    }
+}
+
+fn main_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    app.get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())
+}
+
+fn window_error(e: tauri::Error) -> String {
+    format!("Failed to resize window: {}", e)
+}
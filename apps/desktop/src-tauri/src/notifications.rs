@@ -0,0 +1,273 @@
+//! Every native notification goes through `notify`, which applies the
+//! notification settings in one place: the master switch, quiet hours and
+//! per-category sounds.
+//!
+//! Notifications that arrive during quiet hours are held in the database
+//! and delivered when they end, or dropped, per `quiet_hours_mode`.
+//! Non-urgent ones are also held while the OS Do Not Disturb / Focus mode
+//! is on, so they aren't lost to the system. A held notification leaves the
+//! queue only once it has been shown, so quitting loses nothing.
+//!
+//! The notification plugin only supports action buttons on mobile. A
+//! desktop notice that offers an action also emits an event, and the
+//! webview shows the action.
+
+use chrono::{NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::clock;
+use crate::db::held_notices::{self, HeldNotice};
+use crate::db::Database;
+use crate::dnd::DndState;
+use crate::settings::{self, NotificationSettings, QuietHoursMode};
+use crate::{sounds, timezone};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// More held notifications than this are delivered as one summary
+const MAX_RELEASED: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Scheduled reminders, including those from the webview
+    #[default]
+    Reminder,
+    /// A timer or focus session running out
+    Timer,
+    /// A meeting about to start
+    Meeting,
+    /// Planning and shutdown rituals
+    Ritual,
+    /// Weekly review reports
+    Review,
+    /// Channels using up their weekly time budget
+    Budget,
+    /// Clipboard capture suggestions
+    Capture,
+    /// Confirmation of something the user just did, like toggling the
+    /// timer from a shortcut. Not held for quiet hours.
+    Feedback,
+    /// App messages the user has to see (update checks, where the window
+    /// went). Shown even with notifications switched off.
+    System,
+}
+
+impl Category {
+    /// Key in the per-category sound settings
+    pub fn key(self) -> &'static str {
+        match self {
+            Category::Reminder => "reminder",
+            Category::Timer => "timer",
+            Category::Meeting => "meeting",
+            Category::Ritual => "ritual",
+            Category::Review => "review",
+            Category::Budget => "budget",
+            Category::Capture => "capture",
+            Category::Feedback => "feedback",
+            Category::System => "system",
+        }
+    }
+
+    /// Shown even while the OS is in Do Not Disturb
+    pub fn is_urgent(self) -> bool {
+        matches!(
+            self,
+            Category::Timer | Category::Meeting | Category::Feedback | Category::System
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notice {
+    pub category: Category,
+    pub title: String,
+    pub body: Option<String>,
+    /// Overrides the category's sound setting
+    pub sound: Option<String>,
+    /// The task it's about, handed to the webview when it's clicked
+    pub task_id: Option<String>,
+    /// Whatever the webview needs to act on it, handed back the same way
+    pub data: Option<Value>,
+}
+
+impl Notice {
+    pub fn new(category: Category, title: impl Into<String>) -> Self {
+        Self {
+            category,
+            title: title.into(),
+            body: None,
+            sound: None,
+            task_id: None,
+            data: None,
+        }
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+}
+
+/// How many notifications are waiting for quiet hours or Do Not Disturb
+/// to end
+pub fn held_count(app: &AppHandle) -> Result<usize, String> {
+    app.state::<Database>().with_conn(|conn| held_notices::count(conn))
+}
+
+fn hold(app: &AppHandle, notice: Notice) -> Result<(), String> {
+    let held = HeldNotice {
+        id: 0,
+        category: notice.category.key().to_string(),
+        title: notice.title,
+        body: notice.body,
+        sound: notice.sound,
+        task_id: notice.task_id,
+        data: notice.data,
+        held_at: clock::now_millis(),
+    };
+    app.state::<Database>()
+        .with_conn(|conn| held_notices::insert(conn, &held))
+        .map(|_| ())
+}
+
+/// Show `notice` now, hold it until quiet hours end, or drop it, per the
+/// notification settings
+pub fn notify(app: &AppHandle, notice: Notice) -> Result<(), String> {
+    let config = settings::load(app)?.notifications;
+    if notice.category != Category::System && !config.enabled {
+        return Ok(());
+    }
+
+    let held_back = !matches!(notice.category, Category::Feedback | Category::System);
+    if held_back && is_quiet(&config) {
+        if config.quiet_hours_mode == QuietHoursMode::Queue {
+            hold(app, notice)?;
+        }
+        return Ok(());
+    }
+    if !notice.category.is_urgent() && system_dnd(app, &config) {
+        return hold(app, notice);
+    }
+
+    show(app, &config, &notice)
+}
+
+/// Deliver held notifications once quiet hours and Do Not Disturb are over
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let _ = release_held(&app);
+        }
+    });
+}
+
+fn release_held(app: &AppHandle) -> Result<(), String> {
+    let config = settings::load(app)?.notifications;
+    if is_quiet(&config) || system_dnd(app, &config) {
+        return Ok(());
+    }
+    let db = app.state::<Database>();
+    let held = db.with_conn(|conn| held_notices::all(conn))?;
+    if held.is_empty() {
+        return Ok(());
+    }
+    // Switched off while they waited: drop them
+    if !config.enabled {
+        return db.with_conn(|conn| {
+            held.iter()
+                .try_for_each(|notice| held_notices::delete(conn, notice.id))
+        });
+    }
+
+    if held.len() <= MAX_RELEASED {
+        for notice in &held {
+            let mut shown = Notice::new(category(&notice.category), &notice.title);
+            shown.body = notice.body.clone();
+            shown.sound = notice.sound.clone();
+            shown.task_id = notice.task_id.clone();
+            shown.data = notice.data.clone();
+            show(app, &config, &shown)?;
+            db.with_conn(|conn| held_notices::delete(conn, notice.id))?;
+        }
+        return Ok(());
+    }
+
+    // The summary keeps what each one was about, so clicking it can still
+    // lead to them
+    let titles: Vec<&str> = held.iter().map(|notice| notice.title.as_str()).collect();
+    let mut summary = Notice::new(
+        Category::Reminder,
+        format!("{} notifications during quiet hours", held.len()),
+    )
+    .body(titles.join(", "));
+    summary.data = Some(json!({
+        "held": held
+            .iter()
+            .map(|notice| json!({
+                "title": notice.title,
+                "taskId": notice.task_id,
+                "data": notice.data,
+            }))
+            .collect::<Vec<_>>(),
+    }));
+    show(app, &config, &summary)?;
+    db.with_conn(|conn| {
+        held.iter()
+            .try_for_each(|notice| held_notices::delete(conn, notice.id))
+    })
+}
+
+/// The category with `key`, or a reminder for one this version doesn't know
+pub fn category(key: &str) -> Category {
+    serde_json::from_value(Value::from(key)).unwrap_or_default()
+}
+
+/// Whether quiet hours are on and the local time falls inside them
+pub fn is_quiet(config: &NotificationSettings) -> bool {
+    if !config.quiet_hours {
+        return false;
+    }
+    let (Ok(start), Ok(end)) = (
+        NaiveTime::parse_from_str(&config.quiet_start, "%H:%M"),
+        NaiveTime::parse_from_str(&config.quiet_end, "%H:%M"),
+    ) else {
+        return false;
+    };
+
+    let now = Utc::now().with_timezone(&timezone::local()).time();
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Whether the OS is in Do Not Disturb and notifications should wait for it
+pub fn system_dnd(app: &AppHandle, config: &NotificationSettings) -> bool {
+    config.defer_during_system_dnd && app.state::<DndState>().is_active()
+}
+
+fn show(app: &AppHandle, config: &NotificationSettings, notice: &Notice) -> Result<(), String> {
+    let mut notification = app.notification().builder().title(&notice.title);
+    if let Some(body) = &notice.body {
+        notification = notification.body(body);
+    }
+    if let Some(sound) = sounds::resolve(config, notice.category, notice.sound.as_deref()) {
+        notification = notification.sound(sound);
+    }
+    if let Some(task_id) = &notice.task_id {
+        notification = notification.extra("taskId", task_id);
+    }
+    if let Some(data) = &notice.data {
+        notification = notification.extra("data", data);
+    }
+    notification
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))
+}
@@ -0,0 +1,188 @@
+//! Weekly objectives: goals set for a week, with the tasks working towards
+//! them. Objectives and their task links live in the local database; the
+//! weekly review reports the time tracked on each objective's tasks and how
+//! many of them are done.
+
+use chrono::{Days, NaiveDate};
+use opensunsama_core::task::is_completed;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::Api;
+use crate::connectivity::ConnectivityState;
+use crate::db::objectives::{self, Objective};
+use crate::db::time_entries;
+use crate::db::Database;
+use crate::{clock, timezone, weekly_review};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectiveProgress {
+    pub objective: Objective,
+    /// Minutes tracked that week on its tasks
+    pub tracked_minutes: i64,
+    /// Linked tasks that are done; `None` when the server couldn't be asked
+    pub completed_tasks: Option<usize>,
+}
+
+/// The objectives of the week containing `date`
+pub fn list(app: &AppHandle, date: NaiveDate) -> Result<Vec<Objective>, String> {
+    let week_start = weekly_review::week_start(date).to_string();
+    app.state::<Database>()
+        .with_conn(|conn| objectives::list_week(conn, &week_start))
+}
+
+/// Create or update an objective, moving its week to start on Monday.
+/// Emits `objectives-changed`.
+pub fn save(app: &AppHandle, mut objective: Objective) -> Result<Objective, String> {
+    let title = objective.title.trim();
+    if title.is_empty() {
+        return Err("Objectives need a title".to_string());
+    }
+    objective.title = title.to_string();
+    let week = objective
+        .week_start
+        .parse::<NaiveDate>()
+        .map_err(|e| format!("Invalid date '{}': {}", objective.week_start, e))?;
+    objective.week_start = weekly_review::week_start(week).to_string();
+    objective.task_ids.sort();
+    objective.task_ids.dedup();
+
+    let now = clock::now_millis();
+    let db = app.state::<Database>();
+    let existing = if objective.id.is_empty() {
+        objective.id = uuid::Uuid::new_v4().to_string();
+        None
+    } else {
+        db.with_conn(|conn| objectives::get(conn, &objective.id))?
+    };
+    objective.created_at = existing.map_or(now, |existing| existing.created_at);
+    objective.updated_at = now;
+
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        objectives::upsert(&tx, &objective)?;
+        tx.commit()
+    })?;
+    let _ = app.emit("objectives-changed", ());
+    Ok(objective)
+}
+
+pub fn delete(app: &AppHandle, id: &str) -> Result<(), String> {
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        objectives::delete(&tx, id)?;
+        tx.commit()
+    })?;
+    let _ = app.emit("objectives-changed", ());
+    Ok(())
+}
+
+/// Link a task to an objective, or with `linked` false, unlink it
+pub fn set_task_link(
+    app: &AppHandle,
+    objective_id: &str,
+    task_id: &str,
+    linked: bool,
+) -> Result<(), String> {
+    let db = app.state::<Database>();
+    if db
+        .with_conn(|conn| objectives::get(conn, objective_id))?
+        .is_none()
+    {
+        return Err("Objective not found".to_string());
+    }
+    db.with_conn(|conn| {
+        if linked {
+            objectives::link(conn, objective_id, task_id)
+        } else {
+            objectives::unlink(conn, objective_id, task_id)
+        }
+    })?;
+    let _ = app.emit("objectives-changed", ());
+    Ok(())
+}
+
+/// Progress on the objectives of the week starting `monday`: time tracked
+/// locally on their tasks that week, and, when online, how many of the
+/// tasks are done
+pub async fn progress(
+    app: &AppHandle,
+    monday: NaiveDate,
+) -> Result<Vec<ObjectiveProgress>, String> {
+    let tz = timezone::local();
+    let sunday = monday
+        .checked_add_days(Days::new(6))
+        .ok_or_else(|| format!("Invalid date: {}", monday))?;
+    let (range_start, _) =
+        timezone::day_bounds(monday, tz).ok_or_else(|| format!("Invalid date: {}", monday))?;
+    let (_, range_end) =
+        timezone::day_bounds(sunday, tz).ok_or_else(|| format!("Invalid date: {}", sunday))?;
+
+    let (week, entries) = app.state::<Database>().with_conn(|conn| {
+        Ok((
+            objectives::list_week(conn, &monday.to_string())?,
+            time_entries::list_between(conn, range_start, range_end)?,
+        ))
+    })?;
+    if week.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Running entries count up to now; everything is clipped to the week
+    let now = clock::now_millis();
+    let mut tracked: HashMap<String, i64> = HashMap::new();
+    for entry in &entries {
+        let started = entry.started_at.max(range_start);
+        let ended = entry.ended_at.unwrap_or(now).min(range_end);
+        *tracked.entry(entry.task_id.clone()).or_insert(0) += (ended - started).max(0) / 60_000;
+    }
+
+    let task_ids: HashSet<&str> = week
+        .iter()
+        .flat_map(|objective| objective.task_ids.iter().map(String::as_str))
+        .collect();
+    let completed = completed_tasks(app, task_ids).await;
+
+    Ok(week
+        .into_iter()
+        .map(|objective| ObjectiveProgress {
+            tracked_minutes: objective
+                .task_ids
+                .iter()
+                .filter_map(|task_id| tracked.get(task_id))
+                .sum(),
+            completed_tasks: completed.as_ref().map(|completed| {
+                objective
+                    .task_ids
+                    .iter()
+                    .filter(|task_id| completed.contains(task_id.as_str()))
+                    .count()
+            }),
+            objective,
+        })
+        .collect())
+}
+
+/// Which of `task_ids` are done on the server. Deleted tasks count as not
+/// done; `None` while signed out, offline or on any other failure.
+async fn completed_tasks(app: &AppHandle, task_ids: HashSet<&str>) -> Option<HashSet<String>> {
+    if !app.state::<ConnectivityState>().is_online() {
+        return None;
+    }
+    let api = Api::new(app).ok()?;
+    let mut completed = HashSet::new();
+    for task_id in task_ids {
+        match api.get::<Value>(&format!("/tasks/{}", task_id)).await {
+            Ok(task) if is_completed(&task) => {
+                completed.insert(task_id.to_string());
+            }
+            Ok(_) => {}
+            Err(e) if e.is_not_found() => {}
+            Err(_) => return None,
+        }
+    }
+    Some(completed)
+}
@@ -0,0 +1,85 @@
+//! Native user verification: Touch ID / password on macOS, Windows Hello on
+//! Windows. Other platforms report `Unavailable` so callers can fall back to
+//! the app password.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthOutcome {
+    Verified,
+    Failed,
+    Unavailable,
+}
+
+/// Prompt the user to verify their identity. Blocks until they respond, so
+/// call it from a blocking task.
+pub fn authenticate(reason: &str) -> AuthOutcome {
+    platform::authenticate(reason)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::AuthOutcome;
+    use block2::RcBlock;
+    use objc2::runtime::Bool;
+    use objc2_foundation::{NSError, NSString};
+    use objc2_local_authentication::{LAContext, LAPolicy};
+    use std::sync::mpsc;
+
+    pub fn authenticate(reason: &str) -> AuthOutcome {
+        let context = unsafe { LAContext::new() };
+        let policy = LAPolicy::DeviceOwnerAuthentication;
+
+        let available = unsafe { context.canEvaluatePolicy_error(policy) };
+        if available.is_err() {
+            return AuthOutcome::Unavailable;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let reply = RcBlock::new(move |success: Bool, _error: *mut NSError| {
+            let _ = tx.send(success.as_bool());
+        });
+        unsafe {
+            context.evaluatePolicy_localizedReason_reply(
+                policy,
+                &NSString::from_str(reason),
+                &reply,
+            );
+        }
+
+        match rx.recv() {
+            Ok(true) => AuthOutcome::Verified,
+            _ => AuthOutcome::Failed,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::AuthOutcome;
+    use windows::core::HSTRING;
+    use windows::Security::Credentials::UI::{
+        UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+
+    pub fn authenticate(reason: &str) -> AuthOutcome {
+        let available = UserConsentVerifier::CheckAvailabilityAsync().and_then(|op| op.get());
+        if !matches!(available, Ok(UserConsentVerifierAvailability::Available)) {
+            return AuthOutcome::Unavailable;
+        }
+
+        match UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))
+            .and_then(|op| op.get())
+        {
+            Ok(UserConsentVerificationResult::Verified) => AuthOutcome::Verified,
+            _ => AuthOutcome::Failed,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::AuthOutcome;
+
+    pub fn authenticate(_reason: &str) -> AuthOutcome {
+        AuthOutcome::Unavailable
+    }
+}
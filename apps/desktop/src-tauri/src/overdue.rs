@@ -0,0 +1,211 @@
+//! Overdue task alerts. A task block that ended today without the task
+//! being started (no timer on it) or completed is announced, then again at
+//! growing intervals, up to `ESCALATION_MINUTES.len()` times. Overdue
+//! notifications are also capped per hour; when more are due at once they
+//! are combined into one. Each is also sent as `task-overdue`;
+//! `reschedule_to_free_slot` moves its block to the next free slot.
+
+use chrono::{TimeZone, Utc};
+use opensunsama_core::task::is_completed;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::Api;
+use crate::connectivity::ConnectivityState;
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::db::time_entries;
+use crate::db::Database;
+use crate::notifications::{self, Category, Notice};
+use crate::{clock, settings, timezone, weekly_review};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Minutes after a block ends at which each alert is due
+const ESCALATION_MINUTES: &[i64] = &[0, 30, 120];
+/// Overdue notifications shown in any hour
+const MAX_PER_HOUR: usize = 3;
+/// A timer started this long before its block still counts as starting it
+const EARLY_START_MS: i64 = 30 * 60 * 1000;
+const HOUR_MS: i64 = 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverdueTask {
+    pub block_id: String,
+    pub task_id: String,
+    pub title: String,
+    /// Unix milliseconds
+    pub ended_at: i64,
+    /// How many times the task has been announced, this one included
+    pub alerts: usize,
+}
+
+#[derive(Default)]
+pub struct OverdueState {
+    /// Alerts sent, by block id and end time so a moved block starts over
+    alerts: Mutex<HashMap<(String, i64), usize>>,
+    /// When recent overdue notifications were shown, for the hourly cap
+    shown_at: Mutex<Vec<i64>>,
+}
+
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = check(&app).await {
+                tracing::warn!("Overdue check failed: {}", e);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+async fn check(app: &AppHandle) -> Result<(), String> {
+    if !settings::load(app)?.notifications.overdue_alerts {
+        return Ok(());
+    }
+
+    let now = clock::now_millis();
+    let tz = timezone::local();
+    let (day_start, _) = timezone::day_bounds(weekly_review::today(), tz)
+        .ok_or_else(|| "Invalid local date".to_string())?;
+    let state = app.state::<OverdueState>();
+    if let Ok(mut alerts) = state.alerts.lock() {
+        alerts.retain(|(_, ended_at), _| *ended_at >= day_start);
+    }
+
+    let due = due_alerts(app, &state, day_start, now)?;
+    if due.is_empty() {
+        return Ok(());
+    }
+    // Whether a task was completed is only known to the server; wait
+    // rather than nag about finished work
+    if !app.state::<ConnectivityState>().is_online() {
+        return Ok(());
+    }
+    let Ok(api) = Api::new(app) else {
+        return Ok(());
+    };
+
+    let mut overdue = Vec::new();
+    for (block, alerts) in due {
+        let Some(task_id) = block.task_id.clone() else {
+            continue;
+        };
+        let open = match api.get::<Value>(&format!("/tasks/{}", task_id)).await {
+            Ok(task) => !is_completed(&task),
+            Err(e) if e.is_not_found() => false,
+            Err(e) => return Err(format!("Failed to load task: {}", e)),
+        };
+        let alerts = if open { alerts } else { ESCALATION_MINUTES.len() };
+        if let Ok(mut sent) = state.alerts.lock() {
+            sent.insert((block.id.clone(), block.end_at), alerts);
+        }
+        if open {
+            overdue.push(OverdueTask {
+                block_id: block.id,
+                task_id,
+                title: block.title,
+                ended_at: block.end_at,
+                alerts,
+            });
+        }
+    }
+
+    for task in &overdue {
+        let _ = app.emit("task-overdue", task);
+    }
+    announce(app, &state, &overdue, now);
+    Ok(())
+}
+
+/// Task blocks that ended today without a timer on their task, with the
+/// number of alerts that should have gone out by now, when that's more than
+/// were sent
+fn due_alerts(
+    app: &AppHandle,
+    state: &OverdueState,
+    day_start: i64,
+    now: i64,
+) -> Result<Vec<(TimeBlock, usize)>, String> {
+    let (blocks, entries) = app.state::<Database>().with_conn(|conn| {
+        Ok((
+            time_blocks::list_between(conn, day_start, now)?,
+            time_entries::list_between(conn, day_start - EARLY_START_MS, now)?,
+        ))
+    })?;
+    let sent = state.alerts.lock().map(|alerts| alerts.clone()).unwrap_or_default();
+
+    Ok(blocks
+        .into_iter()
+        .filter(|block| !block.is_event && block.end_at <= now && block.start_at >= day_start)
+        .filter(|block| {
+            let Some(task_id) = &block.task_id else {
+                return false;
+            };
+            !entries.iter().any(|entry| {
+                &entry.task_id == task_id
+                    && entry.ended_at.is_none_or(|ended_at| ended_at > block.start_at - EARLY_START_MS)
+            })
+        })
+        .filter_map(|block| {
+            let owed = ESCALATION_MINUTES
+                .iter()
+                .filter(|minutes| block.end_at + **minutes * 60_000 <= now)
+                .count();
+            let sent = sent.get(&(block.id.clone(), block.end_at)).copied().unwrap_or(0);
+            (owed > sent).then_some((block, owed))
+        })
+        .collect())
+}
+
+/// Notify about `overdue`, within the hourly cap. Alerts over the cap are
+/// skipped, not queued; the next escalation step brings the task back.
+fn announce(app: &AppHandle, state: &OverdueState, overdue: &[OverdueTask], now: i64) {
+    let Ok(mut shown_at) = state.shown_at.lock() else {
+        return;
+    };
+    shown_at.retain(|at| *at > now - HOUR_MS);
+    let room = MAX_PER_HOUR.saturating_sub(shown_at.len());
+    if overdue.is_empty() || room == 0 {
+        return;
+    }
+
+    let notices: Vec<Notice> = if overdue.len() <= room {
+        overdue.iter().map(notice).collect()
+    } else {
+        let titles: Vec<&str> = overdue.iter().map(|task| task.title.as_str()).collect();
+        vec![Notice::new(Category::Reminder, format!("{} tasks are overdue", overdue.len()))
+            .body(titles.join(", "))]
+    };
+    for notice in notices {
+        shown_at.push(now);
+        let _ = notifications::notify(app, notice);
+    }
+}
+
+fn notice(task: &OverdueTask) -> Notice {
+    if task.alerts <= 1 {
+        let ended = Utc
+            .timestamp_millis_opt(task.ended_at)
+            .single()
+            .map(|at| at.with_timezone(&timezone::local()).format("%-I:%M %p").to_string())
+            .unwrap_or_default();
+        return Notice::new(Category::Reminder, format!("{} is overdue", task.title)).body(format!(
+            "Its time block ended at {}. Start it or reschedule it to the next free slot.",
+            ended
+        ));
+    }
+
+    let minutes = (clock::now_millis() - task.ended_at).max(0) / 60_000;
+    let ago = match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{} min", m),
+        (1, _) => "over an hour".to_string(),
+        (h, _) => format!("over {} hours", h),
+    };
+    Notice::new(Category::Reminder, format!("Still not started: {}", task.title))
+        .body(format!("Its time block ended {} ago.", ago))
+}
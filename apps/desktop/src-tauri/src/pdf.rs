@@ -0,0 +1,162 @@
+//! Minimal PDF writer for plain-text reports: A4 pages of Helvetica text
+//! with bold headings, wrapped and paginated. No images, links or fonts
+//! outside the standard 14; characters outside Latin-1 print as `?`.
+
+use std::path::Path;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const BODY_SIZE: f32 = 11.0;
+const HEADING_SIZE: f32 = 15.0;
+const LINE_SPACING: f32 = 1.4;
+/// Average Helvetica glyph width as a fraction of the font size, for wrapping
+const AVERAGE_GLYPH_WIDTH: f32 = 0.5;
+
+pub enum Block {
+    Heading(String),
+    Paragraph(String),
+    Blank,
+}
+
+/// Lay out `blocks` and write the document to `path`
+pub fn write(path: &Path, title: &str, blocks: &[Block]) -> Result<(), String> {
+    std::fs::write(path, render(title, blocks))
+        .map_err(|e| format!("Failed to write PDF: {}", e))
+}
+
+fn render(title: &str, blocks: &[Block]) -> Vec<u8> {
+    let pages = paginate(blocks);
+
+    // 1: catalog, 2: page tree, 3-4: fonts, 5: info, then a page and its
+    // content stream per page
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 6 + i * 2).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        format!("<< /Title ({}) /Producer (Open Sunsama) >>", escape(title)),
+    ];
+    for (page_id, content) in page_ids.iter().zip(&pages) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            page_id + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).into_bytes());
+    }
+
+    let xref_at = out.len();
+    out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        out.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+    }
+    out.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_at
+        )
+        .into_bytes(),
+    );
+    out
+}
+
+/// Content streams, one per page
+fn paginate(blocks: &[Block]) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+
+    for block in blocks {
+        let (font, size, text) = match block {
+            Block::Heading(text) => ("F2", HEADING_SIZE, text.as_str()),
+            Block::Paragraph(text) => ("F1", BODY_SIZE, text.as_str()),
+            Block::Blank => ("F1", BODY_SIZE, ""),
+        };
+        let max_chars = ((PAGE_WIDTH - 2.0 * MARGIN) / (size * AVERAGE_GLYPH_WIDTH)) as usize;
+
+        for line in wrap(text, max_chars) {
+            y -= size * LINE_SPACING;
+            if y < MARGIN {
+                pages.push(std::mem::take(&mut page));
+                y = PAGE_HEIGHT - MARGIN - size * LINE_SPACING;
+            }
+            if !line.is_empty() {
+                page.push_str(&format!(
+                    "BT /{} {} Tf {} {} Td ({}) Tj ET\n",
+                    font,
+                    size,
+                    MARGIN,
+                    y,
+                    escape(&line)
+                ));
+            }
+        }
+    }
+
+    pages.push(page);
+    pages
+}
+
+/// Break `text` into lines of at most `max_chars`, at spaces where possible
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split(' ') {
+        let mut word = word.to_string();
+        while word.chars().count() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let split_at = word.char_indices().nth(max_chars).map_or(word.len(), |(i, _)| i);
+            let rest = word.split_off(split_at);
+            lines.push(std::mem::replace(&mut word, rest));
+        }
+        let needed = line.chars().count() + usize::from(!line.is_empty()) + word.chars().count();
+        if needed > max_chars && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+
+    lines.push(line);
+    lines
+}
+
+/// A PDF string literal body in WinAnsi (Latin-1 for our purposes)
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
@@ -0,0 +1,202 @@
+//! Per-command latency and error counts. Every command runs in a tracing
+//! span (see `commands`); a subscriber layer times each span from creation
+//! to close, so async commands are measured until they finish, and counts
+//! the ones that ended in an error event. `get_perf_metrics` feeds the
+//! diagnostics screen.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::clock;
+use crate::crash::BreadcrumbLayer;
+use crate::telemetry::TelemetryLayer;
+
+/// Recent latencies kept per command for percentiles
+const SAMPLES: usize = 200;
+
+static METRICS: OnceLock<Mutex<Metrics>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandMetrics {
+    pub command: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub mean_ms: f64,
+    /// Over the most recent calls
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub last_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerfMetrics {
+    /// Unix milliseconds when counting started
+    pub since: i64,
+    /// Slowest (by p95) first
+    pub commands: Vec<CommandMetrics>,
+}
+
+struct Metrics {
+    since: i64,
+    commands: HashMap<&'static str, CommandStats>,
+}
+
+#[derive(Default)]
+struct CommandStats {
+    calls: u64,
+    errors: u64,
+    total: Duration,
+    max: Duration,
+    recent: VecDeque<Duration>,
+}
+
+/// Set on a command's span while it runs
+struct Timing {
+    started: Instant,
+    failed: bool,
+}
+
+struct CommandLayer;
+
+impl<S> Layer<S> for CommandLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !is_command(attrs.metadata().target()) {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Timing {
+                started: Instant::now(),
+                failed: false,
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        if let Some(span) = ctx.event_span(event) {
+            if let Some(timing) = span.extensions_mut().get_mut::<Timing>() {
+                timing.failed = true;
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(timing) = extensions.get::<Timing>() else {
+            return;
+        };
+        record(span.name(), timing.started.elapsed(), timing.failed);
+    }
+}
+
+/// Install the tracing subscriber, which also feeds crash report
+/// breadcrumbs and the OTLP export. Call once, before the app starts.
+/// Fails when another subscriber got there first, which keeps receiving
+/// events while command metrics stay off.
+pub fn init() -> Result<(), String> {
+    METRICS.get_or_init(|| {
+        Mutex::new(Metrics {
+            since: clock::now_millis(),
+            commands: HashMap::new(),
+        })
+    });
+    let subscriber = tracing_subscriber::registry()
+        .with(CommandLayer)
+        .with(BreadcrumbLayer)
+        .with(TelemetryLayer);
+    tracing::subscriber::set_global_default(subscriber).map_err(|_| {
+        "A tracing subscriber was already installed; command metrics are off".to_string()
+    })
+}
+
+pub fn metrics() -> PerfMetrics {
+    let Some(Ok(metrics)) = METRICS.get().map(Mutex::lock) else {
+        return PerfMetrics {
+            since: clock::now_millis(),
+            commands: Vec::new(),
+        };
+    };
+
+    let mut commands: Vec<CommandMetrics> = metrics
+        .commands
+        .iter()
+        .map(|(command, stats)| {
+            let mut recent: Vec<Duration> = stats.recent.iter().copied().collect();
+            recent.sort();
+            CommandMetrics {
+                command: command.to_string(),
+                calls: stats.calls,
+                errors: stats.errors,
+                mean_ms: millis(stats.total) / stats.calls.max(1) as f64,
+                p50_ms: percentile(&recent, 50),
+                p95_ms: percentile(&recent, 95),
+                max_ms: millis(stats.max),
+                last_ms: stats.recent.back().copied().map_or(0.0, millis),
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms));
+
+    PerfMetrics {
+        since: metrics.since,
+        commands,
+    }
+}
+
+/// Start counting again from zero
+pub fn reset() {
+    if let Some(Ok(mut metrics)) = METRICS.get().map(Mutex::lock) {
+        metrics.since = clock::now_millis();
+        metrics.commands.clear();
+    }
+}
+
+fn record(command: &'static str, elapsed: Duration, failed: bool) {
+    let Some(Ok(mut metrics)) = METRICS.get().map(Mutex::lock) else {
+        return;
+    };
+    let stats = metrics.commands.entry(command).or_default();
+    stats.calls += 1;
+    stats.errors += u64::from(failed);
+    stats.total += elapsed;
+    stats.max = stats.max.max(elapsed);
+    if stats.recent.len() == SAMPLES {
+        stats.recent.pop_front();
+    }
+    stats.recent.push_back(elapsed);
+}
+
+/// Spans opened by `#[tracing::instrument]` on the command handlers
+fn is_command(target: &str) -> bool {
+    target.contains("::commands")
+}
+
+/// `percent`th percentile of `sorted`, in milliseconds
+fn percentile(sorted: &[Duration], percent: usize) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = (sorted.len() * percent).div_ceil(100).saturating_sub(1);
+    millis(sorted[index.min(sorted.len() - 1)])
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,199 @@
+//! Battery and metered-connection awareness for background work. On
+//! battery power or a metered connection the sync engine and the
+//! connectivity probe run less often and attachment transfers wait; on a
+//! nearly empty battery background sync stops until the charger is back.
+//! Manual syncs and transfers the user asked for always go through.
+//!
+//! Metered connections are read from Windows' connection cost and
+//! NetworkManager on Linux. macOS only exposes this through
+//! Network.framework path monitoring, so there it's never reported.
+
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::watch;
+
+use crate::settings::{self, SyncSettings};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// How much longer background intervals get while saving power
+const REDUCED_FACTOR: u32 = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerStatus {
+    /// Running on battery rather than external power
+    pub on_battery: bool,
+    /// Charge of the battery (or all batteries together), if there is one
+    pub battery_percent: Option<u8>,
+    pub metered: bool,
+}
+
+/// How much background work is allowed right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Budget {
+    Normal,
+    /// Background intervals stretched, transfers held
+    Reduced,
+    /// Background sync and transfers held
+    Paused,
+}
+
+pub struct PowerState {
+    sender: watch::Sender<PowerStatus>,
+}
+
+impl PowerState {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(PowerStatus::default());
+        Self { sender }
+    }
+
+    pub fn status(&self) -> PowerStatus {
+        self.sender.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<PowerStatus> {
+        self.sender.subscribe()
+    }
+}
+
+/// Poll the power source and connection cost, publishing changes and
+/// emitting `power-status-changed`
+pub fn start_monitor(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let status = PowerStatus {
+                metered: platform::is_metered().await,
+                ..battery()
+            };
+            let state = app.state::<PowerState>();
+            if *state.sender.borrow() != status {
+                state.sender.send_replace(status.clone());
+                let _ = app.emit("power-status-changed", &status);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// The background work budget under the current status and settings
+pub fn budget(app: &AppHandle) -> Budget {
+    match settings::load(app) {
+        Ok(settings) => budget_with(app, &settings.sync),
+        Err(_) => Budget::Normal,
+    }
+}
+
+/// `budget` with the sync settings already loaded
+pub fn budget_with(app: &AppHandle, sync: &SyncSettings) -> Budget {
+    if !sync.power_saving {
+        return Budget::Normal;
+    }
+
+    let status = app.state::<PowerState>().status();
+    let low_battery = status.on_battery
+        && status
+            .battery_percent
+            .is_some_and(|percent| percent <= sync.pause_below_battery_percent);
+    if low_battery {
+        Budget::Paused
+    } else if status.on_battery || status.metered {
+        Budget::Reduced
+    } else {
+        Budget::Normal
+    }
+}
+
+/// `base` stretched to fit the budget
+pub fn interval(app: &AppHandle, base: Duration) -> Duration {
+    match budget(app) {
+        Budget::Normal => base,
+        Budget::Reduced | Budget::Paused => base * REDUCED_FACTOR,
+    }
+}
+
+/// Whether background attachment transfers should wait for better
+/// conditions
+pub fn defers_transfers(app: &AppHandle) -> bool {
+    settings::load(app).is_ok_and(|settings| defers_transfers_with(app, &settings.sync))
+}
+
+/// `defers_transfers` with the sync settings already loaded
+pub fn defers_transfers_with(app: &AppHandle, sync: &SyncSettings) -> bool {
+    sync.defer_transfers && budget_with(app, sync) != Budget::Normal
+}
+
+fn battery() -> PowerStatus {
+    let Ok(batteries) = starship_battery::Manager::new().and_then(|manager| manager.batteries()) else {
+        return PowerStatus::default();
+    };
+    let batteries: Vec<_> = batteries.filter_map(Result::ok).collect();
+    if batteries.is_empty() {
+        return PowerStatus::default();
+    }
+
+    let energy: f32 = batteries.iter().map(|b| b.energy().value).sum();
+    let full: f32 = batteries.iter().map(|b| b.energy_full().value).sum();
+    PowerStatus {
+        on_battery: batteries
+            .iter()
+            .any(|b| b.state() == starship_battery::State::Discharging),
+        battery_percent: (full > 0.0).then(|| ((energy / full) * 100.0).round().clamp(0.0, 100.0) as u8),
+        metered: false,
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Networking::Connectivity::{NetworkCostType, NetworkInformation};
+
+    pub async fn is_metered() -> bool {
+        let cost = NetworkInformation::GetInternetConnectionProfile()
+            .and_then(|profile| profile.GetConnectionCost());
+        let Ok(cost) = cost else {
+            return false;
+        };
+        let limited = cost
+            .NetworkCostType()
+            .is_ok_and(|kind| kind == NetworkCostType::Fixed || kind == NetworkCostType::Variable);
+        limited || cost.Roaming().unwrap_or(false) || cost.OverDataLimit().unwrap_or(false)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use zbus::Connection;
+
+    /// NMMetered: 1 yes, 3 guessed yes
+    const METERED_VALUES: [u32; 2] = [1, 3];
+
+    pub async fn is_metered() -> bool {
+        let Ok(connection) = Connection::system().await else {
+            return false;
+        };
+        let proxy = zbus::Proxy::new(
+            &connection,
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+        )
+        .await;
+        match proxy {
+            Ok(proxy) => proxy
+                .get_property::<u32>("Metered")
+                .await
+                .is_ok_and(|metered| METERED_VALUES.contains(&metered)),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    pub async fn is_metered() -> bool {
+        false
+    }
+}
@@ -0,0 +1,203 @@
+//! Profiles keep separate sets of data, e.g. work and personal planning.
+//! Each has its own stores, database, attachments and backups (in its own
+//! directory, see `data_dir`) and its own keychain entries. Profiles other
+//! than the default also get their own webview storage, so each stays
+//! signed in to its own account. A run uses one profile; switching restarts
+//! the app into the other.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{
+    menu::{CheckMenuItem, Submenu},
+    AppHandle, Emitter, Manager, Wry,
+};
+
+use crate::{clock, data_dir};
+
+pub const DEFAULT_PROFILE: &str = "default";
+/// Prefix for tray menu item ids that switch profile
+pub const MENU_ID_PREFIX: &str = "profile:";
+
+const PROFILES_FILE: &str = "profiles.json";
+const PROFILES_DIR: &str = "profiles";
+const MAX_NAME_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    /// The profile this run uses (when listing) or the one to start with
+    /// next (on disk)
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileList {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Personal".to_string(),
+                created_at: 0,
+            }],
+        }
+    }
+}
+
+/// The tray's profile switcher, refilled when profiles change
+#[derive(Default)]
+pub struct ProfilesState {
+    submenu: Mutex<Option<Submenu<Wry>>>,
+}
+
+/// Directory of a profile's data under the data root. The default profile
+/// uses the root itself, where data lived before profiles existed.
+pub fn dir(root: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE {
+        root.to_path_buf()
+    } else {
+        root.join(PROFILES_DIR).join(id)
+    }
+}
+
+pub fn exists(root: &Path, id: &str) -> bool {
+    load(root).profiles.iter().any(|profile| profile.id == id)
+}
+
+/// The profile to start with
+pub fn active_id(root: &Path) -> String {
+    let list = load(root);
+    if list.profiles.iter().any(|profile| profile.id == list.active) {
+        list.active
+    } else {
+        DEFAULT_PROFILE.to_string()
+    }
+}
+
+pub fn list(app: &AppHandle) -> Result<ProfileList, String> {
+    let mut list = load(&data_dir::root(app)?);
+    list.active = data_dir::profile(app);
+    Ok(list)
+}
+
+pub fn create(app: &AppHandle, name: &str) -> Result<Profile, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name can't be empty".to_string());
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(format!("Profile names are limited to {} characters", MAX_NAME_CHARS));
+    }
+
+    let root = data_dir::root(app)?;
+    let mut list = load(&root);
+    if list.profiles.iter().any(|profile| profile.name.eq_ignore_ascii_case(name)) {
+        return Err(format!("A profile named \"{}\" already exists", name));
+    }
+    let profile = Profile {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        created_at: clock::now_millis(),
+    };
+    std::fs::create_dir_all(dir(&root, &profile.id))
+        .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+    list.profiles.push(profile.clone());
+    save(&root, &list)?;
+
+    refresh(app)?;
+    Ok(profile)
+}
+
+/// Make `id` the profile to start with and restart into it
+pub fn switch(app: &AppHandle, id: &str) -> Result<(), String> {
+    if id == data_dir::profile(app) {
+        return Ok(());
+    }
+    let root = data_dir::root(app)?;
+    let mut list = load(&root);
+    if !list.profiles.iter().any(|profile| profile.id == id) {
+        return Err(format!("Unknown profile {}", id));
+    }
+    list.active = id.to_string();
+    save(&root, &list)?;
+
+    app.request_restart();
+    Ok(())
+}
+
+/// Add the tray's profile submenu, to be kept in sync with the list
+pub fn register_submenu(app: &AppHandle, submenu: Submenu<Wry>) -> Result<(), String> {
+    if let Ok(mut current) = app.state::<ProfilesState>().submenu.lock() {
+        *current = Some(submenu.clone());
+    }
+    fill_submenu(app, &submenu, &list(app)?)
+}
+
+/// Switch to the profile behind a menu item
+pub fn select(app: &AppHandle, menu_id: &str) {
+    let Some(id) = menu_id.strip_prefix(MENU_ID_PREFIX) else {
+        return;
+    };
+    if let Err(e) = switch(app, id) {
+        let _ = app.emit("profile-error", e);
+    }
+}
+
+fn refresh(app: &AppHandle) -> Result<(), String> {
+    let list = list(app)?;
+    let submenu = app
+        .state::<ProfilesState>()
+        .submenu
+        .lock()
+        .ok()
+        .and_then(|submenu| submenu.clone());
+    if let Some(submenu) = submenu {
+        fill_submenu(app, &submenu, &list)?;
+    }
+    let _ = app.emit("profiles-changed", &list);
+    Ok(())
+}
+
+fn fill_submenu(app: &AppHandle, submenu: &Submenu<Wry>, list: &ProfileList) -> Result<(), String> {
+    let map_err = |e: tauri::Error| format!("Failed to update profiles menu: {}", e);
+
+    for item in submenu.items().map_err(map_err)? {
+        submenu.remove(&item).map_err(map_err)?;
+    }
+    for profile in &list.profiles {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{}{}", MENU_ID_PREFIX, profile.id),
+            &profile.name,
+            true,
+            profile.id == list.active,
+            None::<&str>,
+        )
+        .map_err(map_err)?;
+        submenu.append(&item).map_err(map_err)?;
+    }
+    Ok(())
+}
+
+fn load(root: &Path) -> ProfileList {
+    std::fs::read_to_string(root.join(PROFILES_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(root: &Path, list: &ProfileList) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(list)
+        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+    std::fs::write(root.join(PROFILES_FILE), json)
+        .map_err(|e| format!("Failed to save profiles: {}", e))
+}
@@ -0,0 +1,162 @@
+//! Completing the current task without opening the window (global shortcut
+//! or tray). The change goes through the outbox with a short delay, which is
+//! the undo window: undoing cancels the queued request, so the backend never
+//! sees it. The "current task" is the one being timed, else the one focused
+//! in the webview.
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::menu::{self, MenuItemState};
+use crate::notifications::{self, Category, Notice};
+use crate::sync::outbox;
+use crate::timer::{self, TaskRef, TimerState};
+use crate::clock;
+
+const UNDO_WINDOW: Duration = Duration::from_secs(10);
+pub const UNDO_MENU_ID: &str = "undo_complete";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingCompletion {
+    pub task_id: String,
+    pub title: String,
+    /// Unix milliseconds; undo is possible until then
+    pub undo_until: i64,
+    #[serde(skip)]
+    outbox_id: String,
+}
+
+#[derive(Default)]
+pub struct QuickCompleteState {
+    pending: Mutex<Option<PendingCompletion>>,
+}
+
+impl QuickCompleteState {
+    fn replace(&self, pending: Option<PendingCompletion>) -> Option<PendingCompletion> {
+        self.pending
+            .lock()
+            .ok()
+            .and_then(|mut current| std::mem::replace(&mut *current, pending))
+    }
+}
+
+/// Complete the current task. Emits `task-completed` with the pending
+/// completion so the webview can update at once.
+pub fn complete_current(app: &AppHandle) -> Result<PendingCompletion, String> {
+    let task = current_task(app)?.ok_or_else(|| "No current task to complete".to_string())?;
+
+    if timer::status(app)?
+        .running
+        .is_some_and(|entry| entry.task_id == task.id)
+    {
+        timer::stop(app)?;
+    }
+
+    let completed_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    let outbox_id = outbox::enqueue(
+        app,
+        "PATCH",
+        &format!("/tasks/{}", task.id),
+        Some(&json!({ "completedAt": completed_at })),
+        UNDO_WINDOW,
+    )?;
+
+    let pending = PendingCompletion {
+        task_id: task.id,
+        title: task.title,
+        undo_until: clock::now_millis() + UNDO_WINDOW.as_millis() as i64,
+        outbox_id,
+    };
+    // Only the latest completion can be undone; earlier ones just go out
+    app.state::<QuickCompleteState>().replace(Some(pending.clone()));
+    app.state::<TimerState>().set_current_task(None);
+
+    set_undo_item(app, Some(&pending.title));
+    let _ = app.emit("task-completed", &pending);
+
+    let handle = app.clone();
+    let outbox_id = pending.outbox_id.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(UNDO_WINDOW).await;
+        let state = handle.state::<QuickCompleteState>();
+        let expired = state
+            .pending
+            .lock()
+            .ok()
+            .is_some_and(|pending| pending.as_ref().is_some_and(|p| p.outbox_id == outbox_id));
+        if expired {
+            state.replace(None);
+            set_undo_item(&handle, None);
+        }
+    });
+
+    Ok(pending)
+}
+
+/// Complete from outside the window (global shortcut, tray) and confirm
+/// with a notification, since the window may not be visible
+pub fn complete_with_notification(app: &AppHandle) {
+    let (title, body) = match complete_current(app) {
+        Ok(pending) => (
+            "Task completed",
+            format!("{} · undo from the tray menu", pending.title),
+        ),
+        Err(e) => ("Couldn't complete the task", e),
+    };
+    notify(app, title, &body);
+}
+
+/// Undo the last quick completion if it hasn't been sent yet. Emits
+/// `task-completion-undone` with the task id.
+pub fn undo(app: &AppHandle) -> Result<bool, String> {
+    let Some(pending) = app.state::<QuickCompleteState>().replace(None) else {
+        return Ok(false);
+    };
+    set_undo_item(app, None);
+
+    if !outbox::cancel(app, &pending.outbox_id)? {
+        return Ok(false);
+    }
+    app.state::<TimerState>().set_current_task(Some(TaskRef {
+        id: pending.task_id.clone(),
+        title: pending.title.clone(),
+    }));
+    let _ = app.emit("task-completion-undone", &pending.task_id);
+    notify(app, "Completion undone", &pending.title);
+    Ok(true)
+}
+
+fn current_task(app: &AppHandle) -> Result<Option<TaskRef>, String> {
+    let status = timer::status(app)?;
+    Ok(status
+        .running
+        .map(|entry| TaskRef {
+            id: entry.task_id,
+            title: entry.task_title,
+        })
+        .or(status.current_task))
+}
+
+fn set_undo_item(app: &AppHandle, title: Option<&str>) {
+    let label = match title {
+        Some(title) => format!("Undo Complete \u{201c}{}\u{201d}", title),
+        None => "Undo Complete".to_string(),
+    };
+    let _ = menu::set_state(
+        app,
+        &[MenuItemState {
+            id: UNDO_MENU_ID.to_string(),
+            enabled: Some(title.is_some()),
+            label: Some(label),
+        }],
+    );
+}
+
+fn notify(app: &AppHandle, title: &str, body: &str) {
+    let _ = notifications::notify(app, Notice::new(Category::Feedback, title).body(body));
+}
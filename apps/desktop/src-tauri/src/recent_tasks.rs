@@ -0,0 +1,181 @@
+//! Recently touched tasks, shown as "Open Recent" in the File and tray
+//! menus. The webview reports tasks as they're opened or edited; the list is
+//! kept in memory and mirrored to a JSON file so it survives restarts.
+//!
+//! Tauri doesn't expose the macOS dock menu, so there the tray submenu is
+//! the OS-level entry point.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{
+    menu::{MenuItem, PredefinedMenuItem, Submenu},
+    AppHandle, Emitter, Manager, Wry,
+};
+
+use crate::{clock, data_dir};
+
+/// Prefix for menu item ids that open a recent task
+pub const MENU_ID_PREFIX: &str = "recent:";
+pub const CLEAR_MENU_ID: &str = "recent-clear";
+
+const MAX_RECENT: usize = 10;
+const RECENT_FILE: &str = "recent-tasks.json";
+/// Longest title shown in the menu before it's cut off
+const MAX_LABEL_CHARS: usize = 48;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentTask {
+    pub id: String,
+    pub title: String,
+    pub touched_at: i64,
+}
+
+pub struct RecentTasksState {
+    tasks: Mutex<Vec<RecentTask>>,
+    submenus: Mutex<Vec<Submenu<Wry>>>,
+}
+
+impl RecentTasksState {
+    /// Load the saved list; a missing or unreadable file starts empty
+    pub fn load(app: &AppHandle) -> Self {
+        let tasks = recent_file(app)
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self {
+            tasks: Mutex::new(tasks),
+            submenus: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<RecentTask> {
+        self.tasks.lock().map(|tasks| tasks.clone()).unwrap_or_default()
+    }
+}
+
+/// Add an "Open Recent" submenu to be kept in sync with the list
+pub fn register_submenu(app: &AppHandle, submenu: Submenu<Wry>) -> Result<(), String> {
+    let state = app.state::<RecentTasksState>();
+    if let Ok(mut submenus) = state.submenus.lock() {
+        submenus.push(submenu.clone());
+    }
+    fill_submenu(app, &submenu, &state.list())
+}
+
+/// Move a task to the top of the list, adding it if needed
+pub fn touch(app: &AppHandle, id: &str, title: &str) -> Result<(), String> {
+    update(app, |tasks| {
+        tasks.retain(|task| task.id != id);
+        tasks.insert(
+            0,
+            RecentTask {
+                id: id.to_string(),
+                title: title.to_string(),
+                touched_at: clock::now_millis(),
+            },
+        );
+        tasks.truncate(MAX_RECENT);
+    })
+}
+
+/// Drop a task, e.g. after it was deleted
+pub fn remove(app: &AppHandle, id: &str) -> Result<(), String> {
+    update(app, |tasks| tasks.retain(|task| task.id != id))
+}
+
+pub fn clear(app: &AppHandle) -> Result<(), String> {
+    update(app, Vec::clear)
+}
+
+/// Bring the main window forward and open the task
+pub fn open(app: &AppHandle, menu_id: &str) {
+    let Some(id) = menu_id.strip_prefix(MENU_ID_PREFIX) else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.emit("open-task", id);
+    }
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut Vec<RecentTask>)) -> Result<(), String> {
+    let state = app.state::<RecentTasksState>();
+    let tasks = {
+        let mut tasks = state
+            .tasks
+            .lock()
+            .map_err(|e| format!("Failed to lock recent tasks: {}", e))?;
+        change(&mut tasks);
+        tasks.clone()
+    };
+
+    let json = serde_json::to_string(&tasks)
+        .map_err(|e| format!("Failed to serialize recent tasks: {}", e))?;
+    std::fs::write(recent_file(app)?, json)
+        .map_err(|e| format!("Failed to save recent tasks: {}", e))?;
+
+    let submenus = state
+        .submenus
+        .lock()
+        .map(|submenus| submenus.clone())
+        .unwrap_or_default();
+    for submenu in &submenus {
+        fill_submenu(app, submenu, &tasks)?;
+    }
+
+    let _ = app.emit("recent-tasks-changed", &tasks);
+    Ok(())
+}
+
+/// Replace the submenu's items with the current list
+fn fill_submenu(app: &AppHandle, submenu: &Submenu<Wry>, tasks: &[RecentTask]) -> Result<(), String> {
+    let map_err = |e: tauri::Error| format!("Failed to update recent tasks menu: {}", e);
+
+    for item in submenu.items().map_err(map_err)? {
+        submenu.remove(&item).map_err(map_err)?;
+    }
+
+    if tasks.is_empty() {
+        let empty = MenuItem::with_id(app, "recent-empty", "No Recent Tasks", false, None::<&str>)
+            .map_err(map_err)?;
+        return submenu.append(&empty).map_err(map_err);
+    }
+
+    for task in tasks {
+        let item = MenuItem::with_id(
+            app,
+            format!("{}{}", MENU_ID_PREFIX, task.id),
+            menu_label(&task.title),
+            true,
+            None::<&str>,
+        )
+        .map_err(map_err)?;
+        submenu.append(&item).map_err(map_err)?;
+    }
+
+    let separator = PredefinedMenuItem::separator(app).map_err(map_err)?;
+    let clear = MenuItem::with_id(app, CLEAR_MENU_ID, "Clear Menu", true, None::<&str>)
+        .map_err(map_err)?;
+    submenu.append(&separator).map_err(map_err)?;
+    submenu.append(&clear).map_err(map_err)
+}
+
+/// A task title fit for a menu item
+pub fn menu_label(title: &str) -> String {
+    let title = title.trim();
+    let title = if title.is_empty() { "Untitled task" } else { title };
+    if title.chars().count() <= MAX_LABEL_CHARS {
+        return title.to_string();
+    }
+    let cut: String = title.chars().take(MAX_LABEL_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+fn recent_file(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(data_dir::get(app)?.join(RECENT_FILE))
+}
@@ -0,0 +1,153 @@
+//! Daily ritual reminders: "Plan your day" in the morning and "Daily
+//! shutdown" in the evening. They're ordinary rows in the reminders table,
+//! kept scheduled for today and tomorrow, so the native scheduler delivers
+//! them like any other reminder. On delivery `ritual-reminder` carries the
+//! route of the ritual view to open.
+
+use chrono::{NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clock;
+use crate::db::reminders::{self, Reminder};
+use crate::db::Database;
+use crate::settings::{PlanningSettings, RitualSettings};
+use crate::timezone;
+use crate::work_calendar;
+
+/// Prefix for the ids of ritual reminders, followed by `<ritual>:<date>`
+pub const ID_PREFIX: &str = "ritual:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ritual {
+    Planning,
+    Shutdown,
+}
+
+impl Ritual {
+    pub const ALL: [Ritual; 2] = [Ritual::Planning, Ritual::Shutdown];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Ritual::Planning => "planning",
+            Ritual::Shutdown => "shutdown",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Ritual::ALL.into_iter().find(|ritual| ritual.key() == key)
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Ritual::Planning => "Plan your day",
+            Ritual::Shutdown => "Daily shutdown",
+        }
+    }
+
+    fn body(self) -> &'static str {
+        match self {
+            Ritual::Planning => "Take a few minutes to choose what you'll work on today.",
+            Ritual::Shutdown => "Review what you got done and plan what's left for tomorrow.",
+        }
+    }
+
+    /// The Today view, opened on the ritual's flow
+    fn route(self) -> String {
+        format!("/app?ritual={}", self.key())
+    }
+
+    pub fn settings(self, planning: &PlanningSettings) -> &RitualSettings {
+        match self {
+            Ritual::Planning => &planning.planning_ritual,
+            Ritual::Shutdown => &planning.shutdown_ritual,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RitualReminder {
+    pub ritual: Ritual,
+    /// Frontend route for the reminder's action
+    pub route: String,
+}
+
+/// Replace the pending ritual reminders with today's and tomorrow's per
+/// `planning`. Run at startup, when settings change and when the day does.
+pub fn schedule(app: &AppHandle, planning: &PlanningSettings) -> Result<(), String> {
+    let tz_name = timezone::detect();
+    let tz = timezone::parse(&tz_name).unwrap_or(Tz::UTC);
+    let now = clock::now_millis();
+    let Some(today) = Utc
+        .timestamp_millis_opt(now)
+        .single()
+        .map(|utc| utc.with_timezone(&tz).date_naive())
+    else {
+        return Ok(());
+    };
+    let calendar = work_calendar::load(app, planning)?;
+
+    let upcoming: Vec<Reminder> = [Some(today), today.succ_opt()]
+        .into_iter()
+        .flatten()
+        .filter(|date| !calendar.is_day_off(*date))
+        .flat_map(|date| Ritual::ALL.into_iter().map(move |ritual| (date, ritual)))
+        .filter_map(|(date, ritual)| {
+            let settings = ritual.settings(planning);
+            if !settings.enabled {
+                return None;
+            }
+            let time = NaiveTime::parse_from_str(&settings.time, "%H:%M").ok()?;
+            let fire_at = tz
+                .from_local_datetime(&date.and_time(time))
+                .earliest()?
+                .timestamp_millis();
+            (fire_at > now).then(|| Reminder {
+                id: format!("{}{}:{}", ID_PREFIX, ritual.key(), date),
+                task_id: None,
+                title: ritual.title().to_string(),
+                body: Some(ritual.body().to_string()),
+                fire_at,
+                timezone: tz_name.clone(),
+                delivered_at: None,
+            })
+        })
+        .collect();
+
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        for reminder in reminders::list_pending(&tx)? {
+            if reminder.id.starts_with(ID_PREFIX) {
+                reminders::delete(&tx, &reminder.id)?;
+            }
+        }
+        for reminder in &upcoming {
+            reminders::upsert(&tx, reminder)?;
+        }
+        tx.commit()
+    })
+}
+
+/// Called by the scheduler after showing a reminder; for ritual reminders,
+/// hands the action to the webview
+pub fn delivered(app: &AppHandle, reminder: &Reminder) {
+    let Some(ritual) = reminder
+        .id
+        .strip_prefix(ID_PREFIX)
+        .and_then(|rest| rest.split(':').next())
+        .and_then(Ritual::from_key)
+    else {
+        return;
+    };
+
+    let _ = app.emit(
+        "ritual-reminder",
+        RitualReminder {
+            ritual,
+            route: ritual.route(),
+        },
+    );
+}
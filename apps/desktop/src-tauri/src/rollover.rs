@@ -0,0 +1,273 @@
+//! Day boundaries. At local midnight `day-changed` is emitted, unfinished
+//! tasks from the days since the last rollover are carried over per the
+//! planning settings (to the next working day when the new one is a day
+//! off), the server is asked to generate the new day's recurring tasks, and
+//! on working days the checklists attached to rituals are created.
+//!
+//! The job polls rather than sleeping until midnight: timers don't advance
+//! while the machine is asleep, so a rollover missed overnight runs within a
+//! minute of waking. The last completed rollover is stored, so one missed
+//! while the app was closed, signed out or offline runs on the next pass.
+
+use chrono::{Days, NaiveDate};
+use opensunsama_core::task::is_completed;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::api::Api;
+use crate::connectivity::ConnectivityState;
+use crate::settings::{self, CarryOver};
+use crate::work_calendar;
+use crate::clock::{Clock, ClockState};
+use crate::{data_dir, rituals, templates, timezone};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const STORE: &str = "rollover.json";
+const LAST_ROLLOVER_KEY: &str = "lastRollover";
+/// Days looked back for unfinished tasks after a long absence
+const MAX_LOOKBACK_DAYS: u64 = 14;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayChange {
+    /// `YYYY-MM-DD`
+    pub previous: String,
+    pub current: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloverSummary {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    /// Unfinished tasks moved to `date` or to the backlog
+    pub carried_over: usize,
+    pub recurring_created: usize,
+    /// Ritual checklists created from templates
+    pub rituals_created: usize,
+}
+
+/// The local date the app last saw
+pub struct RolloverState {
+    today: Mutex<NaiveDate>,
+}
+
+impl RolloverState {
+    pub fn new(clock: &dyn Clock) -> Self {
+        Self {
+            today: Mutex::new(clock.today()),
+        }
+    }
+}
+
+/// Watch for the local date changing, emitting `day-changed` and running
+/// the rollover; finished rollovers emit `tasks-rolled-over`
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let clock = app.state::<ClockState>();
+            let current = clock.today();
+            let previous = match app.state::<RolloverState>().today.lock() {
+                Ok(mut today) if *today != current => Some(std::mem::replace(&mut *today, current)),
+                _ => None,
+            };
+            if let Some(previous) = previous {
+                let change = DayChange {
+                    previous: previous.to_string(),
+                    current: current.to_string(),
+                };
+                let _ = app.emit("day-changed", change);
+                if let Ok(settings) = settings::load(&app) {
+                    let _ = rituals::schedule(&app, &settings.planning);
+                }
+            }
+
+            match run_if_due(&app, current).await {
+                Ok(Some(summary)) => {
+                    let _ = app.emit("tasks-rolled-over", &summary);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Daily rollover failed: {}", e),
+            }
+
+            let wait = CHECK_INTERVAL.min(until_midnight(&**clock));
+            tokio::time::sleep(wait).await;
+        }
+    });
+}
+
+/// Roll over into `today` unless that already happened. Waits, without
+/// recording anything, while signed out or offline.
+async fn run_if_due(app: &AppHandle, today: NaiveDate) -> Result<Option<RolloverSummary>, String> {
+    let last = last_rollover(app)?;
+    if last.is_some_and(|last| last >= today) {
+        return Ok(None);
+    }
+    if !app.state::<ConnectivityState>().is_online() {
+        return Ok(None);
+    }
+    let Ok(api) = Api::new(app) else {
+        return Ok(None);
+    };
+
+    let planning = settings::load(app)?.planning;
+    let calendar = work_calendar::load(app, &planning)?;
+    let carried_over = match planning.carryover {
+        CarryOver::Off => 0,
+        carryover => {
+            // Unfinished work waits for the next working day rather than
+            // landing on a weekend or holiday
+            let target = calendar.next_work_day(today).unwrap_or(today);
+            carry_over(&api, carryover, missed_days(last, today), target).await?
+        }
+    };
+    let recurring_created = generate_recurring(&api, today).await?;
+    // Missing checklists don't hold up the rest of the rollover
+    let rituals_created = if calendar.is_day_off(today) {
+        0
+    } else {
+        templates::create_rituals(app, &api, &planning, today)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to create ritual checklists: {}", e);
+                0
+            })
+    };
+
+    set_last_rollover(app, today)?;
+    Ok(Some(RolloverSummary {
+        date: today.to_string(),
+        carried_over,
+        recurring_created,
+        rituals_created,
+    }))
+}
+
+/// Move unfinished tasks planned for `days` to `target`, or to the backlog
+async fn carry_over(
+    api: &Api,
+    carryover: CarryOver,
+    days: Vec<NaiveDate>,
+    target: NaiveDate,
+) -> Result<usize, String> {
+    let scheduled_date = match carryover {
+        CarryOver::Backlog => Value::Null,
+        _ => json!(target.to_string()),
+    };
+
+    let mut moved = 0;
+    for day in days {
+        let tasks: Vec<Value> = api
+            .get_all(&format!("/tasks?scheduledDate={}", day))
+            .await?;
+        let unfinished = tasks
+            .iter()
+            .filter(|task| !is_completed(task))
+            .filter_map(|task| task.get("id").and_then(Value::as_str));
+
+        for id in unfinished {
+            match api
+                .patch::<Value, _>(&format!("/tasks/{}", id), &json!({ "scheduledDate": scheduled_date }))
+                .await
+            {
+                Ok(_) => moved += 1,
+                // Deleted elsewhere since the list was fetched
+                Err(e) if e.is_not_found() => {}
+                Err(e) => return Err(format!("Failed to carry over task: {}", e)),
+            }
+        }
+    }
+
+    Ok(moved)
+}
+
+/// Have the server create the instances of recurring tasks due on `date`.
+/// Servers without recurring tasks answer 404, which counts as none.
+async fn generate_recurring(api: &Api, date: NaiveDate) -> Result<usize, String> {
+    match api
+        .post::<Vec<Value>, _>("/tasks/recurring/generate", &json!({ "date": date.to_string() }))
+        .await
+    {
+        Ok(created) => Ok(created.len()),
+        Err(e) if e.is_not_found() => Ok(0),
+        Err(e) => Err(format!("Failed to generate recurring tasks: {}", e)),
+    }
+}
+
+/// Days whose unfinished tasks are due to move: from the last rollover up
+/// to yesterday, or just yesterday the first time
+fn missed_days(last: Option<NaiveDate>, today: NaiveDate) -> Vec<NaiveDate> {
+    let earliest = today
+        .checked_sub_days(Days::new(MAX_LOOKBACK_DAYS))
+        .unwrap_or(today);
+    let first = last
+        .unwrap_or_else(|| today.pred_opt().unwrap_or(today))
+        .max(earliest);
+    first.iter_days().take_while(|day| *day < today).collect()
+}
+
+/// Time left until the next local midnight
+fn until_midnight(clock: &dyn Clock) -> Duration {
+    let tz = timezone::local();
+    let next_midnight = timezone::day_bounds(clock.today(), tz).map(|(_, end)| end);
+    next_midnight
+        .map(|end| Duration::from_millis((end - clock.now_millis()).max(0) as u64))
+        .unwrap_or(CHECK_INTERVAL)
+}
+
+fn last_rollover(app: &AppHandle) -> Result<Option<NaiveDate>, String> {
+    let store = app
+        .store(data_dir::store_path(app, STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    Ok(store
+        .get(LAST_ROLLOVER_KEY)
+        .and_then(|value| value.as_str().and_then(|date| date.parse().ok())))
+}
+
+fn set_last_rollover(app: &AppHandle, date: NaiveDate) -> Result<(), String> {
+    let store = app
+        .store(data_dir::store_path(app, STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store.set(LAST_ROLLOVER_KEY, date.to_string());
+    store
+        .save()
+        .map_err(|e| format!("Failed to save rollover: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn first_rollover_carries_over_yesterday() {
+        assert_eq!(
+            missed_days(None, date("2026-03-02")),
+            vec![date("2026-03-01")]
+        );
+    }
+
+    #[test]
+    fn missed_rollovers_carry_over_every_day_since() {
+        assert_eq!(
+            missed_days(Some(date("2026-02-27")), date("2026-03-02")),
+            vec![date("2026-02-27"), date("2026-02-28"), date("2026-03-01")]
+        );
+        assert!(missed_days(Some(date("2026-03-02")), date("2026-03-02")).is_empty());
+    }
+
+    #[test]
+    fn long_absences_look_back_two_weeks() {
+        let days = missed_days(Some(date("2025-12-01")), date("2026-03-02"));
+        assert_eq!(days.len(), MAX_LOOKBACK_DAYS as usize);
+        assert_eq!(days.first(), Some(&date("2026-02-16")));
+    }
+}
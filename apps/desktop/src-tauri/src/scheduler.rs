@@ -0,0 +1,51 @@
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::db::{reminders, Database};
+use crate::notifications::{self, Category, Notice};
+use crate::clock::ClockState;
+use crate::{budgets, rituals, snooze};
+
+const TICK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Start the native reminder scheduler. Reminders and snoozed notifications
+/// live in the local database, so anything scheduled survives restarts and
+/// is delivered on the next tick. Budget alerts are checked from here too.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let now = app.state::<ClockState>().now_millis();
+            let _ = deliver_due(&app, now);
+            let _ = snooze::deliver_due(&app, now);
+            // Budget checks go to the server; keep them off the reminder path
+            let budget_app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = budgets::check_due(&budget_app).await;
+            });
+            tokio::time::sleep(TICK_INTERVAL).await;
+        }
+    });
+}
+
+/// Deliver the reminders due at `now` (Unix milliseconds)
+fn deliver_due(app: &AppHandle, now: i64) -> Result<(), String> {
+    let db = app.state::<Database>();
+    let due = db.with_conn(|conn| reminders::due(conn, now))?;
+
+    for reminder in due {
+        let category = if reminder.id.starts_with(rituals::ID_PREFIX) {
+            Category::Ritual
+        } else {
+            Category::Reminder
+        };
+        let mut notice = Notice::new(category, &reminder.title);
+        notice.body = reminder.body.clone();
+        notifications::notify(app, notice)?;
+
+        db.with_conn(|conn| reminders::mark_delivered(conn, &reminder.id, now))?;
+        rituals::delivered(app, &reminder);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+//! Screen capture into the attachment store. Full screen and window capture
+//! use the platform capture APIs; region selection uses the OS's own
+//! interactive tool where one exists.
+
+use serde::Deserialize;
+use std::io::Cursor;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use xcap::image::{ImageFormat, RgbaImage};
+use xcap::{Monitor, Window};
+
+use crate::attachments;
+use crate::db::attachments::Attachment;
+
+/// Time for our own window to disappear before capturing
+const HIDE_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaptureMode {
+    FullScreen,
+    /// The frontmost window of another app
+    Window,
+    /// An area the user drags out
+    Region,
+}
+
+/// What the global shortcut captures: a region where the OS has a picker
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub const SHORTCUT_MODE: CaptureMode = CaptureMode::Region;
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub const SHORTCUT_MODE: CaptureMode = CaptureMode::FullScreen;
+
+/// Capture the screen and store the image as an attachment. The main window
+/// is hidden during capture so it doesn't end up in the shot.
+pub async fn capture(
+    app: &AppHandle,
+    mode: CaptureMode,
+    task_id: Option<String>,
+) -> Result<Attachment, String> {
+    let window = app
+        .get_webview_window("main")
+        .filter(|window| window.is_visible().unwrap_or(false));
+    if let Some(window) = &window {
+        let _ = window.hide();
+        tokio::time::sleep(HIDE_DELAY).await;
+    }
+
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || capture_png(&handle, mode))
+        .await
+        .map_err(|e| format!("Screenshot failed: {}", e))
+        .and_then(|result| result);
+
+    if let Some(window) = &window {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let png = result?;
+    let file_name = format!("Screenshot {}.png", chrono::Local::now().format("%Y-%m-%d at %H.%M.%S"));
+    let attachment = attachments::attach_bytes(app, &png, &file_name, task_id.as_deref())?;
+
+    let _ = app.emit("screenshot-captured", &attachment);
+    Ok(attachment)
+}
+
+fn capture_png(app: &AppHandle, mode: CaptureMode) -> Result<Vec<u8>, String> {
+    match mode {
+        CaptureMode::FullScreen => encode(&capture_screen()?),
+        CaptureMode::Window => encode(&capture_front_window(app)?),
+        CaptureMode::Region => capture_region(app),
+    }
+}
+
+fn capture_screen() -> Result<RgbaImage, String> {
+    let monitors = Monitor::all().map_err(|e| format!("Failed to list displays: {}", e))?;
+    let monitor = monitors
+        .iter()
+        .find(|monitor| monitor.is_primary())
+        .or_else(|| monitors.first())
+        .ok_or_else(|| "No display found".to_string())?;
+
+    monitor
+        .capture_image()
+        .map_err(|e| format!("Failed to capture screen: {}", e))
+}
+
+fn capture_front_window(app: &AppHandle) -> Result<RgbaImage, String> {
+    let own_name = app.package_info().name.clone();
+    // Windows are listed front to back
+    let window = Window::all()
+        .map_err(|e| format!("Failed to list windows: {}", e))?
+        .into_iter()
+        .find(|window| {
+            !window.is_minimized()
+                && window.width() > 0
+                && window.height() > 0
+                && !window.title().is_empty()
+                && window.app_name() != own_name
+        })
+        .ok_or_else(|| "No window to capture".to_string())?;
+
+    window
+        .capture_image()
+        .map_err(|e| format!("Failed to capture window: {}", e))
+}
+
+/// Run the OS region picker into a temporary file and read it back
+fn capture_region(app: &AppHandle) -> Result<Vec<u8>, String> {
+    let target = app
+        .path()
+        .temp_dir()
+        .map_err(|e| format!("Failed to resolve temp directory: {}", e))?
+        .join(format!("open-sunsama-capture-{}.png", uuid::Uuid::new_v4()));
+
+    run_region_tool(&target)?;
+    let png = std::fs::read(&target);
+    let _ = std::fs::remove_file(&target);
+    // The tools exit successfully without writing a file when cancelled
+    png.map_err(|_| "Screenshot cancelled".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn run_region_tool(target: &Path) -> Result<(), String> {
+    Command::new("screencapture")
+        .args(["-i", "-x"])
+        .arg(target)
+        .status()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to start screencapture: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn run_region_tool(target: &Path) -> Result<(), String> {
+    let target = target.to_string_lossy();
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let tools: Vec<(&str, Vec<String>)> = vec![
+        ("gnome-screenshot", vec!["-a".into(), "-f".into(), target.to_string()]),
+        ("spectacle", vec!["-r".into(), "-b".into(), "-n".into(), "-o".into(), target.to_string()]),
+        (
+            "sh",
+            if wayland {
+                vec!["-c".into(), format!("grim -g \"$(slurp)\" '{}'", target)]
+            } else {
+                vec!["-c".into(), format!("maim -s '{}'", target)]
+            },
+        ),
+    ];
+
+    for (program, args) in tools {
+        if Command::new(program).args(&args).status().is_ok_and(|status| status.success()) {
+            return Ok(());
+        }
+    }
+    Err("Region capture needs gnome-screenshot, spectacle, grim + slurp or maim".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn run_region_tool(_target: &Path) -> Result<(), String> {
+    Err("Region capture is not supported on this platform; use full screen or window".to_string())
+}
+
+fn encode(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode screenshot: {}", e))?;
+    Ok(png.into_inner())
+}
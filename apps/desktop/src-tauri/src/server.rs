@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::Url;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{http, settings};
+
+pub const DEFAULT_SERVER_URL: &str = "https://api.opensunsama.com";
+
+/// Oldest backend release this client can talk to
+const MIN_SERVER_VERSION: (u64, u64, u64) = (1, 0, 0);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Backend base URL currently in use, shared by every native HTTP caller
+pub struct ServerState {
+    url: RwLock<String>,
+}
+
+impl ServerState {
+    pub fn new(app: &AppHandle) -> Self {
+        let url = settings::load(app)
+            .ok()
+            .and_then(|settings| settings.sync.server_url)
+            .unwrap_or_else(|| DEFAULT_SERVER_URL.to_string());
+
+        Self {
+            url: RwLock::new(url),
+        }
+    }
+
+    pub fn url(&self) -> String {
+        self.url
+            .read()
+            .map(|url| url.clone())
+            .unwrap_or_else(|_| DEFAULT_SERVER_URL.to_string())
+    }
+
+    pub fn set_url(&self, url: String) {
+        if let Ok(mut current) = self.url.write() {
+            *current = url;
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HealthResponse {
+    version: String,
+    #[serde(default)]
+    min_client_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerInfo {
+    pub url: String,
+    pub version: String,
+}
+
+/// Normalize and validate a backend URL. Plain HTTP is only accepted for
+/// loopback and private network addresses, where self-hosters run without TLS.
+pub fn normalize_url(input: &str) -> Result<String, AppError> {
+    validate_url(input).map_err(|e| AppError::new(ErrorCode::InvalidInput, e))
+}
+
+fn validate_url(input: &str) -> Result<String, String> {
+    let url = Url::parse(input.trim()).map_err(|e| format!("Invalid server URL: {}", e))?;
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| "Server URL must include a host".to_string())?;
+    match url.scheme() {
+        "https" => {}
+        "http" if is_local_host(host) => {}
+        "http" => return Err("Server URL must use https unless it is on a local network".to_string()),
+        scheme => return Err(format!("Unsupported URL scheme '{}'", scheme)),
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err("Server URL must not include a query or fragment".to_string());
+    }
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+fn is_local_host(host: &str) -> bool {
+    if host == "localhost" || host.ends_with(".local") {
+        return true;
+    }
+    match host.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => ip.is_loopback(),
+        Err(_) => false,
+    }
+}
+
+/// Probe `/health` and check both sides' version requirements
+pub async fn check_health(app: &AppHandle, url: &str) -> Result<ServerInfo, AppError> {
+    let response = http::client(app)
+        .get(format!("{}/health", url))
+        .timeout(HEALTH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Offline, format!("Server unreachable: {}", e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AppError::from_response(status, None, format!("Server health check failed: {}", status)));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Offline, format!("Failed to read health response: {}", e)))?;
+    let health: HealthResponse = serde_json::from_str(&body)
+        .map_err(|_| "Server did not respond like an Open Sunsama backend".to_string())?;
+
+    let server_version = parse_version(&health.version)
+        .ok_or_else(|| format!("Unrecognized server version '{}'", health.version))?;
+    if server_version < MIN_SERVER_VERSION {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!(
+            "Server version {} is too old; {}.{}.{} or newer is required",
+            health.version, MIN_SERVER_VERSION.0, MIN_SERVER_VERSION.1, MIN_SERVER_VERSION.2
+        )));
+    }
+
+    if let Some(required) = health.min_client_version.as_deref().and_then(parse_version) {
+        let app_version = &app.package_info().version;
+        if (app_version.major, app_version.minor, app_version.patch) < required {
+            return Err(AppError::new(ErrorCode::InvalidInput, format!(
+                "This server requires Open Sunsama {} or newer; please update the app",
+                health.min_client_version.unwrap_or_default()
+            )));
+        }
+    }
+
+    Ok(ServerInfo {
+        url: url.to_string(),
+        version: health.version,
+    })
+}
+
+/// Parse "1.2.3" (ignoring any pre-release suffix) into a comparable tuple
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim_start_matches('v').split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    Some((parts.next()??, parts.next().flatten().unwrap_or(0), parts.next().flatten().unwrap_or(0)))
+}
+
+pub fn current_url(app: &AppHandle) -> String {
+    app.state::<ServerState>().url()
+}
@@ -0,0 +1,151 @@
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use super::{SETTINGS_KEY, SETTINGS_STORE};
+use crate::data_dir;
+
+const VERSION_KEY: &str = "version";
+
+/// A migration takes the raw settings JSON at version N and returns version N+1.
+/// Migrations work on `Value` rather than `AppSettings` so renamed or removed
+/// fields can still be read.
+type Migration = fn(Value) -> Result<Value, String>;
+
+/// Never edit an entry once released; append a new one.
+const MIGRATIONS: &[Migration] = &[
+    // 0 -> 1: unversioned settings stored "" for the default theme
+    migrate_v0_to_v1,
+    // 1 -> 2: flat fields split into typed sections
+    migrate_v1_to_v2,
+];
+
+pub const CURRENT_VERSION: u64 = MIGRATIONS.len() as u64;
+
+/// Bring the stored settings up to `CURRENT_VERSION`, backing up the
+/// original file first. Run before anything else reads settings.
+pub fn run<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let store = app
+        .store(data_dir::store_path(app, SETTINGS_STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+
+    let version = store
+        .get(VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    if version >= CURRENT_VERSION {
+        // Up to date, or written by a newer build; leave it untouched
+        return Ok(());
+    }
+
+    if let Some(settings) = store.get(SETTINGS_KEY) {
+        backup(app, version)?;
+        store.set(SETTINGS_KEY, migrate_value(settings, version)?);
+    }
+
+    store.set(VERSION_KEY, CURRENT_VERSION);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+/// Run every migration after `from` on raw settings JSON
+pub fn migrate_value(mut settings: Value, from: u64) -> Result<Value, String> {
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        settings = migration(settings)
+            .map_err(|e| format!("Settings migration {} failed: {}", index + 1, e))?;
+    }
+    Ok(settings)
+}
+
+/// Copy the settings file to `settings.v<version>.backup.json` next to it
+fn backup<R: Runtime>(app: &AppHandle<R>, version: u64) -> Result<(), String> {
+    let dir = data_dir::get(app)?;
+    let source = dir.join(SETTINGS_STORE);
+    if !source.exists() {
+        return Ok(());
+    }
+
+    let target = dir.join(format!("settings.v{}.backup.json", version));
+    std::fs::copy(&source, &target)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to back up settings: {}", e))
+}
+
+fn migrate_v0_to_v1(mut settings: Value) -> Result<Value, String> {
+    let object = settings
+        .as_object_mut()
+        .ok_or_else(|| "settings is not an object".to_string())?;
+
+    let theme_unset = object
+        .get("theme")
+        .and_then(|theme| theme.as_str())
+        .is_none_or(str::is_empty);
+    if theme_unset {
+        object.insert("theme".to_string(), Value::from("system"));
+    }
+
+    Ok(settings)
+}
+
+fn migrate_v1_to_v2(settings: Value) -> Result<Value, String> {
+    let Value::Object(mut flat) = settings else {
+        return Err("settings is not an object".to_string());
+    };
+
+    let mut take = |keys: &[&str]| -> Value {
+        let section: Map<String, Value> = keys
+            .iter()
+            .filter_map(|key| flat.remove(*key).map(|value| (key.to_string(), value)))
+            .collect();
+        Value::Object(section)
+    };
+
+    let general = take(&["auto_launch", "minimize_to_tray"]);
+    let appearance = take(&["theme", "latitude", "longitude"]);
+    // `global_shortcuts_enabled` was never read and defaulted to false, so
+    // carrying it over would switch shortcuts off for everyone.
+    take(&["global_shortcuts_enabled"]);
+
+    let mut nested = flat;
+    nested.insert("general".to_string(), general);
+    nested.insert("appearance".to_string(), appearance);
+
+    Ok(Value::Object(nested))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unversioned_settings_migrate_to_sections() {
+        let migrated = migrate_value(
+            json!({
+                "theme": "",
+                "auto_launch": true,
+                "global_shortcuts_enabled": false,
+            }),
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            migrated,
+            json!({
+                "general": { "auto_launch": true },
+                "appearance": { "theme": "system" },
+            })
+        );
+    }
+
+    #[test]
+    fn current_settings_are_left_alone() {
+        let settings = json!({ "general": { "auto_launch": true } });
+        assert_eq!(
+            migrate_value(settings.clone(), CURRENT_VERSION).unwrap(),
+            settings
+        );
+    }
+}
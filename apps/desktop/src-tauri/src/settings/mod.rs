@@ -0,0 +1,803 @@
+use opensunsama_core::schedule::work_calendar::WorkWeek;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::clock::ClockState;
+use crate::integrations::time_export::TimeExportProvider;
+use crate::window_effects::{self, TitleBar, WindowEffect};
+use crate::{
+    data_dir, http, lan_sync, rituals, shortcuts, telemetry, theme, windows, zoom,
+};
+
+pub mod migrations;
+pub mod secrets;
+pub mod transfer;
+
+pub use opensunsama_core::schedule::work_calendar::DayHours;
+
+pub const SETTINGS_STORE: &str = "settings.json";
+pub const SETTINGS_KEY: &str = "settings";
+/// Per-field modification times (`section.field` -> Unix ms), used for
+/// last-writer-wins settings sync
+pub const MODIFIED_KEY: &str = "modified";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneralSettings {
+    pub auto_launch: bool,
+    pub minimize_to_tray: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceSettings {
+    /// "system", "light", "dark" or "auto" (sunrise/sunset)
+    pub theme: String,
+    /// Location for the "auto" theme; looked up from the IP address when unset
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Webview zoom factor, 1.0 being actual size
+    pub zoom: f64,
+    /// Open the floating timer widget when a focus session starts
+    pub timer_widget_on_focus: bool,
+    /// Let clicks pass through the timer widget to the windows below
+    pub timer_widget_click_through: bool,
+    /// Native backdrop behind the main window
+    pub window_effect: WindowEffect,
+    pub title_bar: TitleBar,
+}
+
+impl Default for AppearanceSettings {
+    fn default() -> Self {
+        Self {
+            theme: "system".to_string(),
+            latitude: None,
+            longitude: None,
+            zoom: 1.0,
+            timer_widget_on_focus: true,
+            timer_widget_click_through: false,
+            window_effect: WindowEffect::None,
+            title_bar: TitleBar::Default,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    pub enabled: bool,
+    /// Accelerators chosen by the user, by shortcut id; missing ids use the
+    /// default binding
+    pub bindings: BTreeMap<String, String>,
+    /// Ids of shortcuts the user switched off individually
+    pub disabled: BTreeSet<String>,
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bindings: BTreeMap::new(),
+            disabled: BTreeSet::new(),
+        }
+    }
+}
+
+impl ShortcutSettings {
+    /// Whether the shortcut `id` should be registered
+    pub fn is_active(&self, id: &str) -> bool {
+        self.enabled && !self.disabled.contains(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuietHoursMode {
+    /// Hold notifications and deliver them when quiet hours end
+    #[default]
+    Queue,
+    Drop,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub quiet_hours: bool,
+    /// Local time quiet hours start, `HH:MM`
+    pub quiet_start: String,
+    /// Local time quiet hours end; earlier than the start to span midnight
+    pub quiet_end: String,
+    pub quiet_hours_mode: QuietHoursMode,
+    /// Hold non-urgent notifications while the OS is in Do Not Disturb
+    pub defer_during_system_dnd: bool,
+    /// Notify before calendar events start
+    pub meeting_alerts: bool,
+    /// How many minutes before a meeting to notify
+    pub meeting_lead_minutes: u32,
+    /// Notify about task blocks that ended without the task being started
+    pub overdue_alerts: bool,
+    /// Notify when a channel reaches 80% and 100% of its weekly budget
+    pub budget_alerts: bool,
+    /// Sound by notification category (`reminder`, `timer`, `meeting`, ...):
+    /// "default", "none" or a name from `list_notification_sounds`. Missing
+    /// categories use the default sound.
+    pub sounds: BTreeMap<String, String>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quiet_hours: false,
+            quiet_start: "22:00".to_string(),
+            quiet_end: "07:00".to_string(),
+            quiet_hours_mode: QuietHoursMode::default(),
+            defer_during_system_dnd: true,
+            meeting_alerts: true,
+            meeting_lead_minutes: 5,
+            overdue_alerts: true,
+            budget_alerts: true,
+            sounds: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncSettings {
+    pub enabled: bool,
+    /// Sync theme and shortcut preferences across devices
+    pub settings_sync: bool,
+    /// Self-hosted backend; `None` uses the hosted service
+    pub server_url: Option<String>,
+    /// Find paired devices on the local network and sync with them directly
+    pub lan_sync: bool,
+    /// Attachment upload limit in KiB per second; `None` is unlimited
+    pub upload_limit_kib: Option<u32>,
+    /// Attachment download limit in KiB per second; `None` is unlimited
+    pub download_limit_kib: Option<u32>,
+    /// Sync less often on battery power and metered connections
+    pub power_saving: bool,
+    /// On battery at or below this charge (percent), background sync stops
+    pub pause_below_battery_percent: u8,
+    /// Hold attachment transfers while saving power
+    pub defer_transfers: bool,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            settings_sync: false,
+            server_url: None,
+            lan_sync: false,
+            upload_limit_kib: None,
+            download_limit_kib: None,
+            power_saving: true,
+            pause_below_battery_percent: 20,
+            defer_transfers: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Environment variables and OS proxy configuration
+    #[default]
+    System,
+    Manual,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub proxy_mode: ProxyMode,
+    /// e.g. `http://proxy.corp.example:8080` or `socks5://127.0.0.1:1080`
+    pub proxy_url: Option<String>,
+    pub proxy_username: Option<String>,
+    pub proxy_password: Option<String>,
+    /// Comma-separated hosts that bypass the proxy
+    pub no_proxy: Option<String>,
+    /// Overall timeout for native HTTP requests
+    pub request_timeout_secs: u64,
+    /// Export traces and metrics from sync and HTTP to an OTLP collector
+    pub otlp_enabled: bool,
+    /// OTLP/HTTP endpoint, e.g. `http://localhost:4318`
+    pub otlp_endpoint: Option<String>,
+    /// Sent with every export, e.g. an `Authorization` header. Kept in the
+    /// keychain, see `secrets`.
+    pub otlp_headers: BTreeMap<String, String>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            proxy_mode: ProxyMode::default(),
+            proxy_url: None,
+            proxy_username: None,
+            proxy_password: None,
+            no_proxy: None,
+            request_timeout_secs: 30,
+            otlp_enabled: false,
+            otlp_endpoint: None,
+            otlp_headers: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecuritySettings {
+    /// Encrypt the local database with SQLCipher
+    pub encrypt_local_data: bool,
+    /// Require OS authentication (or the app password) to unlock
+    pub app_lock_enabled: bool,
+    /// Lock after this many idle minutes; 0 only locks on demand
+    pub auto_lock_minutes: u32,
+}
+
+impl Default for SecuritySettings {
+    fn default() -> Self {
+        Self {
+            encrypt_local_data: false,
+            app_lock_enabled: false,
+            auto_lock_minutes: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    /// Minimum hours between automatic backups
+    pub interval_hours: u32,
+    /// Number of backups to keep; older ones are deleted
+    pub keep: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+/// A clipboard pattern worth offering as a task. A named `title` capture
+/// group, if present, becomes the task title.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardPattern {
+    pub name: String,
+    pub regex: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    /// Off by default: the watcher reads everything the user copies
+    pub enabled: bool,
+    pub patterns: Vec<ClipboardPattern>,
+    /// Longer clipboard contents are ignored without being matched
+    pub max_length: usize,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        let pattern = |name: &str, regex: &str| ClipboardPattern {
+            name: name.to_string(),
+            regex: regex.to_string(),
+        };
+
+        Self {
+            enabled: false,
+            patterns: vec![
+                pattern(
+                    "GitHub",
+                    r"https://github\.com/(?P<title>[\w.-]+/[\w.-]+/(?:issues|pull)/\d+)",
+                ),
+                pattern(
+                    "GitLab",
+                    r"https://gitlab\.com/(?P<title>[\w./-]+/-/(?:issues|merge_requests)/\d+)",
+                ),
+                pattern("Linear", r"https://linear\.app/[\w-]+/issue/(?P<title>[A-Z]+-\d+)"),
+                pattern("Jira", r"https://[\w-]+\.atlassian\.net/browse/(?P<title>[A-Z]+-\d+)"),
+            ],
+            max_length: 2048,
+        }
+    }
+}
+
+/// What happens to unfinished tasks when the day changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CarryOver {
+    /// Move them to the new day, or the first working day after it
+    #[default]
+    Today,
+    /// Unschedule them
+    Backlog,
+    /// Leave them on the day they were planned for
+    Off,
+}
+
+/// A daily ritual reminder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RitualSettings {
+    pub enabled: bool,
+    /// Local time of day, `HH:MM`
+    pub time: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlanningSettings {
+    pub carryover: CarryOver,
+    /// "Plan your day" reminder
+    pub planning_ritual: RitualSettings,
+    /// "Daily shutdown" reminder
+    pub shutdown_ritual: RitualSettings,
+    /// Saturdays and Sundays are days off: no ritual reminders, nothing
+    /// scheduled
+    pub skip_weekends: bool,
+    /// Days off (`YYYY-MM-DD`) besides those from holiday calendars
+    pub holidays: BTreeSet<String>,
+    /// Weekly review reminder and report
+    pub weekly_review: RitualSettings,
+    /// Day of the weekly review, 1 (Monday) to 7 (Sunday)
+    pub weekly_review_day: u8,
+    /// Local time the working day starts, `HH:MM`; the auto-scheduler only
+    /// places blocks inside working hours
+    pub work_start: String,
+    /// Local time the working day ends, `HH:MM`
+    pub work_end: String,
+    /// Working hours per weekday. When set, weekdays not listed are days
+    /// off and `skip_weekends` is ignored; when empty, every day not skipped
+    /// as a weekend runs from `work_start` to `work_end`.
+    pub weekly_hours: Vec<DayHours>,
+    /// Minutes kept free before and after calendar events when looking for
+    /// free time
+    pub buffer_minutes: u32,
+    /// Minutes kept free between task blocks the auto-scheduler places
+    pub block_gap_minutes: u32,
+}
+
+impl Default for PlanningSettings {
+    fn default() -> Self {
+        let ritual = |time: &str| RitualSettings {
+            enabled: true,
+            time: time.to_string(),
+        };
+
+        Self {
+            carryover: CarryOver::default(),
+            planning_ritual: ritual("08:30"),
+            shutdown_ritual: ritual("17:30"),
+            skip_weekends: true,
+            holidays: BTreeSet::new(),
+            weekly_review: ritual("16:00"),
+            weekly_review_day: 5,
+            work_start: "09:00".to_string(),
+            work_end: "18:00".to_string(),
+            weekly_hours: Vec::new(),
+            buffer_minutes: 0,
+            block_gap_minutes: 0,
+        }
+    }
+}
+
+impl PlanningSettings {
+    /// What the calendar, free/busy and the auto-scheduler work from
+    pub fn work_week(&self) -> WorkWeek {
+        WorkWeek {
+            work_start: self.work_start.clone(),
+            work_end: self.work_end.clone(),
+            weekly_hours: self.weekly_hours.clone(),
+            skip_weekends: self.skip_weekends,
+            holidays: self.holidays.clone(),
+            buffer_minutes: self.buffer_minutes,
+            block_gap_minutes: self.block_gap_minutes,
+        }
+    }
+}
+
+/// Where finished time entries go, matched in order; the first rule that
+/// matches an entry decides
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeExportRule {
+    /// Channel of the task, without the `#`; any channel when unset
+    pub channel: Option<String>,
+    /// Text the task title must contain, ignoring case
+    pub title_contains: Option<String>,
+    /// Toggl project id, Harvest `project_id/task_id` or Jira issue key.
+    /// For Jira an unset target uses the issue key in the task title.
+    pub target: Option<String>,
+    /// Keep matching entries to this device instead of exporting them
+    pub skip: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeExportSettings {
+    pub enabled: bool,
+    pub provider: Option<TimeExportProvider>,
+    pub toggl_workspace_id: Option<u64>,
+    pub harvest_account_id: Option<String>,
+    /// Jira Cloud site, e.g. `https://example.atlassian.net`
+    pub jira_site: Option<String>,
+    /// Account email the Jira API token belongs to
+    pub jira_email: Option<String>,
+    pub rules: Vec<TimeExportRule>,
+    /// Only entries started on or after this date (`YYYY-MM-DD`) are
+    /// exported; unset exports the last week
+    pub since: Option<String>,
+    /// Shorter entries aren't exported
+    pub min_minutes: u32,
+}
+
+impl Default for TimeExportSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: None,
+            toggl_workspace_id: None,
+            harvest_account_id: None,
+            jira_site: None,
+            jira_email: None,
+            rules: Vec::new(),
+            since: None,
+            min_minutes: 1,
+        }
+    }
+}
+
+/// Third-party services the app talks to directly. API tokens live in the
+/// keychain, not here.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntegrationSettings {
+    /// Push finished time entries to a time tracker or Jira worklogs
+    pub time_export: TimeExportSettings,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub general: GeneralSettings,
+    pub appearance: AppearanceSettings,
+    pub shortcuts: ShortcutSettings,
+    pub notifications: NotificationSettings,
+    pub sync: SyncSettings,
+    pub network: NetworkSettings,
+    pub security: SecuritySettings,
+    pub backup: BackupSettings,
+    pub clipboard: ClipboardSettings,
+    pub planning: PlanningSettings,
+    pub integrations: IntegrationSettings,
+    /// Fields this build doesn't know about, kept so they survive a save
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// One field that differs between two settings values
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingChange {
+    pub section: SettingsSection,
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsSection {
+    General,
+    Appearance,
+    Shortcuts,
+    Notifications,
+    Sync,
+    Network,
+    Security,
+    Backup,
+    Clipboard,
+    Planning,
+    Integrations,
+}
+
+impl SettingsSection {
+    pub const ALL: [SettingsSection; 11] = [
+        SettingsSection::General,
+        SettingsSection::Appearance,
+        SettingsSection::Shortcuts,
+        SettingsSection::Notifications,
+        SettingsSection::Sync,
+        SettingsSection::Network,
+        SettingsSection::Security,
+        SettingsSection::Backup,
+        SettingsSection::Clipboard,
+        SettingsSection::Planning,
+        SettingsSection::Integrations,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            SettingsSection::General => "general",
+            SettingsSection::Appearance => "appearance",
+            SettingsSection::Shortcuts => "shortcuts",
+            SettingsSection::Notifications => "notifications",
+            SettingsSection::Sync => "sync",
+            SettingsSection::Network => "network",
+            SettingsSection::Security => "security",
+            SettingsSection::Backup => "backup",
+            SettingsSection::Clipboard => "clipboard",
+            SettingsSection::Planning => "planning",
+            SettingsSection::Integrations => "integrations",
+        }
+    }
+}
+
+impl AppSettings {
+    /// Build settings from stored JSON section by section, so a bad value
+    /// only resets the section it lives in.
+    pub fn from_value(value: Value) -> Self {
+        let Value::Object(mut object) = value else {
+            return Self::default();
+        };
+
+        fn section<T: DeserializeOwned + Default>(object: &mut Map<String, Value>, key: &str) -> T {
+            object
+                .remove(key)
+                .and_then(|value| serde_json::from_value(value).ok())
+                .unwrap_or_default()
+        }
+
+        Self {
+            general: section(&mut object, SettingsSection::General.key()),
+            appearance: section(&mut object, SettingsSection::Appearance.key()),
+            shortcuts: section(&mut object, SettingsSection::Shortcuts.key()),
+            notifications: section(&mut object, SettingsSection::Notifications.key()),
+            sync: section(&mut object, SettingsSection::Sync.key()),
+            network: section(&mut object, SettingsSection::Network.key()),
+            security: section(&mut object, SettingsSection::Security.key()),
+            backup: section(&mut object, SettingsSection::Backup.key()),
+            clipboard: section(&mut object, SettingsSection::Clipboard.key()),
+            planning: section(&mut object, SettingsSection::Planning.key()),
+            integrations: section(&mut object, SettingsSection::Integrations.key()),
+            extra: object,
+        }
+    }
+
+    pub fn section_value(&self, section: SettingsSection) -> Result<Value, String> {
+        let value = match section {
+            SettingsSection::General => serde_json::to_value(&self.general),
+            SettingsSection::Appearance => serde_json::to_value(&self.appearance),
+            SettingsSection::Shortcuts => serde_json::to_value(&self.shortcuts),
+            SettingsSection::Notifications => serde_json::to_value(&self.notifications),
+            SettingsSection::Sync => serde_json::to_value(&self.sync),
+            SettingsSection::Network => serde_json::to_value(&self.network),
+            SettingsSection::Security => serde_json::to_value(&self.security),
+            SettingsSection::Backup => serde_json::to_value(&self.backup),
+            SettingsSection::Clipboard => serde_json::to_value(&self.clipboard),
+            SettingsSection::Planning => serde_json::to_value(&self.planning),
+            SettingsSection::Integrations => serde_json::to_value(&self.integrations),
+        };
+        value.map_err(|e| format!("Failed to serialize settings: {}", e))
+    }
+
+    /// Merge `patch` into one section. Only the fields present in the patch
+    /// change; the merged result must still deserialize into the section type.
+    pub fn apply_patch(&mut self, section: SettingsSection, patch: Value) -> Result<(), String> {
+        let Value::Object(patch) = patch else {
+            return Err("Settings patch must be an object".to_string());
+        };
+
+        let mut merged = self.section_value(section)?;
+        if let Some(fields) = merged.as_object_mut() {
+            fields.extend(patch);
+        }
+
+        match section {
+            SettingsSection::General => self.general = parse_section(merged, section)?,
+            SettingsSection::Appearance => self.appearance = parse_section(merged, section)?,
+            SettingsSection::Shortcuts => self.shortcuts = parse_section(merged, section)?,
+            SettingsSection::Notifications => self.notifications = parse_section(merged, section)?,
+            SettingsSection::Sync => self.sync = parse_section(merged, section)?,
+            SettingsSection::Network => self.network = parse_section(merged, section)?,
+            SettingsSection::Security => self.security = parse_section(merged, section)?,
+            SettingsSection::Backup => self.backup = parse_section(merged, section)?,
+            SettingsSection::Clipboard => self.clipboard = parse_section(merged, section)?,
+            SettingsSection::Planning => self.planning = parse_section(merged, section)?,
+            SettingsSection::Integrations => self.integrations = parse_section(merged, section)?,
+        }
+
+        Ok(())
+    }
+
+    /// Copy fields that only dedicated commands may change (because changing
+    /// them has side effects beyond the store) from `current`
+    pub fn preserve_managed(&mut self, current: &AppSettings) {
+        self.security.encrypt_local_data = current.security.encrypt_local_data;
+    }
+
+    /// Field-level differences from `self` to `other`
+    pub fn diff(&self, other: &AppSettings) -> Result<Vec<SettingChange>, String> {
+        let mut changes = Vec::new();
+        for section in SettingsSection::ALL {
+            let before = self.section_value(section)?;
+            let after = other.section_value(section)?;
+            let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+                continue;
+            };
+
+            for (field, to) in after {
+                let from = before.get(field).cloned().unwrap_or(Value::Null);
+                if &from != to {
+                    changes.push(SettingChange {
+                        section,
+                        field: field.clone(),
+                        from,
+                        to: to.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+/// Key used for a field in the modification-time map
+pub fn field_key(section: SettingsSection, field: &str) -> String {
+    format!("{}.{}", section.key(), field)
+}
+
+fn parse_section<T: DeserializeOwned>(value: Value, section: SettingsSection) -> Result<T, String> {
+    serde_json::from_value(value).map_err(|e| format!("Invalid {} settings: {}", section.key(), e))
+}
+
+/// Push changed settings into the running subsystems
+pub fn apply(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    theme::apply(app, &settings.appearance.theme);
+    zoom::apply(app, settings.appearance.zoom);
+    windows::set_timer_widget_click_through(app, settings.appearance.timer_widget_click_through);
+    if let Err(e) = window_effects::apply(app, &settings.appearance) {
+        tracing::warn!("Window effect not applied: {}", e);
+    }
+    shortcuts::reconfigure(app, &settings.shortcuts);
+    lan_sync::reconfigure(app, &settings.sync);
+    rituals::schedule(app, &settings.planning)?;
+    telemetry::reconfigure(&settings.network);
+    http::reconfigure(app, &settings.network)
+}
+
+/// Read settings from the store, falling back to defaults per section
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Result<AppSettings, String> {
+    let store = app
+        .store(data_dir::store_path(app, SETTINGS_STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+
+    Ok(stored(&store))
+}
+
+/// The stored settings with their secrets from the keychain
+fn stored<R: Runtime>(store: &Store<R>) -> AppSettings {
+    let mut value = store.get(SETTINGS_KEY).unwrap_or(Value::Null);
+    secrets::fill(&mut value);
+    AppSettings::from_value(value)
+}
+
+/// Write settings to the store and flush to disk, stamping every changed
+/// field with the current time
+pub fn save<R: Runtime>(app: &AppHandle<R>, settings: &AppSettings) -> Result<(), String> {
+    let now = app.state::<ClockState>().now_millis();
+    save_with_modified(app, settings, |_| now)
+}
+
+/// Like `save`, but `modified_at` decides the timestamp for each changed
+/// field key. Sync uses this to keep remote timestamps so pulled changes
+/// aren't echoed back.
+pub fn save_with_modified<R, F>(app: &AppHandle<R>, settings: &AppSettings, modified_at: F) -> Result<(), String>
+where
+    R: Runtime,
+    F: Fn(&str) -> i64,
+{
+    let store = app
+        .store(data_dir::store_path(app, SETTINGS_STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+
+    let before = stored(&store);
+    let mut modified = match store.get(MODIFIED_KEY) {
+        Some(Value::Object(modified)) => modified,
+        _ => Map::new(),
+    };
+    for change in before.diff(settings)? {
+        let key = field_key(change.section, &change.field);
+        let at = modified_at(&key);
+        modified.insert(key, at.into());
+    }
+
+    let mut value =
+        serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    secrets::take(&mut value)?;
+
+    store.set(SETTINGS_KEY, value);
+    store.set(MODIFIED_KEY, Value::Object(modified));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+/// When each field was last changed on this device (Unix ms)
+pub fn modified_times<R: Runtime>(app: &AppHandle<R>) -> Result<Map<String, Value>, String> {
+    let store = app
+        .store(data_dir::store_path(app, SETTINGS_STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+
+    Ok(match store.get(MODIFIED_KEY) {
+        Some(Value::Object(modified)) => modified,
+        _ => Map::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestApp;
+
+    #[test]
+    fn missing_settings_load_as_defaults() {
+        let app = TestApp::new();
+        let settings = load(app.handle()).unwrap();
+        assert_eq!(settings.appearance, AppearanceSettings::default());
+        assert!(modified_times(app.handle()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn saving_stamps_only_changed_fields() {
+        let app = TestApp::new();
+        app.clock().set(1_000);
+        let mut settings = load(app.handle()).unwrap();
+        settings.general.auto_launch = true;
+        save(app.handle(), &settings).unwrap();
+
+        app.clock().advance(500);
+        settings.appearance.zoom = 1.25;
+        save(app.handle(), &settings).unwrap();
+
+        let loaded = load(app.handle()).unwrap();
+        assert!(loaded.general.auto_launch);
+        assert_eq!(loaded.appearance.zoom, 1.25);
+        let modified = modified_times(app.handle()).unwrap();
+        assert_eq!(modified.len(), 2);
+        assert_eq!(modified["general.auto_launch"], 1_000);
+        assert_eq!(modified["appearance.zoom"], 1_500);
+    }
+
+    #[test]
+    fn unknown_sections_survive_a_save() {
+        let app = TestApp::new();
+        let mut settings = AppSettings::from_value(serde_json::json!({
+            "future": { "enabled": true },
+        }));
+        settings.general.minimize_to_tray = true;
+        save(app.handle(), &settings).unwrap();
+
+        let loaded = load(app.handle()).unwrap();
+        assert_eq!(
+            loaded.extra["future"],
+            serde_json::json!({ "enabled": true })
+        );
+        assert!(app.path().join(SETTINGS_STORE).exists());
+    }
+}
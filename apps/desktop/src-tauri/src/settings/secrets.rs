@@ -0,0 +1,107 @@
+//! Settings fields kept in the OS keychain rather than the settings store.
+//! They stay on the settings structs, so the webview reads and patches them
+//! like any other field; only the stored JSON goes without them. Values
+//! from before a field became secret are read from the store until the
+//! next save moves them.
+
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use super::SettingsSection;
+use crate::keychain;
+
+/// Fields kept in the keychain. Exports leave them out too, and importing
+/// keeps the current values, so secrets stay on the machine they were
+/// entered on.
+pub const SECRET_FIELDS: &[(SettingsSection, &str)] = &[
+    (SettingsSection::Network, "proxy_password"),
+    // Usually carries an Authorization header
+    (SettingsSection::Network, "otlp_headers"),
+];
+
+const ACCOUNT_PREFIX: &str = "settings:";
+
+/// What the keychain holds per account, as JSON, so settings loads don't
+/// go to the keychain every time
+static CACHE: Mutex<BTreeMap<String, Option<String>>> = Mutex::new(BTreeMap::new());
+
+/// Put the keychain's values into stored settings JSON
+pub fn fill(settings: &mut Value) {
+    let Some(object) = settings.as_object_mut() else {
+        return;
+    };
+    for (section, field) in SECRET_FIELDS {
+        let Some(stored) = read(&account(*section, field)) else {
+            continue;
+        };
+        let Ok(value) = serde_json::from_str::<Value>(&stored) else {
+            continue;
+        };
+        let section = object
+            .entry(section.key())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Some(section) = section.as_object_mut() {
+            section.insert(field.to_string(), value);
+        }
+    }
+}
+
+/// Take the secret fields out of settings JSON about to be stored, saving
+/// changed ones to the keychain
+pub fn take(settings: &mut Value) -> Result<(), String> {
+    let Some(object) = settings.as_object_mut() else {
+        return Ok(());
+    };
+    for (section, field) in SECRET_FIELDS {
+        let value = object
+            .get_mut(section.key())
+            .and_then(Value::as_object_mut)
+            .and_then(|section| section.remove(*field))
+            .filter(|value| !is_empty(value))
+            .map(|value| value.to_string());
+        write(&account(*section, field), value)?;
+    }
+    Ok(())
+}
+
+fn read(account: &str) -> Option<String> {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.get(account) {
+        return cached.clone();
+    }
+    let value = keychain::get(account).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read {} from the keychain: {}", account, e);
+        None
+    });
+    cache.insert(account.to_string(), value.clone());
+    value
+}
+
+fn write(account: &str, value: Option<String>) -> Result<(), String> {
+    if read(account) == value {
+        return Ok(());
+    }
+    match &value {
+        Some(value) => keychain::set(account, value)?,
+        None => keychain::delete(account)?,
+    }
+    CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(account.to_string(), value);
+    Ok(())
+}
+
+fn account(section: SettingsSection, field: &str) -> String {
+    format!("{}{}.{}", ACCOUNT_PREFIX, section.key(), field)
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(value) => value.is_empty(),
+        Value::Object(value) => value.is_empty(),
+        _ => false,
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use super::migrations::{self, CURRENT_VERSION};
+use super::secrets::SECRET_FIELDS;
+use super::{AppSettings, SettingsSection};
+
+const EXPORT_FORMAT: &str = "open-sunsama-settings";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingsExport {
+    format: String,
+    version: u64,
+    settings: Value,
+}
+
+/// Write settings to `path` as JSON, without secrets
+pub fn export_to_file(settings: &AppSettings, path: &Path) -> Result<(), String> {
+    let json = export_json(settings)?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+/// Settings in the export file format, without secrets
+pub fn export_json(settings: &AppSettings) -> Result<String, String> {
+    let mut value =
+        serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    if let Some(object) = value.as_object_mut() {
+        for (section, field) in SECRET_FIELDS {
+            if let Some(section) = object.get_mut(section.key()).and_then(Value::as_object_mut) {
+                section.remove(*field);
+            }
+        }
+    }
+
+    let export = SettingsExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: CURRENT_VERSION,
+        settings: value,
+    };
+    serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize settings: {}", e))
+}
+
+/// Read an export file and overlay it on `current`. Exports from older
+/// versions are migrated first; every section is validated before anything
+/// is returned.
+pub fn read_import(current: &AppSettings, path: &Path) -> Result<AppSettings, String> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read settings file: {}", e))?;
+    parse_import(current, &json)
+}
+
+/// Like `read_import`, for export JSON already in memory
+pub fn parse_import(current: &AppSettings, json: &str) -> Result<AppSettings, String> {
+    let export: SettingsExport =
+        serde_json::from_str(json).map_err(|e| format!("Not a settings export: {}", e))?;
+
+    if export.format != EXPORT_FORMAT {
+        return Err(format!("Unsupported settings format '{}'", export.format));
+    }
+    if export.version > CURRENT_VERSION {
+        return Err(format!(
+            "Settings were exported by a newer version of Open Sunsama (schema {})",
+            export.version
+        ));
+    }
+
+    let settings = migrations::migrate_value(export.settings, export.version)?;
+    let Value::Object(sections) = settings else {
+        return Err("Settings export is not an object".to_string());
+    };
+
+    let mut imported = current.clone();
+    for (key, patch) in sections {
+        // Unknown top-level keys are leftovers this build doesn't manage
+        let Ok(section) = serde_json::from_value::<SettingsSection>(Value::String(key)) else {
+            continue;
+        };
+        imported.apply_patch(section, patch)?;
+    }
+    imported.preserve_managed(current);
+
+    Ok(imported)
+}
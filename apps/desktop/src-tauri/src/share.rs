@@ -0,0 +1,169 @@
+//! The OS share sheet: NSSharingServicePicker on macOS and the Share UI
+//! (DataTransferManager) on Windows. Linux has no common share target, so
+//! sharing reports an error there and the webview falls back to copying.
+
+use serde::Deserialize;
+use tauri::WebviewWindow;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ShareContent {
+    Text { text: String },
+    File { path: String },
+}
+
+/// Open the share sheet over `window`. Returns once the sheet is shown; what
+/// the user picks is up to the OS. Blocks on Windows while a shared file is
+/// resolved, so call it from a blocking task.
+pub fn share(window: &WebviewWindow, content: ShareContent, title: Option<String>) -> Result<(), String> {
+    if let ShareContent::File { path } = &content {
+        if !std::path::Path::new(path).is_file() {
+            return Err(format!("Nothing to share at {}", path));
+        }
+    }
+    platform::share(window, content, title)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::ShareContent;
+    use objc2::rc::Retained;
+    use objc2::runtime::AnyObject;
+    use objc2_app_kit::{NSSharingServicePicker, NSWindow};
+    use objc2_foundation::{MainThreadMarker, NSArray, NSPoint, NSRect, NSRectEdge, NSSize, NSString, NSURL};
+    use std::cell::RefCell;
+    use tauri::WebviewWindow;
+
+    thread_local! {
+        // The picker isn't retained by AppKit while it's on screen
+        static PICKER: RefCell<Option<Retained<NSSharingServicePicker>>> = const { RefCell::new(None) };
+    }
+
+    pub fn share(window: &WebviewWindow, content: ShareContent, _title: Option<String>) -> Result<(), String> {
+        let ns_window = window
+            .ns_window()
+            .map_err(|e| format!("Failed to get native window: {}", e))? as usize;
+
+        window
+            .run_on_main_thread(move || unsafe {
+                let mtm = MainThreadMarker::new_unchecked();
+                let ns_window = &*(ns_window as *const NSWindow);
+                let Some(view) = ns_window.contentView() else {
+                    return;
+                };
+
+                let item: Retained<AnyObject> = match content {
+                    ShareContent::Text { text } => Retained::cast(NSString::from_str(&text)),
+                    ShareContent::File { path } => {
+                        Retained::cast(NSURL::fileURLWithPath(&NSString::from_str(&path)))
+                    }
+                };
+                let items = NSArray::from_vec(vec![item]);
+                let picker = NSSharingServicePicker::initWithItems(mtm.alloc(), &items);
+
+                // Anchor to the top centre of the window, below the title bar
+                let bounds = view.bounds();
+                let anchor = NSRect::new(
+                    NSPoint::new(bounds.size.width / 2.0, bounds.size.height - 1.0),
+                    NSSize::new(1.0, 1.0),
+                );
+                picker.showRelativeToRect_ofView_preferredEdge(anchor, &view, NSRectEdge::NSMinYEdge);
+                PICKER.with(|current| *current.borrow_mut() = Some(picker));
+            })
+            .map_err(|e| format!("Failed to show share sheet: {}", e))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::ShareContent;
+    use std::cell::RefCell;
+    use tauri::WebviewWindow;
+    use windows::core::{factory, Interface, HSTRING};
+    use windows::ApplicationModel::DataTransfer::{DataRequestedEventArgs, DataTransferManager};
+    use windows::Foundation::Collections::IIterable;
+    use windows::Foundation::{EventRegistrationToken, TypedEventHandler};
+    use windows::Storage::{IStorageItem, StorageFile};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::IDataTransferManagerInterop;
+
+    const DEFAULT_TITLE: &str = "Open Sunsama";
+
+    thread_local! {
+        // One DataRequested handler per window; each share replaces the last
+        static HANDLER: RefCell<Option<(DataTransferManager, EventRegistrationToken)>> = const { RefCell::new(None) };
+    }
+
+    enum Payload {
+        Text(HSTRING),
+        File(StorageFile),
+    }
+
+    pub fn share(window: &WebviewWindow, content: ShareContent, title: Option<String>) -> Result<(), String> {
+        let hwnd = window
+            .hwnd()
+            .map_err(|e| format!("Failed to get native window: {}", e))?
+            .0 as isize;
+
+        let payload = match content {
+            ShareContent::Text { text } => Payload::Text(HSTRING::from(text)),
+            ShareContent::File { path } => StorageFile::GetFileFromPathAsync(&HSTRING::from(path))
+                .and_then(|op| op.get())
+                .map(Payload::File)
+                .map_err(|e| format!("Failed to open file for sharing: {}", e))?,
+        };
+        let title = HSTRING::from(title.unwrap_or_else(|| DEFAULT_TITLE.to_string()));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        window
+            .run_on_main_thread(move || {
+                let _ = tx.send(show(HWND(hwnd as _), payload, title));
+            })
+            .map_err(|e| format!("Failed to show share sheet: {}", e))?;
+
+        rx.recv()
+            .map_err(|_| "Share sheet was not shown".to_string())?
+            .map_err(|e| format!("Failed to show share sheet: {}", e))
+    }
+
+    fn show(hwnd: HWND, payload: Payload, title: HSTRING) -> windows::core::Result<()> {
+        let interop = factory::<DataTransferManager, IDataTransferManagerInterop>()?;
+        let manager: DataTransferManager = unsafe { interop.GetForWindow(hwnd)? };
+
+        let handler = TypedEventHandler::new(
+            move |_, args: &Option<DataRequestedEventArgs>| {
+                let Some(args) = args else {
+                    return Ok(());
+                };
+                let data = args.Request()?.Data()?;
+                data.Properties()?.SetTitle(&title)?;
+                match &payload {
+                    Payload::Text(text) => data.SetText(text),
+                    Payload::File(file) => {
+                        let items: Vec<Option<IStorageItem>> = vec![Some(file.cast()?)];
+                        data.SetStorageItemsReadOnly(&IIterable::try_from(items)?)
+                    }
+                }
+            },
+        );
+        let token = manager.DataRequested(&handler)?;
+
+        HANDLER.with(|current| {
+            if let Some((manager, token)) = current.borrow_mut().replace((manager, token)) {
+                let _ = manager.RemoveDataRequested(token);
+            }
+        });
+
+        unsafe { interop.ShowShareUIForWindow(hwnd) }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::ShareContent;
+    use tauri::WebviewWindow;
+
+    pub fn share(_window: &WebviewWindow, _content: ShareContent, _title: Option<String>) -> Result<(), String> {
+        Err("Sharing isn't available on this platform".to_string())
+    }
+}
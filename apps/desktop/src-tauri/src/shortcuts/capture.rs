@@ -0,0 +1,231 @@
+//! Support for the "record shortcut" field in settings: checking a
+//! candidate accelerator for conflicts, and capturing the next key
+//! combination pressed anywhere (the webview never sees combinations the OS
+//! or our own global shortcuts swallow).
+
+use device_query::{DeviceQuery, DeviceState, Keycode};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use super::ShortcutState;
+use crate::menu;
+
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+const CAPTURE_POLL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictKind {
+    /// Another Open Sunsama global shortcut
+    App,
+    /// An item in the app menu
+    Menu,
+    /// Taken by the OS or another application
+    System,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutConflict {
+    pub kind: ConflictKind,
+    /// What holds the shortcut; empty when the OS doesn't say
+    pub holder: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutCheck {
+    /// The accelerator in canonical form
+    pub accelerator: String,
+    pub available: bool,
+    pub conflict: Option<ShortcutConflict>,
+}
+
+/// Whether a capture is running, and whether it's been cancelled
+#[derive(Default)]
+pub struct CaptureState {
+    active: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+/// Check whether `accelerator` could be used as a global shortcut. Conflicts
+/// with our own bindings are found by comparison; system conflicts by
+/// registering it for a moment, which is the only way the OS tells. `skip`
+/// is the id of the shortcut being edited, whose current binding isn't a
+/// conflict.
+pub fn check(app: &AppHandle, accelerator: &str, skip: Option<&str>) -> Result<ShortcutCheck, String> {
+    let shortcut: Shortcut = accelerator
+        .parse()
+        .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))?;
+    let canonical = shortcut.into_string();
+    let same = |other: &str| other.parse::<Shortcut>().is_ok_and(|other| other == shortcut);
+
+    let conflict = if let Some((def, _)) = super::bindings(app)
+        .into_iter()
+        .find(|(def, accelerator)| Some(def.id) != skip && same(accelerator))
+    {
+        Some(ShortcutConflict {
+            kind: ConflictKind::App,
+            holder: def.description.to_string(),
+        })
+    } else if let Some((label, _)) = menu::accelerators().into_iter().find(|(_, other)| same(other)) {
+        Some(ShortcutConflict {
+            kind: ConflictKind::Menu,
+            holder: label.to_string(),
+        })
+    } else if app.global_shortcut().is_registered(shortcut) {
+        // Ours, bound to the shortcut being edited
+        None
+    } else {
+        match app.global_shortcut().register(shortcut) {
+            Ok(()) => {
+                let _ = app.global_shortcut().unregister(shortcut);
+                None
+            }
+            Err(e) => Some(ShortcutConflict {
+                kind: ConflictKind::System,
+                holder: e.to_string(),
+            }),
+        }
+    };
+
+    Ok(ShortcutCheck {
+        accelerator: canonical,
+        available: conflict.is_none(),
+        conflict,
+    })
+}
+
+/// Wait for the next key combination with at least one modifier and return
+/// it as an accelerator, or `None` on Escape, timeout or cancel. Our global
+/// shortcuts are paused meanwhile so they don't fire. Emits
+/// `shortcut-captured` with the result.
+pub async fn capture(app: &AppHandle) -> Result<Option<String>, String> {
+    if super::is_wayland() {
+        return Err("Recording shortcuts isn't supported on Wayland; type the shortcut instead".to_string());
+    }
+
+    let state = app.state::<CaptureState>();
+    if state.active.swap(true, Ordering::SeqCst) {
+        return Err("Already recording a shortcut".to_string());
+    }
+    state.cancelled.store(false, Ordering::SeqCst);
+
+    let paused = pause(app);
+    let app_handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<CaptureState>();
+        poll_combination(&state.cancelled)
+    })
+    .await
+    .map_err(|e| format!("Failed to record shortcut: {}", e));
+    resume(app, &paused);
+    state.active.store(false, Ordering::SeqCst);
+
+    let accelerator = result?;
+    let _ = app.emit("shortcut-captured", &accelerator);
+    Ok(accelerator)
+}
+
+pub fn cancel(app: &AppHandle) {
+    app.state::<CaptureState>().cancelled.store(true, Ordering::SeqCst);
+}
+
+/// Unregister our native shortcuts, returning what to restore
+fn pause(app: &AppHandle) -> Vec<Shortcut> {
+    let registered = app
+        .state::<ShortcutState>()
+        .status()
+        .map(|status| status.registered)
+        .unwrap_or_default();
+
+    super::bindings(app)
+        .into_iter()
+        .filter(|(def, _)| registered.iter().any(|id| id == def.id))
+        .filter_map(|(_, accelerator)| accelerator.parse::<Shortcut>().ok())
+        .filter(|shortcut| app.global_shortcut().unregister(*shortcut).is_ok())
+        .collect()
+}
+
+fn resume(app: &AppHandle, paused: &[Shortcut]) {
+    for shortcut in paused {
+        let _ = app.global_shortcut().register(*shortcut);
+    }
+}
+
+fn poll_combination(cancelled: &AtomicBool) -> Option<String> {
+    let device = DeviceState::new();
+    let started = Instant::now();
+
+    // Let go of whatever was held when recording started (e.g. the click)
+    while !device.get_keys().is_empty() && started.elapsed() < CAPTURE_TIMEOUT {
+        std::thread::sleep(CAPTURE_POLL);
+    }
+
+    while started.elapsed() < CAPTURE_TIMEOUT && !cancelled.load(Ordering::SeqCst) {
+        let keys = device.get_keys();
+        if keys.contains(&Keycode::Escape) && keys.len() == 1 {
+            return None;
+        }
+        if let Some(accelerator) = accelerator_for(&keys) {
+            return Some(accelerator);
+        }
+        std::thread::sleep(CAPTURE_POLL);
+    }
+    None
+}
+
+/// Modifiers plus the first non-modifier key, once both are held
+fn accelerator_for(keys: &[Keycode]) -> Option<String> {
+    let held = |candidates: &[Keycode]| keys.iter().any(|key| candidates.contains(key));
+    let mut parts = Vec::new();
+    if held(&[Keycode::LMeta, Keycode::RMeta, Keycode::Command, Keycode::RCommand]) {
+        parts.push("Super".to_string());
+    }
+    if held(&[Keycode::LControl, Keycode::RControl]) {
+        parts.push("Ctrl".to_string());
+    }
+    if held(&[Keycode::LAlt, Keycode::RAlt, Keycode::LOption, Keycode::ROption]) {
+        parts.push("Alt".to_string());
+    }
+    if held(&[Keycode::LShift, Keycode::RShift]) {
+        parts.push("Shift".to_string());
+    }
+    if parts.is_empty() {
+        return None;
+    }
+
+    let key = keys.iter().find_map(key_name)?;
+    parts.push(key);
+    let accelerator = parts.join("+");
+    accelerator.parse::<Shortcut>().ok()?;
+    Some(accelerator)
+}
+
+/// The accelerator name of a non-modifier key
+fn key_name(key: &Keycode) -> Option<String> {
+    use Keycode::*;
+    let name = match key {
+        LMeta | RMeta | Command | RCommand | LControl | RControl | LAlt | RAlt | LOption | ROption
+        | LShift | RShift => return None,
+        Key0 => "0",
+        Key1 => "1",
+        Key2 => "2",
+        Key3 => "3",
+        Key4 => "4",
+        Key5 => "5",
+        Key6 => "6",
+        Key7 => "7",
+        Key8 => "8",
+        Key9 => "9",
+        Grave => "Backquote",
+        LeftBracket => "BracketLeft",
+        RightBracket => "BracketRight",
+        Dot => "Period",
+        _ => return Some(format!("{:?}", key)),
+    };
+    Some(name.to_string())
+}
@@ -0,0 +1,394 @@
+//! Global shortcuts. On macOS, Windows and X11 they go through the
+//! global-shortcut plugin. Most Wayland compositors don't let apps grab keys,
+//! so there the XDG GlobalShortcuts portal is tried first, which asks the
+//! user to confirm the bindings once. `get_shortcut_support_status` reports
+//! which path is active and what failed.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tokio::sync::oneshot;
+
+use crate::settings::ShortcutSettings;
+use crate::{quick_complete, screenshot, settings, timer};
+
+pub mod capture;
+
+pub struct ShortcutDef {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub accelerator: &'static str,
+}
+
+pub const SHORTCUTS: &[ShortcutDef] = &[
+    ShortcutDef {
+        id: "toggle-window",
+        description: "Show or hide Open Sunsama",
+        accelerator: "Super+Shift+O",
+    },
+    ShortcutDef {
+        id: "new-task",
+        description: "Add a task",
+        accelerator: "Super+Shift+T",
+    },
+    ShortcutDef {
+        id: "focus-mode",
+        description: "Start focus mode",
+        accelerator: "Super+Shift+F",
+    },
+    ShortcutDef {
+        id: "screenshot",
+        description: "Capture a screenshot into attachments",
+        accelerator: "Super+Shift+X",
+    },
+    ShortcutDef {
+        id: "toggle-timer",
+        description: "Start or stop the timer on the current task",
+        accelerator: "Super+Shift+Space",
+    },
+    ShortcutDef {
+        id: "complete-task",
+        description: "Complete the current task",
+        accelerator: "Super+Shift+D",
+    },
+];
+
+impl ShortcutDef {
+    /// The user's binding for this shortcut, or the default
+    pub fn accelerator_in<'a>(&'a self, settings: &'a ShortcutSettings) -> &'a str {
+        settings
+            .bindings
+            .get(self.id)
+            .map(String::as_str)
+            .unwrap_or(self.accelerator)
+    }
+}
+
+/// Every active shortcut with its effective accelerator; none when global
+/// shortcuts are switched off
+pub fn bindings(app: &AppHandle) -> Vec<(&'static ShortcutDef, String)> {
+    let settings = settings::load(app).map(|s| s.shortcuts).unwrap_or_default();
+    SHORTCUTS
+        .iter()
+        .filter(|def| settings.is_active(def.id))
+        .map(|def| (def, def.accelerator_in(&settings).to_string()))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShortcutBackend {
+    /// Key grabs through the global-shortcut plugin
+    Native,
+    /// The XDG GlobalShortcuts desktop portal (Wayland)
+    Portal,
+    /// Registered natively on Wayland, where they only fire while an
+    /// XWayland window has focus
+    Limited,
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutFailure {
+    pub id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutSupportStatus {
+    pub backend: ShortcutBackend,
+    pub wayland: bool,
+    pub registered: Vec<String>,
+    pub failed: Vec<ShortcutFailure>,
+    /// Explanation for the settings UI when shortcuts are limited
+    pub message: Option<String>,
+}
+
+pub struct ShortcutState {
+    status: Mutex<ShortcutSupportStatus>,
+    /// The settings the current registration was made from
+    applied: Mutex<Option<ShortcutSettings>>,
+    /// Ends the portal session, which releases its bindings
+    close_portal: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl Default for ShortcutState {
+    fn default() -> Self {
+        Self {
+            status: Mutex::new(ShortcutSupportStatus {
+                backend: ShortcutBackend::Unavailable,
+                wayland: is_wayland(),
+                registered: Vec::new(),
+                failed: Vec::new(),
+                message: None,
+            }),
+            applied: Mutex::new(None),
+            close_portal: Mutex::new(None),
+        }
+    }
+}
+
+impl ShortcutState {
+    pub fn status(&self) -> Option<ShortcutSupportStatus> {
+        self.status.lock().ok().map(|status| status.clone())
+    }
+
+    fn set(&self, status: ShortcutSupportStatus) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status;
+        }
+    }
+
+    /// Record `settings` as applied; false if they already were
+    fn apply(&self, settings: &ShortcutSettings) -> bool {
+        let Ok(mut applied) = self.applied.lock() else {
+            return false;
+        };
+        if applied.as_ref() == Some(settings) {
+            return false;
+        }
+        *applied = Some(settings.clone());
+        true
+    }
+}
+
+pub fn is_wayland() -> bool {
+    cfg!(target_os = "linux")
+        && (std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session.eq_ignore_ascii_case("wayland"))
+            || std::env::var_os("WAYLAND_DISPLAY").is_some())
+}
+
+/// Register every active shortcut, through the portal on Wayland when it's
+/// available. Never fails: problems end up in the support status, and
+/// `shortcut-status-changed` is emitted once registration settles.
+pub fn register_all(app: &AppHandle) {
+    let settings = settings::load(app).map(|s| s.shortcuts).unwrap_or_default();
+    app.state::<ShortcutState>().apply(&settings);
+    if !settings.enabled {
+        publish(
+            app,
+            ShortcutSupportStatus {
+                backend: ShortcutBackend::Unavailable,
+                wayland: is_wayland(),
+                registered: Vec::new(),
+                failed: Vec::new(),
+                message: Some("Global shortcuts are turned off".to_string()),
+            },
+        );
+        return;
+    }
+
+    if is_wayland() {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let status = match portal::bind(&app).await {
+                Ok(registered) => ShortcutSupportStatus {
+                    backend: ShortcutBackend::Portal,
+                    wayland: true,
+                    registered,
+                    failed: Vec::new(),
+                    message: None,
+                },
+                Err(e) => {
+                    let mut status = register_native(&app);
+                    if status.backend == ShortcutBackend::Native {
+                        status.backend = ShortcutBackend::Limited;
+                    }
+                    status.message = Some(format!(
+                        "Your desktop doesn't offer the global shortcuts portal ({}). Shortcuts only work while Open Sunsama is focused; bind them in your compositor to `opensunsama://` links instead.",
+                        e
+                    ));
+                    status
+                }
+            };
+            publish(&app, status);
+        });
+    } else {
+        let status = register_native(app);
+        publish(app, status);
+    }
+}
+
+/// Re-register after a settings change, if the shortcut settings differ from
+/// what's registered
+pub fn reconfigure(app: &AppHandle, settings: &ShortcutSettings) {
+    let state = app.state::<ShortcutState>();
+    if !state.apply(settings) {
+        return;
+    }
+    let _ = app.global_shortcut().unregister_all();
+    if let Some(close) = state.close_portal.lock().ok().and_then(|mut close| close.take()) {
+        let _ = close.send(());
+    }
+    register_all(app);
+}
+
+/// Run the action bound to a shortcut pressed through the plugin
+pub fn handle(app: &AppHandle, shortcut: &Shortcut) {
+    let pressed = bindings(app)
+        .into_iter()
+        .find(|(_, accelerator)| accelerator.parse::<Shortcut>().is_ok_and(|parsed| &parsed == shortcut));
+    if let Some((def, _)) = pressed {
+        trigger(app, def.id);
+    }
+}
+
+/// Run the action for a shortcut id
+pub fn trigger(app: &AppHandle, id: &str) {
+    match id {
+        "toggle-window" => {
+            if let Some(window) = app.get_webview_window("main") {
+                if window.is_visible().unwrap_or(false) {
+                    let _ = window.hide();
+                } else {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+        }
+        "new-task" => {
+            // Show window and emit event to create new task
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+                let _ = window.emit("quick-add-task", ());
+            }
+        }
+        "focus-mode" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.emit("start-focus-mode", ());
+            }
+        }
+        "screenshot" => {
+            // Capture into the attachment store; the webview links it to the
+            // focused task on `screenshot-captured`
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = screenshot::capture(&app, screenshot::SHORTCUT_MODE, None).await {
+                    let _ = app.emit("screenshot-error", e);
+                }
+            });
+        }
+        "toggle-timer" => timer::toggle_with_notification(app),
+        "complete-task" => quick_complete::complete_with_notification(app),
+        _ => {}
+    }
+}
+
+fn register_native(app: &AppHandle) -> ShortcutSupportStatus {
+    let mut registered = Vec::new();
+    let mut failed = Vec::new();
+
+    for (def, accelerator) in bindings(app) {
+        let result = accelerator
+            .parse::<Shortcut>()
+            .map_err(|e| e.to_string())
+            .and_then(|shortcut| app.global_shortcut().register(shortcut).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => registered.push(def.id.to_string()),
+            Err(error) => failed.push(ShortcutFailure {
+                id: def.id.to_string(),
+                error,
+            }),
+        }
+    }
+
+    let backend = if registered.is_empty() {
+        ShortcutBackend::Unavailable
+    } else {
+        ShortcutBackend::Native
+    };
+    ShortcutSupportStatus {
+        backend,
+        wayland: is_wayland(),
+        registered,
+        failed,
+        message: None,
+    }
+}
+
+fn publish(app: &AppHandle, status: ShortcutSupportStatus) {
+    let _ = app.emit("shortcut-status-changed", &status);
+    app.state::<ShortcutState>().set(status);
+}
+
+#[cfg(target_os = "linux")]
+mod portal {
+    use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
+    use futures_util::StreamExt;
+    use tauri::{AppHandle, Manager};
+    use tokio::sync::oneshot;
+
+    use super::{bindings, ShortcutState};
+
+    /// Bind every shortcut through the portal and start listening for
+    /// activations. Returns the ids the compositor accepted.
+    pub async fn bind(app: &AppHandle) -> Result<Vec<String>, String> {
+        let portal = GlobalShortcuts::new().await.map_err(|e| e.to_string())?;
+        let session = portal.create_session().await.map_err(|e| e.to_string())?;
+
+        let shortcuts: Vec<NewShortcut> = bindings(app)
+            .into_iter()
+            .map(|(def, accelerator)| {
+                NewShortcut::new(def.id, def.description)
+                    .preferred_trigger(portal_trigger(&accelerator).as_deref())
+            })
+            .collect();
+        let bound = portal
+            .bind_shortcuts(&session, &shortcuts, None)
+            .await
+            .and_then(|request| request.response())
+            .map_err(|e| e.to_string())?;
+        let registered = bound.shortcuts().iter().map(|shortcut| shortcut.id().to_string()).collect();
+
+        let (close_tx, mut close_rx) = oneshot::channel();
+        if let Ok(mut close) = app.state::<ShortcutState>().close_portal.lock() {
+            *close = Some(close_tx);
+        }
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Ok(mut activated) = portal.receive_activated().await {
+                loop {
+                    tokio::select! {
+                        event = activated.next() => match event {
+                            Some(event) => super::trigger(&app, event.shortcut_id()),
+                            None => break,
+                        },
+                        _ = &mut close_rx => break,
+                    }
+                }
+            }
+            let _ = session.close().await;
+        });
+
+        Ok(registered)
+    }
+
+    /// "Super+Shift+O" in the shortcuts spec's trigger syntax, "LOGO+SHIFT+o"
+    fn portal_trigger(accelerator: &str) -> Option<String> {
+        let parts: Vec<String> = accelerator
+            .split('+')
+            .map(|part| match part.to_ascii_lowercase().as_str() {
+                "super" | "cmd" | "command" | "meta" => "LOGO".to_string(),
+                "ctrl" | "control" | "cmdorctrl" | "commandorcontrol" => "CTRL".to_string(),
+                "alt" | "option" => "ALT".to_string(),
+                "shift" => "SHIFT".to_string(),
+                key => key.to_string(),
+            })
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("+"))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod portal {
+    use tauri::AppHandle;
+
+    pub async fn bind(_app: &AppHandle) -> Result<Vec<String>, String> {
+        Err("not supported on this platform".to_string())
+    }
+}
@@ -0,0 +1,95 @@
+//! Orderly quit. Every exit (tray or menu Quit, closing the main window, a
+//! termination signal at logout) goes through `RunEvent::ExitRequested`,
+//! which is held off while pending work is finished: the running timer is
+//! stopped so its time is kept, queued changes are sent (bounded by a
+//! timeout, since the network may be gone), and stores are flushed to disk.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, RESTART_EXIT_CODE};
+use tauri_plugin_store::StoreExt;
+
+use crate::settings::SETTINGS_STORE;
+use crate::sync::outbox;
+use crate::{data_dir, timer};
+
+/// How long quitting may wait on the network
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+pub struct ShutdownState {
+    started: AtomicBool,
+    finished: AtomicBool,
+}
+
+/// Called on `RunEvent::ExitRequested`. Returns true if the exit should be
+/// prevented because cleanup has to run first; `app.exit` (or `restart`, for
+/// a requested restart) is called again once it's done.
+pub fn on_exit_requested(app: &AppHandle, code: Option<i32>) -> bool {
+    let state = app.state::<ShutdownState>();
+    if state.finished.load(Ordering::SeqCst) {
+        return false;
+    }
+    if !state.started.swap(true, Ordering::SeqCst) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            run(&app).await;
+            app.state::<ShutdownState>().finished.store(true, Ordering::SeqCst);
+            if code == Some(RESTART_EXIT_CODE) {
+                app.restart();
+            }
+            app.exit(code.unwrap_or(0));
+        });
+    }
+    true
+}
+
+/// Whether an exit is under way, so windows closing now aren't the user's doing
+pub fn is_shutting_down(app: &AppHandle) -> bool {
+    app.state::<ShutdownState>().started.load(Ordering::SeqCst)
+}
+
+async fn run(app: &AppHandle) {
+    if let Err(e) = timer::stop(app) {
+        tracing::warn!("Failed to stop timer on quit: {}", e);
+    }
+
+    match tokio::time::timeout(DRAIN_TIMEOUT, outbox::drain(app)).await {
+        Ok(Ok(())) => {}
+        // Whatever didn't go out stays queued for the next launch
+        Ok(Err(e)) => tracing::warn!("Failed to send queued changes on quit: {}", e),
+        Err(_) => tracing::warn!("Timed out sending queued changes on quit"),
+    }
+
+    let store = data_dir::store_path(app, SETTINGS_STORE)
+        .ok()
+        .and_then(|path| app.store(path).ok());
+    if let Some(store) = store {
+        if let Err(e) = store.save() {
+            tracing::warn!("Failed to flush settings on quit: {}", e);
+        }
+    }
+}
+
+/// Quit cleanly on SIGTERM and SIGHUP, which the session sends at logout
+pub fn watch_signals(app: &AppHandle) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let (Ok(mut term), Ok(mut hup)) = (signal(SignalKind::terminate()), signal(SignalKind::hangup()))
+            else {
+                return;
+            };
+            tokio::select! {
+                _ = term.recv() => {}
+                _ = hup.recv() => {}
+            }
+            app.exit(0);
+        });
+    }
+    #[cfg(not(unix))]
+    let _ = app;
+}
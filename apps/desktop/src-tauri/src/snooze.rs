@@ -0,0 +1,109 @@
+//! Snoozing. A snoozed notification is a row in the `snoozed` table with
+//! its new fire time, so it survives restarts. The reminder scheduler hands
+//! it back to `notifications::notify` once due and emits `snooze-ended`
+//! with it, so the webview can offer the original actions again.
+
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clock::ClockState;
+use crate::db::snoozed::{self, Snoozed};
+use crate::db::Database;
+use crate::notifications::{self, Category, Notice};
+
+const DEFAULT_MINUTES: u32 = 10;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnoozeRequest {
+    /// Snoozing the same id again replaces it; a new id is made when empty
+    pub id: Option<String>,
+    #[serde(default)]
+    pub category: Category,
+    pub title: String,
+    pub body: Option<String>,
+    pub sound: Option<String>,
+    pub task_id: Option<String>,
+    /// Handed back untouched in `snooze-ended`
+    pub data: Option<Value>,
+    /// Minutes from now, 10 by default
+    pub minutes: Option<u32>,
+    /// Unix milliseconds; takes precedence over `minutes`
+    pub until: Option<i64>,
+}
+
+/// Put a notification off. Emits `snoozed-changed`.
+pub fn snooze(app: &AppHandle, request: SnoozeRequest) -> Result<Snoozed, String> {
+    let now = app.state::<ClockState>().now_millis();
+    let fire_at = request.until.unwrap_or_else(|| {
+        now + i64::from(request.minutes.unwrap_or(DEFAULT_MINUTES)) * 60 * 1000
+    });
+    if fire_at <= now {
+        return Err("Snooze time is in the past".to_string());
+    }
+
+    let id = request
+        .id
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let snoozed = app.state::<Database>().with_conn(|conn| {
+        snoozed::upsert(
+            conn,
+            &Snoozed {
+                id: id.clone(),
+                category: request.category.key().to_string(),
+                title: request.title,
+                body: request.body,
+                sound: request.sound,
+                task_id: request.task_id,
+                data: request.data,
+                fire_at,
+                snooze_count: 1,
+                snoozed_at: now,
+            },
+        )?;
+        snoozed::get(conn, &id)
+    })?;
+
+    let _ = app.emit("snoozed-changed", ());
+    snoozed.ok_or_else(|| "Failed to snooze notification".to_string())
+}
+
+/// Every snoozed notification, soonest first
+pub fn list(app: &AppHandle) -> Result<Vec<Snoozed>, String> {
+    app.state::<Database>().with_conn(|conn| snoozed::list(conn))
+}
+
+/// Drop a snoozed notification without delivering it. Emits
+/// `snoozed-changed`.
+pub fn cancel(app: &AppHandle, id: &str) -> Result<(), String> {
+    app.state::<Database>().with_conn(|conn| snoozed::delete(conn, id))?;
+    let _ = app.emit("snoozed-changed", ());
+    Ok(())
+}
+
+/// Deliver snoozed notifications due at `now`, including those that came
+/// due while the app was closed
+pub fn deliver_due(app: &AppHandle, now: i64) -> Result<(), String> {
+    let db = app.state::<Database>();
+    let due = db.with_conn(|conn| snoozed::due(conn, now))?;
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    for snoozed in due {
+        let mut notice = Notice::new(notifications::category(&snoozed.category), &snoozed.title);
+        notice.body = snoozed.body.clone();
+        notice.sound = snoozed.sound.clone();
+        notice.task_id = snoozed.task_id.clone();
+        notice.data = snoozed.data.clone();
+        notifications::notify(app, notice)?;
+
+        db.with_conn(|conn| snoozed::delete(conn, &snoozed.id))?;
+        let _ = app.emit("snooze-ended", &snoozed);
+    }
+
+    let _ = app.emit("snoozed-changed", ());
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Notification sounds. Each notification category can use the platform
+//! default, no sound, one of the built-in system sounds or a custom file
+//! the user imported.
+//!
+//! Notification servers only play sounds they can find by name, so custom
+//! files are copied into `sounds/` in the data directory and registered
+//! where the platform looks: `~/Library/Sounds` on macOS and the fallback
+//! freedesktop sound theme under `$XDG_DATA_HOME/sounds` on Linux. Windows
+//! toasts from unpackaged apps can only play the built-in sounds.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::data_dir;
+use crate::notifications::Category;
+use crate::settings::NotificationSettings;
+
+pub const DEFAULT: &str = "default";
+pub const NONE: &str = "none";
+
+const SOUNDS_DIR: &str = "sounds";
+/// Registered custom sounds get this prefix so they can't shadow system ones
+const CUSTOM_PREFIX: &str = "opensunsama-";
+const MAX_SOUND_BYTES: u64 = 5 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundOption {
+    /// Value to store in the notification settings
+    pub name: String,
+    pub label: String,
+    pub custom: bool,
+}
+
+/// Every sound that can be chosen on this platform
+pub fn list(app: &AppHandle) -> Result<Vec<SoundOption>, String> {
+    let mut sounds = vec![
+        SoundOption {
+            name: DEFAULT.to_string(),
+            label: "Default".to_string(),
+            custom: false,
+        },
+        SoundOption {
+            name: NONE.to_string(),
+            label: "None".to_string(),
+            custom: false,
+        },
+    ];
+    sounds.extend(platform::BUILT_IN.iter().map(|(name, label)| SoundOption {
+        name: name.to_string(),
+        label: label.to_string(),
+        custom: false,
+    }));
+
+    let dir = sounds_dir(app)?;
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("Failed to read sounds: {}", e))?;
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+            sounds.push(SoundOption {
+                name: stem.to_string(),
+                label: stem.trim_start_matches(CUSTOM_PREFIX).to_string(),
+                custom: true,
+            });
+        }
+    }
+
+    Ok(sounds)
+}
+
+/// Validate a sound file, copy it into the data directory and register it
+/// with the platform. Returns the name to store in the settings.
+pub fn import(app: &AppHandle, source: &Path) -> Result<String, String> {
+    let extension = source
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    if !platform::EXTENSIONS.contains(&extension.as_str()) {
+        return Err(if platform::EXTENSIONS.is_empty() {
+            "Custom notification sounds aren't supported on this platform".to_string()
+        } else {
+            format!("Unsupported sound format; use {}", platform::EXTENSIONS.join(", "))
+        });
+    }
+
+    let metadata = std::fs::metadata(source).map_err(|e| format!("Failed to read sound: {}", e))?;
+    if !metadata.is_file() {
+        return Err("Not a sound file".to_string());
+    }
+    if metadata.len() > MAX_SOUND_BYTES {
+        return Err("Sound files must be 5 MB or smaller".to_string());
+    }
+
+    let stem: String = source
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let stem = stem.trim_matches('-');
+    if stem.is_empty() {
+        return Err("Sound file needs a name".to_string());
+    }
+    let name = format!("{}{}", CUSTOM_PREFIX, stem);
+
+    let target = sounds_dir(app)?.join(format!("{}.{}", name, extension));
+    std::fs::copy(source, &target).map_err(|e| format!("Failed to import sound: {}", e))?;
+    platform::register(app, &target)?;
+    Ok(name)
+}
+
+/// Register every imported sound again, for a new install location or a
+/// cleared sound directory
+pub fn register_all(app: &AppHandle) {
+    let Ok(entries) = sounds_dir(app).and_then(|dir| {
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read sounds: {}", e))
+    }) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let _ = platform::register(app, &entry.path());
+    }
+}
+
+/// The platform sound name for a notification, or `None` for silence
+pub fn resolve(config: &NotificationSettings, category: Category, requested: Option<&str>) -> Option<String> {
+    let chosen = requested
+        .or_else(|| config.sounds.get(category.key()).map(String::as_str))
+        .unwrap_or(DEFAULT);
+    match chosen {
+        NONE => None,
+        DEFAULT => Some(platform::DEFAULT_SOUND.to_string()),
+        name => Some(name.to_string()),
+    }
+}
+
+fn sounds_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = data_dir::get(app)?.join(SOUNDS_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create sounds directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Copy `file` to `dir` under the same name
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn copy_into(file: &Path, dir: &Path) -> Result<(), String> {
+    let name = file.file_name().ok_or_else(|| "Not a sound file".to_string())?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to register sound: {}", e))?;
+    std::fs::copy(file, dir.join(name))
+        .map(|_| ())
+        .map_err(|e| format!("Failed to register sound: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use tauri::Manager;
+
+    pub const DEFAULT_SOUND: &str = "default";
+    pub const EXTENSIONS: &[&str] = &["aiff", "aif", "caf", "wav"];
+    pub const BUILT_IN: &[(&str, &str)] = &[
+        ("Basso", "Basso"),
+        ("Blow", "Blow"),
+        ("Bottle", "Bottle"),
+        ("Frog", "Frog"),
+        ("Funk", "Funk"),
+        ("Glass", "Glass"),
+        ("Hero", "Hero"),
+        ("Morse", "Morse"),
+        ("Ping", "Ping"),
+        ("Pop", "Pop"),
+        ("Purr", "Purr"),
+        ("Sosumi", "Sosumi"),
+        ("Submarine", "Submarine"),
+        ("Tink", "Tink"),
+    ];
+
+    pub fn register(app: &AppHandle, file: &Path) -> Result<(), String> {
+        let home = app.path().home_dir().map_err(|e| format!("No home directory: {}", e))?;
+        copy_into(file, &home.join("Library").join("Sounds"))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use tauri::Manager;
+
+    pub const DEFAULT_SOUND: &str = "message-new-instant";
+    pub const EXTENSIONS: &[&str] = &["oga", "ogg", "wav"];
+    pub const BUILT_IN: &[(&str, &str)] = &[
+        ("message-new-instant", "Message"),
+        ("bell", "Bell"),
+        ("complete", "Complete"),
+        ("alarm-clock-elapsed", "Alarm clock"),
+        ("dialog-information", "Information"),
+        ("dialog-warning", "Warning"),
+    ];
+
+    pub fn register(app: &AppHandle, file: &Path) -> Result<(), String> {
+        let data = app.path().data_dir().map_err(|e| format!("No data directory: {}", e))?;
+        copy_into(file, &data.join("sounds").join("freedesktop").join("stereo"))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+mod platform {
+    use super::*;
+
+    pub const DEFAULT_SOUND: &str = "Default";
+    pub const EXTENSIONS: &[&str] = &[];
+    pub const BUILT_IN: &[(&str, &str)] = &[
+        ("IM", "Instant message"),
+        ("Mail", "Mail"),
+        ("Reminder", "Reminder"),
+        ("SMS", "Text message"),
+    ];
+
+    pub fn register(_app: &AppHandle, _file: &Path) -> Result<(), String> {
+        Ok(())
+    }
+}
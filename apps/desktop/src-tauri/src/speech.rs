@@ -0,0 +1,81 @@
+//! Native text-to-speech: AVSpeechSynthesizer on macOS, SAPI / WinRT on
+//! Windows, speech-dispatcher on Linux. The engine isn't thread-safe on every
+//! platform, so it lives on a dedicated thread fed by a channel.
+
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use tts::Tts;
+
+enum SpeechCommand {
+    Speak(String),
+    Stop,
+}
+
+#[derive(Default)]
+pub struct SpeechState {
+    sender: Mutex<Option<Sender<SpeechCommand>>>,
+}
+
+impl SpeechState {
+    /// Speak `text`, interrupting anything already being read
+    pub fn speak(&self, text: String) -> Result<(), String> {
+        self.send(SpeechCommand::Speak(text))
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        self.send(SpeechCommand::Stop)
+    }
+
+    fn send(&self, command: SpeechCommand) -> Result<(), String> {
+        let mut sender = self
+            .sender
+            .lock()
+            .map_err(|_| "Speech engine lock poisoned".to_string())?;
+        if sender.is_none() {
+            *sender = Some(spawn_engine()?);
+        }
+
+        let sent = sender.as_ref().map(|engine| engine.send(command));
+        if let Some(Ok(())) = sent {
+            return Ok(());
+        }
+        // The engine thread died; start a new one on the next call
+        *sender = None;
+        Err("Speech engine stopped unexpectedly".to_string())
+    }
+}
+
+/// Start the engine thread, waiting until the engine has initialized so
+/// setup errors reach the caller
+fn spawn_engine() -> Result<Sender<SpeechCommand>, String> {
+    let (commands, receiver) = mpsc::channel::<SpeechCommand>();
+    let (ready, started) = mpsc::channel::<Result<(), String>>();
+
+    std::thread::Builder::new()
+        .name("speech".to_string())
+        .spawn(move || {
+            let mut tts = match Tts::default() {
+                Ok(tts) => {
+                    let _ = ready.send(Ok(()));
+                    tts
+                }
+                Err(e) => {
+                    let _ = ready.send(Err(format!("Text-to-speech is unavailable: {}", e)));
+                    return;
+                }
+            };
+
+            for command in receiver {
+                let _ = match command {
+                    SpeechCommand::Speak(text) => tts.speak(text, true).map(|_| ()),
+                    SpeechCommand::Stop => tts.stop().map(|_| ()),
+                };
+            }
+        })
+        .map_err(|e| format!("Failed to start speech engine: {}", e))?;
+
+    started
+        .recv()
+        .map_err(|_| "Speech engine failed to start".to_string())??;
+    Ok(commands)
+}
@@ -0,0 +1,303 @@
+//! Subtasks and checklist items: the server's subtasks of a task (the ones
+//! templates and imports create), kept locally in order so they work
+//! offline. Each change is applied locally and queued to the server through
+//! the outbox in the same transaction; the server's list replaces the local
+//! one whenever nothing is left to send. When the estimates change, their
+//! total is queued as the parent task's estimate. Every change emits
+//! `subtasks-changed` with the task's updated list.
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::Api;
+use crate::clock;
+use crate::db::outbox::{self, OutboxEntry};
+use crate::db::subtasks::{self, Subtask};
+use crate::db::Database;
+use crate::sync;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtaskList {
+    pub task_id: String,
+    pub items: Vec<Subtask>,
+    /// Sum of the items' estimates
+    pub estimated_mins: i64,
+    pub completed: usize,
+}
+
+/// Changes to a subtask; unset fields are left alone
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SubtaskChanges {
+    pub title: Option<String>,
+    /// 0 clears the estimate
+    pub estimated_mins: Option<i64>,
+    pub completed: Option<bool>,
+}
+
+pub fn list(app: &AppHandle, task_id: &str) -> Result<SubtaskList, String> {
+    let items = app
+        .state::<Database>()
+        .with_conn(|conn| subtasks::list(conn, task_id))?;
+    Ok(summarize(task_id, items))
+}
+
+/// Take the server's list of `task_id`'s subtasks, unless local changes to
+/// them are still queued, and return the task's list. Emits
+/// `subtasks-changed` when it was replaced.
+pub async fn refresh(app: &AppHandle, task_id: &str) -> Result<SubtaskList, String> {
+    let path = subtasks_path(task_id);
+    let remote: Vec<Value> = Api::new(app)?.get_all(&path).await?;
+    let mut items: Vec<Subtask> = remote
+        .iter()
+        .enumerate()
+        .filter_map(|(position, value)| from_server(task_id, position, value))
+        .collect();
+    items.sort_by_key(|item| item.position);
+    let replaced = app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        if outbox::has_pending(&tx, &path)? {
+            return Ok(false);
+        }
+        subtasks::replace(&tx, task_id, &items)?;
+        tx.commit()?;
+        Ok(true)
+    })?;
+    let list = list(app, task_id)?;
+    if replaced {
+        let _ = app.emit("subtasks-changed", &list);
+    }
+    Ok(list)
+}
+
+/// Add a subtask at the end of `task_id`'s list
+pub fn add(
+    app: &AppHandle,
+    task_id: &str,
+    title: &str,
+    estimated_mins: Option<i64>,
+) -> Result<SubtaskList, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Subtasks need a title".to_string());
+    }
+    let now = clock::now_millis();
+    let mut subtask = Subtask {
+        id: uuid::Uuid::new_v4().to_string(),
+        task_id: task_id.to_string(),
+        title: title.to_string(),
+        position: 0,
+        estimated_mins: estimated_mins.filter(|mins| *mins > 0),
+        completed_at: None,
+        created_at: now,
+        updated_at: now,
+    };
+    let estimated = subtask.estimated_mins.is_some();
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        subtasks::insert(&tx, &mut subtask)?;
+        outbox::insert(
+            &tx,
+            &request(
+                app,
+                "POST",
+                &subtasks_path(task_id),
+                Some(json!({
+                    "id": subtask.id,
+                    "title": subtask.title,
+                    "estimatedMins": subtask.estimated_mins,
+                    "position": subtask.position,
+                })),
+            ),
+        )?;
+        tx.commit()
+    })?;
+    changed(app, task_id, estimated)
+}
+
+pub fn update(app: &AppHandle, id: &str, changes: SubtaskChanges) -> Result<SubtaskList, String> {
+    let db = app.state::<Database>();
+    let mut subtask = db
+        .with_conn(|conn| subtasks::get(conn, id))?
+        .ok_or_else(|| "Subtask not found".to_string())?;
+    let previous_estimate = subtask.estimated_mins;
+    let mut fields = Map::new();
+
+    if let Some(title) = changes.title {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err("Subtasks need a title".to_string());
+        }
+        subtask.title = title.to_string();
+        fields.insert("title".to_string(), json!(subtask.title));
+    }
+    if let Some(estimated_mins) = changes.estimated_mins {
+        subtask.estimated_mins = Some(estimated_mins).filter(|mins| *mins > 0);
+        fields.insert("estimatedMins".to_string(), json!(subtask.estimated_mins));
+    }
+    let now = clock::now_millis();
+    if let Some(completed) = changes.completed {
+        match (completed, subtask.completed_at) {
+            (true, None) => subtask.completed_at = Some(now),
+            (false, _) => subtask.completed_at = None,
+            _ => {}
+        }
+        fields.insert("completed".to_string(), json!(completed));
+    }
+    if fields.is_empty() {
+        return list(app, &subtask.task_id);
+    }
+    subtask.updated_at = now;
+
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        subtasks::update(&tx, &subtask)?;
+        outbox::insert(
+            &tx,
+            &request(
+                app,
+                "PATCH",
+                &subtask_path(&subtask),
+                Some(Value::Object(fields)),
+            ),
+        )?;
+        tx.commit()
+    })?;
+    changed(
+        app,
+        &subtask.task_id,
+        subtask.estimated_mins != previous_estimate,
+    )
+}
+
+pub fn delete(app: &AppHandle, id: &str) -> Result<Option<SubtaskList>, String> {
+    let db = app.state::<Database>();
+    let Some(subtask) = db.with_conn(|conn| subtasks::get(conn, id))? else {
+        return Ok(None);
+    };
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        subtasks::delete(&tx, id)?;
+        subtasks::compact(&tx, &subtask.task_id)?;
+        outbox::insert(&tx, &request(app, "DELETE", &subtask_path(&subtask), None))?;
+        tx.commit()
+    })?;
+    changed(app, &subtask.task_id, subtask.estimated_mins.is_some()).map(Some)
+}
+
+/// Put a task's subtasks in the order of `ids` in one transaction; any
+/// left out keep their relative order after them. Each one that moved has
+/// its new position queued.
+pub fn reorder(app: &AppHandle, task_id: &str, ids: &[String]) -> Result<SubtaskList, String> {
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        let before = subtasks::list(&tx, task_id)?;
+        subtasks::reorder(&tx, task_id, ids)?;
+        for subtask in subtasks::list(&tx, task_id)? {
+            let moved = before
+                .iter()
+                .any(|old| old.id == subtask.id && old.position != subtask.position);
+            if moved {
+                outbox::insert(
+                    &tx,
+                    &request(
+                        app,
+                        "PATCH",
+                        &subtask_path(&subtask),
+                        Some(json!({ "position": subtask.position })),
+                    ),
+                )?;
+            }
+        }
+        tx.commit()
+    })?;
+    changed(app, task_id, false)
+}
+
+/// Send what the change queued, emit the task's new list and, if the
+/// estimates changed, queue their total as the task's estimate (cleared
+/// once no subtask has one)
+fn changed(app: &AppHandle, task_id: &str, estimates: bool) -> Result<SubtaskList, String> {
+    let list = list(app, task_id)?;
+    if estimates {
+        let total = list
+            .items
+            .iter()
+            .any(|item| item.estimated_mins.is_some())
+            .then_some(list.estimated_mins);
+        sync::outbox::enqueue(
+            app,
+            "PATCH",
+            &format!("/tasks/{}", task_id),
+            Some(&json!({ "estimatedMins": total })),
+            Duration::ZERO,
+        )?;
+    } else {
+        sync::outbox::flush_after(app, Duration::ZERO);
+    }
+    let _ = app.emit("subtasks-changed", &list);
+    Ok(list)
+}
+
+fn request(app: &AppHandle, method: &str, path: &str, body: Option<Value>) -> OutboxEntry {
+    sync::outbox::entry(app, method, path, body.as_ref(), Duration::ZERO)
+}
+
+fn subtasks_path(task_id: &str) -> String {
+    format!("/tasks/{}/subtasks", task_id)
+}
+
+fn subtask_path(subtask: &Subtask) -> String {
+    format!("{}/{}", subtasks_path(&subtask.task_id), subtask.id)
+}
+
+/// A subtask as the server returns it; `position` is its place in the
+/// server's list
+fn from_server(task_id: &str, position: usize, value: &Value) -> Option<Subtask> {
+    let millis = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.timestamp_millis())
+    };
+    let created_at = millis("createdAt").unwrap_or_default();
+    let updated_at = millis("updatedAt").unwrap_or(created_at);
+    let completed = value
+        .get("completed")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    Some(Subtask {
+        id: value.get("id")?.as_str()?.to_string(),
+        task_id: task_id.to_string(),
+        title: value
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        position: value
+            .get("position")
+            .and_then(Value::as_i64)
+            .unwrap_or(position as i64),
+        estimated_mins: value.get("estimatedMins").and_then(Value::as_i64),
+        completed_at: completed.then_some(updated_at),
+        created_at,
+        updated_at,
+    })
+}
+
+fn summarize(task_id: &str, items: Vec<Subtask>) -> SubtaskList {
+    SubtaskList {
+        task_id: task_id.to_string(),
+        estimated_mins: items.iter().filter_map(|item| item.estimated_mins).sum(),
+        completed: items
+            .iter()
+            .filter(|item| item.completed_at.is_some())
+            .count(),
+        items,
+    }
+}
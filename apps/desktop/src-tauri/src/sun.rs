@@ -0,0 +1,66 @@
+//! Sunrise/sunset calculation using the NOAA sunrise equation. Accurate to
+//! about a minute, which is plenty for switching themes.
+
+use chrono::NaiveDate;
+
+const J2000: f64 = 2_451_545.0;
+const UNIX_EPOCH_JULIAN: f64 = 2_440_587.5;
+const MILLIS_PER_DAY: f64 = 86_400_000.0;
+
+/// Sun altitude at sunrise/sunset, accounting for refraction and the solar disc
+const HORIZON_ALTITUDE: f64 = -0.833;
+const EARTH_TILT: f64 = 23.4397;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SunEvents {
+    /// Unix milliseconds of sunrise and sunset
+    Normal { sunrise: i64, sunset: i64 },
+    /// The sun never sets (polar summer)
+    PolarDay,
+    /// The sun never rises (polar winter)
+    PolarNight,
+}
+
+/// Sunrise and sunset on the local calendar `date` at the given position.
+/// Longitude is east-positive.
+pub fn sun_events(date: NaiveDate, latitude: f64, longitude: f64) -> SunEvents {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch date");
+    let days = date.signed_duration_since(epoch).num_days() as f64;
+    // Julian date at noon UTC on `date`
+    let julian_noon = UNIX_EPOCH_JULIAN + days + 0.5;
+
+    let n = (julian_noon - J2000).round();
+    let mean_solar_noon = n - longitude / 360.0;
+
+    let anomaly = (357.5291 + 0.985_600_28 * mean_solar_noon).rem_euclid(360.0);
+    let m = anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.0200 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic_longitude = (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let lambda = ecliptic_longitude.to_radians();
+
+    let transit = J2000 + mean_solar_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * lambda).sin();
+
+    let declination_sin = lambda.sin() * EARTH_TILT.to_radians().sin();
+    let declination_cos = declination_sin.asin().cos();
+    let phi = latitude.to_radians();
+
+    let hour_angle_cos = (HORIZON_ALTITUDE.to_radians().sin() - phi.sin() * declination_sin)
+        / (phi.cos() * declination_cos);
+
+    if hour_angle_cos < -1.0 {
+        return SunEvents::PolarDay;
+    }
+    if hour_angle_cos > 1.0 {
+        return SunEvents::PolarNight;
+    }
+
+    let hour_angle = hour_angle_cos.acos().to_degrees();
+    SunEvents::Normal {
+        sunrise: julian_to_millis(transit - hour_angle / 360.0),
+        sunset: julian_to_millis(transit + hour_angle / 360.0),
+    }
+}
+
+fn julian_to_millis(julian: f64) -> i64 {
+    ((julian - UNIX_EPOCH_JULIAN) * MILLIS_PER_DAY).round() as i64
+}
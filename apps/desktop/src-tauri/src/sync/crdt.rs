@@ -0,0 +1,357 @@
+//! Task notes and descriptions as Yjs CRDT documents, so edits made on
+//! desktop and mobile at the same time merge instead of the last save
+//! replacing the other. Each field is one document kept in the local
+//! database; the webview sends whole-text edits, which become minimal
+//! insert/delete operations, and exchanges encoded updates with other
+//! replicas through the get/apply commands. The sync pass sends documents
+//! with unsent changes to the server and merges back what it's missing,
+//! except for fields sealed by end-to-end encryption: the server merges
+//! text it can read, so while encryption is on those only leave the device
+//! sealed, on the task itself.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use yrs::updates::decoder::Decode;
+use yrs::updates::encoder::Encode;
+use yrs::{Doc, GetString, ReadTxn, StateVector, Text, Transact, Update};
+
+use crate::api::Api;
+use crate::{clock, crypto};
+use crate::db::text_docs::{self, TextDoc};
+use crate::db::Database;
+
+/// Name of the shared text inside each document
+const TEXT_NAME: &str = "content";
+/// Client id for text that existed before the document did. Every replica
+/// seeds the same text the same way, so the seeds merge into one copy.
+const SEED_CLIENT_ID: u64 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextField {
+    Notes,
+    Description,
+}
+
+impl TextField {
+    pub fn parse(field: &str) -> Option<Self> {
+        match field {
+            "notes" => Some(TextField::Notes),
+            "description" => Some(TextField::Description),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TextField::Notes => "notes",
+            TextField::Description => "description",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextChanged {
+    pub task_id: String,
+    pub field: TextField,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncRequest {
+    /// What this replica has, so the server can reply with the rest
+    state_vector: String,
+    update: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SyncReply {
+    update: String,
+}
+
+/// Current text of a field, empty if it was never edited here
+pub fn text(app: &AppHandle, task_id: &str, field: TextField) -> Result<String, String> {
+    match load(app, task_id, field)? {
+        Some(stored) => Ok(read_text(&open(&stored)?)),
+        None => Ok(String::new()),
+    }
+}
+
+/// Record an edit from the webview, which sends the whole new text. `base`
+/// is the text the field had before the document existed (the plain value
+/// from the server) and is only used the first time.
+pub fn edit(
+    app: &AppHandle,
+    task_id: &str,
+    field: TextField,
+    new_text: &str,
+    base: Option<&str>,
+) -> Result<String, String> {
+    let (_, text) = change(app, task_id, field, base.unwrap_or(""), true, |doc| {
+        let text = doc.get_or_insert_text(TEXT_NAME);
+        let mut txn = doc.transact_mut();
+        let current = text.get_string(&txn);
+        let (start, removed, inserted) = diff(&current, new_text);
+        if removed > 0 {
+            text.remove_range(&mut txn, start as u32, removed as u32);
+        }
+        if !inserted.is_empty() {
+            text.insert(&mut txn, start as u32, inserted);
+        }
+        Ok(())
+    })?;
+    Ok(text)
+}
+
+/// Encoded state vector of a field, for another replica to compute what
+/// this one is missing
+pub fn state_vector(app: &AppHandle, task_id: &str, field: TextField) -> Result<String, String> {
+    let vector = match load(app, task_id, field)? {
+        Some(stored) => open(&stored)?.transact().state_vector(),
+        None => StateVector::default(),
+    };
+    Ok(BASE64.encode(vector.encode_v1()))
+}
+
+/// Encoded update with everything a replica at `remote_vector` is missing,
+/// or the whole document without one
+pub fn update_since(
+    app: &AppHandle,
+    task_id: &str,
+    field: TextField,
+    remote_vector: Option<&str>,
+) -> Result<String, String> {
+    let remote = match remote_vector {
+        Some(encoded) => decode_vector(encoded)?,
+        None => StateVector::default(),
+    };
+    let update = match load(app, task_id, field)? {
+        Some(stored) => open(&stored)?.transact().encode_state_as_update_v1(&remote),
+        None => Doc::new().transact().encode_state_as_update_v1(&remote),
+    };
+    Ok(BASE64.encode(update))
+}
+
+/// Merge an encoded update from another replica and return the merged text.
+/// The webview hears about the result through `task-text-changed`.
+pub fn apply_update(app: &AppHandle, task_id: &str, field: TextField, update: &str) -> Result<String, String> {
+    let bytes = BASE64
+        .decode(update)
+        .map_err(|e| format!("Invalid text update: {}", e))?;
+    merge(app, task_id, field, &bytes, true)
+}
+
+/// Merge an update the server pushed; unlike webview updates it doesn't
+/// need sending back
+pub fn apply_server_update(app: &AppHandle, task_id: &str, field: TextField, update: &str) -> Result<String, String> {
+    let bytes = BASE64
+        .decode(update)
+        .map_err(|e| format!("Invalid text update: {}", e))?;
+    merge(app, task_id, field, &bytes, false)
+}
+
+/// Drop a deleted task's documents
+pub fn forget(app: &AppHandle, task_id: &str) -> Result<(), String> {
+    app.state::<Database>()
+        .with_conn(|conn| text_docs::delete_task(conn, task_id))
+}
+
+/// Exchange documents with unsent changes with the server
+#[tracing::instrument(skip_all, err)]
+pub async fn sync(app: &AppHandle) -> Result<(), String> {
+    let dirty = app.state::<Database>().with_conn(|conn| text_docs::dirty(conn))?;
+    if dirty.is_empty() {
+        return Ok(());
+    }
+    let api = Api::new(app)?;
+    let encrypted = crypto::is_enabled(app)?;
+
+    for stored in dirty {
+        let Some(field) = TextField::parse(&stored.field) else {
+            continue;
+        };
+        let Some(request) = sync_request(&stored, field, encrypted)? else {
+            continue;
+        };
+        let path = format!("/tasks/{}/text/{}/sync", stored.task_id, field.as_str());
+
+        let reply: SyncReply = match api.post(&path, &request).await {
+            Ok(reply) => reply,
+            // The task is gone; its document goes with it
+            Err(e) if e.is_not_found() => {
+                forget(app, &stored.task_id)?;
+                continue;
+            }
+            Err(e) => return Err(format!("Failed to sync task text: {}", e)),
+        };
+
+        app.state::<Database>().with_conn(|conn| {
+            text_docs::mark_synced(conn, &stored.task_id, &stored.field, &stored.state)
+        })?;
+        let missing = BASE64
+            .decode(&reply.update)
+            .map_err(|e| format!("Invalid text update: {}", e))?;
+        merge(app, &stored.task_id, field, &missing, false)?;
+    }
+
+    Ok(())
+}
+
+/// What to send the server for a document, or `None` for a field that's
+/// sealed while encryption is on
+fn sync_request(stored: &TextDoc, field: TextField, encrypted: bool) -> Result<Option<SyncRequest>, String> {
+    if encrypted && crypto::TASK_FIELDS.contains(&field.as_str()) {
+        return Ok(None);
+    }
+    let doc = open(stored)?;
+    let txn = doc.transact();
+    Ok(Some(SyncRequest {
+        state_vector: BASE64.encode(txn.state_vector().encode_v1()),
+        update: BASE64.encode(txn.encode_state_as_update_v1(&StateVector::default())),
+    }))
+}
+
+fn merge(app: &AppHandle, task_id: &str, field: TextField, update: &[u8], dirty: bool) -> Result<String, String> {
+    let update = Update::decode_v1(update).map_err(|e| format!("Invalid text update: {}", e))?;
+    let (before, text) = change(app, task_id, field, "", dirty, |doc| {
+        doc.transact_mut()
+            .apply_update(update)
+            .map_err(|e| format!("Failed to merge text update: {}", e))
+    })?;
+
+    if text != before {
+        let _ = app.emit(
+            "task-text-changed",
+            TextChanged {
+                task_id: task_id.to_string(),
+                field,
+                text: text.clone(),
+            },
+        );
+    }
+    Ok(text)
+}
+
+/// Load a field's document (seeded with `base` if it's new), let `f`
+/// change it and store it, in one transaction so an edit and a merge
+/// running at once can't drop each other's changes. Returns the text
+/// before and after.
+fn change(
+    app: &AppHandle,
+    task_id: &str,
+    field: TextField,
+    base: &str,
+    dirty: bool,
+    f: impl FnOnce(&Doc) -> Result<(), String>,
+) -> Result<(String, String), String> {
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        let client_id = text_docs::replica_client_id(&tx)?;
+        let stored = match text_docs::get(&tx, task_id, field.as_str())? {
+            Some(stored) => TextDoc { client_id, ..stored },
+            None => seeded(task_id, field, base, client_id),
+        };
+        let changed = open(&stored).and_then(|doc| {
+            let before = read_text(&doc);
+            f(&doc)?;
+            Ok((before, doc))
+        });
+        let (before, doc) = match changed {
+            Ok(changed) => changed,
+            Err(e) => return Ok(Err(e)),
+        };
+
+        let state = doc
+            .transact()
+            .encode_state_as_update_v1(&StateVector::default());
+        text_docs::save(&tx, &TextDoc { state, ..stored }, dirty, clock::now_millis())?;
+        tx.commit()?;
+        Ok(Ok((before, read_text(&doc))))
+    })?
+}
+
+fn load(app: &AppHandle, task_id: &str, field: TextField) -> Result<Option<TextDoc>, String> {
+    app.state::<Database>()
+        .with_conn(|conn| text_docs::get(conn, task_id, field.as_str()))
+}
+
+/// A new document holding `base` as seed text
+fn seeded(task_id: &str, field: TextField, base: &str, client_id: u64) -> TextDoc {
+    let seed = Doc::with_client_id(SEED_CLIENT_ID);
+    if !base.is_empty() {
+        let text = seed.get_or_insert_text(TEXT_NAME);
+        text.insert(&mut seed.transact_mut(), 0, base);
+    }
+
+    TextDoc {
+        task_id: task_id.to_string(),
+        field: field.as_str().to_string(),
+        state: seed
+            .transact()
+            .encode_state_as_update_v1(&StateVector::default()),
+        client_id,
+    }
+}
+
+fn open(stored: &TextDoc) -> Result<Doc, String> {
+    let doc = Doc::with_client_id(stored.client_id);
+    let update =
+        Update::decode_v1(&stored.state).map_err(|e| format!("Corrupt text document: {}", e))?;
+    doc.transact_mut()
+        .apply_update(update)
+        .map_err(|e| format!("Corrupt text document: {}", e))?;
+    Ok(doc)
+}
+
+fn read_text(doc: &Doc) -> String {
+    let text = doc.get_or_insert_text(TEXT_NAME);
+    let txn = doc.transact();
+    text.get_string(&txn)
+}
+
+fn decode_vector(encoded: &str) -> Result<StateVector, String> {
+    let bytes = BASE64
+        .decode(encoded)
+        .map_err(|e| format!("Invalid state vector: {}", e))?;
+    StateVector::decode_v1(&bytes).map_err(|e| format!("Invalid state vector: {}", e))
+}
+
+/// The single edit turning `old` into `new`: byte offset, bytes removed and
+/// text inserted. Trims the common prefix and suffix on char boundaries.
+fn diff<'a>(old: &str, new: &'a str) -> (usize, usize, &'a str) {
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+
+    let removed = old.len() - prefix - suffix;
+    (prefix, removed, &new[prefix..new.len() - suffix])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_text_stays_off_the_server_while_encryption_is_on() {
+        let stored = seeded("a", TextField::Notes, "call the lawyer", 7);
+
+        let plain = sync_request(&stored, TextField::Notes, false).unwrap().unwrap();
+        let update = BASE64.decode(&plain.update).unwrap();
+        assert!(update.windows(15).any(|w| w == b"call the lawyer"));
+
+        assert!(sync_request(&stored, TextField::Notes, true).unwrap().is_none());
+    }
+}
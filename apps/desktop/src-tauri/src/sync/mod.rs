@@ -0,0 +1,93 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_http::reqwest;
+
+use crate::api::Api;
+use crate::auth::AuthState;
+use crate::connectivity::ConnectivityState;
+use crate::power::{self, Budget, PowerState};
+use crate::{crypto, http, server, settings};
+
+pub mod crdt;
+pub mod outbox;
+pub mod realtime;
+pub mod settings_sync;
+pub mod task_cache;
+pub mod transfers;
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Fields the server assigns to every record; never sent back to it
+pub const SERVER_FIELDS: &[&str] = &["id", "userId", "createdAt", "updatedAt"];
+
+/// Authenticated request context passed to sync components
+pub struct SyncContext<'a> {
+    pub client: &'a reqwest::Client,
+    pub server_url: &'a str,
+    pub token: &'a str,
+}
+
+/// Start the background sync engine. Runs each sync component in turn on a
+/// fixed interval and whenever connectivity changes; pauses while offline.
+/// On battery or a metered connection the interval stretches, and on a low
+/// battery background passes stop.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut connectivity = app.state::<ConnectivityState>().subscribe();
+        let mut power = app.state::<PowerState>().subscribe();
+        loop {
+            if power::budget(&app) != Budget::Paused {
+                if let Err(e) = run_once(&app).await {
+                    let _ = app.emit("sync-error", e);
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(power::interval(&app, SYNC_INTERVAL)) => {}
+                Ok(()) = connectivity.changed() => {}
+                Ok(()) = power.changed() => {}
+            }
+        }
+    });
+}
+
+/// Run one sync pass. A no-op while offline, signed out or with sync disabled.
+#[tracing::instrument(skip_all, err)]
+pub async fn run_once(app: &AppHandle) -> Result<(), String> {
+    if !app.state::<ConnectivityState>().is_online() {
+        return Ok(());
+    }
+    // Changes the user made natively go out even with background sync off
+    outbox::flush(app).await?;
+    crdt::sync(app).await?;
+    if !settings::load(app)?.sync.enabled {
+        return Ok(());
+    }
+    // Archive and search queries read the local copy of the tasks; a failed
+    // download leaves it a pass behind without holding up the rest
+    if let Err(e) = task_cache::sync(app).await {
+        tracing::warn!("Failed to update the local task copy: {}", e);
+    }
+    let Some(token) = app.state::<AuthState>().token() else {
+        return Ok(());
+    };
+
+    let client = http::client(app);
+    let server_url = server::current_url(app);
+    let ctx = SyncContext {
+        client: &client,
+        server_url: &server_url,
+        token: &token,
+    };
+
+    if let Ok(api) = Api::new(app) {
+        crypto::sync_envelopes(app, &api).await?;
+    }
+    settings_sync::sync(app, &ctx).await?;
+    // Attachments upload in the background on their own queue
+    transfers::queue_uploads(app)?;
+
+    let _ = app.emit("sync-completed", ());
+    Ok(())
+}
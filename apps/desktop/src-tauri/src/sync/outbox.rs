@@ -0,0 +1,183 @@
+//! Changes made natively (outside the webview) waiting to reach the backend.
+//! Entries are sent in order once their `send_after` time passes, so an
+//! action can be undone by cancelling its entry before then. Failures stay
+//! queued and are retried on the next sync pass.
+
+use opensunsama_core::sync::queue::{self, Failure};
+use serde_json::Value;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::api::Api;
+use crate::clock::ClockState;
+use crate::db::outbox::{self, OutboxEntry};
+use crate::db::Database;
+use crate::error::AppError;
+
+/// How long a flush may hold its entries; one that dies mid-way frees them
+/// after this
+const CLAIM_FOR: Duration = Duration::from_secs(5 * 60);
+/// How often a drain checks whether a running flush has let go
+const CLAIM_POLL: Duration = Duration::from_millis(100);
+
+/// Queue a request, to be sent no sooner than `delay` from now. Returns the
+/// entry id for `cancel`.
+pub fn enqueue(
+    app: &AppHandle,
+    method: &str,
+    path: &str,
+    body: Option<&Value>,
+    delay: Duration,
+) -> Result<String, String> {
+    let entry = entry(app, method, path, body, delay);
+    app.state::<Database>()
+        .with_conn(|conn| outbox::insert(conn, &entry))?;
+    flush_after(app, delay);
+    Ok(entry.id)
+}
+
+/// A request ready to be inserted, for callers that queue several in
+/// their own transaction and then call `flush_after`
+pub fn entry<R: Runtime>(
+    app: &AppHandle<R>,
+    method: &str,
+    path: &str,
+    body: Option<&Value>,
+    delay: Duration,
+) -> OutboxEntry {
+    let now = app.state::<ClockState>().now_millis();
+    queue::entry(method, path, body, delay, now)
+}
+
+/// Send as soon as `delay` is up rather than waiting for the next pass
+pub fn flush_after(app: &AppHandle, delay: Duration) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        if let Err(e) = flush(&handle).await {
+            let _ = handle.emit("sync-error", e);
+        }
+    });
+}
+
+/// Drop a queued entry. Returns false if it was already sent.
+pub fn cancel(app: &AppHandle, id: &str) -> Result<bool, String> {
+    app.state::<Database>().with_conn(|conn| outbox::delete(conn, id))
+}
+
+/// Send every due entry in order, stopping at the first failure so later
+/// changes never overtake earlier ones. A no-op when signed out.
+#[tracing::instrument(skip_all, err)]
+pub async fn flush(app: &AppHandle) -> Result<(), String> {
+    let now = app.state::<ClockState>().now_millis();
+    // Another sender holding the claim sends these itself
+    send_until(app, now).await.map(|_| ())
+}
+
+/// Send everything queued, including entries still in their undo window.
+/// Used on quit, when there's nothing left to undo from. A flush already
+/// sending is waited out, then whatever it left is sent; the caller bounds
+/// the wait.
+pub async fn drain(app: &AppHandle) -> Result<(), String> {
+    while send_until(app, i64::MAX).await?.is_none() {
+        tokio::time::sleep(CLAIM_POLL).await;
+    }
+    Ok(())
+}
+
+/// Send the due entries under a claim, so a flush that starts while
+/// another runs (a timer firing during a sync pass) sends nothing rather
+/// than the same entries again, out of order. Returns `None`, having sent
+/// nothing, while the other holds the claim.
+async fn send_until(app: &AppHandle, cutoff: i64) -> Result<Option<()>, String> {
+    let Ok(api) = Api::new(app) else {
+        return Ok(Some(()));
+    };
+    let db = app.state::<Database>();
+    let now = app.state::<ClockState>().now_millis();
+    let Some(due) = db.with_conn(|conn| {
+        outbox::claim_due(conn, cutoff, now, now + CLAIM_FOR.as_millis() as i64)
+    })?
+    else {
+        return Ok(None);
+    };
+    if due.is_empty() {
+        return Ok(Some(()));
+    }
+
+    let result = send_claimed(app, &api, due).await;
+    db.with_conn(|conn| outbox::release(conn))?;
+    result.map(Some)
+}
+
+async fn send_claimed(app: &AppHandle, api: &Api, due: Vec<OutboxEntry>) -> Result<(), String> {
+    let db = app.state::<Database>();
+    for entry in due {
+        if let Err(e) = send(api, &entry).await {
+            match queue::on_failure(&entry, e.is_not_found()) {
+                Failure::Gone => {}
+                Failure::GiveUp => {
+                    let _ = app.emit("outbox-dropped", &entry);
+                }
+                Failure::Retry => {
+                    db.with_conn(|conn| outbox::record_failure(conn, &entry.id, &e.message))?;
+                    return Err(format!("Failed to send queued change: {}", e));
+                }
+            }
+        }
+        db.with_conn(|conn| outbox::delete(conn, &entry.id))?;
+    }
+    Ok(())
+}
+
+async fn send(api: &Api, entry: &OutboxEntry) -> Result<(), AppError> {
+    let body: Value = match &entry.body {
+        Some(body) => serde_json::from_str(body).map_err(|e| format!("Invalid queued body: {}", e))?,
+        None => Value::Null,
+    };
+    match entry.method.as_str() {
+        "POST" => api.post::<Value, _>(&entry.path, &body).await.map(|_| ()),
+        "PATCH" => api.patch::<Value, _>(&entry.path, &body).await.map(|_| ()),
+        "DELETE" => api.delete(&entry.path).await,
+        other => Err(format!("Unsupported method {}", other).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+    use crate::testing::TestApp;
+
+    #[test]
+    fn entries_wait_out_their_undo_window() {
+        let app = TestApp::new();
+        let clock = app.clock();
+        clock.set(10_000);
+        let body = serde_json::json!({ "completedAt": null });
+        let queued = entry(
+            app.handle(),
+            "PATCH",
+            "/tasks/a",
+            Some(&body),
+            Duration::from_secs(5),
+        );
+        assert_eq!(queued.created_at, 10_000);
+        assert_eq!(queued.send_after, 15_000);
+        assert_eq!(queued.body.as_deref(), Some(r#"{"completedAt":null}"#));
+        let db = app.db();
+        db.with_conn(|conn| outbox::insert(conn, &queued)).unwrap();
+
+        clock.advance(4_999);
+        assert!(db
+            .with_conn(|conn| outbox::due(conn, clock.now_millis()))
+            .unwrap()
+            .is_empty());
+        clock.advance(1);
+        let due = db
+            .with_conn(|conn| outbox::due(conn, clock.now_millis()))
+            .unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, queued.id);
+    }
+}
@@ -0,0 +1,246 @@
+//! Realtime updates over a WebSocket to `<server>/ws`, kept open by a
+//! native task so pushes arrive even while the webview is asleep. The
+//! server sends `{ "type": "task.updated", "data": ... }` messages, which
+//! are re-emitted as typed events (`task-created`, `task-updated`,
+//! `task-deleted`, `time-blocks-changed`), text edits are merged into the
+//! local CRDT documents, and settings changes trigger a sync pass. The connection follows sign-in and connectivity, reconnects with
+//! exponential backoff, and is pinged so half-open sockets are noticed.
+//! It goes through the same proxy and TLS trust store as the HTTP client.
+//! `realtime-status-changed` reports whether it's connected.
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::Connector;
+
+use super::crdt::{self, TextField};
+use super::task_cache;
+use crate::auth::AuthState;
+use crate::connectivity::ConnectivityState;
+use crate::{http, recent_tasks, server};
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A connection that lasted this long resets the backoff
+const STABLE_AFTER: Duration = Duration::from_secs(30);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(25);
+/// Silence for this long means the connection is gone
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Close code the server uses for an expired session
+const CLOSE_AUTH_EXPIRED: u16 = 4401;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "data")]
+enum ServerMessage {
+    #[serde(rename = "task.created")]
+    TaskCreated(Value),
+    #[serde(rename = "task.updated")]
+    TaskUpdated(Value),
+    #[serde(rename = "task.deleted")]
+    TaskDeleted { id: String },
+    #[serde(rename = "task.text-updated", rename_all = "camelCase")]
+    TaskTextUpdated {
+        task_id: String,
+        field: TextField,
+        /// Base64 Yjs update
+        update: String,
+    },
+    #[serde(rename = "time-block.changed")]
+    TimeBlocksChanged,
+    #[serde(rename = "settings.updated")]
+    SettingsUpdated,
+    #[serde(rename = "auth.expired")]
+    AuthExpired,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Why a connection ended
+enum Ended {
+    /// A new token (or sign-out); reconnect straight away
+    TokenChanged,
+    /// The server rejected the session; wait for the webview to refresh it
+    AuthExpired,
+    Dropped(String),
+}
+
+/// Keep the realtime connection up for as long as the app runs
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut auth = app.state::<AuthState>().subscribe();
+        let mut connectivity = app.state::<ConnectivityState>().subscribe();
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            let token = auth.borrow_and_update().clone();
+            let online = connectivity.borrow_and_update().online;
+            let Some(token) = token.filter(|_| online) else {
+                tokio::select! {
+                    changed = auth.changed() => if changed.is_err() { return },
+                    changed = connectivity.changed() => if changed.is_err() { return },
+                }
+                continue;
+            };
+
+            let connected_at = Instant::now();
+            let ended = connect(&app, &token, &mut auth).await;
+            let _ = app.emit("realtime-status-changed", false);
+
+            match ended {
+                Ended::TokenChanged => backoff = MIN_BACKOFF,
+                Ended::AuthExpired => {
+                    let _ = app.emit("auth-expired", ());
+                    if auth.changed().await.is_err() {
+                        return;
+                    }
+                    backoff = MIN_BACKOFF;
+                }
+                Ended::Dropped(_) => {
+                    if connected_at.elapsed() >= STABLE_AFTER {
+                        backoff = MIN_BACKOFF;
+                    }
+                    tokio::select! {
+                        _ = tokio::time::sleep(backoff) => {}
+                        changed = auth.changed() => if changed.is_err() { return },
+                    }
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    });
+}
+
+async fn connect(app: &AppHandle, token: &str, auth: &mut watch::Receiver<Option<String>>) -> Ended {
+    let mut request = match socket_url(&server::current_url(app)).into_client_request() {
+        Ok(request) => request,
+        Err(e) => return Ended::Dropped(format!("Invalid realtime URL: {}", e)),
+    };
+    match HeaderValue::from_str(&format!("Bearer {}", token)) {
+        Ok(value) => {
+            request.headers_mut().insert("Authorization", value);
+        }
+        Err(e) => return Ended::Dropped(format!("Invalid token: {}", e)),
+    }
+
+    let Some(host) = request.uri().host().map(str::to_string) else {
+        return Ended::Dropped("Invalid realtime URL: no host".to_string());
+    };
+    let secure = request.uri().scheme_str() == Some("wss");
+    let port = request
+        .uri()
+        .port_u16()
+        .unwrap_or(if secure { 443 } else { 80 });
+    let connecting = async {
+        let stream = http::connect_tcp(app, &host, port).await?;
+        let connector = Connector::Rustls(http::tls_config());
+        tokio_tungstenite::client_async_tls_with_config(request, stream, None, Some(connector))
+            .await
+            .map(|(socket, _)| socket)
+            .map_err(|e| e.to_string())
+    };
+
+    let socket = tokio::select! {
+        connected = connecting => match connected {
+            Ok(socket) => socket,
+            Err(e) => return Ended::Dropped(format!("Failed to connect: {}", e)),
+        },
+        _ = auth.changed() => return Ended::TokenChanged,
+    };
+    let _ = app.emit("realtime-status-changed", true);
+
+    let (mut sink, mut stream) = socket.split();
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut last_heard = Instant::now();
+
+    loop {
+        tokio::select! {
+            message = stream.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    last_heard = Instant::now();
+                    match serde_json::from_str::<ServerMessage>(&text) {
+                        Ok(ServerMessage::AuthExpired) => return Ended::AuthExpired,
+                        Ok(message) => dispatch(app, message),
+                        // Unreadable messages are skipped, not fatal
+                        Err(_) => {}
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    return match frame {
+                        Some(frame) if frame.code == CloseCode::from(CLOSE_AUTH_EXPIRED) => Ended::AuthExpired,
+                        _ => Ended::Dropped("Closed by server".to_string()),
+                    };
+                }
+                Some(Ok(_)) => last_heard = Instant::now(),
+                Some(Err(e)) => return Ended::Dropped(e.to_string()),
+                None => return Ended::Dropped("Connection closed".to_string()),
+            },
+            _ = heartbeat.tick() => {
+                if last_heard.elapsed() >= HEARTBEAT_TIMEOUT {
+                    return Ended::Dropped("Heartbeat timed out".to_string());
+                }
+                if let Err(e) = sink.send(Message::Ping(Vec::new())).await {
+                    return Ended::Dropped(e.to_string());
+                }
+            }
+            _ = auth.changed() => {
+                let _ = sink.close().await;
+                return Ended::TokenChanged;
+            }
+        }
+    }
+}
+
+fn dispatch(app: &AppHandle, message: ServerMessage) {
+    match message {
+        ServerMessage::TaskCreated(task) => {
+            let _ = task_cache::store(app, &task);
+            let _ = app.emit("task-created", task);
+        }
+        ServerMessage::TaskUpdated(task) => {
+            let _ = task_cache::store(app, &task);
+            let _ = app.emit("task-updated", task);
+        }
+        ServerMessage::TaskDeleted { id } => {
+            let _ = recent_tasks::remove(app, &id);
+            let _ = crdt::forget(app, &id);
+            let _ = task_cache::remove(app, &id);
+            let _ = app.emit("task-deleted", id);
+        }
+        ServerMessage::TaskTextUpdated { task_id, field, update } => {
+            if let Err(e) = crdt::apply_server_update(app, &task_id, field, &update) {
+                let _ = app.emit("sync-error", e);
+            }
+        }
+        ServerMessage::TimeBlocksChanged => {
+            let _ = app.emit("time-blocks-changed", ());
+        }
+        ServerMessage::SettingsUpdated => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = super::run_once(&app).await {
+                    let _ = app.emit("sync-error", e);
+                }
+            });
+        }
+        ServerMessage::AuthExpired | ServerMessage::Unknown => {}
+    }
+}
+
+/// `https://host/base` → `wss://host/base/ws`
+fn socket_url(server_url: &str) -> String {
+    let base = server_url.trim_end_matches('/');
+    let base = match base.split_once("://") {
+        Some(("https", rest)) => format!("wss://{}", rest),
+        Some(("http", rest)) => format!("ws://{}", rest),
+        _ => base.to_string(),
+    };
+    format!("{}/ws", base)
+}
@@ -0,0 +1,140 @@
+//! Opt-in sync of preferences that make sense on every device. Device-local
+//! settings (auto-launch, tray behaviour, location) never leave the machine.
+//! Conflicts resolve per field: the most recent change wins.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter};
+
+use super::SyncContext;
+use crate::{clock, crypto};
+use crate::http::{self, Integration};
+use crate::settings::{self, SettingsSection};
+
+const SYNCED_FIELDS: &[(SettingsSection, &str)] = &[
+    (SettingsSection::Appearance, "theme"),
+    (SettingsSection::Shortcuts, "enabled"),
+    (SettingsSection::Shortcuts, "bindings"),
+    (SettingsSection::Shortcuts, "disabled"),
+    (SettingsSection::Planning, "planning_ritual"),
+    (SettingsSection::Planning, "shutdown_ritual"),
+    // Which days the ritual reminders skip
+    (SettingsSection::Planning, "skip_weekends"),
+    (SettingsSection::Planning, "holidays"),
+];
+/// Key of a sealed value's payload
+const SEALED: &str = "sealed";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncedValue {
+    value: Value,
+    updated_at: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SettingsSyncPayload {
+    #[serde(default)]
+    entries: HashMap<String, SyncedValue>,
+}
+
+#[tracing::instrument(skip_all, err)]
+pub async fn sync(app: &AppHandle, ctx: &SyncContext<'_>) -> Result<(), String> {
+    let current = settings::load(app)?;
+    if !current.sync.settings_sync {
+        return Ok(());
+    }
+
+    let url = format!("{}/settings/sync", ctx.server_url.trim_end_matches('/'));
+    let request = ctx.client.get(&url).bearer_auth(ctx.token);
+    let remote: SettingsSyncPayload = http::send(app, Integration::Backend, request)
+        .await?
+        .error_for_status()
+        .map_err(|e| format!("Failed to fetch synced settings: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read synced settings: {}", e))
+        .and_then(|body| {
+            serde_json::from_str(&body).map_err(|e| format!("Invalid synced settings: {}", e))
+        })?;
+
+    let modified = settings::modified_times(app)?;
+    let mut merged = current.clone();
+    let mut pulled = HashMap::new();
+    let mut push = SettingsSyncPayload::default();
+
+    for &(section, field) in SYNCED_FIELDS {
+        let key = settings::field_key(section, field);
+        let local_at = modified.get(&key).and_then(Value::as_i64).unwrap_or(0);
+
+        match remote.entries.get(&key) {
+            Some(entry) if entry.updated_at > local_at => {
+                let mut patch = Map::new();
+                patch.insert(field.to_string(), open_value(app, &entry.value)?);
+                merged.apply_patch(section, Value::Object(patch))?;
+                pulled.insert(key, entry.updated_at);
+            }
+            Some(entry) if entry.updated_at == local_at => {}
+            _ => {
+                let value = current
+                    .section_value(section)?
+                    .get(field)
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                push.entries.insert(
+                    key,
+                    SyncedValue {
+                        value: seal_value(app, value)?,
+                        updated_at: local_at,
+                    },
+                );
+            }
+        }
+    }
+
+    if !pulled.is_empty() {
+        let now = clock::now_millis();
+        settings::save_with_modified(app, &merged, |key| {
+            pulled.get(key).copied().unwrap_or(now)
+        })?;
+        settings::apply(app, &merged)?;
+        let _ = app.emit("settings-changed", &merged);
+    }
+
+    if !push.entries.is_empty() {
+        let body = serde_json::to_string(&push)
+            .map_err(|e| format!("Failed to serialize synced settings: {}", e))?;
+        let request = ctx
+            .client
+            .put(&url)
+            .bearer_auth(ctx.token)
+            .header("Content-Type", "application/json")
+            .body(body);
+        http::send(app, Integration::Backend, request)
+            .await?
+            .error_for_status()
+            .map_err(|e| format!("Failed to push synced settings: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// With encryption set up, a synced value travels sealed, as
+/// `{ "sealed": payload }`
+fn seal_value(app: &AppHandle, value: Value) -> Result<Value, String> {
+    if !crypto::is_enabled(app)? {
+        return Ok(value);
+    }
+    let sealed = crypto::encrypt(app, value.to_string().as_bytes())?;
+    Ok(serde_json::json!({ SEALED: sealed }))
+}
+
+/// Undo `seal_value`. Fails while encryption is locked.
+fn open_value(app: &AppHandle, value: &Value) -> Result<Value, String> {
+    let Some(sealed) = value.get(SEALED).and_then(Value::as_str) else {
+        return Ok(value.clone());
+    };
+    let json = crypto::decrypt(app, sealed)?;
+    serde_json::from_slice(&json).map_err(|e| format!("Corrupt synced setting: {}", e))
+}
@@ -0,0 +1,97 @@
+//! Local copy of the server's tasks, so views over the whole history (the
+//! archive, search) page through indexed queries instead of loading every
+//! task into the webview. Each sync pass downloads what changed since the
+//! stored cursor, deletions included, a page at a time, so an interrupted
+//! download picks up where it stopped; realtime pushes keep the copy
+//! current in between.
+
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::api::Api;
+use crate::crypto;
+use crate::db::tasks::{self, TaskFilter, TaskPage, TaskSort};
+use crate::db::{sync_cursors, Database};
+
+const DEFAULT_LIMIT: usize = 50;
+const CURSOR: &str = "tasks";
+const PAGE_SIZE: usize = 100;
+
+/// Bring the local copy up to date. Pages come oldest change first, and
+/// each one moves the cursor to its newest change as it's stored. With no
+/// cursor yet, the copy is replaced by a full download.
+#[tracing::instrument(skip_all, err)]
+pub async fn sync(app: &AppHandle) -> Result<(), String> {
+    let Ok(api) = Api::new(app) else {
+        return Ok(());
+    };
+    let db = app.state::<Database>();
+    let mut since = db.with_conn(|conn| sync_cursors::get(conn, CURSOR))?;
+    let mut first = true;
+
+    loop {
+        let mut path = format!(
+            "/tasks?includeDeleted=true&sortBy=updatedAt&sortOrder=asc&limit={}",
+            PAGE_SIZE
+        );
+        if let Some(since) = &since {
+            path.push_str("&updatedSince=");
+            // An RFC 3339 offset's `+` would read as a space
+            path.push_str(&since.replace('+', "%2B"));
+        }
+        let mut changed: Vec<Value> = api
+            .get(&path)
+            .await
+            .map_err(|e| format!("Failed to download tasks: {}", e))?;
+        for task in &mut changed {
+            crypto::open_fields(app, task, crypto::TASK_FIELDS);
+        }
+
+        let full = first && since.is_none();
+        let cursor = db.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            if full {
+                tasks::clear(&tx)?;
+            }
+            let cursor = tasks::apply_changes(&tx, &changed)?;
+            if let Some(cursor) = &cursor {
+                sync_cursors::set(&tx, CURSOR, cursor)?;
+            }
+            tx.commit()?;
+            Ok(cursor)
+        })?;
+        first = false;
+
+        // A short page is the last; a cursor that didn't move means the
+        // server can't page by it, and asking again would repeat the page
+        if changed.len() < PAGE_SIZE || cursor.is_none() || cursor == since {
+            return Ok(());
+        }
+        since = cursor;
+    }
+}
+
+/// Store a task pushed by the server
+pub fn store(app: &AppHandle, task: &Value) -> Result<(), String> {
+    let mut task = task.clone();
+    crypto::open_fields(app, &mut task, crypto::TASK_FIELDS);
+    app.state::<Database>()
+        .with_conn(|conn| tasks::upsert(conn, &task))
+}
+
+pub fn remove(app: &AppHandle, id: &str) -> Result<(), String> {
+    app.state::<Database>()
+        .with_conn(|conn| tasks::delete(conn, id))
+}
+
+/// One page of the stored tasks matching `filter`
+pub fn query(
+    app: &AppHandle,
+    filter: &TaskFilter,
+    sort: TaskSort,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Result<TaskPage, String> {
+    app.state::<Database>()
+        .with_conn(|conn| tasks::query(conn, filter, sort, cursor, limit.unwrap_or(DEFAULT_LIMIT)))
+}
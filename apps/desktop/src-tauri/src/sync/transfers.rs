@@ -0,0 +1,515 @@
+//! Attachment uploads and downloads, queued apart from the outbox so a big
+//! file never holds up task changes. Transfers run one at a time in chunks
+//! and record their progress, so a quit, crash or lost connection resumes
+//! where it stopped: uploads through a server upload session, downloads
+//! with a ranged request onto a partial file. Bandwidth limits come from
+//! the sync settings; on battery or a metered connection transfers queued
+//! by sync wait (see `power`), finishing the chunk in flight first, while
+//! the ones the user asked for go ahead.
+//!
+//! Server endpoints: `POST /attachments/uploads` opens a session
+//! (`{ uploadId, offset }`), `GET /attachments/uploads/:id` reports its
+//! offset, `PUT /attachments/uploads/:id` takes a chunk with a
+//! `Content-Range` header, `POST /attachments/uploads/:id/complete` returns
+//! the attachment, and `GET /attachments/:id/content` serves downloads.
+//!
+//! The webview follows along through `transfer-progress` (bytes moved) and
+//! `transfer-updated` (status changes), both carrying the transfer.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_http::reqwest::StatusCode;
+use tokio::sync::Notify;
+
+use crate::api::Api;
+use crate::connectivity::ConnectivityState;
+use crate::db::attachments::{self as attachment_rows, Attachment};
+use crate::db::transfers::{self, Transfer, TransferDirection, TransferStatus};
+use crate::db::Database;
+use crate::http::rate_limit::{RateLimit, TokenBucket};
+use crate::power::PowerState;
+use crate::settings::{self, SyncSettings};
+use crate::{attachments, clock, power};
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+/// Least time between progress events (and progress writes) per transfer
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// How often an idle queue looks again without being woken
+const IDLE_RECHECK: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+pub struct TransferState {
+    wake: Notify,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateUpload<'a> {
+    file_name: &'a str,
+    mime_type: &'a str,
+    size_bytes: i64,
+    hash: &'a str,
+    task_id: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadSession {
+    #[serde(default)]
+    upload_id: Option<String>,
+    offset: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteAttachment {
+    id: String,
+}
+
+/// What to fetch for `queue_download`, as listed by the server
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteFile {
+    pub remote_id: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub hash: String,
+    pub task_id: Option<String>,
+}
+
+/// How a transfer run ended, short of an error
+enum Outcome {
+    Finished,
+    /// Paused or cancelled from the webview, or held to save power
+    Stopped,
+}
+
+/// Run the queue for as long as the app runs. Transfers interrupted by the
+/// last quit go back in the queue.
+pub fn start(app: &AppHandle) {
+    let _ = app
+        .state::<Database>()
+        .with_conn(|conn| transfers::requeue_active(conn));
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut connectivity = app.state::<ConnectivityState>().subscribe();
+        let mut power = app.state::<PowerState>().subscribe();
+        loop {
+            while ready(&app) {
+                let held = power::defers_transfers(&app);
+                let Ok(Some(transfer)) =
+                    app.state::<Database>().with_conn(|conn| transfers::next_queued(conn, held))
+                else {
+                    break;
+                };
+                run(&app, transfer).await;
+            }
+
+            tokio::select! {
+                _ = app.state::<TransferState>().wake.notified() => {}
+                Ok(()) = connectivity.changed() => {}
+                Ok(()) = power.changed() => {}
+                _ = tokio::time::sleep(IDLE_RECHECK) => {}
+            }
+        }
+    });
+}
+
+/// Online and signed in
+fn ready(app: &AppHandle) -> bool {
+    app.state::<ConnectivityState>().is_online() && Api::new(app).is_ok()
+}
+
+pub fn list(app: &AppHandle) -> Result<Vec<Transfer>, String> {
+    app.state::<Database>().with_conn(|conn| transfers::list(conn))
+}
+
+/// Queue uploads for attachments the server doesn't have yet
+pub fn queue_uploads(app: &AppHandle) -> Result<(), String> {
+    let pending = app
+        .state::<Database>()
+        .with_conn(|conn| attachment_rows::not_uploaded(conn))?;
+    for attachment in pending {
+        queue_upload(app, &attachment, false)?;
+    }
+    Ok(())
+}
+
+/// Queue one attachment for upload, unless it's already queued.
+/// `requested` when the user asked for it, which also lets an upload
+/// already queued by sync go ahead while power saving holds the rest.
+pub fn queue_upload(
+    app: &AppHandle,
+    attachment: &Attachment,
+    requested: bool,
+) -> Result<Option<Transfer>, String> {
+    if attachment.remote_id.is_some() {
+        return Ok(None);
+    }
+    let db = app.state::<Database>();
+    if db.with_conn(|conn| transfers::has_pending_upload(conn, &attachment.id))? {
+        if requested {
+            db.with_conn(|conn| transfers::request_upload(conn, &attachment.id))?;
+            app.state::<TransferState>().wake.notify_one();
+        }
+        return Ok(None);
+    }
+
+    let now = clock::now_millis();
+    let transfer = Transfer {
+        id: uuid::Uuid::new_v4().to_string(),
+        direction: TransferDirection::Upload,
+        attachment_id: Some(attachment.id.clone()),
+        remote_id: None,
+        upload_id: None,
+        task_id: attachment.task_id.clone(),
+        file_name: attachment.file_name.clone(),
+        mime_type: attachment.mime_type.clone(),
+        hash: attachment.hash.clone(),
+        size_bytes: attachment.size_bytes,
+        transferred_bytes: 0,
+        status: TransferStatus::Queued,
+        error: None,
+        requested,
+        created_at: now,
+        updated_at: now,
+    };
+    enqueue(app, &transfer)?;
+    Ok(Some(transfer))
+}
+
+/// Queue a server attachment for download. Returns `None` when it's
+/// already stored locally.
+pub fn queue_download(app: &AppHandle, file: RemoteFile) -> Result<Option<Transfer>, String> {
+    let existing = app
+        .state::<Database>()
+        .with_conn(|conn| attachment_rows::find_by_remote_id(conn, &file.remote_id))?;
+    if existing.is_some() {
+        return Ok(None);
+    }
+
+    let now = clock::now_millis();
+    let transfer = Transfer {
+        id: uuid::Uuid::new_v4().to_string(),
+        direction: TransferDirection::Download,
+        attachment_id: None,
+        remote_id: Some(file.remote_id),
+        upload_id: None,
+        task_id: file.task_id,
+        file_name: file.file_name,
+        mime_type: file.mime_type,
+        hash: file.hash,
+        size_bytes: file.size_bytes,
+        transferred_bytes: 0,
+        status: TransferStatus::Queued,
+        error: None,
+        requested: true,
+        created_at: now,
+        updated_at: now,
+    };
+    enqueue(app, &transfer)?;
+    Ok(Some(transfer))
+}
+
+/// Stop a transfer after its current chunk; `resume` picks it up again
+pub fn pause(app: &AppHandle, id: &str) -> Result<(), String> {
+    set_status(app, id, TransferStatus::Paused, None)
+}
+
+/// Put a paused or failed transfer back in the queue, to run even while
+/// power saving holds background transfers
+pub fn resume(app: &AppHandle, id: &str) -> Result<(), String> {
+    app.state::<Database>()
+        .with_conn(|conn| transfers::request(conn, id, clock::now_millis()))?;
+    emit_updated(app, id);
+    app.state::<TransferState>().wake.notify_one();
+    Ok(())
+}
+
+/// Drop a transfer and any partial download
+pub fn cancel(app: &AppHandle, id: &str) -> Result<(), String> {
+    let db = app.state::<Database>();
+    if let Some(transfer) = db.with_conn(|conn| transfers::get(conn, id))? {
+        if transfer.direction == TransferDirection::Download {
+            let _ = std::fs::remove_file(partial_path(app, &transfer)?);
+        }
+    }
+    db.with_conn(|conn| transfers::delete(conn, id))?;
+    let _ = app.emit("transfers-changed", ());
+    Ok(())
+}
+
+fn enqueue(app: &AppHandle, transfer: &Transfer) -> Result<(), String> {
+    app.state::<Database>()
+        .with_conn(|conn| transfers::insert(conn, transfer))?;
+    let _ = app.emit("transfer-updated", transfer);
+    app.state::<TransferState>().wake.notify_one();
+    Ok(())
+}
+
+async fn run(app: &AppHandle, transfer: Transfer) {
+    if set_status(app, &transfer.id, TransferStatus::Active, None).is_err() {
+        return;
+    }
+    let sync = settings::load(app).map(|settings| settings.sync).unwrap_or_default();
+    let result = match transfer.direction {
+        TransferDirection::Upload => upload(app, &transfer, &sync).await,
+        TransferDirection::Download => download(app, &transfer, &sync).await,
+    };
+
+    match result {
+        Ok(Outcome::Finished) | Ok(Outcome::Stopped) => {}
+        // Lost the connection; try again once back online
+        Err(_) if !app.state::<ConnectivityState>().is_online() => {
+            let _ = set_status(app, &transfer.id, TransferStatus::Queued, None);
+        }
+        Err(e) => {
+            let _ = set_status(app, &transfer.id, TransferStatus::Failed, Some(&e));
+        }
+    }
+}
+
+async fn upload(app: &AppHandle, transfer: &Transfer, sync: &SyncSettings) -> Result<Outcome, String> {
+    let api = Api::new(app)?;
+    let attachment_id = transfer
+        .attachment_id
+        .as_deref()
+        .ok_or_else(|| "Upload has no attachment".to_string())?;
+    let attachment = attachments::get(app, attachment_id)?;
+    let mut file = File::open(attachments::blob(app, &attachment)?)
+        .map_err(|e| format!("Failed to open {}: {}", attachment.file_name, e))?;
+    let total = attachment.size_bytes as u64;
+
+    // Resume the server's session if it still has one, else open another
+    let resumed = match &transfer.upload_id {
+        Some(upload_id) => api
+            .get::<UploadSession>(&format!("/attachments/uploads/{}", upload_id))
+            .await
+            .ok()
+            .map(|session| (upload_id.clone(), session.offset)),
+        None => None,
+    };
+    let (upload_id, mut offset) = match resumed {
+        Some(resumed) => resumed,
+        None => {
+            let session: UploadSession = api
+                .post(
+                    "/attachments/uploads",
+                    &CreateUpload {
+                        file_name: &attachment.file_name,
+                        mime_type: &attachment.mime_type,
+                        size_bytes: attachment.size_bytes,
+                        hash: &attachment.hash,
+                        task_id: attachment.task_id.as_deref(),
+                    },
+                )
+                .await?;
+            let upload_id = session
+                .upload_id
+                .ok_or_else(|| "Server did not open an upload".to_string())?;
+            app.state::<Database>()
+                .with_conn(|conn| transfers::set_upload_id(conn, &transfer.id, &upload_id))?;
+            (upload_id, session.offset)
+        }
+    };
+
+    let mut limiter = limiter(sync.upload_limit_kib);
+    let mut progress = Progress::new(app, transfer);
+    let path = format!("/attachments/uploads/{}", upload_id);
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+
+    while offset < total {
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| format!("Failed to read {}: {}", attachment.file_name, e))?;
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", attachment.file_name, e))?;
+        if read == 0 {
+            return Err(format!("{} changed while uploading", attachment.file_name));
+        }
+
+        throttle(&mut limiter, read).await;
+        let session: UploadSession = api.put_chunk(&path, offset, total, buffer[..read].to_vec()).await?;
+        offset = session.offset;
+
+        progress.update(offset as i64);
+        if should_stop(app, transfer, sync)? {
+            progress.flush();
+            return Ok(Outcome::Stopped);
+        }
+    }
+
+    let remote: RemoteAttachment = api
+        .post(&format!("{}/complete", path), &serde_json::json!({}))
+        .await?;
+    let db = app.state::<Database>();
+    db.with_conn(|conn| attachment_rows::set_remote_id(conn, &attachment.id, &remote.id))?;
+    db.with_conn(|conn| transfers::finish(conn, &transfer.id, &attachment.id, &remote.id, clock::now_millis()))?;
+    emit_updated(app, &transfer.id);
+    Ok(Outcome::Finished)
+}
+
+async fn download(app: &AppHandle, transfer: &Transfer, sync: &SyncSettings) -> Result<Outcome, String> {
+    let api = Api::new(app)?;
+    let remote_id = transfer
+        .remote_id
+        .as_deref()
+        .ok_or_else(|| "Download has no server attachment".to_string())?;
+    let path = partial_path(app, transfer)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to save {}: {}", transfer.file_name, e))?;
+    let mut offset = file
+        .metadata()
+        .map(|meta| meta.len())
+        .map_err(|e| format!("Failed to save {}: {}", transfer.file_name, e))?;
+
+    // A complete partial file was cut off just before it was stored
+    if offset < transfer.size_bytes as u64 {
+        let mut response = api
+            .download_from(&format!("/attachments/{}/content", remote_id), offset)
+            .await?;
+        if offset > 0 && response.status() != StatusCode::PARTIAL_CONTENT {
+            // The server sent the whole file; start over
+            file.set_len(0)
+                .map_err(|e| format!("Failed to save {}: {}", transfer.file_name, e))?;
+            offset = 0;
+        }
+
+        let mut limiter = limiter(sync.download_limit_kib);
+        let mut progress = Progress::new(app, transfer);
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Download failed: {}", e))?
+        {
+            throttle(&mut limiter, chunk.len()).await;
+            file.write_all(&chunk)
+                .map_err(|e| format!("Failed to save {}: {}", transfer.file_name, e))?;
+            offset += chunk.len() as u64;
+
+            progress.update(offset as i64);
+            if should_stop(app, transfer, sync)? {
+                progress.flush();
+                drop(file);
+                // Cancelled rather than paused: nothing left to resume
+                if app.state::<Database>().with_conn(|conn| transfers::get(conn, &transfer.id))?.is_none() {
+                    let _ = std::fs::remove_file(&path);
+                }
+                return Ok(Outcome::Stopped);
+            }
+        }
+        file.sync_all()
+            .map_err(|e| format!("Failed to save {}: {}", transfer.file_name, e))?;
+    }
+    drop(file);
+
+    let attachment = attachments::adopt(
+        app,
+        &path,
+        &transfer.file_name,
+        transfer.task_id.as_deref(),
+        &transfer.hash,
+        remote_id,
+    )?;
+    app.state::<Database>().with_conn(|conn| {
+        transfers::finish(conn, &transfer.id, &attachment.id, remote_id, clock::now_millis())
+    })?;
+    emit_updated(app, &transfer.id);
+    Ok(Outcome::Finished)
+}
+
+/// Whether to stop after this chunk: the transfer was paused or cancelled,
+/// or it's a background one held to save power (and goes back in the queue)
+fn should_stop(app: &AppHandle, transfer: &Transfer, sync: &SyncSettings) -> Result<bool, String> {
+    let current = app.state::<Database>().with_conn(|conn| transfers::get(conn, &transfer.id))?;
+    let Some(current) = current.filter(|current| current.status == TransferStatus::Active) else {
+        return Ok(true);
+    };
+    if !current.requested && power::defers_transfers_with(app, sync) {
+        set_status(app, &transfer.id, TransferStatus::Queued, None)?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+fn set_status(app: &AppHandle, id: &str, status: TransferStatus, error: Option<&str>) -> Result<(), String> {
+    app.state::<Database>()
+        .with_conn(|conn| transfers::set_status(conn, id, status, error, clock::now_millis()))?;
+    emit_updated(app, id);
+    Ok(())
+}
+
+fn emit_updated(app: &AppHandle, id: &str) {
+    if let Ok(Some(transfer)) = app.state::<Database>().with_conn(|conn| transfers::get(conn, id)) {
+        let _ = app.emit("transfer-updated", transfer);
+    }
+}
+
+fn partial_path(app: &AppHandle, transfer: &Transfer) -> Result<PathBuf, String> {
+    Ok(attachments::partial_dir(app)?.join(format!("{}.part", transfer.id)))
+}
+
+/// A byte-rate limiter for the configured KiB/s limit, if any
+fn limiter(limit_kib: Option<u32>) -> Option<TokenBucket> {
+    let kib = limit_kib?;
+    let per_second = kib.max(1).saturating_mul(1024);
+    Some(TokenBucket::new(RateLimit {
+        per_second: f64::from(per_second),
+        burst: per_second.max(CHUNK_SIZE as u32),
+    }))
+}
+
+async fn throttle(limiter: &mut Option<TokenBucket>, bytes: usize) {
+    let Some(limiter) = limiter else {
+        return;
+    };
+    while let Err(wait) = limiter.try_acquire_many(bytes as u32) {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Records progress and emits `transfer-progress`, at most every
+/// `PROGRESS_INTERVAL`
+struct Progress<'a> {
+    app: &'a AppHandle,
+    transfer: Transfer,
+    reported_at: Option<Instant>,
+}
+
+impl<'a> Progress<'a> {
+    fn new(app: &'a AppHandle, transfer: &Transfer) -> Self {
+        Self {
+            app,
+            transfer: Transfer {
+                status: TransferStatus::Active,
+                ..transfer.clone()
+            },
+            reported_at: None,
+        }
+    }
+
+    fn update(&mut self, transferred_bytes: i64) {
+        self.transfer.transferred_bytes = transferred_bytes;
+        if self.reported_at.is_some_and(|at| at.elapsed() < PROGRESS_INTERVAL) {
+            return;
+        }
+        self.flush();
+    }
+
+    fn flush(&mut self) {
+        self.reported_at = Some(Instant::now());
+        self.transfer.updated_at = clock::now_millis();
+        let _ = self.app.state::<Database>().with_conn(|conn| {
+            transfers::set_progress(conn, &self.transfer.id, self.transfer.transferred_bytes, self.transfer.updated_at)
+        });
+        let _ = self.app.emit("transfer-progress", &self.transfer);
+    }
+}
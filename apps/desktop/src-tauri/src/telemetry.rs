@@ -0,0 +1,536 @@
+//! Optional OpenTelemetry export for self-hosters. With `otlp_enabled` set
+//! in the network settings, the spans around sync passes and HTTP requests
+//! (see `sync::run_once` and `http::send`) are collected by a tracing layer
+//! and sent every half minute to the collector's OTLP/HTTP JSON endpoints:
+//! the spans themselves to `/v1/traces`, and per-operation duration
+//! histograms and error counts to `/v1/metrics`. Spans that fail to send
+//! are dropped rather than piling up.
+
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::settings::NetworkSettings;
+use crate::{clock, http};
+
+const EXPORT_INTERVAL: Duration = Duration::from_secs(30);
+/// Spans kept between exports; the oldest go first
+const MAX_QUEUED_SPANS: usize = 2_048;
+/// Upper bounds of the duration histogram buckets, in milliseconds
+const BUCKETS_MS: [f64; 10] = [
+    10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1_000.0, 2_500.0, 5_000.0, 10_000.0,
+];
+const SCOPE: &str = "open-sunsama-desktop";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static EXPORTER: RwLock<Option<Exporter>> = RwLock::new(None);
+static SPANS: Mutex<VecDeque<FinishedSpan>> = Mutex::new(VecDeque::new());
+static METRICS: Mutex<BTreeMap<String, Operation>> = Mutex::new(BTreeMap::new());
+
+/// Where to send, from the network settings
+#[derive(Clone)]
+struct Exporter {
+    endpoint: String,
+    headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum AttributeValue {
+    Text(String),
+    Int(i64),
+    Bool(bool),
+}
+
+/// Kept on a span from creation to close
+struct SpanData {
+    /// Module path without the crate and function name, e.g.
+    /// `sync::crdt::sync`
+    name: String,
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    start_nanos: i64,
+    started: Instant,
+    attributes: Vec<(String, AttributeValue)>,
+    failed: bool,
+}
+
+#[derive(Debug, Clone)]
+struct FinishedSpan {
+    name: String,
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    start_nanos: i64,
+    end_nanos: i64,
+    attributes: Vec<(String, AttributeValue)>,
+    failed: bool,
+}
+
+/// Cumulative figures for one span name since the app started
+#[derive(Debug, Clone)]
+struct Operation {
+    start_nanos: i64,
+    count: u64,
+    errors: u64,
+    sum_ms: f64,
+    /// One more than `BUCKETS_MS`, for everything above the last bound
+    buckets: [u64; BUCKETS_MS.len() + 1],
+}
+
+/// Collects sync and HTTP spans while export is on
+pub struct TelemetryLayer;
+
+impl<S> Layer<S> for TelemetryLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let metadata = attrs.metadata();
+        if !ENABLED.load(Ordering::Relaxed) || !is_exported(metadata.target()) {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.scope().skip(1).find_map(|parent| {
+            let extensions = parent.extensions();
+            extensions
+                .get::<SpanData>()
+                .map(|data| (data.trace_id, data.span_id))
+        });
+
+        let mut fields = Fields(Vec::new());
+        attrs.record(&mut fields);
+        let module = metadata
+            .target()
+            .split_once("::")
+            .map_or(metadata.target(), |(_, module)| module);
+        let data = SpanData {
+            name: format!("{}::{}", module, metadata.name()),
+            trace_id: parent.map_or_else(random_id, |(trace_id, _)| trace_id),
+            span_id: random_id(),
+            parent_span_id: parent.map(|(_, span_id)| span_id),
+            start_nanos: clock::now_millis() * 1_000_000,
+            started: Instant::now(),
+            attributes: fields.0,
+            failed: false,
+        };
+        span.extensions_mut().insert(data);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+            let mut fields = Fields(Vec::new());
+            values.record(&mut fields);
+            for (key, value) in fields.0 {
+                // Clients count 4xx and 5xx answers as errors
+                if key == "http.response.status_code"
+                    && matches!(value, AttributeValue::Int(code) if code >= 400)
+                {
+                    data.failed = true;
+                }
+                data.attributes.retain(|(existing, _)| *existing != key);
+                data.attributes.push((key, value));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        if let Some(span) = ctx.event_span(event) {
+            if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+                data.failed = true;
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(data) = span.extensions_mut().remove::<SpanData>() else {
+            return;
+        };
+        let elapsed = data.started.elapsed();
+        record_operation(&data.name, elapsed, data.failed, data.start_nanos);
+
+        let Ok(mut spans) = SPANS.lock() else {
+            return;
+        };
+        if spans.len() == MAX_QUEUED_SPANS {
+            spans.pop_front();
+        }
+        spans.push_back(FinishedSpan {
+            name: data.name,
+            trace_id: data.trace_id,
+            span_id: data.span_id,
+            parent_span_id: data.parent_span_id,
+            start_nanos: data.start_nanos,
+            end_nanos: data.start_nanos + elapsed.as_nanos() as i64,
+            attributes: data.attributes,
+            failed: data.failed,
+        });
+    }
+}
+
+/// Span fields as OTLP attributes
+struct Fields(Vec<(String, AttributeValue)>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push((
+            field.name().to_string(),
+            AttributeValue::Text(format!("{:?}", value)),
+        ));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((
+            field.name().to_string(),
+            AttributeValue::Text(value.to_string()),
+        ));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0
+            .push((field.name().to_string(), AttributeValue::Int(value)));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_i64(field, i64::try_from(value).unwrap_or(i64::MAX));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0
+            .push((field.name().to_string(), AttributeValue::Bool(value)));
+    }
+}
+
+/// Switch export on or off after the network settings changed. Turning it
+/// off drops whatever was collected.
+pub fn reconfigure(network: &NetworkSettings) {
+    let endpoint = network
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| endpoint.trim().trim_end_matches('/'))
+        .filter(|endpoint| !endpoint.is_empty());
+    let exporter = match endpoint {
+        Some(endpoint) if network.otlp_enabled => Some(Exporter {
+            endpoint: endpoint.to_string(),
+            headers: network.otlp_headers.clone(),
+        }),
+        _ => None,
+    };
+
+    ENABLED.store(exporter.is_some(), Ordering::Relaxed);
+    if exporter.is_none() {
+        if let Ok(mut spans) = SPANS.lock() {
+            spans.clear();
+        }
+        if let Ok(mut metrics) = METRICS.lock() {
+            metrics.clear();
+        }
+    }
+    if let Ok(mut current) = EXPORTER.write() {
+        *current = exporter;
+    }
+}
+
+/// Export on an interval while enabled
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(EXPORT_INTERVAL).await;
+            if let Err(e) = export(&app).await {
+                tracing::warn!("Telemetry export failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn export(app: &AppHandle) -> Result<(), String> {
+    let Some(exporter) = EXPORTER.read().ok().and_then(|exporter| exporter.clone()) else {
+        return Ok(());
+    };
+    let resource = resource(&app.package_info().version.to_string());
+    let spans: Vec<FinishedSpan> = SPANS
+        .lock()
+        .map(|mut spans| spans.drain(..).collect())
+        .unwrap_or_default();
+    let metrics = METRICS
+        .lock()
+        .map(|metrics| metrics.clone())
+        .unwrap_or_default();
+
+    if !spans.is_empty() {
+        post(app, &exporter, "/v1/traces", &traces(&resource, &spans)).await?;
+    }
+    if !metrics.is_empty() {
+        let now = clock::now_millis() * 1_000_000;
+        post(
+            app,
+            &exporter,
+            "/v1/metrics",
+            &metrics_body(&resource, &metrics, now),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn post(
+    app: &AppHandle,
+    exporter: &Exporter,
+    path: &str,
+    body: &Value,
+) -> Result<(), String> {
+    // Straight through the client: going via `http::send` would trace the
+    // export itself
+    let mut request = http::client(app)
+        .post(format!("{}{}", exporter.endpoint, path))
+        .json(body);
+    for (name, value) in &exporter.headers {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("{} returned {}", path, response.status()));
+    }
+    Ok(())
+}
+
+/// Spans from the sync engine and the HTTP layer
+fn is_exported(target: &str) -> bool {
+    target.contains("::sync") || target.contains("::http")
+}
+
+fn record_operation(name: &str, elapsed: Duration, failed: bool, start_nanos: i64) {
+    let Ok(mut metrics) = METRICS.lock() else {
+        return;
+    };
+    let operation = metrics
+        .entry(name.to_string())
+        .or_insert_with(|| Operation {
+            start_nanos,
+            count: 0,
+            errors: 0,
+            sum_ms: 0.0,
+            buckets: [0; BUCKETS_MS.len() + 1],
+        });
+    let ms = elapsed.as_secs_f64() * 1000.0;
+    operation.count += 1;
+    operation.errors += u64::from(failed);
+    operation.sum_ms += ms;
+    let bucket = BUCKETS_MS
+        .iter()
+        .position(|bound| ms <= *bound)
+        .unwrap_or(BUCKETS_MS.len());
+    operation.buckets[bucket] += 1;
+}
+
+fn resource(version: &str) -> Value {
+    json!({
+        "attributes": [
+            attribute("service.name", &AttributeValue::Text(SCOPE.to_string())),
+            attribute("service.version", &AttributeValue::Text(version.to_string())),
+            attribute("os.type", &AttributeValue::Text(std::env::consts::OS.to_string())),
+        ]
+    })
+}
+
+fn traces(resource: &Value, spans: &[FinishedSpan]) -> Value {
+    let spans: Vec<Value> = spans
+        .iter()
+        .map(|span| {
+            let mut value = json!({
+                "traceId": hex(&span.trace_id),
+                "spanId": hex(&span.span_id),
+                "name": span.name,
+                // SPAN_KIND_CLIENT for requests, INTERNAL otherwise
+                "kind": if span.name.starts_with("http::") { 3 } else { 1 },
+                "startTimeUnixNano": span.start_nanos.to_string(),
+                "endTimeUnixNano": span.end_nanos.to_string(),
+                "attributes": span
+                    .attributes
+                    .iter()
+                    .map(|(key, value)| attribute(key, value))
+                    .collect::<Vec<_>>(),
+                // STATUS_CODE_ERROR or UNSET
+                "status": { "code": if span.failed { 2 } else { 0 } },
+            });
+            if let Some(parent) = &span.parent_span_id {
+                value["parentSpanId"] = Value::from(hex(parent));
+            }
+            value
+        })
+        .collect();
+
+    json!({
+        "resourceSpans": [{
+            "resource": resource,
+            "scopeSpans": [{ "scope": { "name": SCOPE }, "spans": spans }],
+        }]
+    })
+}
+
+fn metrics_body(resource: &Value, metrics: &BTreeMap<String, Operation>, now: i64) -> Value {
+    let point = |name: &str, operation: &Operation| {
+        json!({
+            "attributes": [attribute("operation", &AttributeValue::Text(name.to_string()))],
+            "startTimeUnixNano": operation.start_nanos.to_string(),
+            "timeUnixNano": now.to_string(),
+        })
+    };
+    let durations: Vec<Value> = metrics
+        .iter()
+        .map(|(name, operation)| {
+            let mut value = point(name, operation);
+            value["count"] = Value::from(operation.count.to_string());
+            value["sum"] = Value::from(operation.sum_ms);
+            value["bucketCounts"] = operation
+                .buckets
+                .iter()
+                .map(|count| Value::from(count.to_string()))
+                .collect();
+            value["explicitBounds"] = BUCKETS_MS.iter().copied().collect();
+            value
+        })
+        .collect();
+    let errors: Vec<Value> = metrics
+        .iter()
+        .map(|(name, operation)| {
+            let mut value = point(name, operation);
+            value["asInt"] = Value::from(operation.errors.to_string());
+            value
+        })
+        .collect();
+
+    // AGGREGATION_TEMPORALITY_CUMULATIVE
+    json!({
+        "resourceMetrics": [{
+            "resource": resource,
+            "scopeMetrics": [{
+                "scope": { "name": SCOPE },
+                "metrics": [
+                    {
+                        "name": "open_sunsama.operation.duration",
+                        "unit": "ms",
+                        "histogram": { "aggregationTemporality": 2, "dataPoints": durations },
+                    },
+                    {
+                        "name": "open_sunsama.operation.errors",
+                        "sum": {
+                            "aggregationTemporality": 2,
+                            "isMonotonic": true,
+                            "dataPoints": errors,
+                        },
+                    },
+                ],
+            }],
+        }]
+    })
+}
+
+fn attribute(key: &str, value: &AttributeValue) -> Value {
+    let value = match value {
+        AttributeValue::Text(text) => json!({ "stringValue": text }),
+        AttributeValue::Int(int) => json!({ "intValue": int.to_string() }),
+        AttributeValue::Bool(flag) => json!({ "boolValue": flag }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn random_id<const N: usize>() -> [u8; N] {
+    let mut id = [0u8; N];
+    // All-zero ids are invalid in OTLP
+    while id.iter().all(|byte| *byte == 0) {
+        if getrandom::getrandom(&mut id).is_err() {
+            id[0] = 1;
+        }
+    }
+    id
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operation(durations_ms: &[u64], failed: usize) -> Operation {
+        let name = format!("test::op{}", durations_ms.len());
+        for (index, ms) in durations_ms.iter().enumerate() {
+            record_operation(&name, Duration::from_millis(*ms), index < failed, 5);
+        }
+        METRICS.lock().unwrap().remove(&name).unwrap()
+    }
+
+    #[test]
+    fn durations_land_in_their_buckets() {
+        let operation = operation(&[5, 10, 11, 20_000], 1);
+        assert_eq!(operation.count, 4);
+        assert_eq!(operation.errors, 1);
+        assert_eq!(operation.buckets[0], 2);
+        assert_eq!(operation.buckets[1], 1);
+        assert_eq!(operation.buckets[BUCKETS_MS.len()], 1);
+    }
+
+    #[test]
+    fn metrics_are_cumulative_histograms_and_counters() {
+        let metrics = BTreeMap::from([("sync::run_once".to_string(), operation(&[100], 0))]);
+        let body = metrics_body(&resource("1.0.0"), &metrics, 9);
+        let metrics = &body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+
+        let point = &metrics[0]["histogram"]["dataPoints"][0];
+        assert_eq!(point["count"], "1");
+        assert_eq!(point["startTimeUnixNano"], "5");
+        assert_eq!(point["timeUnixNano"], "9");
+        assert_eq!(
+            point["bucketCounts"].as_array().unwrap().len(),
+            BUCKETS_MS.len() + 1
+        );
+        assert_eq!(metrics[1]["sum"]["dataPoints"][0]["asInt"], "0");
+    }
+
+    #[test]
+    fn child_spans_point_at_their_parent() {
+        let span = |span_id: u8, parent: Option<u8>| FinishedSpan {
+            name: "http::send".to_string(),
+            trace_id: [1; 16],
+            span_id: [span_id; 8],
+            parent_span_id: parent.map(|parent| [parent; 8]),
+            start_nanos: 1,
+            end_nanos: 2,
+            attributes: vec![("retries".to_string(), AttributeValue::Int(0))],
+            failed: parent.is_some(),
+        };
+        let body = traces(&resource("1.0.0"), &[span(2, None), span(3, Some(2))]);
+        let spans = &body["resourceSpans"][0]["scopeSpans"][0]["spans"];
+
+        assert!(spans[0].get("parentSpanId").is_none());
+        assert_eq!(spans[1]["parentSpanId"], "0202020202020202");
+        assert_eq!(spans[1]["traceId"], "01".repeat(16));
+        assert_eq!(spans[1]["status"]["code"], 2);
+        assert_eq!(spans[1]["attributes"][0]["value"]["intValue"], "0");
+    }
+}
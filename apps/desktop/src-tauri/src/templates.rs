@@ -0,0 +1,170 @@
+//! Task templates and ritual checklists. Templates live in the local
+//! database; using one creates a task on the server for a given day, with
+//! due dates on the task and its subtasks counted from that day. Checklists
+//! attached to a ritual are created by the daily rollover on working days,
+//! once per day each.
+
+use chrono::{Days, NaiveDate};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::Api;
+use crate::clock;
+use crate::db::templates::{self, Template, TemplateKind};
+use crate::db::Database;
+use crate::settings::PlanningSettings;
+
+pub fn list(app: &AppHandle) -> Result<Vec<Template>, String> {
+    app.state::<Database>()
+        .with_conn(|conn| templates::list(conn))
+}
+
+/// Create (with an empty id) or update a template. Emits
+/// `templates-changed`.
+pub fn save(app: &AppHandle, mut template: Template) -> Result<Template, String> {
+    template.title = template.title.trim().to_string();
+    if template.title.is_empty() {
+        return Err("Templates need a task title".to_string());
+    }
+    template.name = match template.name.trim() {
+        "" => template.title.clone(),
+        name => name.to_string(),
+    };
+    template
+        .subtasks
+        .retain(|subtask| !subtask.title.trim().is_empty());
+    if template.ritual.is_some() && template.kind != TemplateKind::Checklist {
+        return Err("Only checklists can be attached to a ritual".to_string());
+    }
+
+    let now = clock::now_millis();
+    let db = app.state::<Database>();
+    let existing = if template.id.is_empty() {
+        template.id = uuid::Uuid::new_v4().to_string();
+        None
+    } else {
+        db.with_conn(|conn| templates::get(conn, &template.id))?
+    };
+    template.created_at = existing.map_or(now, |existing| existing.created_at);
+    template.updated_at = now;
+
+    db.with_conn(|conn| templates::upsert(conn, &template))?;
+    let _ = app.emit("templates-changed", ());
+    Ok(template)
+}
+
+pub fn delete(app: &AppHandle, id: &str) -> Result<(), String> {
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        templates::delete(&tx, id)?;
+        tx.commit()
+    })?;
+    let _ = app.emit("templates-changed", ());
+    Ok(())
+}
+
+/// Create the template's task and subtasks scheduled for `date`. Returns
+/// the created task and emits `task-created`.
+pub async fn instantiate(app: &AppHandle, id: &str, date: NaiveDate) -> Result<Value, String> {
+    let template = app
+        .state::<Database>()
+        .with_conn(|conn| templates::get(conn, id))?
+        .ok_or_else(|| "Template not found".to_string())?;
+    let task = create(app, &Api::new(app)?, &template, date).await?;
+    let _ = app.emit("task-created", &task);
+    Ok(task)
+}
+
+/// Create today's checklists for the enabled rituals, skipping any already
+/// created for `date`. A checklist that fails is logged and the rest are
+/// still created. Called by the rollover on working days.
+pub async fn create_rituals(
+    app: &AppHandle,
+    api: &Api,
+    planning: &PlanningSettings,
+    date: NaiveDate,
+) -> Result<usize, String> {
+    let day = date.to_string();
+    let due: Vec<Template> = app.state::<Database>().with_conn(|conn| {
+        let mut due = Vec::new();
+        for template in templates::list(conn)? {
+            let enabled = template
+                .ritual
+                .is_some_and(|ritual| ritual.settings(planning).enabled);
+            if enabled && templates::run_task(conn, &template.id, &day)?.is_none() {
+                due.push(template);
+            }
+        }
+        Ok(due)
+    })?;
+
+    let mut created = 0;
+    for template in &due {
+        match create(app, api, template, date).await {
+            Ok(_) => created += 1,
+            Err(e) => tracing::warn!("Failed to create ritual '{}': {}", template.title, e),
+        }
+    }
+    Ok(created)
+}
+
+/// Post the task, then its subtasks, and record the run. A subtask that
+/// fails leaves the task in place without it.
+async fn create(
+    app: &AppHandle,
+    api: &Api,
+    template: &Template,
+    date: NaiveDate,
+) -> Result<Value, String> {
+    let task: Value = api
+        .post(
+            "/tasks",
+            &json!({
+                "title": template.title,
+                "notes": template.notes,
+                "scheduledDate": date.to_string(),
+                "dueDate": template.due_offset_days.and_then(|days| offset(date, days)),
+                "estimatedMins": template.estimated_mins,
+            }),
+        )
+        .await
+        .map_err(|e| format!("Failed to create task from template: {}", e))?;
+    let task_id = task
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "Server returned a task without an id".to_string())?;
+
+    for subtask in &template.subtasks {
+        let body = json!({
+            "title": subtask.title.trim(),
+            "dueDate": subtask.offset_days.and_then(|days| offset(date, days)),
+        });
+        if let Err(e) = api
+            .post::<Value, _>(&format!("/tasks/{}/subtasks", task_id), &body)
+            .await
+        {
+            tracing::warn!("Failed to create subtask '{}': {}", subtask.title, e);
+        }
+    }
+
+    app.state::<Database>().with_conn(|conn| {
+        templates::record_run(
+            conn,
+            &template.id,
+            &date.to_string(),
+            task_id,
+            clock::now_millis(),
+        )
+    })?;
+    Ok(task)
+}
+
+/// `date` moved by `days`, as `YYYY-MM-DD`
+fn offset(date: NaiveDate, days: i64) -> Option<String> {
+    let moved = if days >= 0 {
+        date.checked_add_days(Days::new(days.unsigned_abs()))
+    } else {
+        date.checked_sub_days(Days::new(days.unsigned_abs()))
+    };
+    moved.map(|date| date.to_string())
+}
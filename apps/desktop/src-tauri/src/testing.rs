@@ -0,0 +1,111 @@
+//! Test support: a mock app with its own temporary data directory,
+//! database and manual clock. Code under test needs to be generic over
+//! `tauri::Runtime` to take the mock app's handle; database code takes the
+//! connection and only needs `database`.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, AppHandle, Manager, State};
+use tempfile::TempDir;
+
+use crate::clock::{Clock, ClockState};
+use crate::data_dir::{self, DataDir};
+use crate::db::{Database, DATABASE_FILE};
+
+/// Monday 2 March 2026, 09:00 UTC; where `TestApp` clocks start
+pub const START: i64 = 1_772_442_000_000;
+
+/// An app on the mock runtime with `DataDir` and `Database` managed, both
+/// in a directory removed when it's dropped, and a `ManualClock` as its
+/// `ClockState`
+pub struct TestApp {
+    app: App<MockRuntime>,
+    dir: TempDir,
+    clock: ManualClock,
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let app = mock_builder()
+            .plugin(tauri_plugin_store::Builder::default().build())
+            .build(mock_context(noop_assets()))
+            .expect("mock app");
+        let clock = ManualClock::at(START);
+        app.manage(ClockState::new(clock.clone()));
+        app.manage(DataDir::at(dir.path()));
+        let path = data_dir::get(app.handle()).expect("data dir");
+        app.manage(Database::open(&path.join(DATABASE_FILE), None).expect("database"));
+        Self { app, dir, clock }
+    }
+
+    pub fn handle(&self) -> &AppHandle<MockRuntime> {
+        self.app.handle()
+    }
+
+    pub fn db(&self) -> State<'_, Database> {
+        self.app.state::<Database>()
+    }
+
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    /// Root of the temporary data directory
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+/// A migrated database in a temporary directory, for code that only needs
+/// a connection
+pub fn database() -> (TempDir, Database) {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let db = Database::open(&dir.path().join(DATABASE_FILE), None).expect("database");
+    (dir, db)
+}
+
+/// A `Clock` that only moves when told to. Clones share the time, so a test
+/// keeps one and hands the other to `ClockState`.
+#[derive(Clone)]
+pub struct ManualClock(Arc<Mutex<ManualTime>>);
+
+struct ManualTime {
+    wall: i64,
+    monotonic: Instant,
+}
+
+impl ManualClock {
+    /// Start at `now` (Unix milliseconds)
+    pub fn at(now: i64) -> Self {
+        Self(Arc::new(Mutex::new(ManualTime {
+            wall: now,
+            monotonic: Instant::now(),
+        })))
+    }
+
+    /// Let `millis` pass on both clocks
+    pub fn advance(&self, millis: u64) {
+        let mut time = self.0.lock().unwrap();
+        time.wall += millis as i64;
+        time.monotonic += Duration::from_millis(millis);
+    }
+
+    /// Move the wall clock alone, as when the system time is changed or
+    /// (where monotonic time stops) the machine sleeps
+    pub fn set(&self, now: i64) {
+        self.0.lock().unwrap().wall = now;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_millis(&self) -> i64 {
+        self.0.lock().unwrap().wall
+    }
+
+    fn instant(&self) -> Instant {
+        self.0.lock().unwrap().monotonic
+    }
+}
@@ -0,0 +1,190 @@
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Theme, WindowEvent};
+
+use crate::clock;
+use crate::http::{self, Integration};
+use crate::settings;
+use crate::sun::{self, SunEvents};
+
+pub const AUTO: &str = "auto";
+
+const AUTO_THEME_INTERVAL: Duration = Duration::from_secs(60);
+const IP_LOCATION_URL: &str = "https://ipapi.co/json/";
+
+/// Tracks what the auto-theme scheduler last applied so it only switches
+/// (and emits) at sunrise and sunset.
+#[derive(Default)]
+pub struct AutoThemeState {
+    ip_location: Mutex<Option<(f64, f64)>>,
+    applied: Mutex<Option<Theme>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThemeChange {
+    pub theme: &'static str,
+    pub next_switch_at: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct IpLocation {
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Map the `theme` setting to a native window theme. `None` follows the OS.
+pub fn parse_preference(value: &str) -> Option<Theme> {
+    match value {
+        "light" => Some(Theme::Light),
+        "dark" => Some(Theme::Dark),
+        _ => None,
+    }
+}
+
+pub fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+/// Apply the theme preference to the main window
+pub fn apply(app: &AppHandle, preference: &str) {
+    if preference == AUTO {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = refresh_auto(&app).await;
+        });
+        return;
+    }
+
+    if let Some(state) = app.try_state::<AutoThemeState>() {
+        if let Ok(mut applied) = state.applied.lock() {
+            *applied = None;
+        }
+    }
+    set_window_theme(app, parse_preference(preference));
+}
+
+fn set_window_theme(app: &AppHandle, theme: Option<Theme>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_theme(theme);
+    }
+}
+
+/// Forward OS light/dark switches to the webview as `system-theme-changed`
+pub fn watch_system_theme(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let app = app.clone();
+        window.on_window_event(move |event| {
+            if let WindowEvent::ThemeChanged(theme) = event {
+                let _ = app.emit("system-theme-changed", theme_name(*theme));
+            }
+        });
+    }
+}
+
+/// Re-evaluate the "auto" theme every minute. Cheap, and it naturally
+/// recovers from sleep, travel and settings changes.
+pub fn start_auto_scheduler(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let _ = refresh_auto(&app).await;
+            tokio::time::sleep(AUTO_THEME_INTERVAL).await;
+        }
+    });
+}
+
+/// Switch to light between sunrise and sunset, dark otherwise, emitting
+/// `theme-changed` whenever the applied theme flips.
+pub async fn refresh_auto(app: &AppHandle) -> Result<(), String> {
+    let appearance = settings::load(app)?.appearance;
+    if appearance.theme != AUTO {
+        return Ok(());
+    }
+
+    let (latitude, longitude) = match (appearance.latitude, appearance.longitude) {
+        (Some(latitude), Some(longitude)) => (latitude, longitude),
+        _ => ip_location(app).await?,
+    };
+    let (theme, next_switch_at) = auto_theme_at(clock::now_millis(), latitude, longitude);
+
+    let state = app.state::<AutoThemeState>();
+    {
+        let mut applied = state
+            .applied
+            .lock()
+            .map_err(|_| "Theme state lock poisoned".to_string())?;
+        if *applied == Some(theme) {
+            return Ok(());
+        }
+        *applied = Some(theme);
+    }
+
+    set_window_theme(app, Some(theme));
+    let _ = app.emit(
+        "theme-changed",
+        ThemeChange {
+            theme: theme_name(theme),
+            next_switch_at,
+        },
+    );
+
+    Ok(())
+}
+
+/// Theme for the instant `now` and when it should next change
+fn auto_theme_at(now: i64, latitude: f64, longitude: f64) -> (Theme, Option<i64>) {
+    let today = match Local.timestamp_millis_opt(now).single() {
+        Some(local) => local.date_naive(),
+        None => return (Theme::Light, None),
+    };
+
+    match sun::sun_events(today, latitude, longitude) {
+        SunEvents::PolarDay => (Theme::Light, None),
+        SunEvents::PolarNight => (Theme::Dark, None),
+        SunEvents::Normal { sunrise, .. } if now < sunrise => (Theme::Dark, Some(sunrise)),
+        SunEvents::Normal { sunset, .. } if now < sunset => (Theme::Light, Some(sunset)),
+        SunEvents::Normal { .. } => {
+            let next_sunrise = today
+                .succ_opt()
+                .map(|tomorrow| sun::sun_events(tomorrow, latitude, longitude))
+                .and_then(|events| match events {
+                    SunEvents::Normal { sunrise, .. } => Some(sunrise),
+                    _ => None,
+                });
+            (Theme::Dark, next_sunrise)
+        }
+    }
+}
+
+/// Approximate location from the public IP, cached for the session
+async fn ip_location(app: &AppHandle) -> Result<(f64, f64), String> {
+    let state = app.state::<AutoThemeState>();
+    let cached = state.ip_location.lock().ok().and_then(|location| *location);
+    if let Some(location) = cached {
+        return Ok(location);
+    }
+
+    let request = http::client(app).get(IP_LOCATION_URL);
+    let body = http::send(app, Integration::Geolocation, request)
+        .await?
+        .error_for_status()
+        .map_err(|e| format!("Failed to look up location: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read location response: {}", e))?;
+    let location: IpLocation = serde_json::from_str(&body)
+        .map_err(|e| format!("Failed to parse location response: {}", e))?;
+
+    let location = (location.latitude, location.longitude);
+    if let Ok(mut cached) = state.ip_location.lock() {
+        *cached = Some(location);
+    }
+
+    Ok(location)
+}
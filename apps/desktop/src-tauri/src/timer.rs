@@ -0,0 +1,294 @@
+//! Task timer. The running timer is a `time_entries` row without an end, so
+//! it survives restarts and every surface (webview, shortcut, tray, links,
+//! D-Bus) sees the same state. The webview reports which task is focused;
+//! starting without a task id times that one. Emits `timer-changed` with
+//! the new `TimerStatus` on every start and stop.
+//!
+//! Elapsed time is always the running entry's wall-clock span, never a
+//! count of ticks, so it stays right across sleep. The wall clock is
+//! checked against the monotonic clock every few seconds: when they
+//! disagree the machine slept or the clock was changed, and the running
+//! entry is reconciled and `timer-changed` emitted so views resync.
+//! Monotonic time stops during suspend on Linux and macOS, where the wall
+//! clock pulling ahead is taken as sleep and counted. On Windows it keeps
+//! counting, so any disagreement there is a clock change.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::clock::ClockState;
+use crate::db::time_entries::{self, TimeEntry};
+use crate::db::Database;
+use crate::notifications::{self, Category, Notice};
+
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Disagreement between the clocks beyond scheduling jitter
+const CLOCK_TOLERANCE_MS: i64 = 2_000;
+/// Whether the monotonic clock keeps counting while the machine sleeps
+const MONOTONIC_COUNTS_SUSPEND: bool = cfg!(target_os = "windows");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRef {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerStatus {
+    pub running: Option<TimeEntry>,
+    /// The task the webview has in focus
+    pub current_task: Option<TaskRef>,
+    pub elapsed_ms: i64,
+    /// Unix milliseconds `elapsed_ms` was measured at
+    pub measured_at: i64,
+}
+
+/// Both clocks at the last check
+#[derive(Clone, Copy)]
+struct ClockMark {
+    wall: i64,
+    monotonic: Instant,
+}
+
+#[derive(Default)]
+pub struct TimerState {
+    current_task: Mutex<Option<TaskRef>>,
+    clock: Mutex<Option<ClockMark>>,
+}
+
+impl TimerState {
+    pub fn current_task(&self) -> Option<TaskRef> {
+        self.current_task.lock().ok().and_then(|task| task.clone())
+    }
+
+    pub fn set_current_task(&self, task: Option<TaskRef>) {
+        if let Ok(mut current) = self.current_task.lock() {
+            *current = task;
+        }
+    }
+}
+
+pub fn status<R: Runtime>(app: &AppHandle<R>) -> Result<TimerStatus, String> {
+    let running = app.state::<Database>().with_conn(|conn| time_entries::running(conn))?;
+    let measured_at = app.state::<ClockState>().now_millis();
+    let elapsed_ms = running
+        .as_ref()
+        .map_or(0, |entry| (measured_at - entry.started_at).max(0));
+
+    Ok(TimerStatus {
+        running,
+        current_task: app.state::<TimerState>().current_task(),
+        elapsed_ms,
+        measured_at,
+    })
+}
+
+/// The status after reconciling the clocks, for views that show elapsed
+/// time
+pub fn state<R: Runtime>(app: &AppHandle<R>) -> Result<TimerStatus, String> {
+    reconcile(app)?;
+    status(app)
+}
+
+/// Check the clocks every few seconds, emitting `timer-changed` after a
+/// sleep or clock change
+pub fn start_clock_monitor(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Ok(true) = reconcile(&app) {
+                let _ = publish(&app);
+            }
+            tokio::time::sleep(CLOCK_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Compare the wall clock with the monotonic clock since the last check.
+/// A clock change moves the running entry's start by the same step so its
+/// elapsed time stays what was measured. Returns whether the clocks
+/// disagreed.
+fn reconcile<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    let clock = app.state::<ClockState>();
+    let now = ClockMark {
+        wall: clock.now_millis(),
+        monotonic: clock.instant(),
+    };
+    let previous = match app.state::<TimerState>().clock.lock() {
+        Ok(mut mark) => mark.replace(now),
+        Err(_) => return Ok(false),
+    };
+    let Some(previous) = previous else {
+        return Ok(false);
+    };
+
+    let wall_ms = now.wall - previous.wall;
+    let monotonic_ms = now.monotonic.duration_since(previous.monotonic).as_millis() as i64;
+    let step = wall_ms - monotonic_ms;
+    if step.abs() <= CLOCK_TOLERANCE_MS {
+        return Ok(false);
+    }
+    // Slept: the wall clock kept time and the entry rightly grew
+    if step > 0 && !MONOTONIC_COUNTS_SUSPEND {
+        return Ok(true);
+    }
+
+    app.state::<Database>().with_conn(|conn| {
+        if let Some(running) = time_entries::running(conn)? {
+            if running.started_at < previous.wall {
+                time_entries::set_started_at(conn, &running.id, running.started_at + step)?;
+            }
+        }
+        Ok(())
+    })?;
+    Ok(true)
+}
+
+/// Start timing `task`, or the focused task when `None`. A timer already
+/// running on another task is stopped first.
+pub fn start(app: &AppHandle, task: Option<TaskRef>) -> Result<TimerStatus, String> {
+    let task = task
+        .or_else(|| app.state::<TimerState>().current_task())
+        .ok_or_else(|| "No task selected to time".to_string())?;
+
+    let now = app.state::<ClockState>().now_millis();
+    app.state::<Database>().with_conn(|conn| {
+        if let Some(running) = time_entries::running(conn)? {
+            if running.task_id == task.id {
+                return Ok(());
+            }
+            time_entries::finish(conn, &running.id, now)?;
+        }
+        time_entries::insert(
+            conn,
+            &TimeEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                task_id: task.id.clone(),
+                task_title: task.title.clone(),
+                started_at: now,
+                ended_at: None,
+                updated_at: 0,
+            },
+        )
+    })?;
+
+    publish(app)
+}
+
+/// Stop the running timer, if any, and return the finished entry
+pub fn stop(app: &AppHandle) -> Result<Option<TimeEntry>, String> {
+    let _ = reconcile(app);
+    let now = app.state::<ClockState>().now_millis();
+    let finished = app.state::<Database>().with_conn(|conn| {
+        let Some(mut running) = time_entries::running(conn)? else {
+            return Ok(None);
+        };
+        time_entries::finish(conn, &running.id, now)?;
+        running.ended_at = Some(now);
+        Ok(Some(running))
+    })?;
+
+    if finished.is_some() {
+        publish(app)?;
+    }
+    Ok(finished)
+}
+
+/// Stop the running timer, or start one on the focused task
+pub fn toggle(app: &AppHandle) -> Result<TimerStatus, String> {
+    if stop(app)?.is_some() {
+        status(app)
+    } else {
+        start(app, None)
+    }
+}
+
+/// Toggle from outside the window (global shortcut, tray) and confirm the
+/// new state with a notification, since the window may not be visible
+pub fn toggle_with_notification(app: &AppHandle) {
+    let (title, body) = match stop(app) {
+        Ok(Some(entry)) => {
+            let minutes = (entry.ended_at.unwrap_or(entry.started_at) - entry.started_at) / 60_000;
+            ("Timer stopped".to_string(), format!("{} · {} min", entry.task_title, minutes))
+        }
+        Ok(None) => match start(app, None) {
+            Ok(status) => (
+                "Timer started".to_string(),
+                status.running.map(|entry| entry.task_title).unwrap_or_default(),
+            ),
+            Err(e) => ("Couldn't start the timer".to_string(), e),
+        },
+        Err(e) => ("Couldn't stop the timer".to_string(), e),
+    };
+
+    let _ = notifications::notify(app, Notice::new(Category::Feedback, title).body(body));
+}
+
+fn publish(app: &AppHandle) -> Result<TimerStatus, String> {
+    let status = status(app)?;
+    let _ = app.emit("timer-changed", &status);
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TestApp, START};
+
+    /// An app with a timer running since a minute before `START`, and the
+    /// clocks first compared at `START`
+    fn running_timer() -> TestApp {
+        let app = TestApp::new();
+        app.handle().manage(TimerState::default());
+        app.db()
+            .with_conn(|conn| {
+                time_entries::insert(
+                    conn,
+                    &TimeEntry {
+                        id: "entry".to_string(),
+                        task_id: "task".to_string(),
+                        task_title: "Task".to_string(),
+                        started_at: START - 60_000,
+                        ended_at: None,
+                        updated_at: 0,
+                    },
+                )
+            })
+            .unwrap();
+        assert_eq!(state(app.handle()).unwrap().elapsed_ms, 60_000);
+        app
+    }
+
+    #[test]
+    fn clock_changes_keep_the_elapsed_time() {
+        let app = running_timer();
+        app.clock().advance(60_000);
+        app.clock().set(START + 60_000 - 3_600_000);
+
+        let status = state(app.handle()).unwrap();
+        assert_eq!(status.elapsed_ms, 120_000);
+        assert_eq!(status.measured_at, START + 60_000 - 3_600_000);
+    }
+
+    #[test]
+    fn small_drift_is_left_alone() {
+        let app = running_timer();
+        app.clock().advance(30_000);
+        app.clock().set(START + 31_000);
+
+        assert_eq!(state(app.handle()).unwrap().elapsed_ms, 91_000);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn sleep_counts_towards_the_running_entry() {
+        let app = running_timer();
+        app.clock().set(START + 3_600_000);
+
+        assert_eq!(state(app.handle()).unwrap().elapsed_ms, 3_660_000);
+    }
+}
@@ -0,0 +1,178 @@
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use opensunsama_core::timezone::local_date;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::{reminders, time_blocks, Database};
+use crate::{rituals, settings};
+
+pub use opensunsama_core::timezone::day_bounds;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimezoneChange {
+    pub previous: String,
+    pub current: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebaseSummary {
+    pub timezone: String,
+    pub time_blocks: usize,
+    pub reminders: usize,
+}
+
+/// Last observed OS timezone
+pub struct TimezoneState {
+    current: Mutex<String>,
+}
+
+impl TimezoneState {
+    pub fn new() -> Self {
+        Self {
+            current: Mutex::new(detect()),
+        }
+    }
+
+    pub fn current(&self) -> String {
+        self.current.lock().map(|tz| tz.clone()).unwrap_or_else(|_| detect())
+    }
+}
+
+/// IANA name of the OS timezone, falling back to UTC
+pub fn detect() -> String {
+    iana_time_zone::get_timezone().unwrap_or_else(|_| "UTC".to_string())
+}
+
+pub fn parse(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>()
+        .map_err(|e| format!("Invalid timezone '{}': {}", name, e))
+}
+
+/// Poll the OS timezone and emit `timezone-changed` when it moves. There is no
+/// portable change notification, and a minute of latency is fine for travel.
+pub fn start_watcher(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let detected = detect();
+            let state = app.state::<TimezoneState>();
+            let previous = match state.current.lock() {
+                Ok(mut current) if *current != detected => {
+                    std::mem::replace(&mut *current, detected.clone())
+                }
+                _ => continue,
+            };
+
+            // Ritual reminders keep their local times in the new zone
+            if let Ok(settings) = settings::load(&app) {
+                let _ = rituals::schedule(&app, &settings.planning);
+            }
+
+            let _ = app.emit(
+                "timezone-changed",
+                TimezoneChange {
+                    previous,
+                    current: detected,
+                },
+            );
+        }
+    });
+}
+
+/// Re-anchor today's time blocks and pending reminders to `target`, keeping
+/// their local wall-clock times. A 9:00 block planned at home stays a 9:00
+/// block after landing instead of silently moving with the UTC instant.
+pub fn rebase_day(db: &Database, target_name: &str, now: i64) -> Result<RebaseSummary, String> {
+    let target = parse(target_name)?;
+    let today = local_date(now, target);
+
+    // Today in any origin timezone falls within a day either side of today
+    // in the target timezone.
+    let window_start = now - 2 * DAY_MILLIS;
+    let window_end = now + 2 * DAY_MILLIS;
+
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        let mut summary = RebaseSummary {
+            timezone: target_name.to_string(),
+            time_blocks: 0,
+            reminders: 0,
+        };
+
+        for block in time_blocks::list_between(&tx, window_start, window_end)? {
+            if block.timezone == target_name {
+                continue;
+            }
+            let Ok(origin) = block.timezone.parse::<Tz>() else {
+                continue;
+            };
+            if local_date(block.start_at, origin) != today {
+                continue;
+            }
+            let (Some(start_at), Some(end_at)) = (
+                reanchor(block.start_at, origin, target),
+                reanchor(block.end_at, origin, target),
+            ) else {
+                continue;
+            };
+
+            time_blocks::reschedule(&tx, &block.id, start_at, end_at, target_name)?;
+            summary.time_blocks += 1;
+        }
+
+        for reminder in reminders::list_pending(&tx)? {
+            if reminder.timezone == target_name
+                || reminder.fire_at < window_start
+                || reminder.fire_at > window_end
+            {
+                continue;
+            }
+            let Ok(origin) = reminder.timezone.parse::<Tz>() else {
+                continue;
+            };
+            if local_date(reminder.fire_at, origin) != today {
+                continue;
+            }
+            let Some(fire_at) = reanchor(reminder.fire_at, origin, target) else {
+                continue;
+            };
+
+            reminders::reschedule(&tx, &reminder.id, fire_at, target_name)?;
+            summary.reminders += 1;
+        }
+
+        tx.commit()?;
+        Ok(summary)
+    })
+}
+
+/// The OS timezone, falling back to UTC if it isn't a known IANA zone
+pub fn local() -> Tz {
+    parse(&detect()).unwrap_or(Tz::UTC)
+}
+
+fn local_datetime(millis: i64, tz: Tz) -> Option<NaiveDateTime> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(|utc| utc.with_timezone(&tz).naive_local())
+}
+
+/// Same wall-clock time, different zone. Times that fall into a DST gap in
+/// the target zone are skipped rather than guessed.
+fn reanchor(millis: i64, origin: Tz, target: Tz) -> Option<i64> {
+    let local = local_datetime(millis, origin)?;
+    target
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|dt| dt.timestamp_millis())
+}
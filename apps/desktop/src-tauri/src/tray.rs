@@ -1,32 +1,78 @@
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
+    menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter, Manager,
+    AppHandle, Emitter, Manager, Window,
 };
+use tauri_plugin_store::StoreExt;
+
+use crate::menu::MenuRegistry;
+use crate::notifications::{self, Category, Notice};
+use crate::{data_dir, profiles, quick_complete, recent_tasks, settings, views};
+
+/// Remembers that the user has been told where the window went
+const TRAY_STORE: &str = "tray.json";
+const HIDE_HINT_KEY: &str = "hideHintShown";
 
 pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     let new_task = MenuItem::with_id(app, "new_task", "New Task", true, Some("CmdOrCtrl+Shift+T"))?;
-    let today = MenuItem::with_id(app, "today", "Today View", true, None::<&str>)?;
-    let calendar = MenuItem::with_id(app, "calendar", "Calendar", true, None::<&str>)?;
+    let view_items = views::VIEWS
+        .iter()
+        .filter(|view| view.in_tray)
+        .map(|view| MenuItem::with_id(app, views::menu_id(view), view.label, true, None::<&str>))
+        .collect::<Result<Vec<_>, _>>()?;
+    let focus_session =
+        MenuItem::with_id(app, "focus_session", "Start Focus Session", true, None::<&str>)?;
+    let complete_task =
+        MenuItem::with_id(app, "complete_task", "Complete Current Task", true, None::<&str>)?;
+    let undo_complete = MenuItem::with_id(
+        app,
+        quick_complete::UNDO_MENU_ID,
+        "Undo Complete",
+        false,
+        None::<&str>,
+    )?;
+    let open_recent = Submenu::with_id(app, "open_recent", "Open Recent", true)?;
+    let profiles_menu = Submenu::with_id(app, "profiles", "Profile", true)?;
     let separator1 = PredefinedMenuItem::separator(app)?;
     let show_hide = MenuItem::with_id(app, "show_hide", "Show/Hide Window", true, Some("CmdOrCtrl+Shift+O"))?;
     let separator2 = PredefinedMenuItem::separator(app)?;
     let settings = MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
+    let check_updates =
+        MenuItem::with_id(app, "check_updates", "Check for Updates...", true, None::<&str>)?;
     let quit = MenuItem::with_id(app, "quit", "Quit Open Sunsama", true, Some("CmdOrCtrl+Q"))?;
 
-    let menu = Menu::with_items(
-        app,
-        &[
-            &new_task,
-            &today,
-            &calendar,
-            &separator1,
-            &show_hide,
-            &separator2,
-            &settings,
-            &quit,
-        ],
-    )?;
+    let mut items: Vec<&dyn IsMenuItem<_>> = vec![&new_task];
+    items.extend(view_items.iter().map(|item| item as &dyn IsMenuItem<_>));
+    items.extend([
+        &focus_session as &dyn IsMenuItem<_>,
+        &complete_task,
+        &undo_complete,
+        &open_recent,
+        &separator1,
+        &show_hide,
+        &separator2,
+        &profiles_menu,
+        &settings,
+        &check_updates,
+        &quit,
+    ]);
+
+    let menu = Menu::with_items(app, &items)?;
+
+    let registry = app.state::<MenuRegistry>();
+    registry.register(&[
+        &new_task,
+        &focus_session,
+        &complete_task,
+        &undo_complete,
+        &show_hide,
+        &settings,
+        &check_updates,
+        &quit,
+    ]);
+    registry.register(&view_items.iter().collect::<Vec<_>>());
+    recent_tasks::register_submenu(app.handle(), open_recent)?;
+    profiles::register_submenu(app.handle(), profiles_menu)?;
 
     let _tray = TrayIconBuilder::new()
         .icon(app.default_window_icon().unwrap().clone())
@@ -40,19 +86,22 @@ pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                     let _ = window.emit("quick-add-task", ());
                 }
             }
-            "today" => {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                    let _ = window.emit("navigate", "/app");
+            id if id.starts_with(views::MENU_ID_PREFIX) => {
+                if let Some(view) = views::from_menu_id(id) {
+                    views::navigate(app, view);
                 }
             }
-            "calendar" => {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                    let _ = window.emit("navigate", "/app/calendar");
-                }
+            recent_tasks::CLEAR_MENU_ID => {
+                let _ = recent_tasks::clear(app);
+            }
+            id if id.starts_with(recent_tasks::MENU_ID_PREFIX) => recent_tasks::open(app, id),
+            id if id.starts_with(profiles::MENU_ID_PREFIX) => profiles::select(app, id),
+            // "focus_session" and "check_updates" are handled with the app
+            // menu's events, which include the tray's; handling them here
+            // too would toggle twice and check twice
+            "complete_task" => quick_complete::complete_with_notification(app),
+            quick_complete::UNDO_MENU_ID => {
+                let _ = quick_complete::undo(app);
             }
             "show_hide" => {
                 if let Some(window) = app.get_webview_window("main") {
@@ -71,9 +120,7 @@ pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                     let _ = window.emit("navigate", "/app/settings");
                 }
             }
-            "quit" => {
-                std::process::exit(0);
-            }
+            "quit" => app.exit(0),
             _ => {}
         })
         .on_tray_icon_event(|tray, event| {
@@ -98,3 +145,32 @@ pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Whether closing or minimizing the main window should hide it to the tray
+pub fn hides_to_tray(app: &AppHandle) -> bool {
+    settings::load(app).is_ok_and(|s| s.general.minimize_to_tray)
+}
+
+/// Hide the main window to the tray. The first time, a notification says
+/// where it went and how to quit for real.
+pub fn hide_to_tray(window: &Window) {
+    let _ = window.hide();
+
+    let app = window.app_handle();
+    let Some(store) = data_dir::store_path(app, TRAY_STORE)
+        .ok()
+        .and_then(|path| app.store(path).ok())
+    else {
+        return;
+    };
+    if store.get(HIDE_HINT_KEY).is_some_and(|shown| shown.as_bool() == Some(true)) {
+        return;
+    }
+    let _ = notifications::notify(
+        app,
+        Notice::new(Category::System, "Open Sunsama is still running")
+            .body("It's in the menu bar / system tray. Choose Quit Open Sunsama there to exit."),
+    );
+    store.set(HIDE_HINT_KEY, true);
+    let _ = store.save();
+}
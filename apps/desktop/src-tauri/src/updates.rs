@@ -0,0 +1,131 @@
+//! Manual update checks from the Help and tray menus. Progress is reported
+//! to the webview as events; a downloaded update is held until the user
+//! chooses to restart.
+//!
+//! Events: `update-checking`, `update-available`, `update-not-available`,
+//! `update-download-progress`, `update-ready`, `update-error`.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::notifications::{self, Category, Notice};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadProgress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct UpdateState {
+    checking: AtomicBool,
+    /// A downloaded update waiting for restart
+    ready: Mutex<Option<(Update, Vec<u8>)>>,
+}
+
+/// Check for an update and, if there is one, download it in the background.
+/// Returns `None` when already up to date.
+pub async fn check(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let state = app.state::<UpdateState>();
+    if state.checking.swap(true, Ordering::SeqCst) {
+        return Err("Already checking for updates".to_string());
+    }
+    let result = check_and_download(app).await;
+    state.checking.store(false, Ordering::SeqCst);
+
+    if let Err(e) = &result {
+        let _ = app.emit("update-error", e);
+    }
+    result
+}
+
+/// Check from a menu item, where there is no caller to return a result to:
+/// also confirm natively when nothing was found
+pub fn check_from_menu(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let message = match check(&app).await {
+            Ok(Some(info)) => format!("Downloading Open Sunsama {}", info.version),
+            Ok(None) => format!("Open Sunsama {} is the latest version", app.package_info().version),
+            Err(e) => format!("Couldn't check for updates: {}", e),
+        };
+        let _ = notifications::notify(&app, Notice::new(Category::System, "Software Update").body(message));
+    });
+}
+
+/// Install the downloaded update and restart into it
+pub fn install_and_restart(app: &AppHandle) -> Result<(), String> {
+    let (update, bytes) = app
+        .state::<UpdateState>()
+        .ready
+        .lock()
+        .map_err(|_| "Update state poisoned".to_string())?
+        .take()
+        .ok_or_else(|| "No update has been downloaded".to_string())?;
+
+    update
+        .install(bytes)
+        .map_err(|e| format!("Failed to install update: {}", e))?;
+    app.restart()
+}
+
+async fn check_and_download(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let _ = app.emit("update-checking", ());
+
+    let update = app
+        .updater()
+        .map_err(|e| format!("Updater unavailable: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Update check failed: {}", e))?;
+
+    let Some(update) = update else {
+        let _ = app.emit("update-not-available", ());
+        return Ok(None);
+    };
+
+    let info = UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        notes: update.body.clone(),
+        date: update.date.map(|date| date.to_string()),
+    };
+    let _ = app.emit("update-available", &info);
+
+    let mut downloaded = 0u64;
+    let progress_app = app.clone();
+    let bytes = update
+        .download(
+            move |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = progress_app.emit("update-download-progress", DownloadProgress { downloaded, total });
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| format!("Failed to download update: {}", e))?;
+
+    if let Ok(mut ready) = app.state::<UpdateState>().ready.lock() {
+        *ready = Some((update, bytes));
+    }
+    let _ = app.emit("update-ready", &info);
+    Ok(Some(info))
+}
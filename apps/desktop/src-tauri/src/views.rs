@@ -0,0 +1,78 @@
+//! Views the app can navigate to from native UI. The View menu, the tray
+//! menu and the frontend (via `get_nav_views`) all read this one list, so
+//! adding a route here adds it everywhere.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Prefix for menu item ids that navigate to a view
+pub const MENU_ID_PREFIX: &str = "view:";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavView {
+    pub id: &'static str,
+    pub label: &'static str,
+    /// Frontend route emitted with `navigate`
+    pub route: &'static str,
+    pub accelerator: Option<&'static str>,
+    /// Whether the view is also listed in the tray menu
+    pub in_tray: bool,
+}
+
+pub const VIEWS: &[NavView] = &[
+    NavView {
+        id: "today",
+        label: "Today",
+        route: "/app",
+        accelerator: Some("CmdOrCtrl+1"),
+        in_tray: true,
+    },
+    NavView {
+        id: "calendar",
+        label: "Calendar",
+        route: "/app/calendar",
+        accelerator: Some("CmdOrCtrl+2"),
+        in_tray: true,
+    },
+    NavView {
+        id: "week",
+        label: "Week",
+        route: "/app/week",
+        accelerator: Some("CmdOrCtrl+3"),
+        in_tray: true,
+    },
+    NavView {
+        id: "backlog",
+        label: "Backlog",
+        route: "/app/backlog",
+        accelerator: Some("CmdOrCtrl+4"),
+        in_tray: true,
+    },
+    NavView {
+        id: "archive",
+        label: "Archive",
+        route: "/app/archive",
+        accelerator: None,
+        in_tray: false,
+    },
+];
+
+pub fn menu_id(view: &NavView) -> String {
+    format!("{}{}", MENU_ID_PREFIX, view.id)
+}
+
+/// The view a menu item id refers to
+pub fn from_menu_id(id: &str) -> Option<&'static NavView> {
+    let id = id.strip_prefix(MENU_ID_PREFIX)?;
+    VIEWS.iter().find(|view| view.id == id)
+}
+
+/// Bring the main window forward and navigate it to `view`
+pub fn navigate(app: &AppHandle, view: &NavView) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.emit("navigate", view.route);
+    }
+}
@@ -0,0 +1,252 @@
+//! Weekly review: on the configured day and time, summarize the week so far
+//! (completed tasks, tracked time by channel, planned against tracked
+//! time, progress on the week's objectives), write it as Markdown and PDF
+//! under `reports/` in the data directory and notify. A review missed while
+//! the app was closed, asleep or offline is generated on the next check
+//! that week.
+
+use chrono::{Datelike, Days, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::analytics::{self, PeriodSummary};
+use crate::api::Api;
+use crate::connectivity::ConnectivityState;
+use crate::notifications::{self, Category, Notice};
+use crate::objectives::{self, ObjectiveProgress};
+use crate::pdf::{self, Block};
+use crate::{clock, data_dir, settings, timezone};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const STORE: &str = "weekly-review.json";
+/// Monday of the last week reviewed
+const LAST_REVIEW_KEY: &str = "lastReview";
+const REPORTS_DIR: &str = "reports";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyReview {
+    pub summary: PeriodSummary,
+    pub objectives: Vec<ObjectiveProgress>,
+    pub markdown_path: String,
+    pub pdf_path: String,
+}
+
+/// Check for a due review every minute; generated reviews emit
+/// `weekly-review-ready`
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_if_due(&app).await {
+                tracing::warn!("Weekly review failed: {}", e);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Monday of the week containing `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
+
+/// The local date today
+pub fn today() -> NaiveDate {
+    Utc::now().with_timezone(&timezone::local()).date_naive()
+}
+
+/// Summarize the week starting `monday` and write its reports
+pub async fn generate(app: &AppHandle, monday: NaiveDate) -> Result<WeeklyReview, String> {
+    let summary = analytics::summarize(app, monday, 7).await?;
+    let objectives = objectives::progress(app, monday).await?;
+    let blocks = compose(&summary, &objectives);
+
+    let dir = data_dir::get(app)?.join(REPORTS_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create reports directory: {}", e))?;
+    let name = format!("weekly-review-{}", summary.start);
+    let markdown_path: PathBuf = dir.join(format!("{}.md", name));
+    let pdf_path: PathBuf = dir.join(format!("{}.pdf", name));
+
+    std::fs::write(&markdown_path, markdown(&blocks))
+        .map_err(|e| format!("Failed to write report: {}", e))?;
+    pdf::write(&pdf_path, &title(&summary), &blocks)?;
+
+    Ok(WeeklyReview {
+        summary,
+        objectives,
+        markdown_path: markdown_path.to_string_lossy().into_owned(),
+        pdf_path: pdf_path.to_string_lossy().into_owned(),
+    })
+}
+
+/// Generate this week's review once the configured day and time have
+/// passed, unless one already exists for this week. The review is built
+/// from the server's tasks, so it's left for a later check while signed
+/// out or offline, and only marked done once it was written.
+async fn run_if_due(app: &AppHandle) -> Result<(), String> {
+    let planning = settings::load(app)?.planning;
+    if !planning.weekly_review.enabled {
+        return Ok(());
+    }
+
+    let tz = timezone::local();
+    let monday = week_start(today());
+    let review_day = monday + Days::new(u64::from(planning.weekly_review_day.clamp(1, 7) - 1));
+    let Ok(time) = NaiveTime::parse_from_str(&planning.weekly_review.time, "%H:%M") else {
+        return Ok(());
+    };
+    let due = tz
+        .from_local_datetime(&review_day.and_time(time))
+        .earliest()
+        .is_some_and(|at| at.timestamp_millis() <= clock::now_millis());
+    if !due || last_review(app)?.is_some_and(|last| last >= monday) {
+        return Ok(());
+    }
+    if !app.state::<ConnectivityState>().is_online() || Api::new(app).is_err() {
+        return Ok(());
+    }
+
+    let review = generate(app, monday).await?;
+    set_last_review(app, monday)?;
+
+    let _ = notifications::notify(
+        app,
+        Notice::new(Category::Review, "Your weekly review is ready").body(format!(
+            "You completed {} of {} planned tasks and tracked {} this week.",
+            review.summary.completed.len(),
+            review.summary.planned_tasks,
+            short_minutes(review.summary.tracked_minutes)
+        )),
+    );
+    let _ = app.emit("weekly-review-ready", &review);
+    Ok(())
+}
+
+fn title(summary: &PeriodSummary) -> String {
+    format!("Weekly review, {} to {}", summary.start, summary.end)
+}
+
+fn compose(summary: &PeriodSummary, objectives: &[ObjectiveProgress]) -> Vec<Block> {
+    let mut blocks = vec![
+        Block::Heading(title(summary)),
+        Block::Blank,
+        Block::Paragraph(format!(
+            "Completed {} of {} planned tasks.",
+            summary.completed.len(),
+            summary.planned_tasks
+        )),
+        Block::Paragraph(format!(
+            "Planned {}, tracked {}{}.",
+            short_minutes(summary.planned_minutes),
+            short_minutes(summary.tracked_minutes),
+            match summary.planned_minutes {
+                0 => String::new(),
+                planned => format!(" ({}% of plan)", summary.tracked_minutes * 100 / planned),
+            }
+        )),
+        Block::Blank,
+        Block::Heading("Time by channel".to_string()),
+    ];
+
+    if summary.tracked_by_channel.is_empty() {
+        blocks.push(Block::Paragraph("No time tracked.".to_string()));
+    }
+    let mut channels: Vec<(&String, &i64)> = summary.tracked_by_channel.iter().collect();
+    channels.sort_by(|a, b| b.1.cmp(a.1));
+    for (channel, minutes) in channels {
+        blocks.push(Block::Paragraph(format!("- {}: {}", channel, short_minutes(*minutes))));
+    }
+
+    if !objectives.is_empty() {
+        blocks.push(Block::Blank);
+        blocks.push(Block::Heading("Objectives".to_string()));
+    }
+    for progress in objectives {
+        let objective = &progress.objective;
+        let tasks = match progress.completed_tasks {
+            Some(done) => format!("{} of {} tasks done", done, objective.task_ids.len()),
+            None => format!("{} tasks", objective.task_ids.len()),
+        };
+        let time = match objective.target_minutes {
+            Some(target) => format!(
+                "{} of {} tracked",
+                short_minutes(progress.tracked_minutes),
+                short_minutes(target)
+            ),
+            None => format!("{} tracked", short_minutes(progress.tracked_minutes)),
+        };
+        blocks.push(Block::Paragraph(format!(
+            "- {}{}: {}, {}",
+            objective.title,
+            if objective.done_at.is_some() { " (achieved)" } else { "" },
+            tasks,
+            time
+        )));
+    }
+
+    blocks.push(Block::Blank);
+    blocks.push(Block::Heading("Completed tasks".to_string()));
+    if summary.completed.is_empty() {
+        blocks.push(Block::Paragraph("Nothing completed.".to_string()));
+    }
+    for task in &summary.completed {
+        let channel = task
+            .channel
+            .as_ref()
+            .map(|channel| format!(" #{}", channel))
+            .unwrap_or_default();
+        blocks.push(Block::Paragraph(format!(
+            "- {}{} ({})",
+            task.title, channel, task.completed_on
+        )));
+    }
+
+    blocks
+}
+
+fn markdown(blocks: &[Block]) -> String {
+    let mut lines: Vec<String> = blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| match block {
+            Block::Heading(text) if index == 0 => format!("# {}", text),
+            Block::Heading(text) => format!("## {}", text),
+            Block::Paragraph(text) => text.clone(),
+            Block::Blank => String::new(),
+        })
+        .collect();
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn short_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{} min", m),
+        (h, 0) => format!("{} h", h),
+        (h, m) => format!("{} h {} min", h, m),
+    }
+}
+
+fn last_review(app: &AppHandle) -> Result<Option<NaiveDate>, String> {
+    let store = app
+        .store(data_dir::store_path(app, STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    Ok(store
+        .get(LAST_REVIEW_KEY)
+        .and_then(|value| value.as_str().and_then(|date| date.parse().ok())))
+}
+
+fn set_last_review(app: &AppHandle, monday: NaiveDate) -> Result<(), String> {
+    let store = app
+        .store(data_dir::store_path(app, STORE)?)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store.set(LAST_REVIEW_KEY, monday.to_string());
+    store
+        .save()
+        .map_err(|e| format!("Failed to save weekly review: {}", e))
+}
@@ -0,0 +1,94 @@
+//! Native window materials: the Windows 11 Mica and Windows 10/11 acrylic
+//! backdrops, plus the macOS hidden-inset titlebar. The main window is
+//! transparent on Windows, so the webview paints its own background unless
+//! an effect is on. macOS vibrancy would need a transparent webview, which
+//! only the private API allows and the App Store rejects, so it isn't offered.
+
+use serde::{Deserialize, Serialize};
+use tauri::window::{Effect, EffectState, EffectsBuilder};
+use tauri::{AppHandle, Manager};
+
+use crate::settings::AppearanceSettings;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowEffect {
+    #[default]
+    None,
+    /// macOS; kept so saved settings still parse, but never offered
+    Vibrancy,
+    /// Windows 11
+    Mica,
+    /// Windows 10 and 11
+    Acrylic,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TitleBar {
+    #[default]
+    Default,
+    /// Traffic lights over the content, no titlebar strip (macOS)
+    HiddenInset,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowEffectSupport {
+    pub effects: Vec<WindowEffect>,
+    pub title_bars: Vec<TitleBar>,
+}
+
+/// What the settings UI can offer on this platform
+pub fn support() -> WindowEffectSupport {
+    let mut effects = vec![WindowEffect::None];
+    let mut title_bars = vec![TitleBar::Default];
+    if cfg!(target_os = "macos") {
+        title_bars.push(TitleBar::HiddenInset);
+    }
+    if cfg!(windows) {
+        effects.extend([WindowEffect::Mica, WindowEffect::Acrylic]);
+    }
+    WindowEffectSupport { effects, title_bars }
+}
+
+/// Apply the appearance settings' effect and titlebar to the main window.
+/// Options the platform doesn't support are ignored.
+pub fn apply(app: &AppHandle, appearance: &AppearanceSettings) -> Result<(), String> {
+    let Some(window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    let supported = support();
+
+    let effect = match appearance.window_effect {
+        effect if !supported.effects.contains(&effect) => None,
+        WindowEffect::None => None,
+        WindowEffect::Vibrancy => Some(Effect::Sidebar),
+        WindowEffect::Mica => Some(Effect::Mica),
+        WindowEffect::Acrylic => Some(Effect::Acrylic),
+    };
+    let effects = effect.map(|effect| {
+        EffectsBuilder::new()
+            .effect(effect)
+            .state(EffectState::FollowsWindowActiveState)
+            .build()
+    });
+    window
+        .set_effects(effects)
+        .map_err(|e| format!("Failed to set window effect: {}", e))?;
+
+    #[cfg(target_os = "macos")]
+    {
+        use tauri::TitleBarStyle;
+
+        let style = match appearance.title_bar {
+            TitleBar::Default => TitleBarStyle::Visible,
+            TitleBar::HiddenInset => TitleBarStyle::Overlay,
+        };
+        window
+            .set_title_bar_style(style)
+            .map_err(|e| format!("Failed to set titlebar style: {}", e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,343 @@
+//! Windows besides the menus and tray.
+//!
+//! The main window is declared in tauri.conf.json but created here, so
+//! that, like every window, it gets the active profile's webview storage.
+//!
+//! The timer widget is a tiny frameless window pinned above everything,
+//! showing the running timer and current task; the webview renders it at
+//! `/timer-widget` and moves it through its drag region. With click-through
+//! on, clicks pass to whatever is underneath. It opens by itself when a focus
+//! session starts and closes when the session ends.
+//!
+//! Task windows pop a single task out of the main window (rendered at
+//! `/task-window/<id>`). They're listed in the Window menu and the open set
+//! is saved, so they come back on the next launch.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{
+    menu::{MenuItem, PredefinedMenuItem, Submenu},
+    AppHandle, Emitter, LogicalPosition, Manager, WebviewUrl, WebviewWindowBuilder, Wry,
+};
+
+use crate::profiles::DEFAULT_PROFILE;
+use crate::{data_dir, recent_tasks, settings, shutdown};
+
+/// Webview storage of a profile, inside its data directory
+const WEBVIEW_DIR: &str = "webview";
+
+pub const TIMER_WIDGET_LABEL: &str = "timer-widget";
+const TIMER_WIDGET_ROUTE: &str = "/timer-widget";
+const TIMER_WIDGET_WIDTH: f64 = 240.0;
+const TIMER_WIDGET_HEIGHT: f64 = 64.0;
+/// Distance from the screen edges
+const TIMER_WIDGET_MARGIN: f64 = 16.0;
+
+/// Window labels of task windows start with this
+const TASK_WINDOW_LABEL_PREFIX: &str = "task-";
+/// Prefix for Window menu item ids that focus a task window
+pub const TASK_MENU_ID_PREFIX: &str = "task-window:";
+const TASK_WINDOWS_FILE: &str = "task-windows.json";
+const TASK_WINDOW_WIDTH: f64 = 480.0;
+const TASK_WINDOW_HEIGHT: f64 = 640.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskWindow {
+    pub label: String,
+    pub task_id: String,
+    pub title: String,
+}
+
+#[derive(Default)]
+pub struct TaskWindowsState {
+    open: Mutex<Vec<TaskWindow>>,
+    /// The Window menu and how many fixed items it starts with
+    window_menu: Mutex<Option<(Submenu<Wry>, usize)>>,
+}
+
+/// Create the main window from its config entry
+pub fn create_main_window(app: &AppHandle) -> Result<(), String> {
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|window| window.label == "main")
+        .cloned()
+        .ok_or_else(|| "Main window missing from the config".to_string())?;
+    let builder = WebviewWindowBuilder::from_config(app, &config)
+        .map_err(|e| format!("Failed to create main window: {}", e))?;
+    // Mica and acrylic show through a transparent window only
+    #[cfg(windows)]
+    let builder = builder.transparent(true);
+    with_profile_storage(app, builder)
+        .build()
+        .map_err(|e| format!("Failed to create main window: {}", e))?;
+    Ok(())
+}
+
+/// Show the timer widget, creating it in the top-right corner of the main
+/// window's screen the first time
+pub fn open_timer_widget(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(TIMER_WIDGET_LABEL) {
+        return window
+            .show()
+            .map_err(|e| format!("Failed to show timer widget: {}", e));
+    }
+
+    let mut builder =
+        WebviewWindowBuilder::new(app, TIMER_WIDGET_LABEL, WebviewUrl::App(TIMER_WIDGET_ROUTE.into()))
+            .title("Open Sunsama Timer")
+            .inner_size(TIMER_WIDGET_WIDTH, TIMER_WIDGET_HEIGHT)
+            .decorations(false)
+            .resizable(false)
+            .always_on_top(true)
+            .visible_on_all_workspaces(true)
+            .skip_taskbar(true)
+            .focused(false);
+    if let Some(position) = corner_position(app) {
+        builder = builder.position(position.x, position.y);
+    }
+    let window = with_profile_storage(app, builder)
+        .build()
+        .map_err(|e| format!("Failed to open timer widget: {}", e))?;
+
+    let click_through = settings::load(app).is_ok_and(|s| s.appearance.timer_widget_click_through);
+    if click_through {
+        let _ = window.set_ignore_cursor_events(true);
+    }
+    Ok(())
+}
+
+/// Hide the timer widget; it keeps its position for next time
+pub fn close_timer_widget(app: &AppHandle) -> Result<(), String> {
+    match app.get_webview_window(TIMER_WIDGET_LABEL) {
+        Some(window) => window
+            .hide()
+            .map_err(|e| format!("Failed to hide timer widget: {}", e)),
+        None => Ok(()),
+    }
+}
+
+pub fn set_timer_widget_click_through(app: &AppHandle, on: bool) {
+    if let Some(window) = app.get_webview_window(TIMER_WIDGET_LABEL) {
+        let _ = window.set_ignore_cursor_events(on);
+    }
+}
+
+/// Follow the webview's focus sessions: open the widget as one starts (if
+/// enabled in settings) and hide it when it ends
+pub fn focus_session_changed(app: &AppHandle, active: bool) -> Result<(), String> {
+    if !active {
+        return close_timer_widget(app);
+    }
+    if settings::load(app)?.appearance.timer_widget_on_focus {
+        open_timer_widget(app)?;
+    }
+    Ok(())
+}
+
+/// Give webviews of profiles other than the default their own storage,
+/// keeping their sign-in apart
+fn with_profile_storage<'a>(
+    app: &AppHandle,
+    builder: WebviewWindowBuilder<'a, Wry, AppHandle>,
+) -> WebviewWindowBuilder<'a, Wry, AppHandle> {
+    let profile = data_dir::profile(app);
+    if profile == DEFAULT_PROFILE {
+        return builder;
+    }
+    let Ok(dir) = data_dir::get(app) else {
+        return builder;
+    };
+    let builder = builder.data_directory(dir.join(WEBVIEW_DIR));
+    // WKWebView ignores the directory and takes a store id instead
+    #[cfg(target_os = "macos")]
+    let builder = match uuid::Uuid::parse_str(&profile) {
+        Ok(id) => builder.data_store_identifier(*id.as_bytes()),
+        Err(_) => builder,
+    };
+    builder
+}
+
+fn corner_position(app: &AppHandle) -> Option<LogicalPosition<f64>> {
+    let monitor = app
+        .get_webview_window("main")
+        .and_then(|window| window.current_monitor().ok().flatten())
+        .or_else(|| app.primary_monitor().ok().flatten())?;
+    let scale = monitor.scale_factor();
+    let work_area = monitor.work_area();
+    let position = work_area.position.to_logical::<f64>(scale);
+    let size = work_area.size.to_logical::<f64>(scale);
+
+    Some(LogicalPosition::new(
+        position.x + size.width - TIMER_WIDGET_WIDTH - TIMER_WIDGET_MARGIN,
+        position.y + TIMER_WIDGET_MARGIN,
+    ))
+}
+
+/// Open a window for one task, or bring its existing window forward.
+/// Returns the window label.
+pub fn open_task_window(app: &AppHandle, task_id: &str, title: Option<&str>) -> Result<String, String> {
+    let label = task_window_label(task_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        if let Some(title) = title {
+            let _ = window.set_title(title);
+            update_task_windows(app, |windows| {
+                if let Some(open) = windows.iter_mut().find(|open| open.label == label) {
+                    open.title = title.to_string();
+                }
+            })?;
+        }
+        return Ok(label);
+    }
+
+    let title = title.unwrap_or("Task").to_string();
+    let builder = WebviewWindowBuilder::new(
+        app,
+        &label,
+        WebviewUrl::App(format!("/task-window/{}", task_id).into()),
+    )
+    .title(&title)
+    .inner_size(TASK_WINDOW_WIDTH, TASK_WINDOW_HEIGHT)
+    .min_inner_size(TASK_WINDOW_WIDTH / 2.0, TASK_WINDOW_HEIGHT / 2.0);
+    with_profile_storage(app, builder)
+        .build()
+        .map_err(|e| format!("Failed to open task window: {}", e))?;
+
+    update_task_windows(app, |windows| {
+        windows.push(TaskWindow {
+            label: label.clone(),
+            task_id: task_id.to_string(),
+            title,
+        })
+    })?;
+    Ok(label)
+}
+
+pub fn list_task_windows(app: &AppHandle) -> Vec<TaskWindow> {
+    app.state::<TaskWindowsState>()
+        .open
+        .lock()
+        .map(|open| open.clone())
+        .unwrap_or_default()
+}
+
+/// Reopen the task windows that were open at the last quit
+pub fn restore_task_windows(app: &AppHandle) {
+    let saved: Vec<TaskWindow> = task_windows_file(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    for window in saved {
+        let _ = open_task_window(app, &window.task_id, Some(&window.title));
+    }
+}
+
+/// Forget a task window the user closed. Windows closed by quitting are
+/// kept so they're restored.
+pub fn task_window_destroyed(app: &AppHandle, label: &str) {
+    if !label.starts_with(TASK_WINDOW_LABEL_PREFIX) || shutdown::is_shutting_down(app) {
+        return;
+    }
+    let _ = update_task_windows(app, |windows| windows.retain(|open| open.label != label));
+}
+
+/// Keep the task windows listed at the end of the Window menu
+pub fn register_window_menu(app: &AppHandle, submenu: Submenu<Wry>) -> Result<(), String> {
+    let fixed = submenu
+        .items()
+        .map_err(|e| format!("Failed to read Window menu: {}", e))?
+        .len();
+    if let Ok(mut window_menu) = app.state::<TaskWindowsState>().window_menu.lock() {
+        *window_menu = Some((submenu.clone(), fixed));
+    }
+    fill_window_menu(app, &submenu, fixed, &list_task_windows(app))
+}
+
+/// Focus the task window behind a Window menu item
+pub fn focus_from_menu(app: &AppHandle, menu_id: &str) {
+    let Some(label) = menu_id.strip_prefix(TASK_MENU_ID_PREFIX) else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window(label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn task_window_label(task_id: &str) -> String {
+    let id: String = task_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}{}", TASK_WINDOW_LABEL_PREFIX, id)
+}
+
+fn update_task_windows(app: &AppHandle, change: impl FnOnce(&mut Vec<TaskWindow>)) -> Result<(), String> {
+    let state = app.state::<TaskWindowsState>();
+    let windows = {
+        let mut open = state
+            .open
+            .lock()
+            .map_err(|e| format!("Failed to lock task windows: {}", e))?;
+        change(&mut open);
+        open.clone()
+    };
+
+    let json = serde_json::to_string(&windows)
+        .map_err(|e| format!("Failed to serialize task windows: {}", e))?;
+    std::fs::write(task_windows_file(app)?, json)
+        .map_err(|e| format!("Failed to save task windows: {}", e))?;
+
+    let window_menu = state.window_menu.lock().ok().and_then(|menu| menu.clone());
+    if let Some((submenu, fixed)) = window_menu {
+        fill_window_menu(app, &submenu, fixed, &windows)?;
+    }
+
+    let _ = app.emit("task-windows-changed", &windows);
+    Ok(())
+}
+
+/// Replace everything after the fixed Window menu items with the task windows
+fn fill_window_menu(
+    app: &AppHandle,
+    submenu: &Submenu<Wry>,
+    fixed: usize,
+    windows: &[TaskWindow],
+) -> Result<(), String> {
+    let map_err = |e: tauri::Error| format!("Failed to update Window menu: {}", e);
+
+    for item in submenu.items().map_err(map_err)?.iter().skip(fixed) {
+        submenu.remove(item).map_err(map_err)?;
+    }
+    if windows.is_empty() {
+        return Ok(());
+    }
+
+    let separator = PredefinedMenuItem::separator(app).map_err(map_err)?;
+    submenu.append(&separator).map_err(map_err)?;
+    for window in windows {
+        let item = MenuItem::with_id(
+            app,
+            format!("{}{}", TASK_MENU_ID_PREFIX, window.label),
+            recent_tasks::menu_label(&window.title),
+            true,
+            None::<&str>,
+        )
+        .map_err(map_err)?;
+        submenu.append(&item).map_err(map_err)?;
+    }
+    Ok(())
+}
+
+fn task_windows_file(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(data_dir::get(app)?.join(TASK_WINDOWS_FILE))
+}
@@ -0,0 +1,15 @@
+//! Working days and hours, as worked out in `opensunsama-core`, for the
+//! planning settings and the holidays stored in the local database
+
+use tauri::{AppHandle, Manager};
+
+use crate::db::Database;
+use crate::settings::PlanningSettings;
+
+pub use opensunsama_core::schedule::work_calendar::WorkCalendar;
+
+pub fn load(app: &AppHandle, planning: &PlanningSettings) -> Result<WorkCalendar, String> {
+    let week = planning.work_week();
+    app.state::<Database>()
+        .with_conn(|conn| WorkCalendar::read(conn, &week))
+}
@@ -0,0 +1,70 @@
+//! Zip helpers shared by backups and data export. Archives carry a JSON
+//! manifest with SHA-256 checksums of their other entries.
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Write `entries` to a new archive at `path` and flush it to disk
+pub fn write<N: AsRef<str>, B: AsRef<[u8]>>(path: &Path, entries: &[(N, B)]) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+
+    for (name, bytes) in entries {
+        zip.start_file(name.as_ref(), options)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        zip.write_all(bytes.as_ref())
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+    }
+    let file = zip
+        .finish()
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to write archive: {}", e))
+}
+
+pub fn open(path: &Path) -> Result<ZipArchive<File>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    ZipArchive::new(file).map_err(|e| format!("Not a valid archive: {}", e))
+}
+
+pub fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| format!("Archive is missing {}", name))?;
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {} from archive: {}", name, e))?;
+    Ok(bytes)
+}
+
+/// Read an entry and check it against its manifest checksum
+pub fn read_verified(
+    archive: &mut ZipArchive<File>,
+    checksums: &BTreeMap<String, String>,
+    name: &str,
+) -> Result<Vec<u8>, String> {
+    let expected = checksums
+        .get(name)
+        .ok_or_else(|| format!("Archive manifest has no checksum for {}", name))?;
+    let bytes = read_entry(archive, name)?;
+    if &sha256_hex(&bytes) != expected {
+        return Err(format!("Archive is corrupt: {} failed its integrity check", name));
+    }
+    Ok(bytes)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,64 @@
+//! Webview zoom, driven by the View menu and persisted in the appearance
+//! settings so it survives restarts.
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings;
+
+/// Zoom steps the menu moves between, as in browsers
+const LEVELS: &[f64] = &[
+    0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0,
+];
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ZoomChange {
+    In,
+    Out,
+    Reset,
+}
+
+/// Set the main window's zoom factor, clamped to the supported range
+pub fn apply(app: &AppHandle, level: f64) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_zoom(clamp(level));
+    }
+}
+
+/// Step the zoom level, save it and apply it. Emits `zoom-changed` with the
+/// new factor.
+pub fn change(app: &AppHandle, change: ZoomChange) -> Result<f64, String> {
+    let mut current = settings::load(app)?;
+    let level = clamp(current.appearance.zoom);
+
+    let next = match change {
+        ZoomChange::In => LEVELS
+            .iter()
+            .copied()
+            .find(|step| *step > level + f64::EPSILON)
+            .unwrap_or(MAX_ZOOM),
+        ZoomChange::Out => LEVELS
+            .iter()
+            .rev()
+            .copied()
+            .find(|step| *step < level - f64::EPSILON)
+            .unwrap_or(MIN_ZOOM),
+        ZoomChange::Reset => 1.0,
+    };
+
+    current.appearance.zoom = next;
+    settings::save(app, &current)?;
+    apply(app, next);
+    let _ = app.emit("zoom-changed", next);
+
+    Ok(next)
+}
+
+fn clamp(level: f64) -> f64 {
+    if level.is_finite() {
+        level.clamp(MIN_ZOOM, MAX_ZOOM)
+    } else {
+        1.0
+    }
+}
@@ -0,0 +1,78 @@
+package app.opensunsama.bridge
+
+import android.content.Context
+import androidx.work.Constraints
+import androidx.work.ExistingPeriodicWorkPolicy
+import androidx.work.NetworkType
+import androidx.work.PeriodicWorkRequestBuilder
+import androidx.work.WorkManager
+import androidx.work.Worker
+import androidx.work.WorkerParameters
+import app.tauri.plugin.JSObject
+import java.util.UUID
+import java.util.concurrent.ConcurrentHashMap
+import java.util.concurrent.CountDownLatch
+import java.util.concurrent.TimeUnit
+
+/**
+ * Background syncs as WorkManager periodic work. The pass itself runs in
+ * Rust, so a worker only wakes it when the app's process is up with the
+ * Rust side listening; in a process WorkManager started on its own the
+ * worker just returns, and the next launch syncs instead.
+ */
+object BackgroundSync {
+    private const val WORK_NAME = "sync"
+    /** WorkManager stops workers after ten minutes */
+    private const val TIMEOUT_MINUTES = 9L
+
+    val relay = Relay()
+
+    private class Pass {
+        val done = CountDownLatch(1)
+        @Volatile var success = false
+    }
+
+    private val running = ConcurrentHashMap<String, Pass>()
+
+    fun schedule(context: Context, intervalMinutes: Long) {
+        val constraints = Constraints.Builder()
+            .setRequiredNetworkType(NetworkType.CONNECTED)
+            .build()
+        // WorkManager runs periodic work at most every 15 minutes
+        val request = PeriodicWorkRequestBuilder<SyncWorker>(
+            maxOf(intervalMinutes, 15L),
+            TimeUnit.MINUTES
+        ).setConstraints(constraints).build()
+        WorkManager.getInstance(context).enqueueUniquePeriodicWork(
+            WORK_NAME,
+            ExistingPeriodicWorkPolicy.UPDATE,
+            request
+        )
+    }
+
+    /** Runs on the worker's thread; returns whether the pass succeeded */
+    fun run(): Boolean {
+        if (!relay.listening) return true
+        val id = UUID.randomUUID().toString()
+        val pass = Pass()
+        running[id] = pass
+        try {
+            relay.send(JSObject().put("id", id))
+            if (!pass.done.await(TIMEOUT_MINUTES, TimeUnit.MINUTES)) return false
+            return pass.success
+        } finally {
+            running.remove(id)
+        }
+    }
+
+    fun finish(id: String, success: Boolean) {
+        val pass = running[id] ?: return
+        pass.success = success
+        pass.done.countDown()
+    }
+}
+
+class SyncWorker(context: Context, params: WorkerParameters) : Worker(context, params) {
+    override fun doWork(): Result =
+        if (BackgroundSync.run()) Result.success() else Result.retry()
+}
@@ -0,0 +1,43 @@
+package app.opensunsama.bridge
+
+import android.app.PendingIntent
+import android.content.Context
+import androidx.core.app.NotificationCompat
+import androidx.core.app.NotificationManagerCompat
+
+/**
+ * The launcher badge. Android has no API to set a count directly; launchers
+ * that show one take it from the app's notifications, so a silent summary
+ * on the "badge" channel carries it in `setNumber`.
+ */
+object Badge {
+    private const val CHANNEL = "badge"
+    private const val NOTIFICATION_ID = 0x0ba4
+
+    fun set(context: Context, count: Int) {
+        val manager = NotificationManagerCompat.from(context)
+        if (count <= 0) {
+            manager.cancel(NOTIFICATION_ID)
+            return
+        }
+        val notification = NotificationCompat.Builder(context, CHANNEL)
+            .setSmallIcon(context.applicationInfo.icon)
+            .setContentTitle(if (count == 1) "1 task left today" else "$count tasks left today")
+            .setNumber(count)
+            .setBadgeIconType(NotificationCompat.BADGE_ICON_SMALL)
+            .setPriority(NotificationCompat.PRIORITY_MIN)
+            .setSilent(true)
+            .setOnlyAlertOnce(true)
+            .setContentIntent(
+                context.packageManager.getLaunchIntentForPackage(context.packageName)?.let {
+                    PendingIntent.getActivity(context, 0, it, PendingIntent.FLAG_IMMUTABLE)
+                }
+            )
+            .build()
+        try {
+            manager.notify(NOTIFICATION_ID, notification)
+        } catch (e: SecurityException) {
+            // Notifications aren't allowed, so there's nothing to badge from
+        }
+    }
+}
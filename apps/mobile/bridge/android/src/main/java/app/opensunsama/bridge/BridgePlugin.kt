@@ -0,0 +1,183 @@
+package app.opensunsama.bridge
+
+import android.app.Activity
+import android.content.Intent
+import android.webkit.WebView
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Channel
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+
+@InvokeArg
+class ListenArgs {
+    lateinit var handler: Channel
+}
+
+@InvokeArg
+class TileArgs {
+    var timerRunning: Boolean = false
+    var taskTitle: String? = null
+}
+
+@InvokeArg
+class ScheduleBackgroundSyncArgs {
+    var intervalMinutes: Long = 15
+}
+
+@InvokeArg
+class FinishBackgroundSyncArgs {
+    lateinit var id: String
+    var success: Boolean = false
+}
+
+@InvokeArg
+class BadgeArgs {
+    var count: Int = 0
+}
+
+@InvokeArg
+class SecretArgs {
+    lateinit var key: String
+    var value: String? = null
+}
+
+/** Entry point for the Rust side's `Bridge::call` */
+@TauriPlugin
+class BridgePlugin(private val activity: Activity) : Plugin(activity) {
+    override fun load(webView: WebView) {
+        super.load(webView)
+        NotificationActions.take(activity.intent)
+    }
+
+    override fun onNewIntent(intent: Intent) {
+        super.onNewIntent(intent)
+        NotificationActions.take(intent)
+    }
+
+    @Command
+    fun writeWidgetData(invoke: Invoke) {
+        WidgetData.write(activity, invoke.getArgs().toString())
+        invoke.resolve()
+    }
+
+    @Command
+    fun listenWatchMessages(invoke: Invoke) {
+        val args = invoke.parseArgs(ListenArgs::class.java)
+        WearBridge.listen(activity, args.handler)
+        invoke.resolve()
+    }
+
+    @Command
+    fun updateWatchState(invoke: Invoke) {
+        WearBridge.update(activity, invoke.getArgs().toString())
+        invoke.resolve()
+    }
+
+    @Command
+    fun listenTiles(invoke: Invoke) {
+        val args = invoke.parseArgs(ListenArgs::class.java)
+        Tiles.relay.listen(args.handler)
+        invoke.resolve()
+    }
+
+    @Command
+    fun updateTiles(invoke: Invoke) {
+        val args = invoke.parseArgs(TileArgs::class.java)
+        Tiles.update(activity, args.timerRunning, args.taskTitle)
+        invoke.resolve()
+    }
+
+    @Command
+    fun listenShares(invoke: Invoke) {
+        val args = invoke.parseArgs(ListenArgs::class.java)
+        ShareActivity.relay.listen(args.handler)
+        invoke.resolve()
+    }
+
+    @Command
+    fun scheduleBackgroundSync(invoke: Invoke) {
+        val args = invoke.parseArgs(ScheduleBackgroundSyncArgs::class.java)
+        BackgroundSync.schedule(activity, args.intervalMinutes)
+        invoke.resolve()
+    }
+
+    @Command
+    fun listenBackgroundSync(invoke: Invoke) {
+        val args = invoke.parseArgs(ListenArgs::class.java)
+        BackgroundSync.relay.listen(args.handler)
+        invoke.resolve()
+    }
+
+    @Command
+    fun finishBackgroundSync(invoke: Invoke) {
+        val args = invoke.parseArgs(FinishBackgroundSyncArgs::class.java)
+        BackgroundSync.finish(args.id, args.success)
+        invoke.resolve()
+    }
+
+    @Command
+    fun listenPush(invoke: Invoke) {
+        val args = invoke.parseArgs(ListenArgs::class.java)
+        Push.relay.listen(args.handler)
+        invoke.resolve()
+    }
+
+    @Command
+    fun registerForPush(invoke: Invoke) {
+        Push.register()
+        invoke.resolve()
+    }
+
+    @Command
+    fun unregisterForPush(invoke: Invoke) {
+        Push.unregister()
+        invoke.resolve()
+    }
+
+    @Command
+    fun getSecret(invoke: Invoke) {
+        val args = invoke.parseArgs(SecretArgs::class.java)
+        invoke.resolve(JSObject().put("value", Secrets.get(activity, args.key)))
+    }
+
+    @Command
+    fun setSecret(invoke: Invoke) {
+        val args = invoke.parseArgs(SecretArgs::class.java)
+        Secrets.set(activity, args.key, args.value ?: "")
+        invoke.resolve()
+    }
+
+    @Command
+    fun deleteSecret(invoke: Invoke) {
+        val args = invoke.parseArgs(SecretArgs::class.java)
+        Secrets.delete(activity, args.key)
+        invoke.resolve()
+    }
+
+    @Command
+    fun setBadge(invoke: Invoke) {
+        val args = invoke.parseArgs(BadgeArgs::class.java)
+        Badge.set(activity, args.count)
+        invoke.resolve()
+    }
+
+    @Command
+    fun listenNotificationActions(invoke: Invoke) {
+        val args = invoke.parseArgs(ListenArgs::class.java)
+        NotificationActions.relay.listen(args.handler)
+        invoke.resolve()
+    }
+
+    @Command
+    fun listenLifecycle(invoke: Invoke) {
+        val args = invoke.parseArgs(ListenArgs::class.java)
+        activity.runOnUiThread {
+            Lifecycle.observe()
+            Lifecycle.relay.listen(args.handler)
+        }
+        invoke.resolve()
+    }
+}
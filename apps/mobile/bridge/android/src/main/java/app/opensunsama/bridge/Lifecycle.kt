@@ -0,0 +1,31 @@
+package app.opensunsama.bridge
+
+import androidx.lifecycle.DefaultLifecycleObserver
+import androidx.lifecycle.LifecycleOwner
+import androidx.lifecycle.ProcessLifecycleOwner
+import app.tauri.plugin.JSObject
+
+/**
+ * Tells the Rust side when the app goes to and comes back from the
+ * background, for the app lock. Follows the whole process rather than the
+ * activity, so the biometric prompt and the share sheet don't count.
+ */
+object Lifecycle : DefaultLifecycleObserver {
+    val relay = Relay()
+    private var observing = false
+
+    /** Must run on the main thread */
+    fun observe() {
+        if (observing) return
+        observing = true
+        ProcessLifecycleOwner.get().lifecycle.addObserver(this)
+    }
+
+    override fun onStart(owner: LifecycleOwner) {
+        relay.send(JSObject().put("type", "foreground"))
+    }
+
+    override fun onStop(owner: LifecycleOwner) {
+        relay.send(JSObject().put("type", "background"))
+    }
+}
@@ -0,0 +1,37 @@
+package app.opensunsama.bridge
+
+import android.content.Intent
+import app.tauri.plugin.JSObject
+import org.json.JSONException
+import org.json.JSONObject
+
+/**
+ * Complete and Snooze presses on reminders, taken straight to Rust. The
+ * notification plugin starts the activity with the press in its intent and
+ * only passes it on to a listener in the webview, which may not be loaded
+ * yet; this reads the same intent and holds the press until Rust listens.
+ */
+object NotificationActions {
+    /** The buttons `actions::register` adds */
+    private val handled = setOf("complete", "snooze")
+    /** The notification plugin's intent extras */
+    private const val ACTION_KEY = "NotificationUserAction"
+    private const val NOTIFICATION_KEY = "LocalNotficationObject"
+
+    val relay = Relay()
+
+    fun take(intent: Intent?) {
+        val action = intent?.getStringExtra(ACTION_KEY) ?: return
+        if (action !in handled) return
+        val reminderId = try {
+            intent.getStringExtra(NOTIFICATION_KEY)
+                ?.let { JSONObject(it).optJSONObject("extra")?.optString("reminderId") }
+        } catch (_: JSONException) {
+            null
+        }
+        if (reminderId.isNullOrEmpty()) return
+        // The activity keeps its intent when it's recreated; apply once
+        intent.removeExtra(ACTION_KEY)
+        relay.send(JSObject().put("action", action).put("reminderId", reminderId))
+    }
+}
@@ -0,0 +1,51 @@
+package app.opensunsama.bridge
+
+import app.tauri.plugin.JSObject
+import com.google.firebase.messaging.FirebaseMessaging
+import com.google.firebase.messaging.FirebaseMessagingService
+import com.google.firebase.messaging.RemoteMessage
+
+/**
+ * FCM tokens and data pushes. Tokens are held until the Rust side listens;
+ * a data push only matters to a running app, since the next launch syncs
+ * anyway. Needs the app's google-services.json.
+ */
+object Push {
+    val relay = Relay()
+
+    fun register() {
+        FirebaseMessaging.getInstance().isAutoInitEnabled = true
+        FirebaseMessaging.getInstance().token.addOnCompleteListener { task ->
+            if (task.isSuccessful) {
+                token(task.result)
+            } else {
+                relay.send(
+                    JSObject()
+                        .put("type", "error")
+                        .put("message", task.exception?.message ?: "FCM registration failed")
+                )
+            }
+        }
+    }
+
+    fun unregister() {
+        FirebaseMessaging.getInstance().isAutoInitEnabled = false
+        FirebaseMessaging.getInstance().deleteToken()
+    }
+
+    fun token(token: String) {
+        relay.send(JSObject().put("type", "token").put("token", token))
+    }
+}
+
+class PushService : FirebaseMessagingService() {
+    override fun onNewToken(token: String) {
+        Push.token(token)
+    }
+
+    override fun onMessageReceived(message: RemoteMessage) {
+        if (Push.relay.listening) {
+            Push.relay.send(JSObject().put("type", "data"))
+        }
+    }
+}
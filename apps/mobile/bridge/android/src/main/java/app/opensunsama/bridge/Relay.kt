@@ -0,0 +1,34 @@
+package app.opensunsama.bridge
+
+import app.tauri.plugin.Channel
+import app.tauri.plugin.JSObject
+
+/**
+ * Passes messages to a Rust handler, holding those that arrive before the
+ * Rust side listens (e.g. while a tile or share launches the app)
+ */
+class Relay {
+    private var handler: Channel? = null
+    private val pending = mutableListOf<JSObject>()
+
+    /** Whether the Rust side is up */
+    val listening: Boolean
+        get() = handler != null
+
+    fun listen(handler: Channel) {
+        this.handler = handler
+        pending.forEach { handler.send(it) }
+        pending.clear()
+    }
+
+    /** Returns whether the message reached a running app */
+    fun send(message: JSObject): Boolean {
+        val handler = handler
+        if (handler == null) {
+            pending.add(message)
+            return false
+        }
+        handler.send(message)
+        return true
+    }
+}
@@ -0,0 +1,40 @@
+package app.opensunsama.bridge
+
+import android.content.Context
+import android.content.SharedPreferences
+import androidx.security.crypto.EncryptedSharedPreferences
+import androidx.security.crypto.MasterKey
+
+/**
+ * Secrets (the sync session) in preferences encrypted with a key held by
+ * the Android Keystore, which never leaves the device.
+ */
+object Secrets {
+    private const val PREFS = "secrets"
+
+    @Volatile
+    private var prefs: SharedPreferences? = null
+
+    private fun prefs(context: Context): SharedPreferences =
+        prefs ?: synchronized(this) {
+            prefs ?: EncryptedSharedPreferences.create(
+                context,
+                PREFS,
+                MasterKey.Builder(context)
+                    .setKeyScheme(MasterKey.KeyScheme.AES256_GCM)
+                    .build(),
+                EncryptedSharedPreferences.PrefKeyEncryptionScheme.AES256_SIV,
+                EncryptedSharedPreferences.PrefValueEncryptionScheme.AES256_GCM
+            ).also { prefs = it }
+        }
+
+    fun get(context: Context, key: String): String? = prefs(context).getString(key, null)
+
+    fun set(context: Context, key: String, value: String) {
+        prefs(context).edit().putString(key, value).commit()
+    }
+
+    fun delete(context: Context, key: String) {
+        prefs(context).edit().remove(key).commit()
+    }
+}
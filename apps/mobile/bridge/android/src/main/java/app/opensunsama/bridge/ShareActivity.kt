@@ -0,0 +1,34 @@
+package app.opensunsama.bridge
+
+import android.app.Activity
+import android.content.Intent
+import android.os.Bundle
+import app.tauri.plugin.JSObject
+
+/**
+ * The share target for text and links. Hands the share to the Rust side,
+ * marked as a cold start when the app wasn't running, and brings the app
+ * up.
+ */
+class ShareActivity : Activity() {
+    companion object {
+        val relay = Relay()
+    }
+
+    override fun onCreate(savedInstanceState: Bundle?) {
+        super.onCreate(savedInstanceState)
+        val intent = intent
+        if (intent?.action == Intent.ACTION_SEND && intent.type?.startsWith("text/") == true) {
+            val share = JSObject()
+                .put("text", intent.getStringExtra(Intent.EXTRA_TEXT))
+                .put("subject", intent.getStringExtra(Intent.EXTRA_SUBJECT))
+                .put("source", referrer?.host)
+                .put("coldStart", !relay.listening)
+            relay.send(share)
+        }
+        packageManager.getLaunchIntentForPackage(packageName)?.let {
+            startActivity(it.addFlags(Intent.FLAG_ACTIVITY_NEW_TASK))
+        }
+        finish()
+    }
+}
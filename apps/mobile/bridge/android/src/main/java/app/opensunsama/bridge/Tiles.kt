@@ -0,0 +1,83 @@
+package app.opensunsama.bridge
+
+import android.app.PendingIntent
+import android.content.ComponentName
+import android.content.Context
+import android.content.Intent
+import android.os.Build
+import android.service.quicksettings.Tile
+import android.service.quicksettings.TileService
+import app.tauri.plugin.JSObject
+
+/**
+ * Quick Settings tiles. Taps go to the Rust handler; when the app isn't
+ * running they wait here while the tile launches it. The timer tile's
+ * state is kept in shared preferences so it draws without the app.
+ */
+object Tiles {
+    private const val PREFS = "tiles"
+
+    val relay = Relay()
+
+    /** Returns whether the tap reached a running app */
+    fun tap(type: String): Boolean = relay.send(JSObject().put("type", type))
+
+    fun update(context: Context, running: Boolean, taskTitle: String?) {
+        context.getSharedPreferences(PREFS, Context.MODE_PRIVATE)
+            .edit()
+            .putBoolean("timerRunning", running)
+            .putString("taskTitle", taskTitle)
+            .apply()
+        TileService.requestListeningState(
+            context,
+            ComponentName(context, TimerTileService::class.java)
+        )
+    }
+
+    fun timerRunning(context: Context): Boolean =
+        context.getSharedPreferences(PREFS, Context.MODE_PRIVATE).getBoolean("timerRunning", false)
+
+    fun taskTitle(context: Context): String? =
+        context.getSharedPreferences(PREFS, Context.MODE_PRIVATE).getString("taskTitle", null)
+
+    /** Open the app, collapsing the shade */
+    fun openApp(service: TileService) {
+        val intent = service.packageManager.getLaunchIntentForPackage(service.packageName)
+            ?.addFlags(Intent.FLAG_ACTIVITY_NEW_TASK)
+            ?: return
+        if (Build.VERSION.SDK_INT >= 34) {
+            service.startActivityAndCollapse(
+                PendingIntent.getActivity(service, 0, intent, PendingIntent.FLAG_IMMUTABLE)
+            )
+        } else {
+            @Suppress("DEPRECATION")
+            service.startActivityAndCollapse(intent)
+        }
+    }
+}
+
+class QuickAddTileService : TileService() {
+    override fun onClick() {
+        Tiles.tap("quickAdd")
+        Tiles.openApp(this)
+    }
+}
+
+class TimerTileService : TileService() {
+    override fun onStartListening() {
+        val tile = qsTile ?: return
+        val running = Tiles.timerRunning(this)
+        tile.state = if (running) Tile.STATE_ACTIVE else Tile.STATE_INACTIVE
+        if (Build.VERSION.SDK_INT >= 29) {
+            tile.subtitle = if (running) Tiles.taskTitle(this) else null
+        }
+        tile.updateTile()
+    }
+
+    override fun onClick() {
+        // The timer runs in the app; start it when it isn't running
+        if (!Tiles.tap("toggleTimer")) {
+            Tiles.openApp(this)
+        }
+    }
+}
@@ -0,0 +1,38 @@
+package app.opensunsama.bridge
+
+import android.content.Context
+import app.tauri.plugin.Channel
+import app.tauri.plugin.JSObject
+import com.google.android.gms.wearable.MessageClient
+import com.google.android.gms.wearable.PutDataMapRequest
+import com.google.android.gms.wearable.Wearable
+
+/**
+ * The Wearable Data Layer link to a Wear OS watch. The latest state is a
+ * data item at [STATE_PATH], which the watch gets even if it connects
+ * later; control messages from the watch arrive at [MESSAGE_PATH] as JSON
+ * and go to the Rust handler.
+ */
+object WearBridge {
+    const val STATE_PATH = "/opensunsama/state"
+    const val MESSAGE_PATH = "/opensunsama/message"
+
+    private var listener: MessageClient.OnMessageReceivedListener? = null
+
+    fun listen(context: Context, handler: Channel) {
+        val client = Wearable.getMessageClient(context)
+        listener?.let { client.removeListener(it) }
+        listener = MessageClient.OnMessageReceivedListener { event ->
+            if (event.path == MESSAGE_PATH) {
+                handler.send(JSObject(String(event.data, Charsets.UTF_8)))
+            }
+        }.also { client.addListener(it) }
+    }
+
+    fun update(context: Context, json: String) {
+        val request = PutDataMapRequest.create(STATE_PATH).apply {
+            dataMap.putString("state", json)
+        }
+        Wearable.getDataClient(context).putDataItem(request.asPutDataRequest().setUrgent())
+    }
+}
@@ -0,0 +1,26 @@
+package app.opensunsama.bridge
+
+import android.content.Context
+import android.content.Intent
+
+/**
+ * Where the home-screen widget providers find today's tasks and the timer:
+ * JSON in shared preferences, with a broadcast inside the app when it
+ * changes so they can redraw.
+ */
+object WidgetData {
+    const val PREFS = "widget"
+    const val KEY = "data"
+    const val CHANGED = "app.opensunsama.WIDGET_DATA_CHANGED"
+
+    fun write(context: Context, json: String) {
+        context.getSharedPreferences(PREFS, Context.MODE_PRIVATE)
+            .edit()
+            .putString(KEY, json)
+            .apply()
+        context.sendBroadcast(Intent(CHANGED).setPackage(context.packageName))
+    }
+
+    fun read(context: Context): String? =
+        context.getSharedPreferences(PREFS, Context.MODE_PRIVATE).getString(KEY, null)
+}
@@ -0,0 +1,34 @@
+// Nothing is invoked from the webview; the app calls the native side from Rust
+const COMMANDS: &[&str] = &[];
+
+fn main() {
+    tauri_plugin::Builder::new(COMMANDS)
+        .android_path("android")
+        .ios_path("ios")
+        .build();
+
+    // The generated Xcode project's entitlements, which the app target
+    // signs with
+    #[cfg(target_os = "macos")]
+    tauri_plugin::mobile::update_entitlements(|entitlements| {
+        // Universal links for the domains in the deep-link config
+        entitlements.insert(
+            "com.apple.developer.associated-domains".into(),
+            plist::Value::Array(vec![
+                "applinks:opensunsama.com".into(),
+                "applinks:*.opensunsama.com".into(),
+            ]),
+        );
+        // The container the widget and share extensions read and write
+        entitlements.insert(
+            "com.apple.security.application-groups".into(),
+            plist::Value::Array(vec!["group.app.opensunsama.mobile".into()]),
+        );
+        // APNs registration; export switches it to production for release
+        entitlements.insert(
+            "aps-environment".into(),
+            plist::Value::String("development".into()),
+        );
+    })
+    .expect("failed to update the iOS entitlements");
+}
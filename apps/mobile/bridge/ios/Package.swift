@@ -0,0 +1,29 @@
+// swift-tools-version:5.3
+
+import PackageDescription
+
+let package = Package(
+    name: "tauri-plugin-bridge",
+    platforms: [
+        .iOS(.v14),
+    ],
+    products: [
+        .library(
+            name: "tauri-plugin-bridge",
+            type: .static,
+            targets: ["tauri-plugin-bridge"]
+        ),
+    ],
+    dependencies: [
+        .package(name: "Tauri", path: "../.tauri/tauri-api"),
+    ],
+    targets: [
+        .target(
+            name: "tauri-plugin-bridge",
+            dependencies: [
+                .byName(name: "Tauri"),
+            ],
+            path: "Sources"
+        ),
+    ]
+)
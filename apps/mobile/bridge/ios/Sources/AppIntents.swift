@@ -0,0 +1,138 @@
+import AppIntents
+import Foundation
+import Tauri
+
+/// A request from Siri, Shortcuts or the Action button for the Rust side
+struct IntentRequest: Encodable {
+    /// Matches the request to the Rust side's `finishIntent`
+    let id: String
+    /// `addTask` or `startFocus`
+    let type: String
+    let title: String?
+    let taskId: String?
+}
+
+let intentRelay = Relay<IntentRequest>()
+
+/// Requests waiting for the Rust side to say how they went. The app may
+/// have been launched for the request, so the wait covers its startup.
+actor IntentResults {
+    static let shared = IntentResults()
+    private static let timeoutNanoseconds: UInt64 = 10_000_000_000
+
+    private var waiting: [String: CheckedContinuation<String?, Never>] = [:]
+
+    /// Send `type` and wait for it to be applied: nil once it was, else
+    /// why it wasn't
+    func apply(_ type: String, title: String? = nil, taskId: String? = nil) async -> String? {
+        let id = UUID().uuidString
+        return await withCheckedContinuation { continuation in
+            waiting[id] = continuation
+            intentRelay.send(IntentRequest(id: id, type: type, title: title, taskId: taskId))
+            Task {
+                try? await Task.sleep(nanoseconds: Self.timeoutNanoseconds)
+                self.finish(id: id, error: "Open Sunsama didn't respond in time.")
+            }
+        }
+    }
+
+    func finish(id: String, error: String?) {
+        waiting.removeValue(forKey: id)?.resume(returning: error)
+    }
+}
+
+struct FinishIntentArgs: Decodable {
+    let id: String
+    let error: String?
+}
+
+extension BridgePlugin {
+    @objc public func listenIntents(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(ListenArgs.self)
+        intentRelay.listen(args.handler)
+        invoke.resolve()
+    }
+
+    @objc public func finishIntent(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(FinishIntentArgs.self)
+        Task { await IntentResults.shared.finish(id: args.id, error: args.error) }
+        invoke.resolve()
+    }
+}
+
+/// Today's first open task, from the widget data the app keeps current
+private func nextTask(in data: WidgetData?) -> WidgetData.Task? {
+    data?.tasks.first { !$0.completed }
+}
+
+// The intents are public so the app target can list them in its
+// `AppShortcutsProvider` (apps/mobile/ios/AppIntents)
+
+@available(iOS 16.0, *)
+public struct AddTaskIntent: AppIntent {
+    public static var title: LocalizedStringResource = "Add Task"
+    public static var description = IntentDescription("Adds a task to today in Open Sunsama.")
+
+    @Parameter(title: "Task")
+    public var taskTitle: String
+
+    public init() {}
+
+    public func perform() async throws -> some IntentResult & ProvidesDialog {
+        if let error = await IntentResults.shared.apply("addTask", title: taskTitle) {
+            return .result(dialog: "Couldn't add \(taskTitle): \(error)")
+        }
+        return .result(dialog: "Added \(taskTitle) to today.")
+    }
+}
+
+@available(iOS 16.0, *)
+public struct StartFocusIntent: AppIntent {
+    public static var title: LocalizedStringResource = "Start Focus"
+    public static var description = IntentDescription(
+        "Starts the timer on the next task for today in Open Sunsama."
+    )
+
+    public init() {}
+
+    public func perform() async throws -> some IntentResult & ProvidesDialog {
+        let data = WidgetData.load()
+        if let timer = data?.timer {
+            return .result(dialog: "You're already focusing on \(timer.taskTitle).")
+        }
+        guard let task = nextTask(in: data) else {
+            return .result(dialog: "There's nothing left for today.")
+        }
+        if let error = await IntentResults.shared.apply("startFocus", taskId: task.id) {
+            return .result(dialog: "Couldn't start focusing: \(error)")
+        }
+        return .result(dialog: "Focusing on \(task.title).")
+    }
+}
+
+@available(iOS 16.0, *)
+public struct WhatsNextIntent: AppIntent {
+    public static var title: LocalizedStringResource = "What's Next?"
+    public static var description = IntentDescription("Tells you the next task for today.")
+
+    public init() {}
+
+    public func perform() async throws -> some IntentResult & ProvidesDialog {
+        guard let data = WidgetData.load() else {
+            return .result(dialog: "Open Sunsama hasn't loaded today's plan yet.")
+        }
+        if let timer = data.timer {
+            return .result(dialog: "You're focusing on \(timer.taskTitle).")
+        }
+        guard let task = nextTask(in: data) else {
+            return .result(dialog: "You're done for today.")
+        }
+        let more = data.open > 1 ? " Then \(data.open - 1) more." : ""
+        return .result(dialog: "Next up: \(task.title).\(more)")
+    }
+}
+
+/// Lets the app target pick up the intents from this package; its
+/// `AppIntentsPackage` lists this one in `includedPackages`
+@available(iOS 17.0, *)
+public struct BridgeIntents: AppIntentsPackage {}
@@ -0,0 +1,92 @@
+import BackgroundTasks
+import Foundation
+import Tauri
+
+/// Background syncs as BGTaskScheduler app refreshes. iOS launches the app
+/// in the background for each one; the Rust side runs the pass and calls
+/// `finishBackgroundSync`, or iOS expires the task first. The identifier
+/// is listed under BGTaskSchedulerPermittedIdentifiers in Info.plist.
+enum BackgroundSync {
+    static let identifier = "app.opensunsama.mobile.sync"
+
+    struct Wake: Encodable {
+        let id: String
+    }
+
+    static let relay = Relay<Wake>()
+    private static var interval: TimeInterval = 15 * 60
+    private static var registered = false
+    private static var running: [String: BGTask] = [:]
+
+    /// Must run before launch finishes, which the Rust setup does
+    static func register(intervalMinutes: Int) {
+        interval = TimeInterval(intervalMinutes * 60)
+        if !registered {
+            registered = BGTaskScheduler.shared.register(
+                forTaskWithIdentifier: identifier,
+                using: .main
+            ) { task in
+                run(task)
+            }
+        }
+        submit()
+    }
+
+    private static func submit() {
+        let request = BGAppRefreshTaskRequest(identifier: identifier)
+        request.earliestBeginDate = Date(timeIntervalSinceNow: interval)
+        do {
+            try BGTaskScheduler.shared.submit(request)
+        } catch {
+            Logger.error("Failed to schedule background sync: \(error)")
+        }
+    }
+
+    private static func run(_ task: BGTask) {
+        // Each refresh asks for the next one
+        submit()
+        let id = UUID().uuidString
+        running[id] = task
+        task.expirationHandler = {
+            DispatchQueue.main.async { finish(id: id, success: false) }
+        }
+        relay.send(Wake(id: id))
+    }
+
+    static func finish(id: String, success: Bool) {
+        running.removeValue(forKey: id)?.setTaskCompleted(success: success)
+    }
+}
+
+struct ScheduleBackgroundSyncArgs: Decodable {
+    let intervalMinutes: Int
+}
+
+struct FinishBackgroundSyncArgs: Decodable {
+    let id: String
+    let success: Bool
+}
+
+extension BridgePlugin {
+    @objc public func scheduleBackgroundSync(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(ScheduleBackgroundSyncArgs.self)
+        DispatchQueue.main.async {
+            BackgroundSync.register(intervalMinutes: args.intervalMinutes)
+        }
+        invoke.resolve()
+    }
+
+    @objc public func listenBackgroundSync(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(ListenArgs.self)
+        DispatchQueue.main.async { BackgroundSync.relay.listen(args.handler) }
+        invoke.resolve()
+    }
+
+    @objc public func finishBackgroundSync(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(FinishBackgroundSyncArgs.self)
+        DispatchQueue.main.async {
+            BackgroundSync.finish(id: args.id, success: args.success)
+        }
+        invoke.resolve()
+    }
+}
@@ -0,0 +1,28 @@
+import Tauri
+import UIKit
+import UserNotifications
+
+struct BadgeArgs: Decodable {
+    let count: Int
+}
+
+extension BridgePlugin {
+    /// Set the app icon badge; 0 clears it
+    @objc public func setBadge(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(BadgeArgs.self)
+        if #available(iOS 16.0, *) {
+            UNUserNotificationCenter.current().setBadgeCount(args.count) { error in
+                if let error {
+                    invoke.reject("Failed to set the badge: \(error.localizedDescription)")
+                } else {
+                    invoke.resolve()
+                }
+            }
+        } else {
+            DispatchQueue.main.async {
+                UIApplication.shared.applicationIconBadgeNumber = args.count
+                invoke.resolve()
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+import SwiftRs
+import Tauri
+import UIKit
+import WebKit
+
+/// Entry point for the Rust side's `Bridge::call`; each feature adds its
+/// methods in an extension of its own file
+class BridgePlugin: Plugin {}
+
+struct ListenArgs: Decodable {
+    let handler: Channel
+}
+
+/// Passes messages to a Rust handler, holding those that arrive before the
+/// Rust side listens (e.g. while the app is launched in the background)
+class Relay<Message: Encodable> {
+    private var handler: Channel?
+    private var pending: [Message] = []
+
+    func listen(_ handler: Channel) {
+        self.handler = handler
+        pending.forEach(send)
+        pending.removeAll()
+    }
+
+    func send(_ message: Message) {
+        guard let handler else {
+            pending.append(message)
+            return
+        }
+        try? handler.send(message)
+    }
+}
+
+@_cdecl("init_plugin_bridge")
+func initPlugin() -> Plugin {
+    return BridgePlugin()
+}
@@ -0,0 +1,19 @@
+import ActivityKit
+import Foundation
+
+/// The focus session on the lock screen and in the Dynamic Island. The
+/// widget extension (`ios/FocusTimerWidget`) compiles this file too, so
+/// both sides agree on the shape.
+@available(iOS 16.1, *)
+struct FocusTimerAttributes: ActivityAttributes {
+    struct ContentState: Codable, Hashable {
+        var taskTitle: String
+        var startedAt: Date
+        /// The widget counts down to here with `Text(timerInterval:)`, so
+        /// the activity needs no updates while the session runs
+        var endsAt: Date
+    }
+
+    /// The task in focus
+    var taskId: String
+}
@@ -0,0 +1,42 @@
+import Tauri
+import UIKit
+
+/// Tells the Rust side when the app goes to and comes back from the
+/// background, for the app lock. Face ID and other system sheets only make
+/// the app inactive, so they don't count.
+enum Lifecycle {
+    struct Event: Encodable {
+        let type: String
+    }
+
+    static let relay = Relay<Event>()
+    private static var observers: [NSObjectProtocol] = []
+
+    static func observe() {
+        guard observers.isEmpty else { return }
+        let center = NotificationCenter.default
+        observers = [
+            center.addObserver(
+                forName: UIApplication.didEnterBackgroundNotification,
+                object: nil,
+                queue: .main
+            ) { _ in relay.send(Event(type: "background")) },
+            center.addObserver(
+                forName: UIApplication.willEnterForegroundNotification,
+                object: nil,
+                queue: .main
+            ) { _ in relay.send(Event(type: "foreground")) },
+        ]
+    }
+}
+
+extension BridgePlugin {
+    @objc public func listenLifecycle(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(ListenArgs.self)
+        DispatchQueue.main.async {
+            Lifecycle.observe()
+            Lifecycle.relay.listen(args.handler)
+        }
+        invoke.resolve()
+    }
+}
@@ -0,0 +1,77 @@
+import ActivityKit
+import Foundation
+import Tauri
+
+struct LiveActivityArgs: Decodable {
+    let taskId: String
+    let taskTitle: String
+    /// Unix milliseconds
+    let startedAt: Double
+    /// Unix milliseconds
+    let endsAt: Double
+
+    var endDate: Date { Date(timeIntervalSince1970: endsAt / 1000) }
+
+    @available(iOS 16.1, *)
+    var state: FocusTimerAttributes.ContentState {
+        FocusTimerAttributes.ContentState(
+            taskTitle: taskTitle,
+            startedAt: Date(timeIntervalSince1970: startedAt / 1000),
+            endsAt: endDate
+        )
+    }
+}
+
+extension BridgePlugin {
+    /// Show the focus session, replacing any activity for another task and
+    /// bringing one for the same task up to date
+    @objc public func startLiveActivity(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(LiveActivityArgs.self)
+        guard #available(iOS 16.2, *) else {
+            invoke.resolve()
+            return
+        }
+        guard ActivityAuthorizationInfo().areActivitiesEnabled else {
+            invoke.resolve()
+            return
+        }
+        // Stale once the session is over, in case the webview never ends it
+        let content = ActivityContent(state: args.state, staleDate: args.endDate)
+        Task {
+            await endActivities { $0.attributes.taskId != args.taskId }
+            if let current = Activity<FocusTimerAttributes>.activities.first {
+                await current.update(content)
+            } else {
+                do {
+                    _ = try Activity.request(
+                        attributes: FocusTimerAttributes(taskId: args.taskId),
+                        content: content
+                    )
+                } catch {
+                    invoke.reject("Couldn't start the Live Activity: \(error.localizedDescription)")
+                    return
+                }
+            }
+            invoke.resolve()
+        }
+    }
+
+    /// End every focus activity
+    @objc public func endLiveActivity(_ invoke: Invoke) {
+        guard #available(iOS 16.2, *) else {
+            invoke.resolve()
+            return
+        }
+        Task {
+            await endActivities { _ in true }
+            invoke.resolve()
+        }
+    }
+
+    @available(iOS 16.2, *)
+    private func endActivities(where matches: (Activity<FocusTimerAttributes>) -> Bool) async {
+        for activity in Activity<FocusTimerAttributes>.activities where matches(activity) {
+            await activity.end(nil, dismissalPolicy: .immediate)
+        }
+    }
+}
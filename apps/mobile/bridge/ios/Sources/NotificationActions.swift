@@ -0,0 +1,95 @@
+import Foundation
+import Tauri
+import UserNotifications
+
+/// Complete and Snooze presses on reminders, taken straight to Rust. The
+/// notification plugin only passes actions to a listener in the webview,
+/// which isn't there when iOS launches the app in the background for a
+/// press; this delegate sits in front of the plugin's, takes the reminder
+/// actions and passes everything else on. iOS keeps the app running until
+/// the Rust side calls `finishNotificationAction`.
+class NotificationActions: NSObject, UNUserNotificationCenterDelegate {
+    struct Press: Encodable {
+        let id: String
+        let action: String
+        let reminderId: String
+    }
+
+    /// The buttons `actions::register` adds
+    static let handled: Set<String> = ["complete", "snooze"]
+    static let shared = NotificationActions()
+    static let relay = Relay<Press>()
+
+    /// The notification center holds its delegate weakly; the plugin keeps
+    /// its own alive
+    private weak var next: UNUserNotificationCenterDelegate?
+    private var running: [String: () -> Void] = [:]
+
+    /// Step in front of the plugin's delegate, which it sets as it loads.
+    /// Runs on the main thread, from the Rust setup, before launch finishes.
+    func install() {
+        let center = UNUserNotificationCenter.current()
+        guard center.delegate !== self else { return }
+        next = center.delegate
+        center.delegate = self
+    }
+
+    func userNotificationCenter(
+        _ center: UNUserNotificationCenter,
+        willPresent notification: UNNotification,
+        withCompletionHandler completionHandler: @escaping (UNNotificationPresentationOptions) -> Void
+    ) {
+        guard let next, next.responds(to: #selector(userNotificationCenter(_:willPresent:withCompletionHandler:))) else {
+            completionHandler([])
+            return
+        }
+        next.userNotificationCenter?(center, willPresent: notification, withCompletionHandler: completionHandler)
+    }
+
+    func userNotificationCenter(
+        _ center: UNUserNotificationCenter,
+        didReceive response: UNNotificationResponse,
+        withCompletionHandler completionHandler: @escaping () -> Void
+    ) {
+        // The plugin keeps a notification's `extra` under this key
+        let extra = response.notification.request.content.userInfo["__EXTRA__"] as? [String: Any]
+        guard Self.handled.contains(response.actionIdentifier),
+              let reminderId = extra?["reminderId"] as? String
+        else {
+            if let next, next.responds(to: #selector(userNotificationCenter(_:didReceive:withCompletionHandler:))) {
+                next.userNotificationCenter?(center, didReceive: response, withCompletionHandler: completionHandler)
+            } else {
+                completionHandler()
+            }
+            return
+        }
+        let id = UUID().uuidString
+        running[id] = completionHandler
+        Self.relay.send(Press(id: id, action: response.actionIdentifier, reminderId: reminderId))
+    }
+
+    func finish(id: String) {
+        running.removeValue(forKey: id)?()
+    }
+}
+
+struct FinishNotificationActionArgs: Decodable {
+    let id: String
+}
+
+extension BridgePlugin {
+    @objc public func listenNotificationActions(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(ListenArgs.self)
+        DispatchQueue.main.async {
+            NotificationActions.shared.install()
+            NotificationActions.relay.listen(args.handler)
+        }
+        invoke.resolve()
+    }
+
+    @objc public func finishNotificationAction(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(FinishNotificationActionArgs.self)
+        DispatchQueue.main.async { NotificationActions.shared.finish(id: args.id) }
+        invoke.resolve()
+    }
+}
@@ -0,0 +1,118 @@
+import Foundation
+import ObjectiveC
+import Tauri
+import UIKit
+
+/// APNs tokens and silent pushes. Tauri's app delegate doesn't take the
+/// remote notification callbacks, so they're added to its class at launch.
+/// A silent push wakes the app for about 30 seconds; the Rust side runs a
+/// sync pass and calls `finishPush` with how it went. Needs the Push
+/// Notifications capability (the aps-environment entitlement) on the app
+/// target.
+enum Push {
+    struct Event: Encodable {
+        let type: String
+        var token: String? = nil
+        var message: String? = nil
+        var id: String? = nil
+    }
+
+    static let relay = Relay<Event>()
+    private static var installed = false
+    private static var running: [String: (UIBackgroundFetchResult) -> Void] = [:]
+
+    /// Add the delegate callbacks; runs on the main thread
+    static func install() {
+        guard !installed, let delegate = UIApplication.shared.delegate else { return }
+        installed = true
+        let delegateClass: AnyClass = type(of: delegate)
+
+        let didRegister: @convention(block) (AnyObject, UIApplication, Data) -> Void = {
+            _, _, deviceToken in
+            let token = deviceToken.map { String(format: "%02x", $0) }.joined()
+            relay.send(Event(type: "token", token: token))
+        }
+        add(
+            didRegister,
+            as: #selector(UIApplicationDelegate.application(_:didRegisterForRemoteNotificationsWithDeviceToken:)),
+            to: delegateClass
+        )
+
+        let didFail: @convention(block) (AnyObject, UIApplication, NSError) -> Void = {
+            _, _, error in
+            relay.send(Event(type: "error", message: error.localizedDescription))
+        }
+        add(
+            didFail,
+            as: #selector(UIApplicationDelegate.application(_:didFailToRegisterForRemoteNotificationsWithError:)),
+            to: delegateClass
+        )
+
+        let didReceive: @convention(block) (
+            AnyObject, UIApplication, NSDictionary,
+            @escaping @convention(block) (UIBackgroundFetchResult) -> Void
+        ) -> Void = { _, _, _, completion in
+            let id = UUID().uuidString
+            running[id] = completion
+            relay.send(Event(type: "data", id: id))
+        }
+        add(
+            didReceive,
+            as: #selector(UIApplicationDelegate.application(_:didReceiveRemoteNotification:fetchCompletionHandler:)),
+            types: "v@:@@@?",
+            to: delegateClass
+        )
+    }
+
+    private static func add(
+        _ block: Any,
+        as selector: Selector,
+        types: String = "v@:@@",
+        to delegateClass: AnyClass
+    ) {
+        if !class_addMethod(delegateClass, selector, imp_implementationWithBlock(block), types) {
+            Logger.error("The app delegate already handles \(selector)")
+        }
+    }
+
+    static func finish(id: String, success: Bool) {
+        running.removeValue(forKey: id)?(success ? .newData : .failed)
+    }
+}
+
+struct FinishPushArgs: Decodable {
+    let id: String
+    let success: Bool
+}
+
+extension BridgePlugin {
+    @objc public func listenPush(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(ListenArgs.self)
+        DispatchQueue.main.async {
+            Push.install()
+            Push.relay.listen(args.handler)
+        }
+        invoke.resolve()
+    }
+
+    @objc public func registerForPush(_ invoke: Invoke) throws {
+        DispatchQueue.main.async {
+            Push.install()
+            UIApplication.shared.registerForRemoteNotifications()
+        }
+        invoke.resolve()
+    }
+
+    @objc public func unregisterForPush(_ invoke: Invoke) throws {
+        DispatchQueue.main.async {
+            UIApplication.shared.unregisterForRemoteNotifications()
+        }
+        invoke.resolve()
+    }
+
+    @objc public func finishPush(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(FinishPushArgs.self)
+        DispatchQueue.main.async { Push.finish(id: args.id, success: args.success) }
+        invoke.resolve()
+    }
+}
@@ -0,0 +1,74 @@
+import Foundation
+import Security
+import Tauri
+
+/// Secrets (the sync session) in the Keychain. Readable after the first
+/// unlock, so syncs the OS starts in the background can sign in, and never
+/// copied to another device.
+enum Secrets {
+    static let service = "app.opensunsama.mobile"
+
+    private static func query(_ key: String) -> [String: Any] {
+        [
+            kSecClass as String: kSecClassGenericPassword,
+            kSecAttrService as String: service,
+            kSecAttrAccount as String: key,
+        ]
+    }
+
+    static func get(_ key: String) -> String? {
+        var query = query(key)
+        query[kSecReturnData as String] = true
+        query[kSecMatchLimit as String] = kSecMatchLimitOne
+        var item: CFTypeRef?
+        guard SecItemCopyMatching(query as CFDictionary, &item) == errSecSuccess,
+            let data = item as? Data
+        else { return nil }
+        return String(data: data, encoding: .utf8)
+    }
+
+    static func set(_ key: String, _ value: String) -> OSStatus {
+        delete(key)
+        var item = query(key)
+        item[kSecValueData as String] = Data(value.utf8)
+        item[kSecAttrAccessible as String] = kSecAttrAccessibleAfterFirstUnlockThisDeviceOnly
+        return SecItemAdd(item as CFDictionary, nil)
+    }
+
+    @discardableResult
+    static func delete(_ key: String) -> OSStatus {
+        SecItemDelete(query(key) as CFDictionary)
+    }
+}
+
+struct SecretArgs: Decodable {
+    let key: String
+    let value: String?
+}
+
+struct SecretResponse: Encodable {
+    let value: String?
+}
+
+extension BridgePlugin {
+    @objc public func getSecret(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(SecretArgs.self)
+        invoke.resolve(SecretResponse(value: Secrets.get(args.key)))
+    }
+
+    @objc public func setSecret(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(SecretArgs.self)
+        let status = Secrets.set(args.key, args.value ?? "")
+        guard status == errSecSuccess else {
+            invoke.reject("Keychain error \(status)")
+            return
+        }
+        invoke.resolve()
+    }
+
+    @objc public func deleteSecret(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(SecretArgs.self)
+        Secrets.delete(args.key)
+        invoke.resolve()
+    }
+}
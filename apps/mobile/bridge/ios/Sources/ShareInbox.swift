@@ -0,0 +1,66 @@
+import Foundation
+import Tauri
+
+/// Shares handed over by the share extension. The extension can't reach
+/// the app directly, so it drops each share as a JSON file in the app
+/// group and opens `opensunsama://share`; the app takes them from there.
+/// The extension target compiles this file too, for `add`.
+enum ShareInbox {
+    struct Item: Codable {
+        var text: String?
+        /// The page title or subject
+        var subject: String?
+        /// The shared link, when one came as a URL rather than text
+        var url: String?
+        /// Where it came from, e.g. `safari` or `mail`
+        var source: String?
+    }
+
+    static var directory: URL? {
+        FileManager.default
+            .containerURL(forSecurityApplicationGroupIdentifier: appGroup)?
+            .appendingPathComponent("shares", isDirectory: true)
+    }
+
+    /// Queue a share; called by the extension
+    static func add(_ item: Item) throws {
+        guard let directory else { return }
+        try FileManager.default.createDirectory(at: directory, withIntermediateDirectories: true)
+        try JSONEncoder().encode(item).write(
+            to: directory.appendingPathComponent("\(UUID().uuidString).json"),
+            options: .atomic
+        )
+    }
+
+    /// Every queued share, oldest first, removing them from the inbox
+    static func take() -> [Item] {
+        guard let directory,
+              let files = try? FileManager.default.contentsOfDirectory(
+                  at: directory,
+                  includingPropertiesForKeys: [.creationDateKey]
+              )
+        else { return [] }
+
+        let created = { (file: URL) in
+            (try? file.resourceValues(forKeys: [.creationDateKey]).creationDate) ?? .distantPast
+        }
+        return files
+            .filter { $0.pathExtension == "json" }
+            .sorted { created($0) < created($1) }
+            .compactMap { file in
+                defer { try? FileManager.default.removeItem(at: file) }
+                guard let json = try? Data(contentsOf: file) else { return nil }
+                return try? JSONDecoder().decode(Item.self, from: json)
+            }
+    }
+}
+
+struct SharesResponse: Encodable {
+    let items: [ShareInbox.Item]
+}
+
+extension BridgePlugin {
+    @objc public func takeShares(_ invoke: Invoke) {
+        invoke.resolve(SharesResponse(items: ShareInbox.take()))
+    }
+}
@@ -0,0 +1,89 @@
+import Foundation
+import Tauri
+import WatchConnectivity
+
+/// The WatchConnectivity session. The latest state goes out as the
+/// application context, which the watch gets even if it connects later;
+/// control messages from the watch go to the Rust handler as JSON.
+class WatchSession: NSObject, WCSessionDelegate {
+    static let shared = WatchSession()
+
+    let relay = Relay<WatchMessage>()
+
+    func activate() {
+        guard WCSession.isSupported() else { return }
+        WCSession.default.delegate = self
+        WCSession.default.activate()
+    }
+
+    func update(_ json: String) throws {
+        guard WCSession.isSupported(), WCSession.default.isPaired,
+              WCSession.default.isWatchAppInstalled
+        else { return }
+        try WCSession.default.updateApplicationContext(["state": json])
+    }
+
+    private func forward(_ message: [String: Any]) {
+        guard let type = message["type"] as? String else { return }
+        relay.send(WatchMessage(type: type, taskId: message["taskId"] as? String))
+    }
+
+    func session(
+        _ session: WCSession,
+        activationDidCompleteWith state: WCSessionActivationState,
+        error: Error?
+    ) {}
+
+    func sessionDidBecomeInactive(_ session: WCSession) {}
+
+    func sessionDidDeactivate(_ session: WCSession) {
+        // The user switched watches; talk to the new one
+        WCSession.default.activate()
+    }
+
+    func session(_ session: WCSession, didReceiveMessage message: [String: Any]) {
+        DispatchQueue.main.async { self.forward(message) }
+    }
+
+    /// Messages queued while the phone wasn't reachable
+    func session(_ session: WCSession, didReceiveUserInfo userInfo: [String: Any] = [:]) {
+        DispatchQueue.main.async { self.forward(userInfo) }
+    }
+}
+
+extension BridgePlugin {
+    @objc public func listenWatchMessages(_ invoke: Invoke) throws {
+        let args = try invoke.parseArgs(ListenArgs.self)
+        WatchSession.shared.activate()
+        WatchSession.shared.relay.listen(args.handler)
+        invoke.resolve()
+    }
+
+    @objc public func updateWatchState(_ invoke: Invoke) throws {
+        let state = try invoke.parseArgs(WatchState.self)
+        let json = String(decoding: try JSONEncoder().encode(state), as: UTF8.self)
+        try WatchSession.shared.update(json)
+        invoke.resolve()
+    }
+}
+
+/// What the watch shows; the watch app decodes the same shape
+struct WatchState: Codable {
+    struct Task: Codable {
+        let id: String
+        let title: String
+        let estimatedMins: Int?
+    }
+
+    let current: Task?
+    /// Unix milliseconds
+    let timerStartedAt: Double?
+    let next: [Task]
+}
+
+/// A control message from the watch: `complete` and `startTimer` name a
+/// task, `stopTimer` doesn't
+struct WatchMessage: Codable {
+    let type: String
+    let taskId: String?
+}
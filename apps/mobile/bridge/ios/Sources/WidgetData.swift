@@ -0,0 +1,71 @@
+import Foundation
+import Tauri
+import WidgetKit
+
+/// Shared with the widget extension, which reads `WidgetData.fileName`
+/// from this group's container
+let appGroup = "group.app.opensunsama.mobile"
+
+/// What the widgets show; the widget extension decodes the same shape
+struct WidgetData: Codable {
+    struct Task: Codable {
+        let id: String
+        let title: String
+        let completed: Bool
+        let estimatedMins: Int?
+    }
+
+    struct Timer: Codable {
+        let taskId: String
+        let taskTitle: String
+        /// Unix milliseconds
+        let startedAt: Double
+    }
+
+    static let fileName = "widget-data.json"
+
+    /// `YYYY-MM-DD`
+    let date: String
+    let tasks: [Task]
+    let open: Int
+    let completed: Int
+    let timer: Timer?
+    /// Unix milliseconds
+    let updatedAt: Double
+
+    /// What was last written, if it's from today
+    static func load() -> WidgetData? {
+        guard let container = FileManager.default.containerURL(
+            forSecurityApplicationGroupIdentifier: appGroup
+        ),
+            let json = try? Data(contentsOf: container.appendingPathComponent(fileName)),
+            let data = try? JSONDecoder().decode(WidgetData.self, from: json)
+        else { return nil }
+
+        let formatter = DateFormatter()
+        formatter.calendar = Calendar(identifier: .gregorian)
+        formatter.dateFormat = "yyyy-MM-dd"
+        return data.date == formatter.string(from: Date()) ? data : nil
+    }
+}
+
+extension BridgePlugin {
+    /// Store the widget data in the app group and have the widgets redraw
+    @objc public func writeWidgetData(_ invoke: Invoke) throws {
+        let data = try invoke.parseArgs(WidgetData.self)
+        guard let container = FileManager.default.containerURL(
+            forSecurityApplicationGroupIdentifier: appGroup
+        ) else {
+            invoke.reject("The app group \(appGroup) isn't set up")
+            return
+        }
+        try JSONEncoder().encode(data).write(
+            to: container.appendingPathComponent(WidgetData.fileName),
+            options: .atomic
+        )
+        if #available(iOS 14.0, *) {
+            WidgetCenter.shared.reloadAllTimelines()
+        }
+        invoke.resolve()
+    }
+}
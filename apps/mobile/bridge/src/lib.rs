@@ -0,0 +1,113 @@
+//! The mobile app's own Swift and Kotlin code, for OS features no published
+//! plugin covers. The app calls the platform side by method name through
+//! `Bridge::call`; on platforms without one the calls do nothing.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::{Manager, Runtime};
+
+#[cfg(mobile)]
+use tauri::ipc::{Channel, InvokeResponseBody};
+#[cfg(mobile)]
+use tauri::plugin::PluginHandle;
+#[cfg(not(mobile))]
+use tauri::AppHandle;
+
+#[cfg(target_os = "ios")]
+tauri::ios_plugin_binding!(init_plugin_bridge);
+
+/// Handle to the platform side, managed by the app
+pub struct Bridge<R: Runtime> {
+    #[cfg(mobile)]
+    handle: PluginHandle<R>,
+    #[cfg(not(mobile))]
+    _app: AppHandle<R>,
+}
+
+impl<R: Runtime> Bridge<R> {
+    /// Call `method` on the platform side. `Ok(None)` where there's none.
+    pub fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        payload: impl Serialize,
+    ) -> Result<Option<T>, String> {
+        #[cfg(mobile)]
+        {
+            self.handle
+                .run_mobile_plugin(method, payload)
+                .map(Some)
+                .map_err(|e| format!("{} failed: {}", method, e))
+        }
+        #[cfg(not(mobile))]
+        {
+            let _ = (method, payload);
+            Ok(None)
+        }
+    }
+
+    /// Have `method` pass what the platform side receives (e.g. messages
+    /// from a watch) to `handler`, for as long as the app runs
+    pub fn listen<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        handler: impl Fn(T) + Send + Sync + 'static,
+    ) -> Result<(), String> {
+        #[cfg(mobile)]
+        {
+            #[derive(Serialize)]
+            struct Listen {
+                handler: Channel,
+            }
+
+            let channel = Channel::new(move |body| {
+                if let InvokeResponseBody::Json(json) = body {
+                    match serde_json::from_str(&json) {
+                        Ok(value) => handler(value),
+                        Err(e) => tracing::warn!("Unexpected message from the platform: {}", e),
+                    }
+                }
+                Ok(())
+            });
+            self.call::<serde_json::Value>(method, Listen { handler: channel })?;
+            Ok(())
+        }
+        #[cfg(not(mobile))]
+        {
+            let _ = (method, handler);
+            Ok(())
+        }
+    }
+}
+
+pub trait BridgeExt<R: Runtime> {
+    fn bridge(&self) -> &Bridge<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> BridgeExt<R> for T {
+    fn bridge(&self) -> &Bridge<R> {
+        self.state::<Bridge<R>>().inner()
+    }
+}
+
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("bridge")
+        .setup(|app, api| {
+            #[cfg(target_os = "android")]
+            let bridge = Bridge {
+                handle: api.register_android_plugin("app.opensunsama.bridge", "BridgePlugin")?,
+            };
+            #[cfg(target_os = "ios")]
+            let bridge = Bridge {
+                handle: api.register_ios_plugin(init_plugin_bridge)?,
+            };
+            #[cfg(not(mobile))]
+            let bridge = {
+                let _ = api;
+                Bridge { _app: app.clone() }
+            };
+            app.manage(bridge);
+            Ok(())
+        })
+        .build()
+}
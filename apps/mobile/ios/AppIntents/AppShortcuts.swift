@@ -0,0 +1,30 @@
+import AppIntents
+import tauri_plugin_bridge
+
+/// Registers the intents compiled into the bridge plugin. App Intents only
+/// reads intents and shortcuts from the app target and the packages it
+/// lists, so both live here rather than in the plugin.
+@available(iOS 17.0, *)
+struct OpenSunsamaIntents: AppIntentsPackage {
+    static var includedPackages: [any AppIntentsPackage.Type] {
+        [BridgeIntents.self]
+    }
+}
+
+@available(iOS 16.0, *)
+struct OpenSunsamaShortcuts: AppShortcutsProvider {
+    static var appShortcuts: [AppShortcut] {
+        AppShortcut(
+            intent: AddTaskIntent(),
+            phrases: ["Add a task in \(.applicationName)"]
+        )
+        AppShortcut(
+            intent: StartFocusIntent(),
+            phrases: ["Start focus in \(.applicationName)", "Start focusing with \(.applicationName)"]
+        )
+        AppShortcut(
+            intent: WhatsNextIntent(),
+            phrases: ["What's next in \(.applicationName)", "What's next on \(.applicationName)"]
+        )
+    }
+}
@@ -0,0 +1,77 @@
+import ActivityKit
+import SwiftUI
+import WidgetKit
+
+/// The widget extension that draws the focus session's Live Activity. It
+/// compiles `FocusTimerAttributes.swift` from the bridge plugin, which
+/// starts and ends the activity for the app.
+@main
+struct FocusTimerWidgets: WidgetBundle {
+    var body: some Widget {
+        FocusTimerLiveActivity()
+    }
+}
+
+struct FocusTimerLiveActivity: Widget {
+    var body: some WidgetConfiguration {
+        ActivityConfiguration(for: FocusTimerAttributes.self) { context in
+            LockScreenView(state: context.state)
+                .activityBackgroundTint(Color.black.opacity(0.6))
+        } dynamicIsland: { context in
+            DynamicIsland {
+                DynamicIslandExpandedRegion(.leading) {
+                    Image(systemName: "timer")
+                }
+                DynamicIslandExpandedRegion(.trailing) {
+                    Countdown(state: context.state)
+                        .frame(maxWidth: 64)
+                }
+                DynamicIslandExpandedRegion(.bottom) {
+                    Text(context.state.taskTitle)
+                        .lineLimit(1)
+                }
+            } compactLeading: {
+                Image(systemName: "timer")
+            } compactTrailing: {
+                Countdown(state: context.state)
+                    .frame(maxWidth: 44)
+            } minimal: {
+                Image(systemName: "timer")
+            }
+        }
+    }
+}
+
+private struct LockScreenView: View {
+    let state: FocusTimerAttributes.ContentState
+
+    var body: some View {
+        VStack(alignment: .leading, spacing: 8) {
+            HStack {
+                Text(state.taskTitle)
+                    .font(.headline)
+                    .lineLimit(1)
+                Spacer()
+                Countdown(state: state)
+                    .font(.headline)
+            }
+            ProgressView(timerInterval: state.startedAt...state.endsAt, countsDown: true) {
+                EmptyView()
+            } currentValueLabel: {
+                EmptyView()
+            }
+        }
+        .padding()
+    }
+}
+
+/// Time left in the session; the system ticks it without the app
+private struct Countdown: View {
+    let state: FocusTimerAttributes.ContentState
+
+    var body: some View {
+        Text(timerInterval: state.startedAt...state.endsAt, countsDown: true)
+            .monospacedDigit()
+            .multilineTextAlignment(.trailing)
+    }
+}
@@ -0,0 +1,150 @@
+//! Complete and Snooze buttons on reminder notifications. The platform side
+//! takes each press as the OS delivers it, before any webview is up (iOS
+//! wakes the app in the background for one), and passes it to `handle`,
+//! which applies it to the local store. Completions also go into the
+//! outbox, the queue of changes sent to the backend when it next can.
+
+use chrono::Utc;
+use opensunsama_core::db::reminders::{self, Reminder};
+use opensunsama_core::db::Database;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::tasks;
+
+/// Notifications with these buttons are scheduled with this action type
+pub const ACTION_TYPE: &str = "reminder";
+pub const COMPLETE: &str = "complete";
+pub const SNOOZE: &str = "snooze";
+const SNOOZE_FOR: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionResult {
+    pub action: String,
+    pub reminder_id: String,
+    pub task_id: Option<String>,
+    /// Unix milliseconds the reminder fires again, after a snooze
+    pub snoozed_until: Option<i64>,
+}
+
+/// Apply `action` (`complete` or `snooze`) to the reminder `reminder_id`.
+/// Emits `notification-action-applied` for a webview that's open.
+pub fn handle(app: &AppHandle, action: &str, reminder_id: &str) -> Result<ActionResult, String> {
+    let reminder = app
+        .state::<Database>()
+        .with_conn(|conn| reminders::get(conn, reminder_id))?
+        .ok_or_else(|| format!("Unknown reminder {}", reminder_id))?;
+
+    let snoozed_until = match action {
+        COMPLETE => {
+            complete(app, &reminder)?;
+            None
+        }
+        SNOOZE => Some(snooze(app, reminder.clone())?),
+        other => return Err(format!("Unknown notification action {}", other)),
+    };
+
+    let result = ActionResult {
+        action: action.to_string(),
+        reminder_id: reminder.id,
+        task_id: reminder.task_id,
+        snoozed_until,
+    };
+    crate::app_lock::emit(app, "notification-action-applied", &result);
+    Ok(result)
+}
+
+/// Mark the reminder's task done locally and queue the change for the
+/// backend; the reminder itself counts as delivered
+fn complete(app: &AppHandle, reminder: &Reminder) -> Result<(), String> {
+    let task_id = reminder
+        .task_id
+        .as_deref()
+        .ok_or_else(|| "This reminder isn't for a task".to_string())?;
+    let now = Utc::now();
+    app.state::<Database>().with_conn(|conn| {
+        let tx = conn.transaction()?;
+        tasks::complete(&tx, task_id, now)?;
+        reminders::mark_delivered(&tx, &reminder.id, now.timestamp_millis())?;
+        tx.commit()
+    })?;
+    crate::today::changed(app);
+    Ok(())
+}
+
+/// Fire the reminder again in a few minutes. Returns when.
+fn snooze(app: &AppHandle, mut reminder: Reminder) -> Result<i64, String> {
+    reminder.fire_at = Utc::now().timestamp_millis() + SNOOZE_FOR.as_millis() as i64;
+    reminder.delivered_at = None;
+    crate::reminders::schedule(app, &reminder)?;
+    Ok(reminder.fire_at)
+}
+
+/// Register the Complete and Snooze buttons with the OS and apply presses
+/// as they come
+#[cfg(mobile)]
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use tauri_plugin_bridge::BridgeExt;
+    use tauri_plugin_notification::{Action, ActionType, NotificationExt};
+
+    /// A button press, from the platform side
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Press {
+        /// Set when the OS keeps the app running until
+        /// `finishNotificationAction`
+        id: Option<String>,
+        action: String,
+        reminder_id: String,
+    }
+
+    let listener = app.clone();
+    app.bridge()
+        .listen("listenNotificationActions", move |press: Press| {
+            if let Err(e) = handle(&listener, &press.action, &press.reminder_id) {
+                tracing::warn!("Failed to apply the notification action: {}", e);
+            }
+            if let Some(id) = &press.id {
+                if let Err(e) = listener
+                    .bridge()
+                    .call::<Value>("finishNotificationAction", json!({ "id": id }))
+                {
+                    tracing::warn!("{}", e);
+                }
+            }
+        })?;
+
+    let button = |id: &str, title: &str| Action {
+        id: id.to_string(),
+        title: title.to_string(),
+        requires_authentication: false,
+        foreground: false,
+        destructive: false,
+        input: false,
+        input_button_title: None,
+        input_placeholder: None,
+    };
+    app.notification()
+        .register_action_types(vec![ActionType {
+            id: ACTION_TYPE.to_string(),
+            actions: vec![
+                button(COMPLETE, "Complete"),
+                button(SNOOZE, "Snooze 10 min"),
+            ],
+            hidden_previews_body_placeholder: None,
+            custom_dismiss_action: false,
+            allow_in_car_play: false,
+            hidden_previews_show_title: false,
+            hidden_previews_show_subtitle: false,
+        }])
+        .map_err(|e| format!("Failed to register notification actions: {}", e))
+}
+
+#[cfg(not(mobile))]
+pub fn init(_app: &AppHandle) -> Result<(), String> {
+    Ok(())
+}
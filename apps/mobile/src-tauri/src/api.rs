@@ -0,0 +1,208 @@
+//! The slice of the Open Sunsama REST API the native sync needs, signed in
+//! with the session the webview hands over. Responses come wrapped in
+//! `{ success, data, meta }`; errors in `{ success: false, error }`.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_http::reqwest::{self, Client, Method, RequestBuilder, StatusCode};
+use tauri_plugin_store::StoreExt;
+
+use crate::secrets;
+
+/// Where sessions were kept in plain text before the keystore
+const STORE: &str = "session.json";
+const SESSION: &str = "session";
+const PAGE_SIZE: usize = 100;
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where and as whom to sync. Kept in the platform keystore, readable once
+/// the device has been unlocked since boot, so syncs the OS starts in the
+/// background can sign in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub server_url: String,
+    pub token: String,
+}
+
+/// A request that failed
+#[derive(Debug)]
+pub struct ApiError {
+    pub message: String,
+    pub not_found: bool,
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        ApiError {
+            message: e.to_string(),
+            not_found: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Envelope<T> {
+    data: Option<T>,
+    meta: Option<PageMeta>,
+    error: Option<ErrorBody>,
+}
+
+#[derive(Deserialize)]
+struct PageMeta {
+    total: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    code: Option<String>,
+    message: String,
+}
+
+pub fn session(app: &AppHandle) -> Result<Option<Session>, String> {
+    if let Some(json) = secrets::get(app, SESSION)? {
+        return Ok(serde_json::from_str(&json).ok());
+    }
+    // Sessions from before they moved to the keystore
+    let store = app.store(STORE).map_err(|e| e.to_string())?;
+    let Some(legacy) = store.get(SESSION) else {
+        return Ok(None);
+    };
+    let session = serde_json::from_value::<Session>(legacy).ok();
+    set_session(app, session.as_ref())?;
+    Ok(session)
+}
+
+/// Remember the session, or forget it on sign-out
+pub fn set_session(app: &AppHandle, session: Option<&Session>) -> Result<(), String> {
+    let json = session
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    secrets::set(app, SESSION, json.as_deref())?;
+    let store = app.store(STORE).map_err(|e| e.to_string())?;
+    if store.delete(SESSION) {
+        store.save().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub struct Api {
+    client: Client,
+    base_url: String,
+    token: String,
+}
+
+impl Api {
+    pub fn new(session: Session) -> Result<Self, String> {
+        let client = Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Api {
+            client,
+            base_url: session.server_url.trim_end_matches('/').to_string(),
+            token: session.token,
+        })
+    }
+
+    /// Every page of a paginated collection
+    pub async fn get_all<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, ApiError> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        for page in 1.. {
+            let url = format!("{}{}page={}&limit={}", path, separator, page, PAGE_SIZE);
+            let (batch, total) = self.send::<Vec<T>>(self.request(Method::GET, &url)).await?;
+            let fetched = batch.len();
+            items.extend(batch);
+            let done = match total {
+                Some(total) => items.len() >= total,
+                None => fetched < PAGE_SIZE,
+            };
+            if done || fetched == 0 {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Send a request as queued in the outbox
+    pub async fn send_raw(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let method = Method::from_bytes(method.as_bytes()).map_err(|e| ApiError {
+            message: format!("Invalid method {}: {}", method, e),
+            not_found: false,
+        })?;
+        let mut request = self.request(method, path);
+        if let Some(body) = body {
+            request = request
+                .header("Content-Type", "application/json")
+                .body(body.to_string());
+        }
+        // Deletes may answer without a body
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Envelope<Value>>(&body)
+            .ok()
+            .and_then(|envelope| envelope.error)
+            .map(|error| {
+                format!(
+                    "{}: {}",
+                    error.code.unwrap_or_else(|| status.as_u16().to_string()),
+                    error.message
+                )
+            })
+            .unwrap_or_else(|| format!("Server returned {}", status));
+        Err(ApiError {
+            message,
+            not_found: status == StatusCode::NOT_FOUND,
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.base_url, path))
+            .bearer_auth(&self.token)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<(T, Option<usize>), ApiError> {
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        let failed = |message: String| ApiError {
+            message,
+            not_found: status == StatusCode::NOT_FOUND,
+        };
+
+        let envelope: Envelope<T> = serde_json::from_str(&body).map_err(|e| {
+            failed(if status.is_success() {
+                format!("Unexpected response from server: {}", e)
+            } else {
+                format!("Server returned {}", status)
+            })
+        })?;
+        if let Some(error) = envelope.error {
+            let code = error.code.unwrap_or_else(|| status.as_u16().to_string());
+            return Err(failed(format!("{}: {}", code, error.message)));
+        }
+        let total = envelope.meta.and_then(|meta| meta.total);
+        envelope
+            .data
+            .map(|data| (data, total))
+            .ok_or_else(|| failed(format!("Server returned {} without data", status)))
+    }
+}
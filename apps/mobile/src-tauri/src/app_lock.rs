@@ -0,0 +1,229 @@
+//! The app lock: Face ID, Touch ID or a fingerprint before the app shows
+//! any data. The app locks at launch and when it comes back after being in
+//! the background longer than the grace period. Data commands refuse to
+//! answer while it's locked; the webview covers its content on
+//! `app-locked` and asks for `unlock_app`. Syncs, notification actions and
+//! other OS surfaces keep working; the events they raise for the webview
+//! are held until it unlocks.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_bridge::BridgeExt;
+use tauri_plugin_store::StoreExt;
+
+const STORE: &str = "settings.json";
+const KEY: &str = "appLock";
+const UNLOCK_REASON: &str = "Unlock Open Sunsama";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AppLockSettings {
+    pub enabled: bool,
+    /// How long the app may be in the background without locking; 0 locks
+    /// as soon as it leaves the screen
+    pub grace_seconds: u32,
+}
+
+impl Default for AppLockSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            grace_seconds: 60,
+        }
+    }
+}
+
+/// Lock state, when the app went to the background, and the events held
+/// back from the locked webview
+pub struct AppLockState {
+    locked: AtomicBool,
+    backgrounded_at: Mutex<Option<Instant>>,
+    held: Mutex<Vec<(String, Value)>>,
+}
+
+impl AppLockState {
+    pub fn new(locked: bool) -> Self {
+        Self {
+            locked: AtomicBool::new(locked),
+            backgrounded_at: Mutex::new(None),
+            held: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    /// Refuse data commands while locked
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_locked() {
+            return Err("Open Sunsama is locked".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Lifecycle {
+    Background,
+    Foreground,
+}
+
+pub fn load(app: &AppHandle) -> Result<AppLockSettings, String> {
+    let store = app.store(STORE).map_err(|e| e.to_string())?;
+    Ok(store
+        .get(KEY)
+        .and_then(|settings| serde_json::from_value(settings).ok())
+        .unwrap_or_default())
+}
+
+/// Save the settings. Any change while locked, and turning the lock on or
+/// off, takes a successful prompt first: off so the lock can't be lifted
+/// from the locked app, on so nobody is locked out by a sensor that
+/// doesn't work.
+pub async fn save(app: &AppHandle, settings: &AppLockSettings) -> Result<(), String> {
+    let locked = app.state::<AppLockState>().is_locked();
+    if locked || settings.enabled != load(app)?.enabled {
+        authenticate(app).await?;
+    }
+    let store = app.store(STORE).map_err(|e| e.to_string())?;
+    store.set(KEY, serde_json::json!(settings));
+    store.save().map_err(|e| e.to_string())?;
+    // The prompt above proved who's holding the phone
+    if locked {
+        set_locked(app, false);
+    }
+    Ok(())
+}
+
+/// Emit `event` to the webview now, or once it unlocks
+pub fn emit(app: &AppHandle, event: &str, payload: impl Serialize) {
+    let state = app.state::<AppLockState>();
+    if state.is_locked() {
+        if let (Ok(mut held), Ok(payload)) = (state.held.lock(), serde_json::to_value(payload)) {
+            held.push((event.to_string(), payload));
+            return;
+        }
+    }
+    let _ = app.emit(event, payload);
+}
+
+/// Manage the state, locked if the lock is on, and follow the app moving
+/// to and from the background
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let enabled = load(app).map(|settings| settings.enabled).unwrap_or(false);
+    app.manage(AppLockState::new(enabled));
+    if !cfg!(mobile) {
+        return Ok(());
+    }
+    let handle = app.clone();
+    app.bridge()
+        .listen("listenLifecycle", move |event: Lifecycle| {
+            on_lifecycle(&handle, event)
+        })
+}
+
+/// Lock the app now, if the lock is on
+pub fn lock_now(app: &AppHandle) -> Result<(), String> {
+    if !load(app)?.enabled {
+        return Err("The app lock is off".to_string());
+    }
+    set_locked(app, true);
+    Ok(())
+}
+
+/// Unlock with the OS prompt
+pub async fn unlock(app: &AppHandle) -> Result<(), String> {
+    if !app.state::<AppLockState>().is_locked() {
+        return Ok(());
+    }
+    authenticate(app).await?;
+    set_locked(app, false);
+    Ok(())
+}
+
+fn on_lifecycle(app: &AppHandle, event: Lifecycle) {
+    let state = app.state::<AppLockState>();
+    let Ok(mut backgrounded_at) = state.backgrounded_at.lock() else {
+        return;
+    };
+    match event {
+        Lifecycle::Background => *backgrounded_at = Some(Instant::now()),
+        Lifecycle::Foreground => {
+            let Some(since) = backgrounded_at.take() else {
+                return;
+            };
+            let Ok(settings) = load(app) else {
+                return;
+            };
+            if settings.enabled && since.elapsed().as_secs() >= u64::from(settings.grace_seconds) {
+                set_locked(app, true);
+            }
+        }
+    }
+}
+
+fn set_locked(app: &AppHandle, locked: bool) {
+    let state = app.state::<AppLockState>();
+    if state.locked.swap(locked, Ordering::SeqCst) == locked {
+        return;
+    }
+    if locked {
+        let _ = app.emit("app-locked", ());
+        return;
+    }
+    let _ = app.emit("app-unlocked", ());
+    let held = state
+        .held
+        .lock()
+        .map(|mut held| std::mem::take(&mut *held))
+        .unwrap_or_default();
+    for (event, payload) in held {
+        let _ = app.emit(&event, payload);
+    }
+}
+
+/// The prompt blocks until the user answers, so it runs off the async
+/// runtime
+async fn authenticate(app: &AppHandle) -> Result<(), String> {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || os::authenticate(&app, UNLOCK_REASON))
+        .await
+        .map_err(|e| format!("Authentication failed: {}", e))?
+}
+
+#[cfg(mobile)]
+mod os {
+    use tauri::AppHandle;
+    use tauri_plugin_biometric::{AuthOptions, BiometricExt};
+
+    /// Falls back to the device passcode, so a failing sensor can't lock
+    /// the user out
+    pub fn authenticate(app: &AppHandle, reason: &str) -> Result<(), String> {
+        app.biometric()
+            .authenticate(
+                reason.to_string(),
+                AuthOptions {
+                    allow_device_credential: true,
+                    ..Default::default()
+                },
+            )
+            .map_err(|e| format!("Authentication failed: {}", e))
+    }
+}
+
+/// Desktop builds (used while developing the mobile UI) have no biometrics;
+/// unlocking always succeeds
+#[cfg(not(mobile))]
+mod os {
+    use tauri::AppHandle;
+
+    pub fn authenticate(_app: &AppHandle, _reason: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
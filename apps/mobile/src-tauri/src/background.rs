@@ -0,0 +1,62 @@
+//! Syncs the OS runs while the app is in the background: BGTaskScheduler
+//! app refreshes on iOS, WorkManager periodic work on Android. The platform
+//! side wakes the app, passes a `Wake` here, and holds the OS task open
+//! until `finishBackgroundSync` reports how the pass went.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+use tauri_plugin_bridge::BridgeExt;
+
+use crate::sync;
+
+/// How often to ask for a pass; both OSes treat it as a lower bound and
+/// may run passes much less often
+const INTERVAL_MINUTES: u32 = 15;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Wake {
+    /// Identifies the OS task to finish
+    id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Finish<'a> {
+    id: &'a str,
+    success: bool,
+}
+
+/// Ask the OS for periodic passes and run them as they come
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    if !cfg!(mobile) {
+        return Ok(());
+    }
+    let handle = app.clone();
+    app.bridge()
+        .listen("listenBackgroundSync", move |wake: Wake| {
+            let app = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let success = match sync::run_once(&app).await {
+                    Ok(_) => true,
+                    Err(e) => {
+                        tracing::warn!("Background sync failed: {}", e);
+                        false
+                    }
+                };
+                let finish = Finish {
+                    id: &wake.id,
+                    success,
+                };
+                if let Err(e) = app.bridge().call::<Value>("finishBackgroundSync", &finish) {
+                    tracing::warn!("{}", e);
+                }
+            });
+        })?;
+    app.bridge().call::<Value>(
+        "scheduleBackgroundSync",
+        serde_json::json!({ "intervalMinutes": INTERVAL_MINUTES }),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,25 @@
+//! The app icon badge: how many of today's tasks are still open, worked out
+//! from the local database. iOS sets the count directly; Android launchers
+//! read it from a silent notification on the badge channel.
+
+use chrono::{Local, NaiveDate};
+use opensunsama_core::db::{tasks, Database};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_bridge::BridgeExt;
+
+/// Recompute the badge. Failures are only logged: a stale badge shouldn't
+/// fail the change that triggered it.
+pub fn refresh(app: &AppHandle) {
+    if let Err(e) = update(app, Local::now().date_naive()) {
+        tracing::warn!("Failed to update the badge: {}", e);
+    }
+}
+
+fn update(app: &AppHandle, today: NaiveDate) -> Result<(), String> {
+    let open = app
+        .state::<Database>()
+        .with_conn(|conn| tasks::count_open_on(conn, &today.to_string()))?;
+    app.bridge().call::<Value>("setBadge", json!({ "count": open }))?;
+    Ok(())
+}
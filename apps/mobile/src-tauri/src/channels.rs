@@ -0,0 +1,180 @@
+//! Android notification channels, one per kind of notification, so users can
+//! silence or mute each kind in the system settings. Android only takes an
+//! app's importance and sound when a channel is first created; after that
+//! the user's choices win, and creating the channel again only updates its
+//! name and description. iOS has no channels; everything here is a no-op
+//! there.
+
+use serde::Deserialize;
+use tauri::AppHandle;
+
+pub const REMINDERS: &str = "reminders";
+pub const TIMERS: &str = "timers";
+pub const MEETINGS: &str = "meetings";
+pub const SYNC: &str = "sync";
+/// Carries the launcher badge's count; see `badge`
+pub const BADGE: &str = "badge";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Importance {
+    /// Never shown
+    None,
+    /// Only in the shade, collapsed
+    Min,
+    /// Shown, but without a sound
+    Low,
+    /// Makes a sound
+    Default,
+    /// Makes a sound and pops up on screen
+    High,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelConfig {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub importance: Importance,
+    /// Play the default notification sound; ignored below `Default`
+    pub sound: bool,
+    pub vibrate: bool,
+}
+
+/// The channels the app posts to
+pub fn defaults() -> Vec<ChannelConfig> {
+    let channel = |id: &str, name: &str, description: &str, importance, sound| ChannelConfig {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        importance,
+        sound,
+        vibrate: sound,
+    };
+    vec![
+        channel(
+            REMINDERS,
+            "Reminders",
+            "Task reminders and the daily planning and shutdown nudges",
+            Importance::High,
+            true,
+        ),
+        channel(
+            TIMERS,
+            "Timers",
+            "Focus timer and pomodoro progress",
+            Importance::Low,
+            false,
+        ),
+        channel(
+            MEETINGS,
+            "Meetings",
+            "Upcoming calendar events",
+            Importance::High,
+            true,
+        ),
+        channel(
+            SYNC,
+            "Sync",
+            "Sync status and conflicts",
+            Importance::Min,
+            false,
+        ),
+        channel(
+            BADGE,
+            "Open tasks",
+            "How many of today's tasks are left, for the app icon badge",
+            Importance::Min,
+            false,
+        ),
+    ]
+}
+
+/// Create the default channels; run at every launch, which is harmless for
+/// channels that already exist
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    defaults().iter().try_for_each(|config| create(app, config))
+}
+
+/// Create or rename a channel
+pub fn create(app: &AppHandle, config: &ChannelConfig) -> Result<(), String> {
+    os::create(app, config)
+}
+
+/// Remove a channel. Creating one with the same id later brings back the
+/// user's old settings for it.
+pub fn delete(app: &AppHandle, id: &str) -> Result<(), String> {
+    os::delete(app, id)
+}
+
+/// Ids of the app's channels
+pub fn list(app: &AppHandle) -> Result<Vec<String>, String> {
+    os::list(app)
+}
+
+#[cfg(target_os = "android")]
+mod os {
+    use tauri::AppHandle;
+    use tauri_plugin_notification::{Channel, Importance, NotificationExt};
+
+    use super::ChannelConfig;
+
+    pub fn create(app: &AppHandle, config: &ChannelConfig) -> Result<(), String> {
+        let importance = match config.importance {
+            super::Importance::None => Importance::None,
+            super::Importance::Min => Importance::Min,
+            super::Importance::Low => Importance::Low,
+            super::Importance::Default => Importance::Default,
+            super::Importance::High => Importance::High,
+        };
+        let mut channel = Channel::builder(&config.id, &config.name)
+            .importance(importance)
+            .vibration(config.vibrate);
+        if let Some(description) = &config.description {
+            channel = channel.description(description);
+        }
+        if config.sound {
+            channel = channel.sound("default");
+        }
+        app.notification()
+            .create_channel(channel.build())
+            .map_err(|e| format!("Failed to create notification channel: {}", e))
+    }
+
+    pub fn delete(app: &AppHandle, id: &str) -> Result<(), String> {
+        app.notification()
+            .delete_channel(id)
+            .map_err(|e| format!("Failed to delete notification channel: {}", e))
+    }
+
+    pub fn list(app: &AppHandle) -> Result<Vec<String>, String> {
+        let channels = app
+            .notification()
+            .list_channels()
+            .map_err(|e| format!("Failed to list notification channels: {}", e))?;
+        Ok(channels
+            .iter()
+            .map(|channel| channel.id().to_string())
+            .collect())
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+mod os {
+    use tauri::AppHandle;
+
+    use super::ChannelConfig;
+
+    pub fn create(_app: &AppHandle, _config: &ChannelConfig) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn delete(_app: &AppHandle, _id: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn list(_app: &AppHandle) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,35 @@
+use tauri::{AppHandle, State};
+
+use crate::app_lock::{self, AppLockSettings, AppLockState};
+
+/// Lock the app now; fails while the lock is off
+#[tauri::command]
+pub fn lock_now(app: AppHandle) -> Result<(), String> {
+    app_lock::lock_now(&app)
+}
+
+/// Unlock with Face ID, Touch ID or a fingerprint, or the device passcode
+#[tauri::command]
+pub async fn unlock_app(app: AppHandle) -> Result<(), String> {
+    app_lock::unlock(&app).await
+}
+
+/// Whether the app is currently locked
+#[tauri::command]
+pub fn is_app_locked(state: State<'_, AppLockState>) -> bool {
+    state.is_locked()
+}
+
+#[tauri::command]
+pub fn get_app_lock_settings(app: AppHandle) -> Result<AppLockSettings, String> {
+    app_lock::load(&app)
+}
+
+/// Save the lock settings; turning the lock on asks for a prompt first
+#[tauri::command]
+pub async fn set_app_lock_settings(
+    app: AppHandle,
+    settings: AppLockSettings,
+) -> Result<(), String> {
+    app_lock::save(&app, &settings).await
+}
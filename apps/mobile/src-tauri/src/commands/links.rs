@@ -0,0 +1,18 @@
+use tauri::State;
+
+use crate::app_lock::AppLockState;
+use crate::links::{self, LinkEvent};
+
+/// What links asked to show before the webview was listening, e.g. the
+/// link that launched the app: `{ event, payload }` pairs to handle as if
+/// the event had just arrived. Call once the `navigate`, `quick-add-task`
+/// and `confirm-timer` listeners are set up, and again after unlocking if
+/// the app was locked; later ones arrive as events.
+#[tauri::command]
+pub fn take_pending_routes(
+    lock: State<'_, AppLockState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<LinkEvent>, String> {
+    lock.check()?;
+    Ok(links::take_pending(&app))
+}
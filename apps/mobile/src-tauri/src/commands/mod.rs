@@ -1,5 +1,13 @@
+pub mod app_lock;
 pub mod haptics;
+pub mod links;
 pub mod notifications;
+pub mod push;
+pub mod schedule;
+pub mod sync;
+pub mod tasks;
+pub mod timer;
+pub mod widgets;
 
 /// Check if running on mobile platform
 #[tauri::command]
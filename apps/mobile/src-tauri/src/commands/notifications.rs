@@ -10,11 +10,43 @@ pub async fn request_notification_permission(app: tauri::AppHandle) -> Result<bo
     Ok(permission == tauri_plugin_notification::PermissionState::Granted)
 }
 
-/// Set the app badge count (iOS only, no-op on Android)
-/// Note: Badge count is typically set via push notification payload
+/// Show a notification every day at `time` (`HH:MM`, local), e.g. the
+/// planning or shutdown ritual. Scheduling the same id again moves it.
 #[tauri::command]
-pub async fn set_badge_count(_app: tauri::AppHandle, _count: u32) -> Result<(), String> {
-    // Badge count setting is handled via push notification payload on iOS
-    // This is a no-op as direct badge manipulation requires UIApplication access
-    Ok(())
+pub fn schedule_daily_notification(
+    app: tauri::AppHandle,
+    id: String,
+    title: String,
+    body: Option<String>,
+    time: String,
+) -> Result<(), String> {
+    crate::reminders::schedule_daily(&app, &id, &title, body.as_deref(), &time)
+}
+
+/// Stop a daily notification
+#[tauri::command]
+pub fn cancel_daily_notification(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    crate::reminders::cancel_daily(&app, &id)
+}
+
+/// Create an Android notification channel, or rename an existing one. Its
+/// importance and sound only apply when it's new. No-op on iOS.
+#[tauri::command]
+pub fn create_notification_channel(
+    app: tauri::AppHandle,
+    channel: crate::channels::ChannelConfig,
+) -> Result<(), String> {
+    crate::channels::create(&app, &channel)
+}
+
+/// Remove an Android notification channel
+#[tauri::command]
+pub fn delete_notification_channel(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    crate::channels::delete(&app, &id)
+}
+
+/// Ids of the app's Android notification channels; empty on iOS
+#[tauri::command]
+pub fn list_notification_channels(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    crate::channels::list(&app)
 }
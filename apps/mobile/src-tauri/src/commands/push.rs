@@ -0,0 +1,23 @@
+use tauri::AppHandle;
+
+use crate::push::{self, PushStatus};
+
+/// Register for push; the token goes to the backend once the OS hands it
+/// out. Emits `push-registered` when the backend has it, `push-error` if
+/// the OS refuses.
+#[tauri::command]
+pub fn register_for_push(app: AppHandle) -> Result<(), String> {
+    push::register(&app)
+}
+
+/// Stop pushes to this device
+#[tauri::command]
+pub async fn unregister_from_push(app: AppHandle) -> Result<(), String> {
+    push::unregister(&app).await
+}
+
+/// The device token, and whether the backend has it
+#[tauri::command]
+pub fn get_push_status(app: AppHandle) -> Result<PushStatus, String> {
+    push::status(&app)
+}
@@ -0,0 +1,97 @@
+use opensunsama_core::db::reminders::{self, Reminder};
+use opensunsama_core::db::time_blocks::{self, TimeBlock};
+use opensunsama_core::db::time_entries::{self, TimeEntry};
+use opensunsama_core::db::Database;
+use tauri::State;
+
+use crate::app_lock::AppLockState;
+
+/// Save a time block to the local schedule
+#[tauri::command]
+pub fn upsert_time_block(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+    block: TimeBlock,
+) -> Result<(), String> {
+    lock.check()?;
+    db.with_conn(|conn| time_blocks::upsert(conn, &block))
+}
+
+/// Remove a time block from the local schedule
+#[tauri::command]
+pub fn delete_time_block(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+    id: String,
+) -> Result<(), String> {
+    lock.check()?;
+    db.with_conn(|conn| time_blocks::delete(conn, &id))
+}
+
+/// List time blocks overlapping a range of Unix milliseconds
+#[tauri::command]
+pub fn list_time_blocks(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+    start: i64,
+    end: i64,
+) -> Result<Vec<TimeBlock>, String> {
+    lock.check()?;
+    db.with_conn(|conn| time_blocks::list_between(conn, start, end))
+}
+
+/// Save a reminder and schedule its local notification, which fires even
+/// when the app isn't running. Replaces a reminder with the same id.
+#[tauri::command]
+pub fn schedule_reminder(
+    lock: State<'_, AppLockState>,
+    app: tauri::AppHandle,
+    reminder: Reminder,
+) -> Result<(), String> {
+    lock.check()?;
+    crate::reminders::schedule(&app, &reminder)
+}
+
+/// Cancel a reminder and its notification
+#[tauri::command]
+pub fn cancel_reminder(
+    lock: State<'_, AppLockState>,
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<(), String> {
+    lock.check()?;
+    crate::reminders::cancel(&app, &id)
+}
+
+/// List reminders that have not fired yet, soonest first
+#[tauri::command]
+pub fn list_reminders(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+) -> Result<Vec<Reminder>, String> {
+    lock.check()?;
+    db.with_conn(|conn| reminders::list_pending(conn))
+}
+
+/// Record tracked time, or pick up the end of an entry already stored
+#[tauri::command]
+pub fn save_time_entry(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+    entry: TimeEntry,
+) -> Result<(), String> {
+    lock.check()?;
+    db.with_conn(|conn| time_entries::merge(conn, &entry))
+}
+
+/// List time entries overlapping a range of Unix milliseconds
+#[tauri::command]
+pub fn list_time_entries(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+    start: i64,
+    end: i64,
+) -> Result<Vec<TimeEntry>, String> {
+    lock.check()?;
+    db.with_conn(|conn| time_entries::list_between(conn, start, end))
+}
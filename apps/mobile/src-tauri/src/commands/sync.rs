@@ -0,0 +1,83 @@
+use chrono::Utc;
+use opensunsama_core::db::outbox::{self, OutboxEntry};
+use opensunsama_core::db::Database;
+use tauri::{AppHandle, State};
+
+use crate::api::{self, Session};
+use crate::app_lock::AppLockState;
+use crate::push;
+use crate::sync::{self, SyncSummary};
+
+/// How long the webview may hold the changes it took; a reload mid-way
+/// frees them after this
+const CLAIM_FOR: i64 = 2 * 60 * 1000;
+
+/// Queued changes ready to send to the backend, oldest first, claimed for
+/// the webview. Send them in order, report each with `finish_change`, and
+/// stop at the first failure. Empty while a native pass is sending.
+#[tauri::command]
+pub fn pending_changes(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+) -> Result<Vec<OutboxEntry>, String> {
+    lock.check()?;
+    let now = Utc::now().timestamp_millis();
+    db.with_conn(|conn| outbox::claim_due(conn, now, now, now + CLAIM_FOR))
+        .map(Option::unwrap_or_default)
+}
+
+/// Report how sending a queued change went: `error` is unset when the
+/// backend took it, and `notFound` when it answered 404. Returns whether the
+/// change left the queue; when it stays, the claim on the rest is given up.
+#[tauri::command]
+pub fn finish_change(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+    id: String,
+    error: Option<String>,
+    not_found: Option<bool>,
+) -> Result<bool, String> {
+    lock.check()?;
+    db.with_conn(|conn| {
+        let Some(error) = error else {
+            return outbox::delete(conn, &id);
+        };
+        let Some(entry) = outbox::get(conn, &id)? else {
+            return Ok(false);
+        };
+        let removed = sync::record_failure(conn, &entry, &error, not_found.unwrap_or(false))?;
+        if !removed {
+            outbox::release(conn)?;
+        }
+        Ok(removed)
+    })
+}
+
+/// Remember the server and token the native sync signs in with, or forget
+/// them on sign-out. The push token follows: it's dropped from the old
+/// session on any change (sign-out, another account or server) and handed
+/// to the new one.
+#[tauri::command]
+pub async fn set_sync_session(app: AppHandle, session: Option<Session>) -> Result<(), String> {
+    let previous = api::session(&app)?;
+    if previous != session {
+        if let Err(e) = push::forget(&app).await {
+            tracing::warn!("{}", e);
+        }
+    }
+    api::set_session(&app, session.as_ref())?;
+    if let Err(e) = push::upload(&app).await {
+        tracing::warn!("{}", e);
+    }
+    Ok(())
+}
+
+/// Run a native sync pass now
+#[tauri::command]
+pub async fn sync_now(
+    lock: State<'_, AppLockState>,
+    app: AppHandle,
+) -> Result<SyncSummary, String> {
+    lock.check()?;
+    sync::run_once(&app).await
+}
@@ -0,0 +1,66 @@
+use opensunsama_core::db::tasks::{self, TaskFilter, TaskPage, TaskSort};
+use opensunsama_core::db::Database;
+use serde_json::Value;
+use tauri::{AppHandle, State};
+
+use crate::app_lock::AppLockState;
+use crate::today;
+
+const DEFAULT_LIMIT: usize = 50;
+
+/// A page of the locally stored tasks matching `filter`. Pass the returned
+/// `nextCursor` back as `cursor` for the following page.
+#[tauri::command]
+pub fn query_tasks(
+    lock: State<'_, AppLockState>,
+    db: State<'_, Database>,
+    filter: Option<TaskFilter>,
+    sort: Option<TaskSort>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<TaskPage, String> {
+    lock.check()?;
+    db.with_conn(|conn| {
+        tasks::query(
+            conn,
+            &filter.unwrap_or_default(),
+            sort.unwrap_or_default(),
+            cursor.as_deref(),
+            limit.unwrap_or(DEFAULT_LIMIT),
+        )
+    })
+}
+
+/// Store tasks as the server returned them, replacing older copies
+#[tauri::command]
+pub fn save_tasks(
+    lock: State<'_, AppLockState>,
+    app: AppHandle,
+    db: State<'_, Database>,
+    tasks: Vec<Value>,
+) -> Result<(), String> {
+    lock.check()?;
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        for task in &tasks {
+            tasks::upsert(&tx, task)?;
+        }
+        tx.commit()
+    })?;
+    today::changed(&app);
+    Ok(())
+}
+
+/// Drop a task from local storage
+#[tauri::command]
+pub fn remove_task(
+    lock: State<'_, AppLockState>,
+    app: AppHandle,
+    db: State<'_, Database>,
+    id: String,
+) -> Result<(), String> {
+    lock.check()?;
+    db.with_conn(|conn| tasks::delete(conn, &id))?;
+    today::changed(&app);
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use opensunsama_core::db::time_entries::TimeEntry;
+use tauri::State;
+
+use crate::app_lock::AppLockState;
+use crate::live_activity::{self, FocusSession};
+use crate::timer::{self, TaskRef, TimerStatus};
+
+/// The running timer, if any, and how long it has run
+#[tauri::command]
+pub fn get_timer_status(
+    lock: State<'_, AppLockState>,
+    app: tauri::AppHandle,
+) -> Result<TimerStatus, String> {
+    lock.check()?;
+    timer::status(&app)
+}
+
+/// Start timing `task`, stopping a timer running on another task
+#[tauri::command]
+pub fn start_timer(
+    lock: State<'_, AppLockState>,
+    app: tauri::AppHandle,
+    task: TaskRef,
+) -> Result<TimerStatus, String> {
+    lock.check()?;
+    timer::start(&app, task)
+}
+
+/// Stop the running timer and return its entry
+#[tauri::command]
+pub fn stop_timer(
+    lock: State<'_, AppLockState>,
+    app: tauri::AppHandle,
+) -> Result<Option<TimeEntry>, String> {
+    lock.check()?;
+    timer::stop(&app)
+}
+
+/// Tell the native side a focus session started (`Some`) or ended (`None`),
+/// for the Live Activity
+#[tauri::command]
+pub fn set_focus_session(
+    lock: State<'_, AppLockState>,
+    app: tauri::AppHandle,
+    session: Option<FocusSession>,
+) -> Result<(), String> {
+    lock.check()?;
+    match session {
+        Some(session) => live_activity::start(&app, &session),
+        None => live_activity::end(&app),
+    }
+}
@@ -0,0 +1,15 @@
+use tauri::State;
+
+use crate::app_lock::AppLockState;
+use crate::widgets::{self, WidgetData};
+
+/// Rewrite the data the home-screen widgets show and return it. Native
+/// changes refresh it on their own; call this after changes made elsewhere.
+#[tauri::command]
+pub fn refresh_widget_data(
+    lock: State<'_, AppLockState>,
+    app: tauri::AppHandle,
+) -> Result<WidgetData, String> {
+    lock.check()?;
+    widgets::write(&app)
+}
@@ -0,0 +1,19 @@
+use opensunsama_core::db::{outbox, Database, DATABASE_FILE};
+use tauri::{AppHandle, Manager};
+
+/// Open the local database in the app data directory, creating and
+/// migrating it on first launch. Same schema as the desktop app; mobile
+/// links plain SQLite, so the file is never encrypted.
+pub fn init(app: &AppHandle) -> Result<Database, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    let db = Database::open(&dir.join(DATABASE_FILE), None)?;
+    // Nothing is sending yet; a claim left by a run that was killed
+    // mid-send would otherwise hold the outbox until it lapses
+    db.with_conn(|conn| outbox::release(conn))?;
+    Ok(db)
+}
@@ -0,0 +1,87 @@
+//! Siri, Shortcuts and Action button requests. The App Intents live on the
+//! iOS side; "What's next?" answers from the widget data, and the intents
+//! that change something hand an `IntentRequest` over to be applied here.
+//! The intent waits for `finishIntent` before confirming to the user.
+
+use chrono::Utc;
+use opensunsama_core::db::Database;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_bridge::BridgeExt;
+
+use crate::tasks::{self, TaskDraft};
+use crate::timer;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum IntentRequest {
+    AddTask {
+        title: String,
+    },
+    /// Times `task_id`, else the next open task today
+    #[serde(rename_all = "camelCase")]
+    StartFocus {
+        task_id: Option<String>,
+    },
+}
+
+/// A request as the intent sends it
+#[derive(Debug, Deserialize)]
+struct Pending {
+    /// Identifies the waiting intent
+    id: String,
+    #[serde(flatten)]
+    request: IntentRequest,
+}
+
+#[derive(Serialize)]
+struct Finish<'a> {
+    id: &'a str,
+    error: Option<&'a str>,
+}
+
+/// Start taking requests from the intents
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let handle = app.clone();
+    app.bridge()
+        .listen("listenIntents", move |pending: Pending| {
+            let result = handle_request(&handle, &pending.request);
+            if let Err(e) = &result {
+                tracing::warn!("Failed to apply a Siri request: {}", e);
+            }
+            let finish = Finish {
+                id: &pending.id,
+                error: result.as_ref().err().map(String::as_str),
+            };
+            if let Err(e) = handle.bridge().call::<Value>("finishIntent", &finish) {
+                tracing::warn!("{}", e);
+            }
+        })
+}
+
+/// Apply `request`. Emits `intent-applied` for a webview that's open.
+pub fn handle_request(app: &AppHandle, request: &IntentRequest) -> Result<(), String> {
+    match request {
+        IntentRequest::AddTask { title } => {
+            if title.trim().is_empty() {
+                return Err("The task needs a title".to_string());
+            }
+            let draft = TaskDraft {
+                title: title.clone(),
+                notes: None,
+                scheduled_date: None,
+            };
+            app.state::<Database>()
+                .with_conn(|conn| tasks::create(conn, &draft, Utc::now()))?;
+        }
+        IntentRequest::StartFocus { task_id } => {
+            match task_id {
+                Some(id) => timer::start_task(app, id)?,
+                None => timer::start_next(app)?,
+            };
+        }
+    }
+    crate::app_lock::emit(app, "intent-applied", request);
+    Ok(())
+}
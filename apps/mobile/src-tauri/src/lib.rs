@@ -1,21 +1,135 @@
+mod actions;
+mod api;
+mod app_lock;
+mod background;
+mod badge;
+mod channels;
 mod commands;
+mod db;
+mod intents;
+mod links;
+mod live_activity;
+mod push;
+mod reminders;
+mod secrets;
+mod share;
+mod sync;
+mod tasks;
+mod tiles;
+mod timer;
+mod today;
+mod watch;
+mod widgets;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Failures the app carries on past are logged, not surfaced
+    let _ = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .without_time()
+        .try_init();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_haptics::init())
         .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_biometric::init())
+        .plugin(tauri_plugin_bridge::init())
         .invoke_handler(tauri::generate_handler![
             commands::is_mobile,
             commands::get_platform,
             commands::haptics::trigger_haptic,
             commands::notifications::request_notification_permission,
-            commands::notifications::set_badge_count,
+            commands::notifications::schedule_daily_notification,
+            commands::notifications::cancel_daily_notification,
+            commands::notifications::create_notification_channel,
+            commands::notifications::delete_notification_channel,
+            commands::notifications::list_notification_channels,
+            commands::tasks::query_tasks,
+            commands::tasks::save_tasks,
+            commands::tasks::remove_task,
+            commands::schedule::upsert_time_block,
+            commands::schedule::delete_time_block,
+            commands::schedule::list_time_blocks,
+            commands::schedule::schedule_reminder,
+            commands::schedule::cancel_reminder,
+            commands::schedule::list_reminders,
+            commands::schedule::save_time_entry,
+            commands::schedule::list_time_entries,
+            commands::sync::pending_changes,
+            commands::sync::finish_change,
+            commands::sync::set_sync_session,
+            commands::sync::sync_now,
+            commands::push::register_for_push,
+            commands::push::unregister_from_push,
+            commands::push::get_push_status,
+            commands::app_lock::lock_now,
+            commands::app_lock::unlock_app,
+            commands::app_lock::is_app_locked,
+            commands::app_lock::get_app_lock_settings,
+            commands::app_lock::set_app_lock_settings,
+            commands::timer::get_timer_status,
+            commands::timer::start_timer,
+            commands::timer::stop_timer,
+            commands::timer::set_focus_session,
+            commands::widgets::refresh_widget_data,
+            commands::links::take_pending_routes,
         ])
-        .setup(|_app| {
+        .setup(|app| {
+            use tauri::Manager;
+
+            // Offline storage, same schema as the desktop app
+            app.manage(db::init(app.handle())?);
+            // Locked from the start when the lock is on, before the
+            // webview can ask for data
+            if let Err(e) = app_lock::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            // Views opened before the webview listens wait here
+            app.manage(links::RouteQueue::default());
+            app.manage(sync::SyncState::default());
+            // Channels and buttons before any reminder is scheduled: Android
+            // drops notifications posted to a channel that doesn't exist
+            if let Err(e) = channels::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            if let Err(e) = actions::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            if let Err(e) = reminders::restore(app.handle()) {
+                tracing::warn!("Failed to restore reminders: {}", e);
+            }
+            if let Err(e) = watch::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            if let Err(e) = intents::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            if let Err(e) = tiles::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            if let Err(e) = share::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            // The badge, widgets and watch show today's tasks; keep them
+            // current
+            today::changed(app.handle());
+            today::watch_date(app.handle().clone());
+            if let Err(e) = timer::restore(app.handle()) {
+                tracing::warn!("Failed to restore the timer: {}", e);
+            }
+            if let Err(e) = background::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            if let Err(e) = push::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+            // Last, so a launch link finds everything set up
+            if let Err(e) = links::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+
             // DevTools not available on mobile platforms
             // On desktop, devtools would be opened here in debug mode
             Ok(())
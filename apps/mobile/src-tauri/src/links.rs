@@ -0,0 +1,271 @@
+//! Deep links (`opensunsama://…`) and universal links
+//! (`https://opensunsama.com/…`) as typed routes:
+//!
+//! - `task/{id}`: open a task
+//! - `today`, `day/{YYYY-MM-DD}`, `settings`: open a view
+//! - `new-task`: open quick add
+//! - `add-task?title=…&notes=…&date=YYYY-MM-DD`: quick add, filled in
+//! - `start-timer?taskId=…`, `stop-timer`: offer to run the timer (the next
+//!   open task when no id is given)
+//! - `share`: take what the iOS share extension left
+//!
+//! Any web page or app can open these links, so nothing a link asks for
+//! happens without the user: views go to the webview as a `navigate` event
+//! with the frontend route, like on desktop; adding a task opens quick add
+//! filled in (`quick-add-task`), and timer links ask first
+//! (`confirm-timer`). Until the webview has asked for `take_pending`, it
+//! isn't listening yet (the link launched the app), so events wait in a
+//! queue.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::share;
+
+pub const SCHEME: &str = "opensunsama";
+/// Universal links are served from this domain and its subdomains
+const DOMAIN: &str = "opensunsama.com";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    Task {
+        task_id: String,
+    },
+    Today,
+    Day {
+        date: NaiveDate,
+    },
+    Settings,
+    QuickAdd,
+    AddTask {
+        title: String,
+        notes: Option<String>,
+        scheduled_date: Option<NaiveDate>,
+    },
+    StartTimer {
+        task_id: Option<String>,
+    },
+    StopTimer,
+    Share,
+}
+
+impl Route {
+    pub fn parse(url: &Url) -> Result<Self, String> {
+        let segments: Vec<&str> = match url.scheme() {
+            SCHEME => url
+                .host_str()
+                .into_iter()
+                .chain(url.path_segments().into_iter().flatten())
+                .filter(|segment| !segment.is_empty())
+                .collect(),
+            "https"
+                if url.host_str().is_some_and(|host| {
+                    host == DOMAIN || host.ends_with(&format!(".{}", DOMAIN))
+                }) =>
+            {
+                url.path_segments()
+                    .into_iter()
+                    .flatten()
+                    .filter(|segment| !segment.is_empty())
+                    .collect()
+            }
+            _ => return Err(format!("Not an Open Sunsama link: {}", url)),
+        };
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let param = |key: &str| {
+            params
+                .get(key)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let date = |date: &str| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|e| format!("Invalid date '{}': {}", date, e))
+        };
+
+        match segments.as_slice() {
+            [] | ["today"] => Ok(Route::Today),
+            ["task", id] | ["tasks", id] => Ok(Route::Task {
+                task_id: id.to_string(),
+            }),
+            ["day", day] => Ok(Route::Day { date: date(day)? }),
+            ["settings", ..] => Ok(Route::Settings),
+            ["new-task"] => Ok(Route::QuickAdd),
+            ["add-task"] => Ok(Route::AddTask {
+                title: param("title").ok_or_else(|| "add-task needs a title".to_string())?,
+                notes: param("notes"),
+                scheduled_date: param("date").as_deref().map(date).transpose()?,
+            }),
+            ["start-timer"] => Ok(Route::StartTimer {
+                task_id: param("taskId"),
+            }),
+            ["stop-timer"] => Ok(Route::StopTimer),
+            ["share"] => Ok(Route::Share),
+            _ => Err(format!("Unknown link {}", url)),
+        }
+    }
+
+    /// The event that shows this route in the webview, and its payload
+    fn event(&self) -> LinkEvent {
+        let (event, payload) = match self {
+            Route::Task { task_id } => ("navigate", json!(format!("/app/tasks/{}", task_id))),
+            Route::Today | Route::Share => ("navigate", json!("/app")),
+            Route::Day { date } => ("navigate", json!(format!("/app?date={}", date))),
+            Route::Settings => ("navigate", json!("/app/settings")),
+            Route::QuickAdd => ("quick-add-task", Value::Null),
+            Route::AddTask {
+                title,
+                notes,
+                scheduled_date,
+            } => (
+                "quick-add-task",
+                json!({ "title": title, "notes": notes, "scheduledDate": scheduled_date }),
+            ),
+            Route::StartTimer { task_id } => (
+                "confirm-timer",
+                json!({ "action": "start", "taskId": task_id }),
+            ),
+            Route::StopTimer => ("confirm-timer", json!({ "action": "stop" })),
+        };
+        LinkEvent { event, payload }
+    }
+}
+
+/// What a link shows in the webview
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkEvent {
+    pub event: &'static str,
+    pub payload: Value,
+}
+
+/// Events waiting for the webview; `None` once it takes them
+pub struct RouteQueue(Mutex<Option<Vec<LinkEvent>>>);
+
+impl Default for RouteQueue {
+    fn default() -> Self {
+        RouteQueue(Mutex::new(Some(Vec::new())))
+    }
+}
+
+/// Handle the link the app was launched with and every later one
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        handle_urls(&handle, &event.urls());
+    });
+    if let Some(urls) = app
+        .deep_link()
+        .get_current()
+        .map_err(|e| format!("Failed to read the launch link: {}", e))?
+    {
+        handle_urls(app, &urls);
+    }
+    Ok(())
+}
+
+pub fn handle_urls(app: &AppHandle, urls: &[Url]) {
+    for url in urls {
+        if let Err(e) = Route::parse(url).and_then(|route| open(app, route)) {
+            tracing::warn!("Failed to open {}: {}", url, e);
+        }
+    }
+}
+
+/// Take the iOS share inbox for a `share` link; show every other route in
+/// the webview, where the user confirms anything that changes data
+pub fn open(app: &AppHandle, route: Route) -> Result<(), String> {
+    if route == Route::Share {
+        return share::take_inbox(app, !webview_ready(app));
+    }
+    navigate(app, route);
+    Ok(())
+}
+
+/// Show `route` in the webview, or queue it until the webview is listening
+pub fn navigate(app: &AppHandle, route: Route) {
+    let link = route.event();
+    if let Ok(mut queue) = app.state::<RouteQueue>().0.lock() {
+        if let Some(pending) = queue.as_mut() {
+            pending.push(link);
+            return;
+        }
+    }
+    crate::app_lock::emit(app, link.event, link.payload);
+}
+
+/// The events queued before the webview listened, to handle as if they had
+/// just arrived
+pub fn take_pending(app: &AppHandle) -> Vec<LinkEvent> {
+    app.state::<RouteQueue>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|mut queue| queue.take())
+        .unwrap_or_default()
+}
+
+fn webview_ready(app: &AppHandle) -> bool {
+    app.state::<RouteQueue>()
+        .0
+        .lock()
+        .is_ok_and(|queue| queue.is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> Result<Route, String> {
+        Route::parse(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn custom_scheme_and_universal_links_route_the_same() {
+        let task = Route::Task {
+            task_id: "123".to_string(),
+        };
+        assert_eq!(parse("opensunsama://task/123"), Ok(task.clone()));
+        assert_eq!(parse("https://opensunsama.com/task/123"), Ok(task.clone()));
+        assert_eq!(parse("https://app.opensunsama.com/tasks/123/"), Ok(task));
+        assert_eq!(parse("opensunsama://today"), Ok(Route::Today));
+        assert_eq!(
+            parse("opensunsama://day/2026-03-04"),
+            Ok(Route::Day {
+                date: NaiveDate::from_ymd_opt(2026, 3, 4).unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn views_navigate_by_frontend_route() {
+        let task = parse("opensunsama://task/123").unwrap().event();
+        assert_eq!(task.event, "navigate");
+        assert_eq!(task.payload, json!("/app/tasks/123"));
+        let add = parse("opensunsama://add-task?title=Buy%20milk")
+            .unwrap()
+            .event();
+        assert_eq!(add.event, "quick-add-task");
+        assert_eq!(add.payload["title"], json!("Buy milk"));
+    }
+
+    #[test]
+    fn actions_take_their_parameters_from_the_query() {
+        assert_eq!(
+            parse("opensunsama://add-task?title=Buy%20milk&date=2026-03-04"),
+            Ok(Route::AddTask {
+                title: "Buy milk".to_string(),
+                notes: None,
+                scheduled_date: NaiveDate::from_ymd_opt(2026, 3, 4),
+            })
+        );
+        assert!(parse("opensunsama://add-task").is_err());
+        assert!(parse("opensunsama://day/tomorrow").is_err());
+        assert!(parse("https://example.com/task/123").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+//! The webview's focus session as an iOS Live Activity, on the lock screen
+//! and in the Dynamic Island. The webview runs the session and reports it
+//! here as it starts and ends; the activity counts down to the session's
+//! end on its own, so nothing updates it while it runs. Does nothing on
+//! Android and on iOS before 16.2.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusSession {
+    pub task_id: String,
+    pub task_title: String,
+    /// Unix milliseconds
+    pub started_at: i64,
+    /// Unix milliseconds the session is planned to end at
+    pub ends_at: i64,
+}
+
+/// Show `session`, replacing the activity of an earlier one
+pub fn start(app: &AppHandle, session: &FocusSession) -> Result<(), String> {
+    call(app, "startLiveActivity", session)
+}
+
+pub fn end(app: &AppHandle) -> Result<(), String> {
+    call(app, "endLiveActivity", ())
+}
+
+#[cfg(target_os = "ios")]
+fn call(app: &AppHandle, method: &str, payload: impl Serialize) -> Result<(), String> {
+    use tauri_plugin_bridge::BridgeExt;
+
+    app.bridge().call::<serde_json::Value>(method, payload)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "ios"))]
+fn call(_app: &AppHandle, _method: &str, _payload: impl Serialize) -> Result<(), String> {
+    Ok(())
+}
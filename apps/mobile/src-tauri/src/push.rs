@@ -0,0 +1,178 @@
+//! Push notifications through APNs and FCM. The backend sends a silent data
+//! push when something changes elsewhere, and the app runs a sync pass
+//! instead of polling. The OS hands out a device token and may rotate it at
+//! any time; each token goes to the backend under the synced session, and
+//! the one it replaces is dropped there.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_bridge::BridgeExt;
+use tauri_plugin_store::StoreExt;
+
+use crate::api::{self, Api};
+use crate::sync;
+
+const STORE: &str = "push.json";
+/// The latest token from the OS
+const TOKEN: &str = "token";
+/// The token the backend has
+const REGISTERED: &str = "registered";
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum PushEvent {
+    /// A new or rotated token
+    Token { token: String },
+    /// The OS refused to register
+    Error { message: String },
+    /// A data push. `id` is set when the OS waits for `finishPush`.
+    Data { id: Option<String> },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Finish<'a> {
+    id: &'a str,
+    success: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushStatus {
+    pub token: Option<String>,
+    /// The backend has the current token
+    pub registered: bool,
+}
+
+/// Start taking tokens and pushes. Registering is left to
+/// `register_for_push`, so the permission prompt comes when the webview
+/// asks for it.
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    if !cfg!(mobile) {
+        return Ok(());
+    }
+    let handle = app.clone();
+    app.bridge().listen("listenPush", move |event: PushEvent| {
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move { receive(&app, event).await });
+    })
+}
+
+/// Ask the OS for a token; it arrives through the listener, now or later
+pub fn register(app: &AppHandle) -> Result<(), String> {
+    app.bridge().call::<Value>("registerForPush", ())?;
+    Ok(())
+}
+
+/// Stop pushes to this device, here and on the backend
+pub async fn unregister(app: &AppHandle) -> Result<(), String> {
+    forget(app).await?;
+    app.bridge().call::<Value>("unregisterForPush", ())?;
+    set(app, TOKEN, None)
+}
+
+pub fn status(app: &AppHandle) -> Result<PushStatus, String> {
+    let token = get(app, TOKEN)?;
+    let registered = token.is_some() && token == get(app, REGISTERED)?;
+    Ok(PushStatus { token, registered })
+}
+
+/// Hand the latest token to the backend if it doesn't have it yet, dropping
+/// the one it replaces. Waits for a session while signed out.
+pub async fn upload(app: &AppHandle) -> Result<(), String> {
+    let (Some(token), Some(session)) = (get(app, TOKEN)?, api::session(app)?) else {
+        return Ok(());
+    };
+    let previous = get(app, REGISTERED)?;
+    if previous.as_deref() == Some(token.as_str()) {
+        return Ok(());
+    }
+    let api = Api::new(session)?;
+    let body = json!({ "token": token, "platform": crate::commands::get_platform() });
+    api.send_raw("POST", "/devices", Some(&body.to_string()))
+        .await
+        .map_err(|e| format!("Failed to register for push: {}", e.message))?;
+    set(app, REGISTERED, Some(&token))?;
+    if let Some(previous) = previous {
+        if let Err(e) = api
+            .send_raw("DELETE", &format!("/devices/{}", previous), None)
+            .await
+        {
+            if !e.not_found {
+                tracing::warn!("Failed to drop the old push token: {}", e.message);
+            }
+        }
+    }
+    let _ = app.emit("push-registered", status(app)?);
+    Ok(())
+}
+
+/// Drop this device's token on the backend, e.g. before signing out. It
+/// counts as unregistered here even when the backend can't be told, so the
+/// next session still gets it.
+pub async fn forget(app: &AppHandle) -> Result<(), String> {
+    let (Some(token), Some(session)) = (get(app, REGISTERED)?, api::session(app)?) else {
+        return Ok(());
+    };
+    let result = match Api::new(session)?
+        .send_raw("DELETE", &format!("/devices/{}", token), None)
+        .await
+    {
+        Err(e) if !e.not_found => Err(format!("Failed to unregister from push: {}", e.message)),
+        _ => Ok(()),
+    };
+    set(app, REGISTERED, None)?;
+    result
+}
+
+async fn receive(app: &AppHandle, event: PushEvent) {
+    match event {
+        PushEvent::Token { token } => {
+            if let Err(e) = set(app, TOKEN, Some(&token)) {
+                tracing::warn!("{}", e);
+                return;
+            }
+            if let Err(e) = upload(app).await {
+                tracing::warn!("{}", e);
+            }
+        }
+        PushEvent::Error { message } => {
+            tracing::warn!("Failed to register for push: {}", message);
+            let _ = app.emit("push-error", message);
+        }
+        PushEvent::Data { id } => {
+            let success = match sync::run_once(app).await {
+                Ok(_) => true,
+                Err(e) => {
+                    tracing::warn!("Sync after a push failed: {}", e);
+                    false
+                }
+            };
+            if let Some(id) = id {
+                let finish = Finish { id: &id, success };
+                if let Err(e) = app.bridge().call::<Value>("finishPush", &finish) {
+                    tracing::warn!("{}", e);
+                }
+            }
+        }
+    }
+}
+
+fn get(app: &AppHandle, key: &str) -> Result<Option<String>, String> {
+    let store = app.store(STORE).map_err(|e| e.to_string())?;
+    Ok(store
+        .get(key)
+        .and_then(|value| value.as_str().map(str::to_string)))
+}
+
+fn set(app: &AppHandle, key: &str, value: Option<&str>) -> Result<(), String> {
+    let store = app.store(STORE).map_err(|e| e.to_string())?;
+    match value {
+        Some(value) => store.set(key, value),
+        None => {
+            store.delete(key);
+        }
+    }
+    store.save().map_err(|e| e.to_string())
+}
@@ -0,0 +1,201 @@
+//! Reminders as OS-level local notifications. Reminders are kept in the
+//! local database and handed to the OS, which shows them at their time even
+//! when the app has been killed. The notification plugin re-arms scheduled
+//! notifications after an Android reboot; on every launch the pending
+//! reminders are handed over again in case the OS dropped any. iOS keeps at
+//! most 64 pending notifications, so only the soonest reminders are handed
+//! over, and each launch and sync pass tops them up as earlier ones fire.
+//! Daily nudges (the planning and shutdown rituals) repeat at a local time
+//! and live only with the OS.
+
+use opensunsama_core::db::reminders::{self, Reminder};
+use opensunsama_core::db::Database;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// Reminders handed to the OS at once. iOS drops anything past 64 pending
+/// notifications; the rest of the room is for the daily nudges.
+const MAX_SCHEDULED: usize = 60;
+
+/// Save `reminder` and schedule its notification, replacing any earlier one
+/// with the same id. It waits in the database if it isn't among the
+/// soonest reminders.
+pub fn schedule(app: &AppHandle, reminder: &Reminder) -> Result<(), String> {
+    app.state::<Database>()
+        .with_conn(|conn| reminders::upsert(conn, reminder))?;
+    restore(app)?;
+    Ok(())
+}
+
+/// Drop a reminder and its pending notification
+pub fn cancel(app: &AppHandle, id: &str) -> Result<(), String> {
+    app.state::<Database>()
+        .with_conn(|conn| reminders::delete(conn, id))?;
+    os::cancel(app, id)
+}
+
+/// Hand the soonest future reminders to the OS again and withdraw later
+/// ones, so the OS's slots go to what fires next. Returns how many were
+/// handed over.
+pub fn restore(app: &AppHandle) -> Result<usize, String> {
+    let now = now_millis();
+    let pending = app
+        .state::<Database>()
+        .with_conn(|conn| reminders::list_pending(conn))?;
+    // Pending reminders come soonest first
+    let mut upcoming = pending.iter().filter(|reminder| reminder.fire_at > now);
+    let mut scheduled = 0;
+    for reminder in upcoming.by_ref().take(MAX_SCHEDULED) {
+        os::schedule_at(app, reminder)?;
+        scheduled += 1;
+    }
+    for reminder in upcoming {
+        os::cancel(app, &reminder.id)?;
+    }
+    Ok(scheduled)
+}
+
+/// Show a notification every day at `time` (`HH:MM`, local), e.g. a ritual
+/// nudge. Scheduling the same id again moves it.
+pub fn schedule_daily(
+    app: &AppHandle,
+    id: &str,
+    title: &str,
+    body: Option<&str>,
+    time: &str,
+) -> Result<(), String> {
+    let (hour, minute) = time
+        .split_once(':')
+        .and_then(|(hour, minute)| Some((hour.parse::<u8>().ok()?, minute.parse::<u8>().ok()?)))
+        .filter(|(hour, minute)| *hour < 24 && *minute < 60)
+        .ok_or_else(|| format!("Invalid time '{}', expected HH:MM", time))?;
+    os::schedule_daily(app, id, title, body, hour, minute)
+}
+
+/// Stop a daily notification
+pub fn cancel_daily(app: &AppHandle, id: &str) -> Result<(), String> {
+    os::cancel(app, id)
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(mobile)]
+mod os {
+    use tauri::AppHandle;
+    use tauri_plugin_notification::{NotificationExt, Schedule, ScheduleInterval};
+    use time::OffsetDateTime;
+
+    use super::Reminder;
+    use crate::{actions, channels};
+
+    pub fn schedule_at(app: &AppHandle, reminder: &Reminder) -> Result<(), String> {
+        let date =
+            OffsetDateTime::from_unix_timestamp_nanos(i128::from(reminder.fire_at) * 1_000_000)
+                .map_err(|e| format!("Invalid reminder time: {}", e))?;
+        let mut builder = app
+            .notification()
+            .builder()
+            .id(notification_id(&reminder.id))
+            .title(&reminder.title)
+            .channel_id(channels::REMINDERS)
+            .schedule(Schedule::At {
+                date,
+                repeating: false,
+                allow_while_idle: true,
+            });
+        if let Some(body) = &reminder.body {
+            builder = builder.body(body);
+        }
+        // Reminders for a task get the Complete and Snooze buttons
+        if reminder.task_id.is_some() {
+            builder = builder
+                .action_type_id(actions::ACTION_TYPE)
+                .extra("reminderId", &reminder.id);
+        }
+        builder
+            .show()
+            .map_err(|e| format!("Failed to schedule notification: {}", e))
+    }
+
+    pub fn schedule_daily(
+        app: &AppHandle,
+        id: &str,
+        title: &str,
+        body: Option<&str>,
+        hour: u8,
+        minute: u8,
+    ) -> Result<(), String> {
+        let mut builder = app
+            .notification()
+            .builder()
+            .id(notification_id(id))
+            .title(title)
+            .channel_id(channels::REMINDERS)
+            .schedule(Schedule::Interval {
+                interval: ScheduleInterval {
+                    year: None,
+                    month: None,
+                    day: None,
+                    weekday: None,
+                    hour: Some(hour),
+                    minute: Some(minute),
+                    second: Some(0),
+                },
+                allow_while_idle: true,
+            });
+        if let Some(body) = body {
+            builder = builder.body(body);
+        }
+        builder
+            .show()
+            .map_err(|e| format!("Failed to schedule notification: {}", e))
+    }
+
+    pub fn cancel(app: &AppHandle, id: &str) -> Result<(), String> {
+        app.notification()
+            .cancel(vec![notification_id(id)])
+            .map_err(|e| format!("Failed to cancel notification: {}", e))
+    }
+
+    /// The OS wants a 32-bit notification id; derive a stable one from ours
+    /// (FNV-1a) so a notification can be rescheduled or cancelled later
+    fn notification_id(id: &str) -> i32 {
+        let hash = id.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        });
+        (hash & 0x7fff_ffff) as i32
+    }
+}
+
+/// Desktop builds (used while developing the mobile UI) have no scheduled
+/// notifications; reminders are only stored
+#[cfg(not(mobile))]
+mod os {
+    use tauri::AppHandle;
+
+    use super::Reminder;
+
+    pub fn schedule_at(_app: &AppHandle, _reminder: &Reminder) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn schedule_daily(
+        _app: &AppHandle,
+        _id: &str,
+        _title: &str,
+        _body: Option<&str>,
+        _hour: u8,
+        _minute: u8,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn cancel(_app: &AppHandle, _id: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
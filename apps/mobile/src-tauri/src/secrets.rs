@@ -0,0 +1,72 @@
+//! Secrets in the platform's own keystore: the Keychain on iOS, preferences
+//! encrypted with an Android Keystore key on Android. Desktop dev builds,
+//! which have neither, keep them in the app store.
+
+use tauri::AppHandle;
+
+#[cfg(mobile)]
+use serde::{Deserialize, Serialize};
+
+#[cfg(mobile)]
+use tauri_plugin_bridge::BridgeExt;
+#[cfg(not(mobile))]
+use tauri_plugin_store::StoreExt;
+
+#[cfg(not(mobile))]
+const STORE: &str = "secrets.json";
+
+#[cfg(mobile)]
+#[derive(Serialize)]
+struct SecretArgs<'a> {
+    key: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<&'a str>,
+}
+
+#[cfg(mobile)]
+#[derive(Deserialize)]
+struct Secret {
+    value: Option<String>,
+}
+
+#[cfg(mobile)]
+pub fn get(app: &AppHandle, key: &str) -> Result<Option<String>, String> {
+    let secret = app
+        .bridge()
+        .call::<Secret>("getSecret", SecretArgs { key, value: None })?;
+    Ok(secret.and_then(|secret| secret.value))
+}
+
+/// Store `value` under `key`, or delete it
+#[cfg(mobile)]
+pub fn set(app: &AppHandle, key: &str, value: Option<&str>) -> Result<(), String> {
+    let method = if value.is_some() {
+        "setSecret"
+    } else {
+        "deleteSecret"
+    };
+    app.bridge()
+        .call::<serde_json::Value>(method, SecretArgs { key, value })?;
+    Ok(())
+}
+
+#[cfg(not(mobile))]
+pub fn get(app: &AppHandle, key: &str) -> Result<Option<String>, String> {
+    let store = app.store(STORE).map_err(|e| e.to_string())?;
+    Ok(store
+        .get(key)
+        .and_then(|value| value.as_str().map(String::from)))
+}
+
+/// Store `value` under `key`, or delete it
+#[cfg(not(mobile))]
+pub fn set(app: &AppHandle, key: &str, value: Option<&str>) -> Result<(), String> {
+    let store = app.store(STORE).map_err(|e| e.to_string())?;
+    match value {
+        Some(value) => store.set(key, value),
+        None => {
+            store.delete(key);
+        }
+    }
+    store.save().map_err(|e| e.to_string())
+}
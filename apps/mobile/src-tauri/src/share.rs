@@ -0,0 +1,191 @@
+//! Text and links shared into the app from other apps: through the share
+//! target on Android, and on iOS through the share extension, which leaves
+//! shares in the app group and opens `opensunsama://share`. A share becomes
+//! a task draft: the link's page title (or the text) as the title, the link
+//! and any other text in the notes. With the app open the webview gets the
+//! draft to review; a share that launched the app is added straight away,
+//! since there's no view to review it in yet.
+
+use chrono::Utc;
+use opensunsama_core::db::Database;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_bridge::BridgeExt;
+
+use crate::tasks::{self, TaskDraft};
+
+/// Titles are cut here; the full text stays in the notes
+const MAX_TITLE: usize = 200;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedContent {
+    pub text: Option<String>,
+    /// The subject or page title the sharing app sent along
+    pub subject: Option<String>,
+    /// The shared link, when it didn't come as part of `text`
+    pub url: Option<String>,
+    /// The sharing app, e.g. `com.android.chrome` or `safari`
+    pub source: Option<String>,
+    /// It arrived before the app was up
+    #[serde(default)]
+    pub cold_start: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedTask {
+    pub draft: TaskDraft,
+    pub url: Option<String>,
+    pub source: Option<String>,
+    /// Already queued for creation, rather than waiting for review
+    pub created: bool,
+}
+
+#[derive(Deserialize)]
+struct Inbox {
+    items: Vec<SharedContent>,
+}
+
+/// Start taking shares. On iOS that's the ones left while the app wasn't
+/// running; later ones come with `opensunsama://share`.
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    if cfg!(target_os = "ios") {
+        return take_inbox(app, true);
+    }
+    if !cfg!(target_os = "android") {
+        return Ok(());
+    }
+    let handle = app.clone();
+    app.bridge()
+        .listen("listenShares", move |content: SharedContent| {
+            if let Err(e) = receive(&handle, &content) {
+                tracing::warn!("Failed to take a share: {}", e);
+            }
+        })
+}
+
+/// Take what the iOS share extension left in the app group
+pub fn take_inbox(app: &AppHandle, cold_start: bool) -> Result<(), String> {
+    let Some(inbox) = app.bridge().call::<Inbox>("takeShares", ())? else {
+        return Ok(());
+    };
+    for mut content in inbox.items {
+        content.cold_start = cold_start;
+        if let Err(e) = receive(app, &content) {
+            tracing::warn!("Failed to take a share: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Turn `content` into a task, or a draft for the webview. Emits
+/// `share-received` with the `SharedTask` either way.
+pub fn receive(app: &AppHandle, content: &SharedContent) -> Result<SharedTask, String> {
+    let (draft, url) =
+        to_draft(content).ok_or_else(|| "Nothing to make a task from".to_string())?;
+    if content.cold_start {
+        app.state::<Database>()
+            .with_conn(|conn| tasks::create(conn, &draft, Utc::now()))?;
+    }
+    let shared = SharedTask {
+        draft,
+        url,
+        source: content.source.clone(),
+        created: content.cold_start,
+    };
+    crate::app_lock::emit(app, "share-received", &shared);
+    Ok(shared)
+}
+
+/// The draft for `content` and the link in it, if any
+pub fn to_draft(content: &SharedContent) -> Option<(TaskDraft, Option<String>)> {
+    let text = content.text.as_deref().unwrap_or_default().trim();
+    let url = content
+        .url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .or_else(|| {
+            text.split_whitespace()
+                .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+        })
+        .map(str::to_string);
+    let rest = match &url {
+        Some(url) => text.replacen(url.as_str(), "", 1).trim().to_string(),
+        None => text.to_string(),
+    };
+
+    let title = content
+        .subject
+        .as_deref()
+        .map(str::trim)
+        .filter(|subject| !subject.is_empty())
+        .or_else(|| rest.lines().map(str::trim).find(|line| !line.is_empty()))
+        .map(str::to_string)
+        .or_else(|| url.clone())?;
+    let title: String = title.chars().take(MAX_TITLE).collect();
+
+    let notes: Vec<&str> = url
+        .as_deref()
+        .into_iter()
+        .chain(Some(rest.as_str()).filter(|rest| !rest.is_empty() && *rest != title))
+        .collect();
+    Some((
+        TaskDraft {
+            title,
+            notes: (!notes.is_empty()).then(|| notes.join("\n\n")),
+            scheduled_date: None,
+        },
+        url,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shared(text: &str, subject: Option<&str>) -> SharedContent {
+        SharedContent {
+            text: Some(text.to_string()),
+            subject: subject.map(str::to_string),
+            ..SharedContent::default()
+        }
+    }
+
+    #[test]
+    fn links_go_to_the_notes_under_the_page_title() {
+        let (draft, url) = to_draft(&shared(
+            "Worth reading https://example.com/post",
+            Some("A post"),
+        ))
+        .unwrap();
+        assert_eq!(draft.title, "A post");
+        assert_eq!(url.as_deref(), Some("https://example.com/post"));
+        assert_eq!(
+            draft.notes.as_deref(),
+            Some("https://example.com/post\n\nWorth reading")
+        );
+
+        // The iOS share extension sends Safari's link on its own
+        let (draft, _) = to_draft(&SharedContent {
+            url: Some("https://example.com".to_string()),
+            subject: Some("Example".to_string()),
+            ..SharedContent::default()
+        })
+        .unwrap();
+        assert_eq!(draft.title, "Example");
+        assert_eq!(draft.notes.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn plain_text_is_the_title() {
+        let (draft, url) = to_draft(&shared("  Call the dentist\n", None)).unwrap();
+        assert_eq!(draft.title, "Call the dentist");
+        assert!(draft.notes.is_none() && url.is_none());
+
+        let (draft, _) = to_draft(&shared("https://example.com", None)).unwrap();
+        assert_eq!(draft.title, "https://example.com");
+        assert!(to_draft(&shared("  ", None)).is_none());
+    }
+}
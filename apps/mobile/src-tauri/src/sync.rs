@@ -0,0 +1,153 @@
+//! The native sync pass, for when the webview can't run it: the OS woke the
+//! app in the background, or a push said something changed. Sends the
+//! outbox, downloads the tasks changed since the last download, then
+//! refreshes everything that shows them.
+
+use chrono::Utc;
+use opensunsama_core::db::outbox::{self, OutboxEntry};
+use opensunsama_core::db::{sync_cursors, tasks, Database};
+use opensunsama_core::sync::queue::{self, Failure};
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::api::{self, Api};
+use crate::{reminders, today};
+
+const TASKS_CURSOR: &str = "tasks";
+/// How long a pass may hold the outbox; one that dies mid-way frees it
+/// after this
+const CLAIM_FOR: i64 = 5 * 60 * 1000;
+
+/// Keeps passes from overlapping. A pass asked for while another runs is
+/// marked as needed and runs next; asks that pile up meanwhile share it.
+#[derive(Default)]
+pub struct SyncState {
+    running: Mutex<()>,
+    needed: AtomicBool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSummary {
+    /// Queued changes the backend took
+    pub sent: usize,
+    /// Tasks downloaded
+    pub received: usize,
+}
+
+/// Run one pass, after the one running, if any. A no-op while signed out,
+/// and for a caller whose ask a pass that started since already covered.
+/// Emits `sync-completed` with the summary.
+pub async fn run_once(app: &AppHandle) -> Result<SyncSummary, String> {
+    let state = app.state::<SyncState>();
+    state.needed.store(true, Ordering::SeqCst);
+    let _running = state.running.lock().await;
+    if !state.needed.swap(false, Ordering::SeqCst) {
+        return Ok(SyncSummary::default());
+    }
+    let Some(session) = api::session(app)? else {
+        return Ok(SyncSummary::default());
+    };
+    let api = Api::new(session)?;
+
+    let sent = flush(app, &api).await?;
+    let received = pull(app, &api).await?;
+    if received > 0 {
+        today::changed(app);
+    }
+    if let Err(e) = reminders::restore(app) {
+        tracing::warn!("Failed to restore reminders: {}", e);
+    }
+
+    let summary = SyncSummary { sent, received };
+    crate::app_lock::emit(app, "sync-completed", &summary);
+    Ok(summary)
+}
+
+/// Send due outbox entries in order, stopping at the first one that
+/// should be retried. Returns how many went out. Entries are claimed
+/// first, so this never sends what the webview is sending.
+async fn flush(app: &AppHandle, api: &Api) -> Result<usize, String> {
+    let db = app.state::<Database>();
+    let now = Utc::now().timestamp_millis();
+    // Someone else holding the claim sends these itself
+    let Some(due) = db.with_conn(|conn| outbox::claim_due(conn, now, now, now + CLAIM_FOR))?
+    else {
+        return Ok(0);
+    };
+    if due.is_empty() {
+        return Ok(0);
+    }
+    let result = send_claimed(app, api, due).await;
+    db.with_conn(|conn| outbox::release(conn))?;
+    result
+}
+
+async fn send_claimed(app: &AppHandle, api: &Api, due: Vec<OutboxEntry>) -> Result<usize, String> {
+    let db = app.state::<Database>();
+    let mut sent = 0;
+    for entry in due {
+        let result = api
+            .send_raw(&entry.method, &entry.path, entry.body.as_deref())
+            .await;
+        let removed = db.with_conn(|conn| match &result {
+            Ok(()) => outbox::delete(conn, &entry.id),
+            Err(e) => record_failure(conn, &entry, &e.message, e.not_found),
+        })?;
+        match result {
+            Ok(()) => sent += 1,
+            Err(e) if !removed => {
+                return Err(format!("Failed to send queued change: {}", e.message))
+            }
+            Err(_) => {}
+        }
+    }
+    Ok(sent)
+}
+
+/// Download what changed on the server since the last download, deletions
+/// included, page by page. The cursor is the server's newest `updatedAt`,
+/// so local edits (which stamp the device's time) can't skip anything.
+async fn pull(app: &AppHandle, api: &Api) -> Result<usize, String> {
+    let db = app.state::<Database>();
+    let since = db.with_conn(|conn| sync_cursors::get(conn, TASKS_CURSOR))?;
+    let mut path = "/tasks?includeDeleted=true".to_string();
+    if let Some(since) = &since {
+        path.push_str("&updatedSince=");
+        // An RFC 3339 offset's `+` would read as a space
+        path.push_str(&since.replace('+', "%2B"));
+    }
+    let changed: Vec<Value> = api
+        .get_all(&path)
+        .await
+        .map_err(|e| format!("Failed to download tasks: {}", e.message))?;
+    db.with_conn(|conn| {
+        let tx = conn.transaction()?;
+        if let Some(cursor) = tasks::apply_changes(&tx, &changed)? {
+            sync_cursors::set(&tx, TASKS_CURSOR, &cursor)?;
+        }
+        tx.commit()
+    })?;
+    Ok(changed.len())
+}
+
+/// Record that sending `entry` failed. Returns whether it left the queue:
+/// its target is gone, or it failed too often.
+pub fn record_failure(
+    conn: &Connection,
+    entry: &OutboxEntry,
+    error: &str,
+    not_found: bool,
+) -> rusqlite::Result<bool> {
+    match queue::on_failure(entry, not_found) {
+        Failure::Gone | Failure::GiveUp => outbox::delete(conn, &entry.id),
+        Failure::Retry => {
+            outbox::record_failure(conn, &entry.id, error)?;
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Task changes made natively, outside the webview (notification buttons,
+//! the watch, Siri): applied to the local copy right away and queued in the
+//! outbox for the webview to send to the backend
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use opensunsama_core::db::{outbox, tasks};
+use opensunsama_core::sync::queue;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// A task to create from outside the app (Siri, a share)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDraft {
+    pub title: String,
+    pub notes: Option<String>,
+    /// `YYYY-MM-DD`; today when unset
+    pub scheduled_date: Option<String>,
+}
+
+/// Queue the creation of `draft`. The server assigns the id, so the task
+/// only shows up locally after the next sync.
+pub fn create(conn: &Connection, draft: &TaskDraft, now: DateTime<Utc>) -> rusqlite::Result<()> {
+    let scheduled_date = draft
+        .scheduled_date
+        .clone()
+        .unwrap_or_else(|| Local::now().date_naive().to_string());
+    let body = json!({
+        "title": draft.title.trim(),
+        "notes": draft.notes,
+        "scheduledDate": scheduled_date,
+    });
+    outbox::insert(
+        conn,
+        &queue::entry(
+            "POST",
+            "/tasks",
+            Some(&body),
+            Duration::ZERO,
+            now.timestamp_millis(),
+        ),
+    )
+}
+
+/// Mark `task_id` done at `now`. Call it in a transaction, so the local
+/// change and its outbox entry are stored together.
+pub fn complete(conn: &Connection, task_id: &str, now: DateTime<Utc>) -> rusqlite::Result<()> {
+    let completed_at = now.to_rfc3339_opts(SecondsFormat::Millis, true);
+    if let Some(mut task) = tasks::get(conn, task_id)? {
+        if let Value::Object(fields) = &mut task {
+            fields.insert("completedAt".to_string(), json!(completed_at));
+            fields.insert("updatedAt".to_string(), json!(completed_at));
+        }
+        tasks::upsert(conn, &task)?;
+    }
+    let change = json!({ "completedAt": completed_at });
+    outbox::insert(
+        conn,
+        &queue::entry(
+            "PATCH",
+            &format!("/tasks/{}", task_id),
+            Some(&change),
+            Duration::ZERO,
+            now.timestamp_millis(),
+        ),
+    )
+}
@@ -0,0 +1,67 @@
+//! Android Quick Settings tiles, like the desktop tray: one opens quick
+//! add, the other starts or stops the timer. Taps come in as `TileTap`s;
+//! the timer tile shows whether the timer runs and on what.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+use tauri_plugin_bridge::BridgeExt;
+
+use crate::links::{self, Route};
+use crate::timer::{self, TimerStatus};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TileTap {
+    /// The app has been opened for it
+    QuickAdd,
+    ToggleTimer,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TileState<'a> {
+    timer_running: bool,
+    task_title: Option<&'a str>,
+}
+
+/// Start taking taps
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    if !cfg!(target_os = "android") {
+        return Ok(());
+    }
+    let handle = app.clone();
+    app.bridge().listen("listenTiles", move |tap: TileTap| {
+        if let Err(e) = handle_tap(&handle, tap) {
+            tracing::warn!("Failed to apply a tile tap: {}", e);
+        }
+    })
+}
+
+/// Apply a tap. Quick add goes to the webview like a `new-task` link.
+pub fn handle_tap(app: &AppHandle, tap: TileTap) -> Result<(), String> {
+    match tap {
+        TileTap::QuickAdd => links::navigate(app, Route::QuickAdd),
+        TileTap::ToggleTimer => {
+            timer::toggle(app)?;
+        }
+    }
+    Ok(())
+}
+
+/// Show the timer's state on its tile. Failures are only logged.
+pub fn refresh(app: &AppHandle, status: &TimerStatus) {
+    if !cfg!(target_os = "android") {
+        return;
+    }
+    let state = TileState {
+        timer_running: status.running.is_some(),
+        task_title: status
+            .running
+            .as_ref()
+            .map(|entry| entry.task_title.as_str()),
+    };
+    if let Err(e) = app.bridge().call::<Value>("updateTiles", &state) {
+        tracing::warn!("Failed to update the Quick Settings tiles: {}", e);
+    }
+}
@@ -0,0 +1,145 @@
+//! The task timer, kept natively so OS surfaces (tiles, the watch and
+//! anything else outside the webview) see the same state as the app. As on
+//! desktop, the running timer is a `time_entries` row without an end, so it
+//! survives the app being killed. Emits `timer-changed` with the new
+//! `TimerStatus` on every start and stop.
+
+use chrono::Utc;
+use opensunsama_core::db::tasks;
+use opensunsama_core::db::time_entries::{self, TimeEntry};
+use opensunsama_core::db::Database;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::{tiles, today, watch};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRef {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerStatus {
+    pub running: Option<TimeEntry>,
+    pub elapsed_ms: i64,
+    /// Unix milliseconds `elapsed_ms` was measured at
+    pub measured_at: i64,
+}
+
+pub fn status(app: &AppHandle) -> Result<TimerStatus, String> {
+    let running = app
+        .state::<Database>()
+        .with_conn(|conn| time_entries::running(conn))?;
+    let measured_at = Utc::now().timestamp_millis();
+    let elapsed_ms = running
+        .as_ref()
+        .map_or(0, |entry| (measured_at - entry.started_at).max(0));
+    Ok(TimerStatus {
+        running,
+        elapsed_ms,
+        measured_at,
+    })
+}
+
+/// Start timing `task`. A timer already running on another task is
+/// stopped first.
+pub fn start(app: &AppHandle, task: TaskRef) -> Result<TimerStatus, String> {
+    let now = Utc::now().timestamp_millis();
+    let started = app.state::<Database>().with_conn(|conn| {
+        if let Some(running) = time_entries::running(conn)? {
+            if running.task_id == task.id {
+                return Ok(None);
+            }
+            time_entries::finish(conn, &running.id, now)?;
+        }
+        let entry = TimeEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            task_id: task.id.clone(),
+            task_title: task.title.clone(),
+            started_at: now,
+            ended_at: None,
+            updated_at: 0,
+        };
+        time_entries::insert(conn, &entry)?;
+        Ok(Some(entry))
+    })?;
+
+    match started {
+        Some(_) => publish(app),
+        None => status(app),
+    }
+}
+
+/// Start timing the stored task `task_id`
+pub fn start_task(app: &AppHandle, task_id: &str) -> Result<TimerStatus, String> {
+    let task = app
+        .state::<Database>()
+        .with_conn(|conn| tasks::get(conn, task_id))?
+        .ok_or_else(|| format!("Unknown task {}", task_id))?;
+    let title = task
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    start(
+        app,
+        TaskRef {
+            id: task_id.to_string(),
+            title,
+        },
+    )
+}
+
+/// Start timing the next open task today
+pub fn start_next(app: &AppHandle) -> Result<TimerStatus, String> {
+    let next = watch::state(app)?
+        .current
+        .ok_or_else(|| "Nothing left to focus on today".to_string())?;
+    start_task(app, &next.id)
+}
+
+/// Stop the running timer, if any, and return the finished entry
+pub fn stop(app: &AppHandle) -> Result<Option<TimeEntry>, String> {
+    let now = Utc::now().timestamp_millis();
+    let finished = app.state::<Database>().with_conn(|conn| {
+        let Some(mut running) = time_entries::running(conn)? else {
+            return Ok(None);
+        };
+        time_entries::finish(conn, &running.id, now)?;
+        running.ended_at = Some(now);
+        Ok(Some(running))
+    })?;
+
+    if finished.is_some() {
+        publish(app)?;
+    }
+    Ok(finished)
+}
+
+/// Stop the running timer, or start one on the next open task
+pub fn toggle(app: &AppHandle) -> Result<TimerStatus, String> {
+    if stop(app)?.is_some() {
+        status(app)
+    } else {
+        start_next(app)
+    }
+}
+
+/// Bring the OS surfaces in line with the stored timer, at launch
+pub fn restore(app: &AppHandle) -> Result<(), String> {
+    let status = status(app)?;
+    tiles::refresh(app, &status);
+    Ok(())
+}
+
+fn publish(app: &AppHandle) -> Result<TimerStatus, String> {
+    let status = status(app)?;
+    today::changed(app);
+    tiles::refresh(app, &status);
+    crate::app_lock::emit(app, "timer-changed", &status);
+    Ok(status)
+}
@@ -0,0 +1,37 @@
+//! Surfaces outside the app that show today's tasks: the icon badge, the
+//! home-screen widgets and the watch. They're refreshed after every change
+//! to the stored tasks or the timer, and when the local date changes while
+//! the app runs.
+
+use chrono::Local;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::{badge, watch, widgets};
+
+/// How often the date watcher looks at the date
+const DATE_CHECK: Duration = Duration::from_secs(60);
+
+/// The stored tasks or the timer changed
+pub fn changed(app: &AppHandle) {
+    badge::refresh(app);
+    widgets::refresh(app);
+    watch::refresh(app);
+}
+
+/// Refresh when the day changes, so yesterday's tasks drop off at
+/// midnight. Checks periodically rather than sleeping until midnight,
+/// since a suspended app's timers don't keep wall-clock time.
+pub fn watch_date(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut today = Local::now().date_naive();
+        loop {
+            std::thread::sleep(DATE_CHECK);
+            let now = Local::now().date_naive();
+            if now != today {
+                today = now;
+                changed(&app);
+            }
+        }
+    });
+}
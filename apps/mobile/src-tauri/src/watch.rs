@@ -0,0 +1,152 @@
+//! The interface companion watch apps (Apple Watch through
+//! WatchConnectivity, Wear OS through the Wearable Data Layer) are built
+//! against: the phone pushes a compact `WatchState` whenever tasks or the
+//! timer change, and the watch sends back `WatchMessage`s, which are
+//! applied here like any other native change.
+
+use chrono::{Local, Utc};
+use opensunsama_core::db::tasks::{
+    self as stored, TaskFilter, TaskSort, TaskSortField, TaskStatus,
+};
+use opensunsama_core::db::time_entries;
+use opensunsama_core::db::Database;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_bridge::BridgeExt;
+
+use crate::{tasks, timer};
+
+/// Open tasks shown after the current one
+const NEXT_TASKS: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchState {
+    /// The timed task, else the first open one today
+    pub current: Option<WatchTask>,
+    /// Unix milliseconds the timer on `current` started, if it runs
+    pub timer_started_at: Option<i64>,
+    pub next: Vec<WatchTask>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchTask {
+    pub id: String,
+    pub title: String,
+    pub estimated_mins: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WatchMessage {
+    #[serde(rename_all = "camelCase")]
+    Complete {
+        task_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    StartTimer {
+        task_id: String,
+    },
+    StopTimer,
+}
+
+/// Start taking messages from the watch
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let handle = app.clone();
+    app.bridge()
+        .listen("listenWatchMessages", move |message: WatchMessage| {
+            if let Err(e) = handle_message(&handle, &message) {
+                tracing::warn!("Failed to apply a watch message: {}", e);
+            }
+        })
+}
+
+/// Send the current state to the watch. Failures are only logged, like the
+/// badge's.
+pub fn refresh(app: &AppHandle) {
+    let sent = state(app).and_then(|state| {
+        app.bridge()
+            .call::<Value>("updateWatchState", &state)
+            .map(|_| ())
+    });
+    if let Err(e) = sent {
+        tracing::warn!("Failed to update the watch: {}", e);
+    }
+}
+
+pub fn state(app: &AppHandle) -> Result<WatchState, String> {
+    let date = Local::now().date_naive().to_string();
+    let filter = TaskFilter {
+        from: Some(date.clone()),
+        to: Some(date),
+        status: TaskStatus::Open,
+        ..TaskFilter::default()
+    };
+    let sort = TaskSort {
+        field: TaskSortField::CreatedAt,
+        ascending: true,
+    };
+    let (page, running) = app.state::<Database>().with_conn(|conn| {
+        Ok((
+            stored::query(conn, &filter, sort, None, NEXT_TASKS + 1)?,
+            time_entries::running(conn)?,
+        ))
+    })?;
+
+    let mut open: Vec<WatchTask> = page.tasks.iter().filter_map(watch_task).collect();
+    let current = match &running {
+        Some(entry) => {
+            open.retain(|task| task.id != entry.task_id);
+            Some(WatchTask {
+                id: entry.task_id.clone(),
+                title: entry.task_title.clone(),
+                estimated_mins: None,
+            })
+        }
+        None if !open.is_empty() => Some(open.remove(0)),
+        None => None,
+    };
+    open.truncate(NEXT_TASKS);
+    Ok(WatchState {
+        current,
+        timer_started_at: running.map(|entry| entry.started_at),
+        next: open,
+    })
+}
+
+/// Apply a message from the watch. Emits `watch-message-applied` for a
+/// webview that's open.
+pub fn handle_message(app: &AppHandle, message: &WatchMessage) -> Result<(), String> {
+    match message {
+        WatchMessage::Complete { task_id } => {
+            app.state::<Database>().with_conn(|conn| {
+                let tx = conn.transaction()?;
+                tasks::complete(&tx, task_id, Utc::now())?;
+                tx.commit()
+            })?;
+            crate::today::changed(app);
+        }
+        WatchMessage::StartTimer { task_id } => {
+            timer::start_task(app, task_id)?;
+        }
+        WatchMessage::StopTimer => {
+            timer::stop(app)?;
+        }
+    }
+    crate::app_lock::emit(app, "watch-message-applied", message);
+    Ok(())
+}
+
+fn watch_task(task: &Value) -> Option<WatchTask> {
+    Some(WatchTask {
+        id: task.get("id")?.as_str()?.to_string(),
+        title: task
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        estimated_mins: task.get("estimatedMins").and_then(Value::as_u64),
+    })
+}
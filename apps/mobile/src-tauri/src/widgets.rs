@@ -0,0 +1,135 @@
+//! Data for the home-screen widgets: today's tasks and the running timer,
+//! written where the widget extension (iOS, through the app group) or the
+//! widget provider (Android, through shared preferences) can read it
+//! without starting the app. Refreshed after every change to tasks or the
+//! timer and when the date changes.
+
+use chrono::{Local, Utc};
+use opensunsama_core::db::tasks::{self, TaskFilter, TaskSort, TaskSortField};
+use opensunsama_core::db::time_entries;
+use opensunsama_core::db::Database;
+use opensunsama_core::task;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+/// More than any widget size shows
+const MAX_TASKS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetData {
+    /// `YYYY-MM-DD`, so a widget can tell the data is from yesterday
+    pub date: String,
+    pub tasks: Vec<WidgetTask>,
+    /// All of today's, not just the ones in `tasks`
+    pub open: u32,
+    pub completed: u32,
+    pub timer: Option<WidgetTimer>,
+    /// Unix milliseconds
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetTask {
+    pub id: String,
+    pub title: String,
+    pub completed: bool,
+    pub estimated_mins: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetTimer {
+    pub task_id: String,
+    pub task_title: String,
+    /// Unix milliseconds; widgets count up from here themselves
+    pub started_at: i64,
+}
+
+/// Rewrite the widget data. Failures are only logged, like the badge's.
+pub fn refresh(app: &AppHandle) {
+    if let Err(e) = write(app) {
+        tracing::warn!("Failed to update widget data: {}", e);
+    }
+}
+
+/// Rewrite the widget data and return it
+pub fn write(app: &AppHandle) -> Result<WidgetData, String> {
+    let data = read(app)?;
+    os::write(app, &data)?;
+    Ok(data)
+}
+
+fn read(app: &AppHandle) -> Result<WidgetData, String> {
+    let date = Local::now().date_naive().to_string();
+    let filter = TaskFilter {
+        from: Some(date.clone()),
+        to: Some(date.clone()),
+        ..TaskFilter::default()
+    };
+    let sort = TaskSort {
+        field: TaskSortField::CreatedAt,
+        ascending: true,
+    };
+    let (page, open, completed, running) = app.state::<Database>().with_conn(|conn| {
+        Ok((
+            tasks::query(conn, &filter, sort, None, MAX_TASKS)?,
+            tasks::count_open_on(conn, &date)?,
+            tasks::count_completed_on(conn, &date)?,
+            time_entries::running(conn)?,
+        ))
+    })?;
+
+    Ok(WidgetData {
+        tasks: page.tasks.iter().filter_map(widget_task).collect(),
+        date,
+        open,
+        completed,
+        timer: running.map(|entry| WidgetTimer {
+            task_id: entry.task_id,
+            task_title: entry.task_title,
+            started_at: entry.started_at,
+        }),
+        updated_at: Utc::now().timestamp_millis(),
+    })
+}
+
+fn widget_task(value: &Value) -> Option<WidgetTask> {
+    Some(WidgetTask {
+        id: value.get("id")?.as_str()?.to_string(),
+        title: value
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        completed: task::is_completed(value),
+        estimated_mins: value.get("estimatedMins").and_then(Value::as_u64),
+    })
+}
+
+#[cfg(mobile)]
+mod os {
+    use tauri::AppHandle;
+    use tauri_plugin_bridge::BridgeExt;
+
+    use super::WidgetData;
+
+    pub fn write(app: &AppHandle, data: &WidgetData) -> Result<(), String> {
+        app.bridge()
+            .call::<serde_json::Value>("writeWidgetData", data)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(mobile))]
+mod os {
+    use tauri::AppHandle;
+
+    use super::WidgetData;
+
+    pub fn write(_app: &AppHandle, _data: &WidgetData) -> Result<(), String> {
+        Ok(())
+    }
+}
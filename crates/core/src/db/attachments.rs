@@ -0,0 +1,125 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub id: String,
+    pub task_id: Option<String>,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    /// SHA-256 of the contents, which is also the file's key in the store
+    pub hash: String,
+    /// Unix milliseconds
+    pub created_at: i64,
+    /// Id on the server once uploaded
+    pub remote_id: Option<String>,
+}
+
+const COLUMNS: &str = "id, task_id, file_name, mime_type, size_bytes, hash, created_at, remote_id";
+
+pub fn insert(conn: &Connection, attachment: &Attachment) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO attachments (id, task_id, file_name, mime_type, size_bytes, hash, created_at, remote_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            attachment.id,
+            attachment.task_id,
+            attachment.file_name,
+            attachment.mime_type,
+            attachment.size_bytes,
+            attachment.hash,
+            attachment.created_at,
+            attachment.remote_id
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<Attachment>> {
+    conn.query_row(
+        &format!("SELECT {} FROM attachments WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+/// Attachments of a task, oldest first
+pub fn list_for_task(conn: &Connection, task_id: &str) -> rusqlite::Result<Vec<Attachment>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attachments WHERE task_id = ?1 ORDER BY created_at",
+        COLUMNS
+    ))?;
+    let attachments = stmt
+        .query_map(params![task_id], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(attachments)
+}
+
+pub fn set_task(conn: &Connection, id: &str, task_id: Option<&str>) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE attachments SET task_id = ?2 WHERE id = ?1",
+        params![id, task_id],
+    )?;
+    Ok(())
+}
+
+pub fn set_remote_id(conn: &Connection, id: &str, remote_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE attachments SET remote_id = ?2 WHERE id = ?1",
+        params![id, remote_id],
+    )?;
+    Ok(())
+}
+
+/// Attachments not yet on the server
+pub fn not_uploaded(conn: &Connection) -> rusqlite::Result<Vec<Attachment>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attachments WHERE remote_id IS NULL ORDER BY created_at",
+        COLUMNS
+    ))?;
+    let attachments = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(attachments)
+}
+
+/// An attachment already downloaded from the server
+pub fn find_by_remote_id(conn: &Connection, remote_id: &str) -> rusqlite::Result<Option<Attachment>> {
+    conn.query_row(
+        &format!("SELECT {} FROM attachments WHERE remote_id = ?1", COLUMNS),
+        params![remote_id],
+        from_row,
+    )
+    .optional()
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM attachments WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Every content hash still referenced by an attachment
+pub fn referenced_hashes(conn: &Connection) -> rusqlite::Result<HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT hash FROM attachments")?;
+    let hashes = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<HashSet<String>>>()?;
+    Ok(hashes)
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        file_name: row.get(2)?,
+        mime_type: row.get(3)?,
+        size_bytes: row.get(4)?,
+        hash: row.get(5)?,
+        created_at: row.get(6)?,
+        remote_id: row.get(7)?,
+    })
+}
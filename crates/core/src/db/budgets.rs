@@ -0,0 +1,70 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+/// Time a channel may take per week
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Budget {
+    /// Channel name without the `#`; matched ignoring case
+    pub channel: String,
+    pub weekly_minutes: u32,
+}
+
+pub fn set(conn: &Connection, budget: &Budget) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO channel_budgets (channel, weekly_minutes) VALUES (?1, ?2)
+         ON CONFLICT(channel) DO UPDATE SET
+            channel = excluded.channel,
+            weekly_minutes = excluded.weekly_minutes",
+        params![budget.channel, budget.weekly_minutes],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, channel: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM channel_budgets WHERE channel = ?1",
+        params![channel],
+    )?;
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<Budget>> {
+    let mut stmt =
+        conn.prepare("SELECT channel, weekly_minutes FROM channel_budgets ORDER BY channel")?;
+    let budgets = stmt
+        .query_map([], |row| {
+            Ok(Budget {
+                channel: row.get(0)?,
+                weekly_minutes: row.get(1)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(budgets)
+}
+
+/// The highest threshold (percent) already alerted for `channel` in the
+/// week starting `week_start`
+pub fn alerted(conn: &Connection, channel: &str, week_start: &str) -> rusqlite::Result<u32> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(threshold), 0) FROM budget_alerts
+         WHERE channel = ?1 AND week_start = ?2",
+        params![channel, week_start],
+        |row| row.get(0),
+    )
+}
+
+pub fn record_alert(
+    conn: &Connection,
+    channel: &str,
+    week_start: &str,
+    threshold: u32,
+    alerted_at: i64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO budget_alerts (channel, week_start, threshold, alerted_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![channel, week_start, threshold, alerted_at],
+    )?;
+    Ok(())
+}
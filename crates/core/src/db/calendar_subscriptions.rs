@@ -0,0 +1,157 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+/// A read-only ICS calendar fetched from a URL. Its events are kept as
+/// time blocks whose ids start with `block_prefix`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarSubscription {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub refresh_minutes: u32,
+    /// Validators from the last full download, sent back so an unchanged
+    /// feed answers 304
+    #[serde(skip)]
+    pub etag: Option<String>,
+    #[serde(skip)]
+    pub last_modified: Option<String>,
+    /// Unix milliseconds of the last check, successful or not
+    pub checked_at: Option<i64>,
+    /// Unix milliseconds of the last successful check
+    pub fetched_at: Option<i64>,
+    /// Events stored from the last download
+    pub events: usize,
+    pub last_error: Option<String>,
+    /// Unix milliseconds
+    pub created_at: i64,
+    /// A holiday calendar: its all-day events are days off
+    pub holidays: bool,
+}
+
+impl CalendarSubscription {
+    pub fn block_prefix(&self) -> String {
+        format!("sub:{}:", self.id)
+    }
+}
+
+const COLUMNS: &str = "id, name, url, refresh_minutes, etag, last_modified, checked_at, \
+                       fetched_at, events, last_error, created_at, holidays";
+
+pub fn insert(conn: &Connection, subscription: &CalendarSubscription) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO calendar_subscriptions (id, name, url, refresh_minutes, created_at, holidays)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            subscription.id,
+            subscription.name,
+            subscription.url,
+            subscription.refresh_minutes,
+            subscription.created_at,
+            subscription.holidays
+        ],
+    )?;
+    Ok(())
+}
+
+/// Rename a subscription or change how often it's refreshed
+pub fn update(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    refresh_minutes: u32,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE calendar_subscriptions SET name = ?2, refresh_minutes = ?3 WHERE id = ?1",
+        params![id, name, refresh_minutes],
+    )?;
+    Ok(())
+}
+
+/// Record a successful check. `downloaded` carries the new validators and
+/// event count when the feed changed; `None` means it answered 304.
+pub fn set_fetched(
+    conn: &Connection,
+    id: &str,
+    fetched_at: i64,
+    downloaded: Option<(Option<&str>, Option<&str>, usize)>,
+) -> rusqlite::Result<()> {
+    match downloaded {
+        Some((etag, last_modified, events)) => conn.execute(
+            "UPDATE calendar_subscriptions
+             SET etag = ?2, last_modified = ?3, events = ?4,
+                 checked_at = ?5, fetched_at = ?5, last_error = NULL
+             WHERE id = ?1",
+            params![id, etag, last_modified, events, fetched_at],
+        )?,
+        None => conn.execute(
+            "UPDATE calendar_subscriptions
+             SET checked_at = ?2, fetched_at = ?2, last_error = NULL
+             WHERE id = ?1",
+            params![id, fetched_at],
+        )?,
+    };
+    Ok(())
+}
+
+/// Record a failed check; the events from the last good one stay
+pub fn set_error(
+    conn: &Connection,
+    id: &str,
+    checked_at: i64,
+    error: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE calendar_subscriptions SET checked_at = ?2, last_error = ?3 WHERE id = ?1",
+        params![id, checked_at, error],
+    )?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<CalendarSubscription>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM calendar_subscriptions WHERE id = ?1",
+            COLUMNS
+        ),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<CalendarSubscription>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM calendar_subscriptions ORDER BY created_at",
+        COLUMNS
+    ))?;
+    let subscriptions = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(subscriptions)
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM calendar_subscriptions WHERE id = ?1",
+        params![id],
+    )?;
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<CalendarSubscription> {
+    Ok(CalendarSubscription {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        url: row.get(2)?,
+        refresh_minutes: row.get(3)?,
+        etag: row.get(4)?,
+        last_modified: row.get(5)?,
+        checked_at: row.get(6)?,
+        fetched_at: row.get(7)?,
+        events: row.get(8)?,
+        last_error: row.get(9)?,
+        created_at: row.get(10)?,
+        holidays: row.get(11)?,
+    })
+}
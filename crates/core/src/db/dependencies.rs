@@ -0,0 +1,69 @@
+use rusqlite::{params, Connection};
+
+/// Record that `blocker_id` has to be done before `blocked_id`
+pub fn add(
+    conn: &Connection,
+    blocker_id: &str,
+    blocked_id: &str,
+    created_at: i64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO task_dependencies (blocker_id, blocked_id, created_at)
+         VALUES (?1, ?2, ?3)",
+        params![blocker_id, blocked_id, created_at],
+    )?;
+    Ok(())
+}
+
+pub fn remove(conn: &Connection, blocker_id: &str, blocked_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM task_dependencies WHERE blocker_id = ?1 AND blocked_id = ?2",
+        params![blocker_id, blocked_id],
+    )?;
+    Ok(())
+}
+
+/// Remove every dependency on or of `task_id`
+pub fn delete_task(conn: &Connection, task_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM task_dependencies WHERE blocker_id = ?1 OR blocked_id = ?1",
+        params![task_id],
+    )?;
+    Ok(())
+}
+
+/// The tasks that have to be done before `task_id`
+pub fn blockers(conn: &Connection, task_id: &str) -> rusqlite::Result<Vec<String>> {
+    ids(
+        conn,
+        "SELECT blocker_id FROM task_dependencies WHERE blocked_id = ?1 ORDER BY created_at",
+        task_id,
+    )
+}
+
+/// The tasks waiting on `task_id`
+pub fn blocking(conn: &Connection, task_id: &str) -> rusqlite::Result<Vec<String>> {
+    ids(
+        conn,
+        "SELECT blocked_id FROM task_dependencies WHERE blocker_id = ?1 ORDER BY created_at",
+        task_id,
+    )
+}
+
+/// Where the last time block of any of `task_id`'s blockers ends (Unix ms)
+pub fn blockers_end(conn: &Connection, task_id: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        "SELECT MAX(end_at) FROM time_blocks WHERE task_id IN
+            (SELECT blocker_id FROM task_dependencies WHERE blocked_id = ?1)",
+        params![task_id],
+        |row| row.get(0),
+    )
+}
+
+fn ids(conn: &Connection, sql: &str, task_id: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(sql)?;
+    let ids = stmt
+        .query_map(params![task_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(ids)
+}
@@ -0,0 +1,33 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A project imported from another time tracker. Imported time entries use
+/// its id as their task id, and analytics show its name as their channel.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalProject {
+    pub id: String,
+    /// Tool it came from, e.g. `toggl`
+    pub source: String,
+    pub name: String,
+    pub archived: bool,
+}
+
+pub fn upsert(conn: &Connection, project: &ExternalProject) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO external_projects (id, source, name, archived) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name, archived = excluded.archived",
+        params![project.id, project.source, project.name, project.archived],
+    )?;
+    Ok(())
+}
+
+/// Project names by id
+pub fn names(conn: &Connection) -> rusqlite::Result<HashMap<String, String>> {
+    let mut stmt = conn.prepare("SELECT id, name FROM external_projects")?;
+    let names = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+    Ok(names)
+}
@@ -0,0 +1,110 @@
+//! Notifications held back until quiet hours or Do Not Disturb end. Kept
+//! here rather than in memory so a restart doesn't lose them; each leaves
+//! once it has been shown.
+
+use rusqlite::{params, Connection, Row};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeldNotice {
+    /// Set by `insert`; ignored when inserting
+    pub id: i64,
+    /// Notification category key, e.g. `reminder`
+    pub category: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub sound: Option<String>,
+    pub task_id: Option<String>,
+    pub data: Option<Value>,
+    /// Unix milliseconds
+    pub held_at: i64,
+}
+
+const COLUMNS: &str = "id, category, title, body, sound, task_id, data, held_at";
+
+/// Hold a notice. Returns its id.
+pub fn insert(conn: &Connection, notice: &HeldNotice) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO held_notices (category, title, body, sound, task_id, data, held_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            notice.category,
+            notice.title,
+            notice.body,
+            notice.sound,
+            notice.task_id,
+            notice.data.as_ref().map(Value::to_string),
+            notice.held_at
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Every held notice, oldest first
+pub fn all(conn: &Connection) -> rusqlite::Result<Vec<HeldNotice>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM held_notices ORDER BY id", COLUMNS))?;
+    let notices = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(notices)
+}
+
+pub fn count(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.query_row("SELECT COUNT(*) FROM held_notices", [], |row| row.get(0))
+}
+
+pub fn delete(conn: &Connection, id: i64) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM held_notices WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<HeldNotice> {
+    let data: Option<String> = row.get(6)?;
+    Ok(HeldNotice {
+        id: row.get(0)?,
+        category: row.get(1)?,
+        title: row.get(2)?,
+        body: row.get(3)?,
+        sound: row.get(4)?,
+        task_id: row.get(5)?,
+        data: data.and_then(|data| serde_json::from_str(&data).ok()),
+        held_at: row.get(7)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn notice(title: &str) -> HeldNotice {
+        HeldNotice {
+            id: 0,
+            category: "reminder".to_string(),
+            title: title.to_string(),
+            body: None,
+            sound: None,
+            task_id: Some("task".to_string()),
+            data: Some(serde_json::json!({ "action": "open" })),
+            held_at: 1_000,
+        }
+    }
+
+    #[test]
+    fn held_notices_keep_order_until_shown() {
+        let (_dir, db) = testing::database();
+        let first = db.with_conn(|conn| insert(conn, &notice("first"))).unwrap();
+        db.with_conn(|conn| insert(conn, &notice("second")))
+            .unwrap();
+
+        let held = db.with_conn(|conn| all(conn)).unwrap();
+        assert_eq!(
+            held.iter().map(|n| n.title.as_str()).collect::<Vec<_>>(),
+            ["first", "second"]
+        );
+        assert_eq!(held[0].data, notice("first").data);
+
+        db.with_conn(|conn| delete(conn, first)).unwrap();
+        assert_eq!(db.with_conn(|conn| count(conn)).unwrap(), 1);
+    }
+}
@@ -0,0 +1,32 @@
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+/// Replace the holidays from `source` (a calendar subscription id) with
+/// `holidays`, given as `(YYYY-MM-DD, name)`
+pub fn replace(
+    conn: &Connection,
+    source: &str,
+    holidays: &[(String, String)],
+) -> rusqlite::Result<()> {
+    delete_source(conn, source)?;
+    let mut stmt =
+        conn.prepare("INSERT OR REPLACE INTO holidays (date, source, name) VALUES (?1, ?2, ?3)")?;
+    for (date, name) in holidays {
+        stmt.execute(params![date, source, name])?;
+    }
+    Ok(())
+}
+
+pub fn delete_source(conn: &Connection, source: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM holidays WHERE source = ?1", params![source])?;
+    Ok(())
+}
+
+/// Holiday names by date (`YYYY-MM-DD`), from every source
+pub fn by_date(conn: &Connection) -> rusqlite::Result<HashMap<String, String>> {
+    let mut stmt = conn.prepare("SELECT date, name FROM holidays ORDER BY source")?;
+    let holidays = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+    Ok(holidays)
+}
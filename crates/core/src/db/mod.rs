@@ -0,0 +1,627 @@
+use rusqlite::{params, Connection, DatabaseName};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+pub mod attachments;
+pub mod budgets;
+pub mod calendar_subscriptions;
+pub mod dependencies;
+pub mod external_projects;
+pub mod held_notices;
+pub mod holidays;
+pub mod objectives;
+pub mod outbox;
+pub mod reminders;
+pub mod snoozed;
+pub mod subtasks;
+pub mod sync_cursors;
+pub mod tasks;
+pub mod text_docs;
+pub mod time_blocks;
+pub mod time_entries;
+pub mod time_exports;
+pub mod tombstones;
+pub mod transfers;
+
+pub const DATABASE_FILE: &str = "opensunsama.db";
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run. Never edit an entry once released; append a new one.
+const MIGRATIONS: &[&str] = &[
+    // 1: time blocks and reminder schedules
+    r#"
+    CREATE TABLE time_blocks (
+        id TEXT PRIMARY KEY,
+        task_id TEXT,
+        title TEXT NOT NULL,
+        start_at INTEGER NOT NULL,
+        end_at INTEGER NOT NULL,
+        timezone TEXT NOT NULL
+    );
+    CREATE INDEX idx_time_blocks_start_at ON time_blocks(start_at);
+
+    CREATE TABLE reminders (
+        id TEXT PRIMARY KEY,
+        task_id TEXT,
+        title TEXT NOT NULL,
+        body TEXT,
+        fire_at INTEGER NOT NULL,
+        timezone TEXT NOT NULL,
+        delivered_at INTEGER
+    );
+    CREATE INDEX idx_reminders_fire_at ON reminders(fire_at);
+    "#,
+    // 2: attachment metadata; file contents live in the content-addressed store
+    r#"
+    CREATE TABLE attachments (
+        id TEXT PRIMARY KEY,
+        task_id TEXT,
+        file_name TEXT NOT NULL,
+        mime_type TEXT NOT NULL,
+        size_bytes INTEGER NOT NULL,
+        hash TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_attachments_task_id ON attachments(task_id);
+    CREATE INDEX idx_attachments_hash ON attachments(hash);
+    "#,
+    // 3: timer sessions; a row without `ended_at` is the running timer
+    r#"
+    CREATE TABLE time_entries (
+        id TEXT PRIMARY KEY,
+        task_id TEXT NOT NULL,
+        task_title TEXT NOT NULL,
+        started_at INTEGER NOT NULL,
+        ended_at INTEGER
+    );
+    CREATE INDEX idx_time_entries_started_at ON time_entries(started_at);
+    CREATE INDEX idx_time_entries_task_id ON time_entries(task_id);
+    "#,
+    // 4: outgoing backend changes, sent in order by the sync engine
+    r#"
+    CREATE TABLE outbox (
+        id TEXT PRIMARY KEY,
+        method TEXT NOT NULL,
+        path TEXT NOT NULL,
+        body TEXT,
+        created_at INTEGER NOT NULL,
+        send_after INTEGER NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT
+    );
+    CREATE INDEX idx_outbox_send_after ON outbox(send_after);
+    "#,
+    // 5: CRDT documents for task notes and descriptions
+    r#"
+    CREATE TABLE text_docs (
+        task_id TEXT NOT NULL,
+        field TEXT NOT NULL,
+        state BLOB NOT NULL,
+        client_id INTEGER NOT NULL,
+        dirty INTEGER NOT NULL DEFAULT 0,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (task_id, field)
+    );
+    "#,
+    // 6: server ids for uploaded attachments and the attachment transfer queue
+    r#"
+    ALTER TABLE attachments ADD COLUMN remote_id TEXT;
+
+    CREATE TABLE transfers (
+        id TEXT PRIMARY KEY,
+        direction TEXT NOT NULL,
+        attachment_id TEXT,
+        remote_id TEXT,
+        upload_id TEXT,
+        task_id TEXT,
+        file_name TEXT NOT NULL,
+        mime_type TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        size_bytes INTEGER NOT NULL,
+        transferred_bytes INTEGER NOT NULL DEFAULT 0,
+        status TEXT NOT NULL,
+        error TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_transfers_status ON transfers(status);
+    "#,
+    // 7: calendar events among time blocks, with where to meet
+    r#"
+    ALTER TABLE time_blocks ADD COLUMN is_event INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE time_blocks ADD COLUMN location TEXT;
+    ALTER TABLE time_blocks ADD COLUMN description TEXT;
+    "#,
+    // 8: snoozed notifications
+    r#"
+    CREATE TABLE snoozed (
+        id TEXT PRIMARY KEY,
+        category TEXT NOT NULL,
+        title TEXT NOT NULL,
+        body TEXT,
+        sound TEXT,
+        task_id TEXT,
+        data TEXT,
+        fire_at INTEGER NOT NULL,
+        snooze_count INTEGER NOT NULL DEFAULT 1,
+        snoozed_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_snoozed_fire_at ON snoozed(fire_at);
+    "#,
+    // 9: time entries pushed to external time trackers
+    r#"
+    CREATE TABLE time_exports (
+        entry_id TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        status TEXT NOT NULL,
+        remote_id TEXT,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        next_attempt_at INTEGER NOT NULL DEFAULT 0,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (entry_id, provider)
+    );
+    "#,
+    // 10: projects imported from other time trackers
+    r#"
+    CREATE TABLE external_projects (
+        id TEXT PRIMARY KEY,
+        source TEXT NOT NULL,
+        name TEXT NOT NULL,
+        archived INTEGER NOT NULL DEFAULT 0
+    );
+    "#,
+    // 11: subscribed ICS calendars
+    r#"
+    CREATE TABLE calendar_subscriptions (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        url TEXT NOT NULL,
+        refresh_minutes INTEGER NOT NULL,
+        etag TEXT,
+        last_modified TEXT,
+        checked_at INTEGER,
+        fetched_at INTEGER,
+        events INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        created_at INTEGER NOT NULL
+    );
+    "#,
+    // 12: holidays from subscribed holiday calendars
+    r#"
+    ALTER TABLE calendar_subscriptions ADD COLUMN holidays INTEGER NOT NULL DEFAULT 0;
+
+    CREATE TABLE holidays (
+        date TEXT NOT NULL,
+        source TEXT NOT NULL,
+        name TEXT NOT NULL,
+        PRIMARY KEY (date, source)
+    );
+    "#,
+    // 13: weekly objectives and the tasks working towards them
+    r#"
+    CREATE TABLE objectives (
+        id TEXT PRIMARY KEY,
+        week_start TEXT NOT NULL,
+        title TEXT NOT NULL,
+        notes TEXT,
+        target_minutes INTEGER,
+        position INTEGER NOT NULL DEFAULT 0,
+        done_at INTEGER,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_objectives_week_start ON objectives(week_start);
+
+    CREATE TABLE objective_tasks (
+        objective_id TEXT NOT NULL,
+        task_id TEXT NOT NULL,
+        PRIMARY KEY (objective_id, task_id)
+    );
+    CREATE INDEX idx_objective_tasks_task_id ON objective_tasks(task_id);
+    "#,
+    // 14: weekly time budgets per channel and the alerts already sent
+    r#"
+    CREATE TABLE channel_budgets (
+        channel TEXT PRIMARY KEY COLLATE NOCASE,
+        weekly_minutes INTEGER NOT NULL
+    );
+
+    CREATE TABLE budget_alerts (
+        channel TEXT NOT NULL COLLATE NOCASE,
+        week_start TEXT NOT NULL,
+        threshold INTEGER NOT NULL,
+        alerted_at INTEGER NOT NULL,
+        PRIMARY KEY (channel, week_start, threshold)
+    );
+    "#,
+    // 15: task templates and ritual checklists, and the days they were used
+    r#"
+    CREATE TABLE templates (
+        id TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        name TEXT NOT NULL,
+        title TEXT NOT NULL,
+        notes TEXT,
+        estimated_mins INTEGER,
+        due_offset_days INTEGER,
+        subtasks TEXT NOT NULL DEFAULT '[]',
+        ritual TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE TABLE template_runs (
+        template_id TEXT NOT NULL,
+        date TEXT NOT NULL,
+        task_id TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        PRIMARY KEY (template_id, date)
+    );
+    "#,
+    // 16: ordered subtasks and checklist items of tasks
+    r#"
+    CREATE TABLE subtasks (
+        id TEXT PRIMARY KEY,
+        task_id TEXT NOT NULL,
+        title TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        estimated_mins INTEGER,
+        completed_at INTEGER,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_subtasks_task_id ON subtasks(task_id, position);
+    "#,
+    // 17: tasks that have to be done before others
+    r#"
+    CREATE TABLE task_dependencies (
+        blocker_id TEXT NOT NULL,
+        blocked_id TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        PRIMARY KEY (blocker_id, blocked_id)
+    );
+    CREATE INDEX idx_task_dependencies_blocked_id ON task_dependencies(blocked_id);
+    "#,
+    // 18: local copy of the server's tasks for filtered, paged queries
+    r#"
+    CREATE TABLE tasks (
+        id TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        notes TEXT,
+        channel TEXT COLLATE NOCASE,
+        scheduled_date TEXT NOT NULL DEFAULT '',
+        due_date TEXT NOT NULL DEFAULT '',
+        completed_at INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX idx_tasks_scheduled_date ON tasks(scheduled_date, id);
+    CREATE INDEX idx_tasks_completed_at ON tasks(completed_at, id);
+    CREATE INDEX idx_tasks_updated_at ON tasks(updated_at, id);
+    CREATE INDEX idx_tasks_created_at ON tasks(created_at, id);
+    CREATE INDEX idx_tasks_channel ON tasks(channel, scheduled_date);
+    "#,
+    // 19: senders claim outbox entries, so two never send the same one
+    r#"
+    ALTER TABLE outbox ADD COLUMN claimed_until INTEGER NOT NULL DEFAULT 0;
+    "#,
+    // 20: how far each download has got, in the server's own terms
+    r#"
+    CREATE TABLE sync_cursors (
+        name TEXT PRIMARY KEY,
+        cursor TEXT NOT NULL
+    );
+    "#,
+    // 21: change times and deletions of time entries and blocks, so devices
+    // syncing directly keep the newest copy and see what the other removed.
+    // Triggers stamp local writes; merges carry the other device's times.
+    r#"
+    ALTER TABLE time_entries ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE time_blocks ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0;
+
+    CREATE TABLE tombstones (
+        kind TEXT NOT NULL,
+        id TEXT NOT NULL,
+        deleted_at INTEGER NOT NULL,
+        PRIMARY KEY (kind, id)
+    );
+
+    CREATE TRIGGER time_entries_inserted AFTER INSERT ON time_entries
+    WHEN NEW.updated_at = 0
+    BEGIN
+        UPDATE time_entries
+        SET updated_at = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+        WHERE id = NEW.id;
+    END;
+    CREATE TRIGGER time_entries_updated AFTER UPDATE ON time_entries
+    WHEN NEW.updated_at = OLD.updated_at
+    BEGIN
+        UPDATE time_entries
+        SET updated_at = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+        WHERE id = NEW.id;
+    END;
+    CREATE TRIGGER time_entries_deleted AFTER DELETE ON time_entries
+    BEGIN
+        INSERT INTO tombstones (kind, id, deleted_at)
+        VALUES ('time_entry', OLD.id, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER))
+        ON CONFLICT(kind, id) DO UPDATE SET deleted_at = excluded.deleted_at;
+    END;
+
+    CREATE TRIGGER time_blocks_inserted AFTER INSERT ON time_blocks
+    WHEN NEW.updated_at = 0
+    BEGIN
+        UPDATE time_blocks
+        SET updated_at = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+        WHERE id = NEW.id;
+    END;
+    CREATE TRIGGER time_blocks_updated AFTER UPDATE ON time_blocks
+    WHEN NEW.updated_at = OLD.updated_at
+    BEGIN
+        UPDATE time_blocks
+        SET updated_at = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+        WHERE id = NEW.id;
+    END;
+    CREATE TRIGGER time_blocks_deleted AFTER DELETE ON time_blocks
+    BEGIN
+        INSERT INTO tombstones (kind, id, deleted_at)
+        VALUES ('time_block', OLD.id, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER))
+        ON CONFLICT(kind, id) DO UPDATE SET deleted_at = excluded.deleted_at;
+    END;
+    "#,
+    // 22: this replica's Yjs client id, shared by all its text documents
+    r#"
+    CREATE TABLE replica (
+        client_id INTEGER NOT NULL
+    );
+    "#,
+    // 23: notifications held for quiet hours or Do Not Disturb
+    r#"
+    CREATE TABLE held_notices (
+        id INTEGER PRIMARY KEY,
+        category TEXT NOT NULL,
+        title TEXT NOT NULL,
+        body TEXT,
+        sound TEXT,
+        task_id TEXT,
+        data TEXT,
+        held_at INTEGER NOT NULL
+    );
+    "#,
+    // 24: full-text index over the stored tasks' titles and notes, kept in
+    // step by triggers
+    r#"
+    CREATE VIRTUAL TABLE tasks_fts USING fts5(
+        title, notes,
+        content = 'tasks', content_rowid = 'rowid',
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+    CREATE TRIGGER tasks_fts_inserted AFTER INSERT ON tasks
+    BEGIN
+        INSERT INTO tasks_fts (rowid, title, notes) VALUES (NEW.rowid, NEW.title, NEW.notes);
+    END;
+    CREATE TRIGGER tasks_fts_deleted AFTER DELETE ON tasks
+    BEGIN
+        INSERT INTO tasks_fts (tasks_fts, rowid, title, notes)
+        VALUES ('delete', OLD.rowid, OLD.title, OLD.notes);
+    END;
+    CREATE TRIGGER tasks_fts_updated AFTER UPDATE OF title, notes ON tasks
+    BEGIN
+        INSERT INTO tasks_fts (tasks_fts, rowid, title, notes)
+        VALUES ('delete', OLD.rowid, OLD.title, OLD.notes);
+        INSERT INTO tasks_fts (rowid, title, notes) VALUES (NEW.rowid, NEW.title, NEW.notes);
+    END;
+    INSERT INTO tasks_fts (tasks_fts) VALUES ('rebuild');
+    "#,
+    // 25: transfers the user asked for, which don't wait to save power
+    r#"
+    ALTER TABLE transfers ADD COLUMN requested INTEGER NOT NULL DEFAULT 0;
+    "#,
+];
+
+/// Local SQLite database shared by all native subsystems. Keys, `change_key`
+/// and `snapshot` need the app to link SQLCipher, which behaves as plain
+/// SQLite when no key is set; everything else works on either.
+pub struct Database {
+    path: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+impl Database {
+    pub fn open(path: &Path, key: Option<&str>) -> Result<Self, String> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            conn: Mutex::new(connect(path, key)?),
+        })
+    }
+
+    /// Re-encrypt the database file under `key`, or decrypt it with `None`.
+    /// The data is exported into a new file which then replaces the original,
+    /// so a failure part-way leaves the existing database untouched.
+    pub fn change_key(&self, key: Option<&str>) -> Result<(), String> {
+        let mut conn = self.lock()?;
+
+        let target = self.path.with_extension("db.rekey");
+        export_to(&conn, &target, key)
+            .map_err(|e| format!("Failed to re-encrypt database: {}", e))?;
+
+        self.swap_in(&mut conn, &target, key)
+    }
+
+    /// Write a consistent copy of the database to `target`, encrypted with
+    /// `key` (which should be the current key, so the copy opens the same way)
+    pub fn snapshot(&self, target: &Path, key: Option<&str>) -> Result<(), String> {
+        let conn = self.lock()?;
+        export_to(&conn, target, key).map_err(|e| format!("Failed to snapshot database: {}", e))
+    }
+
+    /// Replace the database with the file at `source`, which must open with
+    /// `key`. The source is checked before the live database is touched.
+    pub fn restore_from(&self, source: &Path, key: Option<&str>) -> Result<(), String> {
+        connect(source, key)?
+            .close()
+            .map_err(|(_, e)| format!("Failed to close database: {}", e))?;
+
+        let mut conn = self.lock()?;
+        self.swap_in(&mut conn, source, key)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|_| "Database lock poisoned".to_string())
+    }
+
+    /// Close `conn`, move `source` over the database file and reconnect
+    fn swap_in(&self, conn: &mut Connection, source: &Path, key: Option<&str>) -> Result<(), String> {
+        // Close the old connection so its WAL is checkpointed before the swap
+        let placeholder =
+            Connection::open_in_memory().map_err(|e| format!("Database error: {}", e))?;
+        std::mem::replace(conn, placeholder)
+            .close()
+            .map_err(|(_, e)| format!("Failed to close database: {}", e))?;
+
+        for suffix in ["db-wal", "db-shm"] {
+            let _ = std::fs::remove_file(self.path.with_extension(suffix));
+        }
+        std::fs::rename(source, &self.path)
+            .map_err(|e| format!("Failed to replace database: {}", e))?;
+
+        *conn = connect(&self.path, key)?;
+        Ok(())
+    }
+
+    /// Run `f` with exclusive access to the connection
+    pub fn with_conn<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T>,
+    {
+        let mut conn = self.lock()?;
+
+        f(&mut conn).map_err(|e| format!("Database error: {}", e))
+    }
+}
+
+/// Export the whole database into a new file at `target` keyed with `key`
+fn export_to(conn: &Connection, target: &Path, key: Option<&str>) -> rusqlite::Result<()> {
+    let _ = std::fs::remove_file(target);
+
+    let export = || -> rusqlite::Result<()> {
+        conn.execute(
+            "ATTACH DATABASE ?1 AS target KEY ?2",
+            params![target.to_string_lossy(), key.unwrap_or("")],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('target')", [], |_| Ok(()))?;
+        // sqlcipher_export copies data but not the schema version
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        conn.pragma_update(Some(DatabaseName::Attached("target")), "user_version", version)?;
+        conn.execute("DETACH DATABASE target", [])?;
+        Ok(())
+    };
+
+    export().inspect_err(|_| {
+        let _ = conn.execute("DETACH DATABASE target", []);
+        let _ = std::fs::remove_file(target);
+    })
+}
+
+fn connect(path: &Path, key: Option<&str>) -> Result<Connection, String> {
+    let mut conn =
+        Connection::open(path).map_err(|e| format!("Failed to open database: {}", e))?;
+
+    if let Some(key) = key {
+        conn.pragma_update(None, "key", key)
+            .map_err(|e| format!("Failed to set database key: {}", e))?;
+    }
+    // The key is only checked on first read
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+        .map_err(|_| "Failed to unlock database: wrong key or corrupt file".to_string())?;
+
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))
+        .map_err(|e| format!("Failed to enable WAL: {}", e))?;
+    conn.pragma_update(None, "foreign_keys", true)
+        .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
+
+    migrate(&mut conn)?;
+
+    Ok(conn)
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), String> {
+    let applied: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(applied as usize) {
+        let version = index + 1;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start migration {}: {}", version, e))?;
+        tx.execute_batch(sql)
+            .map_err(|e| format!("Migration {} failed: {}", version, e))?;
+        tx.pragma_update(None, "user_version", version as i64)
+            .map_err(|e| format!("Failed to record migration {}: {}", version, e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit migration {}: {}", version, e))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::budgets::Budget;
+    use crate::testing;
+
+    fn schema_version(db: &Database) -> i64 {
+        db.with_conn(|conn| conn.query_row("PRAGMA user_version", [], |row| row.get(0)))
+            .unwrap()
+    }
+
+    fn add_budget(db: &Database, channel: &str) {
+        let budget = Budget {
+            channel: channel.to_string(),
+            weekly_minutes: 60,
+        };
+        db.with_conn(|conn| budgets::set(conn, &budget)).unwrap();
+    }
+
+    fn budget_channels(db: &Database) -> Vec<String> {
+        db.with_conn(|conn| budgets::list(conn))
+            .unwrap()
+            .into_iter()
+            .map(|budget| budget.channel)
+            .collect()
+    }
+
+    #[test]
+    fn new_database_is_fully_migrated() {
+        let (_dir, db) = testing::database();
+        assert_eq!(schema_version(&db), MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn reopening_keeps_data_and_skips_applied_migrations() {
+        let (dir, db) = testing::database();
+        add_budget(&db, "work");
+        drop(db);
+
+        let db = Database::open(&dir.path().join(DATABASE_FILE), None).unwrap();
+        assert_eq!(schema_version(&db), MIGRATIONS.len() as i64);
+        assert_eq!(budget_channels(&db), vec!["work"]);
+    }
+
+    #[test]
+    fn restoring_a_snapshot_drops_later_changes() {
+        let (dir, db) = testing::database();
+        add_budget(&db, "before");
+        let snapshot = dir.path().join("snapshot.db");
+        db.snapshot(&snapshot, None).unwrap();
+        add_budget(&db, "after");
+
+        db.restore_from(&snapshot, None).unwrap();
+        assert_eq!(budget_channels(&db), vec!["before"]);
+    }
+}
@@ -0,0 +1,151 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// A goal for one week, with the tasks working towards it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Objective {
+    /// Empty for a new objective
+    #[serde(default)]
+    pub id: String,
+    /// Monday of the objective's week, `YYYY-MM-DD`
+    pub week_start: String,
+    pub title: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Time meant to go into it that week
+    #[serde(default)]
+    pub target_minutes: Option<i64>,
+    /// Order within the week
+    #[serde(default)]
+    pub position: i64,
+    /// Unix milliseconds, once marked achieved
+    #[serde(default)]
+    pub done_at: Option<i64>,
+    #[serde(default)]
+    pub task_ids: Vec<String>,
+    /// Unix milliseconds
+    #[serde(default)]
+    pub created_at: i64,
+    /// Unix milliseconds
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+const COLUMNS: &str =
+    "id, week_start, title, notes, target_minutes, position, done_at, created_at, updated_at";
+
+/// Insert or update an objective and replace its task links
+pub fn upsert(conn: &Connection, objective: &Objective) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO objectives ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(id) DO UPDATE SET
+                week_start = excluded.week_start,
+                title = excluded.title,
+                notes = excluded.notes,
+                target_minutes = excluded.target_minutes,
+                position = excluded.position,
+                done_at = excluded.done_at,
+                updated_at = excluded.updated_at",
+            COLUMNS
+        ),
+        params![
+            objective.id,
+            objective.week_start,
+            objective.title,
+            objective.notes,
+            objective.target_minutes,
+            objective.position,
+            objective.done_at,
+            objective.created_at,
+            objective.updated_at
+        ],
+    )?;
+
+    conn.execute(
+        "DELETE FROM objective_tasks WHERE objective_id = ?1",
+        params![objective.id],
+    )?;
+    for task_id in &objective.task_ids {
+        link(conn, &objective.id, task_id)?;
+    }
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM objective_tasks WHERE objective_id = ?1",
+        params![id],
+    )?;
+    conn.execute("DELETE FROM objectives WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn link(conn: &Connection, objective_id: &str, task_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO objective_tasks (objective_id, task_id) VALUES (?1, ?2)",
+        params![objective_id, task_id],
+    )?;
+    Ok(())
+}
+
+pub fn unlink(conn: &Connection, objective_id: &str, task_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM objective_tasks WHERE objective_id = ?1 AND task_id = ?2",
+        params![objective_id, task_id],
+    )?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<Objective>> {
+    let objective = conn
+        .query_row(
+            &format!("SELECT {} FROM objectives WHERE id = ?1", COLUMNS),
+            params![id],
+            from_row,
+        )
+        .optional()?;
+    objective
+        .map(|objective| with_tasks(conn, objective))
+        .transpose()
+}
+
+/// The objectives of the week starting `week_start`, in order
+pub fn list_week(conn: &Connection, week_start: &str) -> rusqlite::Result<Vec<Objective>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM objectives WHERE week_start = ?1 ORDER BY position, created_at",
+        COLUMNS
+    ))?;
+    let objectives = stmt
+        .query_map(params![week_start], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    objectives
+        .into_iter()
+        .map(|objective| with_tasks(conn, objective))
+        .collect()
+}
+
+fn with_tasks(conn: &Connection, mut objective: Objective) -> rusqlite::Result<Objective> {
+    let mut stmt = conn
+        .prepare("SELECT task_id FROM objective_tasks WHERE objective_id = ?1 ORDER BY task_id")?;
+    objective.task_ids = stmt
+        .query_map(params![objective.id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(objective)
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Objective> {
+    Ok(Objective {
+        id: row.get(0)?,
+        week_start: row.get(1)?,
+        title: row.get(2)?,
+        notes: row.get(3)?,
+        target_minutes: row.get(4)?,
+        position: row.get(5)?,
+        done_at: row.get(6)?,
+        task_ids: Vec::new(),
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
@@ -0,0 +1,313 @@
+use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEntry {
+    pub id: String,
+    /// "POST", "PATCH" or "DELETE"
+    pub method: String,
+    /// API path, e.g. `/tasks/abc`
+    pub path: String,
+    /// JSON request body
+    pub body: Option<String>,
+    /// Unix milliseconds
+    pub created_at: i64,
+    /// Not sent before this time (Unix ms), which leaves room for undo
+    pub send_after: i64,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+}
+
+const COLUMNS: &str = "id, method, path, body, created_at, send_after, attempts, last_error";
+
+pub fn insert(conn: &Connection, entry: &OutboxEntry) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO outbox (id, method, path, body, created_at, send_after, attempts, last_error)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            entry.id,
+            entry.method,
+            entry.path,
+            entry.body,
+            entry.created_at,
+            entry.send_after,
+            entry.attempts,
+            entry.last_error
+        ],
+    )?;
+    Ok(())
+}
+
+/// Remove an entry; returns whether it was still queued
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<bool> {
+    Ok(conn.execute("DELETE FROM outbox WHERE id = ?1", params![id])? > 0)
+}
+
+/// Entries ready to send at `now`, oldest first. Stops at the first entry
+/// still waiting for its `send_after`, so a later change never overtakes
+/// one that can still be undone.
+pub fn due(conn: &Connection, now: i64) -> rusqlite::Result<Vec<OutboxEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM outbox ORDER BY created_at, rowid",
+        COLUMNS
+    ))?;
+    let mut entries = Vec::new();
+    for entry in stmt.query_map([], from_row)? {
+        let entry = entry?;
+        if entry.send_after > now {
+            break;
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Whether any queued entry's path starts with `prefix`, i.e. the server
+/// hasn't seen every local change under it yet
+pub fn has_pending(conn: &Connection, prefix: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM outbox WHERE substr(path, 1, length(?1)) = ?1)",
+        params![prefix],
+        |row| row.get(0),
+    )
+}
+
+/// One entry by id, if it's still queued
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<OutboxEntry>> {
+    conn.query_row(
+        &format!("SELECT {} FROM outbox WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+/// Claim the entries due at `cutoff` for one sender until `until` (Unix
+/// ms). Returns `None` while another sender's claim from before `now`
+/// still holds, so two senders (a sync pass and the webview, two passes)
+/// never send the same entry or overtake each other. The holder deletes
+/// what it sent and `release`s the rest; a sender that dies leaves its
+/// claim to lapse, or to `release` when the app starts again.
+pub fn claim_due(
+    conn: &mut Connection,
+    cutoff: i64,
+    now: i64,
+    until: i64,
+) -> rusqlite::Result<Option<Vec<OutboxEntry>>> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let held: bool = tx.query_row(
+        "SELECT EXISTS (SELECT 1 FROM outbox WHERE claimed_until > ?1)",
+        params![now],
+        |row| row.get(0),
+    )?;
+    if held {
+        return Ok(None);
+    }
+    let entries = due(&tx, cutoff)?;
+    for entry in &entries {
+        tx.execute(
+            "UPDATE outbox SET claimed_until = ?2 WHERE id = ?1",
+            params![entry.id, until],
+        )?;
+    }
+    tx.commit()?;
+    Ok(Some(entries))
+}
+
+/// Give up the current claim, e.g. after stopping at a failure. At startup
+/// it clears the claim of a run that quit mid-send.
+pub fn release(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("UPDATE outbox SET claimed_until = 0 WHERE claimed_until > 0", [])?;
+    Ok(())
+}
+
+pub fn record_failure(conn: &Connection, id: &str, error: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE outbox SET attempts = attempts + 1, last_error = ?2 WHERE id = ?1",
+        params![id, error],
+    )?;
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<OutboxEntry> {
+    Ok(OutboxEntry {
+        id: row.get(0)?,
+        method: row.get(1)?,
+        path: row.get(2)?,
+        body: row.get(3)?,
+        created_at: row.get(4)?,
+        send_after: row.get(5)?,
+        attempts: row.get(6)?,
+        last_error: row.get(7)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn entry(id: &str, created_at: i64, send_after: i64) -> OutboxEntry {
+        OutboxEntry {
+            id: id.to_string(),
+            method: "PATCH".to_string(),
+            path: format!("/tasks/{}", id),
+            body: None,
+            created_at,
+            send_after,
+            attempts: 0,
+            last_error: None,
+        }
+    }
+
+    fn ids(entries: Vec<OutboxEntry>) -> Vec<String> {
+        entries.into_iter().map(|entry| entry.id).collect()
+    }
+
+    #[test]
+    fn due_waits_for_send_after_and_keeps_order() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            insert(conn, &entry("later", 3_000, 9_000))?;
+            insert(conn, &entry("second", 2_000, 2_000))?;
+            insert(conn, &entry("first", 1_500, 1_500))
+        })
+        .unwrap();
+
+        let due_ids = |now| ids(db.with_conn(|conn| due(conn, now)).unwrap());
+        assert_eq!(due_ids(1_499), Vec::<String>::new());
+        assert_eq!(due_ids(5_000), vec!["first", "second"]);
+        assert_eq!(due_ids(9_000), vec!["first", "second", "later"]);
+    }
+
+    #[test]
+    fn due_stops_at_an_entry_still_in_its_undo_window() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            insert(conn, &entry("undoable", 1_000, 6_000))?;
+            insert(conn, &entry("instant", 2_000, 2_000))
+        })
+        .unwrap();
+
+        let due_ids = |now| ids(db.with_conn(|conn| due(conn, now)).unwrap());
+        assert_eq!(due_ids(2_000), Vec::<String>::new());
+        assert_eq!(due_ids(6_000), vec!["undoable", "instant"]);
+    }
+
+    #[test]
+    fn pending_changes_are_found_by_path_prefix() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| insert(conn, &entry("abc", 1_000, 1_000)))
+            .unwrap();
+        assert!(db.with_conn(|conn| has_pending(conn, "/tasks/abc")).unwrap());
+        assert!(db.with_conn(|conn| has_pending(conn, "/tasks/")).unwrap());
+        assert!(!db.with_conn(|conn| has_pending(conn, "/tasks/abd")).unwrap());
+    }
+
+    #[test]
+    fn only_one_sender_holds_a_claim() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            insert(conn, &entry("first", 1_000, 1_000))?;
+            insert(conn, &entry("second", 2_000, 2_000))
+        })
+        .unwrap();
+
+        let claimed = db
+            .with_conn(|conn| claim_due(conn, 5_000, 5_000, 65_000))
+            .unwrap();
+        assert_eq!(claimed.map(ids), Some(vec!["first".to_string(), "second".to_string()]));
+        assert!(db
+            .with_conn(|conn| claim_due(conn, 5_000, 6_000, 66_000))
+            .unwrap()
+            .is_none());
+
+        // The holder sent the first and stopped at the second
+        db.with_conn(|conn| {
+            delete(conn, "first")?;
+            release(conn)
+        })
+        .unwrap();
+        let retried = db
+            .with_conn(|conn| claim_due(conn, 7_000, 7_000, 67_000))
+            .unwrap();
+        assert_eq!(retried.map(ids), Some(vec!["second".to_string()]));
+
+        // A claim left behind lapses
+        let lapsed = db
+            .with_conn(|conn| claim_due(conn, 70_000, 70_000, 130_000))
+            .unwrap();
+        assert_eq!(lapsed.map(ids), Some(vec!["second".to_string()]));
+    }
+
+    #[test]
+    fn a_relaunch_clears_a_claim_left_behind() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| insert(conn, &entry("a", 1_000, 1_000)))
+            .unwrap();
+        db.with_conn(|conn| claim_due(conn, 5_000, 5_000, 65_000))
+            .unwrap();
+
+        // The app quit mid-send and starts again well within the claim
+        db.with_conn(|conn| release(conn)).unwrap();
+        let claimed = db
+            .with_conn(|conn| claim_due(conn, 6_000, 6_000, 66_000))
+            .unwrap();
+        assert_eq!(claimed.map(ids), Some(vec!["a".to_string()]));
+    }
+
+    #[test]
+    fn a_held_claim_is_told_apart_from_an_empty_queue() {
+        let (_dir, db) = testing::database();
+        let empty = db
+            .with_conn(|conn| claim_due(conn, 1_000, 1_000, 61_000))
+            .unwrap();
+        assert_eq!(empty.map(ids), Some(Vec::new()));
+
+        db.with_conn(|conn| {
+            insert(conn, &entry("sent", 1_000, 1_000))?;
+            insert(conn, &entry("undoable", 2_000, 9_000))
+        })
+        .unwrap();
+        db.with_conn(|conn| claim_due(conn, 2_000, 2_000, 62_000))
+            .unwrap();
+        // A drain on quit waits while a flush holds the claim
+        assert!(db
+            .with_conn(|conn| claim_due(conn, i64::MAX, 3_000, 63_000))
+            .unwrap()
+            .is_none());
+
+        // and then sends everything, undo window or not
+        db.with_conn(|conn| {
+            delete(conn, "sent")?;
+            release(conn)
+        })
+        .unwrap();
+        let drained = db
+            .with_conn(|conn| claim_due(conn, i64::MAX, 4_000, 64_000))
+            .unwrap();
+        assert_eq!(drained.map(ids), Some(vec!["undoable".to_string()]));
+    }
+
+    #[test]
+    fn failures_are_counted_and_deleted_entries_stay_gone() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| insert(conn, &entry("a", 0, 0)))
+            .unwrap();
+        db.with_conn(|conn| record_failure(conn, "a", "SERVER_ERROR: down"))
+            .unwrap();
+        db.with_conn(|conn| record_failure(conn, "a", "SERVER_ERROR: still down"))
+            .unwrap();
+
+        let queued = db.with_conn(|conn| due(conn, 0)).unwrap();
+        assert_eq!(queued[0].attempts, 2);
+        assert_eq!(
+            queued[0].last_error.as_deref(),
+            Some("SERVER_ERROR: still down")
+        );
+
+        assert!(db.with_conn(|conn| delete(conn, "a")).unwrap());
+        assert!(!db.with_conn(|conn| delete(conn, "a")).unwrap());
+    }
+}
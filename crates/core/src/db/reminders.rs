@@ -0,0 +1,170 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reminder {
+    pub id: String,
+    pub task_id: Option<String>,
+    pub title: String,
+    pub body: Option<String>,
+    /// Unix milliseconds
+    pub fire_at: i64,
+    /// IANA timezone the reminder was scheduled in
+    pub timezone: String,
+    #[serde(default)]
+    pub delivered_at: Option<i64>,
+}
+
+const COLUMNS: &str = "id, task_id, title, body, fire_at, timezone, delivered_at";
+
+/// Insert or replace a reminder. Rescheduling clears any previous delivery.
+pub fn upsert(conn: &Connection, reminder: &Reminder) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO reminders (id, task_id, title, body, fire_at, timezone, delivered_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL)
+         ON CONFLICT(id) DO UPDATE SET
+            task_id = excluded.task_id,
+            title = excluded.title,
+            body = excluded.body,
+            fire_at = excluded.fire_at,
+            timezone = excluded.timezone,
+            delivered_at = NULL",
+        params![
+            reminder.id,
+            reminder.task_id,
+            reminder.title,
+            reminder.body,
+            reminder.fire_at,
+            reminder.timezone
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM reminders WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<Reminder>> {
+    conn.query_row(
+        &format!("SELECT {} FROM reminders WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+/// Reminders that have not been delivered yet, soonest first
+pub fn list_pending(conn: &Connection) -> rusqlite::Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM reminders WHERE delivered_at IS NULL ORDER BY fire_at",
+        COLUMNS
+    ))?;
+    let reminders = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(reminders)
+}
+
+/// Pending reminders whose fire time is at or before `now`
+pub fn due(conn: &Connection, now: i64) -> rusqlite::Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM reminders WHERE delivered_at IS NULL AND fire_at <= ?1 ORDER BY fire_at",
+        COLUMNS
+    ))?;
+    let reminders = stmt
+        .query_map(params![now], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(reminders)
+}
+
+pub fn mark_delivered(conn: &Connection, id: &str, delivered_at: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE reminders SET delivered_at = ?2 WHERE id = ?1",
+        params![id, delivered_at],
+    )?;
+    Ok(())
+}
+
+pub fn reschedule(
+    conn: &Connection,
+    id: &str,
+    fire_at: i64,
+    timezone: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE reminders SET fire_at = ?2, timezone = ?3 WHERE id = ?1",
+        params![id, fire_at, timezone],
+    )?;
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Reminder> {
+    Ok(Reminder {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        title: row.get(2)?,
+        body: row.get(3)?,
+        fire_at: row.get(4)?,
+        timezone: row.get(5)?,
+        delivered_at: row.get(6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn reminder(id: &str, fire_at: i64) -> Reminder {
+        Reminder {
+            id: id.to_string(),
+            task_id: None,
+            title: id.to_string(),
+            body: None,
+            fire_at,
+            timezone: "UTC".to_string(),
+            delivered_at: None,
+        }
+    }
+
+    fn due_ids(conn: &Connection, now: i64) -> Vec<String> {
+        due(conn, now)
+            .unwrap()
+            .into_iter()
+            .map(|reminder| reminder.id)
+            .collect()
+    }
+
+    #[test]
+    fn delivered_reminders_stop_being_due() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            upsert(conn, &reminder("later", 2_000))?;
+            upsert(conn, &reminder("sooner", 1_000))?;
+            assert_eq!(due_ids(conn, 999), Vec::<String>::new());
+            assert_eq!(due_ids(conn, 2_000), vec!["sooner", "later"]);
+
+            mark_delivered(conn, "sooner", 2_000)?;
+            assert_eq!(due_ids(conn, 2_000), vec!["later"]);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn upserting_again_reschedules_a_delivered_reminder() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            upsert(conn, &reminder("a", 1_000))?;
+            mark_delivered(conn, "a", 1_000)?;
+            upsert(conn, &reminder("a", 5_000))?;
+            assert_eq!(due_ids(conn, 4_999), Vec::<String>::new());
+            assert_eq!(due_ids(conn, 5_000), vec!["a"]);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use serde_json::Value;
+
+/// A notification put off until `fire_at`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snoozed {
+    pub id: String,
+    /// Notification category key, e.g. `reminder`
+    pub category: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub sound: Option<String>,
+    pub task_id: Option<String>,
+    /// Whatever the webview needs to show the notification's actions again
+    pub data: Option<Value>,
+    /// Unix milliseconds
+    pub fire_at: i64,
+    /// How many times in a row it has been snoozed
+    pub snooze_count: i64,
+    /// Unix milliseconds
+    pub snoozed_at: i64,
+}
+
+const COLUMNS: &str =
+    "id, category, title, body, sound, task_id, data, fire_at, snooze_count, snoozed_at";
+
+/// Insert or replace a snoozed notification, counting repeat snoozes
+pub fn upsert(conn: &Connection, snoozed: &Snoozed) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO snoozed ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1, ?9)
+             ON CONFLICT(id) DO UPDATE SET
+                category = excluded.category,
+                title = excluded.title,
+                body = excluded.body,
+                sound = excluded.sound,
+                task_id = excluded.task_id,
+                data = excluded.data,
+                fire_at = excluded.fire_at,
+                snooze_count = snoozed.snooze_count + 1,
+                snoozed_at = excluded.snoozed_at",
+            COLUMNS
+        ),
+        params![
+            snoozed.id,
+            snoozed.category,
+            snoozed.title,
+            snoozed.body,
+            snoozed.sound,
+            snoozed.task_id,
+            snoozed.data.as_ref().map(Value::to_string),
+            snoozed.fire_at,
+            snoozed.snoozed_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<Snoozed>> {
+    conn.query_row(
+        &format!("SELECT {} FROM snoozed WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM snoozed WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Every snoozed notification, soonest first
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<Snoozed>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM snoozed ORDER BY fire_at", COLUMNS))?;
+    let snoozed = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(snoozed)
+}
+
+/// Snoozed notifications whose time has come
+pub fn due(conn: &Connection, now: i64) -> rusqlite::Result<Vec<Snoozed>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM snoozed WHERE fire_at <= ?1 ORDER BY fire_at",
+        COLUMNS
+    ))?;
+    let snoozed = stmt
+        .query_map(params![now], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(snoozed)
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Snoozed> {
+    let data: Option<String> = row.get(6)?;
+    Ok(Snoozed {
+        id: row.get(0)?,
+        category: row.get(1)?,
+        title: row.get(2)?,
+        body: row.get(3)?,
+        sound: row.get(4)?,
+        task_id: row.get(5)?,
+        data: data.and_then(|data| serde_json::from_str(&data).ok()),
+        fire_at: row.get(7)?,
+        snooze_count: row.get(8)?,
+        snoozed_at: row.get(9)?,
+    })
+}
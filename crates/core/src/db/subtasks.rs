@@ -0,0 +1,149 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// A subtask or checklist item, ordered within its task
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subtask {
+    pub id: String,
+    pub task_id: String,
+    pub title: String,
+    /// Order within the task, from 0
+    pub position: i64,
+    pub estimated_mins: Option<i64>,
+    /// Unix milliseconds
+    pub completed_at: Option<i64>,
+    /// Unix milliseconds
+    pub created_at: i64,
+    /// Unix milliseconds
+    pub updated_at: i64,
+}
+
+const COLUMNS: &str =
+    "id, task_id, title, position, estimated_mins, completed_at, created_at, updated_at";
+
+/// Insert a subtask after the task's last one, setting its position
+pub fn insert(conn: &Connection, subtask: &mut Subtask) -> rusqlite::Result<()> {
+    subtask.position = conn.query_row(
+        "SELECT COALESCE(MAX(position) + 1, 0) FROM subtasks WHERE task_id = ?1",
+        params![subtask.task_id],
+        |row| row.get(0),
+    )?;
+    conn.execute(
+        &format!(
+            "INSERT INTO subtasks ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            COLUMNS
+        ),
+        params![
+            subtask.id,
+            subtask.task_id,
+            subtask.title,
+            subtask.position,
+            subtask.estimated_mins,
+            subtask.completed_at,
+            subtask.created_at,
+            subtask.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Save a subtask's title, estimate and completion
+pub fn update(conn: &Connection, subtask: &Subtask) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE subtasks
+         SET title = ?2, estimated_mins = ?3, completed_at = ?4, updated_at = ?5
+         WHERE id = ?1",
+        params![
+            subtask.id,
+            subtask.title,
+            subtask.estimated_mins,
+            subtask.completed_at,
+            subtask.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM subtasks WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Remove all of a task's subtasks
+pub fn delete_task(conn: &Connection, task_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM subtasks WHERE task_id = ?1", params![task_id])?;
+    Ok(())
+}
+
+/// Make `subtasks` the task's whole list, in their order, e.g. with the
+/// server's copy
+pub fn replace(conn: &Connection, task_id: &str, subtasks: &[Subtask]) -> rusqlite::Result<()> {
+    delete_task(conn, task_id)?;
+    for subtask in subtasks {
+        insert(conn, &mut subtask.clone())?;
+    }
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<Subtask>> {
+    conn.query_row(
+        &format!("SELECT {} FROM subtasks WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+/// A task's subtasks, in order
+pub fn list(conn: &Connection, task_id: &str) -> rusqlite::Result<Vec<Subtask>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM subtasks WHERE task_id = ?1 ORDER BY position, created_at",
+        COLUMNS
+    ))?;
+    let subtasks = stmt
+        .query_map(params![task_id], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(subtasks)
+}
+
+/// Renumber a task's subtasks: `ids` first in the given order, then any
+/// left out in their current order. Ids of other tasks' subtasks are
+/// ignored.
+pub fn reorder(conn: &Connection, task_id: &str, ids: &[String]) -> rusqlite::Result<()> {
+    let current = list(conn, task_id)?;
+    let mut seen = HashSet::new();
+    let listed = ids
+        .iter()
+        .filter(|id| current.iter().any(|subtask| &subtask.id == *id))
+        .filter(|id| seen.insert(id.as_str()));
+    let rest = current
+        .iter()
+        .map(|subtask| &subtask.id)
+        .filter(|id| !ids.contains(id));
+
+    let mut stmt = conn.prepare("UPDATE subtasks SET position = ?2 WHERE id = ?1")?;
+    for (position, id) in listed.chain(rest).enumerate() {
+        stmt.execute(params![id, position as i64])?;
+    }
+    Ok(())
+}
+
+/// Close the gap left by a deleted subtask
+pub fn compact(conn: &Connection, task_id: &str) -> rusqlite::Result<()> {
+    reorder(conn, task_id, &[])
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Subtask> {
+    Ok(Subtask {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        title: row.get(2)?,
+        position: row.get(3)?,
+        estimated_mins: row.get(4)?,
+        completed_at: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
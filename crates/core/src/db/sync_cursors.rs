@@ -0,0 +1,29 @@
+//! Where each incremental download left off, as the server's own value
+//! (e.g. the newest `updatedAt` it sent). Kept apart from the rows so local
+//! edits, which stamp their own times, can't move it.
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+pub fn get(conn: &Connection, name: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT cursor FROM sync_cursors WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn set(conn: &Connection, name: &str, cursor: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO sync_cursors (name, cursor) VALUES (?1, ?2)
+         ON CONFLICT(name) DO UPDATE SET cursor = excluded.cursor",
+        params![name, cursor],
+    )?;
+    Ok(())
+}
+
+/// Forget a cursor, so the next download starts over
+pub fn clear(conn: &Connection, name: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM sync_cursors WHERE name = ?1", params![name])?;
+    Ok(())
+}
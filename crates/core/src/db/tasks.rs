@@ -0,0 +1,498 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::DateTime;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::task;
+
+/// Pages are never longer than this
+pub const MAX_LIMIT: usize = 500;
+
+/// Which tasks a query returns; unset fields don't filter
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskFilter {
+    /// First scheduled date, `YYYY-MM-DD`
+    pub from: Option<String>,
+    /// Last scheduled date, `YYYY-MM-DD`
+    pub to: Option<String>,
+    pub channel: Option<String>,
+    pub status: TaskStatus,
+    /// Words found in the title or notes, ignoring case and accents; the
+    /// last one may be the start of a word
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskStatus {
+    #[default]
+    All,
+    Open,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskSortField {
+    #[default]
+    ScheduledDate,
+    CompletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl TaskSortField {
+    fn column(self) -> &'static str {
+        match self {
+            TaskSortField::ScheduledDate => "scheduled_date",
+            TaskSortField::CompletedAt => "completed_at",
+            TaskSortField::CreatedAt => "created_at",
+            TaskSortField::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+/// Newest first unless `ascending`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskSort {
+    pub field: TaskSortField,
+    pub ascending: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskPage {
+    /// Tasks as the server last sent them
+    pub tasks: Vec<Value>,
+    /// Pass back for the next page; `None` on the last one
+    pub next_cursor: Option<String>,
+}
+
+/// Store a task as the server sent it. Tasks without an id are ignored.
+pub fn upsert(conn: &Connection, task: &Value) -> rusqlite::Result<()> {
+    let Some(id) = task.get("id").and_then(Value::as_str) else {
+        return Ok(());
+    };
+    let text = |field: &str| task.get(field).and_then(Value::as_str);
+    let millis = |field: &str| {
+        text(field)
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.timestamp_millis())
+    };
+    let date = |field: &str| {
+        text(field)
+            .map(|date| date.get(..10).unwrap_or(date))
+            .unwrap_or_default()
+    };
+    let created_at = millis("createdAt").unwrap_or(0);
+
+    conn.execute(
+        "INSERT INTO tasks (id, title, notes, channel, scheduled_date, due_date, completed_at,
+                            created_at, updated_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT(id) DO UPDATE SET
+            title = excluded.title,
+            notes = excluded.notes,
+            channel = excluded.channel,
+            scheduled_date = excluded.scheduled_date,
+            due_date = excluded.due_date,
+            completed_at = excluded.completed_at,
+            created_at = excluded.created_at,
+            updated_at = excluded.updated_at,
+            data = excluded.data",
+        params![
+            id,
+            text("title").unwrap_or_default(),
+            text("notes"),
+            task::channel(text("notes")),
+            date("scheduledDate"),
+            date("dueDate"),
+            millis("completedAt").unwrap_or(0),
+            created_at,
+            millis("updatedAt").unwrap_or(created_at),
+            task.to_string()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Apply a page of server changes: store each task, or drop it when the
+/// server sent its tombstone (`deletedAt` set). Returns the newest
+/// `updatedAt` among them, as the server wrote it, for the next download's
+/// `updatedSince`.
+pub fn apply_changes(conn: &Connection, changes: &[Value]) -> rusqlite::Result<Option<String>> {
+    let mut newest: Option<(i64, &str)> = None;
+    for task in changes {
+        let deleted = task.get("deletedAt").is_some_and(|at| !at.is_null());
+        match task.get("id").and_then(Value::as_str) {
+            Some(id) if deleted => delete(conn, id)?,
+            _ => upsert(conn, task)?,
+        }
+        let updated = task.get("updatedAt").and_then(Value::as_str).and_then(|at| {
+            DateTime::parse_from_rfc3339(at)
+                .ok()
+                .map(|parsed| (parsed.timestamp_millis(), at))
+        });
+        if let Some(updated) = updated {
+            if newest.is_none_or(|newest| updated.0 > newest.0) {
+                newest = Some(updated);
+            }
+        }
+    }
+    Ok(newest.map(|(_, at)| at.to_string()))
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM tasks WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// The stored copy of a task, as the server returned it
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<Value>> {
+    let data: Option<String> = conn
+        .query_row("SELECT data FROM tasks WHERE id = ?1", params![id], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    Ok(data.and_then(|data| serde_json::from_str(&data).ok()))
+}
+
+/// The channels of the stored tasks among `ids`, by id, in one query.
+/// Tasks that aren't stored are left out.
+pub fn channels(
+    conn: &Connection,
+    ids: &[String],
+) -> rusqlite::Result<HashMap<String, Option<String>>> {
+    let mut stmt = conn
+        .prepare("SELECT id, channel FROM tasks WHERE id IN (SELECT value FROM json_each(?1))")?;
+    let rows = stmt.query_map(params![json!(ids).to_string()], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?;
+    rows.collect()
+}
+
+/// Remove every task whose id starts with `prefix`
+pub fn delete_prefixed(conn: &Connection, prefix: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "DELETE FROM tasks WHERE substr(id, 1, length(?1)) = ?1",
+        params![prefix],
+    )
+}
+
+/// Drop every stored task, before a full download replaces them
+pub fn clear(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM tasks", [])?;
+    Ok(())
+}
+
+/// How many tasks scheduled on `date` (`YYYY-MM-DD`) are still open
+pub fn count_open_on(conn: &Connection, date: &str) -> rusqlite::Result<u32> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM tasks WHERE scheduled_date = ?1 AND completed_at = 0",
+        params![date],
+        |row| row.get(0),
+    )
+}
+
+/// How many tasks scheduled on `date` (`YYYY-MM-DD`) are completed
+pub fn count_completed_on(conn: &Connection, date: &str) -> rusqlite::Result<u32> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM tasks WHERE scheduled_date = ?1 AND completed_at != 0",
+        params![date],
+        |row| row.get(0),
+    )
+}
+
+/// One page of the tasks matching `filter`, after `cursor` from the
+/// previous page. Pages by the sort column and id, so rows changing
+/// between pages don't shift the rest.
+pub fn query(
+    conn: &Connection,
+    filter: &TaskFilter,
+    sort: TaskSort,
+    cursor: Option<&str>,
+    limit: usize,
+) -> rusqlite::Result<TaskPage> {
+    let column = sort.field.column();
+    let mut conditions: Vec<String> = match filter.status {
+        TaskStatus::All => Vec::new(),
+        TaskStatus::Open => vec!["completed_at = 0".to_string()],
+        TaskStatus::Completed => vec!["completed_at != 0".to_string()],
+    };
+    let mut values: Vec<SqlValue> = Vec::new();
+    // `?` in `condition` stands for `value`
+    let mut bind = |condition: &str, value: SqlValue| {
+        values.push(value);
+        conditions.push(condition.replace('?', &format!("?{}", values.len())));
+    };
+
+    if let Some(from) = &filter.from {
+        bind("scheduled_date >= ?", SqlValue::Text(from.clone()));
+    }
+    if let Some(to) = &filter.to {
+        bind(
+            "scheduled_date != '' AND scheduled_date <= ?",
+            SqlValue::Text(to.clone()),
+        );
+    }
+    if let Some(channel) = filter
+        .channel
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+    {
+        bind(
+            "channel = ?",
+            SqlValue::Text(channel.trim_start_matches('#').to_string()),
+        );
+    }
+    if let Some(text) = filter.text.as_deref().and_then(match_expression) {
+        bind(
+            "rowid IN (SELECT rowid FROM tasks_fts WHERE tasks_fts MATCH ?)",
+            SqlValue::Text(text),
+        );
+    }
+    if let Some((key, id)) = cursor.and_then(decode_cursor) {
+        values.push(key);
+        values.push(SqlValue::Text(id));
+        conditions.push(format!(
+            "({}, id) {} (?{}, ?{})",
+            column,
+            if sort.ascending { ">" } else { "<" },
+            values.len() - 1,
+            values.len()
+        ));
+    }
+
+    let limit = limit.clamp(1, MAX_LIMIT);
+    let direction = if sort.ascending { "ASC" } else { "DESC" };
+    let sql = format!(
+        "SELECT id, {column}, data FROM tasks {where_clause}
+         ORDER BY {column} {direction}, id {direction} LIMIT {fetch}",
+        where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        },
+        fetch = limit + 1,
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt
+        .query_map(params_from_iter(values), |row| {
+            let id: String = row.get(0)?;
+            let key: SqlValue = row.get(1)?;
+            let data: String = row.get(2)?;
+            Ok((id, key, data))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let next_cursor = if rows.len() > limit {
+        rows.truncate(limit);
+        rows.last().map(|(id, key, _)| encode_cursor(key, id))
+    } else {
+        None
+    };
+    Ok(TaskPage {
+        tasks: rows
+            .into_iter()
+            .filter_map(|(_, _, data)| serde_json::from_str(&data).ok())
+            .collect(),
+        next_cursor,
+    })
+}
+
+/// An FTS5 query for `text`: every word quoted so its punctuation is
+/// taken literally, the last also matching as a prefix while it's typed
+fn match_expression(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    let (last, rest) = words.split_last()?;
+    Some(
+        rest.iter()
+            .cloned()
+            .chain([format!("{}*", last)])
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// The last row's sort key and id, as opaque text
+fn encode_cursor(key: &SqlValue, id: &str) -> String {
+    let key = match key {
+        SqlValue::Integer(value) => Value::from(*value),
+        SqlValue::Text(value) => Value::from(value.as_str()),
+        _ => Value::Null,
+    };
+    URL_SAFE_NO_PAD.encode(Value::Array(vec![key, Value::from(id)]).to_string())
+}
+
+fn decode_cursor(cursor: &str) -> Option<(SqlValue, String)> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let (key, id): (Value, String) = serde_json::from_slice(&bytes).ok()?;
+    let key = match key {
+        Value::Number(value) => SqlValue::Integer(value.as_i64()?),
+        Value::String(value) => SqlValue::Text(value),
+        _ => return None,
+    };
+    Some((key, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use serde_json::json;
+
+    fn ids(page: &TaskPage) -> Vec<&str> {
+        page.tasks
+            .iter()
+            .filter_map(|task| task.get("id").and_then(Value::as_str))
+            .collect()
+    }
+
+    fn store(conn: &Connection) -> rusqlite::Result<()> {
+        for (id, date, completed_at) in [
+            ("a", "2026-03-02", None),
+            ("b", "2026-03-02", Some("2026-03-02T10:00:00Z")),
+            ("c", "2026-03-03", None),
+            ("d", "2026-03-04", None),
+            ("e", "2026-03-09", None),
+        ] {
+            let notes = if id == "c" { "#deep\nFocus" } else { "" };
+            upsert(
+                conn,
+                &json!({
+                    "id": id,
+                    "title": format!("Task {}", id),
+                    "notes": notes,
+                    "scheduledDate": date,
+                    "completedAt": completed_at,
+                    "createdAt": "2026-03-01T09:00:00Z",
+                }),
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn pages_follow_the_cursor_without_repeats() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            store(conn)?;
+            let sort = TaskSort {
+                field: TaskSortField::ScheduledDate,
+                ascending: true,
+            };
+            let filter = TaskFilter::default();
+
+            let first = query(conn, &filter, sort, None, 2)?;
+            assert_eq!(ids(&first), vec!["a", "b"]);
+            let second = query(conn, &filter, sort, first.next_cursor.as_deref(), 2)?;
+            assert_eq!(ids(&second), vec!["c", "d"]);
+            let last = query(conn, &filter, sort, second.next_cursor.as_deref(), 2)?;
+            assert_eq!(ids(&last), vec!["e"]);
+            assert!(last.next_cursor.is_none());
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn filters_combine() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            store(conn)?;
+            let week = TaskFilter {
+                from: Some("2026-03-02".to_string()),
+                to: Some("2026-03-08".to_string()),
+                status: TaskStatus::Open,
+                ..TaskFilter::default()
+            };
+            let page = query(conn, &week, TaskSort::default(), None, 10)?;
+            assert_eq!(ids(&page), vec!["d", "c", "a"]);
+
+            let channel = TaskFilter {
+                channel: Some("#deep".to_string()),
+                ..TaskFilter::default()
+            };
+            let page = query(conn, &channel, TaskSort::default(), None, 10)?;
+            assert_eq!(ids(&page), vec!["c"]);
+
+            let search = |text: &str| TaskFilter {
+                text: Some(text.to_string()),
+                ..TaskFilter::default()
+            };
+            let page = query(conn, &search("foc"), TaskSort::default(), None, 10)?;
+            assert_eq!(ids(&page), vec!["c"]);
+            // Quotes and operators are just text
+            let page = query(conn, &search("TASK \"e"), TaskSort::default(), None, 10)?;
+            assert_eq!(ids(&page), vec!["e"]);
+            let page = query(conn, &search("task OR"), TaskSort::default(), None, 10)?;
+            assert!(page.tasks.is_empty());
+
+            // Renamed and deleted tasks leave the index
+            upsert(conn, &json!({ "id": "e", "title": "Renamed", "createdAt": "2026-03-01T09:00:00Z" }))?;
+            assert!(query(conn, &search("task e"), TaskSort::default(), None, 10)?.tasks.is_empty());
+            delete(conn, "c")?;
+            assert!(query(conn, &search("focus"), TaskSort::default(), None, 10)?.tasks.is_empty());
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn changes_apply_tombstones_and_report_the_newest_update() {
+        let (_dir, db) = testing::database();
+        let changes = [
+            json!({ "id": "a", "title": "A", "createdAt": "2026-03-01T09:00:00.000Z",
+                    "updatedAt": "2026-03-02T09:00:00.000Z" }),
+            json!({ "id": "b", "title": "B", "createdAt": "2026-03-01T09:00:00.000Z",
+                    "updatedAt": "2026-03-03T09:00:00.000Z" }),
+        ];
+        let cursor = db.with_conn(|conn| apply_changes(conn, &changes)).unwrap();
+        assert_eq!(cursor.as_deref(), Some("2026-03-03T09:00:00.000Z"));
+
+        let tombstone = [json!({ "id": "a", "deletedAt": "2026-03-04T09:00:00.000Z",
+                                 "updatedAt": "2026-03-04T09:00:00.000Z" })];
+        db.with_conn(|conn| apply_changes(conn, &tombstone)).unwrap();
+        assert!(db.with_conn(|conn| get(conn, "a")).unwrap().is_none());
+        assert!(db.with_conn(|conn| get(conn, "b")).unwrap().is_some());
+    }
+
+    #[test]
+    fn channels_come_from_stored_tasks_only() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            store(conn)?;
+            let ids = ["a", "c", "missing"].map(str::to_string);
+            let channels = channels(conn, &ids)?;
+            assert_eq!(channels.len(), 2);
+            assert_eq!(channels["a"], None);
+            assert_eq!(channels["c"].as_deref(), Some("deep"));
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn counts_open_and_completed_tasks_on_a_day() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            store(conn)?;
+            assert_eq!(count_open_on(conn, "2026-03-02")?, 1);
+            assert_eq!(count_open_on(conn, "2026-03-05")?, 0);
+            assert_eq!(count_completed_on(conn, "2026-03-02")?, 1);
+            assert_eq!(count_completed_on(conn, "2026-03-03")?, 0);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+/// CRDT state of one collaboratively edited task field
+#[derive(Debug, Clone)]
+pub struct TextDoc {
+    pub task_id: String,
+    /// "notes" or "description"
+    pub field: String,
+    /// Encoded Yjs update holding the whole document
+    pub state: Vec<u8>,
+    /// Yjs client id of this replica's edits; see `replica_client_id`
+    pub client_id: u64,
+}
+
+const COLUMNS: &str = "task_id, field, state, client_id";
+
+pub fn get(conn: &Connection, task_id: &str, field: &str) -> rusqlite::Result<Option<TextDoc>> {
+    conn.query_row(
+        &format!("SELECT {} FROM text_docs WHERE task_id = ?1 AND field = ?2", COLUMNS),
+        params![task_id, field],
+        from_row,
+    )
+    .optional()
+}
+
+/// Store a document; `dirty` marks local changes the server hasn't seen
+pub fn save(conn: &Connection, doc: &TextDoc, dirty: bool, now: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO text_docs (task_id, field, state, client_id, dirty, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT (task_id, field) DO UPDATE SET
+            state = excluded.state,
+            dirty = text_docs.dirty OR excluded.dirty,
+            updated_at = excluded.updated_at",
+        params![doc.task_id, doc.field, doc.state, doc.client_id as i64, dirty, now],
+    )?;
+    Ok(())
+}
+
+pub fn all(conn: &Connection) -> rusqlite::Result<Vec<TextDoc>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM text_docs", COLUMNS))?;
+    let docs = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(docs)
+}
+
+/// Documents with changes not yet sent
+pub fn dirty(conn: &Connection) -> rusqlite::Result<Vec<TextDoc>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM text_docs WHERE dirty = 1 ORDER BY updated_at",
+        COLUMNS
+    ))?;
+    let docs = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(docs)
+}
+
+/// Clear the dirty flag, unless the document changed again since `sent`
+pub fn mark_synced(conn: &Connection, task_id: &str, field: &str, sent: &[u8]) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE text_docs SET dirty = 0 WHERE task_id = ?1 AND field = ?2 AND state = ?3",
+        params![task_id, field, sent],
+    )?;
+    Ok(())
+}
+
+pub fn delete_task(conn: &Connection, task_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM text_docs WHERE task_id = ?1", params![task_id])?;
+    Ok(())
+}
+
+/// This replica's Yjs client id, picked at random the first time. Yjs ids
+/// must fit in 32 bits for JavaScript peers, and 0 is left for seed text.
+pub fn replica_client_id(conn: &Connection) -> rusqlite::Result<u64> {
+    let stored: Option<i64> = conn
+        .query_row("SELECT client_id FROM replica", [], |row| row.get(0))
+        .optional()?;
+    if let Some(id) = stored {
+        return Ok(id as u64);
+    }
+    let id = u64::from((uuid::Uuid::new_v4().as_u128() as u32).max(1));
+    conn.execute("INSERT INTO replica (client_id) VALUES (?1)", params![id as i64])?;
+    Ok(id)
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<TextDoc> {
+    Ok(TextDoc {
+        task_id: row.get(0)?,
+        field: row.get(1)?,
+        state: row.get(2)?,
+        client_id: row.get::<_, i64>(3)? as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn the_replica_keeps_one_client_id() {
+        let (_dir, db) = testing::database();
+        let first = db.with_conn(|conn| replica_client_id(conn)).unwrap();
+        let again = db.with_conn(|conn| replica_client_id(conn)).unwrap();
+        assert_eq!(first, again);
+        assert!(first > 0 && first <= u64::from(u32::MAX));
+    }
+}
@@ -0,0 +1,161 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::tombstones;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeBlock {
+    pub id: String,
+    pub task_id: Option<String>,
+    pub title: String,
+    /// Unix milliseconds
+    pub start_at: i64,
+    /// Unix milliseconds
+    pub end_at: i64,
+    /// IANA timezone the block was planned in
+    pub timezone: String,
+    /// A calendar event (meeting) rather than time set aside for a task
+    #[serde(default)]
+    pub is_event: bool,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Unix milliseconds of the last change, stamped by the database; 0 for
+    /// a block not stored yet
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+const COLUMNS: &str =
+    "id, task_id, title, start_at, end_at, timezone, is_event, location, description, updated_at";
+
+pub fn upsert(conn: &Connection, block: &TimeBlock) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO time_blocks (id, task_id, title, start_at, end_at, timezone, is_event, location, description)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+            task_id = excluded.task_id,
+            title = excluded.title,
+            start_at = excluded.start_at,
+            end_at = excluded.end_at,
+            timezone = excluded.timezone,
+            is_event = excluded.is_event,
+            location = excluded.location,
+            description = excluded.description",
+        params![
+            block.id,
+            block.task_id,
+            block.title,
+            block.start_at,
+            block.end_at,
+            block.timezone,
+            block.is_event,
+            block.location,
+            block.description
+        ],
+    )?;
+    Ok(())
+}
+
+/// Add or update a block from another device. The newer copy wins, and a
+/// block deleted here since it last changed stays deleted.
+pub fn merge(conn: &Connection, block: &TimeBlock) -> rusqlite::Result<()> {
+    if tombstones::deleted_at(conn, tombstones::TIME_BLOCK, &block.id)?
+        .is_some_and(|deleted_at| deleted_at >= block.updated_at)
+    {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO time_blocks (id, task_id, title, start_at, end_at, timezone, is_event, location, description, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT(id) DO UPDATE SET
+            task_id = excluded.task_id,
+            title = excluded.title,
+            start_at = excluded.start_at,
+            end_at = excluded.end_at,
+            timezone = excluded.timezone,
+            is_event = excluded.is_event,
+            location = excluded.location,
+            description = excluded.description,
+            updated_at = excluded.updated_at
+         WHERE excluded.updated_at > time_blocks.updated_at",
+        params![
+            block.id,
+            block.task_id,
+            block.title,
+            block.start_at,
+            block.end_at,
+            block.timezone,
+            block.is_event,
+            block.location,
+            block.description,
+            block.updated_at
+        ],
+    )?;
+    tombstones::clear(conn, tombstones::TIME_BLOCK, &block.id)
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM time_blocks WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Remove every block whose id starts with `prefix`
+pub fn delete_prefixed(conn: &Connection, prefix: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "DELETE FROM time_blocks WHERE substr(id, 1, length(?1)) = ?1",
+        params![prefix],
+    )
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<TimeBlock>> {
+    conn.query_row(
+        &format!("SELECT {} FROM time_blocks WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+/// Blocks overlapping the `[start, end)` range
+pub fn list_between(conn: &Connection, start: i64, end: i64) -> rusqlite::Result<Vec<TimeBlock>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM time_blocks WHERE start_at < ?2 AND end_at > ?1 ORDER BY start_at",
+        COLUMNS
+    ))?;
+    let blocks = stmt
+        .query_map(params![start, end], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(blocks)
+}
+
+pub fn reschedule(
+    conn: &Connection,
+    id: &str,
+    start_at: i64,
+    end_at: i64,
+    timezone: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE time_blocks SET start_at = ?2, end_at = ?3, timezone = ?4 WHERE id = ?1",
+        params![id, start_at, end_at, timezone],
+    )?;
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<TimeBlock> {
+    Ok(TimeBlock {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        title: row.get(2)?,
+        start_at: row.get(3)?,
+        end_at: row.get(4)?,
+        timezone: row.get(5)?,
+        is_event: row.get(6)?,
+        location: row.get(7)?,
+        description: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
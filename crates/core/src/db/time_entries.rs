@@ -0,0 +1,134 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::tombstones;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeEntry {
+    pub id: String,
+    pub task_id: String,
+    /// Title when the timer started, so entries read well offline
+    pub task_title: String,
+    /// Unix milliseconds
+    pub started_at: i64,
+    /// Unix milliseconds; `None` while the timer runs
+    pub ended_at: Option<i64>,
+    /// Unix milliseconds of the last change, stamped by the database; 0 for
+    /// an entry not stored yet
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+const COLUMNS: &str = "id, task_id, task_title, started_at, ended_at, updated_at";
+
+/// Ids of entries imported from other time trackers start with this
+pub const IMPORTED_PREFIX: &str = "import:";
+
+pub fn insert(conn: &Connection, entry: &TimeEntry) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO time_entries (id, task_id, task_title, started_at, ended_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            entry.id,
+            entry.task_id,
+            entry.task_title,
+            entry.started_at,
+            entry.ended_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Add or update an entry from another device. The newer copy wins, and
+/// an entry deleted here since it last changed stays deleted.
+pub fn merge(conn: &Connection, entry: &TimeEntry) -> rusqlite::Result<()> {
+    if tombstones::deleted_at(conn, tombstones::TIME_ENTRY, &entry.id)?
+        .is_some_and(|deleted_at| deleted_at >= entry.updated_at)
+    {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO time_entries (id, task_id, task_title, started_at, ended_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(id) DO UPDATE SET
+            task_id = excluded.task_id,
+            task_title = excluded.task_title,
+            started_at = excluded.started_at,
+            ended_at = excluded.ended_at,
+            updated_at = excluded.updated_at
+         WHERE excluded.updated_at > time_entries.updated_at",
+        params![
+            entry.id,
+            entry.task_id,
+            entry.task_title,
+            entry.started_at,
+            entry.ended_at,
+            entry.updated_at
+        ],
+    )?;
+    tombstones::clear(conn, tombstones::TIME_ENTRY, &entry.id)
+}
+
+/// Remove every entry whose id starts with `prefix`
+pub fn delete_prefixed(conn: &Connection, prefix: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "DELETE FROM time_entries WHERE substr(id, 1, length(?1)) = ?1",
+        params![prefix],
+    )
+}
+
+/// The running entry, if any
+pub fn running(conn: &Connection) -> rusqlite::Result<Option<TimeEntry>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM time_entries WHERE ended_at IS NULL ORDER BY started_at DESC LIMIT 1",
+            COLUMNS
+        ),
+        [],
+        from_row,
+    )
+    .optional()
+}
+
+/// Move the start of an entry, keeping it otherwise as it is
+pub fn set_started_at(conn: &Connection, id: &str, started_at: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE time_entries SET started_at = ?2 WHERE id = ?1",
+        params![id, started_at],
+    )?;
+    Ok(())
+}
+
+pub fn finish(conn: &Connection, id: &str, ended_at: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE time_entries SET ended_at = ?2 WHERE id = ?1",
+        params![id, ended_at],
+    )?;
+    Ok(())
+}
+
+/// Entries overlapping the `[start, end)` range, running ones included
+pub fn list_between(conn: &Connection, start: i64, end: i64) -> rusqlite::Result<Vec<TimeEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM time_entries
+         WHERE started_at < ?2 AND (ended_at IS NULL OR ended_at > ?1)
+         ORDER BY started_at",
+        COLUMNS
+    ))?;
+    let entries = stmt
+        .query_map(params![start, end], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<TimeEntry> {
+    Ok(TimeEntry {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        task_title: row.get(2)?,
+        started_at: row.get(3)?,
+        ended_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
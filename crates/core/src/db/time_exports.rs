@@ -0,0 +1,217 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+
+use super::time_entries::TimeEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportStatus {
+    Exported,
+    /// A rule kept it local, or nothing says where it should go
+    Skipped,
+    /// Failed; tried again from `next_attempt_at`
+    Retrying,
+    /// Gave up after too many attempts
+    Failed,
+}
+
+/// What happened to one time entry with one export provider
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeExport {
+    pub entry_id: String,
+    pub provider: String,
+    pub status: ExportStatus,
+    /// Id of the entry or worklog created by the provider
+    pub remote_id: Option<String>,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    /// Unix milliseconds
+    pub next_attempt_at: i64,
+    /// Unix milliseconds
+    pub updated_at: i64,
+}
+
+const COLUMNS: &str =
+    "entry_id, provider, status, remote_id, attempts, last_error, next_attempt_at, updated_at";
+
+pub fn upsert(conn: &Connection, export: &TimeExport) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO time_exports ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(entry_id, provider) DO UPDATE SET
+                status = excluded.status,
+                remote_id = excluded.remote_id,
+                attempts = excluded.attempts,
+                last_error = excluded.last_error,
+                next_attempt_at = excluded.next_attempt_at,
+                updated_at = excluded.updated_at",
+            COLUMNS
+        ),
+        params![
+            export.entry_id,
+            export.provider,
+            export.status,
+            export.remote_id,
+            export.attempts,
+            export.last_error,
+            export.next_attempt_at,
+            export.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Finished entries started at or after `since` that haven't gone to
+/// `provider` yet and are due an attempt, oldest first, with the attempts
+/// made so far. Entries skipped before `rules_changed_at` are due again,
+/// since the rules that skipped them may now send them somewhere. Entries
+/// imported from other trackers never go back out, and demo entries (ids
+/// starting `demo-`) never go out at all.
+pub fn pending(
+    conn: &Connection,
+    provider: &str,
+    since: i64,
+    now: i64,
+    rules_changed_at: i64,
+) -> rusqlite::Result<Vec<(TimeEntry, i64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT e.id, e.task_id, e.task_title, e.started_at, e.ended_at, e.updated_at,
+                COALESCE(x.attempts, 0)
+         FROM time_entries e
+         LEFT JOIN time_exports x ON x.entry_id = e.id AND x.provider = ?1
+         WHERE e.ended_at IS NOT NULL AND e.started_at >= ?2 AND e.id NOT LIKE 'import:%'
+           AND e.id NOT LIKE 'demo-%'
+           AND (x.entry_id IS NULL
+                OR (x.status = 'retrying' AND x.next_attempt_at <= ?3)
+                OR (x.status = 'skipped' AND x.updated_at < ?4))
+         ORDER BY e.started_at",
+    )?;
+    let entries = stmt
+        .query_map(params![provider, since, now, rules_changed_at], |row| {
+            Ok((
+                TimeEntry {
+                    id: row.get(0)?,
+                    task_id: row.get(1)?,
+                    task_title: row.get(2)?,
+                    started_at: row.get(3)?,
+                    ended_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                },
+                row.get(6)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+/// Exports that were given up on, newest first
+pub fn list_failed(conn: &Connection, provider: &str) -> rusqlite::Result<Vec<TimeExport>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM time_exports WHERE provider = ?1 AND status = 'failed'
+         ORDER BY updated_at DESC",
+        COLUMNS
+    ))?;
+    let exports = stmt
+        .query_map(params![provider], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(exports)
+}
+
+/// Put failed exports back in line for another round of attempts
+pub fn retry_failed(conn: &Connection, provider: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE time_exports SET status = 'retrying', attempts = 0, next_attempt_at = 0
+         WHERE provider = ?1 AND status = 'failed'",
+        params![provider],
+    )
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<TimeExport> {
+    Ok(TimeExport {
+        entry_id: row.get(0)?,
+        provider: row.get(1)?,
+        status: row.get(2)?,
+        remote_id: row.get(3)?,
+        attempts: row.get(4)?,
+        last_error: row.get(5)?,
+        next_attempt_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+impl ToSql for ExportStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(match self {
+            ExportStatus::Exported => "exported",
+            ExportStatus::Skipped => "skipped",
+            ExportStatus::Retrying => "retrying",
+            ExportStatus::Failed => "failed",
+        }))
+    }
+}
+
+impl FromSql for ExportStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "exported" => Ok(ExportStatus::Exported),
+            "skipped" => Ok(ExportStatus::Skipped),
+            "retrying" => Ok(ExportStatus::Retrying),
+            "failed" => Ok(ExportStatus::Failed),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::time_entries;
+    use crate::testing;
+
+    fn skipped(entry_id: &str, updated_at: i64) -> TimeExport {
+        TimeExport {
+            entry_id: entry_id.to_string(),
+            provider: "toggl".to_string(),
+            status: ExportStatus::Skipped,
+            remote_id: None,
+            attempts: 0,
+            last_error: Some("Kept local by a mapping rule".to_string()),
+            next_attempt_at: 0,
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn skipped_entries_are_due_again_after_the_rules_change() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            for (started_at, id) in [(1_000, "kept"), (1_500, "new")] {
+                time_entries::insert(
+                    conn,
+                    &TimeEntry {
+                        id: id.to_string(),
+                        task_id: "task".to_string(),
+                        task_title: "Write".to_string(),
+                        started_at,
+                        ended_at: Some(2_000),
+                        updated_at: 2_000,
+                    },
+                )?;
+            }
+            upsert(conn, &skipped("kept", 5_000))
+        })
+        .unwrap();
+
+        let pending_ids = |rules_changed_at| {
+            db.with_conn(|conn| pending(conn, "toggl", 0, 10_000, rules_changed_at))
+                .unwrap()
+                .into_iter()
+                .map(|(entry, _)| entry.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pending_ids(4_000), vec!["new"]);
+        assert_eq!(pending_ids(6_000), vec!["kept", "new"]);
+    }
+}
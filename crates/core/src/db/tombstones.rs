@@ -0,0 +1,137 @@
+//! Rows deleted from tables that sync directly between devices, so a
+//! deletion reaches the other side instead of the row coming back. The
+//! tables' delete triggers record them.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// `kind` of a deleted time entry
+pub const TIME_ENTRY: &str = "time_entry";
+/// `kind` of a deleted time block
+pub const TIME_BLOCK: &str = "time_block";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tombstone {
+    pub kind: String,
+    pub id: String,
+    /// Unix milliseconds
+    pub deleted_at: i64,
+}
+
+/// When the row was deleted, if it was
+pub fn deleted_at(conn: &Connection, kind: &str, id: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        "SELECT deleted_at FROM tombstones WHERE kind = ?1 AND id = ?2",
+        params![kind, id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn all(conn: &Connection) -> rusqlite::Result<Vec<Tombstone>> {
+    let mut stmt =
+        conn.prepare("SELECT kind, id, deleted_at FROM tombstones ORDER BY deleted_at")?;
+    let tombstones = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(tombstones)
+}
+
+/// Apply a deletion from another device: the row goes unless it changed
+/// after it was deleted there. Returns whether it went.
+pub fn apply(conn: &Connection, tombstone: &Tombstone) -> rusqlite::Result<bool> {
+    let table = match tombstone.kind.as_str() {
+        TIME_ENTRY => "time_entries",
+        TIME_BLOCK => "time_blocks",
+        _ => return Ok(false),
+    };
+    let known = deleted_at(conn, &tombstone.kind, &tombstone.id)?;
+    let deleted = conn.execute(
+        &format!("DELETE FROM {} WHERE id = ?1 AND updated_at <= ?2", table),
+        params![tombstone.id, tombstone.deleted_at],
+    )? > 0;
+    // Keep the time it was deleted there rather than the trigger's now, so
+    // a later edit elsewhere still wins
+    conn.execute(
+        "INSERT INTO tombstones (kind, id, deleted_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(kind, id) DO UPDATE SET deleted_at = excluded.deleted_at",
+        params![
+            tombstone.kind,
+            tombstone.id,
+            known.map_or(tombstone.deleted_at, |known| known
+                .max(tombstone.deleted_at))
+        ],
+    )?;
+    Ok(deleted)
+}
+
+/// Forget a deletion, when a newer copy of the row comes back
+pub fn clear(conn: &Connection, kind: &str, id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM tombstones WHERE kind = ?1 AND id = ?2",
+        params![kind, id],
+    )?;
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Tombstone> {
+    Ok(Tombstone {
+        kind: row.get(0)?,
+        id: row.get(1)?,
+        deleted_at: row.get(2)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::time_entries::{self, TimeEntry};
+    use crate::testing;
+
+    fn entry(updated_at: i64) -> TimeEntry {
+        TimeEntry {
+            id: "e".to_string(),
+            task_id: "t".to_string(),
+            task_title: "Task".to_string(),
+            started_at: 1_000,
+            ended_at: Some(2_000),
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn deletions_and_edits_sync_by_time() {
+        let (_dir, db) = testing::database();
+        db.with_conn(|conn| {
+            time_entries::insert(conn, &entry(0))?;
+            conn.execute("DELETE FROM time_entries WHERE id = 'e'", [])?;
+            let deleted = deleted_at(conn, TIME_ENTRY, "e")?.expect("tombstone");
+
+            // A copy from before the deletion stays deleted
+            time_entries::merge(conn, &entry(deleted - 1))?;
+            assert!(time_entries::list_between(conn, 0, 10_000)?.is_empty());
+
+            // One edited after it comes back
+            time_entries::merge(conn, &entry(deleted + 1))?;
+            assert_eq!(time_entries::list_between(conn, 0, 10_000)?.len(), 1);
+            assert_eq!(deleted_at(conn, TIME_ENTRY, "e")?, None);
+
+            // And outlives an older deletion from elsewhere
+            let stale = Tombstone {
+                kind: TIME_ENTRY.to_string(),
+                id: "e".to_string(),
+                deleted_at: deleted,
+            };
+            assert!(!apply(conn, &stale)?);
+            let fresh = Tombstone {
+                deleted_at: deleted + 2,
+                ..stale
+            };
+            assert!(apply(conn, &fresh)?);
+            assert_eq!(deleted_at(conn, TIME_ENTRY, "e")?, Some(deleted + 2));
+            Ok(())
+        })
+        .unwrap();
+    }
+}
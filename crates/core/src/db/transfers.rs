@@ -0,0 +1,272 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransferStatus {
+    Queued,
+    Active,
+    Paused,
+    Failed,
+    Done,
+}
+
+/// An attachment upload or download, resumable from `transferred_bytes`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transfer {
+    pub id: String,
+    pub direction: TransferDirection,
+    /// Local attachment; set for downloads once they finish
+    pub attachment_id: Option<String>,
+    /// Server attachment; set for uploads once they finish
+    pub remote_id: Option<String>,
+    /// Server upload session, kept so an upload resumes where it stopped
+    #[serde(skip)]
+    pub upload_id: Option<String>,
+    pub task_id: Option<String>,
+    pub file_name: String,
+    pub mime_type: String,
+    /// SHA-256 of the contents
+    pub hash: String,
+    pub size_bytes: i64,
+    pub transferred_bytes: i64,
+    pub status: TransferStatus,
+    pub error: Option<String>,
+    /// Queued or resumed by the user rather than by sync, so it runs while
+    /// background transfers are held to save power
+    pub requested: bool,
+    /// Unix milliseconds
+    pub created_at: i64,
+    /// Unix milliseconds
+    pub updated_at: i64,
+}
+
+const COLUMNS: &str = "id, direction, attachment_id, remote_id, upload_id, task_id, file_name, mime_type, \
+                       hash, size_bytes, transferred_bytes, status, error, requested, created_at, updated_at";
+
+pub fn insert(conn: &Connection, transfer: &Transfer) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO transfers ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            COLUMNS
+        ),
+        params![
+            transfer.id,
+            transfer.direction,
+            transfer.attachment_id,
+            transfer.remote_id,
+            transfer.upload_id,
+            transfer.task_id,
+            transfer.file_name,
+            transfer.mime_type,
+            transfer.hash,
+            transfer.size_bytes,
+            transfer.transferred_bytes,
+            transfer.status,
+            transfer.error,
+            transfer.requested,
+            transfer.created_at,
+            transfer.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> rusqlite::Result<Option<Transfer>> {
+    conn.query_row(
+        &format!("SELECT {} FROM transfers WHERE id = ?1", COLUMNS),
+        params![id],
+        from_row,
+    )
+    .optional()
+}
+
+/// Every transfer that isn't finished, then finished ones, newest first
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<Transfer>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM transfers ORDER BY status = 'done', created_at DESC",
+        COLUMNS
+    ))?;
+    let transfers = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(transfers)
+}
+
+/// The oldest queued transfer, only among those the user asked for when
+/// `requested_only`
+pub fn next_queued(conn: &Connection, requested_only: bool) -> rusqlite::Result<Option<Transfer>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM transfers WHERE status = 'queued' AND (requested OR NOT ?1)
+             ORDER BY created_at LIMIT 1",
+            COLUMNS
+        ),
+        params![requested_only],
+        from_row,
+    )
+    .optional()
+}
+
+/// Put a transfer back in the queue at the user's request
+pub fn request(conn: &Connection, id: &str, now: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE transfers SET status = 'queued', error = NULL, requested = 1, updated_at = ?2
+         WHERE id = ?1",
+        params![id, now],
+    )?;
+    Ok(())
+}
+
+/// Whether an attachment already has an upload that isn't finished
+pub fn has_pending_upload(conn: &Connection, attachment_id: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM transfers
+         WHERE direction = 'upload' AND attachment_id = ?1 AND status != 'done')",
+        params![attachment_id],
+        |row| row.get(0),
+    )
+}
+
+/// Let an attachment's unfinished upload run while background transfers
+/// are held, now that the user asked for it
+pub fn request_upload(conn: &Connection, attachment_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE transfers SET requested = 1
+         WHERE direction = 'upload' AND attachment_id = ?1 AND status != 'done'",
+        params![attachment_id],
+    )?;
+    Ok(())
+}
+
+pub fn set_status(
+    conn: &Connection,
+    id: &str,
+    status: TransferStatus,
+    error: Option<&str>,
+    now: i64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE transfers SET status = ?2, error = ?3, updated_at = ?4 WHERE id = ?1",
+        params![id, status, error, now],
+    )?;
+    Ok(())
+}
+
+pub fn set_progress(conn: &Connection, id: &str, transferred_bytes: i64, now: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE transfers SET transferred_bytes = ?2, updated_at = ?3 WHERE id = ?1",
+        params![id, transferred_bytes, now],
+    )?;
+    Ok(())
+}
+
+pub fn set_upload_id(conn: &Connection, id: &str, upload_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE transfers SET upload_id = ?2 WHERE id = ?1",
+        params![id, upload_id],
+    )?;
+    Ok(())
+}
+
+/// Record a finished transfer and what it produced
+pub fn finish(
+    conn: &Connection,
+    id: &str,
+    attachment_id: &str,
+    remote_id: &str,
+    now: i64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE transfers SET status = 'done', error = NULL, attachment_id = ?2, remote_id = ?3,
+            transferred_bytes = size_bytes, updated_at = ?4
+         WHERE id = ?1",
+        params![id, attachment_id, remote_id, now],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM transfers WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Transfers cut off by a quit or crash go back in the queue
+pub fn requeue_active(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("UPDATE transfers SET status = 'queued' WHERE status = 'active'", [])?;
+    Ok(())
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<Transfer> {
+    Ok(Transfer {
+        id: row.get(0)?,
+        direction: row.get(1)?,
+        attachment_id: row.get(2)?,
+        remote_id: row.get(3)?,
+        upload_id: row.get(4)?,
+        task_id: row.get(5)?,
+        file_name: row.get(6)?,
+        mime_type: row.get(7)?,
+        hash: row.get(8)?,
+        size_bytes: row.get(9)?,
+        transferred_bytes: row.get(10)?,
+        status: row.get(11)?,
+        error: row.get(12)?,
+        requested: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+    })
+}
+
+impl ToSql for TransferDirection {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(match self {
+            TransferDirection::Upload => "upload",
+            TransferDirection::Download => "download",
+        }))
+    }
+}
+
+impl FromSql for TransferDirection {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "upload" => Ok(TransferDirection::Upload),
+            "download" => Ok(TransferDirection::Download),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl ToSql for TransferStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(match self {
+            TransferStatus::Queued => "queued",
+            TransferStatus::Active => "active",
+            TransferStatus::Paused => "paused",
+            TransferStatus::Failed => "failed",
+            TransferStatus::Done => "done",
+        }))
+    }
+}
+
+impl FromSql for TransferStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "queued" => Ok(TransferStatus::Queued),
+            "active" => Ok(TransferStatus::Active),
+            "paused" => Ok(TransferStatus::Paused),
+            "failed" => Ok(TransferStatus::Failed),
+            "done" => Ok(TransferStatus::Done),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
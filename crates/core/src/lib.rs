@@ -0,0 +1,14 @@
+//! Native code shared by the desktop and mobile apps, so features behave
+//! the same on both and work offline against the same local database: the
+//! schema and queries, reading server tasks, scheduling, and the sync
+//! queue. Nothing here depends on Tauri; each app opens the database where
+//! it keeps its data, supplies its settings and exposes its own commands.
+
+pub mod db;
+pub mod schedule;
+pub mod sync;
+pub mod task;
+pub mod timezone;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,166 @@
+//! Placing task blocks: the next free slot for a block, inside working
+//! hours and outside days off, and re-flowing the rest of a day after a
+//! meeting ran over. Free time comes from `free_busy`, which keeps buffers
+//! around meetings and gaps between blocks. A task's block never goes
+//! before the blocks of the tasks blocking it.
+
+use chrono::{Days, NaiveDate};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+
+use super::free_busy::{self, Constraints};
+use super::work_calendar::{WorkCalendar, WorkWeek};
+use crate::db::dependencies;
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::timezone::{self, local_date};
+
+/// Slots start on quarter hours
+pub const SLOT_MS: i64 = 15 * 60 * 1000;
+/// How far ahead to look for a free slot
+const MAX_DAYS: u64 = 14;
+/// Reflowed blocks start on five-minute marks
+const REFLOW_STEP_MS: i64 = 5 * 60 * 1000;
+
+/// Start and end (Unix ms) of the first gap of `duration_ms` at or after
+/// `after`, ignoring the block `moving` itself
+pub fn next_free_slot(
+    conn: &Connection,
+    moving: Option<&str>,
+    after: i64,
+    duration_ms: i64,
+    planning: &WorkWeek,
+    tz: Tz,
+) -> rusqlite::Result<Option<(i64, i64)>> {
+    let Some(first_day) = local_date(after, tz) else {
+        return Ok(None);
+    };
+    let constraints = Constraints {
+        exclude_blocks: moving.map(str::to_string).into_iter().collect(),
+        ..Default::default()
+    };
+    let calendar = WorkCalendar::read(conn, planning)?;
+
+    for offset in 0..MAX_DAYS {
+        let Some(date) = first_day.checked_add_days(Days::new(offset)) else {
+            break;
+        };
+        for slot in free_busy::for_day(conn, date, tz, planning, &calendar, &constraints)?.free {
+            let start = round_up(slot.start.max(after), SLOT_MS);
+            if start + duration_ms <= slot.end {
+                return Ok(Some((start, start + duration_ms)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Re-place the remaining task blocks of `date` one by one, each kept out
+/// of the free/busy picture until its turn. Returns the moved blocks with
+/// their previous start.
+pub fn reflow(
+    conn: &Connection,
+    date: NaiveDate,
+    from: i64,
+    planning: &WorkWeek,
+    tz: Tz,
+) -> rusqlite::Result<Vec<(TimeBlock, i64)>> {
+    let Some((_, day_end)) = timezone::day_bounds(date, tz) else {
+        return Ok(Vec::new());
+    };
+    let calendar = WorkCalendar::read(conn, planning)?;
+    let remaining = blockers_first(
+        conn,
+        time_blocks::list_between(conn, from, day_end)?
+            .into_iter()
+            .filter(|block| !block.is_event)
+            .collect(),
+    )?;
+    let mut constraints = Constraints {
+        exclude_blocks: remaining.iter().map(|block| block.id.clone()).collect(),
+        ..Default::default()
+    };
+
+    let mut cursor = from;
+    let mut moved = Vec::new();
+    for mut block in remaining {
+        let duration = block.end_at - block.start_at;
+        // Blockers were placed first, so their blocks are where they'll stay
+        let earliest = cursor.max(blockers_end(conn, &block)?);
+        let today = free_busy::for_day(conn, date, tz, planning, &calendar, &constraints)?
+            .free
+            .into_iter()
+            .find_map(|slot| {
+                let start = round_up(slot.start.max(earliest), REFLOW_STEP_MS);
+                (start + duration <= slot.end).then_some((start, start + duration))
+            });
+        let slot = match today {
+            Some(slot) => Some(slot),
+            None => {
+                let after = day_end.max(earliest);
+                next_free_slot(conn, Some(&block.id), after, duration, planning, tz)?
+            }
+        };
+        constraints.exclude_blocks.retain(|id| *id != block.id);
+
+        // Nothing free in the next two weeks: it stays where it was
+        let Some((start_at, end_at)) = slot else {
+            continue;
+        };
+        if end_at <= day_end {
+            cursor = end_at;
+        }
+        if start_at == block.start_at {
+            continue;
+        }
+        time_blocks::reschedule(conn, &block.id, start_at, end_at, tz.name())?;
+        let previous_start = block.start_at;
+        block.start_at = start_at;
+        block.end_at = end_at;
+        block.timezone = tz.name().to_string();
+        moved.push((block, previous_start));
+    }
+
+    Ok(moved)
+}
+
+/// Where the blocks of the tasks blocking `block`'s task end, or 0
+pub fn blockers_end(conn: &Connection, block: &TimeBlock) -> rusqlite::Result<i64> {
+    match &block.task_id {
+        Some(task_id) => Ok(dependencies::blockers_end(conn, task_id)?.unwrap_or(0)),
+        None => Ok(0),
+    }
+}
+
+/// `blocks` in their order, except that a block whose task waits on another
+/// block's task comes after it
+fn blockers_first(conn: &Connection, blocks: Vec<TimeBlock>) -> rusqlite::Result<Vec<TimeBlock>> {
+    let mut waiting: Vec<(TimeBlock, Vec<String>)> = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let blockers = match &block.task_id {
+            Some(task_id) => dependencies::blockers(conn, task_id)?,
+            None => Vec::new(),
+        };
+        waiting.push((block, blockers));
+    }
+
+    let mut ordered: Vec<TimeBlock> = Vec::with_capacity(waiting.len());
+    while !waiting.is_empty() {
+        let pending = |task_id: &String| {
+            waiting
+                .iter()
+                .any(|(block, _)| block.task_id.as_ref() == Some(task_id))
+        };
+        // Dependencies can't form a cycle, but fall back to the first block
+        let next = waiting
+            .iter()
+            .position(|(_, blockers)| !blockers.iter().any(pending))
+            .unwrap_or(0);
+        ordered.push(waiting.remove(next).0);
+    }
+    Ok(ordered)
+}
+
+fn round_up(millis: i64, step: i64) -> i64 {
+    (millis + step - 1) / step * step
+}
@@ -0,0 +1,252 @@
+//! Free/busy for one day: calendar events (imported or from subscribed
+//! feeds) and planned task blocks from the local schedule, merged into busy
+//! intervals, and the free slots left in working hours. Meetings can be
+//! padded with a buffer on both sides, and task blocks with a gap so new
+//! ones don't butt up against them. The auto-scheduler and "find a slot"
+//! both work from this.
+
+use chrono::{NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::work_calendar::{WorkCalendar, WorkWeek};
+use crate::db::time_blocks::{self, TimeBlock};
+use crate::timezone;
+
+/// Overrides for one query; unset fields come from the planning settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Constraints {
+    /// `HH:MM`
+    pub work_start: Option<String>,
+    /// `HH:MM`
+    pub work_end: Option<String>,
+    /// Kept free before and after each calendar event
+    pub buffer_minutes: Option<u32>,
+    /// Kept free before and after each task block
+    pub gap_minutes: Option<u32>,
+    /// Shorter gaps aren't reported as free
+    pub min_slot_minutes: Option<u32>,
+    /// Treat planned task blocks as free time
+    pub ignore_tasks: bool,
+    /// Report free time on days off too
+    pub include_days_off: bool,
+    /// Leave these blocks out, e.g. the ones being moved
+    pub exclude_blocks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BusyKind {
+    Event,
+    Task,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Busy {
+    pub block_id: String,
+    pub kind: BusyKind,
+    pub title: String,
+    /// Unix milliseconds, buffer or gap included
+    pub start: i64,
+    /// Unix milliseconds, buffer or gap included
+    pub end: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Slot {
+    /// Unix milliseconds
+    pub start: i64,
+    /// Unix milliseconds
+    pub end: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreeBusy {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    /// A holiday or a weekday without working hours; no free time unless
+    /// asked for
+    pub day_off: bool,
+    /// The holiday's name, if it's one
+    pub holiday: Option<String>,
+    /// Working hours that day, if they could be placed on the clock
+    pub working_hours: Option<Slot>,
+    /// Everything on the calendar that day, soonest first
+    pub busy: Vec<Busy>,
+    /// Gaps in working hours, soonest first
+    pub free: Vec<Slot>,
+}
+
+/// Free/busy for `date` in `tz`
+pub fn for_day(
+    conn: &Connection,
+    date: NaiveDate,
+    tz: Tz,
+    planning: &WorkWeek,
+    calendar: &WorkCalendar,
+    constraints: &Constraints,
+) -> rusqlite::Result<FreeBusy> {
+    let day_off = calendar.is_day_off(date);
+    let buffer = i64::from(
+        constraints
+            .buffer_minutes
+            .unwrap_or(planning.buffer_minutes),
+    ) * 60_000;
+    let gap = i64::from(
+        constraints
+            .gap_minutes
+            .unwrap_or(planning.block_gap_minutes),
+    ) * 60_000;
+    let min_slot = i64::from(constraints.min_slot_minutes.unwrap_or(0)) * 60_000;
+    let working_hours = working_hours(date, tz, calendar, constraints);
+
+    let mut busy: Vec<Busy> = match timezone::day_bounds(date, tz) {
+        Some((day_start, day_end)) => time_blocks::list_between(conn, day_start, day_end)?,
+        None => Vec::new(),
+    }
+    .into_iter()
+    .filter(|block| !constraints.exclude_blocks.contains(&block.id))
+    .filter(|block| block.is_event || !constraints.ignore_tasks)
+    .map(|block| busy_interval(block, buffer, gap))
+    .collect();
+    busy.sort_by_key(|interval| (interval.start, interval.end));
+
+    let free = match working_hours {
+        Some(hours) if !day_off || constraints.include_days_off => {
+            free_slots(hours, &busy, min_slot)
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(FreeBusy {
+        date: date.to_string(),
+        day_off,
+        holiday: calendar.holiday(date).map(str::to_string),
+        working_hours,
+        busy,
+        free,
+    })
+}
+
+fn working_hours(
+    date: NaiveDate,
+    tz: Tz,
+    calendar: &WorkCalendar,
+    constraints: &Constraints,
+) -> Option<Slot> {
+    let time = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|value| NaiveTime::parse_from_str(value, "%H:%M").ok())
+    };
+    let usual = calendar.hours(date);
+    let start = time(&constraints.work_start).or(usual.map(|(start, _)| start))?;
+    let end = time(&constraints.work_end).or(usual.map(|(_, end)| end))?;
+    let local = |time: NaiveTime| {
+        tz.from_local_datetime(&date.and_time(time))
+            .earliest()
+            .map(|at| at.timestamp_millis())
+    };
+
+    let slot = Slot {
+        start: local(start)?,
+        end: local(end)?,
+    };
+    (slot.start < slot.end).then_some(slot)
+}
+
+fn busy_interval(block: TimeBlock, buffer: i64, gap: i64) -> Busy {
+    let (kind, pad) = if block.is_event {
+        (BusyKind::Event, buffer)
+    } else {
+        (BusyKind::Task, gap)
+    };
+    Busy {
+        kind,
+        start: block.start_at - pad,
+        end: block.end_at + pad,
+        block_id: block.id,
+        title: block.title,
+    }
+}
+
+/// `hours` minus the busy intervals, dropping gaps shorter than `min_slot`
+fn free_slots(hours: Slot, busy: &[Busy], min_slot: i64) -> Vec<Slot> {
+    let mut free = Vec::new();
+    let mut cursor = hours.start;
+    for interval in busy {
+        if interval.start >= hours.end {
+            break;
+        }
+        if interval.start > cursor {
+            free.push(Slot {
+                start: cursor,
+                end: interval.start,
+            });
+        }
+        cursor = cursor.max(interval.end);
+    }
+    if cursor < hours.end {
+        free.push(Slot {
+            start: cursor,
+            end: hours.end,
+        });
+    }
+
+    free.retain(|slot| slot.end - slot.start >= min_slot.max(1));
+    free
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn busy(start: i64, end: i64) -> Busy {
+        Busy {
+            block_id: format!("{}-{}", start, end),
+            kind: BusyKind::Event,
+            title: String::new(),
+            start,
+            end,
+        }
+    }
+
+    fn slots(free: &[Slot]) -> Vec<(i64, i64)> {
+        free.iter().map(|slot| (slot.start, slot.end)).collect()
+    }
+
+    #[test]
+    fn free_slots_skip_overlapping_and_outlying_intervals() {
+        let hours = Slot {
+            start: 100,
+            end: 1_000,
+        };
+        let busy = [
+            busy(50, 150),
+            busy(300, 500),
+            busy(400, 450),
+            busy(600, 700),
+            busy(1_200, 1_300),
+        ];
+        assert_eq!(
+            slots(&free_slots(hours, &busy, 0)),
+            vec![(150, 300), (500, 600), (700, 1_000)]
+        );
+    }
+
+    #[test]
+    fn free_slots_drop_gaps_below_the_minimum() {
+        let hours = Slot {
+            start: 0,
+            end: 1_000,
+        };
+        let blocks = [busy(100, 950)];
+        assert_eq!(slots(&free_slots(hours, &blocks, 60)), vec![(0, 100)]);
+        assert_eq!(slots(&free_slots(hours, &[busy(0, 1_000)], 0)), vec![]);
+    }
+}
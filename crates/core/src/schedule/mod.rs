@@ -0,0 +1,7 @@
+//! Planning time: working days and hours, free/busy, and where the
+//! auto-scheduler puts task blocks. Everything works on a connection and
+//! the planning settings it's given, so both apps schedule the same way.
+
+pub mod auto_schedule;
+pub mod free_busy;
+pub mod work_calendar;
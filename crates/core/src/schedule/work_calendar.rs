@@ -0,0 +1,133 @@
+//! Working days and hours: the weekly hours, weekends and holidays from
+//! the planning settings, plus holidays from subscribed holiday calendars.
+//! Free/busy, the auto-scheduler, ritual reminders and the daily carry-over
+//! all ask this, so nothing lands on a day off or outside working hours.
+
+use chrono::{Datelike, Days, NaiveDate, NaiveTime};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::db::holidays;
+
+/// How far ahead to look for the next working day
+const MAX_LOOKAHEAD_DAYS: u64 = 366;
+const MANUAL_HOLIDAY: &str = "Holiday";
+
+/// Working hours on one day of the week
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayHours {
+    /// 1 (Monday) to 7 (Sunday)
+    pub weekday: u8,
+    /// Local time, `HH:MM`
+    pub start: String,
+    /// Local time, `HH:MM`
+    pub end: String,
+}
+
+/// The parts of each app's planning settings that scheduling works from
+#[derive(Debug, Clone, Default)]
+pub struct WorkWeek {
+    /// Local time the working day starts, `HH:MM`
+    pub work_start: String,
+    /// Local time the working day ends, `HH:MM`
+    pub work_end: String,
+    /// Working hours per weekday. When set, weekdays not listed are days
+    /// off and `skip_weekends` is ignored.
+    pub weekly_hours: Vec<DayHours>,
+    pub skip_weekends: bool,
+    /// Days off (`YYYY-MM-DD`) besides those from holiday calendars
+    pub holidays: BTreeSet<String>,
+    /// Minutes kept free before and after calendar events
+    pub buffer_minutes: u32,
+    /// Minutes kept free between task blocks
+    pub block_gap_minutes: u32,
+}
+
+pub struct WorkCalendar {
+    /// Working hours by weekday, Monday first; `None` on days off
+    weekly: [Option<(NaiveTime, NaiveTime)>; 7],
+    /// `work_start`-`work_end`, for days off that are worked anyway
+    default_hours: Option<(NaiveTime, NaiveTime)>,
+    holidays: HashMap<NaiveDate, String>,
+}
+
+impl WorkCalendar {
+    /// The calendar from `planning` plus the given holiday calendar dates
+    /// (`YYYY-MM-DD`) and names
+    pub fn new(planning: &WorkWeek, feed_holidays: HashMap<String, String>) -> Self {
+        let default_hours = hours(&planning.work_start, &planning.work_end);
+        let weekly = std::array::from_fn(|index| {
+            let weekday = index as u8 + 1;
+            if planning.weekly_hours.is_empty() {
+                let weekend = weekday >= 6;
+                return if planning.skip_weekends && weekend {
+                    None
+                } else {
+                    default_hours
+                };
+            }
+            planning
+                .weekly_hours
+                .iter()
+                .find(|day| day.weekday == weekday)
+                .and_then(|day| hours(&day.start, &day.end))
+        });
+
+        let mut holidays: HashMap<NaiveDate, String> = feed_holidays
+            .into_iter()
+            .filter_map(|(date, name)| Some((date.parse().ok()?, name)))
+            .collect();
+        for date in planning
+            .holidays
+            .iter()
+            .filter_map(|date| date.parse().ok())
+        {
+            holidays.insert(date, MANUAL_HOLIDAY.to_string());
+        }
+
+        Self {
+            weekly,
+            default_hours,
+            holidays,
+        }
+    }
+
+    /// The calendar with holidays from the local database
+    pub fn read(conn: &Connection, planning: &WorkWeek) -> rusqlite::Result<Self> {
+        Ok(Self::new(planning, holidays::by_date(conn)?))
+    }
+
+    /// A holiday or a weekday without working hours
+    pub fn is_day_off(&self, date: NaiveDate) -> bool {
+        self.holidays.contains_key(&date) || self.weekly_hours(date).is_none()
+    }
+
+    /// The holiday's name, if `date` is one
+    pub fn holiday(&self, date: NaiveDate) -> Option<&str> {
+        self.holidays.get(&date).map(String::as_str)
+    }
+
+    /// Start and end of the working day on `date`. Days off get the
+    /// default hours, for when they're worked anyway.
+    pub fn hours(&self, date: NaiveDate) -> Option<(NaiveTime, NaiveTime)> {
+        self.weekly_hours(date).or(self.default_hours)
+    }
+
+    /// `from` if it's a working day, otherwise the first one after it
+    pub fn next_work_day(&self, from: NaiveDate) -> Option<NaiveDate> {
+        (0..MAX_LOOKAHEAD_DAYS)
+            .filter_map(|offset| from.checked_add_days(Days::new(offset)))
+            .find(|date| !self.is_day_off(*date))
+    }
+
+    fn weekly_hours(&self, date: NaiveDate) -> Option<(NaiveTime, NaiveTime)> {
+        self.weekly[date.weekday().num_days_from_monday() as usize]
+    }
+}
+
+fn hours(start: &str, end: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let start = NaiveTime::parse_from_str(start, "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(end, "%H:%M").ok()?;
+    (start < end).then_some((start, end))
+}
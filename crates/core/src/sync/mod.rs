@@ -0,0 +1,3 @@
+//! Sync rules both apps follow, independent of how they reach the server
+
+pub mod queue;
@@ -0,0 +1,73 @@
+//! The outbox of changes waiting to reach the backend. Entries go out in
+//! order once their `send_after` time passes, so an action can be undone by
+//! cancelling its entry before then. A sender stops at the first failure,
+//! so later changes never overtake earlier ones.
+
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::db::outbox::OutboxEntry;
+
+/// Entries that keep failing are dropped after this many attempts
+pub const MAX_ATTEMPTS: i64 = 10;
+
+/// What becomes of an entry the server didn't accept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Its target was deleted elsewhere; there's nothing left to change
+    Gone,
+    /// It failed too often and is dropped
+    GiveUp,
+    /// It stays queued, and sending stops until the next pass
+    Retry,
+}
+
+/// A request ready to be inserted, to be sent no sooner than `delay` after
+/// `now` (Unix ms)
+pub fn entry(
+    method: &str,
+    path: &str,
+    body: Option<&Value>,
+    delay: Duration,
+    now: i64,
+) -> OutboxEntry {
+    OutboxEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        method: method.to_string(),
+        path: path.to_string(),
+        body: body.map(Value::to_string),
+        created_at: now,
+        send_after: now + delay.as_millis() as i64,
+        attempts: 0,
+        last_error: None,
+    }
+}
+
+/// How to handle `entry` failing to send; `not_found` when the server
+/// answered 404
+pub fn on_failure(entry: &OutboxEntry, not_found: bool) -> Failure {
+    if not_found {
+        Failure::Gone
+    } else if entry.attempts + 1 >= MAX_ATTEMPTS {
+        Failure::GiveUp
+    } else {
+        Failure::Retry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_are_retried_until_the_last_attempt() {
+        let mut queued = entry("DELETE", "/tasks/a", None, Duration::ZERO, 0);
+        assert_eq!(on_failure(&queued, false), Failure::Retry);
+        assert_eq!(on_failure(&queued, true), Failure::Gone);
+
+        queued.attempts = MAX_ATTEMPTS - 2;
+        assert_eq!(on_failure(&queued, false), Failure::Retry);
+        queued.attempts = MAX_ATTEMPTS - 1;
+        assert_eq!(on_failure(&queued, false), Failure::GiveUp);
+    }
+}
@@ -0,0 +1,19 @@
+//! What the native side reads out of server tasks
+
+use serde_json::Value;
+
+/// The channel named by a leading `#channel` line in a task's notes
+pub fn channel(notes: Option<&str>) -> Option<String> {
+    let first = notes?.lines().next()?.trim();
+    let name = first.strip_prefix('#')?;
+    // A Markdown heading, not a channel
+    if name.is_empty() || name.starts_with(['#', ' ']) {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// Whether the task has been marked done
+pub fn is_completed(task: &Value) -> bool {
+    task.get("completedAt").is_some_and(|at| !at.is_null())
+}
@@ -0,0 +1,13 @@
+//! Helpers for tests against a real database
+
+use tempfile::TempDir;
+
+use crate::db::{Database, DATABASE_FILE};
+
+/// A fresh, migrated database in a temporary directory. Keep the directory
+/// alive for as long as the database is used.
+pub fn database() -> (TempDir, Database) {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let db = Database::open(&dir.path().join(DATABASE_FILE), None).expect("database");
+    (dir, db)
+}
@@ -0,0 +1,21 @@
+//! Placing calendar days and local times on the clock
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Unix ms bounds `[start, end)` of a calendar day in `tz`
+pub fn day_bounds(date: NaiveDate, tz: Tz) -> Option<(i64, i64)> {
+    let midnight = |date: NaiveDate| {
+        tz.from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest()
+            .map(|dt| dt.timestamp_millis())
+    };
+    Some((midnight(date)?, midnight(date.succ_opt()?)?))
+}
+
+/// The calendar day in `tz` at `millis`
+pub fn local_date(millis: i64, tz: Tz) -> Option<NaiveDate> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(|utc| utc.with_timezone(&tz).date_naive())
+}